@@ -1,24 +1,41 @@
 use alloc::collections::BTreeMap;
-use std::fs;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::mpsc::{channel, Sender};
 use std::time::Instant;
+use std::{fs, panic, thread};
 
-use log::{debug, trace};
+use anyhow::{bail, Result};
+use log::{debug, trace, Level};
 use plonky2::field::types::PrimeField64;
-use plonky2::iop::witness::PartialWitness;
+use plonky2::iop::witness::{PartialWitness, Witness};
 use plonky2::plonk::circuit_data::CircuitData;
-use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, GenericHashOut};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, GenericHashOut, Hasher};
 use plonky2::plonk::proof::ProofWithPublicInputs;
 use plonky2::plonk::prover::prove_with_partition_witness;
+use plonky2::recursion::dummy_circuit::cyclic_base_proof;
 use plonky2::util::serialization::{Buffer, GateSerializer, IoResult, Read, Write};
 use plonky2::util::timing::TimingTree;
+use tokio::runtime::Handle;
 
+use super::cache::ProofCache;
 use super::config::PlonkParameters;
+use super::constraints::check_gate_constraints;
+use super::dump::dump_on_failure;
 use super::input::PublicInput;
+use super::mock::MockProveResult;
 use super::output::PublicOutput;
+use super::progress::{
+    report_progress, witness_generation_weight, ProveOptions, ProvePhase, ProveProgress,
+};
 use super::serialization::hints::HintSerializer;
 use super::serialization::{GateRegistry, HintRegistry};
-use super::witness::{generate_witness, generate_witness_async};
+use super::witness::{
+    explain_conflict, generate_witness, generate_witness_async, generate_witness_with_runtime,
+    unset_targets,
+};
+use crate::frontend::builder::debug::DebugAssertion;
+use crate::frontend::builder::profiling::ProfilingReport;
 use crate::frontend::builder::CircuitIO;
 use crate::frontend::hint::asynchronous::generator::AsyncHintDataRef;
 use crate::utils::hex;
@@ -32,6 +49,12 @@ pub struct CircuitBuild<L: PlonkParameters<D>, const D: usize> {
     pub data: CircuitData<L::Field, L::Config, D>,
     pub io: CircuitIO<D>,
     pub async_hints: BTreeMap<usize, AsyncHintDataRef<L, D>>,
+    /// The equalities asserted in debug mode, used to explain the failures of `mock_prove`. They
+    /// are not serialized.
+    pub debug_assertions: Vec<DebugAssertion>,
+    /// The profiling report of the circuit, if it was built with profiling enabled. It is not
+    /// serialized.
+    pub profiling_report: Option<ProfilingReport>,
 }
 
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
@@ -40,6 +63,16 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
         PublicInput::new(&self.io)
     }
 
+    /// Returns the dummy proof that a circuit with cyclic IO verifies in its base case, when it has
+    /// no previous proof of itself. Its public inputs are zero, except for the verifier data.
+    pub fn cyclic_base_proof(&self) -> ProofWithPublicInputs<L::Field, L::Config, D>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        cyclic_base_proof(&self.data.common, &self.data.verifier_only, HashMap::new())
+    }
+
     /// Generates a proof for the circuit using a plonky2 partial witness. The proof can be verified
     /// using `verify`.
     pub fn prove_with_partial_witness(
@@ -53,27 +86,71 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
         <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
             AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
     {
+        self.prove_with_progress(pw, None, None)
+    }
+
+    fn prove_with_progress(
+        &self,
+        pw: PartialWitness<L::Field>,
+        progress: Option<Sender<ProveProgress>>,
+        runtime: Option<&Handle>,
+    ) -> (
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        // The receiving end may have stopped listening, in which case progress is dropped.
+        let report = |phase, fraction| {
+            if let Some(tx) = &progress {
+                let _ = tx.send(ProveProgress { phase, fraction });
+            }
+        };
+        let nb_generators = self.data.prover_only.generators.len();
+        let witness_weight =
+            witness_generation_weight::<L, D>(&self.data.prover_only, &self.data.common);
+
         let start_time = Instant::now();
         trace!("generating witness...");
-        let partition_witness = generate_witness(
-            pw,
-            &self.data.prover_only,
-            &self.data.common,
-            &self.async_hints,
-        )
+        report(ProvePhase::WitnessGenerationStarted { nb_generators }, 0.0);
+        let partition_witness = match runtime {
+            Some(handle) => generate_witness_with_runtime(
+                pw,
+                &self.data.prover_only,
+                &self.data.common,
+                &self.async_hints,
+                handle,
+            ),
+            None => generate_witness(
+                pw,
+                &self.data.prover_only,
+                &self.data.common,
+                &self.async_hints,
+            ),
+        }
         .unwrap();
         let elapsed_time = start_time.elapsed();
         debug!("Witness generation took {:?}", elapsed_time);
         trace!("finished generating witness");
+        report(
+            ProvePhase::WitnessGenerationFinished { nb_generators },
+            witness_weight,
+        );
         trace!("generating proof...");
+        report(ProvePhase::ProofGenerationStarted, witness_weight);
+        let mut timing = TimingTree::new("generate proof", Level::Debug);
         let proof_with_pis = prove_with_partition_witness::<L::Field, L::Config, D>(
             &self.data.prover_only,
             &self.data.common,
             partition_witness,
-            &mut TimingTree::default(),
+            &mut timing,
         )
         .unwrap();
+        timing.print();
         trace!("finished generating proof");
+        report(ProvePhase::ProofGenerationFinished, 1.0);
         let output = PublicOutput::from_proof_with_pis(&self.io, &proof_with_pis);
         let elapsed_time = start_time.elapsed();
         debug!("proving took: {:?}", elapsed_time);
@@ -97,6 +174,245 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
         self.prove_with_partial_witness(pw)
     }
 
+    /// Generates a proof for the circuit, running its async hints on the runtime of `handle`
+    /// instead of the runtime managed by the crate. The proof can be verified using `verify`.
+    ///
+    /// This blocks the calling thread, so if it is called from a worker of the runtime, the runtime
+    /// must be multi-threaded and the call wrapped in `tokio::task::block_in_place`.
+    pub fn prove_with_executor(
+        &self,
+        input: &PublicInput<L, D>,
+        handle: Handle,
+    ) -> (
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let mut pw = PartialWitness::new();
+        self.io.set_witness(&mut pw, input);
+        self.prove_with_progress(pw, None, Some(&handle))
+    }
+
+    /// Generates a proof for the circuit, or returns the error of witness generation after writing a
+    /// `WitnessDump` of it to `dump_path`, i.e. the input, the hint streams and the watched values.
+    /// Successful proofs write nothing.
+    ///
+    /// Panics of the hints are caught and returned as errors. Async hints are not recorded.
+    pub fn prove_with_debug_dump(
+        &self,
+        input: &PublicInput<L, D>,
+        dump_path: impl AsRef<Path>,
+    ) -> Result<(
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let mut pw = PartialWitness::new();
+        self.io.set_witness(&mut pw, input);
+        let partition_witness = dump_on_failure(self.id(), input, dump_path.as_ref(), || {
+            generate_witness(
+                pw,
+                &self.data.prover_only,
+                &self.data.common,
+                &self.async_hints,
+            )
+        })?;
+        let proof_with_pis = prove_with_partition_witness::<L::Field, L::Config, D>(
+            &self.data.prover_only,
+            &self.data.common,
+            partition_witness,
+            &mut TimingTree::default(),
+        )?;
+        let output = PublicOutput::from_proof_with_pis(&self.io, &proof_with_pis);
+        Ok((proof_with_pis, output))
+    }
+
+    /// Generates the witness of a proof and checks it against the circuit without proving, and
+    /// returns it with the output and the virtual targets that it leaves unset.
+    ///
+    /// The witness is generated exactly as by `prove`, including the async hints. Copy constraints
+    /// are checked while it is generated, since setting two copied targets to different values is
+    /// an error, and the constraints of every gate are then evaluated on its rows, which skips the
+    /// FFTs and FRI of proving. If the circuit was built in debug mode, the failure of an equality
+    /// assertion is described with its label, its location and the two values.
+    pub fn try_mock_prove(&self, input: &PublicInput<L, D>) -> Result<MockProveResult<L, D>>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let prover_data = &self.data.prover_only;
+        let common_data = &self.data.common;
+        let num_wires = common_data.config.num_wires;
+        let degree = common_data.degree();
+
+        let mut pw = PartialWitness::new();
+        self.io.set_witness(&mut pw, input);
+        let witness =
+            generate_witness(pw, prover_data, common_data, &self.async_hints).map_err(|e| {
+                explain_conflict::<L::Field>(
+                    e,
+                    &self.debug_assertions,
+                    &prover_data.representative_map,
+                    num_wires,
+                    degree,
+                )
+            })?;
+
+        let unset_targets =
+            unset_targets(&witness, &prover_data.representative_map, num_wires, degree);
+
+        let output = PublicOutput::from_witness(&self.io, &witness);
+
+        let public_inputs = witness.get_targets(&prover_data.public_inputs);
+        let public_inputs_hash = <<L::Config as GenericConfig<D>>::InnerHasher as Hasher<
+            L::Field,
+        >>::hash_no_pad(&public_inputs);
+        let violations = check_gate_constraints::<L, D>(
+            prover_data,
+            common_data,
+            &witness.full_witness(),
+            &public_inputs_hash,
+        );
+        if let Some(violation) = violations.first() {
+            bail!(
+                "{} gate constraints are not satisfied, the first is {}",
+                violations.len(),
+                violation
+            );
+        }
+        Ok(MockProveResult {
+            witness,
+            output,
+            unset_targets,
+        })
+    }
+
+    /// Same as `try_mock_prove`, but panics if the witness does not satisfy the circuit.
+    pub fn mock_prove(&self, input: &PublicInput<L, D>) -> MockProveResult<L, D>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        self.try_mock_prove(input).unwrap()
+    }
+
+    /// Generates a proof for the circuit with the given options. The proof can be verified using
+    /// `verify`.
+    ///
+    /// If a progress callback is set, the proof is generated on a separate thread which reports
+    /// its progress over a channel, and the callback is invoked on the calling thread.
+    pub fn prove_with_options(
+        &self,
+        input: &PublicInput<L, D>,
+        mut options: ProveOptions,
+    ) -> (
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let mut pw = PartialWitness::new();
+        self.io.set_witness(&mut pw, input);
+
+        let Some(mut callback) = options.take_progress() else {
+            return self.prove_with_partial_witness(pw);
+        };
+
+        let (tx, rx) = channel();
+        thread::scope(|s| {
+            let handle = s.spawn(move || self.prove_with_progress(pw, Some(tx), None));
+            for progress in rx {
+                report_progress(&mut callback, &progress);
+            }
+            match handle.join() {
+                Ok(result) => result,
+                Err(e) => panic::resume_unwind(e),
+            }
+        })
+    }
+
+    /// Generates a proof for the circuit, or returns the proof stored in the cache for the same
+    /// circuit and input. Freshly generated proofs are stored in the cache.
+    pub fn prove_cached(
+        &self,
+        input: &PublicInput<L, D>,
+        cache: &ProofCache,
+    ) -> (
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        if let Some(proof) = cache.get(self, input) {
+            let output = PublicOutput::from_proof_with_pis(&self.io, &proof);
+            return (proof, output);
+        }
+        let (proof, output) = self.prove(input);
+        cache.put(self, input, &proof);
+        (proof, output)
+    }
+
+    /// Returns an empty witness template for the circuit.
+    ///
+    /// Values that are identical across proofs (e.g. large constant tables that are not part of
+    /// the circuit io) can be written into the template once and then reused for every proof with
+    /// `prove_with_template`.
+    pub fn witness_template(&self) -> PartialWitness<L::Field> {
+        PartialWitness::new()
+    }
+
+    /// Generates a proof for the circuit starting from a pre-filled witness template. Only the
+    /// input targets are written on top of a clone of the template.
+    ///
+    /// Panics if the template contains any of the circuit's input targets.
+    pub fn prove_with_template(
+        &self,
+        template: &PartialWitness<L::Field>,
+        input: &PublicInput<L, D>,
+    ) -> (
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        self.assert_template_is_disjoint_from_io(template);
+        let mut pw = template.clone();
+        self.io.set_witness(&mut pw, input);
+        self.prove_with_partial_witness(pw)
+    }
+
+    /// Checks that a witness template does not set any of the targets written by the circuit io.
+    fn assert_template_is_disjoint_from_io(&self, template: &PartialWitness<L::Field>) {
+        let input_targets = self
+            .io
+            .input_variables()
+            .into_iter()
+            .map(|v| v.0)
+            .collect::<HashSet<_>>();
+        let overlap = template
+            .target_values
+            .keys()
+            .filter(|t| input_targets.contains(t))
+            .count();
+        assert_eq!(
+            overlap, 0,
+            "witness template sets {} targets that overlap with the circuit io",
+            overlap
+        );
+    }
+
     /// Generates a proof for the circuit using a plonky2 partial witness. The proof can be verified
     /// using `verify`.
     pub async fn prove_with_partial_witness_async(
@@ -172,15 +488,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
 
     /// A unique identifier for the circuit.
     pub fn id(&self) -> String {
-        let circuit_digest = hex!(self
-            .data
-            .verifier_only
-            .circuit_digest
-            .to_vec()
-            .iter()
-            .flat_map(|e| e.to_canonical_u64().to_be_bytes())
-            .collect::<Vec<u8>>());
-        circuit_digest[0..22].to_string()
+        circuit_id(&self.data.verifier_only.circuit_digest.to_vec())
     }
 
     /// Serializes the circuit to bytes.
@@ -238,6 +546,8 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
             data,
             io,
             async_hints,
+            debug_assertions: Vec::new(),
+            profiling_report: None,
         })
     }
 
@@ -295,11 +605,31 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
     }
 }
 
+/// Derives a circuit id from its digest. Circuit ids are used as release identifiers, so this
+/// deliberately stays on the legacy `hex!` formatting instead of `encode_digest` and must never
+/// change its output.
+pub(super) fn circuit_id<F: PrimeField64>(circuit_digest: &[F]) -> String {
+    let circuit_digest = hex!(circuit_digest
+        .iter()
+        .flat_map(|e| e.to_canonical_u64().to_be_bytes())
+        .collect::<Vec<u8>>());
+    circuit_digest[0..22].to_string()
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
 
-    use crate::backend::circuit::CircuitBuild;
+    use async_trait::async_trait;
+    use serde::{Deserialize, Serialize};
+
+    use super::circuit_id;
+    use crate::backend::circuit::{CircuitBuild, ProofCache, ProveOptions, ProvePhase};
+    use crate::frontend::hint::asynchronous::hint::AsyncHint;
+    use crate::frontend::hint::simple::hint::Hint;
     use crate::prelude::*;
+    use crate::utils::{encode_digest, DigestFormat};
 
     type L = DefaultParameters;
     const D: usize = 2;
@@ -358,6 +688,105 @@ pub(crate) mod tests {
         }
     }
 
+    #[test]
+    fn test_prove_with_template() {
+        const TABLE_SIZE: usize = 1024;
+
+        // Define a circuit that uses a large table which is not part of the circuit io.
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let table = builder.init::<ArrayVariable<Variable, TABLE_SIZE>>();
+        let sum = builder.add_many(table.as_slice());
+        let c = builder.add(a, sum);
+        builder.write(c);
+        let circuit = builder.build();
+
+        let table_values = (0..TABLE_SIZE)
+            .map(GoldilocksField::from_canonical_usize)
+            .collect::<Vec<_>>();
+
+        // Write the table into the template once.
+        let mut template = circuit.witness_template();
+        table.set(&mut template, table_values.clone());
+
+        // Write to the circuit input.
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::TWO);
+
+        // Generate a proof using the template.
+        let (proof, output) = circuit.prove_with_template(&template, &input);
+        circuit.verify(&proof, &input, &output);
+
+        // Generate a proof by writing the table into a fresh witness.
+        let mut pw = PartialWitness::new();
+        table.set(&mut pw, table_values);
+        circuit.io.set_witness(&mut pw, &input);
+        let (expected_proof, expected_output) = circuit.prove_with_partial_witness(pw);
+
+        assert_eq!(proof, expected_proof);
+        assert_eq!(output, expected_output);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_prove_with_template_overlapping_io() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        builder.write(a);
+        let circuit = builder.build();
+
+        // The template sets a target that belongs to the circuit input.
+        let mut template = circuit.witness_template();
+        a.set(&mut template, GoldilocksField::ONE);
+
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::TWO);
+        circuit.prove_with_template(&template, &input);
+    }
+
+    #[test]
+    fn test_prove_with_progress() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let c = builder.mul(a, b);
+        builder.write(c);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::TWO);
+        input.write::<Variable>(GoldilocksField::TWO);
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorded = events.clone();
+        let options = ProveOptions::new().with_progress(move |progress| {
+            recorded.borrow_mut().push(*progress);
+            // A panicking callback must not affect the proof.
+            if progress.phase == ProvePhase::ProofGenerationStarted {
+                panic!("callback failure");
+            }
+        });
+        let (proof, mut output) = circuit.prove_with_options(&input, options);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(
+            output.read::<Variable>(),
+            GoldilocksField::from_canonical_u64(4)
+        );
+
+        let events = events.borrow();
+        assert_eq!(events.len(), 4);
+        assert!(matches!(
+            events[0].phase,
+            ProvePhase::WitnessGenerationStarted { .. }
+        ));
+        assert!(events.windows(2).all(|w| w[0].fraction <= w[1].fraction));
+        assert_eq!(
+            events.last().unwrap().phase,
+            ProvePhase::ProofGenerationFinished
+        );
+        assert_eq!(events.last().unwrap().fraction, 1.0);
+    }
+
     #[test]
     fn test_serialize_with_evm_io() {
         // Define your circuit.
@@ -421,4 +850,174 @@ pub(crate) mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_circuit_id_golden() {
+        let digest = [0x0123456789abcdef, 0xfedcba9876543210, 1, 2]
+            .map(GoldilocksField::from_canonical_u64)
+            .to_vec();
+        assert_eq!(circuit_id(&digest), "0x0123456789abcdeffedc");
+        assert_eq!(
+            circuit_id(&digest),
+            encode_digest(&digest, DigestFormat::BE_PREFIXED)[0..22]
+        );
+    }
+
+    #[test]
+    fn test_prove_cached() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let c = builder.add(a, b);
+        builder.write(c);
+        let circuit = builder.build();
+
+        let dir = std::env::temp_dir().join(format!("proofs-{}", uuid::Uuid::new_v4()));
+        let cache = ProofCache::new(&dir);
+
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::TWO);
+        input.write::<Variable>(GoldilocksField::ONE);
+        assert!(cache.get(&circuit, &input).is_none());
+
+        let (proof, output) = circuit.prove_cached(&input, &cache);
+        circuit.verify(&proof, &input, &output);
+        let cached = cache.get(&circuit, &input).expect("proof was not cached");
+        assert_eq!(cached, proof);
+
+        let (second_proof, mut second_output) = circuit.prove_cached(&input, &cache);
+        assert_eq!(second_proof, proof);
+        assert_eq!(second_output, output);
+        assert_eq!(
+            second_output.read::<Variable>(),
+            GoldilocksField::from_canonical_u64(3)
+        );
+
+        // A corrupted entry is a miss and gets overwritten.
+        for entry in walk_files(&dir) {
+            std::fs::write(entry, b"not a proof").unwrap();
+        }
+        assert!(cache.get(&circuit, &input).is_none());
+        let (proof, output) = circuit.prove_cached(&input, &cache);
+        circuit.verify(&proof, &input, &output);
+        assert!(cache.get(&circuit, &input).is_some());
+
+        // A valid proof of another input is a miss, and its output is never returned.
+        let mut other_input = circuit.input();
+        other_input.write::<Variable>(GoldilocksField::ONE);
+        other_input.write::<Variable>(GoldilocksField::ONE);
+        let (other_proof, _) = circuit.prove(&other_input);
+        for entry in walk_files(&dir) {
+            std::fs::write(entry, other_proof.to_bytes()).unwrap();
+        }
+        assert!(cache.get(&circuit, &input).is_none());
+        let (proof, mut output) = circuit.prove_cached(&input, &cache);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(
+            output.read::<Variable>(),
+            GoldilocksField::from_canonical_u64(3)
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct DoubleHint;
+
+    impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for DoubleHint {
+        fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            let a = input_stream.read_value::<ByteVariable>();
+            output_stream.write_value::<ByteVariable>(a.wrapping_mul(2));
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct IncrementHint;
+
+    #[async_trait]
+    impl<L: PlonkParameters<D>, const D: usize> AsyncHint<L, D> for IncrementHint {
+        async fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            let a = input_stream.read_value::<ByteVariable>();
+            output_stream.write_value::<ByteVariable>(a.wrapping_add(1));
+        }
+    }
+
+    #[test]
+    fn test_mock_prove_matches_prove() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<ByteVariable>();
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&a);
+        let doubled = builder
+            .hint(input_stream.clone(), DoubleHint)
+            .read::<ByteVariable>(&mut builder);
+        let incremented = builder
+            .async_hint(input_stream, IncrementHint)
+            .read::<ByteVariable>(&mut builder);
+        builder.write(doubled);
+        builder.write(incremented);
+        let unset = builder.api.add_virtual_target();
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<ByteVariable>(20);
+        let result = circuit.mock_prove(&input);
+        assert!(result.unset_targets.contains(&unset));
+        let mut mock_output = result.output;
+
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(mock_output, output);
+        assert_eq!(mock_output.read::<ByteVariable>(), 40);
+        assert_eq!(mock_output.read::<ByteVariable>(), 21);
+    }
+
+    #[test]
+    fn test_mock_prove_debug_assertion() {
+        let mut builder = DefaultBuilder::new();
+        builder.set_debug();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let expected = builder.read::<Variable>();
+        let sum = builder.add(a, b);
+        builder.assert_is_equal_labeled(sum, expected, "sum of the inputs");
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::TWO);
+        input.write::<Variable>(GoldilocksField::TWO);
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(5));
+        let error = circuit.try_mock_prove(&input).unwrap_err().to_string();
+        assert!(error.contains("\"sum of the inputs\""), "{}", error);
+        assert!(error.contains(file!()), "{}", error);
+        assert!(error.contains("5 != 4"), "{}", error);
+
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::TWO);
+        input.write::<Variable>(GoldilocksField::TWO);
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(4));
+        assert!(circuit.try_mock_prove(&input).is_ok());
+    }
+
+    fn walk_files(dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .flat_map(|entry| {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    walk_files(&path)
+                } else {
+                    vec![path]
+                }
+            })
+            .collect()
+    }
 }
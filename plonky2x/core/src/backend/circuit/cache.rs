@@ -0,0 +1,112 @@
+use std::fs;
+use std::path::PathBuf;
+
+use log::{debug, warn};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+
+use super::{CircuitBuild, PlonkParameters, PublicInput};
+use crate::frontend::builder::CircuitIO;
+use crate::utils::hash::sha256;
+use crate::utils::{encode_digest, DigestFormat, Endianness};
+
+/// A filesystem-backed cache of proofs, used by `CircuitBuild::prove_cached`.
+///
+/// Entries are stored at `{dir}/{circuit_digest}/{input_digest}.proof`, where the input digest is
+/// the sha256 of the bincode-serialized public input. Entries are re-verified when they are read,
+/// and an entry that cannot be deserialized, does not verify or proves another input is treated as
+/// a miss. Circuits whose inputs are not all public, which are the ones with proof io, never hit.
+#[derive(Debug, Clone)]
+pub struct ProofCache {
+    dir: PathBuf,
+}
+
+impl Default for ProofCache {
+    fn default() -> Self {
+        Self::new("./proofs")
+    }
+}
+
+impl ProofCache {
+    /// Creates a cache that stores its entries under `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn entry_path<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        circuit: &CircuitBuild<L, D>,
+        input: &PublicInput<L, D>,
+    ) -> PathBuf {
+        let circuit_digest = encode_digest(
+            &circuit.data.verifier_only.circuit_digest.elements,
+            DigestFormat {
+                endianness: Endianness::Big,
+                prefix: false,
+                uppercase: false,
+            },
+        );
+        let input_bytes = bincode::serialize(input).expect("failed to serialize input");
+        let input_digest = hex::encode(sha256(&input_bytes));
+        self.dir
+            .join(circuit_digest)
+            .join(format!("{}.proof", input_digest))
+    }
+
+    /// Returns the cached proof for the input, if there is a valid one.
+    pub fn get<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        circuit: &CircuitBuild<L, D>,
+        input: &PublicInput<L, D>,
+    ) -> Option<ProofWithPublicInputs<L::Field, L::Config, D>> {
+        let path = self.entry_path(circuit, input);
+        let bytes = fs::read(&path).ok()?;
+        let proof = match ProofWithPublicInputs::from_bytes(bytes, &circuit.data.common) {
+            Ok(proof) => proof,
+            Err(e) => {
+                warn!("ignoring corrupted proof cache entry {:?}: {}", path, e);
+                return None;
+            }
+        };
+        if !proves_input(circuit, input, &proof) {
+            warn!("ignoring proof cache entry {:?} of another input", path);
+            return None;
+        }
+        if let Err(e) = circuit.data.verify(proof.clone()) {
+            warn!("ignoring invalid proof cache entry {:?}: {}", path, e);
+            return None;
+        }
+        debug!("proof cache hit {:?}", path);
+        Some(proof)
+    }
+
+    /// Stores a proof for the input. Failing to write the entry is logged and otherwise ignored.
+    pub fn put<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        circuit: &CircuitBuild<L, D>,
+        input: &PublicInput<L, D>,
+        proof: &ProofWithPublicInputs<L::Field, L::Config, D>,
+    ) {
+        let path = self.entry_path(circuit, input);
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, proof.to_bytes()));
+        if let Err(e) = result {
+            warn!("failed to write proof cache entry {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Returns whether the public inputs of `proof` are the input.
+fn proves_input<L: PlonkParameters<D>, const D: usize>(
+    circuit: &CircuitBuild<L, D>,
+    input: &PublicInput<L, D>,
+    proof: &ProofWithPublicInputs<L::Field, L::Config, D>,
+) -> bool {
+    match &circuit.io {
+        CircuitIO::Bytes(_) | CircuitIO::Elements(_) | CircuitIO::None() => {
+            PublicInput::from_proof_with_pis(&circuit.io, proof) == *input
+        }
+        CircuitIO::RecursiveProofs(_) | CircuitIO::CyclicProof(_) => false,
+    }
+}
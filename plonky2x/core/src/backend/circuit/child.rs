@@ -0,0 +1,269 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::circuit_data::{
+    CircuitData, CommonCircuitData, VerifierCircuitData, VerifierOnlyCircuitData,
+};
+use plonky2::plonk::config::{GenericConfig, GenericHashOut, Hasher};
+use plonky2::util::serialization::{Buffer, GateSerializer, IoError, IoResult, Read, Write};
+
+use super::build::CircuitBuild;
+use super::config::PlonkParameters;
+use crate::frontend::builder::CircuitIO;
+use crate::utils::serde::{BufferRead, BufferWrite};
+
+/// The version of the serialization of `ChildCircuitData`, which is bumped whenever it changes.
+const CHILD_CIRCUIT_DATA_VERSION: usize = 1;
+
+/// Data that a recursive circuit needs to verify proofs of another circuit.
+pub trait VerifierData<F, C: GenericConfig<D, F = F>, const D: usize>
+where
+    F: RichField + Extendable<D>,
+{
+    fn verifier_only(&self) -> &VerifierOnlyCircuitData<C, D>;
+
+    fn common(&self) -> &CommonCircuitData<F, D>;
+}
+
+/// The verifier data of a circuit, as shipped to a process that builds a circuit verifying its
+/// proofs without having built the circuit itself.
+#[derive(Debug, Clone)]
+pub struct ChildCircuitData<L: PlonkParameters<D>, const D: usize> {
+    pub verifier_only: VerifierOnlyCircuitData<L::Config, D>,
+    pub common: CommonCircuitData<L::Field, D>,
+    pub io: CircuitIO<D>,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D> {
+    /// Serializes the verifier data of the circuit, along with its IO, to be read with
+    /// `ChildCircuitData::from_bytes`.
+    pub fn verifier_data_bytes(
+        &self,
+        gate_serializer: &impl GateSerializer<L::Field, D>,
+    ) -> IoResult<Vec<u8>> {
+        write_child_circuit_data::<L, D>(
+            &self.data.verifier_only,
+            &self.data.common,
+            &self.io,
+            gate_serializer,
+        )
+    }
+}
+
+/// Serializes the verifier data and the IO of a circuit, with a header of the version of the
+/// serialization and the circuit digest.
+fn write_child_circuit_data<L: PlonkParameters<D>, const D: usize>(
+    verifier_only: &VerifierOnlyCircuitData<L::Config, D>,
+    common: &CommonCircuitData<L::Field, D>,
+    io: &CircuitIO<D>,
+    gate_serializer: &impl GateSerializer<L::Field, D>,
+) -> IoResult<Vec<u8>> {
+    let mut buffer = Vec::new();
+    buffer.write_usize(CHILD_CIRCUIT_DATA_VERSION)?;
+    buffer.write_bytes(&verifier_only.circuit_digest.to_bytes())?;
+
+    let verifier_only = verifier_only.to_bytes()?;
+    buffer.write_bytes(&verifier_only)?;
+
+    let common = common.to_bytes(gate_serializer)?;
+    buffer.write_bytes(&common)?;
+
+    let io = bincode::serialize(io).map_err(|_| IoError)?;
+    buffer.write_bytes(&io)?;
+
+    Ok(buffer)
+}
+
+/// Returns the digest of a circuit with the given constants and sigmas cap and common data, as
+/// plonky2 computes it when building a circuit without a domain separator.
+fn circuit_digest<L: PlonkParameters<D>, const D: usize>(
+    verifier_only: &VerifierOnlyCircuitData<L::Config, D>,
+    common: &CommonCircuitData<L::Field, D>,
+) -> Vec<u8> {
+    let domain_separator_digest = <L::Config as GenericConfig<D>>::Hasher::hash_pad(&[]);
+    let elements = [
+        verifier_only.constants_sigmas_cap.flatten(),
+        domain_separator_digest.to_vec(),
+        vec![L::Field::from_canonical_usize(common.degree_bits())],
+    ]
+    .concat();
+    <L::Config as GenericConfig<D>>::Hasher::hash_no_pad(&elements).to_bytes()
+}
+
+impl<L: PlonkParameters<D>, const D: usize> ChildCircuitData<L, D> {
+    /// Deserializes the output of `CircuitBuild::verifier_data_bytes`.
+    ///
+    /// Returns an error if the bytes were written by another version of the serialization, or if
+    /// the circuit digest in their header or in the verifier data does not match the digest
+    /// recomputed from the constants and sigmas cap and the common data.
+    pub fn from_bytes(
+        bytes: &[u8],
+        gate_serializer: &impl GateSerializer<L::Field, D>,
+    ) -> IoResult<Self> {
+        let mut buffer = Buffer::new(bytes);
+        let version = buffer.read_usize()?;
+        if version != CHILD_CIRCUIT_DATA_VERSION {
+            return Err(IoError);
+        }
+        let header_digest = buffer.read_bytes()?;
+
+        let verifier_only = buffer.read_bytes()?;
+        let verifier_only = VerifierOnlyCircuitData::<L::Config, D>::from_bytes(verifier_only)?;
+
+        let common = buffer.read_bytes()?;
+        let common = CommonCircuitData::<L::Field, D>::from_bytes(common, gate_serializer)?;
+
+        let digest = circuit_digest::<L, D>(&verifier_only, &common);
+        if header_digest != digest || verifier_only.circuit_digest.to_bytes() != digest {
+            return Err(IoError);
+        }
+
+        let io = buffer.read_bytes()?;
+        let io: CircuitIO<D> = bincode::deserialize(&io).map_err(|_| IoError)?;
+
+        Ok(Self {
+            verifier_only,
+            common,
+            io,
+        })
+    }
+
+    /// The number of public inputs of proofs of the circuit.
+    pub fn num_public_inputs(&self) -> usize {
+        self.common.num_public_inputs
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> VerifierData<L::Field, L::Config, D>
+    for ChildCircuitData<L, D>
+{
+    fn verifier_only(&self) -> &VerifierOnlyCircuitData<L::Config, D> {
+        &self.verifier_only
+    }
+
+    fn common(&self) -> &CommonCircuitData<L::Field, D> {
+        &self.common
+    }
+}
+
+impl<F, C: GenericConfig<D, F = F>, const D: usize> VerifierData<F, C, D> for CircuitData<F, C, D>
+where
+    F: RichField + Extendable<D>,
+{
+    fn verifier_only(&self) -> &VerifierOnlyCircuitData<C, D> {
+        &self.verifier_only
+    }
+
+    fn common(&self) -> &CommonCircuitData<F, D> {
+        &self.common
+    }
+}
+
+impl<F, C: GenericConfig<D, F = F>, const D: usize> VerifierData<F, C, D>
+    for VerifierCircuitData<F, C, D>
+where
+    F: RichField + Extendable<D>,
+{
+    fn verifier_only(&self) -> &VerifierOnlyCircuitData<C, D> {
+        &self.verifier_only
+    }
+
+    fn common(&self) -> &CommonCircuitData<F, D> {
+        &self.common
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::plonk::proof::ProofWithPublicInputs;
+
+    use super::*;
+    use crate::backend::circuit::GateRegistry;
+    use crate::prelude::{DefaultBuilder, DefaultParameters, Field, GoldilocksField, Variable};
+
+    type L = DefaultParameters;
+    type F = GoldilocksField;
+
+    /// Builds a circuit that squares its input.
+    fn square_circuit() -> CircuitBuild<L, 2> {
+        let mut builder = DefaultBuilder::new();
+        let x = builder.read::<Variable>();
+        let y = builder.mul(x, x);
+        builder.write(y);
+        builder.build()
+    }
+
+    /// Builds a circuit that squares its input, and returns its verifier data and a proof of
+    /// `3^2 = 9` as bytes.
+    fn inner() -> (Vec<u8>, Vec<u8>) {
+        let circuit = square_circuit();
+
+        let mut input = circuit.input();
+        input.write::<Variable>(F::from_canonical_u64(3));
+        let (proof, _) = circuit.prove(&input);
+        let gate_serializer = GateRegistry::<L, 2>::new();
+        let verifier_data = circuit.verifier_data_bytes(&gate_serializer).unwrap();
+        (verifier_data, proof.to_bytes())
+    }
+
+    /// Builds a circuit that verifies the proof of `inner` from its bytes, and proves it.
+    fn outer(verifier_data: &[u8], proof: &[u8]) {
+        let gate_serializer = GateRegistry::<L, 2>::new();
+        let child = ChildCircuitData::<L, 2>::from_bytes(verifier_data, &gate_serializer).unwrap();
+        assert_eq!(child.num_public_inputs(), 2);
+        assert_eq!(child.io.input().len(), 1);
+
+        let mut builder = DefaultBuilder::new();
+        let verifier_data = builder.constant_verifier_data::<L>(&child);
+        let proof_target = builder.proof_read(&child.common);
+        builder.verify_proof::<L>(&proof_target, &verifier_data, &child.common);
+        builder.proof_write(Variable(proof_target.public_inputs[1]));
+        let circuit = builder.build();
+
+        let proof = ProofWithPublicInputs::from_bytes(proof.to_vec(), &child.common).unwrap();
+        let mut input = circuit.input();
+        input.proof_write(proof);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.data.verify(proof).unwrap();
+        assert_eq!(output.proof_read::<Variable>(), F::from_canonical_u64(9));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_child_circuit_data() {
+        let (verifier_data, proof) = inner();
+        outer(&verifier_data, &proof);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_child_circuit_data_digest_mismatch() {
+        let (mut verifier_data, _) = inner();
+        // The digest follows the version and its length in the header.
+        verifier_data[16] ^= 1;
+        let gate_serializer = GateRegistry::<L, 2>::new();
+        assert!(ChildCircuitData::<L, 2>::from_bytes(&verifier_data, &gate_serializer).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_child_circuit_data_cap_mismatch() {
+        let circuit = square_circuit();
+        let gate_serializer = GateRegistry::<L, 2>::new();
+
+        // Verifier data whose digests are consistent with each other, but not with its cap.
+        let mut verifier_only = circuit.data.verifier_only.clone();
+        verifier_only.constants_sigmas_cap.0[0].elements[0] += F::ONE;
+        let verifier_data = write_child_circuit_data::<L, 2>(
+            &verifier_only,
+            &circuit.data.common,
+            &circuit.io,
+            &gate_serializer,
+        )
+        .unwrap();
+        assert!(ChildCircuitData::<L, 2>::from_bytes(&verifier_data, &gate_serializer).is_err());
+
+        let verifier_data = circuit.verifier_data_bytes(&gate_serializer).unwrap();
+        assert!(ChildCircuitData::<L, 2>::from_bytes(&verifier_data, &gate_serializer).is_ok());
+    }
+}
@@ -0,0 +1,132 @@
+//! Evaluation of the gate constraints of a circuit on a witness, without proving.
+
+use core::fmt::{self, Display};
+
+use itertools::Itertools;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::types::{Field, PrimeField64};
+use plonky2::hash::hash_types::HashOut;
+use plonky2::iop::witness::MatrixWitness;
+use plonky2::plonk::circuit_data::{CommonCircuitData, ProverOnlyCircuitData};
+use plonky2::plonk::vars::EvaluationVars;
+
+use super::PlonkParameters;
+
+/// The value of the selectors of the groups of gates that a row does not belong to, as set by
+/// plonky2.
+const UNUSED_SELECTOR: u64 = u32::MAX as u64;
+
+/// A gate constraint that is not satisfied by a witness.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ConstraintViolation {
+    pub row: usize,
+    pub gate: String,
+    /// The index of the constraint among the constraints of the gate.
+    pub constraint: usize,
+}
+
+impl Display for ConstraintViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "constraint {} of {} at row {}",
+            self.constraint, self.gate, self.row
+        )
+    }
+}
+
+/// Evaluates the constraints of the gate of every row on the wire values of `witness`, and returns
+/// the constraints that are not satisfied.
+///
+/// The gate of a row is read from the selector columns, which are the first constants of the
+/// circuit. Lookups are not checked, as their arguments are only built while proving.
+pub(crate) fn check_gate_constraints<L: PlonkParameters<D>, const D: usize>(
+    prover_data: &ProverOnlyCircuitData<L::Field, L::Config, D>,
+    common_data: &CommonCircuitData<L::Field, D>,
+    witness: &MatrixWitness<L::Field>,
+    public_inputs_hash: &HashOut<L::Field>,
+) -> Vec<ConstraintViolation> {
+    let num_selectors = common_data.selectors_info.num_selectors();
+    let num_wires = common_data.config.num_wires;
+    let to_extension = <L::Field as Extendable<D>>::Extension::from_basefield;
+    let constants = constant_values::<L, D>(prover_data, common_data);
+
+    let mut violations = Vec::new();
+    for row in 0..common_data.degree() {
+        let Some(gate_index) = row_gate(&constants[..num_selectors], row) else {
+            continue;
+        };
+        let gate = &common_data.gates[gate_index];
+
+        let local_constants = constants[num_selectors + common_data.num_lookup_selectors..]
+            .iter()
+            .map(|constant| to_extension(constant[row]))
+            .collect::<Vec<_>>();
+        let local_wires = (0..num_wires)
+            .map(|column| to_extension(witness.get_wire(row, column)))
+            .collect::<Vec<_>>();
+        let vars = EvaluationVars {
+            local_constants: &local_constants,
+            local_wires: &local_wires,
+            public_inputs_hash,
+        };
+
+        let constraints = gate.0.eval_unfiltered(vars);
+        violations.extend(
+            constraints
+                .iter()
+                .enumerate()
+                .filter(|(_, value)| !value.is_zero())
+                .map(|(constraint, _)| ConstraintViolation {
+                    row,
+                    gate: gate.0.id(),
+                    constraint,
+                }),
+        );
+    }
+    violations
+}
+
+/// Returns the values of the constant columns of a circuit on its rows, starting with the selector
+/// columns.
+pub(crate) fn constant_values<L: PlonkParameters<D>, const D: usize>(
+    prover_data: &ProverOnlyCircuitData<L::Field, L::Config, D>,
+    common_data: &CommonCircuitData<L::Field, D>,
+) -> Vec<Vec<L::Field>> {
+    // The constants are committed in coefficient form, so their values on the rows are recovered
+    // with an FFT, which is much cheaper than the FFTs of the witness polynomials.
+    prover_data.constants_sigmas_commitment.polynomials[..common_data.num_constants]
+        .iter()
+        .map(|poly| poly.clone().fft().values)
+        .collect()
+}
+
+/// Returns the index in `CommonCircuitData::gates` of the gate of a row, read from the values of
+/// the selector columns, or `None` if the row has no gate.
+pub(crate) fn row_gate<F: PrimeField64>(selectors: &[Vec<F>], row: usize) -> Option<usize> {
+    selectors
+        .iter()
+        .map(|selector| selector[row].to_canonical_u64())
+        .find(|&selector| selector != UNUSED_SELECTOR)
+        .map(|selector| selector as usize)
+}
+
+/// Counts the instances of each gate type in the first `num_rows` rows of a circuit, from the most
+/// used.
+pub(crate) fn gate_counts<L: PlonkParameters<D>, const D: usize>(
+    prover_data: &ProverOnlyCircuitData<L::Field, L::Config, D>,
+    common_data: &CommonCircuitData<L::Field, D>,
+    num_rows: usize,
+) -> Vec<(String, usize)> {
+    let num_selectors = common_data.selectors_info.num_selectors();
+    let constants = constant_values::<L, D>(prover_data, common_data);
+    (0..num_rows)
+        .filter_map(|row| row_gate(&constants[..num_selectors], row))
+        .counts()
+        .into_iter()
+        .map(|(gate_index, count)| (common_data.gates[gate_index].0.id(), count))
+        .sorted_by(|(a_gate, a_count), (b_gate, b_count)| {
+            b_count.cmp(a_count).then_with(|| a_gate.cmp(b_gate))
+        })
+        .collect()
+}
@@ -0,0 +1,288 @@
+//! Post-mortem dumps of the witness of failed proofs.
+//!
+//! While a dump is being recorded, the simple hints record their input and output streams and the
+//! watched variables record their values on the thread generating the witness. If witness
+//! generation fails, the recording is written to a JSON file with the input of the proof and the
+//! error.
+
+use core::cell::RefCell;
+use std::fs;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+
+use anyhow::{anyhow, Error, Result};
+use plonky2::field::types::PrimeField64;
+use serde::{Deserialize, Serialize};
+
+use super::input::PublicInput;
+use super::PlonkParameters;
+use crate::frontend::hint::validation::panic_message;
+
+thread_local! {
+    static RECORDING: RefCell<Option<WitnessRecording>> = RefCell::new(None);
+}
+
+/// The values seen by the hints and the watched variables during witness generation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WitnessRecording {
+    /// The streams of the hints, in the order they were run.
+    pub hints: Vec<HintRecord>,
+    /// The values of the watched variables, in the order they were set.
+    pub watches: Vec<WatchRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HintRecord {
+    pub id: String,
+    pub inputs: Vec<u64>,
+    /// The outputs of the hint, or `None` if it failed.
+    pub outputs: Option<Vec<u64>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchRecord {
+    pub label: String,
+    /// The values of the watched variables, formatted as in the logs.
+    pub values: Vec<String>,
+}
+
+/// The state of the witness of a proof whose witness generation failed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WitnessDump {
+    pub circuit_id: String,
+    /// The input of the proof, as serialized by `PublicInput`.
+    pub input: serde_json::Value,
+    pub hints: Vec<HintRecord>,
+    pub watches: Vec<WatchRecord>,
+    /// The error of witness generation, with the description of the failed assertion when it is
+    /// known, e.g. for mock circuits built in debug mode.
+    pub error: String,
+}
+
+impl WitnessDump {
+    pub fn new<L: PlonkParameters<D>, const D: usize>(
+        circuit_id: String,
+        input: &PublicInput<L, D>,
+        recording: WitnessRecording,
+        error: &Error,
+    ) -> Result<Self> {
+        Ok(Self {
+            circuit_id,
+            input: serde_json::to_value(input)?,
+            hints: recording.hints,
+            watches: recording.watches,
+            error: format!("{:#}", error),
+        })
+    }
+
+    /// Writes the dump to a JSON file, creating its parent directories if needed.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent_dir) = path.parent() {
+            fs::create_dir_all(parent_dir)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Runs the witness generation `f` while recording it. If it fails or panics, writes a dump of the
+/// recording to `dump_path` and returns the error.
+pub(crate) fn dump_on_failure<L: PlonkParameters<D>, const D: usize, T>(
+    circuit_id: String,
+    input: &PublicInput<L, D>,
+    dump_path: &Path,
+    f: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    let (result, recording) = record_witness(|| catch_unwind(AssertUnwindSafe(f)));
+    let error = match result {
+        Ok(Ok(value)) => return Ok(value),
+        Ok(Err(e)) => e,
+        Err(payload) => anyhow!("Witness generation panicked: {}", panic_message(&*payload)),
+    };
+    WitnessDump::new(circuit_id, input, recording, &error)?.save(dump_path)?;
+    Err(error)
+}
+
+/// Runs `f` and returns its result with the values recorded on the current thread while it ran.
+pub(crate) fn record_witness<T>(f: impl FnOnce() -> T) -> (T, WitnessRecording) {
+    RECORDING.with(|recording| *recording.borrow_mut() = Some(WitnessRecording::default()));
+    let result = f();
+    let recording = RECORDING.with(|recording| recording.borrow_mut().take().unwrap());
+    (result, recording)
+}
+
+fn with_recording(f: impl FnOnce(&mut WitnessRecording)) {
+    RECORDING.with(|recording| {
+        if let Some(recording) = recording.borrow_mut().as_mut() {
+            f(recording);
+        }
+    });
+}
+
+/// Records the inputs of a hint that is about to run.
+pub(crate) fn record_hint_inputs<F: PrimeField64>(id: &str, inputs: &[F]) {
+    with_recording(|recording| {
+        recording.hints.push(HintRecord {
+            id: id.to_string(),
+            inputs: inputs.iter().map(|x| x.to_canonical_u64()).collect(),
+            outputs: None,
+        })
+    });
+}
+
+/// Records the outputs of the hint whose inputs were recorded last.
+pub(crate) fn record_hint_outputs<F: PrimeField64>(outputs: &[F]) {
+    with_recording(|recording| {
+        if let Some(hint) = recording.hints.last_mut() {
+            hint.outputs = Some(outputs.iter().map(|x| x.to_canonical_u64()).collect());
+        }
+    });
+}
+
+pub(crate) fn record_watch(label: &str, values: Vec<String>) {
+    with_recording(|recording| {
+        recording.watches.push(WatchRecord {
+            label: label.to_string(),
+            values,
+        })
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::frontend::hint::simple::hint::Hint;
+    use crate::prelude::*;
+
+    /// A hint that writes one more byte than the circuit reads.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct BadHint;
+
+    impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for BadHint {
+        fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            let a = input_stream.read_value::<ByteVariable>();
+            output_stream.write_value::<ByteVariable>(a);
+            output_stream.write_value::<ByteVariable>(a);
+        }
+    }
+
+    fn dump_path() -> PathBuf {
+        std::env::temp_dir().join(format!("witness-dump-{}.json", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_record_witness() {
+        record_watch("ignored", vec![]);
+        let ((), recording) = record_witness(|| {
+            record_hint_inputs::<GoldilocksField>("hint", &[GoldilocksField::ONE]);
+            record_hint_outputs::<GoldilocksField>(&[GoldilocksField::TWO]);
+            record_hint_inputs::<GoldilocksField>("failing", &[GoldilocksField::ZERO]);
+            record_watch("a", vec!["0x01".to_string()]);
+        });
+        assert_eq!(
+            recording.hints,
+            vec![
+                HintRecord {
+                    id: "hint".to_string(),
+                    inputs: vec![1],
+                    outputs: Some(vec![2]),
+                },
+                HintRecord {
+                    id: "failing".to_string(),
+                    inputs: vec![0],
+                    outputs: None,
+                },
+            ]
+        );
+        assert_eq!(
+            recording.watches,
+            vec![WatchRecord {
+                label: "a".to_string(),
+                values: vec!["0x01".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_prove_with_debug_dump() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.evm_read::<ByteVariable>();
+        let b = builder.evm_read::<ByteVariable>();
+        builder.evm_write(b);
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&a);
+        let output_stream = builder.hint(input_stream, BadHint);
+        let c = output_stream.read::<ByteVariable>(&mut builder);
+        builder.evm_write(c);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.evm_write::<ByteVariable>(0x2a);
+        input.evm_write::<ByteVariable>(0x01);
+        let path = dump_path();
+        let error = circuit.prove_with_debug_dump(&input, &path).unwrap_err();
+        assert!(error.to_string().contains("wrote 16 output values"));
+
+        let dump: WitnessDump = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(dump.circuit_id, circuit.id());
+        assert_eq!(dump.input, serde_json::json!({ "Bytes": [0x2a, 0x01] }));
+        let hint = dump
+            .hints
+            .iter()
+            .find(|h| h.id.contains("BadHint"))
+            .unwrap();
+        assert_eq!(hint.inputs, vec![0, 0, 1, 0, 1, 0, 1, 0]);
+        assert_eq!(hint.outputs, None);
+        assert!(dump.error.contains("BadHint"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_prove_with_debug_dump_success() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let c = builder.add(a, b);
+        builder.write(c);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::TWO);
+        input.write::<Variable>(GoldilocksField::ONE);
+        let path = dump_path();
+        let (proof, output) = circuit.prove_with_debug_dump(&input, &path).unwrap();
+        circuit.verify(&proof, &input, &output);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_mock_prove_with_debug_dump() {
+        let mut builder = DefaultBuilder::new();
+        builder.set_debug();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let expected = builder.read::<Variable>();
+        let sum = builder.add(a, b);
+        builder.assert_is_equal_labeled(sum, expected, "sum of the inputs");
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::TWO);
+        input.write::<Variable>(GoldilocksField::TWO);
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(5));
+        let path = dump_path();
+        assert!(circuit.mock_prove_with_debug_dump(&input, &path).is_err());
+
+        let dump: WitnessDump = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(dump.input, serde_json::json!({ "Elements": [2, 2, 5] }));
+        assert!(dump.error.contains("\"sum of the inputs\""));
+        assert!(dump.error.contains("5 != 4"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
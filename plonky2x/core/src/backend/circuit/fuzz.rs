@@ -0,0 +1,223 @@
+//! Deterministic fuzzing of circuits with bytes io against a reference implementation.
+//!
+//! The inputs of a run are derived from its seed and the index of the iteration, so a divergence is
+//! reproduced from the seed and iteration it reports, without rerunning the previous iterations.
+
+use core::fmt::{self, Display};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::{CircuitBuild, PlonkParameters};
+use crate::frontend::builder::CircuitIO;
+use crate::frontend::hint::validation::panic_message;
+
+/// Bytes at the edges of the ranges checked by circuits, e.g. booleans, signs and masks.
+const BOUNDARY_BYTES: [u8; 6] = [0x00, 0x01, 0x7f, 0x80, 0xfe, 0xff];
+
+/// The maximum number of random bytes of the sparse inputs.
+const MAX_SPARSE_BYTES: usize = 4;
+
+/// Runs mock proving on generated inputs of a circuit with bytes io, and checks that the circuit
+/// agrees with a reference implementation on each of them.
+///
+/// The reference returns the output bytes of the circuit for the input, or `None` if the circuit
+/// must reject it. The circuit rejects an input when its witness cannot be generated or does not
+/// satisfy its constraints, i.e. when `try_mock_prove` fails or panics.
+pub struct CircuitFuzzer<'a, L: PlonkParameters<D>, const D: usize, R> {
+    circuit: &'a CircuitBuild<L, D>,
+    reference: R,
+    seed: u64,
+    iterations: usize,
+    input_len: usize,
+}
+
+/// The number of inputs accepted and rejected by a run without divergence.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FuzzReport {
+    pub accepted: usize,
+    pub rejected: usize,
+}
+
+/// An input on which the circuit and its reference disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzDivergence {
+    pub seed: u64,
+    pub iteration: usize,
+    pub input: Vec<u8>,
+    /// The output of the reference, or `None` if it rejects the input.
+    pub expected: Option<Vec<u8>>,
+    /// The output of the circuit, or the error of mock proving if it rejects the input.
+    pub actual: Result<Vec<u8>, String>,
+}
+
+impl Display for FuzzDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Circuit diverged from its reference at iteration {} of seed {}",
+            self.iteration, self.seed
+        )?;
+        writeln!(f, "input: 0x{}", hex::encode(&self.input))?;
+        match &self.expected {
+            Some(output) => writeln!(f, "expected: 0x{}", hex::encode(output))?,
+            None => writeln!(f, "expected: rejected")?,
+        }
+        match &self.actual {
+            Ok(output) => write!(f, "actual: 0x{}", hex::encode(output)),
+            Err(error) => write!(f, "actual: rejected with {}", error),
+        }
+    }
+}
+
+impl std::error::Error for FuzzDivergence {}
+
+impl<'a, L: PlonkParameters<D>, const D: usize, R> CircuitFuzzer<'a, L, D, R>
+where
+    R: Fn(&[u8]) -> Option<Vec<u8>>,
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    /// Creates a fuzzer running `iterations` inputs derived from `seed`. Panics if the circuit does
+    /// not have bytes io.
+    pub fn new(
+        circuit: &'a CircuitBuild<L, D>,
+        reference: R,
+        seed: u64,
+        iterations: usize,
+    ) -> Self {
+        let input_len = match &circuit.io {
+            CircuitIO::Bytes(io) => io.input.len(),
+            _ => panic!("CircuitFuzzer requires a circuit with bytes io"),
+        };
+        Self {
+            circuit,
+            reference,
+            seed,
+            iterations,
+            input_len,
+        }
+    }
+
+    /// Returns the input of the given iteration.
+    pub fn input(&self, iteration: usize) -> Vec<u8> {
+        generate_input(self.seed, iteration, self.input_len)
+    }
+
+    /// Runs the iterations, and returns the first divergence from the reference if there is one.
+    pub fn run(&self) -> Result<FuzzReport, FuzzDivergence> {
+        let mut report = FuzzReport::default();
+        for iteration in 0..self.iterations {
+            let input = self.input(iteration);
+            let expected = (self.reference)(&input);
+            let actual = self.mock_prove(&input);
+            match (&expected, &actual) {
+                (Some(expected), Ok(actual)) if expected == actual => report.accepted += 1,
+                (None, Err(_)) => report.rejected += 1,
+                _ => {
+                    return Err(FuzzDivergence {
+                        seed: self.seed,
+                        iteration,
+                        input,
+                        expected,
+                        actual,
+                    })
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Returns the output of the circuit for `bytes`, or the reason it rejects them.
+    fn mock_prove(&self, bytes: &[u8]) -> Result<Vec<u8>, String> {
+        let mut input = self.circuit.input();
+        input.evm_write_all(bytes);
+        match catch_unwind(AssertUnwindSafe(|| self.circuit.try_mock_prove(&input))) {
+            Ok(Ok(result)) => Ok(result.output.evm_read_all()),
+            Ok(Err(e)) => Err(format!("{:#}", e)),
+            Err(payload) => Err(format!("panic: {}", panic_message(&*payload))),
+        }
+    }
+}
+
+/// Generates the input of an iteration. The first two inputs are all zeros and all ones, and the
+/// next ones are uniformly random, sparse, made of boundary bytes or a repeated byte.
+fn generate_input(seed: u64, iteration: usize, len: usize) -> Vec<u8> {
+    match iteration {
+        0 => return vec![0x00; len],
+        1 => return vec![0xff; len],
+        _ => {}
+    }
+    let mut rng_seed = [0u8; 32];
+    rng_seed[..8].copy_from_slice(&seed.to_le_bytes());
+    rng_seed[8..16].copy_from_slice(&(iteration as u64).to_le_bytes());
+    let mut rng = StdRng::from_seed(rng_seed);
+    match rng.gen_range(0..4) {
+        0 => (0..len).map(|_| rng.gen()).collect(),
+        1 => {
+            let mut input = vec![0x00; len];
+            if len > 0 {
+                for _ in 0..rng.gen_range(1..=MAX_SPARSE_BYTES) {
+                    input[rng.gen_range(0..len)] = rng.gen();
+                }
+            }
+            input
+        }
+        2 => (0..len)
+            .map(|_| BOUNDARY_BYTES[rng.gen_range(0..BOUNDARY_BYTES.len())])
+            .collect(),
+        _ => vec![rng.gen(); len],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_generate_input() {
+        assert_eq!(generate_input(7, 0, 4), vec![0x00; 4]);
+        assert_eq!(generate_input(7, 1, 4), vec![0xff; 4]);
+        for iteration in 2..32 {
+            let input = generate_input(7, iteration, 16);
+            assert_eq!(input.len(), 16);
+            assert_eq!(input, generate_input(7, iteration, 16));
+        }
+        assert_ne!(
+            (2..32)
+                .map(|i| generate_input(7, i, 16))
+                .collect::<Vec<_>>(),
+            (2..32)
+                .map(|i| generate_input(8, i, 16))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_fuzzer_divergence() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.evm_read::<ByteVariable>();
+        let b = builder.evm_read::<ByteVariable>();
+        let c = builder.xor(a, b);
+        builder.evm_write(c);
+        let circuit = builder.build();
+
+        let xor = |input: &[u8]| Some(vec![input[0] ^ input[1]]);
+        let report = CircuitFuzzer::new(&circuit, xor, 3, 16).run().unwrap();
+        assert_eq!(report.accepted, 16);
+
+        // The reference is wrong on the second input, which is all ones.
+        let and = |input: &[u8]| Some(vec![input[0] & input[1]]);
+        let divergence = CircuitFuzzer::new(&circuit, and, 3, 16).run().unwrap_err();
+        assert_eq!(divergence.iteration, 1);
+        assert_eq!(divergence.input, vec![0xff, 0xff]);
+        assert_eq!(divergence.expected, Some(vec![0xff]));
+        assert_eq!(divergence.actual, Ok(vec![0x00]));
+        let message = divergence.to_string();
+        assert!(message.contains("iteration 1 of seed 3"), "{}", message);
+        assert!(message.contains("input: 0xffff"), "{}", message);
+    }
+}
@@ -1,9 +1,10 @@
 use itertools::Itertools;
 use plonky2::plonk::circuit_data::VerifierCircuitData;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 use plonky2::plonk::proof::ProofWithPublicInputs;
 use serde::{Deserialize, Serialize};
 
-use super::PlonkParameters;
+use super::{CircuitBuild, PlonkParameters};
 use crate::backend::prover::ProofId;
 use crate::frontend::builder::CircuitIO;
 use crate::frontend::vars::{EvmVariable, ValueStream};
@@ -157,6 +158,22 @@ impl<L: PlonkParameters<D>, const D: usize> PublicInput<L, D> {
         };
     }
 
+    /// Writes the previous proof of a circuit with cyclic IO, along with the verifier data of the
+    /// circuit. Without a previous proof, i.e. in the base case, the dummy proof of the circuit is
+    /// written instead.
+    pub fn self_proof_write(
+        &mut self,
+        circuit: &CircuitBuild<L, D>,
+        proof: Option<ProofWithPublicInputs<L::Field, L::Config, D>>,
+    ) where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let proof = proof.unwrap_or_else(|| circuit.cyclic_base_proof());
+        self.data_write(circuit.data.verifier_data());
+        self.proof_write(proof);
+    }
+
     /// Sets a value to the circuit input. This method only works if the circuit is using
     /// field element-based IO.
     pub fn set<V: CircuitVariable>(&mut self, _: V, _: V::ValueType<L::Field>) {
@@ -1,14 +1,21 @@
 use alloc::collections::BTreeMap;
 use std::collections::HashMap;
+use std::path::Path;
 
+use anyhow::{Error, Result};
+use plonky2::iop::target::Target;
 use plonky2::iop::witness::{PartialWitness, PartitionWitness};
 use plonky2::plonk::circuit_data::MockCircuitData;
-use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, GenericHashOut};
 
+use super::build::circuit_id;
+use super::dump::dump_on_failure;
 use super::input::PublicInput;
 use super::output::PublicOutput;
-use super::witness::generate_witness;
+use super::witness::{explain_conflict, generate_witness, unset_targets};
 use super::PlonkParameters;
+use crate::frontend::builder::debug::DebugAssertion;
+use crate::frontend::builder::profiling::ProfilingReport;
 use crate::frontend::builder::CircuitIO;
 use crate::frontend::hint::asynchronous::generator::AsyncHintDataRef;
 
@@ -21,7 +28,21 @@ pub struct MockCircuitBuild<L: PlonkParameters<D>, const D: usize> {
     pub data: MockCircuitData<L::Field, L::Config, D>,
     pub io: CircuitIO<D>,
     pub debug_variables: HashMap<usize, String>,
+    pub debug_assertions: Vec<DebugAssertion>,
     pub async_hints: BTreeMap<usize, AsyncHintDataRef<L, D>>,
+    /// The profiling report of the circuit, if it was built with profiling enabled.
+    pub profiling_report: Option<ProfilingReport>,
+}
+
+/// The result of mock proving a circuit, i.e. of generating its witness without proving.
+#[derive(Debug)]
+pub struct MockProveResult<'a, L: PlonkParameters<D>, const D: usize> {
+    /// The generated witness.
+    pub witness: PartitionWitness<'a, L::Field>,
+    /// The output of the circuit.
+    pub output: PublicOutput<L, D>,
+    /// The virtual targets that the witness leaves unset, which no generator or hint computes.
+    pub unset_targets: Vec<Target>,
 }
 
 impl<L: PlonkParameters<D>, const D: usize> MockCircuitBuild<L, D> {
@@ -31,10 +52,19 @@ impl<L: PlonkParameters<D>, const D: usize> MockCircuitBuild<L, D> {
     }
 
     /// Generates a mock proof.
-    pub fn mock_prove(
-        &self,
-        input: &PublicInput<L, D>,
-    ) -> (PartitionWitness<L::Field>, PublicOutput<L, D>)
+    pub fn mock_prove(&self, input: &PublicInput<L, D>) -> MockProveResult<L, D>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        self.try_mock_prove(input).unwrap()
+    }
+
+    /// Generates a mock proof, or returns the error of witness generation.
+    ///
+    /// If the circuit was built in debug mode and an equality assertion fails, the error describes
+    /// the assertion, with its label, its location and the two values.
+    pub fn try_mock_prove(&self, input: &PublicInput<L, D>) -> Result<MockProveResult<L, D>>
     where
         <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
             AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
@@ -52,12 +82,50 @@ impl<L: PlonkParameters<D>, const D: usize> MockCircuitBuild<L, D> {
             &self.data.common,
             &self.async_hints,
         )
-        .unwrap();
+        .map_err(|e| self.explain_failure(e))?;
 
         // Get the output from the witness.
         let output = PublicOutput::from_witness(&self.io, &witness);
 
-        (witness, output)
+        let unset_targets = unset_targets(
+            &witness,
+            &self.data.prover_only.representative_map,
+            self.data.common.config.num_wires,
+            self.data.common.degree(),
+        );
+
+        Ok(MockProveResult {
+            witness,
+            output,
+            unset_targets,
+        })
+    }
+
+    /// Generates a mock proof, or returns the error of witness generation after writing a
+    /// `WitnessDump` of it to `dump_path`. The error of the dump describes the failed debug
+    /// assertion, as in `try_mock_prove`.
+    pub fn mock_prove_with_debug_dump(
+        &self,
+        input: &PublicInput<L, D>,
+        dump_path: impl AsRef<Path>,
+    ) -> Result<MockProveResult<L, D>>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let id = circuit_id(&self.data.prover_only.circuit_digest.to_vec());
+        dump_on_failure(id, input, dump_path.as_ref(), || self.try_mock_prove(input))
+    }
+
+    /// Adds the description of the failed debug assertion to a witness generation error.
+    fn explain_failure(&self, error: Error) -> Error {
+        explain_conflict::<L::Field>(
+            error,
+            &self.debug_assertions,
+            &self.data.prover_only.representative_map,
+            self.data.common.config.num_wires,
+            self.data.common.degree(),
+        )
     }
 }
 
@@ -89,7 +157,7 @@ pub(crate) mod tests {
         input.write::<Variable>(GoldilocksField::TWO);
 
         // Generate a proof.
-        let (_witness, mut output) = mock_circuit.mock_prove(&input);
+        let mut output = mock_circuit.mock_prove(&input).output;
 
         // Read output.
         let sum = output.read::<Variable>();
@@ -116,10 +184,48 @@ pub(crate) mod tests {
         input.evm_write::<ByteVariable>(7u8);
 
         // // Generate a proof.
-        let (_witness, mut output) = mock_circuit.mock_prove(&input);
+        let mut output = mock_circuit.mock_prove(&input).output;
 
         // // Read output.
         let xor = output.evm_read::<ByteVariable>();
         debug!("{}", xor);
     }
+
+    #[test]
+    fn test_mock_circuit_unset_targets() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        builder.write(a);
+        let unset = builder.api.add_virtual_target();
+        let mock_circuit = builder.mock_build();
+
+        let mut input = mock_circuit.input();
+        input.write::<Variable>(GoldilocksField::TWO);
+        let mut result = mock_circuit.mock_prove(&input);
+        assert!(result.unset_targets.contains(&unset));
+        assert_eq!(result.output.read::<Variable>(), GoldilocksField::TWO);
+    }
+
+    #[test]
+    fn test_mock_circuit_debug_assertion() {
+        utils::setup_logger();
+
+        let mut builder = DefaultBuilder::new();
+        builder.set_debug();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let expected = builder.read::<Variable>();
+        let sum = builder.add(a, b);
+        builder.assert_is_equal_labeled(sum, expected, "sum of the inputs");
+        let mock_circuit = builder.mock_build();
+
+        let mut input = mock_circuit.input();
+        input.write::<Variable>(GoldilocksField::TWO);
+        input.write::<Variable>(GoldilocksField::TWO);
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(5));
+        let error = mock_circuit.try_mock_prove(&input).unwrap_err().to_string();
+        assert!(error.contains("\"sum of the inputs\""), "{}", error);
+        assert!(error.contains(file!()), "{}", error);
+        assert!(error.contains("5 != 4"), "{}", error);
+    }
 }
@@ -1,8 +1,14 @@
 mod build;
+mod cache;
+mod child;
 pub mod config;
+mod constraints;
+pub mod dump;
+mod fuzz;
 mod input;
 mod mock;
 mod output;
+mod progress;
 mod serialization;
 mod witness;
 
@@ -11,14 +17,23 @@ use core::fmt::Debug;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 
 pub use self::build::CircuitBuild;
+pub use self::cache::ProofCache;
+pub use self::child::{ChildCircuitData, VerifierData};
 pub use self::config::{DefaultParameters, Groth16WrapperParameters, PlonkParameters};
+pub(crate) use self::constraints::gate_counts;
+pub use self::dump::WitnessDump;
+pub use self::fuzz::{CircuitFuzzer, FuzzDivergence, FuzzReport};
 pub use self::input::PublicInput;
-pub use self::mock::MockCircuitBuild;
+pub use self::mock::{MockCircuitBuild, MockProveResult};
 pub use self::output::PublicOutput;
+pub use self::progress::{ProveOptions, ProvePhase, ProveProgress};
 pub use self::serialization::{
     CircuitSerializer, DefaultSerializer, GateRegistry, HintRegistry, Serializer,
 };
-pub use self::witness::{generate_witness, generate_witness_async};
+pub(crate) use self::witness::HINT_RUNTIME;
+pub use self::witness::{
+    generate_witness, generate_witness_async, generate_witness_with_runtime, WitnessConflict,
+};
 use crate::prelude::CircuitBuilder;
 
 pub trait Circuit: Debug + Clone + Send + Sync + 'static {
@@ -0,0 +1,79 @@
+use core::fmt::Debug;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use log::warn;
+use plonky2::plonk::circuit_data::{CommonCircuitData, ProverOnlyCircuitData};
+
+use super::PlonkParameters;
+
+/// A phase boundary reached while generating a proof.
+///
+/// The plonky2 prover does not expose its internal rounds (wire commitments, FRI commit phases and
+/// the final opening) while they run, so everything after witness generation is reported as a
+/// single phase. With the `timing` feature, the duration of each round is logged at the debug
+/// level once the proof is generated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProvePhase {
+    WitnessGenerationStarted { nb_generators: usize },
+    WitnessGenerationFinished { nb_generators: usize },
+    ProofGenerationStarted,
+    ProofGenerationFinished,
+}
+
+/// A progress event emitted while generating a proof.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProveProgress {
+    pub phase: ProvePhase,
+    /// An estimate of the fraction of the work done so far. It never decreases and is exactly
+    /// `1.0` once the proof is generated.
+    pub fraction: f64,
+}
+
+/// Options for `CircuitBuild::prove_with_options`.
+#[derive(Default)]
+pub struct ProveOptions {
+    progress: Option<Box<dyn FnMut(&ProveProgress)>>,
+}
+
+impl Debug for ProveOptions {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ProveOptions")
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+impl ProveOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a callback that is invoked on the caller's thread at every phase boundary. A panic in
+    /// the callback is caught and logged, and does not affect the proof.
+    pub fn with_progress(mut self, callback: impl FnMut(&ProveProgress) + 'static) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    pub(crate) fn take_progress(&mut self) -> Option<Box<dyn FnMut(&ProveProgress)>> {
+        self.progress.take()
+    }
+}
+
+/// Estimates the fraction of the proving time spent in witness generation, weighing the number of
+/// generators against the size of the trace that the prover commits to.
+pub(crate) fn witness_generation_weight<L: PlonkParameters<D>, const D: usize>(
+    prover_data: &ProverOnlyCircuitData<L::Field, L::Config, D>,
+    common_data: &CommonCircuitData<L::Field, D>,
+) -> f64 {
+    let witness_work = prover_data.generators.len() as f64;
+    let proof_work = (common_data.degree() * common_data.config.num_wires) as f64;
+    witness_work / (witness_work + proof_work)
+}
+
+/// Invokes the callback, catching and logging any panic.
+pub(crate) fn report_progress(callback: &mut dyn FnMut(&ProveProgress), progress: &ProveProgress) {
+    if catch_unwind(AssertUnwindSafe(|| callback(progress))).is_err() {
+        warn!("progress callback panicked on {:?}", progress.phase);
+    }
+}
@@ -38,20 +38,31 @@ use starkyx::plonky2::cubic::mul_gate::MulCubicGenerator;
 use super::registry::{SerializationRegistry, Serializer};
 use super::PlonkParameters;
 use crate as plonky2x;
+use crate::frontend::ecc::bls12_381::decompress::G1DecompressHint;
+use crate::frontend::ecc::bls12_381::field::{Fp2InvHint, FpInvHint};
+use crate::frontend::ecc::bls12_381::hash_to_curve::Fp2SqrtHint;
+use crate::frontend::ecc::bls12_381::tower::Fp12InvHint;
 use crate::frontend::ecc::curve25519::curta::proof_hint::EcOpProofHint;
 use crate::frontend::ecc::curve25519::curta::result_hint::EcOpResultHint;
+use crate::frontend::ecc::curve25519::decompress::Ed25519DecompressHint;
+use crate::frontend::ecc::p256::field::{P256Base, P256Scalar};
+use crate::frontend::ecc::secp256k1::curve::Secp256k1LiftXHint;
+use crate::frontend::ecc::secp256k1::ecdsa::EcrecoverHint;
+use crate::frontend::ecc::secp256k1::field::{NonNativeDivHint, Secp256k1Base, Secp256k1Scalar};
 use crate::frontend::eth::beacon::generators::{
     BeaconAllWithdrawalsHint, BeaconBalanceBatchWitnessHint, BeaconBalanceGenerator,
-    BeaconBalanceWitnessHint, BeaconBalancesGenerator, BeaconBlockRootsHint, BeaconGraffitiHint,
-    BeaconHeaderHint, BeaconHeadersFromOffsetRangeHint, BeaconHistoricalBlockHint,
-    BeaconPartialBalancesHint, BeaconPartialValidatorsHint, BeaconValidatorBatchHint,
-    BeaconValidatorGenerator, BeaconValidatorsGenerator, BeaconValidatorsHint,
-    BeaconWithdrawalGenerator, BeaconWithdrawalsGenerator, CompressedBeaconValidatorBatchHint,
+    BeaconBalanceWitnessHint, BeaconBalancesGenerator, BeaconBlockRootsHint,
+    BeaconExecutionPayloadProofHint, BeaconGraffitiHint, BeaconHeaderHint,
+    BeaconHeadersFromOffsetRangeHint, BeaconHistoricalBlockHint, BeaconPartialBalancesHint,
+    BeaconPartialValidatorsHint, BeaconValidatorBatchHint, BeaconValidatorGenerator,
+    BeaconValidatorsGenerator, BeaconValidatorsHint, BeaconWithdrawalGenerator,
+    BeaconWithdrawalsGenerator, CompressedBeaconValidatorBatchHint,
 };
 use crate::frontend::eth::beacon::vars::{
     BeaconBalancesVariable, BeaconHeaderVariable, BeaconValidatorVariable,
     BeaconValidatorsVariable, BeaconWithdrawalVariable, BeaconWithdrawalsVariable,
 };
+use crate::frontend::eth::blockhash::BlockHashHint;
 use crate::frontend::eth::storage::generators::{
     EthBlockGenerator, EthLogGenerator, EthStorageKeyGenerator, EthStorageProofHint,
 };
@@ -59,12 +70,14 @@ use crate::frontend::hash::curta::digest_hint::HashDigestHint;
 use crate::frontend::hash::curta::proof_hint::HashProofHint;
 use crate::frontend::hash::keccak::keccak256::Keccak256Generator;
 use crate::frontend::hash::poseidon::poseidon256::PoseidonHashOutVariable;
+use crate::frontend::hint::asynchronous::config::AsyncHintConfig;
 use crate::frontend::hint::asynchronous::generator::{AsyncHintDataRef, AsyncHintRef};
 use crate::frontend::hint::asynchronous::hint::AsyncHint;
 use crate::frontend::hint::asynchronous::serializer::AsyncHintSerializer;
 use crate::frontend::hint::simple::hint::Hint;
 use crate::frontend::hint::simple::serializer::SimpleHintSerializer;
 use crate::frontend::hint::synchronous::Async;
+use crate::frontend::recursion::proof::DummyProofHint;
 use crate::frontend::uint::num::biguint::BigUintDivRemGenerator;
 use crate::frontend::uint::num::u32::gates::add_many_u32::U32AddManyGenerator;
 use crate::frontend::uint::num::u32::gates::arithmetic_u32::U32ArithmeticGenerator;
@@ -72,7 +85,9 @@ use crate::frontend::uint::num::u32::gates::comparison::ComparisonGenerator;
 use crate::frontend::uint::num::u32::gates::range_check_u32::U32RangeCheckGenerator;
 use crate::frontend::uint::num::u32::gates::subtraction_u32::U32SubtractionGenerator;
 use crate::frontend::uint::uint64::U64Variable;
-use crate::frontend::vars::{Bytes32Variable, SubArrayExtractorHint, U256Variable};
+use crate::frontend::vars::{
+    Bytes32Variable, DivRemConstHint, SubArrayExtractorHint, U256Variable,
+};
 use crate::prelude::{ArrayVariable, BoolVariable, U32Variable, Variable};
 
 pub trait HintSerializer<L: PlonkParameters<D>, const D: usize>:
@@ -143,6 +158,10 @@ impl<L: PlonkParameters<D>, const D: usize> HintRegistry<L, D> {
     }
 
     /// Registers a hint into the registry.
+    ///
+    /// The hint itself is serialized with `bincode` alongside its generator, so hints carrying
+    /// state (e.g. an RPC URL or a chunk size) are restored with that state when the circuit is
+    /// deserialized.
     pub fn register_hint<H: Hint<L, D>>(&mut self) {
         let serializer = SimpleHintSerializer::<L, H>::new();
         let id = H::id();
@@ -151,7 +170,19 @@ impl<L: PlonkParameters<D>, const D: usize> HintRegistry<L, D> {
 
     /// Registers an asynchronous hint into the registry.
     pub fn register_async_hint<H: AsyncHint<L, D>>(&mut self) {
-        let serializer = AsyncHintSerializer::<L, H>::new();
+        self.register_async_hint_serializer(AsyncHintSerializer::<L, H>::new());
+    }
+
+    /// Registers an asynchronous hint into the registry, whose calls are run with `config` instead
+    /// of the config they were built with.
+    pub fn register_async_hint_with_config<H: AsyncHint<L, D>>(&mut self, config: AsyncHintConfig) {
+        self.register_async_hint_serializer(AsyncHintSerializer::<L, H>::with_config(config));
+    }
+
+    fn register_async_hint_serializer<H: AsyncHint<L, D>>(
+        &mut self,
+        serializer: AsyncHintSerializer<L, H>,
+    ) {
         let id = AsyncHintRef::<L, D>::id(H::id());
         self.generators
             .register(id.clone(), serializer.clone())
@@ -372,6 +403,7 @@ where
 
         r.register_async_hint::<BeaconAllWithdrawalsHint>();
         r.register_async_hint::<BeaconHeaderHint>();
+        r.register_async_hint::<BeaconExecutionPayloadProofHint>();
         r.register_async_hint::<BeaconHistoricalBlockHint>();
         r.register_async_hint::<EthStorageProofHint<L, D>>();
         r.register_async_hint::<BeaconValidatorsHint>();
@@ -422,9 +454,29 @@ where
         r.register_hint::<EcOpResultHint>();
         r.register_async_hint::<Async<EcOpResultHint>>();
 
+        r.register_hint::<Ed25519DecompressHint>();
+
+        r.register_hint::<NonNativeDivHint<Secp256k1Base>>();
+        r.register_hint::<NonNativeDivHint<Secp256k1Scalar>>();
+        r.register_hint::<Secp256k1LiftXHint>();
+        r.register_hint::<EcrecoverHint>();
+        r.register_hint::<NonNativeDivHint<P256Base>>();
+        r.register_hint::<NonNativeDivHint<P256Scalar>>();
+
+        r.register_hint::<FpInvHint>();
+        r.register_hint::<Fp2InvHint>();
+        r.register_hint::<Fp12InvHint>();
+        r.register_hint::<Fp2SqrtHint>();
+        r.register_hint::<G1DecompressHint>();
+
+        r.register_hint::<BlockHashHint>();
+
+        r.register_hint::<DivRemConstHint>();
+
         let dummy_proof_generator_id =
             DummyProofGenerator::<L::Field, L::Config, D>::default().id();
         r.register_simple::<DummyProofGenerator<L::Field, L::Config, D>>(dummy_proof_generator_id);
+        r.register_hint::<DummyProofHint<L, D>>();
 
         register_powers_of_two!(r, BeaconHeadersFromOffsetRangeHint);
 
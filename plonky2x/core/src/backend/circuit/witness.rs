@@ -7,56 +7,128 @@
 //! [1] : https://github.com/mir-protocol/plonky2/blob/main/plonky2/src/iop/generator.rs#L19
 
 use alloc::collections::BTreeMap;
+use core::fmt::{self, Debug, Display};
 use std::collections::HashSet;
 
 use anyhow::{anyhow, Error, Result};
+use lazy_static::lazy_static;
 use log::trace;
+use plonky2::field::types::Field;
 use plonky2::iop::generator::{GeneratedValues, WitnessGeneratorRef};
-use plonky2::iop::witness::{PartialWitness, PartitionWitness, WitnessWrite};
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartialWitness, PartitionWitness, Witness, WitnessWrite};
 use plonky2::plonk::circuit_data::{CommonCircuitData, ProverOnlyCircuitData};
-use starkyx::maybe_rayon::rayon;
+use tokio::runtime::{Handle, Runtime};
 use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::oneshot;
 
 use super::PlonkParameters;
+use crate::frontend::builder::debug::DebugAssertion;
 use crate::frontend::hint::asynchronous::generator::{AsyncHintDataRef, AsyncHintRef, HintPoll};
 use crate::frontend::hint::asynchronous::handler::HintHandler;
 
+lazy_static! {
+    /// The runtime running the async hints of proofs that are not given a runtime handle.
+    pub(crate) static ref HINT_RUNTIME: Runtime = Runtime::new().expect("Failed to create runtime");
+}
+
+/// An error of witness generation when a value is set for a target whose partition already has
+/// another value, i.e. when two variables asserted to be equal are not.
+#[derive(Debug, Clone)]
+pub struct WitnessConflict<F> {
+    pub target: Target,
+    pub existing: F,
+    pub new: F,
+}
+
+impl<F: Display> Display for WitnessConflict<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Partition containing {:?} was set twice with different values: {} != {}",
+            self.target, self.existing, self.new
+        )
+    }
+}
+
+impl<F: Debug + Display> std::error::Error for WitnessConflict<F> {}
+
+/// Adds the description of the failed debug assertion to a witness generation error, if the error
+/// is a conflict in the partition of the targets of one of `assertions`.
+pub(crate) fn explain_conflict<F: Field>(
+    error: Error,
+    assertions: &[DebugAssertion],
+    representative_map: &[usize],
+    num_wires: usize,
+    degree: usize,
+) -> Error {
+    let Some(conflict) = error.downcast_ref::<WitnessConflict<F>>() else {
+        return error;
+    };
+    let representative = |target: Target| representative_map[target.index(num_wires, degree)];
+
+    let conflict_representative = representative(conflict.target);
+    let assertion = assertions.iter().find(|assertion| {
+        assertion
+            .targets
+            .iter()
+            .any(|(t1, _)| representative(*t1) == conflict_representative)
+    });
+    match assertion {
+        Some(assertion) => {
+            let description = assertion.describe(conflict.existing, conflict.new);
+            error.context(description)
+        }
+        None => error,
+    }
+}
+
+/// Returns the virtual targets that `witness` leaves unset. The virtual targets come after the
+/// wires in the representative map.
+pub(crate) fn unset_targets<F: Field>(
+    witness: &PartitionWitness<F>,
+    representative_map: &[usize],
+    num_wires: usize,
+    degree: usize,
+) -> Vec<Target> {
+    let num_virtual_targets = representative_map.len() - num_wires * degree;
+    (0..num_virtual_targets)
+        .map(|index| Target::VirtualTarget { index })
+        .filter(|target| witness.try_get_target(*target).is_none())
+        .collect()
+}
+
 /// Given a `PartialWitness` that has only inputs set, populates the rest of the witness using the
 /// given set of generators.
+///
+/// Async hints are run on a runtime managed by the crate.
 pub fn generate_witness<'a, L: PlonkParameters<D>, const D: usize>(
     inputs: PartialWitness<L::Field>,
     prover_data: &'a ProverOnlyCircuitData<L::Field, L::Config, D>,
     common_data: &'a CommonCircuitData<L::Field, D>,
     async_generator_refs: &'a BTreeMap<usize, AsyncHintDataRef<L, D>>,
 ) -> Result<PartitionWitness<'a, L::Field>> {
-    // If async hints are present, set up the a handler and initialize the generators with the
-    // handler's communication channel.
-    let (tx_handler_error, rx_handler_error) = oneshot::channel();
-    let async_generators = match async_generator_refs.is_empty() {
-        true => BTreeMap::new(),
-        false => {
-            let (tx, rx) = unbounded_channel();
-            // Initialize the hint handler.
-            let mut hint_handler = HintHandler::<L, D>::new(rx);
-
-            // Spawn a runtime and run the hint handler.
-            let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
-            rayon::spawn(move || {
-                let result = rt.block_on(hint_handler.run());
-                if let Err(e) = result {
-                    tx_handler_error.send(e).unwrap();
-                }
-            });
-
-            BTreeMap::from_iter(
-                async_generator_refs
-                    .iter()
-                    .map(|(i, g)| (*i, g.0.generator(tx.clone()))),
-            )
-        }
-    };
+    generate_witness_with_runtime(
+        inputs,
+        prover_data,
+        common_data,
+        async_generator_refs,
+        HINT_RUNTIME.handle(),
+    )
+}
 
+/// Same as `generate_witness`, but runs the async hints on the runtime of `handle`.
+///
+/// Witness generation blocks the calling thread, so the runtime must be able to make progress
+/// without it, i.e. be a multi-threaded runtime if it is called from one of its workers.
+pub fn generate_witness_with_runtime<'a, L: PlonkParameters<D>, const D: usize>(
+    inputs: PartialWitness<L::Field>,
+    prover_data: &'a ProverOnlyCircuitData<L::Field, L::Config, D>,
+    common_data: &'a CommonCircuitData<L::Field, D>,
+    async_generator_refs: &'a BTreeMap<usize, AsyncHintDataRef<L, D>>,
+    handle: &Handle,
+) -> Result<PartitionWitness<'a, L::Field>> {
+    let (async_generators, rx_handler_error) = spawn_hint_handler(async_generator_refs, handle);
     fill_witness_values::<L, D>(
         inputs,
         prover_data,
@@ -72,32 +144,8 @@ pub async fn generate_witness_async<'a, L: PlonkParameters<D>, const D: usize>(
     common_data: &'a CommonCircuitData<L::Field, D>,
     async_generator_refs: &'a BTreeMap<usize, AsyncHintDataRef<L, D>>,
 ) -> Result<PartitionWitness<'a, L::Field>> {
-    // If async hints are present, set up the a handler and initialize the generators with the
-    // handler's communication channel.
-    let (tx_handler_error, rx_handler_error) = oneshot::channel();
-    let async_generators = match async_generator_refs.is_empty() {
-        true => BTreeMap::new(),
-        false => {
-            let (tx, rx) = unbounded_channel();
-            // Initialize the hint handler.
-            let mut hint_handler = HintHandler::<L, D>::new(rx);
-
-            // Spawn a runtime and run the hint handler.
-            tokio::spawn(async move {
-                let result = hint_handler.run().await;
-                if let Err(e) = result {
-                    tx_handler_error.send(e).unwrap();
-                }
-            });
-
-            BTreeMap::from_iter(
-                async_generator_refs
-                    .iter()
-                    .map(|(i, g)| (*i, g.0.generator(tx.clone()))),
-            )
-        }
-    };
-
+    let (async_generators, rx_handler_error) =
+        spawn_hint_handler(async_generator_refs, &Handle::current());
     tokio::task::block_in_place(move || {
         fill_witness_values::<L, D>(
             inputs,
@@ -109,6 +157,38 @@ pub async fn generate_witness_async<'a, L: PlonkParameters<D>, const D: usize>(
     })
 }
 
+/// If async hints are present, spawns a hint handler on the runtime of `handle` and initializes the
+/// generators with the handler's communication channel. The receiver gets the error of the handler
+/// if it fails.
+#[allow(clippy::type_complexity)]
+fn spawn_hint_handler<L: PlonkParameters<D>, const D: usize>(
+    async_generator_refs: &BTreeMap<usize, AsyncHintDataRef<L, D>>,
+    handle: &Handle,
+) -> (
+    BTreeMap<usize, AsyncHintRef<L, D>>,
+    oneshot::Receiver<Error>,
+) {
+    let (tx_handler_error, rx_handler_error) = oneshot::channel();
+    if async_generator_refs.is_empty() {
+        return (BTreeMap::new(), rx_handler_error);
+    }
+
+    let (tx, rx) = unbounded_channel();
+    let mut hint_handler = HintHandler::<L, D>::new(rx);
+    handle.spawn(async move {
+        let result = hint_handler.run().await;
+        if let Err(e) = result {
+            tx_handler_error.send(e).unwrap();
+        }
+    });
+
+    let async_generators = async_generator_refs
+        .iter()
+        .map(|(i, g)| (*i, g.0.generator(tx.clone())))
+        .collect();
+    (async_generators, rx_handler_error)
+}
+
 /// Fill in the witness after intiializing async generators.
 fn fill_witness_values<'a, L: PlonkParameters<D>, const D: usize>(
     inputs: PartialWitness<L::Field>,
@@ -175,10 +255,20 @@ fn fill_witness_values<'a, L: PlonkParameters<D>, const D: usize>(
 
             // Merge any generated values into our witness, and get a list of newly-populated
             // targets' representatives.
-            let new_target_reps = buffer
-                .target_values
-                .drain(..)
-                .flat_map(|(t, v)| witness.set_target_returning_rep(t, v));
+            let mut new_target_reps = Vec::new();
+            for (target, value) in buffer.target_values.drain(..) {
+                if let Some(existing) = witness.try_get_target(target) {
+                    if existing != value {
+                        return Err(WitnessConflict {
+                            target,
+                            existing,
+                            new: value,
+                        }
+                        .into());
+                    }
+                }
+                new_target_reps.extend(witness.set_target_returning_rep(target, value));
+            }
 
             // Enqueue unfinished generators that were watching one of the newly populated targets.
             for watch in new_target_reps {
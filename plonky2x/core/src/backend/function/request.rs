@@ -76,10 +76,22 @@ pub enum ProofRequest<L: PlonkParameters<D>, const D: usize> {
 }
 
 impl<L: PlonkParameters<D>, const D: usize> ProofRequest<L, D> {
-    /// Creates a new function request from a circuit and public input.
+    /// Creates a new function request from a circuit and public input, for the release and the
+    /// parent proof in the `RELEASE_ID` and `PROOF_ID` environment variables.
     pub fn new(circuit_id: &str, input: &PublicInput<L, D>) -> Self {
         let release_id = env::var("RELEASE_ID").unwrap();
         let parent_id = Some(env::var("PROOF_ID").unwrap());
+        Self::with_ids(circuit_id, input, release_id, parent_id)
+    }
+
+    /// Creates a new function request from a circuit and public input, for the given release and
+    /// parent proof.
+    pub fn with_ids(
+        circuit_id: &str,
+        input: &PublicInput<L, D>,
+        release_id: String,
+        parent_id: Option<String>,
+    ) -> Self {
         match input {
             PublicInput::Bytes(input) => ProofRequest::Bytes(ProofRequestBase {
                 release_id,
@@ -2,10 +2,13 @@ use std::env;
 
 use anyhow::Result;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::proof::ProofWithPublicInputs;
 
 use super::local::LocalProver;
-use super::{ProverOutput, ProverOutputs, RemoteProver};
-use crate::backend::circuit::{CircuitBuild, CircuitSerializer, PlonkParameters, PublicInput};
+use super::{Prover, ProverOutput, ProverOutputs, RemoteProver};
+use crate::backend::circuit::{
+    CircuitBuild, CircuitSerializer, PlonkParameters, PublicInput, PublicOutput,
+};
 
 /// A prover that can generate proofs locally or remotely based on the env variable `PROVER` which
 /// can either be `remote` or `local`.
@@ -63,3 +66,24 @@ impl EnvProver {
         }
     }
 }
+
+impl<L: PlonkParameters<D>, const D: usize> Prover<L, D> for EnvProver
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    fn prove(
+        &self,
+        circuit: &CircuitBuild<L, D>,
+        input: &PublicInput<L, D>,
+    ) -> Result<(
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )> {
+        if env::var("PROVER").unwrap_or("local".to_string()) == "remote" {
+            Prover::prove(&RemoteProver::new(), circuit, input)
+        } else {
+            Prover::prove(&LocalProver::new(), circuit, input)
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use anyhow::Result;
 use log::debug;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::proof::ProofWithPublicInputs;
 
-use super::{ProverOutput, ProverOutputs};
-use crate::backend::circuit::{CircuitBuild, PlonkParameters, PublicInput};
+use super::{Prover, ProverOutput, ProverOutputs};
+use crate::backend::circuit::{CircuitBuild, PlonkParameters, PublicInput, PublicOutput};
 
 /// A prover that generates proofs locally.
 #[derive(Debug, Clone)]
@@ -56,3 +57,20 @@ impl LocalProver {
         Ok(ProverOutputs::Local(proofs, outputs))
     }
 }
+
+impl<L: PlonkParameters<D>, const D: usize> Prover<L, D> for LocalProver
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    fn prove(
+        &self,
+        circuit: &CircuitBuild<L, D>,
+        input: &PublicInput<L, D>,
+    ) -> Result<(
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )> {
+        Ok(circuit.prove(input))
+    }
+}
@@ -6,11 +6,30 @@ mod service;
 use anyhow::Result;
 pub use env::EnvProver;
 pub use local::LocalProver;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 use plonky2::plonk::proof::ProofWithPublicInputs;
-pub use remote::RemoteProver;
+pub use remote::{PollConfig, RemoteProver};
 pub use service::{BatchProofId, ProofId, ProofService};
 
-use super::circuit::{PlonkParameters, PublicOutput};
+use super::circuit::{CircuitBuild, PlonkParameters, PublicInput, PublicOutput};
+
+/// A backend that generates proofs for a circuit, regardless of where the proving happens.
+pub trait Prover<L: PlonkParameters<D>, const D: usize>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    /// Generates a proof for the circuit with the given input.
+    #[allow(clippy::type_complexity)]
+    fn prove(
+        &self,
+        circuit: &CircuitBuild<L, D>,
+        input: &PublicInput<L, D>,
+    ) -> Result<(
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )>;
+}
 
 #[allow(clippy::large_enum_variant)]
 pub enum ProverOutput<L: PlonkParameters<D>, const D: usize> {
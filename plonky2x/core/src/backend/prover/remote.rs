@@ -1,25 +1,85 @@
 use core::time::Duration;
 use std::env;
 use std::net::ToSocketAddrs;
+use std::time::Instant;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, ensure, Result};
 use itertools::Itertools;
 use log::debug;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::proof::ProofWithPublicInputs;
 use rand::Rng;
 use reqwest::Client;
+use tokio::runtime::Handle;
 use tokio::time::sleep;
 
-use super::ProverOutput;
-use crate::backend::circuit::{PlonkParameters, PublicInput};
+use super::{Prover, ProverOutput};
+use crate::backend::circuit::{
+    CircuitBuild, PlonkParameters, PublicInput, PublicOutput, HINT_RUNTIME,
+};
 use crate::backend::function::ProofRequest;
 use crate::backend::prover::service::{ProofRequestStatus, ProofService};
 use crate::backend::prover::ProverOutputs;
 
+/// Controls how a `RemoteProver` waits for the proof service to finish a proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollConfig {
+    /// How long to wait for the proof before giving up.
+    pub timeout: Duration,
+    /// The delay before the first poll.
+    pub initial_interval: Duration,
+    /// The upper bound on the delay between two polls.
+    pub max_interval: Duration,
+    /// The factor by which the delay grows after every poll. It must be at least 1, as a factor of
+    /// 0 would poll the service without any delay.
+    pub backoff_factor: u32,
+    /// The upper bound on the random delay before submitting, which spreads out the requests of
+    /// concurrent provers.
+    pub submit_jitter: Duration,
+}
+
+impl Default for PollConfig {
+    fn default() -> Self {
+        // Default timeout for a proof is 60 minutes. Users can override this value by
+        // setting the PROOF_TIMEOUT_SECS environment variable.
+        const DEFAULT_PROOF_TIMEOUT_SECS: u64 = 60 * 60;
+        let proof_timeout_secs = env::var("PROOF_TIMEOUT_SECS")
+            .unwrap_or(DEFAULT_PROOF_TIMEOUT_SECS.to_string())
+            .parse::<u64>()
+            .unwrap();
+        Self {
+            timeout: Duration::from_secs(proof_timeout_secs),
+            initial_interval: Duration::from_secs(10),
+            max_interval: Duration::from_secs(10),
+            backoff_factor: 1,
+            submit_jitter: Duration::from_secs(5),
+        }
+    }
+}
+
+impl PollConfig {
+    /// Returns an error if the config is invalid, i.e. if `backoff_factor` is below 1.
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.backoff_factor >= 1,
+            "backoff_factor must be at least 1, got {}",
+            self.backoff_factor
+        );
+        Ok(())
+    }
+}
+
 /// A prover that generates proofs remotely on another machine.
 #[derive(Debug, Clone, Default)]
 pub struct RemoteProver {
     pub client: Client,
+    pub poll_config: PollConfig,
+    /// The url of the proof service. If unset, `PROOF_SERVICE_URL` is used.
+    pub service_url: Option<String>,
+    /// The release of the requests. If unset, `RELEASE_ID` is used.
+    pub release_id: Option<String>,
+    /// The proof that the requests are made for. If unset, `PROOF_ID` is used.
+    pub parent_id: Option<String>,
 }
 
 impl RemoteProver {
@@ -35,6 +95,54 @@ impl RemoteProver {
                 .resolve_to_addrs(host, &sock_addrs)
                 .build()
                 .unwrap(),
+            poll_config: PollConfig::default(),
+            service_url: None,
+            release_id: None,
+            parent_id: None,
+        }
+    }
+
+    pub fn with_poll_config(mut self, poll_config: PollConfig) -> Self {
+        poll_config.validate().unwrap();
+        self.poll_config = poll_config;
+        self
+    }
+
+    pub fn with_service_url(mut self, service_url: impl Into<String>) -> Self {
+        self.service_url = Some(service_url.into());
+        self
+    }
+
+    pub fn with_release_id(mut self, release_id: impl Into<String>) -> Self {
+        self.release_id = Some(release_id.into());
+        self
+    }
+
+    pub fn with_parent_id(mut self, parent_id: impl Into<String>) -> Self {
+        self.parent_id = Some(parent_id.into());
+        self
+    }
+
+    fn request<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        circuit_id: &str,
+        input: &PublicInput<L, D>,
+    ) -> ProofRequest<L, D> {
+        let release_id = self
+            .release_id
+            .clone()
+            .unwrap_or_else(|| env::var("RELEASE_ID").unwrap());
+        let parent_id = self
+            .parent_id
+            .clone()
+            .unwrap_or_else(|| env::var("PROOF_ID").unwrap());
+        ProofRequest::with_ids(circuit_id, input, release_id, Some(parent_id))
+    }
+
+    fn service(&self) -> ProofService {
+        match &self.service_url {
+            Some(url) => ProofService::new(url.clone()),
+            None => ProofService::new_from_env(),
         }
     }
 
@@ -50,38 +158,33 @@ impl RemoteProver {
         debug!("prove: circuit_id={}", circuit_id);
 
         // Initialize the proof service.
-        let service = ProofService::new_from_env();
+        let config = self.poll_config;
+        config.validate()?;
+        let service = self.service();
 
         // Submit the proof request.
-        let mut rng = rand::thread_rng();
-        let sleep_time = rng.gen_range(0..=5000);
+        let jitter_millis = config.submit_jitter.as_millis() as u64;
+        let sleep_time = rand::thread_rng().gen_range(0..=jitter_millis);
         sleep(Duration::from_millis(sleep_time)).await;
-        let request = ProofRequest::new(circuit_id, input);
+        let request = self.request(circuit_id, input);
         let proof_id = service
             .submit::<L, D>(request)
             .expect("failed to submit proof request");
 
-        // Default timeout for a proof is 60 minutes. Users can override this value by
-        // setting the PROOF_TIMEOUT_SECS environment variable.
-        const DEFAULT_PROOF_TIMEOUT_SECS: u64 = 60 * 60;
-        let proof_timeout_secs = env::var("PROOF_TIMEOUT_SECS")
-            .unwrap_or(DEFAULT_PROOF_TIMEOUT_SECS.to_string())
-            .parse::<u64>()
-            .unwrap();
-        let poll_secs = 10;
-        // Maximum number of polls for proof status before timeout.
-        let max_polls = proof_timeout_secs / poll_secs;
-
+        let start_time = Instant::now();
+        let mut interval = config.initial_interval;
+        let mut nb_polls = 0;
         let mut status = ProofRequestStatus::Pending;
-        for i in 0..max_polls {
-            sleep(Duration::from_secs(poll_secs)).await;
+        while start_time.elapsed() < config.timeout {
+            sleep(interval).await;
+            nb_polls += 1;
             let request = service.get::<L, D>(proof_id)?;
             debug!(
-                "proof {:?}: status={:?}, nb_polls={}/{}",
+                "proof {:?}: status={:?}, nb_polls={}, elapsed={:?}",
                 proof_id,
                 request.status,
-                i + 1,
-                max_polls,
+                nb_polls,
+                start_time.elapsed(),
             );
 
             status = request.status;
@@ -94,6 +197,7 @@ impl RemoteProver {
                 }
                 _ => break,
             };
+            interval = (interval * config.backoff_factor).min(config.max_interval);
         }
 
         // Return an error if the proof failed to generate.
@@ -114,12 +218,12 @@ impl RemoteProver {
             AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
     {
         // Initialize the proof service.
-        let service = ProofService::new_from_env();
+        let service = self.service();
 
         // Submit the batch proof request.
         let requests = inputs
             .iter()
-            .map(|input| ProofRequest::new(circuit_id, input))
+            .map(|input| self.request(circuit_id, input))
             .collect_vec();
         let (batch_id, proof_ids) = service.submit_batch(&requests)?;
 
@@ -178,3 +282,182 @@ impl RemoteProver {
         Err(anyhow!("could not generate proof {:?}", batch_id,))
     }
 }
+
+impl<L: PlonkParameters<D>, const D: usize> Prover<L, D> for RemoteProver
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+        AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+{
+    fn prove(
+        &self,
+        circuit: &CircuitBuild<L, D>,
+        input: &PublicInput<L, D>,
+    ) -> Result<(
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        PublicOutput<L, D>,
+    )> {
+        let circuit_id = circuit.id();
+        let future = RemoteProver::prove(self, &circuit_id, input);
+        // Blocking on the future from a worker of the caller's runtime must let the runtime move
+        // its other tasks away, so it must be multi-threaded, as for `prove_with_executor`.
+        let output = match Handle::try_current() {
+            Ok(handle) => tokio::task::block_in_place(|| handle.block_on(future)),
+            Err(_) => HINT_RUNTIME.block_on(future),
+        }?;
+        output.materialize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::backend::function::ProofResult;
+    use crate::prelude::*;
+    use crate::utils;
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    /// Serves the proof service routes for a single proof request on a local port. The proof is
+    /// reported as running on the first poll and as done with `result` afterwards.
+    fn spawn_mock_service(result: Value) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let proof_id = Uuid::new_v4();
+
+        thread::spawn(move || {
+            let mut nb_polls = 0;
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header == "\r\n" {
+                        break;
+                    }
+                    if let Some(len) = header.to_lowercase().strip_prefix("content-length:") {
+                        content_length = len.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0u8; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let response = if request_line.starts_with("POST /api/proof/new") {
+                    json!({ "proof_id": proof_id })
+                } else {
+                    nb_polls += 1;
+                    match nb_polls {
+                        1 => json!({ "id": proof_id, "status": "running", "result": null }),
+                        _ => json!({ "id": proof_id, "status": "success", "result": result }),
+                    }
+                };
+                let response = response.to_string();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                )
+                .unwrap();
+            }
+        });
+
+        url
+    }
+
+    #[test]
+    fn test_remote_prover_with_mock_service() {
+        utils::setup_logger();
+
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let c = builder.add(a, b);
+        builder.write(c);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::ONE);
+        input.write::<Variable>(GoldilocksField::TWO);
+
+        // The mock service answers with a proof generated locally.
+        let (proof, output) = circuit.prove(&input);
+        let result = ProofResult::<L, D>::from_proof_output(proof, output);
+        let url = spawn_mock_service(serde_json::to_value(result).unwrap());
+
+        let prover = RemoteProver::default()
+            .with_service_url(url)
+            .with_release_id("test")
+            .with_parent_id(Uuid::new_v4().to_string())
+            .with_poll_config(PollConfig {
+                timeout: Duration::from_secs(10),
+                initial_interval: Duration::from_millis(10),
+                max_interval: Duration::from_millis(100),
+                backoff_factor: 2,
+                submit_jitter: Duration::ZERO,
+            });
+
+        let (proof, mut output) = Prover::prove(&prover, &circuit, &input).unwrap();
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(
+            output.read::<Variable>(),
+            GoldilocksField::from_canonical_u64(3)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_remote_prover_in_runtime() {
+        utils::setup_logger();
+
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        builder.write(a);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::TWO);
+
+        let (proof, output) = tokio::task::block_in_place(|| circuit.prove(&input));
+        let result = ProofResult::<L, D>::from_proof_output(proof, output);
+        let url = spawn_mock_service(serde_json::to_value(result).unwrap());
+
+        let prover = RemoteProver::default()
+            .with_service_url(url)
+            .with_release_id("test")
+            .with_parent_id(Uuid::new_v4().to_string())
+            .with_poll_config(PollConfig {
+                timeout: Duration::from_secs(10),
+                initial_interval: Duration::from_millis(10),
+                max_interval: Duration::from_millis(100),
+                backoff_factor: 1,
+                submit_jitter: Duration::ZERO,
+            });
+
+        // The blocking prover is called from a worker of the test runtime.
+        let (proof, mut output) = Prover::prove(&prover, &circuit, &input).unwrap();
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<Variable>(), GoldilocksField::TWO);
+    }
+
+    #[test]
+    fn test_poll_config_rejects_zero_backoff() {
+        let config = PollConfig {
+            backoff_factor: 0,
+            ..PollConfig::default()
+        };
+        assert!(config.validate().is_err());
+        assert!(PollConfig::default().validate().is_ok());
+    }
+}
@@ -92,15 +92,13 @@ impl ProofService {
     /// Creates a new instance of the function service client.
     pub fn new(url: String) -> Self {
         let host = url.split("://").last().unwrap();
-        let sock_addrs = format!("{}:443", host)
-            .to_socket_addrs()
-            .unwrap()
-            .collect::<Vec<_>>();
+        let mut builder = Client::builder();
+        // Urls with an explicit port (e.g. a local service) are left to the default resolver.
+        if let Ok(sock_addrs) = format!("{}:443", host).to_socket_addrs() {
+            builder = builder.resolve_to_addrs(host, &sock_addrs.collect::<Vec<_>>());
+        }
         Self {
-            client: Client::builder()
-                .resolve_to_addrs(host, &sock_addrs)
-                .build()
-                .unwrap(),
+            client: builder.build().unwrap(),
             base_url: url,
         }
     }
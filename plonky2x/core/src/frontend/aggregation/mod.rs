@@ -0,0 +1,251 @@
+//! Aggregation of many proofs of the same circuit into a single proof.
+//!
+//! The proofs are the leaves of a tree of a fixed arity. Each node of the tree is a proof of a node
+//! circuit, which verifies `arity` child proofs and commits to them with a Poseidon hash of their
+//! digests. A leaf digest is the hash of the public inputs of the leaf proof, and a node digest is
+//! its commitment, so the root proof has a single public input: a commitment to the public inputs
+//! of all the leaves, in order.
+//!
+//! When the number of proofs at a level is not a multiple of the arity, the last node is padded
+//! with dummy proofs, whose digests are zero. This is equivalent to padding the leaves to the next
+//! power of the arity, where a subtree of padding has a zero digest.
+
+pub mod standardize;
+
+use itertools::Itertools;
+use plonky2::hash::hash_types::HashOut;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use starkyx::maybe_rayon::*;
+
+use crate::backend::circuit::CircuitBuild;
+use crate::frontend::hash::poseidon::poseidon256::PoseidonHashOutVariable;
+use crate::prelude::{BoolVariable, CircuitBuilder, CircuitVariable, Field, PlonkParameters};
+
+/// Builds the node circuits of an aggregation tree over proofs of a leaf circuit, and proves the
+/// tree level by level.
+///
+/// The node circuits of each level verify proofs of the level below, so the circuit of the root
+/// depends on the number of leaves. The circuits are built the first time a level is needed.
+#[derive(Debug)]
+pub struct AggregationTreeBuilder<'a, L: PlonkParameters<D>, const D: usize> {
+    leaf_circuit: &'a CircuitBuild<L, D>,
+    arity: usize,
+    node_circuits: Vec<CircuitBuild<L, D>>,
+}
+
+impl<'a, L: PlonkParameters<D>, const D: usize> AggregationTreeBuilder<'a, L, D>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+{
+    /// Creates a tree over proofs of `leaf_circuit`, building the node circuit that verifies
+    /// `arity` leaf proofs.
+    pub fn new(leaf_circuit: &'a CircuitBuild<L, D>, arity: usize) -> Self {
+        assert!(arity >= 2, "arity must be at least 2");
+        let node_circuit = Self::build_node(leaf_circuit, arity, true);
+        Self {
+            leaf_circuit,
+            arity,
+            node_circuits: vec![node_circuit],
+        }
+    }
+
+    /// The circuit of the aggregated proofs.
+    pub fn leaf_circuit(&self) -> &CircuitBuild<L, D> {
+        self.leaf_circuit
+    }
+
+    /// The number of child proofs verified by each node.
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// Returns the number of levels of nodes in a tree over `num_leaves` leaves. There is always
+    /// at least one, so that the root is a proof of a node circuit even for a single leaf.
+    pub fn depth(&self, num_leaves: usize) -> usize {
+        assert!(num_leaves > 0, "cannot aggregate zero proofs");
+        let mut depth = 1;
+        let mut capacity = self.arity;
+        while capacity < num_leaves {
+            capacity *= self.arity;
+            depth += 1;
+        }
+        depth
+    }
+
+    /// Returns the node circuit at `level`, building it and the levels below if needed. The nodes
+    /// at level 0 verify the leaves, and the root of a tree over `n` leaves is at level
+    /// `depth(n) - 1`.
+    pub fn node_circuit(&mut self, level: usize) -> &CircuitBuild<L, D> {
+        while self.node_circuits.len() <= level {
+            let child_circuit = self.node_circuits.last().unwrap();
+            let node_circuit = Self::build_node(child_circuit, self.arity, false);
+            self.node_circuits.push(node_circuit);
+        }
+        &self.node_circuits[level]
+    }
+
+    /// Aggregates proofs of the leaf circuit into a proof of the root node circuit, whose public
+    /// inputs are the commitment returned by `commitment`. The nodes of each level are proven in
+    /// parallel if the `parallel` feature is enabled.
+    pub fn aggregate(
+        &mut self,
+        proofs: Vec<ProofWithPublicInputs<L::Field, L::Config, D>>,
+    ) -> ProofWithPublicInputs<L::Field, L::Config, D> {
+        let depth = self.depth(proofs.len());
+        self.node_circuit(depth - 1);
+
+        let arity = self.arity;
+        let mut proofs = proofs;
+        for node_circuit in &self.node_circuits[..depth] {
+            proofs = proofs
+                .par_chunks(arity)
+                .map(|children| Self::prove_node(node_circuit, arity, children))
+                .collect();
+        }
+        assert_eq!(proofs.len(), 1);
+        proofs.pop().unwrap()
+    }
+
+    /// Computes the commitment of the root of a tree over leaves with the given public inputs,
+    /// outside of the circuit.
+    pub fn commitment(&self, leaf_public_inputs: &[Vec<L::Field>]) -> HashOut<L::Field> {
+        let depth = self.depth(leaf_public_inputs.len());
+        let mut digests = leaf_public_inputs
+            .iter()
+            .map(|public_inputs| Self::hash(public_inputs))
+            .collect_vec();
+        for _ in 0..depth {
+            digests = digests
+                .chunks(self.arity)
+                .map(|children| {
+                    let mut elements = children.iter().flat_map(|d| d.elements).collect_vec();
+                    elements.resize(
+                        self.arity * PoseidonHashOutVariable::nb_elements(),
+                        L::Field::ZERO,
+                    );
+                    Self::hash(&elements)
+                })
+                .collect();
+        }
+        digests[0]
+    }
+
+    fn hash(elements: &[L::Field]) -> HashOut<L::Field> {
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher::hash_no_pad(elements)
+    }
+
+    /// Builds a node circuit that verifies `arity` proofs of `child_circuit`, which are either
+    /// leaves or nodes of the level below. The proofs are followed by a flag for each of them that
+    /// is false for padding.
+    fn build_node(
+        child_circuit: &CircuitBuild<L, D>,
+        arity: usize,
+        is_leaf_level: bool,
+    ) -> CircuitBuild<L, D> {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let common_data = &child_circuit.data.common;
+        let verifier_data = builder.constant_verifier_data::<L>(&child_circuit.data);
+        let proofs = (0..arity)
+            .map(|_| builder.proof_read(common_data))
+            .collect_vec();
+        let is_real = (0..arity)
+            .map(|_| builder.read::<BoolVariable>())
+            .collect_vec();
+
+        let zero = builder.constant::<PoseidonHashOutVariable>(HashOut::ZERO);
+        let mut digests = Vec::new();
+        for (proof, is_real) in proofs.iter().zip_eq(is_real) {
+            // A dummy proof is verified in place of padding, and its public inputs are zero.
+            let public_inputs =
+                builder.conditionally_verify_proof(is_real, proof, &verifier_data, common_data);
+            let digest = if is_leaf_level {
+                let digest = builder.poseidon_hash(&public_inputs);
+                builder.select(is_real, digest, zero.clone())
+            } else {
+                PoseidonHashOutVariable::from_variables_unsafe(&public_inputs)
+            };
+            digests.push(digest);
+        }
+
+        let elements = digests.iter().flat_map(|d| d.variables()).collect_vec();
+        let commitment = builder.poseidon_hash(&elements);
+        builder.proof_write(commitment);
+        builder.build()
+    }
+
+    /// Proves a node over `children`, padding them to `arity` proofs.
+    fn prove_node(
+        node_circuit: &CircuitBuild<L, D>,
+        arity: usize,
+        children: &[ProofWithPublicInputs<L::Field, L::Config, D>],
+    ) -> ProofWithPublicInputs<L::Field, L::Config, D> {
+        let mut input = node_circuit.input();
+        for i in 0..arity {
+            // The padding only needs the shape of a child proof, as the circuit replaces it with a
+            // dummy proof.
+            let proof = children.get(i).unwrap_or(&children[0]);
+            input.proof_write(proof.clone());
+        }
+        for i in 0..arity {
+            input.write::<BoolVariable>(i < children.len());
+        }
+        let (proof, _) = node_circuit.prove(&input);
+        proof
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::hash::poseidon::PoseidonHash;
+
+    use super::*;
+    use crate::prelude::{DefaultBuilder, GoldilocksField, Variable};
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_aggregate() {
+        let mut builder = DefaultBuilder::new();
+        let x = builder.read::<Variable>();
+        let y = builder.mul(x, x);
+        builder.write(y);
+        let leaf_circuit = builder.build();
+
+        let mut leaf_proofs = Vec::new();
+        for i in 0..5 {
+            let mut input = leaf_circuit.input();
+            input.write::<Variable>(GoldilocksField::from_canonical_u64(i));
+            let (proof, _) = leaf_circuit.prove(&input);
+            leaf_proofs.push(proof);
+        }
+        let leaf_public_inputs = leaf_proofs
+            .iter()
+            .map(|proof| proof.public_inputs.clone())
+            .collect_vec();
+
+        let mut tree = AggregationTreeBuilder::new(&leaf_circuit, 2);
+        assert_eq!(tree.depth(5), 3);
+        let commitment = tree.commitment(&leaf_public_inputs);
+        let root = tree.aggregate(leaf_proofs);
+        let root_circuit = tree.node_circuit(2);
+        root_circuit.data.verify(root.clone()).unwrap();
+        assert_eq!(root.public_inputs, commitment.elements.to_vec());
+
+        // The 5 leaves are padded to 8, and subtrees of padding have a zero digest.
+        let hash = |elements: Vec<GoldilocksField>| PoseidonHash::hash_no_pad(&elements);
+        let pair = |left: HashOut<GoldilocksField>, right: HashOut<GoldilocksField>| {
+            hash([left.elements, right.elements].concat())
+        };
+        let leaves = leaf_public_inputs.into_iter().map(hash).collect_vec();
+        let level_0 = [
+            pair(leaves[0], leaves[1]),
+            pair(leaves[2], leaves[3]),
+            pair(leaves[4], HashOut::ZERO),
+        ];
+        let level_1 = [
+            pair(level_0[0], level_0[1]),
+            pair(level_0[2], HashOut::ZERO),
+        ];
+        assert_eq!(commitment, pair(level_1[0], level_1[1]));
+    }
+}
@@ -0,0 +1,317 @@
+//! Standardization of circuits with different shapes to a common one.
+//!
+//! A recursive circuit verifies proofs of a single `CommonCircuitData`, so proofs of circuits with
+//! different numbers of public inputs or degrees cannot be verified by the same node circuit. A
+//! standardized circuit is an adapter that verifies a proof of the original circuit and exposes its
+//! public inputs, padded to a target count, in a circuit padded to a target degree. Adapters of the
+//! same target shape have the same common data.
+//!
+//! A recursive circuit that verifies proofs of different adapters must not take their verifier data
+//! as a free witness, or it would accept a proof of any circuit of the same shape. Instead, the
+//! verifier data is selected from an allowlist of adapters with `select_verifier_data`.
+
+use itertools::Itertools;
+use plonky2::gates::noop::NoopGate;
+use plonky2::hash::hash_types::{HashOutTarget, MerkleCapTarget, NUM_HASH_OUT_ELTS};
+use plonky2::plonk::circuit_data::VerifierCircuitTarget;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+
+use crate::backend::circuit::CircuitBuild;
+use crate::frontend::fold::util::common_data_for_recursion;
+use crate::frontend::hash::poseidon::poseidon256::PoseidonHashOutVariable;
+use crate::prelude::{CircuitBuilder, CircuitVariable, PlonkParameters, Variable};
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuild<L, D>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+{
+    /// Wraps the circuit in an adapter circuit with `target_num_public_inputs` public inputs and
+    /// `2^target_degree_bits` gates, whose input is a proof of this circuit written with
+    /// `proof_write`.
+    ///
+    /// The public inputs of the adapter are those of the original proof followed by zeros. If the
+    /// original circuit has more than `target_num_public_inputs` public inputs, they are replaced
+    /// by their Poseidon hash instead. Adapters of the same target shape share their common data,
+    /// so their proofs can be verified by the same recursive circuit.
+    pub fn standardize(
+        &self,
+        target_num_public_inputs: usize,
+        target_degree_bits: usize,
+    ) -> CircuitBuild<L, D> {
+        let circuit = self.build_adapter(target_num_public_inputs, Some(target_degree_bits));
+        assert_eq!(
+            circuit.data.common.degree_bits(),
+            target_degree_bits,
+            "circuit is too large to be standardized to {} degree bits",
+            target_degree_bits
+        );
+        circuit
+    }
+
+    /// Builds the adapter of `standardize`, padded to `target_degree_bits` if given.
+    fn build_adapter(
+        &self,
+        target_num_public_inputs: usize,
+        target_degree_bits: Option<usize>,
+    ) -> CircuitBuild<L, D> {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let common_data = &self.data.common;
+        let verifier_data = builder.constant_verifier_data::<L>(&self.data);
+        let proof = builder.proof_read(common_data);
+        builder.verify_proof::<L>(&proof, &verifier_data, common_data);
+
+        let mut public_inputs = proof
+            .public_inputs
+            .iter()
+            .copied()
+            .map(Variable)
+            .collect_vec();
+        if public_inputs.len() > target_num_public_inputs {
+            assert!(
+                target_num_public_inputs >= PoseidonHashOutVariable::nb_elements(),
+                "too few public inputs to hold the hash of the public inputs"
+            );
+            public_inputs = builder.poseidon_hash(&public_inputs).variables();
+        }
+        let zero = builder.zero::<Variable>();
+        public_inputs.resize(target_num_public_inputs, zero);
+        for public_input in public_inputs {
+            builder.proof_write(public_input);
+        }
+
+        // The gates of a recursive circuit are a superset of the gates of the adapter in practice,
+        // so that adapters of different circuits have the same gate set.
+        for gate in common_data_for_recursion::<L, D>().gates {
+            builder.api.add_gate_to_gate_set(gate);
+        }
+        // Building the circuit adds gates for the constants and the public inputs, which must fit
+        // in the remaining rows.
+        if let Some(degree_bits) = target_degree_bits {
+            while builder.api.num_gates() <= 1 << (degree_bits - 1) {
+                builder.api.add_gate(NoopGate, vec![]);
+            }
+        }
+        builder.build()
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns the verifier data of `circuits[index]`, where the `circuits` have the same common
+    /// data, e.g. adapters of the same target shape.
+    ///
+    /// Both the circuit digest and the constants and sigmas cap are selected from the constants of
+    /// the allowed circuits, so a proof of any other circuit fails to verify. The selection
+    /// constrains `index` to be less than the number of circuits rounded up to a power of two, and
+    /// the padding repeats the first circuit.
+    pub fn select_verifier_data(
+        &mut self,
+        circuits: &[&CircuitBuild<L, D>],
+        index: Variable,
+    ) -> VerifierCircuitTarget {
+        assert!(!circuits.is_empty(), "no circuits to select from");
+        assert!(
+            circuits
+                .iter()
+                .all(|circuit| circuit.data.common == circuits[0].data.common),
+            "circuits must have the same common data"
+        );
+        let mut elements = circuits
+            .iter()
+            .map(|circuit| {
+                let verifier_only = &circuit.data.verifier_only;
+                let cap = verifier_only.constants_sigmas_cap.0.iter();
+                verifier_only
+                    .circuit_digest
+                    .elements
+                    .into_iter()
+                    .chain(cap.flat_map(|hash| hash.elements))
+                    .collect_vec()
+            })
+            .collect_vec();
+        elements.resize(circuits.len().next_power_of_two(), elements[0].clone());
+
+        let selected = (0..elements[0].len())
+            .map(|i| {
+                let column = elements
+                    .iter()
+                    .map(|element| self.api.constant(element[i]))
+                    .collect_vec();
+                self.api.random_access(index.0, column)
+            })
+            .collect_vec();
+        let (circuit_digest, cap) = selected.split_at(NUM_HASH_OUT_ELTS);
+        VerifierCircuitTarget {
+            circuit_digest: HashOutTarget::from_vec(circuit_digest.to_vec()),
+            constants_sigmas_cap: MerkleCapTarget(
+                cap.chunks(NUM_HASH_OUT_ELTS)
+                    .map(|hash| HashOutTarget::from_vec(hash.to_vec()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Returns the smallest number of public inputs and degree bits that all the `circuits` can be
+/// standardized to, so that none of their public inputs are hashed.
+pub fn standard_shape<L: PlonkParameters<D>, const D: usize>(
+    circuits: &[&CircuitBuild<L, D>],
+) -> (usize, usize)
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+{
+    assert!(!circuits.is_empty(), "no circuits to standardize");
+    let num_public_inputs = circuits
+        .iter()
+        .map(|circuit| circuit.data.common.num_public_inputs)
+        .max()
+        .unwrap();
+    let degree_bits = circuits
+        .iter()
+        .map(|circuit| {
+            let adapter = circuit.build_adapter(num_public_inputs, None);
+            adapter.data.common.degree_bits()
+        })
+        .collect_vec();
+    (num_public_inputs, degree_bits.into_iter().max().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::plonk::config::Hasher;
+    use plonky2::plonk::proof::ProofWithPublicInputs;
+
+    use super::*;
+    use crate::prelude::{DefaultBuilder, DefaultParameters, Field, GoldilocksField};
+
+    type F = GoldilocksField;
+
+    fn prove(
+        circuit: &CircuitBuild<DefaultParameters, 2>,
+        inputs: &[u64],
+    ) -> ProofWithPublicInputs<F, <DefaultParameters as PlonkParameters<2>>::Config, 2> {
+        let mut input = circuit.input();
+        for i in inputs {
+            input.write::<Variable>(F::from_canonical_u64(*i));
+        }
+        let (proof, _) = circuit.prove(&input);
+        proof
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_standardize() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let c = builder.add(a, b);
+        builder.write(c);
+        let adder = builder.build();
+
+        let mut builder = DefaultBuilder::new();
+        let x = builder.read::<Variable>();
+        let y = builder.mul(x, x);
+        let z = builder.mul(x, y);
+        builder.write(z);
+        builder.write(y);
+        builder.write(x);
+        let cuber = builder.build();
+
+        let (num_public_inputs, degree_bits) = standard_shape(&[&adder, &cuber]);
+        assert_eq!(num_public_inputs, 4);
+        let standard_adder = adder.standardize(num_public_inputs, degree_bits);
+        let standard_cuber = cuber.standardize(num_public_inputs, degree_bits);
+        assert_eq!(standard_adder.data.common, standard_cuber.data.common);
+
+        let mut input = standard_adder.input();
+        input.proof_write(prove(&adder, &[3, 4]));
+        let (adder_proof, _) = standard_adder.prove(&input);
+        let expected = [3, 4, 7, 0].map(F::from_canonical_u64);
+        assert_eq!(adder_proof.public_inputs, expected);
+
+        let mut input = standard_cuber.input();
+        input.proof_write(prove(&cuber, &[2]));
+        let (cuber_proof, _) = standard_cuber.prove(&input);
+        let expected = [2, 8, 4, 2].map(F::from_canonical_u64);
+        assert_eq!(cuber_proof.public_inputs, expected);
+
+        // A node circuit that selects the verifier data from both adapters verifies proofs of
+        // both.
+        let common_data = &standard_adder.data.common;
+        let mut builder = DefaultBuilder::new();
+        let proof = builder.proof_read(common_data);
+        let index = builder.read::<Variable>();
+        let verifier_data =
+            builder.select_verifier_data(&[&standard_adder, &standard_cuber], index);
+        builder.verify_proof::<DefaultParameters>(&proof, &verifier_data, common_data);
+        let node = builder.build();
+        for (index, child_proof) in [adder_proof, cuber_proof].into_iter().enumerate() {
+            let mut input = node.input();
+            input.proof_write(child_proof);
+            input.write::<Variable>(F::from_canonical_usize(index));
+            let (node_proof, _) = node.prove(&input);
+            node.data.verify(node_proof).unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    #[should_panic]
+    fn test_select_verifier_data_rejects_foreign_circuit() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let c = builder.add(a, b);
+        builder.write(c);
+        let adder = builder.build();
+
+        // A circuit of the same shape that is not in the allowlist.
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let c = builder.mul(a, b);
+        builder.write(c);
+        let multiplier = builder.build();
+
+        let (num_public_inputs, degree_bits) = standard_shape(&[&adder, &multiplier]);
+        let standard_adder = adder.standardize(num_public_inputs, degree_bits);
+        let standard_multiplier = multiplier.standardize(num_public_inputs, degree_bits);
+        assert_eq!(standard_adder.data.common, standard_multiplier.data.common);
+
+        let common_data = &standard_adder.data.common;
+        let mut builder = DefaultBuilder::new();
+        let proof = builder.proof_read(common_data);
+        let index = builder.read::<Variable>();
+        let verifier_data = builder.select_verifier_data(&[&standard_adder], index);
+        builder.verify_proof::<DefaultParameters>(&proof, &verifier_data, common_data);
+        let node = builder.mock_build();
+
+        let mut input = standard_multiplier.input();
+        input.proof_write(prove(&multiplier, &[3, 4]));
+        let (multiplier_proof, _) = standard_multiplier.prove(&input);
+        let mut input = node.input();
+        input.proof_write(multiplier_proof);
+        input.write::<Variable>(F::ZERO);
+        node.mock_prove(&input);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_standardize_hashes_public_inputs() {
+        let mut builder = DefaultBuilder::new();
+        let inputs = (0..6).map(|_| builder.read::<Variable>()).collect_vec();
+        for input in inputs {
+            builder.write(input);
+        }
+        let circuit = builder.build();
+
+        let (_, degree_bits) = standard_shape(&[&circuit]);
+        let standard_circuit = circuit.standardize(5, degree_bits);
+        let mut input = standard_circuit.input();
+        let proof = prove(&circuit, &[1, 2, 3, 4, 5, 6]);
+        let hash = <<DefaultParameters as PlonkParameters<2>>::Config as GenericConfig<2>>::Hasher::hash_no_pad(&proof.public_inputs);
+        input.proof_write(proof);
+        let (proof, _) = standard_circuit.prove(&input);
+        assert_eq!(proof.public_inputs[..4], hash.elements);
+        assert_eq!(proof.public_inputs[4], F::ZERO);
+    }
+}
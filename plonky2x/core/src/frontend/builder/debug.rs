@@ -0,0 +1,69 @@
+use core::fmt::Display;
+use core::panic::Location;
+
+use plonky2::iop::target::Target;
+
+use super::CircuitBuilder;
+use crate::frontend::vars::CircuitVariable;
+use crate::prelude::PlonkParameters;
+
+/// An equality asserted while building a circuit in debug mode.
+///
+/// Assertions are only recorded in debug mode and are used by mock proving to explain failed
+/// assertions when generating a witness. They do not change the circuit.
+#[derive(Debug, Clone)]
+pub struct DebugAssertion {
+    pub label: Option<String>,
+    pub location: &'static Location<'static>,
+    /// The pairs of targets that are asserted to be equal.
+    pub targets: Vec<(Target, Target)>,
+}
+
+impl DebugAssertion {
+    /// Describes the failure of the assertion, with the two values it was given.
+    pub fn describe<F: Display>(&self, existing: F, new: F) -> String {
+        match &self.label {
+            Some(label) => format!(
+                "Assertion {:?} at {} failed: {} != {}",
+                label, self.location, existing, new
+            ),
+            None => format!(
+                "Assertion at {} failed: {} != {}",
+                self.location, existing, new
+            ),
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Fails if i1 != i2. In debug mode, the failure is reported by mock proving with `label`.
+    #[track_caller]
+    pub fn assert_is_equal_labeled<V: CircuitVariable>(&mut self, i1: V, i2: V, label: &str) {
+        self.assert_is_equal_with_label(i1, i2, Some(label));
+    }
+
+    /// Connects the targets of `i1` and `i2`, and records the assertion in debug mode.
+    #[track_caller]
+    pub(crate) fn assert_is_equal_with_label<V: CircuitVariable>(
+        &mut self,
+        i1: V,
+        i2: V,
+        label: Option<&str>,
+    ) {
+        let targets = i1
+            .targets()
+            .into_iter()
+            .zip(i2.targets())
+            .collect::<Vec<_>>();
+        for (t1, t2) in targets.iter() {
+            self.api.connect(*t1, *t2);
+        }
+        if self.debug {
+            self.debug_assertions.push(DebugAssertion {
+                label: label.map(str::to_string),
+                location: Location::caller(),
+                targets,
+            });
+        }
+    }
+}
@@ -80,6 +80,18 @@ impl<const D: usize> CircuitIO<D> {
         }
     }
 
+    /// Returns all the variables that are written to the witness by `set_witness`, excluding the
+    /// targets of any recursive proofs.
+    pub fn input_variables(&self) -> Vec<Variable> {
+        match self {
+            Self::Bytes(io) => io.input.iter().flat_map(|b| b.variables()).collect(),
+            Self::Elements(io) => io.input.clone(),
+            Self::RecursiveProofs(io) => io.input.clone(),
+            Self::CyclicProof(io) => io.input.clone(),
+            Self::None() => vec![],
+        }
+    }
+
     pub fn output(&self) -> Vec<Variable> {
         match self {
             Self::Bytes(io) => io.output.iter().flat_map(|b| b.variables()).collect(),
@@ -1,7 +1,10 @@
 mod boolean;
+pub mod debug;
 pub mod io;
 pub mod permutation;
+pub mod profiling;
 mod proof;
+pub mod size;
 pub mod watch;
 
 use alloc::collections::BTreeMap;
@@ -12,27 +15,34 @@ use backtrace::Backtrace;
 use ethers::providers::{Http, Middleware, Provider};
 use ethers::types::U256;
 use itertools::Itertools;
+use log::info;
 use plonky2::iop::generator::{SimpleGenerator, WitnessGeneratorRef};
 use plonky2::iop::target::{BoolTarget, Target};
 use plonky2::plonk::circuit_builder::CircuitBuilder as CircuitAPI;
-use plonky2::plonk::circuit_data::CircuitConfig;
+use plonky2::plonk::circuit_data::{CircuitConfig, CommonCircuitData, VerifierCircuitTarget};
+use plonky2::plonk::proof::ProofWithPublicInputsTarget;
 use starkyx::machine::hash::blake::blake2b::BLAKE2B;
 use starkyx::machine::hash::sha::sha256::SHA256;
 use starkyx::machine::hash::sha::sha512::SHA512;
 use tokio::runtime::Runtime;
 
+use self::debug::DebugAssertion;
 pub use self::io::CircuitIO;
+use self::profiling::{Profiler, ProfilingReport};
 use super::ecc::curve25519::curta::accelerator::EcOpAccelerator;
 use super::hash::blake2::curta::BLAKE2BAccelerator;
 use super::hash::sha::sha256::curta::SHA256Accelerator;
 use super::hash::sha::sha512::curta::SHA512Accelerator;
 use super::hint::HintGenerator;
-use super::vars::EvmVariable;
+use super::vars::{EnumVariable, EvmVariable};
 use crate::backend::circuit::{CircuitBuild, DefaultParameters, MockCircuitBuild, PlonkParameters};
 use crate::frontend::hint::asynchronous::generator::AsyncHintDataRef;
-use crate::frontend::vars::{BoolVariable, CircuitVariable, Variable};
+use crate::frontend::vars::{
+    BoolVariable, ByteVariable, Bytes32Variable, CircuitVariable, Variable,
+};
 use crate::prelude::ArrayVariable;
 use crate::utils::eth::beacon::BeaconClient;
+use crate::utils::eth::cache::RpcCacheMode;
 
 /// The universal builder for building circuits using `plonky2x`.
 pub struct CircuitBuilder<L: PlonkParameters<D>, const D: usize> {
@@ -40,15 +50,35 @@ pub struct CircuitBuilder<L: PlonkParameters<D>, const D: usize> {
     pub io: CircuitIO<D>,
     pub execution_client: Option<Provider<Http>>,
     pub chain_id: Option<u64>,
+    /// The cache mode of the RPC requests of the execution hints, which otherwise use the mode of
+    /// the `RPC_CACHE_MODE` environment variable.
+    pub rpc_cache_mode: Option<RpcCacheMode>,
     pub beacon_client: Option<BeaconClient>,
     pub debug: bool,
     pub debug_variables: HashMap<usize, String>,
+    /// The equalities asserted in debug mode, which mock circuits use to explain failures.
+    pub debug_assertions: Vec<DebugAssertion>,
+    /// The state of profiling, if it is enabled with `enable_profiling`.
+    pub(crate) profiler: Option<Profiler>,
     pub(crate) hints: Vec<Box<dyn HintGenerator<L, D>>>,
     pub(crate) async_hints: Vec<AsyncHintDataRef<L, D>>,
     pub(crate) async_hints_indices: Vec<usize>,
+    /// The little-endian bit decompositions of targets, so that chained bitwise operations on the
+    /// same word only decompose it once.
+    pub(crate) le_bits_cache: HashMap<Target, Vec<BoolTarget>>,
+    /// The dummy proofs and verifier data of `conditionally_verify_proof`, with the common data of
+    /// the proofs they stand in for, so that each shape of proof only has one dummy.
+    pub(crate) dummy_proofs: Vec<(
+        CommonCircuitData<L::Field, D>,
+        ProofWithPublicInputsTarget<D>,
+        VerifierCircuitTarget,
+    )>,
 
     pub blake2b_accelerator: Option<BLAKE2BAccelerator>,
     pub sha256_accelerator: Option<SHA256Accelerator>,
+    /// The inputs and digests of the SHA256 hashes requested with `sha256_accelerated`, which are
+    /// constrained when the circuit is built.
+    pub sha256_accelerated_requests: Vec<(Vec<ByteVariable>, Bytes32Variable)>,
     pub sha512_accelerator: Option<SHA512Accelerator>,
     pub ec_25519_ops_accelerator: Option<EcOpAccelerator>,
 }
@@ -74,13 +104,19 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             beacon_client: None,
             execution_client: None,
             chain_id: None,
+            rpc_cache_mode: None,
             debug: false,
             debug_variables: HashMap::new(),
+            debug_assertions: Vec::new(),
+            profiler: None,
             hints: Vec::new(),
             async_hints: Vec::new(),
             async_hints_indices: Vec::new(),
+            le_bits_cache: HashMap::new(),
+            dummy_proofs: Vec::new(),
             blake2b_accelerator: None,
             sha256_accelerator: None,
+            sha256_accelerated_requests: Vec::new(),
             sha512_accelerator: None,
             ec_25519_ops_accelerator: None,
         };
@@ -123,6 +159,13 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         self.chain_id = Some(result_cast);
     }
 
+    /// Sets the chain of the execution hints without querying an RPC, along with the cache mode of
+    /// their requests, so that they can replay recorded responses offline.
+    pub fn set_execution_cache(&mut self, chain_id: u64, mode: RpcCacheMode) {
+        self.chain_id = Some(chain_id);
+        self.rpc_cache_mode = Some(mode);
+    }
+
     pub fn get_chain_id(&self) -> u64 {
         self.chain_id.unwrap()
     }
@@ -133,24 +176,35 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
 
     /// Adds all the constraints nedded before building the circuit and registering hints.
     fn pre_build(&mut self) {
+        // This may add requests to the SHA256 accelerator, so it must run before it is constrained.
+        self.constrain_sha256_accelerated_requests();
+
         let blake2b_accelerator = self.blake2b_accelerator.clone();
         if let Some(accelerator) = blake2b_accelerator {
+            self.push_scope("blake2b_accelerator");
             self.curta_constrain_hash::<BLAKE2B, 96, true, 4>(accelerator);
+            self.pop_scope();
         }
 
         let sha256_accelerator = self.sha256_accelerator.clone();
         if let Some(accelerator) = sha256_accelerator {
+            self.push_scope("sha256_accelerator");
             self.curta_constrain_hash::<SHA256, 64, false, 8>(accelerator);
+            self.pop_scope();
         }
 
         let sha512_accelerator = self.sha512_accelerator.clone();
         if let Some(accelerator) = sha512_accelerator {
+            self.push_scope("sha512_accelerator");
             self.curta_constrain_hash::<SHA512, 80, false, 8>(accelerator);
+            self.pop_scope();
         }
 
         let ec_ops_accelerator = self.ec_25519_ops_accelerator.clone();
         if let Some(accelerator) = ec_ops_accelerator {
+            self.push_scope("ec_25519_ops_accelerator");
             self.curta_constrain_ec_op(accelerator);
+            self.pop_scope();
         }
 
         for (index, gen_ref) in self
@@ -239,15 +293,28 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         async_hints_map
     }
 
+    /// Completes the profiling report taken before building the circuit with the gates that
+    /// plonky2 added to build it with `degree` rows, and logs it.
+    fn finish_profiling(report: Option<ProfilingReport>, degree: usize) -> Option<ProfilingReport> {
+        let mut report = report?;
+        report.add_build_scope(degree);
+        info!("Profiling report:\n{}", report);
+        Some(report)
+    }
+
     /// Build the circuit.
     pub fn build(mut self) -> CircuitBuild<L, D> {
         self.pre_build();
+        let profiling_report = self.profiling_report();
         let data = self.api.build();
+        let profiling_report = Self::finish_profiling(profiling_report, data.common.degree());
         let async_hints = Self::async_hint_map(&data.prover_only.generators, self.async_hints);
         CircuitBuild {
             data,
             io: self.io,
             async_hints,
+            debug_assertions: self.debug_assertions,
+            profiling_report,
         }
     }
 
@@ -255,13 +322,17 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     /// common_data, if will still return the data and success as false.
     pub fn try_build(mut self) -> (CircuitBuild<L, D>, bool) {
         self.pre_build();
+        let profiling_report = self.profiling_report();
         let (data, success) = self.api.try_build_with_options(true);
+        let profiling_report = Self::finish_profiling(profiling_report, data.common.degree());
         let async_hints = Self::async_hint_map(&data.prover_only.generators, self.async_hints);
         (
             CircuitBuild {
                 data,
                 io: self.io,
                 async_hints,
+                debug_assertions: self.debug_assertions,
+                profiling_report,
             },
             success,
         )
@@ -269,14 +340,18 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
 
     pub fn mock_build(mut self) -> MockCircuitBuild<L, D> {
         self.pre_build();
+        let profiling_report = self.profiling_report();
         let mock_data = self.api.mock_build();
+        let profiling_report = Self::finish_profiling(profiling_report, mock_data.common.degree());
         let async_hints = Self::async_hint_map(&mock_data.prover_only.generators, self.async_hints);
 
         MockCircuitBuild {
             data: mock_data,
             io: self.io,
             debug_variables: self.debug_variables,
+            debug_assertions: self.debug_assertions,
             async_hints,
+            profiling_report,
         }
     }
 
@@ -345,6 +420,9 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
 
     // @audit
     /// If selector is true, yields i1 else yields i2.
+    ///
+    /// Works for any `CircuitVariable`, including arrays and derived structs, by selecting each of
+    /// their underlying variables.
     pub fn select<V: CircuitVariable>(&mut self, selector: BoolVariable, i1: V, i2: V) -> V {
         assert_eq!(i1.targets().len(), i2.targets().len());
         let mut targets = Vec::new();
@@ -366,10 +444,18 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     }
 
     /// Fails if i1 != i2.
+    ///
+    /// This only adds copy constraints between the targets of `i1` and `i2`, which are enforced by
+    /// the permutation argument and do not add any gates. For this reason there is no accumulator
+    /// that defers equality checks and enforces `sum r^i (a_i - b_i) = 0` for a challenge `r`
+    /// hashed from all the pairs: the hash and the linear combination can only add gates to checks
+    /// that cost none. `test_assert_is_equal_adds_no_gates` compares the two.
+    ///
+    /// In debug mode, the assertion is recorded with the location of the caller, so that mock
+    /// proving reports where it failed. See `assert_is_equal_labeled`.
+    #[track_caller]
     pub fn assert_is_equal<V: CircuitVariable>(&mut self, i1: V, i2: V) {
-        for (t1, t2) in i1.targets().iter().zip(i2.targets().iter()) {
-            self.api.connect(*t1, *t2);
-        }
+        self.assert_is_equal_with_label(i1, i2, None);
     }
 
     /// Returns 1 if i1 == i2 and 0 otherwise as a BoolVariable.
@@ -382,8 +468,25 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         }
         result
     }
+
+    /// Returns whether all the underlying variables of a variable of any type are zero.
+    #[must_use]
+    pub fn is_zero_variables<V: CircuitVariable>(&mut self, a: &V) -> BoolVariable {
+        let mut result = self._true();
+        for variable in a.variables() {
+            let is_zero = self.is_zero(variable);
+            result = self.and(result, is_zero);
+        }
+        result
+    }
     // @end-audit
 
+    /// Returns the index of the active variant of an enum variable, for branching with `select`
+    /// or `is_equal`.
+    pub fn match_variant<V: EnumVariable>(&mut self, variable: &V) -> Variable {
+        variable.discriminant()
+    }
+
     /// Connects two variables.
     pub fn connect<V: CircuitVariable>(&mut self, i1: V, i2: V) {
         let i1 = i1.targets();
@@ -393,6 +496,26 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         }
     }
 
+    /// Decomposes a target into `nb_bits` little-endian bits, reusing a previous decomposition of
+    /// the same target if there is one.
+    pub(crate) fn split_le_cached(&mut self, target: Target, nb_bits: usize) -> Vec<BoolTarget> {
+        if let Some(bits) = self.le_bits_cache.get(&target) {
+            if bits.len() == nb_bits {
+                return bits.clone();
+            }
+        }
+        let bits = self.api.split_le(target, nb_bits);
+        self.le_bits_cache.insert(target, bits.clone());
+        bits
+    }
+
+    /// Sums little-endian bits into a target and records them as its decomposition.
+    pub(crate) fn le_sum_cached(&mut self, bits: &[BoolTarget]) -> Target {
+        let target = self.api.le_sum(bits.iter());
+        self.le_bits_cache.insert(target, bits.to_vec());
+        target
+    }
+
     pub fn to_le_bits<V: EvmVariable>(&mut self, variable: V) -> Vec<BoolVariable> {
         variable.to_le_bits(self)
     }
@@ -400,6 +523,30 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     pub fn to_be_bits<V: EvmVariable>(&mut self, variable: V) -> Vec<BoolVariable> {
         variable.to_be_bits(self)
     }
+
+    pub fn from_le_bits<V: EvmVariable>(&mut self, bits: &[BoolVariable]) -> V {
+        V::from_le_bits(self, bits)
+    }
+
+    pub fn from_be_bits<V: EvmVariable>(&mut self, bits: &[BoolVariable]) -> V {
+        V::from_be_bits(self, bits)
+    }
+
+    pub fn to_le_bytes<V: EvmVariable>(&mut self, variable: V) -> Vec<ByteVariable> {
+        variable.to_le_bytes(self)
+    }
+
+    pub fn to_be_bytes<V: EvmVariable>(&mut self, variable: V) -> Vec<ByteVariable> {
+        variable.to_be_bytes(self)
+    }
+
+    pub fn from_le_bytes<V: EvmVariable>(&mut self, bytes: &[ByteVariable]) -> V {
+        V::from_le_bytes(self, bytes)
+    }
+
+    pub fn from_be_bytes<V: EvmVariable>(&mut self, bytes: &[ByteVariable]) -> V {
+        V::from_be_bytes(self, bytes)
+    }
 }
 
 impl<L: PlonkParameters<D>, const D: usize> Default for CircuitBuilder<L, D> {
@@ -473,4 +620,153 @@ pub(crate) mod tests {
         let xor = output.evm_read::<ByteVariable>();
         debug!("{}", xor);
     }
+
+    #[derive(Debug, Clone, CircuitVariable)]
+    #[value_name(Inner)]
+    #[value_derive(PartialEq, Eq)]
+    struct InnerVariable {
+        a: U32Variable,
+        b: ArrayVariable<ByteVariable, 3>,
+    }
+
+    #[derive(Debug, Clone, CircuitVariable)]
+    #[value_name(Outer)]
+    #[value_derive(PartialEq, Eq)]
+    struct OuterVariable {
+        inner: InnerVariable,
+        flag: BoolVariable,
+        c: [Variable; 2],
+    }
+
+    #[test]
+    fn test_select_derived_struct() {
+        let mut builder = DefaultBuilder::new();
+        let selector = builder.read::<BoolVariable>();
+        let a = builder.read::<OuterVariable>();
+        let b = builder.read::<OuterVariable>();
+
+        let variables = a.variables();
+        let a_back = OuterVariable::from_variables_unsafe(&variables);
+        assert_eq!(a_back.variables(), variables);
+
+        let selected = builder.select(selector, a, b);
+        builder.write(selected);
+        let circuit = builder.build();
+
+        let a = Outer {
+            inner: Inner {
+                a: 1,
+                b: vec![2, 3, 4],
+            },
+            flag: true,
+            c: [GoldilocksField::from_canonical_u64(5); 2],
+        };
+        let b = Outer {
+            inner: Inner {
+                a: 6,
+                b: vec![7, 8, 9],
+            },
+            flag: false,
+            c: [GoldilocksField::from_canonical_u64(10); 2],
+        };
+        for selector in [true, false] {
+            let mut input = circuit.input();
+            input.write::<BoolVariable>(selector);
+            input.write::<OuterVariable>(a.clone());
+            input.write::<OuterVariable>(b.clone());
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            let expected = if selector { a.clone() } else { b.clone() };
+            assert_eq!(output.read::<OuterVariable>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_is_equal_derived_struct() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<OuterVariable>();
+        let b = builder.read::<OuterVariable>();
+        let is_equal = builder.is_equal(a.clone(), b);
+        let is_zero = builder.is_zero_variables(&a);
+        builder.write(is_equal);
+        builder.write(is_zero);
+        let circuit = builder.build();
+
+        let outer = |a, last_byte, flag, c| Outer {
+            inner: Inner {
+                a,
+                b: vec![1, 2, last_byte],
+            },
+            flag,
+            c: [GoldilocksField::from_canonical_u64(c); 2],
+        };
+        let zero = Outer {
+            inner: Inner {
+                a: 0,
+                b: vec![0; 3],
+            },
+            flag: false,
+            c: [GoldilocksField::ZERO; 2],
+        };
+        let cases = [
+            (outer(1, 3, true, 5), outer(1, 3, true, 5), true, false),
+            // Differing in a single byte of the nested struct.
+            (outer(1, 3, true, 5), outer(1, 4, true, 5), false, false),
+            (outer(1, 3, true, 5), outer(1, 3, false, 5), false, false),
+            (zero.clone(), zero.clone(), true, true),
+            (zero.clone(), outer(0, 0, false, 1), false, true),
+        ];
+        for (a, b, is_equal, is_zero) in cases {
+            let mut input = circuit.input();
+            input.write::<OuterVariable>(a);
+            input.write::<OuterVariable>(b);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<BoolVariable>(), is_equal);
+            assert_eq!(output.read::<BoolVariable>(), is_zero);
+        }
+    }
+
+    #[test]
+    fn test_assert_is_equal_adds_no_gates() {
+        let mut builder = DefaultBuilder::new();
+        let pairs = (0..1000)
+            .map(|_| {
+                (
+                    builder.read::<Bytes32Variable>(),
+                    builder.read::<Bytes32Variable>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let num_gates = builder.api.num_gates();
+        for (a, b) in pairs.iter() {
+            builder.assert_is_equal(a.clone(), b.clone());
+        }
+        assert_eq!(builder.api.num_gates(), num_gates);
+
+        // The same checks batched with a random linear combination, with a challenge hashed from
+        // all the pairs.
+        let elements = pairs
+            .iter()
+            .flat_map(|(a, b)| [a.variables(), b.variables()].concat())
+            .collect::<Vec<_>>();
+        let challenge = builder.poseidon_hash(&elements).variables()[0];
+        let mut sum = builder.zero::<Variable>();
+        for (a, b) in pairs.iter() {
+            for (x, y) in a.variables().into_iter().zip(b.variables()) {
+                let difference = builder.sub(x, y);
+                sum = builder.mul(sum, challenge);
+                sum = builder.add(sum, difference);
+            }
+        }
+        let zero = builder.zero::<Variable>();
+        builder.assert_is_equal(sum, zero);
+        let batched_gates = builder.api.num_gates() - num_gates;
+        debug!(
+            "a random linear combination of 1000 pairs adds {} gates",
+            batched_gates
+        );
+        assert!(batched_gates > 0);
+    }
 }
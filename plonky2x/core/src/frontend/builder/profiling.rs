@@ -0,0 +1,401 @@
+//! Attribution of the cost of a circuit to named scopes of the builder.
+//!
+//! When profiling is enabled, the builder measures the cost of the circuit between each
+//! `push_scope` and the matching `pop_scope`. Scopes nest, and the scopes with the same name under
+//! the same parent are merged, so that the report of a circuit hashing many storage proofs has a
+//! single `circuit > storage_proof > keccak` entry. The built-in hash gadgets open their own scopes.
+//! The gates that plonky2 adds when it builds the circuit are reported in a `plonky2_build` scope.
+
+use core::cmp::Reverse;
+use core::fmt::{self, Display};
+use core::ops;
+
+use itertools::Itertools;
+
+use super::CircuitBuilder;
+use crate::prelude::PlonkParameters;
+
+/// The name of the scope of the whole circuit.
+const ROOT_SCOPE: &str = "circuit";
+
+/// The name of the scope of the gates added when the circuit is built.
+const BUILD_SCOPE: &str = "plonky2_build";
+
+/// The cost of a part of a circuit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ProfilingCost {
+    /// The number of gates, i.e. the rows of the circuit. The padding rows of a built circuit are
+    /// counted in its `plonky2_build` scope.
+    pub gates: usize,
+    /// The number of `add`, `sub`, `mul`, `div` and `neg` operations of the builder.
+    pub arithmetic_ops: usize,
+    /// The number of lookup tables.
+    pub lookup_tables: usize,
+}
+
+impl ops::Add for ProfilingCost {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            gates: self.gates + rhs.gates,
+            arithmetic_ops: self.arithmetic_ops + rhs.arithmetic_ops,
+            lookup_tables: self.lookup_tables + rhs.lookup_tables,
+        }
+    }
+}
+
+impl ops::Sub for ProfilingCost {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            gates: self.gates - rhs.gates,
+            arithmetic_ops: self.arithmetic_ops - rhs.arithmetic_ops,
+            lookup_tables: self.lookup_tables - rhs.lookup_tables,
+        }
+    }
+}
+
+/// The cost of a scope and of the scopes nested in it.
+///
+/// The report of a circuit is its root scope, named `circuit`, whose cost is the cost of the whole
+/// circuit. Its `Display` is a table of all the scopes, with the most costly scopes of each level
+/// first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfilingReport {
+    pub name: String,
+    /// The number of times the scope was opened.
+    pub calls: usize,
+    /// The cost of the scope, including its nested scopes.
+    pub cost: ProfilingCost,
+    pub children: Vec<ProfilingReport>,
+}
+
+impl ProfilingReport {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            calls: 1,
+            cost: ProfilingCost::default(),
+            children: Vec::new(),
+        }
+    }
+
+    /// The cost of the scope outside of its nested scopes.
+    pub fn self_cost(&self) -> ProfilingCost {
+        self.children
+            .iter()
+            .fold(self.cost, |cost, child| cost - child.cost)
+    }
+
+    /// Returns the nested scope at `path`, e.g. `["storage_proof", "keccak", "xor"]`.
+    pub fn scope(&self, path: &[&str]) -> Option<&ProfilingReport> {
+        match path.split_first() {
+            None => Some(self),
+            Some((name, rest)) => self
+                .children
+                .iter()
+                .find(|child| child.name == *name)?
+                .scope(rest),
+        }
+    }
+
+    /// Adds the gates of a built circuit of `degree` rows that the builder did not add, i.e. the
+    /// gates of the constants and of the public inputs and the padding rows, as a `plonky2_build`
+    /// scope. The cost of the report is then the number of rows of the built circuit.
+    pub(crate) fn add_build_scope(&mut self, degree: usize) {
+        let mut scope = ProfilingReport::new(BUILD_SCOPE);
+        scope.cost.gates = degree - self.cost.gates;
+        self.cost.gates = degree;
+        self.add_child(scope);
+    }
+
+    /// Adds a nested scope, merging it with the nested scope of the same name if there is one.
+    fn add_child(&mut self, child: ProfilingReport) {
+        match self.children.iter_mut().find(|c| c.name == child.name) {
+            Some(existing) => {
+                existing.calls += child.calls;
+                existing.cost = existing.cost + child.cost;
+                for grandchild in child.children {
+                    existing.add_child(grandchild);
+                }
+            }
+            None => self.children.push(child),
+        }
+    }
+
+    /// Lists the scopes depth first with their path, with the most costly children first.
+    fn rows<'a>(&'a self, path: String, rows: &mut Vec<(String, &'a ProfilingReport)>) {
+        rows.push((path.clone(), self));
+        for child in self.children.iter().sorted_by_key(|c| Reverse(c.cost)) {
+            child.rows(format!("{} > {}", path, child.name), rows);
+        }
+    }
+}
+
+impl Display for ProfilingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rows = Vec::new();
+        self.rows(self.name.clone(), &mut rows);
+        let width = rows.iter().map(|(path, _)| path.len()).max().unwrap_or(0);
+        writeln!(
+            f,
+            "{:<width$}  {:>8}  {:>10}  {:>10}  {:>10}  {:>8}",
+            "scope",
+            "calls",
+            "gates",
+            "self gates",
+            "arithmetic",
+            "lookups",
+            width = width
+        )?;
+        for (path, report) in rows {
+            writeln!(
+                f,
+                "{:<width$}  {:>8}  {:>10}  {:>10}  {:>10}  {:>8}",
+                path,
+                report.calls,
+                report.cost.gates,
+                report.self_cost().gates,
+                report.cost.arithmetic_ops,
+                report.cost.lookup_tables,
+                width = width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The state of the profiling of a builder.
+#[derive(Debug, Clone)]
+pub(crate) struct Profiler {
+    /// The number of arithmetic operations since profiling was enabled.
+    arithmetic_ops: usize,
+    /// The open scopes from the outermost, with the cost of the circuit when they were opened.
+    scopes: Vec<(ProfilingCost, ProfilingReport)>,
+}
+
+impl Profiler {
+    fn new() -> Self {
+        Self {
+            arithmetic_ops: 0,
+            scopes: vec![(ProfilingCost::default(), ProfilingReport::new(ROOT_SCOPE))],
+        }
+    }
+
+    /// The report of the scopes, where the open scopes end at the cost `current`.
+    fn report(&self, current: ProfilingCost) -> ProfilingReport {
+        self.scopes
+            .iter()
+            .rev()
+            .fold(None, |inner: Option<ProfilingReport>, (start, scope)| {
+                let mut report = scope.clone();
+                report.cost = current - *start;
+                if let Some(inner) = inner {
+                    report.add_child(inner);
+                }
+                Some(report)
+            })
+            .unwrap()
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Enables profiling, so that the circuit is built with a `ProfilingReport` of its scopes.
+    ///
+    /// The whole circuit is profiled, but arithmetic operations are only counted from this call.
+    pub fn enable_profiling(&mut self) {
+        if self.profiler.is_none() {
+            self.profiler = Some(Profiler::new());
+        }
+    }
+
+    /// Opens a profiling scope, nested in the open scope. Does nothing if profiling is disabled.
+    pub fn push_scope(&mut self, name: &str) {
+        let cost = self.profiling_cost();
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.scopes.push((cost, ProfilingReport::new(name)));
+        }
+    }
+
+    /// Closes the innermost profiling scope opened by `push_scope`.
+    pub fn pop_scope(&mut self) {
+        let cost = self.profiling_cost();
+        if let Some(profiler) = self.profiler.as_mut() {
+            assert!(
+                profiler.scopes.len() > 1,
+                "pop_scope called without an open scope"
+            );
+            let (start, mut report) = profiler.scopes.pop().unwrap();
+            report.cost = cost - start;
+            profiler.scopes.last_mut().unwrap().1.add_child(report);
+        }
+    }
+
+    /// Runs `f` in the profiling scope `name`.
+    pub fn profile<T>(&mut self, name: &str, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.push_scope(name);
+        let result = f(self);
+        self.pop_scope();
+        result
+    }
+
+    /// Returns the report of the circuit so far, or `None` if profiling is disabled.
+    pub fn profiling_report(&self) -> Option<ProfilingReport> {
+        let cost = self.profiling_cost();
+        self.profiler.as_ref().map(|profiler| profiler.report(cost))
+    }
+
+    /// Counts an arithmetic operation of the builder if profiling is enabled.
+    pub(crate) fn count_arithmetic_op(&mut self) {
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.arithmetic_ops += 1;
+        }
+    }
+
+    fn profiling_cost(&self) -> ProfilingCost {
+        ProfilingCost {
+            gates: self.api.num_gates(),
+            arithmetic_ops: self
+                .profiler
+                .as_ref()
+                .map_or(0, |profiler| profiler.arithmetic_ops),
+            lookup_tables: self.api.num_luts(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_profiling_report() {
+        let mut builder = DefaultBuilder::new();
+        builder.enable_profiling();
+        let bytes = builder.read::<BytesVariable<64>>();
+
+        builder.push_scope("storage_proof");
+        for _ in 0..2 {
+            builder.keccak256(&bytes.0);
+        }
+        builder.profile("xor", |builder| {
+            let a = builder.read::<ByteVariable>();
+            let b = builder.read::<ByteVariable>();
+            builder.xor(a, b)
+        });
+        builder.pop_scope();
+
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        builder.push_scope("arithmetic");
+        let c = builder.add(a, b);
+        builder.mul(c, c);
+        builder.pop_scope();
+        builder.sha256(&bytes.0);
+
+        // The report covers the gates added when the circuit is built.
+        builder.pre_build();
+        let num_gates = builder.api.num_gates();
+        let report = builder.profiling_report().unwrap();
+
+        assert_eq!(report.name, "circuit");
+        assert_eq!(report.cost.gates, num_gates);
+        assert_eq!(
+            report.self_cost().gates + report.children.iter().map(|c| c.cost.gates).sum::<usize>(),
+            num_gates
+        );
+
+        let storage_proof = report.scope(&["storage_proof"]).unwrap();
+        let keccak = report.scope(&["storage_proof", "keccak"]).unwrap();
+        let xor = report.scope(&["storage_proof", "xor"]).unwrap();
+        assert_eq!(keccak.calls, 2);
+        assert!(keccak.cost.gates > 0);
+        assert_eq!(
+            storage_proof.cost.gates,
+            storage_proof.self_cost().gates + keccak.cost.gates + xor.cost.gates
+        );
+        assert_eq!(
+            report.scope(&["arithmetic"]).unwrap().cost.arithmetic_ops,
+            2
+        );
+        assert!(report.scope(&["sha256"]).unwrap().cost.gates > 0);
+
+        let table = report.to_string();
+        assert!(table.contains("circuit > storage_proof > keccak"));
+        let position = |path: &str| table.find(&format!("\n{} ", path)).unwrap();
+        assert!(position("circuit > storage_proof") < position("circuit > storage_proof > keccak"));
+
+        // A header, then a row per scope, whose last columns are the calls, the gates, the self
+        // gates, the arithmetic operations and the lookup tables.
+        let lines = table.lines().collect::<Vec<_>>();
+        assert!(lines[0].starts_with("scope "));
+        assert!(lines[1].starts_with("circuit "));
+        assert_eq!(lines.len(), 7);
+        let columns = |path: &str| {
+            let row = lines
+                .iter()
+                .find(|line| line.starts_with(&format!("{} ", path)))
+                .unwrap();
+            let mut columns = row.split_whitespace().rev().take(5).collect::<Vec<_>>();
+            columns.reverse();
+            columns
+        };
+        assert_eq!(
+            columns("circuit"),
+            [
+                "1".to_string(),
+                num_gates.to_string(),
+                report.self_cost().gates.to_string(),
+                report.cost.arithmetic_ops.to_string(),
+                report.cost.lookup_tables.to_string(),
+            ]
+        );
+        let keccak_columns = columns("circuit > storage_proof > keccak");
+        assert_eq!(keccak_columns[0], "2");
+        assert_eq!(keccak_columns[1], keccak.cost.gates.to_string());
+        let arithmetic_columns = columns("circuit > arithmetic");
+        assert_eq!(arithmetic_columns[3], "2");
+    }
+
+    #[test]
+    fn test_profiling_report_of_built_circuit() {
+        let mut builder = DefaultBuilder::new();
+        builder.enable_profiling();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let c = builder.profile("arithmetic", |builder| builder.mul(a, b));
+        builder.write(c);
+        let circuit = builder.build();
+
+        let report = circuit.profiling_report.unwrap();
+        let degree = circuit.data.common.degree();
+        assert_eq!(report.cost.gates, degree);
+        let build = report.scope(&["plonky2_build"]).unwrap();
+        assert!(build.cost.gates > 0);
+        assert_eq!(
+            report.self_cost().gates + report.children.iter().map(|c| c.cost.gates).sum::<usize>(),
+            degree
+        );
+    }
+
+    #[test]
+    fn test_profiling_disabled() {
+        let mut builder = DefaultBuilder::new();
+        builder.push_scope("scope");
+        let a = builder.read::<Variable>();
+        builder.add(a, a);
+        builder.pop_scope();
+        assert!(builder.profiling_report().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "pop_scope called without an open scope")]
+    fn test_pop_scope_without_push() {
+        let mut builder = DefaultBuilder::new();
+        builder.enable_profiling();
+        builder.pop_scope();
+    }
+}
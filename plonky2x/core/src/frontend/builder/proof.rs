@@ -1,9 +1,17 @@
+use anyhow::{anyhow, ensure, Result};
+use plonky2::gates::noop::NoopGate;
+use plonky2::iop::target::BoolTarget;
+use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder as CircuitAPI;
 use plonky2::plonk::circuit_data::{CircuitData, CommonCircuitData, VerifierCircuitTarget};
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, GenericHashOut};
 use plonky2::plonk::proof::ProofWithPublicInputsTarget;
 
-use crate::backend::circuit::PlonkParameters;
+use crate::backend::circuit::{PlonkParameters, VerifierData};
 use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::fold::util::common_data_for_recursion;
+use crate::frontend::recursion::proof::{DummyProofHint, ProofWithPublicInputsVariable};
+use crate::prelude::{BoolVariable, Field, Variable, VariableStream};
 
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     pub fn add_virtual_proof_with_pis(
@@ -20,26 +28,158 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         inner_common_data: &CommonCircuitData<L::Field, D>,
     ) where
         <<P as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    {
+        self.verify_proof_with_config::<P::Config>(
+            proof_with_pis,
+            inner_verifier_data,
+            inner_common_data,
+        );
+    }
+
+    /// Verifies a proof generated under the config `C`, which need not be the config of this
+    /// circuit. The transcript and the Merkle proofs of the inner proof are checked with the hasher
+    /// of `C`, which must therefore be arithmetizable over the field: a Poseidon proof can be
+    /// verified in a circuit proven with the BN128 config.
+    ///
+    /// Proofs under `KeccakGoldilocksConfig` are not supported, as plonky2 has no in-circuit
+    /// Keccak hasher for its recursive verifier, and the bound on `C::Hasher` rejects them at
+    /// compile time. A Keccak proof must be wrapped by re-proving its circuit under an algebraic
+    /// config instead.
+    pub fn verify_proof_with_config<C: GenericConfig<D, F = L::Field>>(
+        &mut self,
+        proof_with_pis: &ProofWithPublicInputsTarget<D>,
+        inner_verifier_data: &VerifierCircuitTarget,
+        inner_common_data: &CommonCircuitData<L::Field, D>,
+    ) where
+        C::Hasher: AlgebraicHasher<L::Field>,
     {
         self.api
-            .verify_proof::<P::Config>(proof_with_pis, inner_verifier_data, inner_common_data);
+            .verify_proof::<C>(proof_with_pis, inner_verifier_data, inner_common_data);
+    }
+
+    /// Returns the common data of a circuit with cyclic IO, to read and verify proofs of itself
+    /// with. Must be called after `close_cyclic_io`, so that all the public inputs are known.
+    pub fn cyclic_common_data(&self) -> CommonCircuitData<L::Field, D>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    {
+        let mut common_data = common_data_for_recursion::<L, D>();
+        common_data.num_public_inputs = self.api.num_public_inputs();
+        common_data
+    }
+
+    /// Reads the previous proof of a circuit with cyclic IO, which is set at proving time with
+    /// `PublicInput::self_proof_write`. Must be called after `close_cyclic_io`.
+    pub fn read_self_proof(
+        &mut self,
+        common_data: &CommonCircuitData<L::Field, D>,
+    ) -> ProofWithPublicInputsVariable<D> {
+        self.proof_read(common_data).into()
+    }
+
+    /// Verifies a proof of this circuit if `condition` is true, and a dummy proof otherwise, which
+    /// is how the base case of cyclic recursion avoids needing a previous proof. The verifier data
+    /// of the inner proof is checked against the verifier data in the public inputs of this circuit.
+    pub fn verify_self_proof(
+        &mut self,
+        condition: BoolVariable,
+        proof: ProofWithPublicInputsVariable<D>,
+        common_data: &CommonCircuitData<L::Field, D>,
+    ) where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    {
+        let condition = BoolTarget::new_unsafe(condition.variable.0);
+        self.api
+            .conditionally_verify_cyclic_proof_or_dummy::<L::Config>(
+                condition,
+                &proof.into(),
+                common_data,
+            )
+            .expect("failed to verify cyclic proof");
+    }
+
+    /// Verifies `proof_with_pis` if `condition` is true, and a dummy proof of the same shape
+    /// otherwise, so that the constraints hold for any proof when the condition is false. Returns
+    /// the public inputs of the proof, replaced by zeros when the condition is false.
+    ///
+    /// The dummy proof is generated when the circuit is built, once for each shape of proof.
+    pub fn conditionally_verify_proof(
+        &mut self,
+        condition: BoolVariable,
+        proof_with_pis: &ProofWithPublicInputsTarget<D>,
+        inner_verifier_data: &VerifierCircuitTarget,
+        inner_common_data: &CommonCircuitData<L::Field, D>,
+    ) -> Vec<Variable>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    {
+        let (dummy_proof, dummy_verifier_data) = self.dummy_proof(inner_common_data);
+        self.api.conditionally_verify_proof::<L::Config>(
+            BoolTarget::new_unsafe(condition.variable.0),
+            proof_with_pis,
+            inner_verifier_data,
+            &dummy_proof,
+            &dummy_verifier_data,
+            inner_common_data,
+        );
+
+        let zero = self.zero::<Variable>();
+        proof_with_pis
+            .public_inputs
+            .iter()
+            .map(|target| self.select(condition, Variable(*target), zero))
+            .collect()
+    }
+
+    /// Returns a dummy proof with the shape of `common_data`, whose public inputs are zero, and the
+    /// verifier data of its circuit.
+    fn dummy_proof(
+        &mut self,
+        common_data: &CommonCircuitData<L::Field, D>,
+    ) -> (ProofWithPublicInputsTarget<D>, VerifierCircuitTarget)
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    {
+        if let Some((_, proof, verifier_data)) = self
+            .dummy_proofs
+            .iter()
+            .find(|(dummy_common_data, _, _)| dummy_common_data == common_data)
+        {
+            return (proof.clone(), verifier_data.clone());
+        }
+
+        let dummy_data = dummy_circuit::<L, D>(common_data).expect("failed to build dummy circuit");
+        let mut pw = PartialWitness::new();
+        for target in dummy_data.prover_only.public_inputs.iter() {
+            pw.set_target(*target, L::Field::ZERO);
+        }
+        let proof = dummy_data.prove(pw).expect("failed to prove dummy circuit");
+
+        let verifier_data = self.constant_verifier_data::<L>(&dummy_data);
+        let output_stream = self.hint(VariableStream::new(), DummyProofHint::<L, D> { proof });
+        let proof: ProofWithPublicInputsTarget<D> =
+            output_stream.read_proof_with_pis(self, common_data).into();
+        self.dummy_proofs
+            .push((common_data.clone(), proof.clone(), verifier_data.clone()));
+        (proof, verifier_data)
     }
 
     // @ audit
     pub fn constant_verifier_data<P: PlonkParameters<D, Field = L::Field>>(
         &mut self,
-        data: &CircuitData<P::Field, P::Config, D>,
+        data: &impl VerifierData<P::Field, P::Config, D>,
     ) -> VerifierCircuitTarget {
+        let common = data.common();
+        let verifier_only = data.verifier_only();
+
         // Set the verifier data target to be the verifier data, which is a constant.
         let vd = self
             .api
-            .add_virtual_verifier_data(data.common.config.fri_config.cap_height);
+            .add_virtual_verifier_data(common.config.fri_config.cap_height);
 
         // Set the circuit digest.
         for i in 0..vd.circuit_digest.elements.len() {
-            let constant = self
-                .api
-                .constant(data.verifier_only.circuit_digest.to_vec()[i]);
+            let constant = self.api.constant(verifier_only.circuit_digest.to_vec()[i]);
             self.api.connect(vd.circuit_digest.elements[i], constant);
         }
 
@@ -49,7 +189,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             for j in 0..cap.len() {
                 let constant = self
                     .api
-                    .constant(data.verifier_only.constants_sigmas_cap.0[i].to_vec()[j]);
+                    .constant(verifier_only.constants_sigmas_cap.0[i].to_vec()[j]);
                 self.api.connect(cap[j], constant);
             }
         }
@@ -57,3 +197,221 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         vd
     }
 }
+
+/// Builds a circuit without constraints that has the given common data, so that its proofs can
+/// stand in for proofs of any circuit with the same common data. Fails if the public inputs of
+/// `common_data` do not fit in a circuit of its degree.
+fn dummy_circuit<L: PlonkParameters<D>, const D: usize>(
+    common_data: &CommonCircuitData<L::Field, D>,
+) -> Result<CircuitData<L::Field, L::Config, D>> {
+    let mut builder = CircuitAPI::<L::Field, D>::new(common_data.config.clone());
+    // Building the circuit adds the gates that hash the public inputs, a public input gate and a
+    // constant gate, which fill the circuit up to its degree.
+    let num_noop_gates = common_data
+        .degree()
+        .checked_sub(common_data.num_public_inputs.div_ceil(8) + 2)
+        .ok_or_else(|| {
+            anyhow!(
+                "{} public inputs do not fit in a dummy circuit of degree {}",
+                common_data.num_public_inputs,
+                common_data.degree()
+            )
+        })?;
+    for _ in 0..num_noop_gates {
+        builder.add_gate(NoopGate, vec![]);
+    }
+    for gate in common_data.gates.iter() {
+        builder.add_gate_to_gate_set(gate.clone());
+    }
+    for _ in 0..common_data.num_public_inputs {
+        builder.add_virtual_public_input();
+    }
+    let data = builder.build::<L::Config>();
+    ensure!(
+        &data.common == common_data,
+        "dummy circuit has different common data"
+    );
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+    use plonky2::plonk::proof::ProofWithPublicInputs;
+    use plonky2::recursion::cyclic_recursion::check_cyclic_proof_verifier_data;
+
+    use super::*;
+    use crate::backend::circuit::{CircuitBuild, Groth16WrapperParameters};
+    use crate::prelude::{DefaultBuilder, DefaultParameters, GoldilocksField};
+
+    type Proof = ProofWithPublicInputs<GoldilocksField, PoseidonGoldilocksConfig, 2>;
+
+    /// Returns a circuit that adds its two inputs, and a proof of `3 + 4 = 7`.
+    fn adder_proof() -> (CircuitBuild<DefaultParameters, 2>, Proof) {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let c = builder.add(a, b);
+        builder.write(c);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(3));
+        input.write::<Variable>(GoldilocksField::from_canonical_u64(4));
+        let (proof, _) = circuit.prove(&input);
+        (circuit, proof)
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_proof_with_config() {
+        let (inner_circuit, inner_proof) = adder_proof();
+
+        // The outer circuit hashes with Poseidon over BN128, the inner proof with Poseidon over
+        // Goldilocks.
+        let mut builder = CircuitBuilder::<Groth16WrapperParameters, 2>::new();
+        let proof_target = builder.add_virtual_proof_with_pis(&inner_circuit.data.common);
+        let verifier_target =
+            builder.constant_verifier_data::<DefaultParameters>(&inner_circuit.data);
+        builder.verify_proof_with_config::<PoseidonGoldilocksConfig>(
+            &proof_target,
+            &verifier_target,
+            &inner_circuit.data.common,
+        );
+        let circuit = builder.build();
+
+        let mut pw = PartialWitness::new();
+        pw.set_proof_with_pis_target(&proof_target, &inner_proof);
+        let proof = circuit.data.prove(pw).unwrap();
+        circuit.data.verify(proof).unwrap();
+    }
+
+    /// A circuit that conditionally verifies a proof of `inner_circuit`, and outputs its selected
+    /// public inputs.
+    fn conditional_circuit(
+        inner_circuit: &CircuitBuild<DefaultParameters, 2>,
+    ) -> CircuitBuild<DefaultParameters, 2> {
+        let mut builder = DefaultBuilder::new();
+        let proof = builder.proof_read(&inner_circuit.data.common);
+        let condition = builder.read::<BoolVariable>();
+        let verifier_data =
+            builder.constant_verifier_data::<DefaultParameters>(&inner_circuit.data);
+        let public_inputs = builder.conditionally_verify_proof(
+            condition,
+            &proof,
+            &verifier_data,
+            &inner_circuit.data.common,
+        );
+        for public_input in public_inputs {
+            builder.proof_write(public_input);
+        }
+        builder.build()
+    }
+
+    fn prove_conditional(
+        circuit: &CircuitBuild<DefaultParameters, 2>,
+        inner_proof: Proof,
+        condition: bool,
+    ) -> Vec<GoldilocksField> {
+        let mut input = circuit.input();
+        input.proof_write(inner_proof);
+        input.write::<BoolVariable>(condition);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.data.verify(proof).unwrap();
+        (0..3).map(|_| output.proof_read::<Variable>()).collect()
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_conditionally_verify_proof() {
+        let (inner_circuit, inner_proof) = adder_proof();
+        let circuit = conditional_circuit(&inner_circuit);
+        let mut bogus_proof = inner_proof.clone();
+        bogus_proof.public_inputs[2] = GoldilocksField::from_canonical_u64(8);
+
+        let expected = [3, 4, 7].map(GoldilocksField::from_canonical_u64).to_vec();
+        assert_eq!(
+            prove_conditional(&circuit, inner_proof.clone(), true),
+            expected
+        );
+        assert_eq!(
+            prove_conditional(&circuit, inner_proof, false),
+            vec![GoldilocksField::ZERO; 3]
+        );
+        assert_eq!(
+            prove_conditional(&circuit, bogus_proof, false),
+            vec![GoldilocksField::ZERO; 3]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_conditionally_verify_proof_bogus() {
+        let (inner_circuit, mut bogus_proof) = adder_proof();
+        let circuit = conditional_circuit(&inner_circuit);
+        bogus_proof.public_inputs[2] = GoldilocksField::from_canonical_u64(8);
+        prove_conditional(&circuit, bogus_proof, true);
+    }
+
+    /// A circuit that adds its input `x` to the accumulator of its previous proof.
+    fn accumulator_circuit() -> CircuitBuild<DefaultParameters, 2> {
+        let mut builder = DefaultBuilder::new();
+        builder.use_cyclic_recursion();
+        let x = builder.read::<Variable>();
+        let prev = builder.read::<Variable>();
+        let is_recursive = builder.read::<BoolVariable>();
+        let acc = builder.add(prev, x);
+        builder.write(acc);
+        builder.close_cyclic_io();
+
+        let common_data = builder.cyclic_common_data();
+        let proof = builder.read_self_proof(&common_data);
+
+        // The public inputs of the previous proof are `x`, `prev`, `is_recursive` and `acc`.
+        let inner_acc = proof.public_inputs[3];
+        let zero = builder.zero::<Variable>();
+        let expected_prev = builder.select(is_recursive, inner_acc, zero);
+        builder.assert_is_equal(prev, expected_prev);
+        builder.verify_self_proof(is_recursive, proof, &common_data);
+        builder.build()
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_self_proof() {
+        let circuit = accumulator_circuit();
+
+        let mut proof = None;
+        let mut acc = GoldilocksField::ZERO;
+        for i in 1..=3 {
+            let x = GoldilocksField::from_canonical_u64(i);
+            let mut input = circuit.input();
+            input.write::<Variable>(x);
+            input.write::<Variable>(acc);
+            input.write::<BoolVariable>(proof.is_some());
+            input.self_proof_write(&circuit, proof);
+            let (next_proof, mut output) = circuit.prove(&input);
+
+            circuit.data.verify(next_proof.clone()).unwrap();
+            check_cyclic_proof_verifier_data(
+                &next_proof,
+                &circuit.data.verifier_only,
+                &circuit.data.common,
+            )
+            .unwrap();
+            acc = output.read::<Variable>();
+            // The accumulator is 1 + 2 + ... + i.
+            assert_eq!(acc, GoldilocksField::from_canonical_u64(i * (i + 1) / 2));
+            proof = Some(next_proof);
+        }
+    }
+
+    #[test]
+    fn test_dummy_circuit_too_many_public_inputs() {
+        let (circuit, _) = adder_proof();
+        let mut common_data = circuit.data.common.clone();
+        common_data.num_public_inputs = 8 * common_data.degree();
+        assert!(dummy_circuit::<DefaultParameters, 2>(&common_data).is_err());
+    }
+}
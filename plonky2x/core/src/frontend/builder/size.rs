@@ -0,0 +1,259 @@
+//! Budgets for the size of circuits, to catch size regressions in tests.
+
+use core::fmt::{self, Display};
+use core::panic::Location;
+use std::path::PathBuf;
+use std::{env, fs};
+
+use log::debug;
+
+use super::CircuitBuilder;
+use crate::backend::circuit::gate_counts;
+use crate::prelude::PlonkParameters;
+
+/// The environment variable that makes `assert_circuit_size` rewrite the budgets of its calls to
+/// the sizes of their circuits instead of checking them.
+pub const UPDATE_CIRCUIT_SIZES: &str = "UPDATE_CIRCUIT_SIZES";
+
+/// The number of gate types shown with the size of a circuit.
+const NB_TOP_GATE_TYPES: usize = 5;
+
+/// The size of a circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitSize {
+    pub degree_bits: usize,
+    /// The number of gates before plonky2 hashes the public inputs and pads the circuit.
+    pub gates: usize,
+    pub public_inputs: usize,
+    /// The number of instances of each gate type, from the most used.
+    pub gate_counts: Vec<(String, usize)>,
+}
+
+impl CircuitSize {
+    /// Returns a diff from the budgets to the size, with the quantities that exceed their budget,
+    /// or `None` if the size is within the budgets.
+    pub fn budget_diff(&self, max_gates: usize, max_public_inputs: usize) -> Option<String> {
+        let diff = [
+            ("gates", max_gates, self.gates),
+            ("public inputs", max_public_inputs, self.public_inputs),
+        ]
+        .into_iter()
+        .filter(|(_, max, actual)| actual > max)
+        .map(|(name, max, actual)| format!("- {}: {}\n+ {}: {}\n", name, max, name, actual))
+        .collect::<String>();
+        (!diff.is_empty()).then_some(diff)
+    }
+}
+
+impl Display for CircuitSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "degree bits: {}, gates: {}, public inputs: {}",
+            self.degree_bits, self.gates, self.public_inputs
+        )?;
+        writeln!(f, "top gate types:")?;
+        for (gate, count) in self.gate_counts.iter().take(NB_TOP_GATE_TYPES) {
+            writeln!(f, "{:>10}  {}", count, gate)?;
+        }
+        Ok(())
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Measures the size of the circuit. The circuit is built, and the gate types of its rows are
+    /// read from its selectors.
+    pub fn circuit_size(mut self) -> CircuitSize {
+        self.pre_build();
+        // The gates added by plonky2 when it builds the circuit come after these rows.
+        let gates = self.api.num_gates();
+        let data = self.api.build::<L::Config>();
+        CircuitSize {
+            degree_bits: data.common.degree_bits(),
+            gates,
+            public_inputs: data.common.num_public_inputs,
+            gate_counts: gate_counts::<L, D>(&data.prover_only, &data.common, gates),
+        }
+    }
+}
+
+/// Builds the circuit defined by `define`, and panics if its gates or its public inputs exceed the
+/// budgets, showing the excess as a diff and the most used gate types.
+///
+/// If the `UPDATE_CIRCUIT_SIZES` environment variable is set, the budgets of the call are rewritten
+/// in its source to the size of the circuit instead, so that budgets are updated after an
+/// intentional change with `UPDATE_CIRCUIT_SIZES=1 cargo test`. The budgets must be the last two
+/// arguments of the call, written as literals.
+#[track_caller]
+pub fn assert_circuit_size<L: PlonkParameters<D>, const D: usize>(
+    define: impl FnOnce(&mut CircuitBuilder<L, D>),
+    max_gates: usize,
+    max_public_inputs: usize,
+) {
+    let location = Location::caller();
+    let mut builder = CircuitBuilder::<L, D>::new();
+    define(&mut builder);
+    let size = builder.circuit_size();
+    debug!("Circuit size at {}:\n{}", location, size);
+
+    if env::var(UPDATE_CIRCUIT_SIZES).is_ok() {
+        if size.gates != max_gates || size.public_inputs != max_public_inputs {
+            update_budgets(location, size.gates, size.public_inputs);
+        }
+        return;
+    }
+    if let Some(diff) = size.budget_diff(max_gates, max_public_inputs) {
+        panic!(
+            "Circuit size at {} exceeds its budget:\n{}\n{}\nRerun with {}=1 to update the budgets.",
+            location, diff, size, UPDATE_CIRCUIT_SIZES
+        );
+    }
+}
+
+/// Rewrites the budgets of the call to `assert_circuit_size` at `location` in its source file.
+fn update_budgets(location: &Location, gates: usize, public_inputs: usize) {
+    // The path of the caller is relative to the root of its workspace, which contains the
+    // directory of its package.
+    let package_dir = env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| env::current_dir().unwrap());
+    let path = package_dir
+        .ancestors()
+        .map(|dir| dir.join(location.file()))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| panic!("Could not find the source file {}", location.file()));
+
+    let source = fs::read_to_string(&path).unwrap();
+    let source = rewrite_budgets(&source, location.line() as usize, gates, public_inputs)
+        .unwrap_or_else(|| panic!("Could not find the budgets of the call at {}", location));
+    fs::write(&path, source).unwrap();
+}
+
+/// Replaces the last two arguments of the first call to `assert_circuit_size` starting at the line
+/// `line` of `source` with `gates` and `public_inputs`.
+fn rewrite_budgets(
+    source: &str,
+    line: usize,
+    gates: usize,
+    public_inputs: usize,
+) -> Option<String> {
+    let line_start = source
+        .split_inclusive('\n')
+        .take(line - 1)
+        .map(str::len)
+        .sum::<usize>();
+    let call = line_start + source[line_start..].find("assert_circuit_size")?;
+    let open = call + source[call..].find('(')?;
+
+    // Find the commas between the arguments of the call and its closing parenthesis.
+    let mut depth = 0;
+    let mut commas = Vec::new();
+    let mut close = None;
+    for (i, c) in source[open..].char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(open + i);
+                    break;
+                }
+            }
+            ',' if depth == 1 => commas.push(open + i),
+            _ => {}
+        }
+    }
+    let mut end = close?;
+    if let Some(&last) = commas.last() {
+        if source[last + 1..end].trim().is_empty() {
+            end = last;
+            commas.pop();
+        }
+    }
+    let [.., gates_comma, public_inputs_comma] = commas[..] else {
+        return None;
+    };
+
+    // Replace the arguments, keeping the whitespace around them.
+    let replace = |start: usize, end: usize, value: usize| {
+        let arg = &source[start..end];
+        let leading = arg.len() - arg.trim_start().len();
+        let trailing = arg.trim_end().len();
+        format!("{}{}{}", &arg[..leading], value, &arg[trailing..])
+    };
+    Some(format!(
+        "{},{},{}{}",
+        &source[..gates_comma],
+        replace(gates_comma + 1, public_inputs_comma, gates),
+        replace(public_inputs_comma + 1, end, public_inputs),
+        &source[end..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_rewrite_budgets() {
+        let source = "fn test() {\n    assert_circuit_size(\n        |builder: &mut CircuitBuilder<L, D>| {\n            builder.add(a, b);\n        },\n        10,\n        4,\n    );\n}\n";
+        let expected = "fn test() {\n    assert_circuit_size(\n        |builder: &mut CircuitBuilder<L, D>| {\n            builder.add(a, b);\n        },\n        1200,\n        8,\n    );\n}\n";
+        assert_eq!(rewrite_budgets(source, 2, 1200, 8).unwrap(), expected);
+
+        let source = "size::assert_circuit_size(define, 10, 4);\n";
+        assert_eq!(
+            rewrite_budgets(source, 1, 3, 2).unwrap(),
+            "size::assert_circuit_size(define, 3, 2);\n"
+        );
+        assert_eq!(
+            rewrite_budgets("assert_circuit_size(define);", 1, 3, 2),
+            None
+        );
+    }
+
+    #[test]
+    fn test_budget_diff() {
+        let size = CircuitSize {
+            degree_bits: 12,
+            gates: 3000,
+            public_inputs: 8,
+            gate_counts: vec![("ArithmeticGate".to_string(), 2500)],
+        };
+        assert_eq!(size.budget_diff(3000, 8), None);
+        assert_eq!(
+            size.budget_diff(2000, 8).unwrap(),
+            "- gates: 2000\n+ gates: 3000\n"
+        );
+        assert_eq!(
+            size.budget_diff(2000, 4).unwrap(),
+            "- gates: 2000\n+ gates: 3000\n- public inputs: 4\n+ public inputs: 8\n"
+        );
+    }
+
+    #[test]
+    fn test_circuit_size_gate_counts() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let mut c = a;
+        for _ in 0..100 {
+            c = builder.mul(c, b);
+            c = builder.add(c, a);
+        }
+        builder.write(c);
+        let size = builder.circuit_size();
+
+        assert_eq!(
+            size.gate_counts
+                .iter()
+                .map(|(_, count)| count)
+                .sum::<usize>(),
+            size.gates
+        );
+        assert!(size.gate_counts.windows(2).all(|w| w[0].1 >= w[1].1));
+        let (gate, count) = &size.gate_counts[0];
+        assert!(gate.starts_with("ArithmeticGate"), "{}", gate);
+        assert!(*count >= 200 / 20);
+    }
+}
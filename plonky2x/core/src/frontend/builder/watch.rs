@@ -8,14 +8,21 @@ use plonky2::plonk::circuit_data::CommonCircuitData;
 use plonky2::util::serialization::{IoResult, Read, Write};
 
 use super::CircuitBuilder;
+use crate::backend::circuit::dump::record_watch;
 use crate::backend::circuit::PlonkParameters;
-use crate::prelude::CircuitVariable;
+use crate::prelude::{BoolVariable, CircuitVariable};
 
+/// A generator that logs the values of variables during witness generation.
+///
+/// The values are formatted with `CircuitVariable::format_value`. The values of a slice are logged
+/// on separate lines, annotated with their index. If the generator has a condition, the values are
+/// only logged when it is true.
 #[derive(Debug, Clone)]
 pub struct WatchGenerator<L: PlonkParameters<D>, const D: usize, V: CircuitVariable> {
     pub variables: Vec<V>,
     pub log: String,
     pub log_level: Level,
+    pub condition: Option<BoolVariable>,
     _phantom: PhantomData<L>,
 }
 
@@ -28,6 +35,30 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             variables: vec![variable],
             log,
             log_level: Level::Info,
+            condition: None,
+            _phantom: PhantomData,
+        };
+        self.add_simple_generator(generator);
+    }
+
+    /// Logs the decoded value of a variable of any type at the debug level, e.g. bytes as hex and
+    /// derived variables with the `Debug` of their value type.
+    pub fn watch_variables<V: CircuitVariable>(&mut self, variable: &V, log: &str) {
+        self.watch_with_level(variable, log, Level::Debug);
+    }
+
+    /// Logs the value of a variable at the debug level, only if `condition` is true.
+    pub fn watch_if<V: CircuitVariable>(
+        &mut self,
+        condition: BoolVariable,
+        variable: &V,
+        log: &str,
+    ) {
+        let generator: WatchGenerator<L, D, V> = WatchGenerator {
+            variables: vec![variable.clone()],
+            log: String::from(log),
+            log_level: Level::Debug,
+            condition: Some(condition),
             _phantom: PhantomData,
         };
         self.add_simple_generator(generator);
@@ -46,6 +77,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             variables: vec![variable],
             log,
             log_level,
+            condition: None,
             _phantom: PhantomData,
         };
         self.add_simple_generator(generator);
@@ -59,6 +91,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             variables,
             log,
             log_level: Level::Info,
+            condition: None,
             _phantom: PhantomData,
         };
         self.add_simple_generator(generator);
@@ -77,6 +110,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             variables,
             log,
             log_level,
+            condition: None,
             _phantom: PhantomData,
         };
         self.add_simple_generator(generator);
@@ -100,6 +134,7 @@ impl<L: PlonkParameters<D>, V: CircuitVariable, const D: usize> SimpleGenerator<
         self.variables
             .iter()
             .flat_map(|x| x.targets())
+            .chain(self.condition.iter().flat_map(|c| c.targets()))
             .collect::<Vec<Target>>()
     }
 
@@ -122,6 +157,14 @@ impl<L: PlonkParameters<D>, V: CircuitVariable, const D: usize> SimpleGenerator<
         };
         dst.write_usize(log_level_num)?;
 
+        match &self.condition {
+            Some(condition) => {
+                dst.write_usize(1)?;
+                dst.write_target_vec(&condition.targets())?;
+            }
+            None => dst.write_usize(0)?,
+        }
+
         dst.write_usize(self.variables.len())?;
         self.variables
             .iter()
@@ -151,6 +194,11 @@ impl<L: PlonkParameters<D>, V: CircuitVariable, const D: usize> SimpleGenerator<
             _ => panic!("Invalid log level"),
         };
 
+        let condition = match src.read_usize()? {
+            0 => None,
+            _ => Some(BoolVariable::from_targets(&src.read_target_vec()?)),
+        };
+
         let variables_len = src.read_usize()?;
         let mut variables = Vec::new();
 
@@ -163,6 +211,7 @@ impl<L: PlonkParameters<D>, V: CircuitVariable, const D: usize> SimpleGenerator<
             variables,
             log,
             log_level,
+            condition,
             _phantom: PhantomData,
         })
     }
@@ -172,14 +221,25 @@ impl<L: PlonkParameters<D>, V: CircuitVariable, const D: usize> SimpleGenerator<
         witness: &PartitionWitness<L::Field>,
         _out_buffer: &mut GeneratedValues<L::Field>,
     ) {
-        let values: Vec<V::ValueType<L::Field>> =
-            self.variables.iter().map(|x| x.get(witness)).collect();
-        let formatted_log = if values.len() == 1 {
-            format!("[Watch] {}: {:?}", self.log, values[0])
+        if let Some(condition) = &self.condition {
+            if !condition.get(witness) {
+                return;
+            }
+        }
+
+        let values: Vec<String> = self
+            .variables
+            .iter()
+            .map(|x| V::format_value::<L::Field>(&x.get(witness)))
+            .collect();
+        if values.len() == 1 {
+            log!(self.log_level, "[Watch] {}: {}", self.log, values[0]);
         } else {
-            format!("[Watch] {}: {:?}", self.log, values)
-        };
-        log!(self.log_level, "{}", formatted_log);
+            for (i, value) in values.iter().enumerate() {
+                log!(self.log_level, "[Watch] {}[{}]: {}", self.log, i, value);
+            }
+        }
+        record_watch(&self.log, values);
     }
 }
 
@@ -189,6 +249,7 @@ mod tests {
 
     use crate::prelude::*;
     use crate::utils;
+    use crate::utils::capture_logs;
 
     #[test]
     fn test_watcher() {
@@ -220,4 +281,68 @@ mod tests {
         let sum = output.read::<Variable>();
         debug!("{}", sum.0);
     }
+
+    #[test]
+    fn test_watch_variables() {
+        #[derive(Debug, Clone, CircuitVariable)]
+        struct Pair {
+            x: Variable,
+            y: ByteVariable,
+        }
+
+        let mut builder = DefaultBuilder::new();
+        let byte = builder.read::<ByteVariable>();
+        let bytes = builder.read::<BytesVariable<4>>();
+        let pair = builder.read::<Pair>();
+        let array = builder.read::<ArrayVariable<ByteVariable, 2>>();
+        builder.watch_variables(&byte, "byte");
+        builder.watch_variables(&bytes, "bytes");
+        builder.watch_variables(&pair, "pair");
+        builder.watch_variables(&array, "array");
+        builder.watch_slice(&array.data, "slice");
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.write::<ByteVariable>(0x2a);
+        input.write::<BytesVariable<4>>([0xde, 0xad, 0xbe, 0xef]);
+        input.write::<Pair>(PairValue {
+            x: GoldilocksField::from_canonical_u64(7),
+            y: 1,
+        });
+        input.write::<ArrayVariable<ByteVariable, 2>>(vec![1, 2]);
+        let (_, logs) = capture_logs(|| circuit.mock_prove(&input));
+
+        for expected in [
+            "[Watch] byte: 0x2a",
+            "[Watch] bytes: 0xdeadbeef",
+            "[Watch] pair: PairValue { x: 7, y: 1 }",
+            "[Watch] array: [0x01, 0x02]",
+            "[Watch] slice[0]: 0x01",
+            "[Watch] slice[1]: 0x02",
+        ] {
+            assert!(
+                logs.iter().any(|log| log == expected),
+                "{:?} not in {:?}",
+                expected,
+                logs
+            );
+        }
+    }
+
+    #[test]
+    fn test_watch_if() {
+        let mut builder = DefaultBuilder::new();
+        let condition = builder.read::<BoolVariable>();
+        let a = builder.read::<ByteVariable>();
+        builder.watch_if(condition, &a, "a");
+        let circuit = builder.mock_build();
+
+        for value in [true, false] {
+            let mut input = circuit.input();
+            input.write::<BoolVariable>(value);
+            input.write::<ByteVariable>(0xff);
+            let (_, logs) = capture_logs(|| circuit.mock_prove(&input));
+            assert_eq!(logs.contains(&"[Watch] a: 0xff".to_string()), value);
+        }
+    }
 }
@@ -0,0 +1,465 @@
+use num::BigUint;
+use plonky2::hash::hash_types::RichField;
+
+use super::field::{fp_from_decimal, Fp2Value, Fp2Variable, FpVariable};
+use crate::prelude::{
+    Add, BoolVariable, CircuitBuilder, CircuitVariable, Mul, Neg, One, PlonkParameters, Sub,
+    Variable, Zero,
+};
+
+/// An affine point on the curve `y^2 = x^3 + 4` over `Fp`, e.g. a public key. The point at
+/// infinity is not representable.
+#[derive(Debug, Clone, Copy, CircuitVariable)]
+#[value_name(G1Value)]
+#[value_derive(PartialEq, Eq)]
+pub struct G1Variable {
+    pub x: FpVariable,
+    pub y: FpVariable,
+}
+
+/// An affine point on the twist `y^2 = x^3 + 4 * (1 + u)` over `Fp2`, e.g. a signature. The point
+/// at infinity is not representable.
+#[derive(Debug, Clone, Copy, CircuitVariable)]
+#[value_name(G2Value)]
+#[value_derive(PartialEq, Eq)]
+pub struct G2Variable {
+    pub x: Fp2Variable,
+    pub y: Fp2Variable,
+}
+
+/// A point `(X : Y : Z)` in projective coordinates, standing for the affine point `(X / Z, Y / Z)`
+/// or for the point at infinity if `Z` is zero.
+#[derive(Debug, Clone, Copy)]
+pub struct ProjectivePoint<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+/// The field of the coordinates of G1 or G2, with the operations of the complete formulas.
+pub trait BlsCurveField<L: PlonkParameters<D>, const D: usize>:
+    CircuitVariable
+    + Copy
+    + Add<L, D, Output = Self>
+    + Sub<L, D, Output = Self>
+    + Mul<L, D, Output = Self>
+    + Neg<L, D, Output = Self>
+    + Zero<L, D>
+    + One<L, D>
+{
+    /// Three times the coefficient `b` of the curve `y^2 = x^3 + b` over this field.
+    fn b3(builder: &mut CircuitBuilder<L, D>) -> Self;
+}
+
+impl<L: PlonkParameters<D>, const D: usize> BlsCurveField<L, D> for FpVariable {
+    fn b3(builder: &mut CircuitBuilder<L, D>) -> Self {
+        builder.constant::<FpVariable>(BigUint::from(12u32))
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> BlsCurveField<L, D> for Fp2Variable {
+    fn b3(builder: &mut CircuitBuilder<L, D>) -> Self {
+        builder.bls_fp2_constant(BigUint::from(12u32), BigUint::from(12u32))
+    }
+}
+
+/// The absolute value of the parameter `x = -0xd201000000010000` of BLS12-381.
+pub(crate) const X_ABS: u64 = 0xd201000000010000;
+
+/// The generator of G1.
+const G1_GENERATOR: (&[u8], &[u8]) = (
+    b"17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb",
+    b"08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1",
+);
+
+/// The generator of G2, with the decimal coefficients of `x` and `y`.
+const G2_GENERATOR: [(&str, &str); 2] = [
+    (
+        "352701069587466618187139116011060144890029952792775240219908644239793785735715026873347600343865175952761926303160",
+        "3059144344244213709971259814753781636986470325476647558659373206291635324768958432433509563104347017837885763365758",
+    ),
+    (
+        "1985150602287291935568054521177171638300868978215655730859378665066344726373823718423869104263333984641494340347905",
+        "927553665492332455747201965776037880757740193453592970025027978793976877002675564980949289727957565575433344219582",
+    ),
+];
+
+// The constants of the endomorphism `psi`, which multiply the conjugates of `x` and `y`.
+const PSI_COEFF_X: (&str, &str) = (
+    "0",
+    "4002409555221667392624310435006688643935503118305586438271171395842971157480381377015405980053539358417135540939437",
+);
+const PSI_COEFF_Y: (&str, &str) = (
+    "2973677408986561043442465346520108879172042883009249989176415018091420807192182638567116318576472649347015917690530",
+    "1028732146235106349975324479215795277384839936929757896155643118032610843298655225875571310552543014690878354869257",
+);
+
+// The constant of `psi^2`, which multiplies `x`. It is an element of `Fp`.
+const PSI2_COEFF_X: &str = "4002409555221667392624310435006688643935503118305586438271171395842971157480381377015405980053539358417135540939436";
+
+// The cube root of unity `beta` of the endomorphism `sigma(x, y) = (beta * x, y)` of G1, which acts
+// as the multiplication by `-x^2` on G1.
+const SIGMA_BETA: &str = "793479390729215512621379701633421447060886740281060493010456487427281649075476305620758731620350";
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// The point at infinity `(0 : 1 : 0)`.
+    pub fn bls_projective_identity<T: BlsCurveField<L, D>>(&mut self) -> ProjectivePoint<T> {
+        ProjectivePoint {
+            x: self.zero(),
+            y: self.one(),
+            z: self.zero(),
+        }
+    }
+
+    /// Adds two points of G1 or G2 with the complete formulas for `a = 0` of Renes, Costello and
+    /// Batina (algorithm 7 of ePrint 2015/1060). Neither curve has a point of order two, so the
+    /// formulas hold for all inputs, including equal and opposite points and the point at
+    /// infinity.
+    pub fn bls_projective_add<T: BlsCurveField<L, D>>(
+        &mut self,
+        p: ProjectivePoint<T>,
+        q: ProjectivePoint<T>,
+    ) -> ProjectivePoint<T> {
+        let b3 = T::b3(self);
+        let (x1, y1, z1) = (p.x, p.y, p.z);
+        let (x2, y2, z2) = (q.x, q.y, q.z);
+
+        let t0 = self.mul(x1, x2);
+        let t1 = self.mul(y1, y2);
+        let t2 = self.mul(z1, z2);
+        let t3 = self.add(x1, y1);
+        let t4 = self.add(x2, y2);
+        let t3 = self.mul(t3, t4);
+        let t4 = self.add(t0, t1);
+        let t3 = self.sub(t3, t4);
+        let t4 = self.add(y1, z1);
+        let x3 = self.add(y2, z2);
+        let t4 = self.mul(t4, x3);
+        let x3 = self.add(t1, t2);
+        let t4 = self.sub(t4, x3);
+        let x3 = self.add(x1, z1);
+        let y3 = self.add(x2, z2);
+        let x3 = self.mul(x3, y3);
+        let y3 = self.add(t0, t2);
+        let y3 = self.sub(x3, y3);
+        let x3 = self.add(t0, t0);
+        let t0 = self.add(x3, t0);
+        let t2 = self.mul(b3, t2);
+        let z3 = self.add(t1, t2);
+        let t1 = self.sub(t1, t2);
+        let y3 = self.mul(b3, y3);
+        let x3 = self.mul(t4, y3);
+        let t2 = self.mul(t3, t1);
+        let x3 = self.sub(t2, x3);
+        let y3 = self.mul(y3, t0);
+        let t1 = self.mul(t1, z3);
+        let y3 = self.add(t1, y3);
+        let t0 = self.mul(t0, t3);
+        let z3 = self.mul(z3, t4);
+        let z3 = self.add(z3, t0);
+
+        ProjectivePoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    /// Computes `-p` for a point of G1 or G2.
+    pub fn bls_projective_neg<T: BlsCurveField<L, D>>(
+        &mut self,
+        p: ProjectivePoint<T>,
+    ) -> ProjectivePoint<T> {
+        ProjectivePoint {
+            x: p.x,
+            y: self.neg(p.y),
+            z: p.z,
+        }
+    }
+
+    /// Computes `[x] p` for the parameter `x` of the curve, with a double-and-add over its bits.
+    pub fn bls_projective_mul_by_x<T: BlsCurveField<L, D>>(
+        &mut self,
+        p: ProjectivePoint<T>,
+    ) -> ProjectivePoint<T> {
+        let mut result = p;
+        for i in (0..63).rev() {
+            result = self.bls_projective_add(result, result);
+            if (X_ABS >> i) & 1 == 1 {
+                result = self.bls_projective_add(result, p);
+            }
+        }
+        // The parameter is negative.
+        self.bls_projective_neg(result)
+    }
+
+    /// Selects `a` if `selector` is true and `b` otherwise.
+    pub fn bls_projective_select<T: BlsCurveField<L, D>>(
+        &mut self,
+        selector: BoolVariable,
+        a: ProjectivePoint<T>,
+        b: ProjectivePoint<T>,
+    ) -> ProjectivePoint<T> {
+        ProjectivePoint {
+            x: self.select(selector, a.x, b.x),
+            y: self.select(selector, a.y, b.y),
+            z: self.select(selector, a.z, b.z),
+        }
+    }
+
+    /// Returns whether two points of G1 or G2 are equal, comparing `X1 * Z2 == X2 * Z1` and
+    /// `Y1 * Z2 == Y2 * Z1`.
+    pub fn bls_projective_is_equal<T: BlsCurveField<L, D>>(
+        &mut self,
+        p: ProjectivePoint<T>,
+        q: ProjectivePoint<T>,
+    ) -> BoolVariable {
+        let mut cross = |a: T, b: T| {
+            let lhs = self.mul(a, q.z);
+            let rhs = self.mul(b, p.z);
+            self.is_equal(lhs, rhs)
+        };
+        let x_equal = cross(p.x, q.x);
+        let y_equal = cross(p.y, q.y);
+        self.and(x_equal, y_equal)
+    }
+
+    /// The generator of G1.
+    pub fn bls_g1_generator(&mut self) -> G1Variable {
+        let parse = |value: &[u8]| BigUint::parse_bytes(value, 16).unwrap();
+        self.constant::<G1Variable>(G1Value {
+            x: parse(G1_GENERATOR.0),
+            y: parse(G1_GENERATOR.1),
+        })
+    }
+
+    /// Computes `-p` for an affine point of G1.
+    pub fn bls_g1_neg(&mut self, p: G1Variable) -> G1Variable {
+        G1Variable {
+            x: p.x,
+            y: self.bls_fp_neg(p.y),
+        }
+    }
+
+    /// Returns whether `y^2 = x^3 + 4`.
+    pub fn bls_g1_is_on_curve(&mut self, p: G1Variable) -> BoolVariable {
+        let y_sq = self.bls_fp_mul(p.y, p.y);
+        let x_sq = self.bls_fp_mul(p.x, p.x);
+        let x_cube = self.bls_fp_mul(x_sq, p.x);
+        let b = self.constant::<FpVariable>(BigUint::from(4u32));
+        let rhs = self.bls_fp_add(x_cube, b);
+        self.is_equal(y_sq, rhs)
+    }
+
+    /// Converts an affine point of G1 to projective coordinates.
+    pub fn bls_g1_to_projective(&mut self, p: G1Variable) -> ProjectivePoint<FpVariable> {
+        ProjectivePoint {
+            x: p.x,
+            y: p.y,
+            z: self.one(),
+        }
+    }
+
+    /// Converts a point of G1 to affine coordinates. The circuit is unsatisfiable if it is the point
+    /// at infinity.
+    pub fn bls_g1_to_affine(&mut self, p: ProjectivePoint<FpVariable>) -> G1Variable {
+        let z_inv = self.bls_fp_inv(p.z);
+        G1Variable {
+            x: self.bls_fp_mul(p.x, z_inv),
+            y: self.bls_fp_mul(p.y, z_inv),
+        }
+    }
+
+    /// Returns whether a point of the curve is in G1, i.e. has the prime order `r`. This is the
+    /// case if and only if `sigma(P) == -[x^2] P` (Scott, ePrint 2021/1130).
+    pub fn bls_g1_is_in_subgroup(&mut self, p: G1Variable) -> BoolVariable {
+        let beta = self.constant::<FpVariable>(fp_from_decimal(SIGMA_BETA));
+        let p = self.bls_g1_to_projective(p);
+        let sigma_p = ProjectivePoint {
+            x: self.bls_fp_mul(p.x, beta),
+            y: p.y,
+            z: p.z,
+        };
+        let x_p = self.bls_projective_mul_by_x(p);
+        let x2_p = self.bls_projective_mul_by_x(x_p);
+        let neg_x2_p = self.bls_projective_neg(x2_p);
+        self.bls_projective_is_equal(sigma_p, neg_x2_p)
+    }
+
+    /// The generator of G2.
+    pub fn bls_g2_generator(&mut self) -> G2Variable {
+        let [x, y] = G2_GENERATOR;
+        self.constant::<G2Variable>(G2Value {
+            x: Fp2Value::from_decimal(x.0, x.1),
+            y: Fp2Value::from_decimal(y.0, y.1),
+        })
+    }
+
+    /// Returns whether `y^2 = x^3 + 4 * (1 + u)`.
+    pub fn bls_g2_is_on_curve(&mut self, p: G2Variable) -> BoolVariable {
+        let y_sq = self.bls_fp2_mul(p.y, p.y);
+        let x_sq = self.bls_fp2_mul(p.x, p.x);
+        let x_cube = self.bls_fp2_mul(x_sq, p.x);
+        let b = self.bls_fp2_constant(BigUint::from(4u32), BigUint::from(4u32));
+        let rhs = self.bls_fp2_add(x_cube, b);
+        self.is_equal(y_sq, rhs)
+    }
+
+    /// Converts an affine point of G2 to projective coordinates.
+    pub fn bls_g2_to_projective(&mut self, p: G2Variable) -> ProjectivePoint<Fp2Variable> {
+        ProjectivePoint {
+            x: p.x,
+            y: p.y,
+            z: self.one(),
+        }
+    }
+
+    /// Converts a point of G2 to affine coordinates. The circuit is unsatisfiable if it is the point
+    /// at infinity.
+    pub fn bls_g2_to_affine(&mut self, p: ProjectivePoint<Fp2Variable>) -> G2Variable {
+        let z_inv = self.bls_fp2_inv(p.z);
+        G2Variable {
+            x: self.bls_fp2_mul(p.x, z_inv),
+            y: self.bls_fp2_mul(p.y, z_inv),
+        }
+    }
+
+    /// Applies the endomorphism `psi = twist^-1 . frobenius . twist` of the twist.
+    pub fn bls_g2_psi(&mut self, p: ProjectivePoint<Fp2Variable>) -> ProjectivePoint<Fp2Variable> {
+        let coeff_x =
+            self.constant::<Fp2Variable>(Fp2Value::from_decimal(PSI_COEFF_X.0, PSI_COEFF_X.1));
+        let coeff_y =
+            self.constant::<Fp2Variable>(Fp2Value::from_decimal(PSI_COEFF_Y.0, PSI_COEFF_Y.1));
+        let x = self.bls_fp2_conjugate(p.x);
+        let y = self.bls_fp2_conjugate(p.y);
+        ProjectivePoint {
+            x: self.bls_fp2_mul(x, coeff_x),
+            y: self.bls_fp2_mul(y, coeff_y),
+            z: self.bls_fp2_conjugate(p.z),
+        }
+    }
+
+    /// Applies `psi^2`, which has a simpler form than two applications of `psi`.
+    pub fn bls_g2_psi2(&mut self, p: ProjectivePoint<Fp2Variable>) -> ProjectivePoint<Fp2Variable> {
+        let coeff_x = self.constant::<FpVariable>(fp_from_decimal(PSI2_COEFF_X));
+        ProjectivePoint {
+            x: self.bls_fp2_mul_by_fp(p.x, coeff_x),
+            y: self.bls_fp2_neg(p.y),
+            z: p.z,
+        }
+    }
+
+    /// Maps a point of the twist to G2 by multiplying it by the effective cofactor, with the method
+    /// of Budroni and Pintore used by the hash-to-curve standard (RFC 9380).
+    pub fn bls_g2_clear_cofactor(
+        &mut self,
+        p: ProjectivePoint<Fp2Variable>,
+    ) -> ProjectivePoint<Fp2Variable> {
+        // h(P) = psi^2(2P) + [x]([x]P + psi(P)) - [x]P - psi(P) - P.
+        let x_p = self.bls_projective_mul_by_x(p);
+        let psi_p = self.bls_g2_psi(p);
+        let t = self.bls_projective_add(x_p, psi_p);
+        let x_t = self.bls_projective_mul_by_x(t);
+
+        let double_p = self.bls_projective_add(p, p);
+        let mut result = self.bls_g2_psi2(double_p);
+        result = self.bls_projective_add(result, x_t);
+        for point in [x_p, psi_p, p] {
+            let neg_point = self.bls_projective_neg(point);
+            result = self.bls_projective_add(result, neg_point);
+        }
+        result
+    }
+
+    /// Returns whether a point of the twist is in G2, i.e. has the prime order `r`. This is the
+    /// case if and only if `psi(P) == [x] P` (Scott, ePrint 2021/1130).
+    pub fn bls_g2_is_in_subgroup(&mut self, p: G2Variable) -> BoolVariable {
+        let p = self.bls_g2_to_projective(p);
+        let psi_p = self.bls_g2_psi(p);
+        let x_p = self.bls_projective_mul_by_x(p);
+        self.bls_projective_is_equal(psi_p, x_p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{DefaultBuilder, GoldilocksField};
+
+    fn g2_value(x: (&str, &str), y: (&str, &str)) -> G2Value<GoldilocksField> {
+        G2Value {
+            x: Fp2Value::from_decimal(x.0, x.1),
+            y: Fp2Value::from_decimal(y.0, y.1),
+        }
+    }
+
+    #[test]
+    fn test_bls_g1_double() {
+        let mut builder = DefaultBuilder::new();
+        let generator = builder.bls_g1_generator();
+        let generator = builder.bls_g1_to_projective(generator);
+        let double = builder.bls_projective_add(generator, generator);
+        let double = builder.bls_g1_to_affine(double);
+        builder.write(double);
+
+        // The identity is neutral, and `P - P` is the identity.
+        let identity = builder.bls_projective_identity::<FpVariable>();
+        let sum = builder.bls_projective_add(identity, generator);
+        let sum = builder.bls_g1_to_affine(sum);
+        builder.write(sum);
+        let neg_generator = builder.bls_projective_neg(generator);
+        let zero = builder.bls_projective_add(generator, neg_generator);
+        let is_zero = builder.is_equal(zero.z, identity.z);
+        builder.write(is_zero);
+        let circuit = builder.mock_build();
+
+        let input = circuit.input();
+        let mut output = circuit.mock_prove(&input).output;
+        let parse = |value: &[u8]| BigUint::parse_bytes(value, 16).unwrap();
+        assert_eq!(
+            output.read::<G1Variable>(),
+            G1Value {
+                x: parse(b"0572cbea904d67468808c8eb50a9450c9721db309128012543902d0ac358a62ae28f75bb8f1c7c42c39a8c5529bf0f4e"),
+                y: parse(b"166a9d8cabc673a322fda673779d8e3822ba3ecb8670e461f73bb9021d5fd76a4c56d9d4cd16bd1bba86881979749d28"),
+            }
+        );
+        assert_eq!(
+            output.read::<G1Variable>(),
+            G1Value {
+                x: parse(G1_GENERATOR.0),
+                y: parse(G1_GENERATOR.1),
+            }
+        );
+        assert!(output.read::<BoolVariable>());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_bls_g2_is_in_subgroup() {
+        let mut builder = DefaultBuilder::new();
+        let p = builder.read::<G2Variable>();
+        let is_on_curve = builder.bls_g2_is_on_curve(p);
+        let is_in_subgroup = builder.bls_g2_is_in_subgroup(p);
+        builder.write(is_on_curve);
+        builder.write(is_in_subgroup);
+        let circuit = builder.mock_build();
+
+        let [x, y] = G2_GENERATOR;
+        let generator = g2_value(x, y);
+        // The point of the twist with `x = 2`, which is not in G2.
+        let outside = g2_value(
+            ("2", "0"),
+            (
+                "3813414062821088896965879244443358096636228247329175415943186029072982909461945441384695595240360445618611812101176",
+                "3568027680765585585945490907042741669558639753778547462314760963815399658271727325750766584361357481230047117262172",
+            ),
+        );
+        for (point, expected) in [(generator, true), (outside, false)] {
+            let mut input = circuit.input();
+            input.write::<G2Variable>(point);
+            let mut output = circuit.mock_prove(&input).output;
+            assert!(output.read::<BoolVariable>());
+            assert_eq!(output.read::<BoolVariable>(), expected);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! Decompression of G1 points from the 48-byte encoding of the Zcash serialization format, used for
+//! the public keys of the Ethereum consensus layer.
+
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::curve::G1Variable;
+use super::field::{fp_modulus, FpVariable};
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::vars::{BigUintVariable, EvmVariable, ValueStream, VariableStream};
+use crate::prelude::{
+    ArrayVariable, BoolVariable, ByteVariable, CircuitBuilder, CircuitVariable, PlonkParameters,
+};
+
+/// Recovers the y coordinate of the point of G1 with x coordinate `x` outside of the circuit,
+/// choosing the larger of the two roots if `is_largest` is set. An x coordinate that is not on the
+/// curve yields zero, which fails the constraints of `decompress_bls_g1`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct G1DecompressHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for G1DecompressHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let x = input_stream.read_value::<FpVariable>();
+        let is_largest = input_stream.read_value::<BoolVariable>();
+
+        // The modulus is 3 mod 4, so a square root of a square is its power (p + 1) / 4.
+        let modulus = fp_modulus();
+        let rhs = (x.modpow(&BigUint::from(3u32), &modulus) + 4u32) % &modulus;
+        let mut y = rhs.modpow(&((&modulus + 1u32) / 4u32), &modulus);
+        if &y * &y % &modulus != rhs {
+            y = BigUint::default();
+        }
+        if (y > (&modulus - 1u32) / 2u32) != is_largest {
+            y = (&modulus - y) % &modulus;
+        }
+        output_stream.write_value::<FpVariable>(y);
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Decompresses a point of G1 from its encoding, i.e. the big-endian x coordinate with three
+    /// flags in the most significant bits: the compression flag, the infinity flag and whether y
+    /// is the larger of its two possible values.
+    ///
+    /// The y coordinate is hinted, and constrained by the curve equation and the sign flag. The
+    /// circuit is unsatisfiable for invalid encodings, which includes a cleared compression flag,
+    /// the point at infinity, an x coordinate of at least `p` or not on the curve, and a point
+    /// that is not in G1.
+    pub fn decompress_bls_g1(&mut self, compressed: ArrayVariable<ByteVariable, 48>) -> G1Variable {
+        let _true = self._true();
+        let _false = self._false();
+        let mut bytes = compressed.as_vec();
+        let mut first_byte = bytes[0].as_be_bits();
+        let (is_compressed, is_infinity, is_largest) =
+            (first_byte[0], first_byte[1], first_byte[2]);
+        self.assert_is_equal(is_compressed, _true);
+        // The point at infinity is not representable, and is not a valid public key anyway.
+        self.assert_is_equal(is_infinity, _false);
+        first_byte[..3].copy_from_slice(&[_false; 3]);
+        bytes[0] = ByteVariable::from_be_bits(first_byte);
+        let x = FpVariable {
+            value: BigUintVariable::decode(self, &bytes),
+        };
+        x.assert_is_valid(self);
+
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&x);
+        input_stream.write(&is_largest);
+        let output_stream = self.hint(input_stream, G1DecompressHint);
+        let y = output_stream.read::<FpVariable>(self);
+        let point = G1Variable { x, y };
+
+        let is_on_curve = self.bls_g1_is_on_curve(point);
+        self.assert_is_equal(is_on_curve, _true);
+        let half = self.constant::<BigUintVariable<12>>((fp_modulus() - 1u32) / 2u32);
+        let is_larger = self.lt(half, y.value);
+        self.assert_is_equal(is_larger, is_largest);
+        let is_in_subgroup = self.bls_g1_is_in_subgroup(point);
+        self.assert_is_equal(is_in_subgroup, _true);
+
+        point
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::ecc::bls12_381::curve::G1Value;
+    use crate::prelude::{bytes, DefaultBuilder, GoldilocksField};
+
+    fn decompress(compressed: [u8; 48]) -> G1Value<GoldilocksField> {
+        let mut builder = DefaultBuilder::new();
+        let compressed_variable = builder.read::<ArrayVariable<ByteVariable, 48>>();
+        let point = builder.decompress_bls_g1(compressed_variable);
+        builder.write(point);
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<ByteVariable, 48>>(compressed.to_vec());
+        let mut output = circuit.mock_prove(&input).output;
+        output.read::<G1Variable>()
+    }
+
+    fn parse(value: &[u8]) -> BigUint {
+        BigUint::parse_bytes(value, 16).unwrap()
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_decompress_bls_g1() {
+        let generator = decompress(bytes!(
+            "0x97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"
+        ));
+        assert_eq!(
+            generator,
+            G1Value {
+                x: parse(b"17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"),
+                y: parse(b"08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1"),
+            }
+        );
+
+        // A public key, and the same key with the sign flag flipped, which is its negation.
+        let x = parse(b"15a254501b7733239ed3cec4d56737977bd09ede881d8a234560e83e5525017add3b1dcc3eabfb85e12a4131b19c253b");
+        let y = parse(b"09d4984959de1b75ed9379860a2c07d1576916055ddc967e28765e043968c02328b6b78f23414ad5e179c3cc7bbed432");
+        let pubkey = decompress(bytes!(
+            "0x95a254501b7733239ed3cec4d56737977bd09ede881d8a234560e83e5525017add3b1dcc3eabfb85e12a4131b19c253b"
+        ));
+        assert_eq!(
+            pubkey,
+            G1Value {
+                x: x.clone(),
+                y: y.clone()
+            }
+        );
+        let flipped = decompress(bytes!(
+            "0xb5a254501b7733239ed3cec4d56737977bd09ede881d8a234560e83e5525017add3b1dcc3eabfb85e12a4131b19c253b"
+        ));
+        assert_eq!(
+            flipped,
+            G1Value {
+                x,
+                y: fp_modulus() - y
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_decompress_bls_g1_not_on_curve() {
+        // x^3 + 4 is not a square for x = 1.
+        let mut compressed = [0u8; 48];
+        compressed[0] = 0x80;
+        compressed[47] = 1;
+        decompress(compressed);
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_decompress_bls_g1_not_in_subgroup() {
+        // (0, 2) is on the curve, but has order 3.
+        let mut compressed = [0u8; 48];
+        compressed[0] = 0x80;
+        decompress(compressed);
+    }
+}
@@ -0,0 +1,570 @@
+use array_macro::array;
+use num::BigUint;
+use plonky2::hash::hash_types::RichField;
+use serde::{Deserialize, Serialize};
+
+use super::native;
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
+use crate::frontend::vars::{BigUintVariable, ValueStream, VariableStream};
+use crate::prelude::{
+    Add, BoolVariable, CircuitBuilder, CircuitVariable, Mul, Neg, One, PlonkParameters, Sub,
+    Variable, Zero,
+};
+
+/// The number of u32 limbs of an element of the base field.
+const FP_LIMBS: usize = 12;
+
+/// The modulus `p` of the base field of BLS12-381.
+pub fn fp_modulus() -> BigUint {
+    BigUint::parse_bytes(
+        b"1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab",
+        16,
+    )
+    .unwrap()
+}
+
+/// Parses a decimal constant of the base field.
+pub(crate) fn fp_from_decimal(value: &str) -> BigUint {
+    BigUint::parse_bytes(value.as_bytes(), 10).unwrap()
+}
+
+/// An element of the base field `Fp` of BLS12-381. The value is always kept reduced modulo `p`, so
+/// two elements are equal if and only if their limbs are equal.
+#[derive(Debug, Clone, Copy)]
+pub struct FpVariable {
+    pub value: BigUintVariable<FP_LIMBS>,
+}
+
+impl FpVariable {
+    fn from_biguint_target_unsafe(value: &BigUintTarget) -> Self {
+        assert_eq!(value.num_limbs(), FP_LIMBS);
+        Self {
+            value: BigUintVariable {
+                limbs: array![i => value.limbs[i].into(); FP_LIMBS],
+            },
+        }
+    }
+
+    fn to_biguint_target(self) -> BigUintTarget {
+        self.value.to_biguint_target()
+    }
+}
+
+impl CircuitVariable for FpVariable {
+    type ValueType<F: RichField> = BigUint;
+
+    fn init_unsafe<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        Self {
+            value: BigUintVariable::init_unsafe(builder),
+        }
+    }
+
+    fn variables(&self) -> Vec<Variable> {
+        self.value.variables()
+    }
+
+    fn from_variables_unsafe(variables: &[Variable]) -> Self {
+        Self {
+            value: BigUintVariable::from_variables_unsafe(variables),
+        }
+    }
+
+    fn assert_is_valid<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        self.value.assert_is_valid(builder);
+        let modulus = builder.constant::<BigUintVariable<FP_LIMBS>>(fp_modulus());
+        let is_reduced = builder.lt(self.value, modulus);
+        let _true = builder._true();
+        builder.assert_is_equal(is_reduced, _true);
+    }
+
+    fn nb_elements() -> usize {
+        BigUintVariable::<FP_LIMBS>::nb_elements()
+    }
+
+    fn elements<F: RichField>(value: Self::ValueType<F>) -> Vec<F> {
+        BigUintVariable::<FP_LIMBS>::elements::<F>(value)
+    }
+
+    fn from_elements<F: RichField>(elements: &[F]) -> Self::ValueType<F> {
+        BigUintVariable::<FP_LIMBS>::from_elements::<F>(elements)
+    }
+}
+
+/// An element `c0 + c1 * u` of the quadratic extension `Fp2 = Fp[u] / (u^2 + 1)`.
+#[derive(Debug, Clone, Copy, CircuitVariable)]
+#[value_name(Fp2Value)]
+#[value_derive(PartialEq, Eq)]
+pub struct Fp2Variable {
+    pub c0: FpVariable,
+    pub c1: FpVariable,
+}
+
+impl<F: RichField> Fp2Value<F> {
+    pub fn new(c0: BigUint, c1: BigUint) -> Self {
+        Self { c0, c1 }
+    }
+
+    /// Parses an element from the decimal strings of its coefficients.
+    pub(crate) fn from_decimal(c0: &str, c1: &str) -> Self {
+        Self::new(fp_from_decimal(c0), fp_from_decimal(c1))
+    }
+
+    pub(crate) fn from_native(value: native::Fp2) -> Self {
+        Self::new(value.0, value.1)
+    }
+
+    pub(crate) fn to_native(&self) -> native::Fp2 {
+        (self.c0.clone(), self.c1.clone())
+    }
+}
+
+/// The unreduced terms of a sum of products in `Fp2`, which are reduced at once by
+/// `bls_fp2_reduce`. Each term is below `p^2`, and a subtracted term is offset by `p^2` so that
+/// the sum of each coefficient never underflows.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Fp2Accumulator {
+    c0: (Vec<BigUintTarget>, Vec<BigUintTarget>),
+    c1: (Vec<BigUintTarget>, Vec<BigUintTarget>),
+}
+
+impl Fp2Accumulator {
+    /// Adds `times * x * y`, multiplied by the non-residue `1 + u` if `nonresidue` is set. The four
+    /// products of the coefficients are only computed once.
+    pub(crate) fn add_product<L: PlonkParameters<D>, const D: usize>(
+        &mut self,
+        builder: &mut CircuitBuilder<L, D>,
+        x: Fp2Variable,
+        y: Fp2Variable,
+        nonresidue: bool,
+        times: usize,
+    ) {
+        let mut mul = |a: FpVariable, b: FpVariable| {
+            builder
+                .api
+                .mul_biguint(&a.to_biguint_target(), &b.to_biguint_target())
+        };
+        let (x0y0, x1y1) = (mul(x.c0, y.c0), mul(x.c1, y.c1));
+        let (x0y1, x1y0) = (mul(x.c0, y.c1), mul(x.c1, y.c0));
+        for _ in 0..times {
+            self.push(
+                [x0y0.clone()],
+                [x1y1.clone()],
+                [x0y1.clone(), x1y0.clone()],
+                nonresidue,
+            );
+        }
+    }
+
+    /// Adds `x`, multiplied by the non-residue `1 + u` if `nonresidue` is set.
+    pub(crate) fn add(&mut self, x: Fp2Variable, nonresidue: bool) {
+        self.push(
+            [x.c0.to_biguint_target()],
+            [],
+            [x.c1.to_biguint_target()],
+            nonresidue,
+        );
+    }
+
+    /// Subtracts `x`.
+    pub(crate) fn sub(&mut self, x: Fp2Variable) {
+        self.c0.1.push(x.c0.to_biguint_target());
+        self.c1.1.push(x.c1.to_biguint_target());
+    }
+
+    /// Adds `(re_pos - re_neg) + im * u`, multiplied by `1 + u` if `nonresidue` is set, in which
+    /// case it is `(re_pos - re_neg - im) + (re_pos - re_neg + im) * u`.
+    fn push<const P: usize, const N: usize, const I: usize>(
+        &mut self,
+        re_pos: [BigUintTarget; P],
+        re_neg: [BigUintTarget; N],
+        im: [BigUintTarget; I],
+        nonresidue: bool,
+    ) {
+        self.c0.0.extend(re_pos.iter().cloned());
+        self.c0.1.extend(re_neg.iter().cloned());
+        if nonresidue {
+            self.c0.1.extend(im.iter().cloned());
+            self.c1.0.extend(re_pos);
+            self.c1.1.extend(re_neg);
+            self.c1.0.extend(im);
+        } else {
+            self.c1.0.extend(im);
+        }
+    }
+}
+
+/// Computes the inverse of an element of `Fp` outside of the circuit. Zero yields zero, which
+/// `bls_fp_inv` then rejects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FpInvHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for FpInvHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let a = input_stream.read_value::<FpVariable>();
+        output_stream.write_value::<FpVariable>(native::fp_inv(&a));
+    }
+}
+
+/// Computes the inverse of an element of `Fp2` outside of the circuit. Zero yields zero, which
+/// `bls_fp2_inv` then rejects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fp2InvHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for Fp2InvHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let a = input_stream.read_value::<Fp2Variable>();
+        let inverse = native::fp2_inv(&a.to_native());
+        output_stream.write_value::<Fp2Variable>(Fp2Value::from_native(inverse));
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Reduces `value` modulo `p` with a single hinted division.
+    fn bls_fp_reduce_target(&mut self, value: &BigUintTarget) -> FpVariable {
+        let modulus = self.api.constant_biguint(&fp_modulus());
+        // The modulus is above 2^352, so the quotient always fits in the `value.num_limbs() - 11`
+        // limbs allocated by the unsafe variant.
+        let (_, rem) = self.api.div_rem_biguint_unsafe(value, &modulus);
+        FpVariable::from_biguint_target_unsafe(&rem)
+    }
+
+    /// Reduces an integer of at least twelve limbs modulo `p`, e.g. the wide output of a hash.
+    pub fn bls_fp_reduce<const LIMBS: usize>(
+        &mut self,
+        value: BigUintVariable<LIMBS>,
+    ) -> FpVariable {
+        assert!(
+            LIMBS >= FP_LIMBS,
+            "the value must have at least twelve limbs"
+        );
+        self.bls_fp_reduce_target(&value.to_biguint_target())
+    }
+
+    /// Reduces `sum(positive) - sum(negative)`, where every term is below `p^2`.
+    fn bls_fp_reduce_terms(
+        &mut self,
+        positive: &[BigUintTarget],
+        negative: &[BigUintTarget],
+    ) -> FpVariable {
+        assert!(
+            !positive.is_empty() || !negative.is_empty(),
+            "no terms to reduce"
+        );
+        let modulus = fp_modulus();
+        let offset = &modulus * &modulus * BigUint::from(negative.len());
+        // Each subtraction removes less than the `p^2` that the offset reserves for it, so the sum
+        // never underflows. The offset is a multiple of `p`, so it does not change the result.
+        let mut sum = self.api.constant_biguint(&offset);
+        for term in negative {
+            sum = self.api.sub_biguint(&sum, term);
+        }
+        for term in positive {
+            sum = self.api.add_biguint(&sum, term);
+        }
+        self.bls_fp_reduce_target(&sum)
+    }
+
+    /// Computes `a + b` in `Fp`.
+    pub fn bls_fp_add(&mut self, a: FpVariable, b: FpVariable) -> FpVariable {
+        self.bls_fp_reduce_terms(&[a.to_biguint_target(), b.to_biguint_target()], &[])
+    }
+
+    /// Computes `a - b` in `Fp`.
+    pub fn bls_fp_sub(&mut self, a: FpVariable, b: FpVariable) -> FpVariable {
+        self.bls_fp_reduce_terms(&[a.to_biguint_target()], &[b.to_biguint_target()])
+    }
+
+    /// Computes `-a` in `Fp`.
+    pub fn bls_fp_neg(&mut self, a: FpVariable) -> FpVariable {
+        self.bls_fp_reduce_terms(&[], &[a.to_biguint_target()])
+    }
+
+    /// Computes `a * b` in `Fp`.
+    pub fn bls_fp_mul(&mut self, a: FpVariable, b: FpVariable) -> FpVariable {
+        let product = self
+            .api
+            .mul_biguint(&a.to_biguint_target(), &b.to_biguint_target());
+        self.bls_fp_reduce_target(&product)
+    }
+
+    /// Computes the inverse of `a` in `Fp`. The circuit is unsatisfiable if `a` is zero.
+    pub fn bls_fp_inv(&mut self, a: FpVariable) -> FpVariable {
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&a);
+        let output_stream = self.hint(input_stream, FpInvHint);
+        let inverse = output_stream.read::<FpVariable>(self);
+
+        let product = self.bls_fp_mul(a, inverse);
+        let one = self.constant::<FpVariable>(BigUint::from(1u32));
+        self.assert_is_equal(product, one);
+
+        inverse
+    }
+
+    /// Returns whether `a`, as an integer in `[0, p)`, is odd.
+    pub fn bls_fp_is_odd(&mut self, a: FpVariable) -> BoolVariable {
+        a.value.limbs[0].to_be_bits(self)[31]
+    }
+
+    /// Initializes an element of `Fp2` with a constant value in the circuit.
+    pub fn bls_fp2_constant(&mut self, c0: BigUint, c1: BigUint) -> Fp2Variable {
+        self.constant::<Fp2Variable>(Fp2Value::new(c0, c1))
+    }
+
+    /// Reduces the terms of `accumulator`, with a single reduction per coefficient.
+    pub(crate) fn bls_fp2_reduce(&mut self, accumulator: Fp2Accumulator) -> Fp2Variable {
+        let Fp2Accumulator { c0, c1 } = accumulator;
+        Fp2Variable {
+            c0: self.bls_fp_reduce_terms(&c0.0, &c0.1),
+            c1: self.bls_fp_reduce_terms(&c1.0, &c1.1),
+        }
+    }
+
+    /// Computes `a + b` in `Fp2`.
+    pub fn bls_fp2_add(&mut self, a: Fp2Variable, b: Fp2Variable) -> Fp2Variable {
+        let mut sum = Fp2Accumulator::default();
+        sum.add(a, false);
+        sum.add(b, false);
+        self.bls_fp2_reduce(sum)
+    }
+
+    /// Computes `a - b` in `Fp2`.
+    pub fn bls_fp2_sub(&mut self, a: Fp2Variable, b: Fp2Variable) -> Fp2Variable {
+        let mut sum = Fp2Accumulator::default();
+        sum.add(a, false);
+        sum.sub(b);
+        self.bls_fp2_reduce(sum)
+    }
+
+    /// Computes `-a` in `Fp2`.
+    pub fn bls_fp2_neg(&mut self, a: Fp2Variable) -> Fp2Variable {
+        let mut sum = Fp2Accumulator::default();
+        sum.sub(a);
+        self.bls_fp2_reduce(sum)
+    }
+
+    /// Computes `a * b` in `Fp2`.
+    pub fn bls_fp2_mul(&mut self, a: Fp2Variable, b: Fp2Variable) -> Fp2Variable {
+        let mut sum = Fp2Accumulator::default();
+        sum.add_product(self, a, b, false, 1);
+        self.bls_fp2_reduce(sum)
+    }
+
+    /// Computes `a * b` for `a` in `Fp2` and `b` in `Fp`.
+    pub fn bls_fp2_mul_by_fp(&mut self, a: Fp2Variable, b: FpVariable) -> Fp2Variable {
+        Fp2Variable {
+            c0: self.bls_fp_mul(a.c0, b),
+            c1: self.bls_fp_mul(a.c1, b),
+        }
+    }
+
+    /// Computes the conjugate `c0 - c1 * u` of `a`, which is also its image by the Frobenius map.
+    pub fn bls_fp2_conjugate(&mut self, a: Fp2Variable) -> Fp2Variable {
+        Fp2Variable {
+            c0: a.c0,
+            c1: self.bls_fp_neg(a.c1),
+        }
+    }
+
+    /// Computes the inverse of `a` in `Fp2`. The circuit is unsatisfiable if `a` is zero.
+    pub fn bls_fp2_inv(&mut self, a: Fp2Variable) -> Fp2Variable {
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&a);
+        let output_stream = self.hint(input_stream, Fp2InvHint);
+        let inverse = output_stream.read::<Fp2Variable>(self);
+
+        let product = self.bls_fp2_mul(a, inverse);
+        let one = self.one::<Fp2Variable>();
+        self.assert_is_equal(product, one);
+
+        inverse
+    }
+
+    /// Computes `a / b` in `Fp2`. The circuit is unsatisfiable if `b` is zero.
+    pub fn bls_fp2_div(&mut self, a: Fp2Variable, b: Fp2Variable) -> Fp2Variable {
+        let inverse = self.bls_fp2_inv(b);
+        self.bls_fp2_mul(a, inverse)
+    }
+
+    /// Returns the sign of `a` as defined by the hash-to-curve standard (RFC 9380), i.e. the parity
+    /// of `c0`, or of `c1` if `c0` is zero.
+    pub fn bls_fp2_sgn0(&mut self, a: Fp2Variable) -> BoolVariable {
+        let sign_0 = self.bls_fp_is_odd(a.c0);
+        let zero = self.zero::<FpVariable>();
+        let zero_0 = self.is_equal(a.c0, zero);
+        let sign_1 = self.bls_fp_is_odd(a.c1);
+        let zero_and_sign_1 = self.and(zero_0, sign_1);
+        self.or(sign_0, zero_and_sign_1)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Zero<L, D> for FpVariable {
+    fn zero(builder: &mut CircuitBuilder<L, D>) -> Self {
+        builder.constant::<FpVariable>(BigUint::from(0u32))
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> One<L, D> for FpVariable {
+    fn one(builder: &mut CircuitBuilder<L, D>) -> Self {
+        builder.constant::<FpVariable>(BigUint::from(1u32))
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Add<L, D> for FpVariable {
+    type Output = Self;
+
+    fn add(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        builder.bls_fp_add(self, rhs)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Sub<L, D> for FpVariable {
+    type Output = Self;
+
+    fn sub(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        builder.bls_fp_sub(self, rhs)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Mul<L, D> for FpVariable {
+    type Output = Self;
+
+    fn mul(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        builder.bls_fp_mul(self, rhs)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Neg<L, D> for FpVariable {
+    type Output = Self;
+
+    fn neg(self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        builder.bls_fp_neg(self)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Zero<L, D> for Fp2Variable {
+    fn zero(builder: &mut CircuitBuilder<L, D>) -> Self {
+        builder.bls_fp2_constant(BigUint::from(0u32), BigUint::from(0u32))
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> One<L, D> for Fp2Variable {
+    fn one(builder: &mut CircuitBuilder<L, D>) -> Self {
+        builder.bls_fp2_constant(BigUint::from(1u32), BigUint::from(0u32))
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Add<L, D> for Fp2Variable {
+    type Output = Self;
+
+    fn add(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        builder.bls_fp2_add(self, rhs)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Sub<L, D> for Fp2Variable {
+    type Output = Self;
+
+    fn sub(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        builder.bls_fp2_sub(self, rhs)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Mul<L, D> for Fp2Variable {
+    type Output = Self;
+
+    fn mul(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        builder.bls_fp2_mul(self, rhs)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Neg<L, D> for Fp2Variable {
+    type Output = Self;
+
+    fn neg(self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        builder.bls_fp2_neg(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::RandBigInt;
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::prelude::{DefaultBuilder, GoldilocksField};
+
+    type Value = Fp2Value<GoldilocksField>;
+
+    #[test]
+    fn test_bls_fp2_arithmetic() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Fp2Variable>();
+        let b = builder.read::<Fp2Variable>();
+        let results = [
+            builder.bls_fp2_add(a, b),
+            builder.bls_fp2_sub(a, b),
+            builder.bls_fp2_neg(a),
+            builder.bls_fp2_mul(a, b),
+            builder.bls_fp2_inv(b),
+        ];
+        for result in results {
+            builder.write(result);
+        }
+        let sgn0 = builder.bls_fp2_sgn0(a);
+        builder.write(sgn0);
+        let circuit = builder.mock_build();
+
+        let modulus = fp_modulus();
+        let max = &modulus - 1u32;
+        let mut rng = OsRng;
+        let mut cases = vec![(
+            (max.clone(), max.clone()),
+            (max.clone(), BigUint::from(1u32)),
+        )];
+        for _ in 0..3 {
+            let mut random = || rng.gen_biguint_below(&modulus);
+            cases.push(((random(), random()), (random(), random())));
+        }
+        for (a, b) in cases {
+            let mut input = circuit.input();
+            input.write::<Fp2Variable>(Value::from_native(a.clone()));
+            input.write::<Fp2Variable>(Value::from_native(b.clone()));
+            let mut output = circuit.mock_prove(&input).output;
+
+            let zero = (BigUint::from(0u32), BigUint::from(0u32));
+            let expected = [
+                native::fp2_add(&a, &b),
+                native::fp2_sub(&a, &b),
+                native::fp2_sub(&zero, &a),
+                native::fp2_mul(&a, &b),
+                native::fp2_inv(&b),
+            ];
+            for expected in expected {
+                assert_eq!(output.read::<Fp2Variable>(), Value::from_native(expected));
+            }
+            let sgn0 = a.0.bit(0) || (a.0 == BigUint::from(0u32) && a.1.bit(0));
+            assert_eq!(output.read::<BoolVariable>(), sgn0);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bls_fp2_inv_zero() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Fp2Variable>();
+        let inverse = builder.bls_fp2_inv(a);
+        builder.write(inverse);
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.write::<Fp2Variable>(Value::new(BigUint::from(0u32), BigUint::from(0u32)));
+        circuit.mock_prove(&input);
+    }
+}
@@ -0,0 +1,392 @@
+//! Hashing to G2 with the `BLS12381G2_XMD:SHA-256_SSWU_RO_` suite of RFC 9380.
+
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::curve::{G2Variable, ProjectivePoint};
+use super::field::{fp_modulus, Fp2Accumulator, Fp2Value, Fp2Variable};
+use super::native;
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::vars::{BigUintVariable, EvmVariable, ValueStream, VariableStream};
+use crate::prelude::{
+    BoolVariable, ByteVariable, Bytes32Variable, CircuitBuilder, PlonkParameters,
+};
+
+/// The domain separation tag of the signatures of the Ethereum consensus layer, which use the
+/// proof of possession scheme of the IETF BLS signature draft.
+pub const ETH_SIGNATURE_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+// The coefficients `A' = 240 * u` and `B' = 1012 * (1 + u)` of the curve `E'` that is 3-isogenous
+// to the twist, and the non-square `Z = -(2 + u)` of the simplified SWU map.
+const SSWU_A: (u32, u32) = (0, 240);
+const SSWU_B: (u32, u32) = (1012, 1012);
+
+// The coefficients of the polynomials of the 3-isogeny from `E'` to the twist, in ascending order
+// of degree, as decimal coefficients of elements of `Fp2` (RFC 9380, appendix E.3).
+const ISO_X_NUM: [(&str, &str); 4] = [
+    (
+        "889424345604814976315064405719089812568196182208668418962679585805340366775741747653930584250892369786198727235542",
+        "889424345604814976315064405719089812568196182208668418962679585805340366775741747653930584250892369786198727235542",
+    ),
+    (
+        "0",
+        "2668273036814444928945193217157269437704588546626005256888038757416021100327225242961791752752677109358596181706522",
+    ),
+    (
+        "2668273036814444928945193217157269437704588546626005256888038757416021100327225242961791752752677109358596181706526",
+        "1334136518407222464472596608578634718852294273313002628444019378708010550163612621480895876376338554679298090853261",
+    ),
+    (
+        "3557697382419259905260257622876359250272784728834673675850718343221361467102966990615722337003569479144794908942033",
+        "0",
+    ),
+];
+
+const ISO_X_DEN: [(&str, &str); 3] = [
+    (
+        "0",
+        "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559715",
+    ),
+    (
+        "12",
+        "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559775",
+    ),
+    ("1", "0"),
+];
+
+const ISO_Y_NUM: [(&str, &str); 4] = [
+    (
+        "3261222600550988246488569487636662646083386001431784202863158481286248011511053074731078808919938689216061999863558",
+        "3261222600550988246488569487636662646083386001431784202863158481286248011511053074731078808919938689216061999863558",
+    ),
+    (
+        "0",
+        "889424345604814976315064405719089812568196182208668418962679585805340366775741747653930584250892369786198727235518",
+    ),
+    (
+        "2668273036814444928945193217157269437704588546626005256888038757416021100327225242961791752752677109358596181706524",
+        "1334136518407222464472596608578634718852294273313002628444019378708010550163612621480895876376338554679298090853263",
+    ),
+    (
+        "2816510427748580758331037284777117739799287910327449993381818688383577828123182200904113516794492504322962636245776",
+        "0",
+    ),
+];
+
+const ISO_Y_DEN: [(&str, &str); 4] = [
+    (
+        "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559355",
+        "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559355",
+    ),
+    (
+        "0",
+        "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559571",
+    ),
+    (
+        "18",
+        "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559769",
+    ),
+    ("1", "0"),
+];
+
+fn fp2_from_u32s((c0, c1): (u32, u32)) -> native::Fp2 {
+    (BigUint::from(c0), BigUint::from(c1))
+}
+
+/// The non-square `Z = -(2 + u)` of the simplified SWU map.
+fn sswu_z() -> native::Fp2 {
+    let modulus = fp_modulus();
+    (&modulus - 2u32, &modulus - 1u32)
+}
+
+/// Finds a square root of `a` if it is a square, and of `b` otherwise, along with whether `a` is a
+/// square. The simplified SWU map guarantees that one of them is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fp2SqrtHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for Fp2SqrtHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let a = input_stream.read_value::<Fp2Variable>().to_native();
+        let b = input_stream.read_value::<Fp2Variable>().to_native();
+
+        let (is_square, root) = match native::fp2_sqrt(&a) {
+            Some(root) => (true, root),
+            None => (
+                false,
+                native::fp2_sqrt(&b).unwrap_or((BigUint::from(0u32), BigUint::from(0u32))),
+            ),
+        };
+
+        output_stream.write_value::<BoolVariable>(is_square);
+        output_stream.write_value::<Fp2Variable>(Fp2Value::from_native(root));
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Expands `message` into `len_in_bytes` uniformly random bytes with `expand_message_xmd` and
+    /// SHA-256 (RFC 9380, section 5.3.1).
+    pub fn bls_expand_message_xmd(
+        &mut self,
+        message: &[ByteVariable],
+        dst: &[u8],
+        len_in_bytes: usize,
+    ) -> Vec<ByteVariable> {
+        let ell = (len_in_bytes + 31) / 32;
+        assert!(
+            ell <= 255 && len_in_bytes <= 65535,
+            "the output is too long"
+        );
+        assert!(dst.len() <= 255, "the domain separation tag is too long");
+
+        let mut constant_bytes = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|byte| self.constant::<ByteVariable>(*byte))
+                .collect::<Vec<_>>()
+        };
+        let mut dst_prime = constant_bytes(dst);
+        dst_prime.extend(constant_bytes(&[dst.len() as u8]));
+
+        let mut input = constant_bytes(&[0u8; 64]);
+        input.extend_from_slice(message);
+        input.extend(constant_bytes(&[
+            (len_in_bytes >> 8) as u8,
+            len_in_bytes as u8,
+            0,
+        ]));
+        input.extend_from_slice(&dst_prime);
+        let b_0 = self.sha256_accelerated(&input);
+
+        let mut uniform_bytes = Vec::with_capacity(ell * 32);
+        let mut b_i: Option<Bytes32Variable> = None;
+        for i in 1..=ell {
+            // b_1 = H(b_0 || 1 || DST'), and b_i = H((b_0 xor b_(i - 1)) || i || DST').
+            let chained = match b_i {
+                Some(b_prev) => self.xor(b_0, b_prev),
+                None => b_0,
+            };
+            let mut input = chained.as_bytes().to_vec();
+            input.push(self.constant::<ByteVariable>(i as u8));
+            input.extend_from_slice(&dst_prime);
+            let digest = self.sha256_accelerated(&input);
+            uniform_bytes.extend(digest.as_bytes());
+            b_i = Some(digest);
+        }
+        uniform_bytes.truncate(len_in_bytes);
+        uniform_bytes
+    }
+
+    /// Hashes `message` to two elements of `Fp2` (RFC 9380, section 5.2). Each coefficient is
+    /// reduced from 64 uniform bytes, so it is statistically close to uniform.
+    pub fn bls_hash_to_field_fp2(
+        &mut self,
+        message: &[ByteVariable],
+        dst: &[u8],
+    ) -> [Fp2Variable; 2] {
+        let uniform_bytes = self.bls_expand_message_xmd(message, dst, 256);
+        let coefficients = uniform_bytes
+            .chunks(64)
+            .map(|chunk| {
+                let value = BigUintVariable::<16>::decode(self, chunk);
+                self.bls_fp_reduce(value)
+            })
+            .collect::<Vec<_>>();
+        [
+            Fp2Variable {
+                c0: coefficients[0],
+                c1: coefficients[1],
+            },
+            Fp2Variable {
+                c0: coefficients[2],
+                c1: coefficients[3],
+            },
+        ]
+    }
+
+    /// Computes `x^3 + A' * x + B'`, the right-hand side of the equation of `E'`.
+    fn bls_sswu_curve_rhs(&mut self, x: Fp2Variable) -> Fp2Variable {
+        let a = self.constant::<Fp2Variable>(Fp2Value::from_native(fp2_from_u32s(SSWU_A)));
+        let b = self.constant::<Fp2Variable>(Fp2Value::from_native(fp2_from_u32s(SSWU_B)));
+        let x_sq = self.bls_fp2_mul(x, x);
+        let mut sum = Fp2Accumulator::default();
+        sum.add_product(self, x_sq, x, false, 1);
+        sum.add_product(self, a, x, false, 1);
+        sum.add(b, false);
+        self.bls_fp2_reduce(sum)
+    }
+
+    /// Maps an element of `Fp2` to an affine point of `E'` with the simplified SWU map (RFC 9380,
+    /// section 6.6.2).
+    ///
+    /// The square root is hinted along with whether `g(x1)` is a square, and constrained by
+    /// `y^2 == g(x1)` or `y^2 == g(x2)`. Since `g(x2) = Z^3 * u^6 * g(x1)` with `Z` a non-square,
+    /// only one of them is a square and the prover cannot choose, except when `g(x1)` is zero,
+    /// which does not happen for the outputs of `bls_hash_to_field_fp2` but with negligible
+    /// probability.
+    pub fn bls_map_to_curve_sswu(&mut self, u: Fp2Variable) -> (Fp2Variable, Fp2Variable) {
+        let a = fp2_from_u32s(SSWU_A);
+        let b = fp2_from_u32s(SSWU_B);
+        let z = sswu_z();
+        let zero = (BigUint::from(0u32), BigUint::from(0u32));
+        let neg_b_over_a = native::fp2_sub(&zero, &native::fp2_mul(&b, &native::fp2_inv(&a)));
+        let b_over_za = native::fp2_mul(&b, &native::fp2_inv(&native::fp2_mul(&z, &a)));
+        let z = self.constant::<Fp2Variable>(Fp2Value::from_native(z));
+        let neg_b_over_a = self.constant::<Fp2Variable>(Fp2Value::from_native(neg_b_over_a));
+        let b_over_za = self.constant::<Fp2Variable>(Fp2Value::from_native(b_over_za));
+
+        // x1 = (-B' / A') * (1 + 1 / (Z^2 u^4 + Z u^2)), or B' / (Z A') if the denominator is zero.
+        let u_sq = self.bls_fp2_mul(u, u);
+        let z_u_sq = self.bls_fp2_mul(z, u_sq);
+        let mut den = Fp2Accumulator::default();
+        den.add_product(self, z_u_sq, z_u_sq, false, 1);
+        den.add(z_u_sq, false);
+        let den = self.bls_fp2_reduce(den);
+        let zero = self.zero::<Fp2Variable>();
+        let one = self.one::<Fp2Variable>();
+        let den_is_zero = self.is_equal(den, zero);
+        let den = self.select(den_is_zero, one, den);
+        let den_inv = self.bls_fp2_inv(den);
+        let one_plus_den_inv = self.bls_fp2_add(one, den_inv);
+        let x1 = self.bls_fp2_mul(neg_b_over_a, one_plus_den_inv);
+        let x1 = self.select(den_is_zero, b_over_za, x1);
+        let gx1 = self.bls_sswu_curve_rhs(x1);
+
+        let x2 = self.bls_fp2_mul(z_u_sq, x1);
+        let gx2 = self.bls_sswu_curve_rhs(x2);
+
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&gx1);
+        input_stream.write(&gx2);
+        let output_stream = self.hint(input_stream, Fp2SqrtHint);
+        let is_square = output_stream.read::<BoolVariable>(self);
+        let y = output_stream.read::<Fp2Variable>(self);
+
+        let y_sq = self.bls_fp2_mul(y, y);
+        let gx = self.select(is_square, gx1, gx2);
+        self.assert_is_equal(y_sq, gx);
+        let x = self.select(is_square, x1, x2);
+
+        // The sign of `y` is that of `u`.
+        let sign_u = self.bls_fp2_sgn0(u);
+        let sign_y = self.bls_fp2_sgn0(y);
+        let same_sign = self.is_equal(sign_u, sign_y);
+        let neg_y = self.bls_fp2_neg(y);
+        let y = self.select(same_sign, y, neg_y);
+
+        (x, y)
+    }
+
+    /// Evaluates the polynomial with the given coefficients at `x`, with Horner's method.
+    fn bls_fp2_eval_polynomial(
+        &mut self,
+        coefficients: &[(&str, &str)],
+        x: Fp2Variable,
+    ) -> Fp2Variable {
+        let mut coefficients = coefficients
+            .iter()
+            .rev()
+            .map(|(c0, c1)| Fp2Value::from_decimal(c0, c1));
+        let mut result = self.constant::<Fp2Variable>(coefficients.next().unwrap());
+        for coefficient in coefficients {
+            let coefficient = self.constant::<Fp2Variable>(coefficient);
+            let mut sum = Fp2Accumulator::default();
+            sum.add_product(self, result, x, false, 1);
+            sum.add(coefficient, false);
+            result = self.bls_fp2_reduce(sum);
+        }
+        result
+    }
+
+    /// Maps an affine point of `E'` to the twist with the 3-isogeny of RFC 9380 (appendix E.3).
+    /// The result is projective, with the denominators of both coordinates as `Z`, so that the
+    /// exceptional points of the isogeny map to the point at infinity.
+    pub fn bls_iso_map(&mut self, x: Fp2Variable, y: Fp2Variable) -> ProjectivePoint<Fp2Variable> {
+        let x_num = self.bls_fp2_eval_polynomial(&ISO_X_NUM, x);
+        let x_den = self.bls_fp2_eval_polynomial(&ISO_X_DEN, x);
+        let y_num = self.bls_fp2_eval_polynomial(&ISO_Y_NUM, x);
+        let y_den = self.bls_fp2_eval_polynomial(&ISO_Y_DEN, x);
+
+        // (x_num / x_den, y * y_num / y_den) = (x_num * y_den : y * y_num * x_den : x_den * y_den).
+        let y_y_num = self.bls_fp2_mul(y, y_num);
+        ProjectivePoint {
+            x: self.bls_fp2_mul(x_num, y_den),
+            y: self.bls_fp2_mul(y_y_num, x_den),
+            z: self.bls_fp2_mul(x_den, y_den),
+        }
+    }
+
+    /// Hashes `message` to a point of G2 with the `BLS12381G2_XMD:SHA-256_SSWU_RO_` suite of
+    /// RFC 9380 and the domain separation tag `dst`.
+    pub fn bls_hash_to_g2(&mut self, message: &[ByteVariable], dst: &[u8]) -> G2Variable {
+        let [u0, u1] = self.bls_hash_to_field_fp2(message, dst);
+        let (x0, y0) = self.bls_map_to_curve_sswu(u0);
+        let q0 = self.bls_iso_map(x0, y0);
+        let (x1, y1) = self.bls_map_to_curve_sswu(u1);
+        let q1 = self.bls_iso_map(x1, y1);
+        let sum = self.bls_projective_add(q0, q1);
+        let point = self.bls_g2_clear_cofactor(sum);
+        self.bls_g2_to_affine(point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::ecc::bls12_381::curve::G2Value;
+    use crate::prelude::{DefaultBuilder, GoldilocksField};
+
+    fn fp2_from_hex(c0: &str, c1: &str) -> Fp2Value<GoldilocksField> {
+        let parse = |value: &str| BigUint::parse_bytes(value.as_bytes(), 16).unwrap();
+        Fp2Value::new(parse(c0), parse(c1))
+    }
+
+    /// The test vector of RFC 9380 (appendix J.10.1) for the message "abc".
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_bls_hash_to_g2() {
+        let dst = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+        let message = b"abc";
+
+        let mut builder = DefaultBuilder::new();
+        let message = message
+            .iter()
+            .map(|byte| builder.constant::<ByteVariable>(*byte))
+            .collect::<Vec<_>>();
+        let [u0, u1] = builder.bls_hash_to_field_fp2(&message, dst);
+        let point = builder.bls_hash_to_g2(&message, dst);
+        builder.write(u0);
+        builder.write(u1);
+        builder.write(point);
+        let circuit = builder.mock_build();
+
+        let input = circuit.input();
+        let mut output = circuit.mock_prove(&input).output;
+        assert_eq!(
+            output.read::<Fp2Variable>(),
+            fp2_from_hex(
+                "15f7c0aa8f6b296ab5ff9c2c7581ade64f4ee6f1bf18f55179ff44a2cf355fa53dd2a2158c5ecb17d7c52f63e7195771",
+                "01c8067bf4c0ba709aa8b9abc3d1cef589a4758e09ef53732d670fd8739a7274e111ba2fcaa71b3d33df2a3a0c8529dd",
+            )
+        );
+        assert_eq!(
+            output.read::<Fp2Variable>(),
+            fp2_from_hex(
+                "187111d5e088b6b9acfdfad078c4dacf72dcd17ca17c82be35e79f8c372a693f60a033b461d81b025864a0ad051a06e4",
+                "08b852331c96ed983e497ebc6dee9b75e373d923b729194af8e72a051ea586f3538a6ebb1e80881a082fa2b24df9f566",
+            )
+        );
+        assert_eq!(
+            output.read::<G2Variable>(),
+            G2Value {
+                x: fp2_from_hex(
+                    "02c2d18e033b960562aae3cab37a27ce00d80ccd5ba4b7fe0e7a210245129dbec7780ccc7954725f4168aff2787776e6",
+                    "139cddbccdc5e91b9623efd38c49f81a6f83f175e80b06fc374de9eb4b41dfe4ca3a230ed250fbe3a2acf73a41177fd8",
+                ),
+                y: fp2_from_hex(
+                    "1787327b68159716a37440985269cf584bcb1e621d3a7202be6ea05c4cfe244aeb197642555a0645fb87bf7466b2ba48",
+                    "00aa65dae3c8d732d10ecd2c50f8a1baf3001578f71c694e03866e9f3d49ac1e1ce70dd94a733534f106d4cec0eddd16",
+                ),
+            }
+        );
+    }
+}
@@ -0,0 +1,8 @@
+pub mod curve;
+pub mod decompress;
+pub mod field;
+pub mod hash_to_curve;
+mod native;
+pub mod pairing;
+pub mod signature;
+pub mod tower;
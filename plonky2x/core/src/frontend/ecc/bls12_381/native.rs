@@ -0,0 +1,178 @@
+//! Arithmetic in the extension fields of BLS12-381 outside of the circuit, for the hints. Elements
+//! are reduced `BigUint` coefficients in the same tower as the circuit variables.
+
+use num::{BigUint, Zero};
+
+use super::field::fp_modulus;
+
+pub type Fp2 = (BigUint, BigUint);
+pub type Fp6 = [Fp2; 3];
+pub type Fp12 = [Fp6; 2];
+
+fn fp_add(a: &BigUint, b: &BigUint) -> BigUint {
+    (a + b) % fp_modulus()
+}
+
+fn fp_sub(a: &BigUint, b: &BigUint) -> BigUint {
+    let modulus = fp_modulus();
+    (a + &modulus - b % &modulus) % modulus
+}
+
+fn fp_mul(a: &BigUint, b: &BigUint) -> BigUint {
+    a * b % fp_modulus()
+}
+
+/// The inverse of `a`, or zero if `a` is zero.
+pub fn fp_inv(a: &BigUint) -> BigUint {
+    let modulus = fp_modulus();
+    a.modpow(&(&modulus - 2u32), &modulus)
+}
+
+fn fp_sqrt(a: &BigUint) -> Option<BigUint> {
+    // p = 3 mod 4, so a^((p + 1) / 4) is a square root of every square.
+    let modulus = fp_modulus();
+    let root = a.modpow(&((&modulus + 1u32) >> 2), &modulus);
+    (fp_mul(&root, &root) == a % &modulus).then_some(root)
+}
+
+pub fn fp2_add(a: &Fp2, b: &Fp2) -> Fp2 {
+    (fp_add(&a.0, &b.0), fp_add(&a.1, &b.1))
+}
+
+pub fn fp2_sub(a: &Fp2, b: &Fp2) -> Fp2 {
+    (fp_sub(&a.0, &b.0), fp_sub(&a.1, &b.1))
+}
+
+pub fn fp2_mul(a: &Fp2, b: &Fp2) -> Fp2 {
+    (
+        fp_sub(&fp_mul(&a.0, &b.0), &fp_mul(&a.1, &b.1)),
+        fp_add(&fp_mul(&a.0, &b.1), &fp_mul(&a.1, &b.0)),
+    )
+}
+
+fn fp2_neg(a: &Fp2) -> Fp2 {
+    fp2_sub(&(BigUint::zero(), BigUint::zero()), a)
+}
+
+/// Multiplies by the non-residue `1 + u` of the cubic extension.
+fn fp2_mul_by_nonresidue(a: &Fp2) -> Fp2 {
+    (fp_sub(&a.0, &a.1), fp_add(&a.0, &a.1))
+}
+
+/// The inverse of `a`, or zero if `a` is zero.
+pub fn fp2_inv(a: &Fp2) -> Fp2 {
+    let norm_inv = fp_inv(&fp_add(&fp_mul(&a.0, &a.0), &fp_mul(&a.1, &a.1)));
+    (
+        fp_mul(&a.0, &norm_inv),
+        fp_sub(&BigUint::zero(), &fp_mul(&a.1, &norm_inv)),
+    )
+}
+
+/// A square root of `a`, if `a` is a square.
+pub fn fp2_sqrt(a: &Fp2) -> Option<Fp2> {
+    let zero = BigUint::zero();
+    if a.1.is_zero() {
+        // Every element of Fp is a square in Fp2, either of an element of Fp or of `u` times one.
+        return match fp_sqrt(&a.0) {
+            Some(root) => Some((root, zero)),
+            None => fp_sqrt(&fp_sub(&zero, &a.0)).map(|root| (zero, root)),
+        };
+    }
+    // (x0 + x1 u)^2 = a gives x0^2 = (a0 + |a|) / 2 with |a| = sqrt(a0^2 + a1^2), and x1 = a1 / 2x0.
+    let norm_root = fp_sqrt(&fp_add(&fp_mul(&a.0, &a.0), &fp_mul(&a.1, &a.1)))?;
+    let two_inv = fp_inv(&BigUint::from(2u32));
+    let x0 = fp_sqrt(&fp_mul(&fp_add(&a.0, &norm_root), &two_inv))
+        .or_else(|| fp_sqrt(&fp_mul(&fp_sub(&a.0, &norm_root), &two_inv)))?;
+    let x1 = fp_mul(&a.1, &fp_inv(&fp_add(&x0, &x0)));
+    Some((x0, x1))
+}
+
+fn fp6_add(a: &Fp6, b: &Fp6) -> Fp6 {
+    [
+        fp2_add(&a[0], &b[0]),
+        fp2_add(&a[1], &b[1]),
+        fp2_add(&a[2], &b[2]),
+    ]
+}
+
+fn fp6_sub(a: &Fp6, b: &Fp6) -> Fp6 {
+    [
+        fp2_sub(&a[0], &b[0]),
+        fp2_sub(&a[1], &b[1]),
+        fp2_sub(&a[2], &b[2]),
+    ]
+}
+
+fn fp6_mul(a: &Fp6, b: &Fp6) -> Fp6 {
+    let a1 = fp2_mul_by_nonresidue(&a[1]);
+    let a2 = fp2_mul_by_nonresidue(&a[2]);
+    [
+        fp2_add(
+            &fp2_add(&fp2_mul(&a[0], &b[0]), &fp2_mul(&a1, &b[2])),
+            &fp2_mul(&a2, &b[1]),
+        ),
+        fp2_add(
+            &fp2_add(&fp2_mul(&a[0], &b[1]), &fp2_mul(&a[1], &b[0])),
+            &fp2_mul(&a2, &b[2]),
+        ),
+        fp2_add(
+            &fp2_add(&fp2_mul(&a[0], &b[2]), &fp2_mul(&a[1], &b[1])),
+            &fp2_mul(&a[2], &b[0]),
+        ),
+    ]
+}
+
+/// Multiplies by `v`, the generator of the cubic extension.
+fn fp6_mul_by_v(a: &Fp6) -> Fp6 {
+    [fp2_mul_by_nonresidue(&a[2]), a[0].clone(), a[1].clone()]
+}
+
+fn fp6_neg(a: &Fp6) -> Fp6 {
+    [fp2_neg(&a[0]), fp2_neg(&a[1]), fp2_neg(&a[2])]
+}
+
+fn fp6_inv(a: &Fp6) -> Fp6 {
+    let t0 = fp2_sub(
+        &fp2_mul(&a[0], &a[0]),
+        &fp2_mul_by_nonresidue(&fp2_mul(&a[1], &a[2])),
+    );
+    let t1 = fp2_sub(
+        &fp2_mul_by_nonresidue(&fp2_mul(&a[2], &a[2])),
+        &fp2_mul(&a[0], &a[1]),
+    );
+    let t2 = fp2_sub(&fp2_mul(&a[1], &a[1]), &fp2_mul(&a[0], &a[2]));
+    let norm = fp2_add(
+        &fp2_mul(&a[0], &t0),
+        &fp2_mul_by_nonresidue(&fp2_add(&fp2_mul(&a[2], &t1), &fp2_mul(&a[1], &t2))),
+    );
+    let norm_inv = fp2_inv(&norm);
+    [
+        fp2_mul(&t0, &norm_inv),
+        fp2_mul(&t1, &norm_inv),
+        fp2_mul(&t2, &norm_inv),
+    ]
+}
+
+pub fn fp12_mul(a: &Fp12, b: &Fp12) -> Fp12 {
+    let t0 = fp6_mul(&a[0], &b[0]);
+    let t1 = fp6_mul(&a[1], &b[1]);
+    let s = fp6_mul(&fp6_add(&a[0], &a[1]), &fp6_add(&b[0], &b[1]));
+    [
+        fp6_add(&t0, &fp6_mul_by_v(&t1)),
+        fp6_sub(&fp6_sub(&s, &t0), &t1),
+    ]
+}
+
+/// The inverse of `a`, or zero if `a` is zero.
+pub fn fp12_inv(a: &Fp12) -> Fp12 {
+    // (c0 + c1 w)^-1 = (c0 - c1 w) / (c0^2 - v c1^2), since w^2 = v.
+    let norm = fp6_sub(
+        &fp6_mul(&a[0], &a[0]),
+        &fp6_mul_by_v(&fp6_mul(&a[1], &a[1])),
+    );
+    let norm_inv = fp6_inv(&norm);
+    [
+        fp6_mul(&a[0], &norm_inv),
+        fp6_neg(&fp6_mul(&a[1], &norm_inv)),
+    ]
+}
@@ -0,0 +1,197 @@
+//! The optimal ate pairing of BLS12-381, with the Miller loop and the final exponentiation of the
+//! arkworks implementation.
+
+use num::BigUint;
+
+use super::curve::{G1Variable, G2Variable, ProjectivePoint, X_ABS};
+use super::field::{fp_modulus, Fp2Accumulator, Fp2Variable, FpVariable};
+use super::tower::Fp12Variable;
+use crate::prelude::{BoolVariable, CircuitBuilder, PlonkParameters};
+
+/// The coefficients of a line of the Miller loop, evaluated at a point of G1 by `bls_ell`.
+type LineCoefficients = (Fp2Variable, Fp2Variable, Fp2Variable);
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Doubles `r` and returns the tangent line at `r`, with the formulas of Costello, Lange and
+    /// Naehrig (ePrint 2009/615) for homogeneous projective coordinates.
+    fn bls_line_double(&mut self, r: &mut ProjectivePoint<Fp2Variable>) -> LineCoefficients {
+        let modulus = fp_modulus();
+        let two_inv = self.constant::<FpVariable>((&modulus + 1u32) / 2u32);
+        // 3 * b' for the coefficient b' = 4 * (1 + u) of the twist.
+        let b3 = self.bls_fp2_constant(BigUint::from(12u32), BigUint::from(12u32));
+
+        let xy = self.bls_fp2_mul(r.x, r.y);
+        let a = self.bls_fp2_mul_by_fp(xy, two_inv);
+        let b = self.bls_fp2_mul(r.y, r.y);
+        let c = self.bls_fp2_mul(r.z, r.z);
+        let e = self.bls_fp2_mul(b3, c);
+
+        // f = 3e, g = (b + f) / 2 and h = (y + z)^2 - (b + c).
+        let mut b_plus_f = Fp2Accumulator::default();
+        b_plus_f.add(b, false);
+        let mut b_minus_f = Fp2Accumulator::default();
+        b_minus_f.add(b, false);
+        for _ in 0..3 {
+            b_plus_f.add(e, false);
+            b_minus_f.sub(e);
+        }
+        let b_plus_f = self.bls_fp2_reduce(b_plus_f);
+        let b_minus_f = self.bls_fp2_reduce(b_minus_f);
+        let g = self.bls_fp2_mul_by_fp(b_plus_f, two_inv);
+        let y_plus_z = self.bls_fp2_add(r.y, r.z);
+        let mut h = Fp2Accumulator::default();
+        h.add_product(self, y_plus_z, y_plus_z, false, 1);
+        h.sub(b);
+        h.sub(c);
+        let h = self.bls_fp2_reduce(h);
+        let i = self.bls_fp2_sub(e, b);
+        let j = self.bls_fp2_mul(r.x, r.x);
+        let e_sq = self.bls_fp2_mul(e, e);
+
+        r.x = self.bls_fp2_mul(a, b_minus_f);
+        let mut y = Fp2Accumulator::default();
+        y.add_product(self, g, g, false, 1);
+        for _ in 0..3 {
+            y.sub(e_sq);
+        }
+        r.y = self.bls_fp2_reduce(y);
+        r.z = self.bls_fp2_mul(b, h);
+
+        let mut three_j = Fp2Accumulator::default();
+        for _ in 0..3 {
+            three_j.add(j, false);
+        }
+        let three_j = self.bls_fp2_reduce(three_j);
+        let neg_h = self.bls_fp2_neg(h);
+        (i, three_j, neg_h)
+    }
+
+    /// Adds the affine point `q` to `r` and returns the line through them.
+    fn bls_line_add(
+        &mut self,
+        r: &mut ProjectivePoint<Fp2Variable>,
+        q: G2Variable,
+    ) -> LineCoefficients {
+        let qy_z = self.bls_fp2_mul(q.y, r.z);
+        let theta = self.bls_fp2_sub(r.y, qy_z);
+        let qx_z = self.bls_fp2_mul(q.x, r.z);
+        let lambda = self.bls_fp2_sub(r.x, qx_z);
+        let c = self.bls_fp2_mul(theta, theta);
+        let d = self.bls_fp2_mul(lambda, lambda);
+        let e = self.bls_fp2_mul(lambda, d);
+        let f = self.bls_fp2_mul(r.z, c);
+        let g = self.bls_fp2_mul(r.x, d);
+        let mut h = Fp2Accumulator::default();
+        h.add(e, false);
+        h.add(f, false);
+        h.sub(g);
+        h.sub(g);
+        let h = self.bls_fp2_reduce(h);
+
+        r.x = self.bls_fp2_mul(lambda, h);
+        let g_minus_h = self.bls_fp2_sub(g, h);
+        let theta_g_minus_h = self.bls_fp2_mul(theta, g_minus_h);
+        let e_y = self.bls_fp2_mul(e, r.y);
+        r.y = self.bls_fp2_sub(theta_g_minus_h, e_y);
+        r.z = self.bls_fp2_mul(r.z, e);
+
+        let theta_qx = self.bls_fp2_mul(theta, q.x);
+        let lambda_qy = self.bls_fp2_mul(lambda, q.y);
+        let j = self.bls_fp2_sub(theta_qx, lambda_qy);
+        let neg_theta = self.bls_fp2_neg(theta);
+        (j, neg_theta, lambda)
+    }
+
+    /// Multiplies `f` by the value of a line at `p`.
+    fn bls_ell(
+        &mut self,
+        f: Fp12Variable,
+        coefficients: LineCoefficients,
+        p: G1Variable,
+    ) -> Fp12Variable {
+        let (c0, c1, c2) = coefficients;
+        let c1 = self.bls_fp2_mul_by_fp(c1, p.x);
+        let c2 = self.bls_fp2_mul_by_fp(c2, p.y);
+        self.bls_fp12_mul_by_014(f, c0, c1, c2)
+    }
+
+    /// Computes the product of the Miller loops of the pairs `(P, Q)`, sharing the squarings of the
+    /// accumulator. The points of G2 must be in the prime-order subgroup.
+    pub fn bls_miller_loop(&mut self, pairs: &[(G1Variable, G2Variable)]) -> Fp12Variable {
+        let mut f = self.one::<Fp12Variable>();
+        let mut r = pairs
+            .iter()
+            .map(|(_, q)| self.bls_g2_to_projective(*q))
+            .collect::<Vec<_>>();
+
+        // The loop runs over the bits of |x| after the most significant one.
+        for i in (0..63).rev() {
+            f = self.bls_fp12_square(f);
+            for ((p, _), r) in pairs.iter().zip(r.iter_mut()) {
+                let line = self.bls_line_double(r);
+                f = self.bls_ell(f, line, *p);
+            }
+            if (X_ABS >> i) & 1 == 1 {
+                for ((p, q), r) in pairs.iter().zip(r.iter_mut()) {
+                    let line = self.bls_line_add(r, *q);
+                    f = self.bls_ell(f, line, *p);
+                }
+            }
+        }
+
+        // The parameter x is negative.
+        self.bls_fp12_conjugate(f)
+    }
+
+    /// Computes `f^x` for an element of the cyclotomic subgroup, where the conjugate is the inverse.
+    fn bls_fp12_exp_by_x(&mut self, f: Fp12Variable) -> Fp12Variable {
+        let mut result = f;
+        for i in (0..63).rev() {
+            result = self.bls_fp12_square(result);
+            if (X_ABS >> i) & 1 == 1 {
+                result = self.bls_fp12_mul(result, f);
+            }
+        }
+        self.bls_fp12_conjugate(result)
+    }
+
+    /// Raises the output of the Miller loop to the power `(p^12 - 1) / r`. The easy part uses a
+    /// hinted inverse, and the hard part follows the addition chain of Hayashida, Hayasaka and
+    /// Teruya (ePrint 2020/875).
+    pub fn bls_final_exponentiation(&mut self, f: Fp12Variable) -> Fp12Variable {
+        // Easy part: f^((p^6 - 1) * (p^2 + 1)).
+        let f1 = self.bls_fp12_conjugate(f);
+        let f2 = self.bls_fp12_inv(f);
+        let r = self.bls_fp12_mul(f1, f2);
+        let r_frobenius = self.bls_fp12_frobenius(r, 2);
+        let r = self.bls_fp12_mul(r_frobenius, r);
+
+        // Hard part.
+        let mut y0 = self.bls_fp12_square(r);
+        let mut y1 = self.bls_fp12_exp_by_x(r);
+        let mut y2 = self.bls_fp12_conjugate(r);
+        y1 = self.bls_fp12_mul(y1, y2);
+        y2 = self.bls_fp12_exp_by_x(y1);
+        y1 = self.bls_fp12_conjugate(y1);
+        y1 = self.bls_fp12_mul(y1, y2);
+        y2 = self.bls_fp12_exp_by_x(y1);
+        y1 = self.bls_fp12_frobenius(y1, 1);
+        y1 = self.bls_fp12_mul(y1, y2);
+        let r = self.bls_fp12_mul(r, y0);
+        y0 = self.bls_fp12_exp_by_x(y1);
+        y2 = self.bls_fp12_exp_by_x(y0);
+        y0 = self.bls_fp12_frobenius(y1, 2);
+        y1 = self.bls_fp12_conjugate(y1);
+        y1 = self.bls_fp12_mul(y1, y2);
+        y1 = self.bls_fp12_mul(y1, y0);
+        self.bls_fp12_mul(r, y1)
+    }
+
+    /// Returns whether the product of the pairings `e(P, Q)` of `pairs` is one. The points of G2
+    /// must be in the prime-order subgroup.
+    pub fn bls_pairing_check(&mut self, pairs: &[(G1Variable, G2Variable)]) -> BoolVariable {
+        let f = self.bls_miller_loop(pairs);
+        let result = self.bls_final_exponentiation(f);
+        self.bls_fp12_is_one(result)
+    }
+}
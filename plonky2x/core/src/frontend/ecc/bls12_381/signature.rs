@@ -0,0 +1,166 @@
+//! BLS signatures over BLS12-381 with public keys in G1 and signatures in G2, as used by the
+//! Ethereum consensus layer.
+
+use super::curve::{G1Variable, G2Variable};
+use super::field::FpVariable;
+use super::hash_to_curve::ETH_SIGNATURE_DST;
+use crate::prelude::{BoolVariable, Bytes32Variable, CircuitBuilder, PlonkParameters};
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns whether `signature` is a valid signature of `message` under `pubkey`, i.e. whether
+    /// `e(pubkey, H(message)) * e(-G1, signature) = 1` where `H` hashes to G2 with the Ethereum
+    /// domain separation tag.
+    ///
+    /// The result is false if the public key is not on the curve, or if the signature is not on
+    /// the curve or not in G2. Membership of the public key in G1 is not checked, since consensus
+    /// public keys are validated when they are deposited.
+    pub fn bls_verify(
+        &mut self,
+        pubkey: G1Variable,
+        message: Bytes32Variable,
+        signature: G2Variable,
+    ) -> BoolVariable {
+        let pubkey_on_curve = self.bls_g1_is_on_curve(pubkey);
+        let signature_on_curve = self.bls_g2_is_on_curve(signature);
+        let signature_in_subgroup = self.bls_g2_is_in_subgroup(signature);
+
+        // Invalid points are replaced so that the rest of the circuit stays satisfiable, and the
+        // result is false anyway.
+        let g1_generator = self.bls_g1_generator();
+        let g2_generator = self.bls_g2_generator();
+        let pubkey = self.select(pubkey_on_curve, pubkey, g1_generator);
+        let signature_is_valid = self.and(signature_on_curve, signature_in_subgroup);
+        let signature = self.select(signature_is_valid, signature, g2_generator);
+
+        let hash = self.bls_hash_to_g2(&message.as_bytes(), ETH_SIGNATURE_DST);
+        let neg_g1_generator = self.bls_g1_neg(g1_generator);
+        let pairing_holds =
+            self.bls_pairing_check(&[(pubkey, hash), (neg_g1_generator, signature)]);
+
+        let is_valid = self.and(pubkey_on_curve, signature_is_valid);
+        self.and(is_valid, pairing_holds)
+    }
+
+    /// Sums the public keys whose bit is set in `bitmask`, e.g. the participants of a sync
+    /// committee. The circuit is unsatisfiable if no bit is set.
+    pub fn bls_aggregate_pubkeys(
+        &mut self,
+        pubkeys: &[G1Variable],
+        bitmask: &[BoolVariable],
+    ) -> G1Variable {
+        assert_eq!(
+            pubkeys.len(),
+            bitmask.len(),
+            "each public key needs a bit in the bitmask"
+        );
+        let mut aggregate = self.bls_projective_identity::<FpVariable>();
+        for (pubkey, bit) in pubkeys.iter().zip(bitmask) {
+            let pubkey = self.bls_g1_to_projective(*pubkey);
+            let sum = self.bls_projective_add(aggregate, pubkey);
+            aggregate = self.bls_projective_select(*bit, sum, aggregate);
+        }
+        self.bls_g1_to_affine(aggregate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigUint;
+
+    use super::*;
+    use crate::frontend::ecc::bls12_381::curve::{G1Value, G2Value};
+    use crate::frontend::ecc::bls12_381::field::Fp2Value;
+    use crate::prelude::{DefaultBuilder, GoldilocksField};
+
+    const PUBKEYS: [(&[u8], &[u8]); 4] = [
+        (
+            b"15a254501b7733239ed3cec4d56737977bd09ede881d8a234560e83e5525017add3b1dcc3eabfb85e12a4131b19c253b",
+            b"09d4984959de1b75ed9379860a2c07d1576916055ddc967e28765e043968c02328b6b78f23414ad5e179c3cc7bbed432",
+        ),
+        (
+            b"0c80a5e08c712d5f08f0306ad743f7d8c215d982489b84a1d6ba805733d94c006e8938f9089a75db3ffa135af33bc69a",
+            b"0e11d357778f22dfcfd979b83b62241af34ae4b5bd205750382b3ffd293d4cb5063e3aa3a870884ed6e9f3acfde1a4c3",
+        ),
+        (
+            b"16df714a5cc9ddd2298546dce3d6d3827762a6d5b1c2a91e5ca93c9c898b1b4319cc105c493212a55b63080732ec2249",
+            b"07af606f64049d6ab7b57c1be4114edb965ab2cef5592869ccb248b3ea6170fbf071f34e82f1635bfb371a67711e61ca",
+        ),
+        (
+            b"15e05aea89db0e84b87ab96a0203cbff924f86a35494c9a9ce274b768fc555a6b761f2fc2b1b58d9cda73d4cdf4bca24",
+            b"018d140b5df2596f58d7a3262e3683b67fc31a1cdef2cbbb2e15ae738c5b105178a2087e1a7bce7e315d2463a906ef69",
+        ),
+    ];
+
+    const SIGNATURE: [&[u8]; 4] = [
+        b"163dbf0c528ab35cc6e70651c61056fdfdae97af050401ae4376a64d88d6bb962b6eed0893fefa9b98eebbe1a7e7785e",
+        b"055a1d816713b53b0a378c9bef7eb2bd5b94c29d90407d50002772d43dcdf5c080a145b5329d7ab9a9316aaddde3e05d",
+        b"0a099c52e945feb280d8b5d66ab5406c14e0a07a4fc58cd4684a857dd5c380f69c8b77f92c01c5ac9f1ff67cc7ff08d3",
+        b"06c8a25e88b2070a97fd222eb551e99fb2fc1d08b24fdd12c0fd00e666ccfef898ebed0c06d9d76a30aae7ef2d729082",
+    ];
+
+    /// The aggregate signature of the same message by the keys 0, 2 and 3 only.
+    const PARTIAL_SIGNATURE: [&[u8]; 4] = [
+        b"13a5a5ffc795e1ad09d574b94d18faec9aa8ab3c5c01b19543cc95f5f9b6603399c92e4051b81f25018f332e0a77e1d3",
+        b"13d3784a494662c875e425b21b72fcd4ad4fa109c0e0c3b0f12b3a4f94fb05911b52aff07add3a3028c3e7c7760e34c3",
+        b"01d0658feb678e0abc4eae7bb62f34e92a7f15f7ac50f0ce5058bf1a23d8ee3a98ee0824d6ee56b9b2e11f0ba5a13889",
+        b"1860ac82e1eddc3d0b2ae6f619fc008183a554487ea4805e28b611f96972a9187f16921fad3f76aa9ff06eb5de6cb409",
+    ];
+
+    fn parse(value: &[u8]) -> BigUint {
+        BigUint::parse_bytes(value, 16).unwrap()
+    }
+
+    fn parse_signature(signature: [&[u8]; 4]) -> G2Value<GoldilocksField> {
+        G2Value {
+            x: Fp2Value::new(parse(signature[0]), parse(signature[1])),
+            y: Fp2Value::new(parse(signature[2]), parse(signature[3])),
+        }
+    }
+
+    /// Aggregate signatures generated with blst under the Ethereum domain separation tag, as a sync
+    /// committee would sign a block root, by all four keys and by a partial committee.
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_bls_verify_aggregate() {
+        let mut builder = DefaultBuilder::new();
+        let pubkeys = (0..PUBKEYS.len())
+            .map(|_| builder.read::<G1Variable>())
+            .collect::<Vec<_>>();
+        let bitmask = (0..PUBKEYS.len())
+            .map(|_| builder.read::<BoolVariable>())
+            .collect::<Vec<_>>();
+        let message = builder.read::<Bytes32Variable>();
+        let signature = builder.read::<G2Variable>();
+        let aggregate = builder.bls_aggregate_pubkeys(&pubkeys, &bitmask);
+        let is_valid = builder.bls_verify(aggregate, message, signature);
+        builder.write(is_valid);
+        let circuit = builder.mock_build();
+
+        let all = [true; 4];
+        let partial = [true, false, true, true];
+        let cases = [
+            (all, SIGNATURE, [0x5au8; 32], true),
+            (all, SIGNATURE, [0x5bu8; 32], false),
+            (partial, PARTIAL_SIGNATURE, [0x5au8; 32], true),
+            // The partial signature does not cover the key 1.
+            (all, PARTIAL_SIGNATURE, [0x5au8; 32], false),
+            (partial, SIGNATURE, [0x5au8; 32], false),
+        ];
+        for (bitmask, signature, message, expected) in cases {
+            let mut input = circuit.input();
+            for (x, y) in PUBKEYS {
+                input.write::<G1Variable>(G1Value {
+                    x: parse(x),
+                    y: parse(y),
+                });
+            }
+            for bit in bitmask {
+                input.write::<BoolVariable>(bit);
+            }
+            input.write::<Bytes32Variable>(message.into());
+            input.write::<G2Variable>(parse_signature(signature));
+            let mut output = circuit.mock_prove(&input).output;
+            assert_eq!(output.read::<BoolVariable>(), expected);
+        }
+    }
+}
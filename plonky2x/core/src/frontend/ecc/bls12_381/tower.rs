@@ -0,0 +1,350 @@
+use num::BigUint;
+use plonky2::hash::hash_types::RichField;
+use serde::{Deserialize, Serialize};
+
+use super::field::{Fp2Accumulator, Fp2Value, Fp2Variable};
+use super::native;
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::vars::{ValueStream, VariableStream};
+use crate::prelude::{
+    BoolVariable, CircuitBuilder, CircuitVariable, One, PlonkParameters, Variable,
+};
+
+/// An element `c0 + c1 * v + c2 * v^2` of the cubic extension `Fp6 = Fp2[v] / (v^3 - (1 + u))`.
+#[derive(Debug, Clone, Copy, CircuitVariable)]
+#[value_name(Fp6Value)]
+#[value_derive(PartialEq, Eq)]
+pub struct Fp6Variable {
+    pub c0: Fp2Variable,
+    pub c1: Fp2Variable,
+    pub c2: Fp2Variable,
+}
+
+/// An element `c0 + c1 * w` of the quadratic extension `Fp12 = Fp6[w] / (w^2 - v)`, which holds
+/// the values of the pairing.
+#[derive(Debug, Clone, Copy, CircuitVariable)]
+#[value_name(Fp12Value)]
+#[value_derive(PartialEq, Eq)]
+pub struct Fp12Variable {
+    pub c0: Fp6Variable,
+    pub c1: Fp6Variable,
+}
+
+impl<F: RichField> Fp6Value<F> {
+    pub(crate) fn from_native(value: native::Fp6) -> Self {
+        let [c0, c1, c2] = value;
+        Self {
+            c0: Fp2Value::from_native(c0),
+            c1: Fp2Value::from_native(c1),
+            c2: Fp2Value::from_native(c2),
+        }
+    }
+
+    pub(crate) fn to_native(&self) -> native::Fp6 {
+        [
+            self.c0.to_native(),
+            self.c1.to_native(),
+            self.c2.to_native(),
+        ]
+    }
+}
+
+impl<F: RichField> Fp12Value<F> {
+    pub(crate) fn from_native(value: native::Fp12) -> Self {
+        let [c0, c1] = value;
+        Self {
+            c0: Fp6Value::from_native(c0),
+            c1: Fp6Value::from_native(c1),
+        }
+    }
+
+    pub(crate) fn to_native(&self) -> native::Fp12 {
+        [self.c0.to_native(), self.c1.to_native()]
+    }
+}
+
+impl Fp12Variable {
+    /// The coefficients of the element in the basis `1, w, ..., w^5` of `Fp12` over `Fp2`, where
+    /// `w^6 = 1 + u`. The arithmetic below works on these coefficients.
+    fn to_coefficients(self) -> [Fp2Variable; 6] {
+        [
+            self.c0.c0, self.c1.c0, self.c0.c1, self.c1.c1, self.c0.c2, self.c1.c2,
+        ]
+    }
+
+    fn from_coefficients(coefficients: [Fp2Variable; 6]) -> Self {
+        let [a0, a1, a2, a3, a4, a5] = coefficients;
+        Self {
+            c0: Fp6Variable {
+                c0: a0,
+                c1: a2,
+                c2: a4,
+            },
+            c1: Fp6Variable {
+                c0: a1,
+                c1: a3,
+                c2: a5,
+            },
+        }
+    }
+}
+
+// The Frobenius coefficients of `v` and `v^2` for the powers 1 and 2 of the Frobenius map, as
+// decimal coefficients of elements of `Fp2`.
+const FROBENIUS_COEFF_FP6_C1: [(&str, &str); 2] = [
+    (
+        "0",
+        "4002409555221667392624310435006688643935503118305586438271171395842971157480381377015405980053539358417135540939436",
+    ),
+    (
+        "793479390729215512621379701633421447060886740281060493010456487427281649075476305620758731620350",
+        "0",
+    ),
+];
+
+const FROBENIUS_COEFF_FP6_C2: [(&str, &str); 2] = [
+    (
+        "4002409555221667392624310435006688643935503118305586438271171395842971157480381377015405980053539358417135540939437",
+        "0",
+    ),
+    (
+        "4002409555221667392624310435006688643935503118305586438271171395842971157480381377015405980053539358417135540939436",
+        "0",
+    ),
+];
+
+// The Frobenius coefficients of `w` for the powers 1 and 2 of the Frobenius map.
+const FROBENIUS_COEFF_FP12_C1: [(&str, &str); 2] = [
+    (
+        "3850754370037169011952147076051364057158807420970682438676050522613628423219637725072182697113062777891589506424760",
+        "151655185184498381465642749684540099398075398968325446656007613510403227271200139370504932015952886146304766135027",
+    ),
+    (
+        "793479390729215512621379701633421447060886740281060493010456487427281649075476305620758731620351",
+        "0",
+    ),
+];
+
+/// Computes the inverse of an element of `Fp12` outside of the circuit. Zero yields zero, which
+/// `bls_fp12_inv` then rejects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fp12InvHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for Fp12InvHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let a = input_stream.read_value::<Fp12Variable>();
+        let inverse = native::fp12_inv(&a.to_native());
+        output_stream.write_value::<Fp12Variable>(Fp12Value::from_native(inverse));
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Multiplies the coefficients `a` by the coefficients `b`, skipping the zero coefficients of
+    /// `b`, with a single reduction per coefficient of `Fp2`.
+    fn bls_fp12_mul_coefficients(
+        &mut self,
+        a: [Fp2Variable; 6],
+        b: [Option<Fp2Variable>; 6],
+    ) -> Fp12Variable {
+        let mut sums: [Fp2Accumulator; 6] = Default::default();
+        for (i, a_i) in a.iter().enumerate() {
+            for (j, b_j) in b.iter().enumerate() {
+                if let Some(b_j) = b_j {
+                    // w^6 = 1 + u, so the terms of degree 6 and above wrap around.
+                    sums[(i + j) % 6].add_product(self, *a_i, *b_j, i + j >= 6, 1);
+                }
+            }
+        }
+        Fp12Variable::from_coefficients(sums.map(|sum| self.bls_fp2_reduce(sum)))
+    }
+
+    /// Computes `a * b` in `Fp12`.
+    pub fn bls_fp12_mul(&mut self, a: Fp12Variable, b: Fp12Variable) -> Fp12Variable {
+        self.bls_fp12_mul_coefficients(a.to_coefficients(), b.to_coefficients().map(Some))
+    }
+
+    /// Computes `a^2` in `Fp12`, computing each cross product once.
+    pub fn bls_fp12_square(&mut self, a: Fp12Variable) -> Fp12Variable {
+        let a = a.to_coefficients();
+        let mut sums: [Fp2Accumulator; 6] = Default::default();
+        for i in 0..6 {
+            for j in i..6 {
+                let times = if i == j { 1 } else { 2 };
+                sums[(i + j) % 6].add_product(self, a[i], a[j], i + j >= 6, times);
+            }
+        }
+        Fp12Variable::from_coefficients(sums.map(|sum| self.bls_fp2_reduce(sum)))
+    }
+
+    /// Computes `a * ((c0 + c1 * v) + c4 * v * w)`, the product by the sparse value of a line of
+    /// the Miller loop.
+    pub fn bls_fp12_mul_by_014(
+        &mut self,
+        a: Fp12Variable,
+        c0: Fp2Variable,
+        c1: Fp2Variable,
+        c4: Fp2Variable,
+    ) -> Fp12Variable {
+        // In the basis of powers of `w`, `v = w^2` and `v * w = w^3`.
+        let b = [Some(c0), None, Some(c1), Some(c4), None, None];
+        self.bls_fp12_mul_coefficients(a.to_coefficients(), b)
+    }
+
+    /// Computes the conjugate `c0 - c1 * w` of `a`, which is its inverse when `a` is in the
+    /// cyclotomic subgroup, e.g. after the easy part of the final exponentiation.
+    pub fn bls_fp12_conjugate(&mut self, a: Fp12Variable) -> Fp12Variable {
+        Fp12Variable {
+            c0: a.c0,
+            c1: Fp6Variable {
+                c0: self.bls_fp2_neg(a.c1.c0),
+                c1: self.bls_fp2_neg(a.c1.c1),
+                c2: self.bls_fp2_neg(a.c1.c2),
+            },
+        }
+    }
+
+    /// Computes `a^(p^power)` with the Frobenius map, for a power of 1 or 2.
+    pub fn bls_fp12_frobenius(&mut self, a: Fp12Variable, power: usize) -> Fp12Variable {
+        assert!(
+            power == 1 || power == 2,
+            "unsupported power of the Frobenius map"
+        );
+        let parse = |(c0, c1): (&str, &str)| Fp2Value::<L::Field>::from_decimal(c0, c1);
+        let one = (BigUint::from(1u32), BigUint::from(0u32));
+        let coeffs_fp6 = [
+            one.clone(),
+            parse(FROBENIUS_COEFF_FP6_C1[power - 1]).to_native(),
+            parse(FROBENIUS_COEFF_FP6_C2[power - 1]).to_native(),
+        ];
+        let coeff_fp12 = parse(FROBENIUS_COEFF_FP12_C1[power - 1]).to_native();
+
+        let mut coefficients = a.to_coefficients();
+        for (k, coefficient) in coefficients.iter_mut().enumerate() {
+            // The coefficient of `w^k` is that of `v^(k / 2)`, times `w` if `k` is odd.
+            let mut constant = coeffs_fp6[k / 2].clone();
+            if k % 2 == 1 {
+                constant = native::fp2_mul(&constant, &coeff_fp12);
+            }
+            if power == 1 {
+                *coefficient = self.bls_fp2_conjugate(*coefficient);
+            }
+            if constant != one {
+                let constant = self.constant::<Fp2Variable>(Fp2Value::from_native(constant));
+                *coefficient = self.bls_fp2_mul(*coefficient, constant);
+            }
+        }
+        Fp12Variable::from_coefficients(coefficients)
+    }
+
+    /// Computes the inverse of `a` in `Fp12`. The circuit is unsatisfiable if `a` is zero.
+    pub fn bls_fp12_inv(&mut self, a: Fp12Variable) -> Fp12Variable {
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&a);
+        let output_stream = self.hint(input_stream, Fp12InvHint);
+        let inverse = output_stream.read::<Fp12Variable>(self);
+
+        let product = self.bls_fp12_mul(a, inverse);
+        let one = self.one::<Fp12Variable>();
+        self.assert_is_equal(product, one);
+
+        inverse
+    }
+
+    /// Returns whether `a` is the identity of `Fp12`.
+    pub fn bls_fp12_is_one(&mut self, a: Fp12Variable) -> BoolVariable {
+        let one = self.one::<Fp12Variable>();
+        self.is_equal(a, one)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> One<L, D> for Fp12Variable {
+    fn one(builder: &mut CircuitBuilder<L, D>) -> Self {
+        let zero = || (BigUint::from(0u32), BigUint::from(0u32));
+        let one = (BigUint::from(1u32), BigUint::from(0u32));
+        builder.constant::<Fp12Variable>(Fp12Value::from_native([
+            [one, zero(), zero()],
+            [zero(), zero(), zero()],
+        ]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::RandBigInt;
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::frontend::ecc::bls12_381::field::fp_modulus;
+    use crate::prelude::{DefaultBuilder, GoldilocksField};
+
+    type Value = Fp12Value<GoldilocksField>;
+
+    fn random_fp12() -> native::Fp12 {
+        let modulus = fp_modulus();
+        let mut rng = OsRng;
+        let mut fp2 = || {
+            (
+                rng.gen_biguint_below(&modulus),
+                rng.gen_biguint_below(&modulus),
+            )
+        };
+        [[fp2(), fp2(), fp2()], [fp2(), fp2(), fp2()]]
+    }
+
+    fn fp12_pow(a: &native::Fp12, exponent: &BigUint) -> native::Fp12 {
+        let zero = || (BigUint::from(0u32), BigUint::from(0u32));
+        let one = (BigUint::from(1u32), BigUint::from(0u32));
+        let mut result = [[one, zero(), zero()], [zero(), zero(), zero()]];
+        for i in (0..exponent.bits()).rev() {
+            result = native::fp12_mul(&result, &result);
+            if exponent.bit(i) {
+                result = native::fp12_mul(&result, a);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_bls_fp12_arithmetic() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Fp12Variable>();
+        let b = builder.read::<Fp12Variable>();
+        let results = [
+            builder.bls_fp12_mul(a, b),
+            builder.bls_fp12_square(a),
+            builder.bls_fp12_mul_by_014(a, b.c0.c0, b.c0.c1, b.c1.c1),
+            builder.bls_fp12_inv(a),
+            builder.bls_fp12_frobenius(a, 1),
+            builder.bls_fp12_frobenius(a, 2),
+        ];
+        for result in results {
+            builder.write(result);
+        }
+        let circuit = builder.mock_build();
+
+        let a = random_fp12();
+        let b = random_fp12();
+        let mut input = circuit.input();
+        input.write::<Fp12Variable>(Value::from_native(a.clone()));
+        input.write::<Fp12Variable>(Value::from_native(b.clone()));
+        let mut output = circuit.mock_prove(&input).output;
+
+        let zero = || (BigUint::from(0u32), BigUint::from(0u32));
+        let sparse = [
+            [b[0][0].clone(), b[0][1].clone(), zero()],
+            [zero(), b[1][1].clone(), zero()],
+        ];
+        let modulus = fp_modulus();
+        let expected = [
+            native::fp12_mul(&a, &b),
+            native::fp12_mul(&a, &a),
+            native::fp12_mul(&a, &sparse),
+            native::fp12_inv(&a),
+            fp12_pow(&a, &modulus),
+            fp12_pow(&a, &(&modulus * &modulus)),
+        ];
+        for expected in expected {
+            assert_eq!(output.read::<Fp12Variable>(), Value::from_native(expected));
+        }
+    }
+}
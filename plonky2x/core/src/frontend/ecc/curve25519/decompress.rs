@@ -0,0 +1,211 @@
+//! Decompression of Ed25519 points from their 32-byte encoding, as specified in RFC 8032.
+
+use array_macro::array;
+use ethers::types::U256;
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+use starkyx::chip::ec::edwards::ed25519::params::Ed25519;
+use starkyx::chip::ec::point::AffinePoint;
+use starkyx::chip::ec::EllipticCurveParameters;
+
+use crate::frontend::curta::ec::point::AffinePointVariable;
+use crate::frontend::curta::field::variable::FieldVariable;
+use crate::frontend::ecc::secp256k1::field::{
+    u256_to_biguint, NonNativeFieldParameters, NonNativeFieldVariable,
+};
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::vars::{ValueStream, VariableStream};
+use crate::prelude::{
+    ByteVariable, Bytes32Variable, BytesVariable, CircuitBuilder, CircuitVariable, Field,
+    PlonkParameters, U256Variable, U32Variable, Variable,
+};
+
+type Ed25519BaseField = <Ed25519 as EllipticCurveParameters>::BaseField;
+
+/// The base field of Ed25519, with modulus `2^255 - 19`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ed25519Base;
+
+impl NonNativeFieldParameters for Ed25519Base {
+    const MODULUS: U256 = U256([
+        0xFFFFFFFFFFFFFFED,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0x7FFFFFFFFFFFFFFF,
+    ]);
+}
+
+/// The constant `d = -121665 / 121666` of the curve `-x^2 + y^2 = 1 + d * x^2 * y^2`.
+const EDWARDS_D: U256 = U256([
+    0x75EB4DCA135978A3,
+    0x00700A4D4141D8AB,
+    0x8CC740797779E898,
+    0x52036CEE2B6FFE73,
+]);
+
+/// Recovers the x coordinate with the given parity of the point with y coordinate `y`, if any.
+fn ed25519_recover_x(y: &BigUint, is_odd: bool) -> Option<BigUint> {
+    let modulus = Ed25519Base::modulus();
+    let d = u256_to_biguint(EDWARDS_D);
+    let y_sq = y * y % &modulus;
+    let numerator = (&y_sq + &modulus - 1u32) % &modulus;
+    let denominator = (d * &y_sq + 1u32) % &modulus;
+    let x_sq = numerator * denominator.modpow(&(&modulus - 2u32), &modulus) % &modulus;
+
+    // The modulus is 5 mod 8, so a square root is either x_sq^((p + 3) / 8) or that times
+    // sqrt(-1) = 2^((p - 1) / 4).
+    let mut x = x_sq.modpow(&((&modulus + 3u32) / 8u32), &modulus);
+    if &x * &x % &modulus != x_sq {
+        let sqrt_minus_one = BigUint::from(2u32).modpow(&((&modulus - 1u32) / 4u32), &modulus);
+        x = x * sqrt_minus_one % &modulus;
+    }
+    if &x * &x % &modulus != x_sq {
+        return None;
+    }
+    if x.bit(0) != is_odd {
+        x = (&modulus - x) % &modulus;
+    }
+    Some(x)
+}
+
+/// Decompresses an Ed25519 point outside of the circuit. An invalid encoding yields a point with
+/// x = 0, which fails the constraints of `decompress_ed25519`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ed25519DecompressHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for Ed25519DecompressHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let mut bytes = input_stream.read_value::<Bytes32Variable>().0;
+        let is_odd = bytes[31] >> 7 == 1;
+        bytes[31] &= 0x7F;
+        let y = BigUint::from_bytes_le(&bytes) % Ed25519Base::modulus();
+        let x = ed25519_recover_x(&y, is_odd).unwrap_or_default();
+        output_stream.write_value::<AffinePointVariable<Ed25519>>(AffinePoint::new(x, y));
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Decompresses an Ed25519 point from its encoding, i.e. the little-endian y coordinate with
+    /// the parity of x in the most significant bit.
+    ///
+    /// The point is hinted, and constrained by the curve equation and the parity bit. The circuit
+    /// is unsatisfiable for invalid encodings, which includes a y coordinate of at least `p`, a y
+    /// coordinate that is not on the curve, and x = 0 with the parity bit set.
+    pub fn decompress_ed25519(
+        &mut self,
+        compressed: Bytes32Variable,
+    ) -> AffinePointVariable<Ed25519> {
+        let mut bytes = compressed.as_bytes();
+        let mut last_byte = bytes[31].as_be_bits();
+        let is_odd = last_byte[0];
+        last_byte[0] = self._false();
+        bytes[31] = ByteVariable::from_be_bits(last_byte);
+        let encoded_y = self.u256_from_le_bytes(BytesVariable(bytes));
+
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&compressed);
+        let output_stream = self.hint(input_stream, Ed25519DecompressHint);
+        let point = output_stream.read::<AffinePointVariable<Ed25519>>(self);
+
+        // The hinted coordinates are reduced, so this also rejects non-canonical encodings of y.
+        let x = self.ed25519_field_to_nonnative(&point.x);
+        let y = self.ed25519_field_to_nonnative(&point.y);
+        self.assert_is_equal(y.value, encoded_y);
+
+        // -x^2 + y^2 = 1 + d * x^2 * y^2, i.e. x^2 * (d * y^2 + 1) = y^2 - 1.
+        let one = self.constant::<NonNativeFieldVariable<Ed25519Base>>(U256::one());
+        let d = self.constant::<NonNativeFieldVariable<Ed25519Base>>(EDWARDS_D);
+        let y_sq = self.nonnative_mul(y, y);
+        let denominator = self.nonnative_mul_add(d, y_sq, one);
+        let x_sq = self.nonnative_mul(x, x);
+        let lhs = self.nonnative_mul(x_sq, denominator);
+        let rhs = self.nonnative_sub(y_sq, one);
+        self.assert_is_equal(lhs, rhs);
+
+        // The parity of x must match, which also rejects x = 0 with the parity bit set.
+        let x_bits = x.value.limbs[0].to_be_bits(self);
+        self.assert_is_equal(x_bits[31], is_odd);
+
+        point
+    }
+
+    /// Converts a reduced element of the base field from sixteen 16-bit limbs to eight u32 limbs.
+    fn ed25519_field_to_nonnative(
+        &mut self,
+        value: &FieldVariable<Ed25519BaseField>,
+    ) -> NonNativeFieldVariable<Ed25519Base> {
+        let limbs = value.variables();
+        assert_eq!(limbs.len(), 16);
+        let base = self.constant::<Variable>(L::Field::from_canonical_u32(1 << 16));
+        let limbs = array![i => {
+            let high = self.mul(limbs[2 * i + 1], base);
+            let limb = self.add(limbs[2 * i], high);
+            U32Variable::from_variables_unsafe(&[limb])
+        }; 8];
+        NonNativeFieldVariable::from_u256_unsafe(U256Variable { limbs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+    use starkyx::chip::ec::edwards::ed25519::params::Ed25519Parameters;
+    use starkyx::chip::ec::edwards::EdwardsParameters;
+
+    use super::*;
+    use crate::prelude::DefaultBuilder;
+
+    /// The encoding of the base point, whose x coordinate is even.
+    const BASE_POINT: [u8; 32] = [
+        0x58, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+        0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+        0x66, 0x66,
+    ];
+
+    fn decompress(compressed: [u8; 32]) -> AffinePoint<Ed25519> {
+        let mut builder = DefaultBuilder::new();
+        let compressed_variable = builder.read::<Bytes32Variable>();
+        let point = builder.decompress_ed25519(compressed_variable);
+        builder.write(point);
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.write::<Bytes32Variable>(H256(compressed));
+        let mut output = circuit.mock_prove(&input).output;
+        output.read::<AffinePointVariable<Ed25519>>()
+    }
+
+    #[test]
+    fn test_decompress_ed25519() {
+        let (generator_x, generator_y) = Ed25519Parameters::generator();
+        let point = decompress(BASE_POINT);
+        assert_eq!(point.x, generator_x);
+        assert_eq!(point.y, generator_y);
+
+        // Flipping the sign bit yields the negation of the point.
+        let mut flipped = BASE_POINT;
+        flipped[31] ^= 0x80;
+        let point = decompress(flipped);
+        assert_eq!(point.x, Ed25519Base::modulus() - generator_x);
+        assert_eq!(point.y, generator_y);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decompress_ed25519_not_on_curve() {
+        // There is no point with y = 2.
+        let mut compressed = [0u8; 32];
+        compressed[0] = 2;
+        decompress(compressed);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decompress_ed25519_non_canonical() {
+        // y = p + 1 is the non-canonical encoding of y = 1, i.e. of the identity.
+        let mut compressed = [0xFF; 32];
+        compressed[0] = 0xEE;
+        compressed[31] = 0x7F;
+        decompress(compressed);
+    }
+}
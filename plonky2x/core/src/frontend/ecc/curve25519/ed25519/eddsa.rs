@@ -5,19 +5,29 @@ use ::starkyx::chip::field::parameters::FieldParameters;
 use array_macro::array;
 use curve25519_dalek::edwards::CompressedEdwardsY;
 use ethers::types::{U256, U512};
+use itertools::Itertools;
+use num_bigint::BigUint;
 use plonky2::hash::hash_types::RichField;
-use starkyx::chip::ec::edwards::ed25519::params::Ed25519Parameters;
+use starkyx::chip::ec::edwards::ed25519::params::{Ed25519, Ed25519Parameters};
 use starkyx::chip::ec::edwards::EdwardsParameters;
 use starkyx::chip::ec::point::AffinePoint;
 
 use crate::frontend::curta::ec::point::{AffinePointVariable, CompressedEdwardsYVariable};
 use crate::frontend::uint::num::biguint::biguint_from_bytes_variable;
 use crate::frontend::uint::uint512::U512Variable;
+use crate::frontend::vars::EvmVariable;
 use crate::prelude::{
-    ArrayVariable, BoolVariable, BytesVariable, CircuitBuilder, CircuitVariable, PlonkParameters,
-    U256Variable, U32Variable, Variable,
+    ArrayVariable, BoolVariable, ByteVariable, BytesVariable, CircuitBuilder, CircuitVariable,
+    Field, PlonkParameters, U256Variable, U32Variable, Variable,
 };
 
+/// The number of bits of a scalar reduced modulo the order of the curve.
+const ED25519_SCALAR_BITS: usize = 253;
+
+/// The number of points that `curta_25519_msm` adds at once, from a table of the sums of their
+/// subsets.
+const MSM_GROUP_SIZE: usize = 4;
+
 #[derive(Clone, Debug, CircuitVariable)]
 pub struct EDDSASignatureVariable {
     pub r: CompressedEdwardsYVariable,
@@ -126,6 +136,46 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         self.curta_eddsa_verify_sigs(msg_array, Some(msg_len_vec), sig_array, pub_key_array);
     }
 
+    /// Returns the challenge `H(R || A || M)` of a signature, reduced modulo the order of the
+    /// curve. If message_byte_length is None, the message has the length of MAX_MSG_LENGTH_BYTES.
+    fn curta_eddsa_challenge<const MAX_MSG_LENGTH_BYTES: usize>(
+        &mut self,
+        signature: &EDDSASignatureVariable,
+        pubkey: &CompressedEdwardsYVariable,
+        message: &BytesVariable<MAX_MSG_LENGTH_BYTES>,
+        message_byte_length: Option<U32Variable>,
+    ) -> U256Variable {
+        // Create a new BytesVariable that will contain the message to be hashed.
+        // The hashed message is a concatenation of sigR, pk, and msg.
+        let mut message_bytes = Vec::new();
+        message_bytes.extend(signature.r.0.as_bytes());
+        message_bytes.extend(pubkey.0.as_bytes());
+        message_bytes.extend(message.0);
+
+        let digest: BytesVariable<64>;
+        if let Some(msg_len) = message_byte_length {
+            let const_64 = U32Variable::constant(self, 64);
+            let message_to_hash_len = self.add(msg_len, const_64);
+            digest = self.curta_sha512_variable(&message_bytes, message_to_hash_len);
+        } else {
+            digest = self.curta_sha512(&message_bytes);
+        }
+
+        let scalar_modulus_value =
+            U512::from_little_endian(&Ed25519ScalarField::modulus().to_bytes_le());
+        let scalar_modulus = self.constant::<U512Variable>(scalar_modulus_value);
+        let h_limbs = biguint_from_bytes_variable(self, digest)
+            .limbs
+            .into_iter()
+            .map(|x| x.target)
+            .collect::<Vec<_>>();
+        let h_int = U512Variable::from_targets(&h_limbs);
+        let h_scalar_512_limbs = self.rem(h_int, scalar_modulus).limbs;
+        U256Variable {
+            limbs: array![i => h_scalar_512_limbs[i]; 8],
+        }
+    }
+
     /// This function will verify a set of eddsa signatures. If message_byte_lengths is None, then
     /// all the messages should have the length of MAX_MSG_LENGTH_BYTES.
     pub fn curta_eddsa_verify_sigs<
@@ -151,40 +201,18 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         let generator_affine = AffinePoint::new(generator_x, generator_y);
         let generator_var = AffinePointVariable::constant(self, generator_affine);
 
-        let scalar_modulus_value =
-            U512::from_little_endian(&Ed25519ScalarField::modulus().to_bytes_le());
-        let scalar_modulus = self.constant::<U512Variable>(scalar_modulus_value);
         let scalar_mod_256_value =
             U256::from_little_endian(&Ed25519ScalarField::modulus().to_bytes_le());
         let scalar_mod_256 = self.constant::<U256Variable>(scalar_mod_256_value);
 
         for i in 0..NUM_SIGS {
-            // Create a new BytesVariable that will contain the message to be hashed.
-            // The hashed message is a concatenation of sigR, pk, and msg.
-            let mut message_bytes = Vec::new();
-            message_bytes.extend(signatures[i].r.0.as_bytes());
-            message_bytes.extend(pubkeys[i].0.as_bytes());
-            message_bytes.extend(messages[i].0);
-
-            let digest: BytesVariable<64>;
-            if let Some(ref msg_lens) = message_byte_lengths {
-                let const_64 = U32Variable::constant(self, 64);
-                let message_to_hash_len = self.add(msg_lens[i], const_64);
-                digest = self.curta_sha512_variable(&message_bytes, message_to_hash_len);
-            } else {
-                digest = self.curta_sha512(&message_bytes);
-            }
-
-            let h_limbs = biguint_from_bytes_variable(self, digest)
-                .limbs
-                .into_iter()
-                .map(|x| x.target)
-                .collect::<Vec<_>>();
-            let h_int = U512Variable::from_targets(&h_limbs);
-            let h_scalar_512_limbs = self.rem(h_int, scalar_modulus).limbs;
-            let h_scalar = U256Variable {
-                limbs: array![i => h_scalar_512_limbs[i]; 8],
-            };
+            let message_byte_length = message_byte_lengths.as_ref().map(|lens| lens[i]);
+            let h_scalar = self.curta_eddsa_challenge(
+                &signatures[i],
+                &pubkeys[i],
+                &messages[i],
+                message_byte_length,
+            );
 
             let s = signatures[i].s;
             // Assert that s is less than the scalar modulus.
@@ -202,6 +230,192 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             self.assert_is_equal(p1, p2);
         }
     }
+
+    /// Returns whether all the active signatures are valid, checking them at once with the batch
+    /// equation `[8] ([sum z_i * s_i] B - sum ([z_i] R_i + [z_i * h_i] A_i)) = 0`.
+    ///
+    /// The 128-bit randomizers `z_i` are derived in the circuit from a hash of all the signatures,
+    /// public keys and challenges, so that a prover can't pick them to cancel invalid signatures.
+    /// The left-hand side is a single multi-scalar multiplication over `B`, every `R_i` and every
+    /// `A_i`, computed with `curta_25519_msm`, so its doublings are shared. The entries
+    /// where `is_active` is false are replaced by a dummy signature and have a randomizer of zero,
+    /// so they are skipped. The circuit is unsatisfiable if a point of an active entry can't be
+    /// decompressed.
+    pub fn ed25519_verify_batch<const MAX_MSG_LENGTH_BYTES: usize, const NUM_SIGS: usize>(
+        &mut self,
+        messages: ArrayVariable<BytesVariable<MAX_MSG_LENGTH_BYTES>, NUM_SIGS>,
+        signatures: ArrayVariable<EDDSASignatureVariable, NUM_SIGS>,
+        pubkeys: ArrayVariable<CompressedEdwardsYVariable, NUM_SIGS>,
+        is_active: ArrayVariable<BoolVariable, NUM_SIGS>,
+    ) -> BoolVariable {
+        assert!(NUM_SIGS > 0);
+
+        let (dummy_pub_key, dummy_sig, dummy_msg, _) =
+            self.get_dummy_variables::<MAX_MSG_LENGTH_BYTES>();
+        let scalar_mod_256_value =
+            U256::from_little_endian(&Ed25519ScalarField::modulus().to_bytes_le());
+        let scalar_mod_256 = self.constant::<U256Variable>(scalar_mod_256_value);
+
+        let mut is_valid = self._true();
+        let mut transcript = Vec::new();
+        let mut entries = Vec::new();
+        for i in 0..NUM_SIGS {
+            let message = self.select(is_active[i], messages[i], dummy_msg);
+            let signature = self.select(is_active[i], signatures[i].clone(), dummy_sig.clone());
+            let pubkey = self.select(is_active[i], pubkeys[i].clone(), dummy_pub_key.clone());
+            let h_scalar = self.curta_eddsa_challenge(&signature, &pubkey, &message, None);
+
+            let s_lt_scalar_mod = self.lt(signature.s, scalar_mod_256);
+            is_valid = self.and(is_valid, s_lt_scalar_mod);
+
+            let s_bytes = self.u256_to_le_bytes(signature.s);
+            let h_bytes = self.u256_to_le_bytes(h_scalar);
+            transcript.extend(signature.r.0.as_bytes());
+            transcript.extend(pubkey.0.as_bytes());
+            transcript.extend(s_bytes.0);
+            transcript.extend(h_bytes.0);
+            entries.push((signature, pubkey, h_scalar));
+        }
+        let seed = self.curta_sha512(&transcript);
+
+        let zero_u256 = self.zero::<U256Variable>();
+        let zero_byte = self.zero::<ByteVariable>();
+        let mut s_sum = zero_u256;
+        let mut scalars = Vec::with_capacity(2 * NUM_SIGS + 1);
+        let mut points = Vec::with_capacity(2 * NUM_SIGS + 1);
+        for (i, (signature, pubkey, h_scalar)) in entries.into_iter().enumerate() {
+            // z_i is the first 16 bytes of H(seed || i), or zero for an inactive entry.
+            let mut randomizer_input = seed.0.to_vec();
+            let index = self.constant::<U32Variable>(i as u32);
+            randomizer_input.extend(index.encode(self));
+            let randomizer_digest = self.curta_sha512(&randomizer_input);
+            let mut randomizer_bytes = [zero_byte; 32];
+            randomizer_bytes[16..].copy_from_slice(&randomizer_digest.0[..16]);
+            let randomizer = self.u256_from_be_bytes(BytesVariable(randomizer_bytes));
+            let randomizer = self.select(is_active[i], randomizer, zero_u256);
+
+            let s_term = self.mulmod(randomizer, signature.s, scalar_mod_256);
+            s_sum = self.addmod(s_sum, s_term, scalar_mod_256);
+
+            let sigr_affine = self.curta_25519_decompress(signature.r);
+            self.curta_25519_is_valid(sigr_affine.clone());
+            let pubkey_affine = self.curta_25519_decompress(pubkey);
+            self.curta_25519_is_valid(pubkey_affine.clone());
+            let pubkey_scalar = self.mulmod(randomizer, h_scalar, scalar_mod_256);
+
+            // The terms of the right-hand side are subtracted by multiplying their points by the
+            // opposite scalars. For a zero scalar, the point is multiplied by the order of the
+            // curve instead, which only leaves a torsion component that the cofactor clears.
+            let r_scalar = self.sub(scalar_mod_256, randomizer);
+            let pubkey_scalar = self.sub(scalar_mod_256, pubkey_scalar);
+            scalars.push(r_scalar);
+            points.push(sigr_affine);
+            scalars.push(pubkey_scalar);
+            points.push(pubkey_affine);
+        }
+
+        let (generator_x, generator_y) = Ed25519Parameters::generator();
+        let generator_var =
+            AffinePointVariable::constant(self, AffinePoint::new(generator_x, generator_y));
+        scalars.push(s_sum);
+        points.push(generator_var);
+        let sum = self.curta_25519_msm(&scalars, &points);
+
+        let sum = self.curta_25519_mul_by_cofactor(sum);
+        let identity = self.curta_25519_identity();
+        let equation_holds = self.is_equal(sum, identity);
+        self.and(is_valid, equation_holds)
+    }
+
+    /// Returns `sum [scalars[i]] points[i]` with Straus' method, where the scalars are below
+    /// 2^253, e.g. reduced modulo the order of the curve.
+    ///
+    /// The doublings are shared by all the points, and at each bit the points are added by groups
+    /// of `MSM_GROUP_SIZE`, selecting the sum of the points of the group whose scalars have the
+    /// bit set from a table of the sums of the subsets of the group. This takes 253 doublings and
+    /// `253 * ceil(n / MSM_GROUP_SIZE)` additions, instead of the 256 rows in the Curta stark of
+    /// each of `n` scalar multiplications.
+    fn curta_25519_msm(
+        &mut self,
+        scalars: &[U256Variable],
+        points: &[AffinePointVariable<Ed25519>],
+    ) -> AffinePointVariable<Ed25519> {
+        assert_eq!(scalars.len(), points.len());
+        let f = self._false();
+        let scalar_bits = scalars
+            .iter()
+            .map(|scalar| {
+                let bytes = self.u256_to_le_bytes(*scalar);
+                let bits = bytes
+                    .0
+                    .iter()
+                    .flat_map(|byte| byte.as_le_bits())
+                    .collect_vec();
+                for bit in bits[ED25519_SCALAR_BITS..].iter() {
+                    self.assert_is_equal(*bit, f);
+                }
+                bits
+            })
+            .collect_vec();
+
+        // The entry `j` of the table of a group is the sum of the points of the group at the
+        // indices of the bits set in `j`.
+        let identity = self.curta_25519_identity();
+        let tables = points
+            .chunks(MSM_GROUP_SIZE)
+            .map(|group| {
+                let mut table = vec![identity.clone()];
+                for point in group {
+                    for j in 0..table.len() {
+                        let sum = match j {
+                            0 => point.clone(),
+                            _ => self.curta_25519_add(table[j].clone(), point.clone()),
+                        };
+                        table.push(sum);
+                    }
+                }
+                table
+            })
+            .collect_vec();
+
+        let mut result = identity;
+        for bit in (0..ED25519_SCALAR_BITS).rev() {
+            if bit + 1 < ED25519_SCALAR_BITS {
+                result = self.curta_25519_add(result.clone(), result);
+            }
+            for (group_bits, table) in scalar_bits.chunks(MSM_GROUP_SIZE).zip(tables.iter()) {
+                let mut index = self.zero::<Variable>();
+                for (j, bits) in group_bits.iter().enumerate() {
+                    let weight = self.constant::<Variable>(L::Field::from_canonical_usize(1 << j));
+                    let term = self.mul(bits[bit].variable, weight);
+                    index = self.add(index, term);
+                }
+                let term = self.select_array_random_gate(table, index);
+                result = self.curta_25519_add(result, term);
+            }
+        }
+        result
+    }
+
+    /// Returns the identity of the curve, which is the point `(0, 1)`.
+    fn curta_25519_identity(&mut self) -> AffinePointVariable<Ed25519> {
+        AffinePointVariable::constant(
+            self,
+            AffinePoint::new(BigUint::from(0u32), BigUint::from(1u32)),
+        )
+    }
+
+    /// Returns `[8] point`, clearing the torsion component of `point`.
+    fn curta_25519_mul_by_cofactor(
+        &mut self,
+        point: AffinePointVariable<Ed25519>,
+    ) -> AffinePointVariable<Ed25519> {
+        let mut result = point;
+        for _ in 0..3 {
+            result = self.curta_25519_add(result.clone(), result);
+        }
+        result
+    }
 }
 
 #[cfg(test)]
@@ -209,11 +423,13 @@ mod tests {
     use curve25519_dalek::edwards::CompressedEdwardsY;
     use ed25519_dalek::{Signer, SigningKey};
     use ethers::types::U256;
+    use log::debug;
     use plonky2::field::goldilocks_field::GoldilocksField;
     use rand::rngs::OsRng;
     use rand::Rng;
 
     use crate::frontend::curta::ec::point::CompressedEdwardsYVariable;
+    use crate::frontend::ecc::curve25519::curta::request::EcOpRequestType;
     use crate::frontend::ecc::curve25519::ed25519::eddsa::{
         EDDSASignatureVariable, EDDSASignatureVariableValue,
     };
@@ -474,4 +690,120 @@ mod tests {
             false,
         );
     }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ed25519_verify_batch() {
+        utils::setup_logger();
+        const BATCH_SIZE: usize = 8;
+        const BATCH_MSG_LEN_BYTES: usize = 32;
+        const INACTIVE_SLOT: usize = 3;
+
+        let mut builder = DefaultBuilder::new();
+        let is_active = builder.read::<ArrayVariable<BoolVariable, BATCH_SIZE>>();
+        let pkeys = builder.read::<ArrayVariable<CompressedEdwardsYVariable, BATCH_SIZE>>();
+        let signatures = builder.read::<ArrayVariable<EDDSASignatureVariable, BATCH_SIZE>>();
+        let messages =
+            builder.read::<ArrayVariable<BytesVariable<BATCH_MSG_LEN_BYTES>, BATCH_SIZE>>();
+        let is_valid = builder.ed25519_verify_batch(messages, signatures, pkeys, is_active);
+        builder.write(is_valid);
+        let circuit = builder.build();
+
+        let mut test_is_active = Vec::new();
+        let mut test_messages = Vec::new();
+        let mut test_pub_keys = Vec::new();
+        let mut test_signatures = Vec::new();
+        let mut csprng = OsRng;
+        for i in 0..BATCH_SIZE {
+            let mut test_message = [0u8; BATCH_MSG_LEN_BYTES];
+            rand::thread_rng().fill(&mut test_message);
+            let test_signing_key = SigningKey::generate(&mut csprng);
+            let test_signature = test_signing_key.sign(&test_message);
+
+            // The inactive slot holds a signature of another message, which must be skipped.
+            if i == INACTIVE_SLOT {
+                test_message[0] = test_message[0].wrapping_add(1);
+            }
+            test_is_active.push(i != INACTIVE_SLOT);
+            test_messages.push(test_message);
+            test_pub_keys.push(CompressedEdwardsY(
+                test_signing_key.verifying_key().to_bytes(),
+            ));
+            test_signatures.push(EDDSASignatureVariableValue {
+                r: CompressedEdwardsY(*test_signature.r_bytes()),
+                s: U256::from_little_endian(test_signature.s_bytes()),
+            });
+        }
+
+        let prove = |messages: Vec<[u8; BATCH_MSG_LEN_BYTES]>| {
+            let mut input = circuit.input();
+            input.write::<ArrayVariable<BoolVariable, BATCH_SIZE>>(test_is_active.clone());
+            input.write::<ArrayVariable<CompressedEdwardsYVariable, BATCH_SIZE>>(
+                test_pub_keys.clone(),
+            );
+            input.write::<ArrayVariable<EDDSASignatureVariable, BATCH_SIZE>>(
+                test_signatures.clone(),
+            );
+            input.write::<ArrayVariable<BytesVariable<BATCH_MSG_LEN_BYTES>, BATCH_SIZE>>(messages);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            output.read::<BoolVariable>()
+        };
+
+        assert!(prove(test_messages.clone()));
+
+        // An invalid signature in an active slot fails the batch.
+        let mut invalid_messages = test_messages;
+        invalid_messages[0][0] = invalid_messages[0][0].wrapping_add(1);
+        assert!(!prove(invalid_messages));
+    }
+
+    /// Returns the number of point additions of the EC operations requested by a circuit, counting
+    /// a scalar multiplication as the 256 additions of its rows in the Curta stark.
+    fn ec_op_additions(builder: &DefaultBuilder) -> usize {
+        builder
+            .ec_25519_ops_accelerator
+            .as_ref()
+            .map_or(0, |accelerator| {
+                accelerator
+                    .ec_op_requests
+                    .iter()
+                    .map(|request| match request.req_type() {
+                        EcOpRequestType::Add => 1,
+                        EcOpRequestType::ScalarMul => 256,
+                        EcOpRequestType::Decompress | EcOpRequestType::IsValid => 0,
+                    })
+                    .sum()
+            })
+    }
+
+    #[test]
+    fn test_ed25519_verify_batch_op_count() {
+        const BATCH_SIZE: usize = 8;
+        const BATCH_MSG_LEN_BYTES: usize = 32;
+
+        let mut builder = DefaultBuilder::new();
+        let is_active = builder.read::<ArrayVariable<BoolVariable, BATCH_SIZE>>();
+        let pkeys = builder.read::<ArrayVariable<CompressedEdwardsYVariable, BATCH_SIZE>>();
+        let signatures = builder.read::<ArrayVariable<EDDSASignatureVariable, BATCH_SIZE>>();
+        let messages =
+            builder.read::<ArrayVariable<BytesVariable<BATCH_MSG_LEN_BYTES>, BATCH_SIZE>>();
+        builder.ed25519_verify_batch(messages, signatures, pkeys, is_active);
+        let batch_additions = ec_op_additions(&builder);
+
+        // The same signatures, each verified on its own.
+        let mut builder_single = DefaultBuilder::new();
+        let pkeys = builder_single.read::<ArrayVariable<CompressedEdwardsYVariable, BATCH_SIZE>>();
+        let signatures = builder_single.read::<ArrayVariable<EDDSASignatureVariable, BATCH_SIZE>>();
+        let messages =
+            builder_single.read::<ArrayVariable<BytesVariable<BATCH_MSG_LEN_BYTES>, BATCH_SIZE>>();
+        builder_single.curta_eddsa_verify_sigs(messages, None, signatures, pkeys);
+        let single_additions = ec_op_additions(&builder_single);
+
+        debug!(
+            "batch: {} additions, single: {} additions",
+            batch_additions, single_additions
+        );
+        assert!(batch_additions < single_additions);
+    }
 }
@@ -1,3 +1,4 @@
 pub mod curta;
+pub mod decompress;
 pub mod ec_ops;
 pub mod ed25519;
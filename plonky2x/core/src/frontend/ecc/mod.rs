@@ -1 +1,4 @@
+pub mod bls12_381;
 pub mod curve25519;
+pub mod p256;
+pub mod secp256k1;
@@ -0,0 +1,77 @@
+use ethers::types::U256;
+
+use super::field::{P256Base, P256Scalar};
+use crate::frontend::ecc::secp256k1::curve::{
+    WeierstrassCurve, WeierstrassPointVariable, WeierstrassPointVariableValue,
+};
+use crate::frontend::ecc::secp256k1::field::NonNativeFieldVariable;
+
+/// The NIST curve P-256 (secp256r1), `y^2 = x^3 - 3 * x + b`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct P256;
+
+impl WeierstrassCurve for P256 {
+    type BaseField = P256Base;
+    type ScalarField = P256Scalar;
+
+    const A: U256 = U256([
+        0xFFFFFFFFFFFFFFFC,
+        0x00000000FFFFFFFF,
+        0x0000000000000000,
+        0xFFFFFFFF00000001,
+    ]);
+    const B: U256 = U256([
+        0x3BCE3C3E27D2604B,
+        0x651D06B0CC53B0F6,
+        0xB3EBBD55769886BC,
+        0x5AC635D8AA3A93E7,
+    ]);
+    const GENERATOR: (U256, U256) = (
+        U256([
+            0xF4A13945D898C296,
+            0x77037D812DEB33A0,
+            0xF8BCE6E563A440F2,
+            0x6B17D1F2E12C4247,
+        ]),
+        U256([
+            0xCBB6406837BF51F5,
+            0x2BCE33576B315ECE,
+            0x8EE7EB4A7C0F9E16,
+            0x4FE342E2FE1A7F9B,
+        ]),
+    );
+    // The point with the even y coordinate and x = SHA-256("plonky2x/P-256/offset") mod p, which
+    // happens to be on the curve.
+    const OFFSET: (U256, U256) = (
+        U256([
+            0x9AD281B10D891DF9,
+            0x0DA651435EF9A094,
+            0xBF9972E7B57EA45B,
+            0x7AEBC2811763DBC3,
+        ]),
+        U256([
+            0x7C14B0FF2520394A,
+            0xF19A6D1E287073AA,
+            0x662D26FF2828BF23,
+            0xCB223C1B351E7815,
+        ]),
+    );
+    const SHIFTED_OFFSET: (U256, U256) = (
+        U256([
+            0xA784C3859B8901F6,
+            0xB48D2BEF534FDB97,
+            0xB46A48F029F12DDD,
+            0xDC90EB242DF60DB5,
+        ]),
+        U256([
+            0xD9AF91D5B23AA197,
+            0xB213B437AF8FE369,
+            0x31FF1DDC892FF45F,
+            0x15AA26103D485489,
+        ]),
+    );
+}
+
+pub type P256BaseVariable = NonNativeFieldVariable<P256Base>;
+pub type P256PointVariable = WeierstrassPointVariable<P256>;
+pub type P256PointVariableValue<F> = WeierstrassPointVariableValue<P256, F>;
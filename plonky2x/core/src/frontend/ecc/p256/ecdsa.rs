@@ -0,0 +1,87 @@
+//! ECDSA over P-256, the curve of WebAuthn and passkey signatures.
+
+use super::curve::{P256PointVariable, P256};
+use crate::frontend::ecc::secp256k1::ecdsa::{ECDSAConfig, ECDSASignatureVariable};
+use crate::prelude::{BoolVariable, Bytes32Variable, CircuitBuilder, PlonkParameters};
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Verifies a P-256 ECDSA signature of `message_hash` under `public_key`, returning whether it
+    /// is valid. Unlike `ecdsa_verify`, signatures with a high `s` are valid, since P-256 verifiers
+    /// accept both twins. Signatures with `r` or `s` out of `[1, n)` and public keys that are not
+    /// on the curve yield `false` rather than an unsatisfiable circuit.
+    pub fn p256_verify(
+        &mut self,
+        message_hash: Bytes32Variable,
+        signature: ECDSASignatureVariable,
+        public_key: P256PointVariable,
+    ) -> BoolVariable {
+        let generator_table = self.ec_generator_window_table::<P256>();
+        self.ecdsa_verify_with_table(
+            &generator_table,
+            message_hash,
+            signature,
+            public_key,
+            ECDSAConfig { allow_high_s: true },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::U256;
+    use p256::ecdsa::signature::hazmat::PrehashSigner;
+    use p256::ecdsa::{Signature, SigningKey};
+    use p256::elliptic_curve::sec1::ToEncodedPoint;
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+    use crate::backend::circuit::MockCircuitBuild;
+    use crate::frontend::ecc::p256::curve::P256PointVariableValue;
+    use crate::frontend::ecc::p256::field::P256Scalar;
+    use crate::frontend::ecc::secp256k1::ecdsa::ECDSASignature;
+    use crate::frontend::ecc::secp256k1::field::NonNativeFieldParameters;
+    use crate::prelude::{DefaultBuilder, GoldilocksField};
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_p256_verify() {
+        let mut builder = DefaultBuilder::new();
+        let message_hash = builder.read::<Bytes32Variable>();
+        let signature = builder.read::<ECDSASignatureVariable>();
+        let public_key = builder.read::<P256PointVariable>();
+        let is_valid = builder.p256_verify(message_hash, signature, public_key);
+        builder.write(is_valid);
+        let circuit = builder.mock_build();
+
+        let key = SigningKey::from_bytes(&[0x42; 32].into()).unwrap();
+        let hash: [u8; 32] = Sha256::digest(b"webauthn client data").into();
+        let signature: Signature = key.sign_prehash(&hash).unwrap();
+        let signature = ECDSASignature::<GoldilocksField> {
+            r: U256::from_big_endian(&signature.r().to_bytes()),
+            s: U256::from_big_endian(&signature.s().to_bytes()),
+        };
+        let public_key = key.verifying_key().to_encoded_point(false);
+        let public_key = P256PointVariableValue {
+            x: U256::from_big_endian(public_key.x().unwrap()),
+            y: U256::from_big_endian(public_key.y().unwrap()),
+        };
+
+        // Both twins of the signature are valid.
+        let twin = ECDSASignature {
+            r: signature.r,
+            s: P256Scalar::MODULUS - signature.s,
+        };
+        let corrupted = ECDSASignature {
+            r: signature.r,
+            s: signature.s ^ U256::one(),
+        };
+        for (signature, expected) in [(signature, true), (twin, true), (corrupted, false)] {
+            let mut input = circuit.input();
+            input.write::<Bytes32Variable>(hash.into());
+            input.write::<ECDSASignatureVariable>(signature);
+            input.write::<P256PointVariable>(public_key.clone());
+            let mut output = circuit.mock_prove(&input).output;
+            assert_eq!(output.read::<BoolVariable>(), expected);
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use ethers::types::U256;
+
+use crate::frontend::ecc::secp256k1::field::NonNativeFieldParameters;
+
+/// The base field of P-256.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct P256Base;
+
+impl NonNativeFieldParameters for P256Base {
+    const MODULUS: U256 = U256([
+        0xFFFFFFFFFFFFFFFF,
+        0x00000000FFFFFFFF,
+        0x0000000000000000,
+        0xFFFFFFFF00000001,
+    ]);
+}
+
+/// The scalar field of P-256, i.e. the order of its group of points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct P256Scalar;
+
+impl NonNativeFieldParameters for P256Scalar {
+    const MODULUS: U256 = U256([
+        0xF3B9CAC2FC632551,
+        0xBCE6FAADA7179E84,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFF00000000,
+    ]);
+}
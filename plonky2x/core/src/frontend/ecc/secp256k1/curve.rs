@@ -0,0 +1,438 @@
+use core::fmt::Debug;
+
+use ethers::types::U256;
+use num::BigUint;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::BoolTarget;
+use serde::{Deserialize, Serialize};
+
+use super::field::{
+    biguint_to_u256, u256_to_biguint, NonNativeFieldParameters, NonNativeFieldVariable,
+    Secp256k1Base, Secp256k1Scalar,
+};
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::vars::{ValueStream, VariableStream};
+use crate::prelude::{
+    BoolVariable, CircuitBuilder, CircuitVariable, PlonkParameters, U256Variable, Variable,
+};
+
+/// The parameters of a curve `y^2 = x^3 + a * x + b` of prime order over a non-native field. The
+/// point arithmetic, the scalar multiplication and ECDSA are generic over these parameters.
+pub trait WeierstrassCurve: Debug + Clone + Copy + Send + Sync + 'static {
+    /// The field of the coordinates.
+    type BaseField: NonNativeFieldParameters;
+    /// The field of the scalars, whose modulus is the order of the curve.
+    type ScalarField: NonNativeFieldParameters;
+
+    /// The coefficient `a` of the curve equation.
+    const A: U256;
+    /// The coefficient `b` of the curve equation.
+    const B: U256;
+    /// The generator of the group of points.
+    const GENERATOR: (U256, U256);
+    /// The point that the accumulator of the scalar multiplication starts at, so that the
+    /// incomplete addition formulas never see the point at infinity. Its discrete logarithm must
+    /// be unknown.
+    const OFFSET: (U256, U256);
+    /// `2^256 * OFFSET`, which is what the offset accumulates to after 256 doublings.
+    const SHIFTED_OFFSET: (U256, U256);
+}
+
+/// An affine point on a Weierstrass curve. The point at infinity is not representable.
+#[derive(Debug, Clone, Copy, CircuitVariable)]
+pub struct WeierstrassPointVariable<C: WeierstrassCurve> {
+    pub x: NonNativeFieldVariable<C::BaseField>,
+    pub y: NonNativeFieldVariable<C::BaseField>,
+}
+
+/// The curve secp256k1, `y^2 = x^3 + 7`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Secp256k1;
+
+impl WeierstrassCurve for Secp256k1 {
+    type BaseField = Secp256k1Base;
+    type ScalarField = Secp256k1Scalar;
+
+    const A: U256 = U256([0; 4]);
+    const B: U256 = U256([7, 0, 0, 0]);
+    const GENERATOR: (U256, U256) = GENERATOR;
+    const OFFSET: (U256, U256) = OFFSET;
+    const SHIFTED_OFFSET: (U256, U256) = SHIFTED_OFFSET;
+}
+
+pub type Secp256k1BaseVariable = NonNativeFieldVariable<Secp256k1Base>;
+pub type Secp256k1ScalarVariable = NonNativeFieldVariable<Secp256k1Scalar>;
+pub type Secp256k1PointVariable = WeierstrassPointVariable<Secp256k1>;
+pub type Secp256k1PointVariableValue<F> = WeierstrassPointVariableValue<Secp256k1, F>;
+pub type Secp256k1WindowTable = WindowTable<Secp256k1>;
+
+/// The generator of secp256k1.
+pub const GENERATOR: (U256, U256) = (
+    U256([
+        0x59F2815B16F81798,
+        0x029BFCDB2DCE28D9,
+        0x55A06295CE870B07,
+        0x79BE667EF9DCBBAC,
+    ]),
+    U256([
+        0x9C47D08FFB10D4B8,
+        0xFD17B448A6855419,
+        0x5DA4FBFC0E1108A8,
+        0x483ADA7726A3C465,
+    ]),
+);
+
+// The offset of secp256k1 is the "nothing up my sleeve" point H from BIP-341.
+const OFFSET: (U256, U256) = (
+    U256([
+        0x47BFEE9ACE803AC0,
+        0x078A5A0F28EC96D5,
+        0xB78B4B6035E97A5E,
+        0x50929B74C1A04954,
+    ]),
+    U256([
+        0xC30C2313F3A38904,
+        0x36DAC28AF1766968,
+        0x049E637CB1B5F40A,
+        0x31D3C6863973926E,
+    ]),
+);
+
+const SHIFTED_OFFSET: (U256, U256) = (
+    U256([
+        0xA01F23194376F4CC,
+        0x4D9D23788515348E,
+        0x55D74F50AFA5F72C,
+        0x37772A1198A2EA28,
+    ]),
+    U256([
+        0x5C8D588521661F96,
+        0x5274EC09C33BA027,
+        0xF1B68185EAA1DFAB,
+        0x4E25FFC6E3619109,
+    ]),
+);
+
+/// Adds two points outside of the circuit, e.g. to precompute constant multiples of the generator.
+/// The points must not be opposite.
+pub(crate) fn ec_native_add<C: WeierstrassCurve>(a: (U256, U256), b: (U256, U256)) -> (U256, U256) {
+    let p = C::BaseField::modulus();
+    let inverse = |value: BigUint| value.modpow(&(&p - 2u32), &p);
+    let (ax, ay) = (u256_to_biguint(a.0), u256_to_biguint(a.1));
+    let (bx, by) = (u256_to_biguint(b.0), u256_to_biguint(b.1));
+    let lambda = if a == b {
+        (3u32 * &ax * &ax + u256_to_biguint(C::A)) * inverse(2u32 * &ay) % &p
+    } else {
+        (&by + &p - &ay) * inverse((&bx + &p - &ax) % &p) % &p
+    };
+    let x = (&lambda * &lambda + 2u32 * &p - &ax - &bx) % &p;
+    let y = (lambda * ((&ax + &p - &x) % &p) + &p - &ay) % &p;
+    (biguint_to_u256(&x), biguint_to_u256(&y))
+}
+
+/// Finds the even `y` such that `(x, y)` is on the curve. If `x^3 + 7` is not a square, it instead
+/// returns the even square root of `-(x^3 + 7)`, which always exists because `p = 3 mod 4`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Secp256k1LiftXHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for Secp256k1LiftXHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let x = u256_to_biguint(input_stream.read_value::<Secp256k1BaseVariable>());
+
+        let p = Secp256k1Base::modulus();
+        let exponent = (&p + 1u32) / 4u32;
+        let c = (x.modpow(&3u32.into(), &p) + 7u32) % &p;
+
+        let y = c.modpow(&exponent, &p);
+        let is_square = &y * &y % &p == c;
+        let y = if is_square {
+            y
+        } else {
+            (&p - &c).modpow(&exponent, &p)
+        };
+        let y = if y.bit(0) { &p - y } else { y };
+
+        output_stream.write_value::<BoolVariable>(is_square);
+        output_stream.write_value::<Secp256k1BaseVariable>(biguint_to_u256(&y));
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Initializes a point with a constant value in the circuit.
+    pub fn ec_constant_point<C: WeierstrassCurve>(
+        &mut self,
+        point: (U256, U256),
+    ) -> WeierstrassPointVariable<C> {
+        WeierstrassPointVariable {
+            x: self.constant::<NonNativeFieldVariable<C::BaseField>>(point.0),
+            y: self.constant::<NonNativeFieldVariable<C::BaseField>>(point.1),
+        }
+    }
+
+    /// Adds two points. The circuit is unsatisfiable if the points share an x coordinate, i.e. if
+    /// they are equal or opposite.
+    pub fn ec_add<C: WeierstrassCurve>(
+        &mut self,
+        a: WeierstrassPointVariable<C>,
+        b: WeierstrassPointVariable<C>,
+    ) -> WeierstrassPointVariable<C> {
+        let dy = self.nonnative_sub(b.y, a.y);
+        let dx = self.nonnative_sub(b.x, a.x);
+        let lambda = self.nonnative_div(dy, dx);
+
+        let lambda_sq = self.nonnative_mul(lambda, lambda);
+        let x = self.nonnative_sub(lambda_sq, a.x);
+        let x = self.nonnative_sub(x, b.x);
+
+        let x_diff = self.nonnative_sub(a.x, x);
+        let y = self.nonnative_mul(lambda, x_diff);
+        let y = self.nonnative_sub(y, a.y);
+
+        WeierstrassPointVariable { x, y }
+    }
+
+    /// Doubles a point.
+    pub fn ec_double<C: WeierstrassCurve>(
+        &mut self,
+        a: WeierstrassPointVariable<C>,
+    ) -> WeierstrassPointVariable<C> {
+        let x_sq = self.nonnative_mul(a.x, a.x);
+        let numerator = if C::A.is_zero() {
+            self.nonnative_sum(&[x_sq, x_sq, x_sq])
+        } else {
+            let coeff_a = self.constant::<NonNativeFieldVariable<C::BaseField>>(C::A);
+            self.nonnative_sum(&[x_sq, x_sq, x_sq, coeff_a])
+        };
+        let two_y = self.nonnative_add(a.y, a.y);
+        let lambda = self.nonnative_div(numerator, two_y);
+
+        let lambda_sq = self.nonnative_mul(lambda, lambda);
+        let two_x = self.nonnative_add(a.x, a.x);
+        let x = self.nonnative_sub(lambda_sq, two_x);
+
+        let x_diff = self.nonnative_sub(a.x, x);
+        let y = self.nonnative_mul(lambda, x_diff);
+        let y = self.nonnative_sub(y, a.y);
+
+        WeierstrassPointVariable { x, y }
+    }
+
+    /// Returns whether `point` satisfies `y^2 = x^3 + a * x + b`.
+    pub fn ec_is_on_curve<C: WeierstrassCurve>(
+        &mut self,
+        point: WeierstrassPointVariable<C>,
+    ) -> BoolVariable {
+        let coeff_b = self.constant::<NonNativeFieldVariable<C::BaseField>>(C::B);
+        let x_sq = self.nonnative_mul(point.x, point.x);
+        let x_sq_plus_a = if C::A.is_zero() {
+            x_sq
+        } else {
+            let coeff_a = self.constant::<NonNativeFieldVariable<C::BaseField>>(C::A);
+            self.nonnative_add(x_sq, coeff_a)
+        };
+        let rhs = self.nonnative_mul_add(x_sq_plus_a, point.x, coeff_b);
+        let lhs = self.nonnative_mul(point.y, point.y);
+        self.is_equal(lhs, rhs)
+    }
+
+    /// Returns the secp256k1 point with x coordinate `x` and an even y coordinate, along with
+    /// whether such a point exists. If it does not, the returned point is not on the curve and
+    /// must not be used.
+    pub fn secp256k1_lift_x(
+        &mut self,
+        x: Secp256k1BaseVariable,
+    ) -> (BoolVariable, Secp256k1PointVariable) {
+        let seven = self.constant::<Secp256k1BaseVariable>(U256::from(7));
+        let x_sq = self.nonnative_mul(x, x);
+        let c = self.nonnative_mul_add(x_sq, x, seven);
+        let neg_c = self.nonnative_neg(c);
+
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&x);
+        let output_stream = self.hint(input_stream, Secp256k1LiftXHint);
+        let is_on_curve = output_stream.read::<BoolVariable>(self);
+        let y = output_stream.read::<Secp256k1BaseVariable>(self);
+
+        // Exactly one of `c` and `-c` is a square, so the prover cannot lie about `is_on_curve`.
+        let y_sq = self.nonnative_mul(y, y);
+        let expected = self.select(is_on_curve, c, neg_c);
+        self.assert_is_equal(y_sq, expected);
+
+        let y_bits = self.api.split_le(y.value.limbs[0].variable.0, 32);
+        self.api.assert_zero(y_bits[0].target);
+
+        (is_on_curve, Secp256k1PointVariable { x, y })
+    }
+
+    /// Computes `2^256 * OFFSET + k1 * p1 + k2 * p2` with a joint double-and-add over the bits of
+    /// both scalars. The offset keeps the accumulator away from the point at infinity; callers
+    /// compare against a point shifted by `ec_shifted_offset` instead of removing it.
+    ///
+    /// The sum `p1 + p2` is computed with a doubling when the points are equal, and is skipped when
+    /// they are opposite, so any pair of points is supported. The other additions are incomplete,
+    /// so the circuit is unsatisfiable in the (negligibly likely, as the discrete logarithm of the
+    /// offset is unknown) event of a collision with the accumulator.
+    pub(crate) fn ec_mul_add_with_offset<C: WeierstrassCurve>(
+        &mut self,
+        k1: U256Variable,
+        p1: WeierstrassPointVariable<C>,
+        k2: U256Variable,
+        p2: WeierstrassPointVariable<C>,
+    ) -> WeierstrassPointVariable<C> {
+        let k1_bits = self.to_be_bits(k1);
+        let k2_bits = self.to_be_bits(k2);
+
+        // When the points share an x coordinate, `p1` is added to its double instead of `p2`, which
+        // keeps the addition satisfiable, and the sum is replaced by the double.
+        let same_x = self.is_equal(p1.x, p2.x);
+        let same_y = self.is_equal(p1.y, p2.y);
+        let not_same_y = self.not(same_y);
+        let is_opposite = self.and(same_x, not_same_y);
+        let p1_doubled = self.ec_double(p1);
+        let addend = self.select(same_x, p1_doubled, p2);
+        let p1_plus_addend = self.ec_add(p1, addend);
+        let p1_plus_p2 = self.select(same_x, p1_doubled, p1_plus_addend);
+
+        let mut acc = self.ec_constant_point::<C>(C::OFFSET);
+        for i in 0..256 {
+            acc = self.ec_double(acc);
+            let addend = self.select(k1_bits[i], p1_plus_p2, p2);
+            let addend = self.select(k2_bits[i], addend, p1);
+            let sum = self.ec_add(acc, addend);
+            // The sum of opposite points is the point at infinity, which adds nothing.
+            let should_add = self.or(k1_bits[i], k2_bits[i]);
+            let both_bits = self.and(k1_bits[i], k2_bits[i]);
+            let adds_infinity = self.and(both_bits, is_opposite);
+            let adds_point = self.not(adds_infinity);
+            let should_add = self.and(should_add, adds_point);
+            acc = self.select(should_add, sum, acc);
+        }
+        acc
+    }
+
+    /// The offset that `ec_mul_add_with_offset` adds to its result.
+    pub(crate) fn ec_shifted_offset<C: WeierstrassCurve>(&mut self) -> WeierstrassPointVariable<C> {
+        self.ec_constant_point(C::SHIFTED_OFFSET)
+    }
+
+    /// Computes the window table of `p`. The circuit is unsatisfiable if `p` has a small order,
+    /// which no point on a curve of prime order has.
+    pub fn ec_window_table<C: WeierstrassCurve>(
+        &mut self,
+        p: WeierstrassPointVariable<C>,
+    ) -> WindowTable<C> {
+        let mut multiples = vec![p, self.ec_double(p)];
+        for i in 2..WINDOW_TABLE_LEN {
+            let multiple = self.ec_add(multiples[i - 1], p);
+            multiples.push(multiple);
+        }
+        WindowTable { multiples }
+    }
+
+    /// Returns the window table of the generator, whose points are constants.
+    pub fn ec_generator_window_table<C: WeierstrassCurve>(&mut self) -> WindowTable<C> {
+        let mut multiple = C::GENERATOR;
+        let mut multiples = vec![self.ec_constant_point(multiple)];
+        for _ in 1..WINDOW_TABLE_LEN {
+            multiple = ec_native_add::<C>(multiple, C::GENERATOR);
+            multiples.push(self.ec_constant_point(multiple));
+        }
+        WindowTable { multiples }
+    }
+
+    /// Computes `2^256 * OFFSET + k1 * p1 + k2 * p2`, like `ec_mul_add_with_offset`, over windows
+    /// of `WINDOW_BITS` bits of the scalars. Each window takes `WINDOW_BITS` doublings and one
+    /// addition per nonzero digit, with the multiples of the points read from their tables.
+    pub(crate) fn ec_mul_add_windowed_with_offset<C: WeierstrassCurve>(
+        &mut self,
+        k1: U256Variable,
+        t1: &WindowTable<C>,
+        k2: U256Variable,
+        t2: &WindowTable<C>,
+    ) -> WeierstrassPointVariable<C> {
+        let k1_bits = self.to_be_bits(k1);
+        let k2_bits = self.to_be_bits(k2);
+
+        let mut acc = self.ec_constant_point::<C>(C::OFFSET);
+        for window in 0..256 / WINDOW_BITS {
+            for _ in 0..WINDOW_BITS {
+                acc = self.ec_double(acc);
+            }
+            let range = window * WINDOW_BITS..(window + 1) * WINDOW_BITS;
+            for (bits, table) in [(&k1_bits[range.clone()], t1), (&k2_bits[range], t2)] {
+                acc = self.ec_add_window(acc, bits, table);
+            }
+        }
+        acc
+    }
+
+    /// Adds `digit * P` to `acc`, where `digit` has the big-endian bits `bits` and the multiples
+    /// of `P` are in `table`.
+    fn ec_add_window<C: WeierstrassCurve>(
+        &mut self,
+        acc: WeierstrassPointVariable<C>,
+        bits: &[BoolVariable],
+        table: &WindowTable<C>,
+    ) -> WeierstrassPointVariable<C> {
+        let digit = self.api.le_sum(
+            bits.iter()
+                .rev()
+                .map(|bit| BoolTarget::new_unsafe(bit.variable.0)),
+        );
+        let digit = Variable(digit);
+        let zero = self.zero::<Variable>();
+        let is_zero = self.is_equal(digit, zero);
+
+        // A zero digit adds nothing, so it selects any point and the sum is discarded.
+        let mut points = vec![table.multiples[0]];
+        points.extend_from_slice(&table.multiples);
+        let addend = self.select_array(&points, digit);
+        let sum = self.ec_add(acc, addend);
+        self.select(is_zero, acc, sum)
+    }
+}
+
+/// The number of bits of the scalars handled by each window of the scalar multiplication.
+pub const WINDOW_BITS: usize = 4;
+
+const WINDOW_TABLE_LEN: usize = (1 << WINDOW_BITS) - 1;
+
+/// The multiples `1 * P` to `15 * P` of a point `P`, for the windowed scalar multiplication. The
+/// table of a point can be computed once and shared by all the multiplications of that point.
+#[derive(Debug, Clone)]
+pub struct WindowTable<C: WeierstrassCurve> {
+    multiples: Vec<WeierstrassPointVariable<C>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::DefaultBuilder;
+
+    #[test]
+    fn test_secp256k1_lift_x() {
+        let mut builder = DefaultBuilder::new();
+
+        let x = builder.read::<Secp256k1BaseVariable>();
+        let (is_on_curve, point) = builder.secp256k1_lift_x(x);
+        builder.write(is_on_curve);
+        builder.write(point.y);
+
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Secp256k1BaseVariable>(GENERATOR.0);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert!(output.read::<BoolVariable>());
+        // The generator has an even y coordinate.
+        assert_eq!(output.read::<Secp256k1BaseVariable>(), GENERATOR.1);
+
+        // 5^3 + 7 = 132 is not a square modulo p.
+        let mut input = circuit.input();
+        input.write::<Secp256k1BaseVariable>(U256::from(5));
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert!(!output.read::<BoolVariable>());
+    }
+}
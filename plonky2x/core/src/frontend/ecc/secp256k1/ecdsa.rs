@@ -0,0 +1,465 @@
+use ethers::core::k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use ethers::core::k256::elliptic_curve::sec1::ToEncodedPoint;
+use ethers::types::U256;
+use plonky2::hash::hash_types::RichField;
+use serde::{Deserialize, Serialize};
+
+use super::curve::{
+    ec_native_add, Secp256k1, Secp256k1PointVariable, Secp256k1PointVariableValue,
+    WeierstrassCurve, WeierstrassPointVariable, WindowTable, GENERATOR,
+};
+use super::field::{NonNativeFieldParameters, Secp256k1Base, Secp256k1Scalar};
+use crate::frontend::eth::vars::AddressVariable;
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::uint::uint256::u256_to_be_bytes;
+use crate::frontend::vars::{EvmVariable, ValueStream, VariableStream};
+use crate::prelude::{
+    BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitBuilder, CircuitVariable,
+    PlonkParameters, U256Variable,
+};
+
+/// An ECDSA signature, with `r` and `s` as big-endian integers.
+#[derive(Debug, Clone, Copy, CircuitVariable)]
+#[value_name(ECDSASignature)]
+pub struct ECDSASignatureVariable {
+    pub r: U256Variable,
+    pub s: U256Variable,
+}
+
+/// The checks of `ecdsa_verify_with_config` that are not part of the ECDSA equation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ECDSAConfig {
+    /// Whether signatures with `s > n / 2` are valid. Each signature has such a twin, so they are
+    /// rejected by default to make signatures non-malleable, as Ethereum transactions do.
+    pub allow_high_s: bool,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Verifies an ECDSA signature of `message_hash` under `public_key`, returning whether it is
+    /// valid. Signatures with `r` or `s` out of `[1, n)` or with a high `s`, and public keys that
+    /// are not on the curve, yield `false` rather than an unsatisfiable circuit.
+    pub fn ecdsa_verify(
+        &mut self,
+        message_hash: Bytes32Variable,
+        signature: ECDSASignatureVariable,
+        public_key: Secp256k1PointVariable,
+    ) -> BoolVariable {
+        self.ecdsa_verify_with_config(message_hash, signature, public_key, ECDSAConfig::default())
+    }
+
+    /// Verifies an ECDSA signature like `ecdsa_verify`, with the checks of `config`.
+    pub fn ecdsa_verify_with_config(
+        &mut self,
+        message_hash: Bytes32Variable,
+        signature: ECDSASignatureVariable,
+        public_key: Secp256k1PointVariable,
+        config: ECDSAConfig,
+    ) -> BoolVariable {
+        let generator_table = self.ec_generator_window_table::<Secp256k1>();
+        self.ecdsa_verify_with_table(
+            &generator_table,
+            message_hash,
+            signature,
+            public_key,
+            config,
+        )
+    }
+
+    /// Verifies several ECDSA signatures, sharing the window table of the generator between them.
+    /// Each item is a message hash, a signature and a public key as in `ecdsa_verify`.
+    pub fn ecdsa_verify_batch(
+        &mut self,
+        items: &[(
+            Bytes32Variable,
+            ECDSASignatureVariable,
+            Secp256k1PointVariable,
+        )],
+        config: ECDSAConfig,
+    ) -> Vec<BoolVariable> {
+        let generator_table = self.ec_generator_window_table::<Secp256k1>();
+        items
+            .iter()
+            .map(|(message_hash, signature, public_key)| {
+                self.ecdsa_verify_with_table(
+                    &generator_table,
+                    *message_hash,
+                    *signature,
+                    *public_key,
+                    config,
+                )
+            })
+            .collect()
+    }
+
+    /// Verifies an ECDSA signature over any curve, given the window table of its generator.
+    pub(crate) fn ecdsa_verify_with_table<C: WeierstrassCurve>(
+        &mut self,
+        generator_table: &WindowTable<C>,
+        message_hash: Bytes32Variable,
+        signature: ECDSASignatureVariable,
+        public_key: WeierstrassPointVariable<C>,
+        config: ECDSAConfig,
+    ) -> BoolVariable {
+        let zero = self.zero::<U256Variable>();
+        let one = self.one::<U256Variable>();
+        let group_order = C::ScalarField::MODULUS;
+
+        let r_is_zero = self.is_equal(signature.r, zero);
+        let r_in_range = self.lt(signature.r, group_order);
+        let r_is_valid = self.not(r_is_zero);
+        let r_is_valid = self.and(r_is_valid, r_in_range);
+        let s_is_zero = self.is_equal(signature.s, zero);
+        let s_in_range = if config.allow_high_s {
+            self.lt(signature.s, group_order)
+        } else {
+            self.lte(signature.s, group_order >> 1)
+        };
+        let s_is_valid = self.not(s_is_zero);
+        let s_is_valid = self.and(s_is_valid, s_in_range);
+
+        let key_on_curve = self.ec_is_on_curve(public_key);
+
+        // Invalid values are replaced so that the rest of the circuit stays satisfiable, and the
+        // result is false anyway.
+        let r = self.select(r_is_valid, signature.r, one);
+        let s = self.select(s_is_valid, signature.s, one);
+        // 2 * G stands in for invalid public keys and for the point at infinity.
+        let dummy = self.ec_constant_point(ec_native_add::<C>(C::GENERATOR, C::GENERATOR));
+        let public_key = self.select(key_on_curve, public_key, dummy);
+
+        // R = u1 * G + u2 * Q, computed with an offset that is then removed. R is the point at
+        // infinity iff the sum is the offset itself, in which case the signature is invalid.
+        let shifted_point =
+            self.ecdsa_shifted_point(generator_table, message_hash, r, s, public_key);
+        let shifted_offset = self.ec_shifted_offset::<C>();
+        let is_infinity = self.is_equal(shifted_point, shifted_offset);
+        let shifted_point = self.select(is_infinity, dummy, shifted_point);
+        let neg_shifted_offset = WeierstrassPointVariable {
+            x: shifted_offset.x,
+            y: self.nonnative_neg(shifted_offset.y),
+        };
+        let point = self.ec_add(shifted_point, neg_shifted_offset);
+
+        // The signature is valid iff x(R) = r mod n.
+        let x = self.nonnative_from_u256::<C::ScalarField>(point.x.value);
+        let r = self.nonnative_from_u256::<C::ScalarField>(r);
+        let x_matches = self.is_equal(x, r);
+
+        let mut is_valid = self.and(r_is_valid, s_is_valid);
+        is_valid = self.and(is_valid, key_on_curve);
+        let is_finite = self.not(is_infinity);
+        is_valid = self.and(is_valid, is_finite);
+        self.and(is_valid, x_matches)
+    }
+
+    /// Returns `u1 * G + u2 * Q` shifted by `ec_shifted_offset`, where `u1 = z / s` and
+    /// `u2 = r / s` with `z` the message hash reduced modulo n. `s` must be nonzero modulo n.
+    fn ecdsa_shifted_point<C: WeierstrassCurve>(
+        &mut self,
+        generator_table: &WindowTable<C>,
+        message_hash: Bytes32Variable,
+        r: U256Variable,
+        s: U256Variable,
+        public_key: WeierstrassPointVariable<C>,
+    ) -> WeierstrassPointVariable<C> {
+        let z = message_hash.as_u256(self);
+        let z = self.nonnative_from_u256::<C::ScalarField>(z);
+        let r = self.nonnative_from_u256::<C::ScalarField>(r);
+        let s = self.nonnative_from_u256::<C::ScalarField>(s);
+        let s_inv = self.nonnative_inv(s);
+        let u1 = self.nonnative_mul(z, s_inv);
+        let u2 = self.nonnative_mul(r, s_inv);
+
+        let public_key_table = self.ec_window_table(public_key);
+        self.ec_mul_add_windowed_with_offset(u1.value, generator_table, u2.value, &public_key_table)
+    }
+
+    /// Recovers the address of the signer of `message_hash`, like the `ecrecover` precompile.
+    ///
+    /// `v` is either 27 or 28 as in legacy signatures, or the y parity 0 or 1 of typed and
+    /// EIP-155 transactions (i.e. `v - 35 - 2 * chain_id`). The public key is hinted, and
+    /// constrained to be on the curve and to satisfy `u1 * G + u2 * Q = R`, where `R` is the point
+    /// with x coordinate `r` and the parity of `v`.
+    ///
+    /// The circuit is unsatisfiable for invalid signatures, which includes `r` or `s` out of
+    /// `[1, n)`, a high `s`, an invalid `v`, and an `r` that is not an x coordinate on the curve.
+    pub fn ecrecover(
+        &mut self,
+        message_hash: Bytes32Variable,
+        v: ByteVariable,
+        r: U256Variable,
+        s: U256Variable,
+    ) -> AddressVariable {
+        let _true = self._true();
+        let _false = self._false();
+        let zero = self.zero::<U256Variable>();
+        let r_is_zero = self.is_equal(r, zero);
+        self.assert_is_equal(r_is_zero, _false);
+        let r_in_range = self.lt(r, Secp256k1Scalar::MODULUS);
+        self.assert_is_equal(r_in_range, _true);
+        let s_is_zero = self.is_equal(s, zero);
+        self.assert_is_equal(s_is_zero, _false);
+        let s_is_low = self.lte(s, Secp256k1Scalar::MODULUS >> 1);
+        self.assert_is_equal(s_is_low, _true);
+
+        // The parity is the least significant bit of v, flipped for 27 and 28.
+        let is_legacy = self.is_one_of(v, &[27, 28]);
+        let is_parity = self.is_one_of(v, &[0, 1]);
+        let is_valid_v = self.or(is_legacy, is_parity);
+        self.assert_is_equal(is_valid_v, _true);
+        let lsb = v.as_le_bits()[0];
+        let flipped = self.not(lsb);
+        let is_odd = self.select(is_legacy, flipped, lsb);
+
+        // R has x coordinate r, as r < n < p, and the parity of v.
+        let rx = self.nonnative_from_u256::<Secp256k1Base>(r);
+        let (r_on_curve, r_point) = self.secp256k1_lift_x(rx);
+        self.assert_is_equal(r_on_curve, _true);
+        let neg_y = self.nonnative_neg(r_point.y);
+        let r_point = Secp256k1PointVariable {
+            x: r_point.x,
+            y: self.select(is_odd, neg_y, r_point.y),
+        };
+
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&message_hash);
+        input_stream.write(&is_odd);
+        input_stream.write(&r);
+        input_stream.write(&s);
+        let output_stream = self.hint(input_stream, EcrecoverHint);
+        let public_key = output_stream.read::<Secp256k1PointVariable>(self);
+
+        let key_on_curve = self.ec_is_on_curve(public_key);
+        self.assert_is_equal(key_on_curve, _true);
+        let generator_table = self.ec_generator_window_table::<Secp256k1>();
+        let shifted_point =
+            self.ecdsa_shifted_point(&generator_table, message_hash, r, s, public_key);
+        let shifted_offset = self.ec_shifted_offset();
+        let expected = self.ec_add(r_point, shifted_offset);
+        self.assert_is_equal(shifted_point, expected);
+
+        // The address is the last 20 bytes of the hash of the uncompressed public key.
+        let mut encoded = public_key.x.value.encode(self);
+        encoded.extend(public_key.y.value.encode(self));
+        let hash = self.keccak256(&encoded);
+        AddressVariable(BytesVariable(hash.as_bytes()[12..].try_into().unwrap()))
+    }
+
+    fn is_one_of(&mut self, byte: ByteVariable, values: &[u8]) -> BoolVariable {
+        let mut result = self._false();
+        for value in values {
+            let value = self.constant::<ByteVariable>(*value);
+            let is_equal = self.is_equal(byte, value);
+            result = self.or(result, is_equal);
+        }
+        result
+    }
+}
+
+/// Recovers the public key of an ECDSA signature, given the parity of the y coordinate of `R`. An
+/// unrecoverable signature yields the generator, which fails the constraints of `ecrecover`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EcrecoverHint;
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for EcrecoverHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let message_hash = input_stream.read_value::<Bytes32Variable>();
+        let is_odd = input_stream.read_value::<BoolVariable>();
+        let r = input_stream.read_value::<U256Variable>();
+        let s = input_stream.read_value::<U256Variable>();
+
+        let recover = || {
+            let signature = Signature::from_scalars(u256_to_be_bytes(r), u256_to_be_bytes(s))?;
+            let recovery_id = RecoveryId::new(is_odd, false);
+            VerifyingKey::recover_from_prehash(message_hash.as_bytes(), &signature, recovery_id)
+        };
+        let (x, y) = match recover() {
+            Ok(key) => {
+                let point = key.to_encoded_point(false);
+                (
+                    U256::from_big_endian(point.x().unwrap()),
+                    U256::from_big_endian(point.y().unwrap()),
+                )
+            }
+            Err(_) => GENERATOR,
+        };
+        output_stream.write_value::<Secp256k1PointVariable>(Secp256k1PointVariableValue { x, y });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::core::k256::ecdsa::SigningKey;
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::H256;
+    use ethers::utils::keccak256;
+
+    use super::*;
+    use crate::backend::circuit::MockCircuitBuild;
+    use crate::prelude::{DefaultBuilder, DefaultParameters, GoldilocksField};
+
+    /// Signs `message` with a fixed key, returning its hash, the signature and the public key.
+    fn sign(message: &[u8]) -> ([u8; 32], ECDSASignature<GoldilocksField>, (U256, U256)) {
+        let key = SigningKey::from_bytes(&[0x42; 32].into()).unwrap();
+        let hash = keccak256(message);
+        let (signature, _) = key.sign_prehash_recoverable(&hash).unwrap();
+        let public_key = key.verifying_key().to_encoded_point(false);
+        (
+            hash,
+            ECDSASignature {
+                r: U256::from_big_endian(&signature.r().to_bytes()),
+                s: U256::from_big_endian(&signature.s().to_bytes()),
+            },
+            (
+                U256::from_big_endian(public_key.x().unwrap()),
+                U256::from_big_endian(public_key.y().unwrap()),
+            ),
+        )
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ecdsa_verify() {
+        let mut builder = DefaultBuilder::new();
+        let message_hash = builder.read::<Bytes32Variable>();
+        let signature = builder.read::<ECDSASignatureVariable>();
+        let public_key = builder.read::<Secp256k1PointVariable>();
+        let is_valid = builder.ecdsa_verify(message_hash, signature, public_key);
+        let config = ECDSAConfig { allow_high_s: true };
+        let is_valid_high_s =
+            builder.ecdsa_verify_with_config(message_hash, signature, public_key, config);
+        builder.write(is_valid);
+        builder.write(is_valid_high_s);
+        let circuit = builder.mock_build();
+
+        let (hash, signature, public_key) = sign(b"ETH/USD 1850.42");
+        let high_s = ECDSASignature {
+            r: signature.r,
+            s: Secp256k1Scalar::MODULUS - signature.s,
+        };
+        let corrupted = ECDSASignature {
+            r: signature.r,
+            s: signature.s ^ U256::one(),
+        };
+        let zero_r = ECDSASignature {
+            r: U256::zero(),
+            s: signature.s,
+        };
+        let zero_s = ECDSASignature {
+            r: signature.r,
+            s: U256::zero(),
+        };
+        let (other_hash, _, _) = sign(b"ETH/USD 1850.43");
+        let off_curve_key = (public_key.0, public_key.1 + 1);
+
+        // (hash, signature, public key, valid by default, valid with high s).
+        let cases = [
+            (hash, signature.clone(), public_key, true, true),
+            (hash, high_s, public_key, false, true),
+            (hash, corrupted, public_key, false, false),
+            (hash, zero_r, public_key, false, false),
+            (hash, zero_s, public_key, false, false),
+            (other_hash, signature.clone(), public_key, false, false),
+            (hash, signature.clone(), off_curve_key, false, false),
+        ];
+        for (hash, signature, public_key, expected, expected_high_s) in cases {
+            let mut input = circuit.input();
+            input.write::<Bytes32Variable>(hash.into());
+            input.write::<ECDSASignatureVariable>(signature);
+            input.write::<Secp256k1PointVariable>(Secp256k1PointVariableValue {
+                x: public_key.0,
+                y: public_key.1,
+            });
+            let mut output = circuit.mock_prove(&input).output;
+            assert_eq!(output.read::<BoolVariable>(), expected);
+            assert_eq!(output.read::<BoolVariable>(), expected_high_s);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ecdsa_verify_batch() {
+        let mut builder = DefaultBuilder::new();
+        let items = (0..2)
+            .map(|_| {
+                (
+                    builder.read::<Bytes32Variable>(),
+                    builder.read::<ECDSASignatureVariable>(),
+                    builder.read::<Secp256k1PointVariable>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let results = builder.ecdsa_verify_batch(&items, ECDSAConfig::default());
+        for result in results {
+            builder.write(result);
+        }
+        let circuit = builder.mock_build();
+
+        let (hash, signature, public_key) = sign(b"BTC/USD 29000.00");
+        let (other_hash, _, _) = sign(b"BTC/USD 29000.01");
+        let mut input = circuit.input();
+        for hash in [hash, other_hash] {
+            input.write::<Bytes32Variable>(hash.into());
+            input.write::<ECDSASignatureVariable>(signature.clone());
+            input.write::<Secp256k1PointVariable>(Secp256k1PointVariableValue {
+                x: public_key.0,
+                y: public_key.1,
+            });
+        }
+        let mut output = circuit.mock_prove(&input).output;
+        assert!(output.read::<BoolVariable>());
+        assert!(!output.read::<BoolVariable>());
+    }
+
+    fn ecrecover_circuit() -> MockCircuitBuild<DefaultParameters, 2> {
+        let mut builder = DefaultBuilder::new();
+        let message_hash = builder.read::<Bytes32Variable>();
+        let v = builder.read::<ByteVariable>();
+        let r = builder.read::<U256Variable>();
+        let s = builder.read::<U256Variable>();
+        let address = builder.ecrecover(message_hash, v, r, s);
+        builder.write(address);
+        builder.mock_build()
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ecrecover() {
+        let circuit = ecrecover_circuit();
+        for i in 0..2u8 {
+            let wallet = LocalWallet::from_bytes(&[0x11 + i; 32]).unwrap();
+            let hash = H256(keccak256([i; 7]));
+            let signature = wallet.sign_hash(hash).unwrap();
+            assert!(signature.v == 27 || signature.v == 28);
+
+            // Both the legacy v and the y parity recover the signer.
+            for v in [signature.v as u8, signature.v as u8 - 27] {
+                let mut input = circuit.input();
+                input.write::<Bytes32Variable>(hash);
+                input.write::<ByteVariable>(v);
+                input.write::<U256Variable>(signature.r);
+                input.write::<U256Variable>(signature.s);
+                let mut output = circuit.mock_prove(&input).output;
+                assert_eq!(output.read::<AddressVariable>(), wallet.address());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ecrecover_high_s() {
+        let circuit = ecrecover_circuit();
+        let wallet = LocalWallet::from_bytes(&[0x11; 32]).unwrap();
+        let hash = H256(keccak256(b"malleable"));
+        let signature = wallet.sign_hash(hash).unwrap();
+
+        // The twin signature (r, n - s) with the other parity is valid in plain ECDSA.
+        let mut input = circuit.input();
+        input.write::<Bytes32Variable>(hash);
+        input.write::<ByteVariable>(55 - signature.v as u8);
+        input.write::<U256Variable>(signature.r);
+        input.write::<U256Variable>(Secp256k1Scalar::MODULUS - signature.s);
+        circuit.mock_prove(&input);
+    }
+}
@@ -0,0 +1,464 @@
+use core::fmt::Debug;
+use core::marker::PhantomData;
+
+use array_macro::array;
+use ethers::types::U256;
+use num::BigUint;
+use plonky2::hash::hash_types::RichField;
+use serde::{Deserialize, Serialize};
+
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
+use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::U32Target;
+use crate::frontend::vars::{BigUintVariable, ValueStream, VariableStream};
+use crate::prelude::{CircuitBuilder, CircuitVariable, PlonkParameters, U256Variable, Variable};
+
+/// The parameters of a prime field with a 256-bit modulus that is emulated inside the circuit.
+pub trait NonNativeFieldParameters: Debug + Clone + Copy + Send + Sync + 'static {
+    /// The modulus of the field. It must occupy the full eight u32 limbs of a `U256`.
+    const MODULUS: U256;
+
+    fn modulus() -> BigUint {
+        u256_to_biguint(Self::MODULUS)
+    }
+}
+
+/// The base field of secp256k1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Secp256k1Base;
+
+impl NonNativeFieldParameters for Secp256k1Base {
+    const MODULUS: U256 = U256([
+        0xFFFFFFFEFFFFFC2F,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+    ]);
+}
+
+/// The scalar field of secp256k1, i.e. the order of its group of points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Secp256k1Scalar;
+
+impl NonNativeFieldParameters for Secp256k1Scalar {
+    const MODULUS: U256 = U256([
+        0xBFD25E8CD0364141,
+        0xBAAEDCE6AF48A03B,
+        0xFFFFFFFFFFFFFFFE,
+        0xFFFFFFFFFFFFFFFF,
+    ]);
+}
+
+pub(crate) fn u256_to_biguint(value: U256) -> BigUint {
+    let mut bytes = [0u8; 32];
+    value.to_little_endian(&mut bytes);
+    BigUint::from_bytes_le(&bytes)
+}
+
+pub(crate) fn biguint_to_u256(value: &BigUint) -> U256 {
+    U256::from_little_endian(&value.to_bytes_le())
+}
+
+/// An element of a non-native field. The value is always kept reduced modulo the field's modulus,
+/// so two elements are equal if and only if their limbs are equal.
+#[derive(Debug, Clone, Copy)]
+pub struct NonNativeFieldVariable<P: NonNativeFieldParameters> {
+    pub value: U256Variable,
+    _marker: PhantomData<P>,
+}
+
+impl<P: NonNativeFieldParameters> NonNativeFieldVariable<P> {
+    pub(crate) fn from_u256_unsafe(value: U256Variable) -> Self {
+        Self {
+            value,
+            _marker: PhantomData,
+        }
+    }
+
+    fn from_biguint_target_unsafe(value: BigUintTarget) -> Self {
+        assert_eq!(value.num_limbs(), 8);
+        Self::from_u256_unsafe(U256Variable {
+            limbs: array![i => value.limbs[i].into(); 8],
+        })
+    }
+
+    fn to_biguint_target(self) -> BigUintTarget {
+        BigUintTarget {
+            limbs: self
+                .value
+                .limbs
+                .iter()
+                .map(|x| U32Target::from(*x))
+                .collect(),
+        }
+    }
+}
+
+impl<P: NonNativeFieldParameters> CircuitVariable for NonNativeFieldVariable<P> {
+    type ValueType<F: RichField> = U256;
+
+    fn init_unsafe<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        Self::from_u256_unsafe(U256Variable::init_unsafe(builder))
+    }
+
+    fn variables(&self) -> Vec<Variable> {
+        self.value.variables()
+    }
+
+    fn from_variables_unsafe(variables: &[Variable]) -> Self {
+        Self::from_u256_unsafe(U256Variable::from_variables_unsafe(variables))
+    }
+
+    fn assert_is_valid<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        self.value.assert_is_valid(builder);
+        let modulus = builder.constant::<U256Variable>(P::MODULUS);
+        let is_reduced = builder.lt(self.value, modulus);
+        let _true = builder._true();
+        builder.assert_is_equal(is_reduced, _true);
+    }
+
+    fn nb_elements() -> usize {
+        U256Variable::nb_elements()
+    }
+
+    fn elements<F: RichField>(value: Self::ValueType<F>) -> Vec<F> {
+        U256Variable::elements::<F>(value)
+    }
+
+    fn from_elements<F: RichField>(elements: &[F]) -> Self::ValueType<F> {
+        U256Variable::from_elements::<F>(elements)
+    }
+}
+
+/// Computes `numerator / denominator` in the field outside of the circuit. A zero denominator
+/// yields zero, which `nonnative_div` then rejects.
+///
+/// The inverse is computed with `BigUint::modpow`, whose running time depends on the operands, so
+/// the hint must not be used to divide private values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct NonNativeDivHint<P: NonNativeFieldParameters> {
+    _marker: PhantomData<P>,
+}
+
+impl<L: PlonkParameters<D>, const D: usize, P: NonNativeFieldParameters> Hint<L, D>
+    for NonNativeDivHint<P>
+{
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let numerator = u256_to_biguint(input_stream.read_value::<NonNativeFieldVariable<P>>());
+        let denominator = u256_to_biguint(input_stream.read_value::<NonNativeFieldVariable<P>>());
+
+        let modulus = P::modulus();
+        let inverse = denominator.modpow(&(&modulus - 2u32), &modulus);
+        let quotient = numerator * inverse % &modulus;
+
+        output_stream.write_value::<NonNativeFieldVariable<P>>(biguint_to_u256(&quotient));
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Reduces a 256-bit integer modulo the modulus of a non-native field.
+    pub fn nonnative_from_u256<P: NonNativeFieldParameters>(
+        &mut self,
+        value: U256Variable,
+    ) -> NonNativeFieldVariable<P> {
+        let value = BigUintTarget {
+            limbs: value.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+        };
+        self.nonnative_reduce_target(&value)
+    }
+
+    /// Reduces an integer of at least eight limbs modulo the modulus of a non-native field, e.g.
+    /// the unreduced result of a sum of products.
+    pub fn nonnative_reduce<P: NonNativeFieldParameters, const LIMBS: usize>(
+        &mut self,
+        value: BigUintVariable<LIMBS>,
+    ) -> NonNativeFieldVariable<P> {
+        assert!(LIMBS >= 8, "the value must have at least eight limbs");
+        self.nonnative_reduce_target(&value.to_biguint_target())
+    }
+
+    /// Reduces `value` with a single hinted division. The arithmetic below only reduces once per
+    /// operation, and leaves the intermediate sums and products unreduced.
+    fn nonnative_reduce_target<P: NonNativeFieldParameters>(
+        &mut self,
+        value: &BigUintTarget,
+    ) -> NonNativeFieldVariable<P> {
+        let modulus = self.api.constant_biguint(&P::modulus());
+        // The modulus is at least 2^224, so the quotient always fits in the
+        // `value.num_limbs() - 7` limbs allocated by the unsafe variant.
+        let (_, rem) = self.api.div_rem_biguint_unsafe(value, &modulus);
+        NonNativeFieldVariable::from_biguint_target_unsafe(rem)
+    }
+
+    /// Computes `a + b` in a non-native field.
+    pub fn nonnative_add<P: NonNativeFieldParameters>(
+        &mut self,
+        a: NonNativeFieldVariable<P>,
+        b: NonNativeFieldVariable<P>,
+    ) -> NonNativeFieldVariable<P> {
+        let sum = self
+            .api
+            .add_biguint(&a.to_biguint_target(), &b.to_biguint_target());
+        self.nonnative_reduce_target(&sum)
+    }
+
+    /// Computes `-a` in a non-native field.
+    pub fn nonnative_neg<P: NonNativeFieldParameters>(
+        &mut self,
+        a: NonNativeFieldVariable<P>,
+    ) -> NonNativeFieldVariable<P> {
+        let modulus = self.api.constant_biguint(&P::modulus());
+        // `a` is reduced, so `modulus - a` cannot underflow.
+        let diff = self.api.sub_biguint(&modulus, &a.to_biguint_target());
+        self.nonnative_reduce_target(&diff)
+    }
+
+    /// Computes `a - b` in a non-native field.
+    pub fn nonnative_sub<P: NonNativeFieldParameters>(
+        &mut self,
+        a: NonNativeFieldVariable<P>,
+        b: NonNativeFieldVariable<P>,
+    ) -> NonNativeFieldVariable<P> {
+        let modulus = self.api.constant_biguint(&P::modulus());
+        let neg_b = self.api.sub_biguint(&modulus, &b.to_biguint_target());
+        let diff = self.api.add_biguint(&a.to_biguint_target(), &neg_b);
+        self.nonnative_reduce_target(&diff)
+    }
+
+    /// Computes `a * b` in a non-native field.
+    pub fn nonnative_mul<P: NonNativeFieldParameters>(
+        &mut self,
+        a: NonNativeFieldVariable<P>,
+        b: NonNativeFieldVariable<P>,
+    ) -> NonNativeFieldVariable<P> {
+        let product = self
+            .api
+            .mul_biguint(&a.to_biguint_target(), &b.to_biguint_target());
+        self.nonnative_reduce_target(&product)
+    }
+
+    /// Computes `a * b + c` in a non-native field, with a single reduction.
+    pub fn nonnative_mul_add<P: NonNativeFieldParameters>(
+        &mut self,
+        a: NonNativeFieldVariable<P>,
+        b: NonNativeFieldVariable<P>,
+        c: NonNativeFieldVariable<P>,
+    ) -> NonNativeFieldVariable<P> {
+        let result = self.api.mul_add_biguint(
+            &a.to_biguint_target(),
+            &b.to_biguint_target(),
+            &c.to_biguint_target(),
+        );
+        self.nonnative_reduce_target(&result)
+    }
+
+    /// Computes the sum of `values` in a non-native field, with a single reduction.
+    pub fn nonnative_sum<P: NonNativeFieldParameters>(
+        &mut self,
+        values: &[NonNativeFieldVariable<P>],
+    ) -> NonNativeFieldVariable<P> {
+        assert!(!values.is_empty(), "the sum of no values");
+        // Each addition grows the sum by a limb, so the sum of any number of reduced values fits.
+        let mut sum = values[0].to_biguint_target();
+        for value in &values[1..] {
+            sum = self.api.add_biguint(&sum, &value.to_biguint_target());
+        }
+        self.nonnative_reduce_target(&sum)
+    }
+
+    /// Computes the inverse of `a` in a non-native field. The inverse is hinted and constrained by
+    /// `a * inv == 1`, so the circuit is unsatisfiable if `a` is zero.
+    pub fn nonnative_inv<P: NonNativeFieldParameters>(
+        &mut self,
+        a: NonNativeFieldVariable<P>,
+    ) -> NonNativeFieldVariable<P> {
+        let one = self.constant::<NonNativeFieldVariable<P>>(U256::one());
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&one);
+        input_stream.write(&a);
+        let output_stream = self.hint(
+            input_stream,
+            NonNativeDivHint::<P> {
+                _marker: PhantomData,
+            },
+        );
+        let inverse = output_stream.read::<NonNativeFieldVariable<P>>(self);
+
+        let product = self.nonnative_mul(a, inverse);
+        self.assert_is_equal(product, one);
+
+        inverse
+    }
+
+    /// Computes `a / b` in a non-native field. The circuit is unsatisfiable if `b` is zero.
+    pub fn nonnative_div<P: NonNativeFieldParameters>(
+        &mut self,
+        a: NonNativeFieldVariable<P>,
+        b: NonNativeFieldVariable<P>,
+    ) -> NonNativeFieldVariable<P> {
+        let zero = self.constant::<NonNativeFieldVariable<P>>(U256::zero());
+        let b_is_zero = self.is_equal(b, zero);
+        let _false = self._false();
+        self.assert_is_equal(b_is_zero, _false);
+
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&a);
+        input_stream.write(&b);
+        let output_stream = self.hint(
+            input_stream,
+            NonNativeDivHint::<P> {
+                _marker: PhantomData,
+            },
+        );
+        let quotient = output_stream.read::<NonNativeFieldVariable<P>>(self);
+
+        let product = self.nonnative_mul(quotient, b);
+        self.assert_is_equal(product, a);
+
+        quotient
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::RandBigInt;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::prelude::DefaultBuilder;
+
+    #[test]
+    fn test_nonnative_arithmetic() {
+        let mut builder = DefaultBuilder::new();
+
+        let a = builder.read::<NonNativeFieldVariable<Secp256k1Base>>();
+        let b = builder.read::<NonNativeFieldVariable<Secp256k1Base>>();
+        let sum = builder.nonnative_add(a, b);
+        let diff = builder.nonnative_sub(a, b);
+        let product = builder.nonnative_mul(a, b);
+        let quotient = builder.nonnative_div(a, b);
+        builder.write(sum);
+        builder.write(diff);
+        builder.write(product);
+        builder.write(quotient);
+
+        let circuit = builder.build();
+
+        let modulus = Secp256k1Base::modulus();
+        let a = &modulus - 5u32;
+        let b = BigUint::from(7u32);
+
+        let mut input = circuit.input();
+        input.write::<NonNativeFieldVariable<Secp256k1Base>>(biguint_to_u256(&a));
+        input.write::<NonNativeFieldVariable<Secp256k1Base>>(biguint_to_u256(&b));
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let inverse = b.modpow(&(&modulus - 2u32), &modulus);
+        assert_eq!(
+            output.read::<NonNativeFieldVariable<Secp256k1Base>>(),
+            U256::from(2)
+        );
+        assert_eq!(
+            output.read::<NonNativeFieldVariable<Secp256k1Base>>(),
+            biguint_to_u256(&(&modulus - 12u32))
+        );
+        assert_eq!(
+            output.read::<NonNativeFieldVariable<Secp256k1Base>>(),
+            biguint_to_u256(&(&a * &b % &modulus))
+        );
+        assert_eq!(
+            output.read::<NonNativeFieldVariable<Secp256k1Base>>(),
+            biguint_to_u256(&(&a * inverse % &modulus))
+        );
+    }
+
+    fn check_nonnative_ops<P: NonNativeFieldParameters>() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<NonNativeFieldVariable<P>>();
+        let b = builder.read::<NonNativeFieldVariable<P>>();
+        let c = builder.read::<NonNativeFieldVariable<P>>();
+        let wide = builder.read::<BigUintVariable<16>>();
+        let results = [
+            builder.nonnative_add(a, b),
+            builder.nonnative_sub(a, b),
+            builder.nonnative_mul(a, b),
+            builder.nonnative_inv(b),
+            builder.nonnative_mul_add(a, b, c),
+            builder.nonnative_sum(&[a, b, c]),
+            builder.nonnative_reduce(wide),
+        ];
+        for result in results {
+            builder.write(result);
+        }
+        let circuit = builder.mock_build();
+
+        let modulus = P::modulus();
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        let max = &modulus - 1u32;
+        let mut cases = vec![(max.clone(), max.clone(), max.clone())];
+        for _ in 0..4 {
+            cases.push((
+                rng.gen_biguint_below(&modulus),
+                rng.gen_biguint_below(&modulus),
+                rng.gen_biguint_below(&modulus),
+            ));
+        }
+        for (a, b, c) in cases {
+            let wide = rng.gen_biguint(512);
+            let mut input = circuit.input();
+            for value in [&a, &b, &c] {
+                input.write::<NonNativeFieldVariable<P>>(biguint_to_u256(value));
+            }
+            input.write::<BigUintVariable<16>>(wide.clone());
+            let mut output = circuit.mock_prove(&input).output;
+
+            let expected = [
+                (&a + &b) % &modulus,
+                (&a + &modulus - &b) % &modulus,
+                &a * &b % &modulus,
+                b.modpow(&(&modulus - 2u32), &modulus),
+                (&a * &b + &c) % &modulus,
+                (&a + &b + &c) % &modulus,
+                wide % &modulus,
+            ];
+            for expected in expected {
+                assert_eq!(
+                    output.read::<NonNativeFieldVariable<P>>(),
+                    biguint_to_u256(&expected)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_nonnative_ops_secp256k1_base() {
+        check_nonnative_ops::<Secp256k1Base>();
+    }
+
+    #[test]
+    fn test_nonnative_ops_secp256k1_scalar() {
+        check_nonnative_ops::<Secp256k1Scalar>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_nonnative_inv_zero() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<NonNativeFieldVariable<Secp256k1Base>>();
+        let inverse = builder.nonnative_inv(a);
+        builder.write(inverse);
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.write::<NonNativeFieldVariable<Secp256k1Base>>(U256::zero());
+        circuit.mock_prove(&input);
+    }
+}
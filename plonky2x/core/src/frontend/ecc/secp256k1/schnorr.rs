@@ -0,0 +1,246 @@
+use ethers::types::U256;
+use sha2::{Digest, Sha256};
+
+use super::curve::GENERATOR;
+use super::field::{NonNativeFieldParameters, Secp256k1Base, Secp256k1Scalar};
+use crate::frontend::vars::EvmVariable;
+use crate::prelude::{
+    BoolVariable, Bytes32Variable, BytesVariable, CircuitBuilder, PlonkParameters, U256Variable,
+};
+
+const CHALLENGE_TAG: &[u8] = b"BIP0340/challenge";
+
+// 2 * GENERATOR, substituted for points that fail to lift so that the scalar multiplication stays
+// satisfiable. It must differ from the generator, which is added to it.
+const DUMMY_POINT: (U256, U256) = (
+    U256([
+        0xABAC09B95C709EE5,
+        0x5C778E4B8CEF3CA7,
+        0x3045406E95C07CD8,
+        0xC6047F9441ED7D6D,
+    ]),
+    U256([
+        0x236431A950CFE52A,
+        0xF7F632653266D0E1,
+        0xA3C58419466CEAEE,
+        0x1AE168FEA63DC339,
+    ]),
+);
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Verifies a BIP-340 Schnorr signature `sig = r || s` of `msg` under the x-only public key
+    /// `pubkey_x`, returning whether it is valid. Malformed keys and signatures (coordinates that
+    /// are out of range or not on the curve) yield `false` rather than an unsatisfiable circuit.
+    pub fn schnorr_verify(
+        &mut self,
+        pubkey_x: Bytes32Variable,
+        msg: Bytes32Variable,
+        sig: BytesVariable<64>,
+    ) -> BoolVariable {
+        let field_modulus = self.constant::<U256Variable>(Secp256k1Base::MODULUS);
+        let group_order = self.constant::<U256Variable>(Secp256k1Scalar::MODULUS);
+
+        let px = pubkey_x.as_u256(self);
+        let r = U256Variable::decode(self, &sig.0[0..32]);
+        let s = U256Variable::decode(self, &sig.0[32..64]);
+
+        let px_in_range = self.lt(px, field_modulus);
+        let r_in_range = self.lt(r, field_modulus);
+        let s_in_range = self.lt(s, group_order);
+
+        // Both the public key and the nonce are lifted to the point with an even y coordinate.
+        let px = self.nonnative_from_u256::<Secp256k1Base>(px);
+        let (pubkey_on_curve, pubkey) = self.secp256k1_lift_x(px);
+        let rx = self.nonnative_from_u256::<Secp256k1Base>(r);
+        let (r_on_curve, r_point) = self.secp256k1_lift_x(rx);
+
+        let dummy = self.ec_constant_point(DUMMY_POINT);
+        let pubkey = self.select(pubkey_on_curve, pubkey, dummy);
+        let r_point = self.select(r_on_curve, r_point, dummy);
+
+        // e = int(tagged_hash("BIP0340/challenge", bytes(r) || bytes(P) || m)) mod n.
+        let tag_hash: [u8; 32] = Sha256::digest(CHALLENGE_TAG).into();
+        let tag_hash = self.constant::<BytesVariable<32>>(tag_hash);
+        let mut challenge_input = Vec::new();
+        challenge_input.extend(tag_hash.0);
+        challenge_input.extend(tag_hash.0);
+        challenge_input.extend(&sig.0[0..32]);
+        challenge_input.extend(pubkey_x.as_bytes());
+        challenge_input.extend(msg.as_bytes());
+        let e = self.curta_sha256(&challenge_input).as_u256(self);
+        let e = self.nonnative_from_u256::<Secp256k1Scalar>(e);
+        let neg_e = self.nonnative_neg(e);
+
+        // The signature is valid iff s * G - e * P = R. Both sides are shifted by the same offset
+        // so that the check never needs the point at infinity.
+        let generator = self.ec_constant_point(GENERATOR);
+        let lhs = self.ec_mul_add_with_offset(s, generator, neg_e.value, pubkey);
+        let shifted_offset = self.ec_shifted_offset();
+        // A nonce sharing its x coordinate with the shifted offset cannot be added to it. The
+        // left-hand side never reaches the point at infinity it would sum to, so such a nonce only
+        // makes the signature invalid.
+        let r_is_offset = self.is_equal(r_point.x, shifted_offset.x);
+        let r_point = self.select(r_is_offset, dummy, r_point);
+        let rhs = self.ec_add(r_point, shifted_offset);
+        let equation_holds = self.is_equal(lhs, rhs);
+        let r_not_offset = self.not(r_is_offset);
+
+        let mut is_valid = self.and(px_in_range, pubkey_on_curve);
+        is_valid = self.and(is_valid, r_in_range);
+        is_valid = self.and(is_valid, r_on_curve);
+        is_valid = self.and(is_valid, s_in_range);
+        is_valid = self.and(is_valid, r_not_offset);
+        self.and(is_valid, equation_holds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::{BoolVariable, Bytes32Variable, BytesVariable, DefaultBuilder};
+    use crate::utils::{bytes, bytes32};
+
+    // The official BIP-340 test vectors with 32-byte messages, as
+    // (public key, message, signature, expected result).
+    const TEST_VECTORS: [(&str, &str, &str, bool); 17] = [
+        (
+            "0xF9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+            "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "0xE907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA821525F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0",
+            true,
+        ),
+        (
+            "0xDFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "0x243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "0x6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE33418906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A",
+            true,
+        ),
+        (
+            "0xDD308AFEC5777E13121FA72B9CC1B7CC0139715309B086C960E18FD969774EB8",
+            "0x7E2D58D8B3BCDF1ABADEC7829054F90DDA9805AAB56C77333024B9D0A508B75C",
+            "0x5831AAEED7B44BB74E5EAB94BA9D4294C49BCF2A60728D8B4C200F50DD313C1BAB745879A5AD954A72C45A91C3A51D3C7ADEA98D82F8481E0E1E03674A6F3FB7",
+            true,
+        ),
+        (
+            "0x25D1DFF95105F5253C4022F628A996AD3A0D95FBF21D468A1B33F8C160D8F517",
+            "0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+            "0x7EB0509757E246F19449885651611CB965ECC1A187DD51B64FDA1EDC9637D5EC97582B9CB13DB3933705B32BA982AF5AF25FD78881EBB32771FC5922EFC66EA3",
+            true,
+        ),
+        (
+            "0xD69C3509BB99E412E68B0FE8544E72837DFA30746D8BE2AA65975F29D22DC7B9",
+            "0x4DF3C3F68FCC83B27E9D42C90431A72499F17875C81A599B566C9889B9696703",
+            "0x00000000000000000000003B78CE563F89A0ED9414F5AA28AD0D96D6795F9C6376AFB1548AF603B3EB45C9F8207DEE1060CB71C04E80F593060B07D28308D7F4",
+            true,
+        ),
+        // Public key not on the curve.
+        (
+            "0xEEFDEA4CDB677750A420FEE807EACF21EB9898AE79B9768766E4FAA04A2D4A34",
+            "0x243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "0x6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            false,
+        ),
+        // has_even_y(R) is false.
+        (
+            "0xDFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "0x243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "0xFFF97BD5755EEEA420453A14355235D382F6472F8568A18B2F057A14602975563CC27944640AC607CD107AE10923D9EF7A73C643E166BE5EBEAFA34B1AC553E2",
+            false,
+        ),
+        // Negated message.
+        (
+            "0xDFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "0x243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "0x1FA62E331EDBC21C394792D2AB1100A7B432B013DF3F6FF4F99FCB33E0E1515F28890B3EDB6E7189B630448B515CE4F8622A954CFE545735AAEA5134FCCDB2BD",
+            false,
+        ),
+        // Negated s value.
+        (
+            "0xDFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "0x243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "0x6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769961764B3AA9B2FFCB6EF947B6887A226E8D7C93E00C5ED0C1834FF0D0C2E6DA6",
+            false,
+        ),
+        // sG - eP is infinite, with r = 0.
+        (
+            "0xDFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "0x243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "0x0000000000000000000000000000000000000000000000000000000000000000123DDA8328AF9C23A94C1FEECFD123BA4FB73476F0D594DCB65C6425BD186051",
+            false,
+        ),
+        // sG - eP is infinite, with r = 1.
+        (
+            "0xDFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "0x243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "0x00000000000000000000000000000000000000000000000000000000000000017615FBAF5AE28864013C099742DEADB4DBA87F11AC6754F93780D5A1837CF197",
+            false,
+        ),
+        // sig[0:32] is not an x coordinate on the curve.
+        (
+            "0xDFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "0x243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "0x4A298DACAE57395A15D0795DDBFD1DCB564DA82B0F269BC70A74F8220429BA1D69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            false,
+        ),
+        // sig[0:32] is equal to the field size.
+        (
+            "0xDFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "0x243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            false,
+        ),
+        // sig[32:64] is equal to the curve order.
+        (
+            "0xDFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "0x243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "0x6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+            false,
+        ),
+        // Public key exceeds the field size.
+        (
+            "0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC30",
+            "0x243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "0x6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            false,
+        ),
+        // Public key equal to the generator, whose sum with the generator is a doubling. The
+        // signature is generated with the reference implementation, for the secret key 1 and
+        // zero auxiliary randomness.
+        (
+            "0x79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798",
+            "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "0xD2BCEE6A047E765467F3ED7C3E8F55EDCFA4A5FD37A9BCD064C1B5041599B187C3F9F2BE0665D539E38EB75989B4BC3F6DD2D9D18C5C123613615D1731E0523E",
+            true,
+        ),
+        // The same signature with a different s value.
+        (
+            "0x79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798",
+            "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "0xD2BCEE6A047E765467F3ED7C3E8F55EDCFA4A5FD37A9BCD064C1B5041599B187C3F9F2BE0665D539E38EB75989B4BC3F6DD2D9D18C5C123613615D1731E0523F",
+            false,
+        ),
+    ];
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_schnorr_verify() {
+        let mut builder = DefaultBuilder::new();
+
+        let pubkey_x = builder.read::<Bytes32Variable>();
+        let msg = builder.read::<Bytes32Variable>();
+        let sig = builder.read::<BytesVariable<64>>();
+        let is_valid = builder.schnorr_verify(pubkey_x, msg, sig);
+        builder.write(is_valid);
+
+        let circuit = builder.build();
+
+        for (pubkey_x, msg, sig, expected) in TEST_VECTORS {
+            let mut input = circuit.input();
+            input.write::<Bytes32Variable>(bytes32!(pubkey_x));
+            input.write::<Bytes32Variable>(bytes32!(msg));
+            input.write::<BytesVariable<64>>(bytes!(sig));
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<BoolVariable>(), expected);
+        }
+    }
+}
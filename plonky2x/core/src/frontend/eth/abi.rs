@@ -0,0 +1,419 @@
+//! ABI encoding and decoding of typed values, so that circuit outputs can be recomputed by
+//! contracts with `abi.encodePacked(...)` and circuit inputs can be read from `abi.encode(...)`.
+//!
+//! Only static types are supported: addresses, `uintN` for the widths of the uint variables,
+//! `bytesN` and `bool`. Dynamic types (`bytes`, `string` and arrays) would be new variants of
+//! `AbiType` and `AbiValueVariable`, encoded by an offset in the head and their data in the tail.
+
+use crate::frontend::eth::vars::AddressVariable;
+use crate::frontend::uint::uint64::U64Variable;
+use crate::frontend::vars::EvmVariable;
+use crate::prelude::{
+    BoolVariable, ByteVariable, CircuitBuilder, PlonkParameters, U128Variable, U256Variable,
+    U32Variable,
+};
+
+/// The size of a head slot of the ABI encoding.
+const ABI_SLOT_BYTES: usize = 32;
+
+/// A static ABI type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbiType {
+    Address,
+    /// An unsigned integer of the given number of bits, which must be 8, 32, 64, 128 or 256.
+    Uint(usize),
+    /// A fixed-size byte array of the given number of bytes, from 1 to 32.
+    FixedBytes(usize),
+    Bool,
+}
+
+impl AbiType {
+    /// Panics if the type has no variable, i.e. if it is a `uintN` whose width is not a multiple
+    /// of 8 of at most 256 bits or not the width of a uint variable, or a `bytesN` of more than 32
+    /// bytes.
+    fn assert_supported(&self) {
+        match *self {
+            AbiType::Uint(bits) => {
+                assert!(
+                    bits > 0 && bits % 8 == 0 && bits <= 256,
+                    "uint{} is not a valid ABI type",
+                    bits
+                );
+                assert!(
+                    matches!(bits, 8 | 32 | 64 | 128 | 256),
+                    "uint{} is not supported",
+                    bits
+                );
+            }
+            AbiType::FixedBytes(len) => assert!(
+                len > 0 && len <= ABI_SLOT_BYTES,
+                "bytesN must have 1 to 32 bytes"
+            ),
+            AbiType::Address | AbiType::Bool => {}
+        }
+    }
+}
+
+/// A value of a static ABI type.
+#[derive(Debug, Clone)]
+pub enum AbiValueVariable {
+    Address(AddressVariable),
+    Uint8(ByteVariable),
+    Uint32(U32Variable),
+    Uint64(U64Variable),
+    Uint128(U128Variable),
+    Uint256(U256Variable),
+    FixedBytes(Vec<ByteVariable>),
+    Bool(BoolVariable),
+}
+
+impl AbiValueVariable {
+    /// Returns the ABI type of the value.
+    pub fn abi_type(&self) -> AbiType {
+        match self {
+            AbiValueVariable::Address(_) => AbiType::Address,
+            AbiValueVariable::Uint8(_) => AbiType::Uint(8),
+            AbiValueVariable::Uint32(_) => AbiType::Uint(32),
+            AbiValueVariable::Uint64(_) => AbiType::Uint(64),
+            AbiValueVariable::Uint128(_) => AbiType::Uint(128),
+            AbiValueVariable::Uint256(_) => AbiType::Uint(256),
+            AbiValueVariable::FixedBytes(bytes) => AbiType::FixedBytes(bytes.len()),
+            AbiValueVariable::Bool(_) => AbiType::Bool,
+        }
+    }
+
+    /// Returns the encoding of the value in `abi.encodePacked`, which is its big-endian encoding
+    /// without any padding.
+    fn encode_packed<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        match self {
+            AbiValueVariable::Address(address) => address.encode(builder),
+            AbiValueVariable::Uint8(value) => vec![*value],
+            AbiValueVariable::Uint32(value) => value.encode(builder),
+            AbiValueVariable::Uint64(value) => value.encode(builder),
+            AbiValueVariable::Uint128(value) => value.encode(builder),
+            AbiValueVariable::Uint256(value) => value.encode(builder),
+            AbiValueVariable::FixedBytes(bytes) => {
+                assert!(
+                    !bytes.is_empty() && bytes.len() <= ABI_SLOT_BYTES,
+                    "bytesN must have 1 to 32 bytes"
+                );
+                bytes.clone()
+            }
+            AbiValueVariable::Bool(value) => {
+                let f = builder._false();
+                let mut bits = [f; 8];
+                bits[7] = *value;
+                vec![ByteVariable(bits)]
+            }
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns `abi.encodePacked(values...)`.
+    pub fn abi_encode_packed(&mut self, values: &[AbiValueVariable]) -> Vec<ByteVariable> {
+        values
+            .iter()
+            .flat_map(|value| value.encode_packed(self))
+            .collect()
+    }
+
+    /// Decodes the ABI encoding of values of the static types of `schema`, each of which takes a
+    /// 32 byte slot. Like the ABI decoder of Solidity, the padding of the slots is asserted to be
+    /// zero and booleans to be 0 or 1.
+    pub fn abi_decode_static(
+        &mut self,
+        bytes: &[ByteVariable],
+        schema: &[AbiType],
+    ) -> Vec<AbiValueVariable> {
+        assert_eq!(
+            bytes.len(),
+            schema.len() * ABI_SLOT_BYTES,
+            "the encoding must have one slot per value"
+        );
+        for abi_type in schema {
+            abi_type.assert_supported();
+        }
+        schema
+            .iter()
+            .zip(bytes.chunks(ABI_SLOT_BYTES))
+            .map(|(abi_type, slot)| self.abi_decode_slot(slot, *abi_type))
+            .collect()
+    }
+
+    /// Decodes a value of a static type from its slot.
+    fn abi_decode_slot(&mut self, slot: &[ByteVariable], abi_type: AbiType) -> AbiValueVariable {
+        let zero = self.zero::<ByteVariable>();
+        let assert_zero = |builder: &mut Self, bytes: &[ByteVariable]| {
+            for byte in bytes {
+                builder.assert_is_equal(*byte, zero);
+            }
+        };
+
+        // Values are left-padded, except for `bytesN` which is right-padded.
+        match abi_type {
+            AbiType::Address => {
+                assert_zero(self, &slot[..12]);
+                AbiValueVariable::Address(AddressVariable::decode(self, &slot[12..]))
+            }
+            AbiType::Uint(bits) => {
+                let start = ABI_SLOT_BYTES - bits / 8;
+                assert_zero(self, &slot[..start]);
+                let value = &slot[start..];
+                match bits {
+                    8 => AbiValueVariable::Uint8(value[0]),
+                    32 => AbiValueVariable::Uint32(U32Variable::decode(self, value)),
+                    64 => AbiValueVariable::Uint64(U64Variable::decode(self, value)),
+                    128 => AbiValueVariable::Uint128(U128Variable::decode(self, value)),
+                    256 => AbiValueVariable::Uint256(U256Variable::decode(self, value)),
+                    _ => unreachable!("the schema is checked before decoding"),
+                }
+            }
+            AbiType::FixedBytes(len) => {
+                assert_zero(self, &slot[len..]);
+                AbiValueVariable::FixedBytes(slot[..len].to_vec())
+            }
+            AbiType::Bool => {
+                assert_zero(self, &slot[..ABI_SLOT_BYTES - 1]);
+                let bits = slot[ABI_SLOT_BYTES - 1].as_be_bits();
+                let f = self._false();
+                for bit in bits.iter().take(7) {
+                    self.assert_is_equal(*bit, f);
+                }
+                AbiValueVariable::Bool(bits[7])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::{decode, encode, encode_packed, ParamType, Token};
+    use ethers::types::{H160, U256};
+
+    use super::*;
+    use crate::backend::circuit::CircuitFuzzer;
+    use crate::prelude::{Bytes32Variable, DefaultBuilder};
+    use crate::utils::{address, bytes32};
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_abi_encode_packed() {
+        let recipient = address!("0x55032650b14df07b85bF18A3a3eC8E0Af2e028d5");
+        let amount = U256::from_dec_str("1000000000000000000000").unwrap();
+        let commitment =
+            bytes32!("0x0c8f2fa2a3a1b3a4fe4a9d7f6c6e6f9f0f0e0d0c0b0a09080706050403020100");
+
+        let mut builder = DefaultBuilder::new();
+        let recipient_variable = builder.read::<AddressVariable>();
+        let amount_variable = builder.read::<U256Variable>();
+        let commitment_variable = builder.read::<Bytes32Variable>();
+        let decimals = builder.read::<ByteVariable>();
+        let nonce = builder.read::<U32Variable>();
+        let slot = builder.read::<U64Variable>();
+        let total = builder.read::<U128Variable>();
+        let selector = builder.read::<[ByteVariable; 4]>();
+        let flag = builder.read::<BoolVariable>();
+
+        // The output of a bridge circuit, `abi.encodePacked(address, uint256, bytes32)`.
+        let bridge = builder.abi_encode_packed(&[
+            AbiValueVariable::Address(recipient_variable),
+            AbiValueVariable::Uint256(amount_variable),
+            AbiValueVariable::FixedBytes(commitment_variable.as_bytes().to_vec()),
+        ]);
+        let narrow = builder.abi_encode_packed(&[
+            AbiValueVariable::Uint8(decimals),
+            AbiValueVariable::Uint32(nonce),
+            AbiValueVariable::Uint64(slot),
+            AbiValueVariable::Uint128(total),
+            AbiValueVariable::FixedBytes(selector.to_vec()),
+            AbiValueVariable::Bool(flag),
+        ]);
+        for byte in bridge.iter().chain(narrow.iter()) {
+            builder.write(*byte);
+        }
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.write::<AddressVariable>(recipient);
+        input.write::<U256Variable>(amount);
+        input.write::<Bytes32Variable>(commitment);
+        input.write::<ByteVariable>(18);
+        input.write::<U32Variable>(0xdeadbeef);
+        input.write::<U64Variable>(7404237);
+        input.write::<U128Variable>(u128::MAX - 1);
+        input.write::<[ByteVariable; 4]>([0xa9, 0x05, 0x9c, 0xbb]);
+        input.write::<BoolVariable>(true);
+        let mut output = circuit.mock_prove(&input).output;
+        let bridge_bytes = (0..bridge.len())
+            .map(|_| output.read::<ByteVariable>())
+            .collect::<Vec<_>>();
+        let narrow_bytes = (0..narrow.len())
+            .map(|_| output.read::<ByteVariable>())
+            .collect::<Vec<_>>();
+
+        let expected_bridge = encode_packed(&[
+            Token::Address(recipient),
+            Token::Uint(amount),
+            Token::FixedBytes(commitment.as_bytes().to_vec()),
+        ])
+        .unwrap();
+        assert_eq!(bridge_bytes, expected_bridge);
+
+        // `Token::Uint` doesn't carry its width, so `encode_packed` always encodes it on 32 bytes.
+        let mut expected_narrow = vec![18u8];
+        expected_narrow.extend_from_slice(&0xdeadbeefu32.to_be_bytes());
+        expected_narrow.extend_from_slice(&7404237u64.to_be_bytes());
+        expected_narrow.extend_from_slice(&(u128::MAX - 1).to_be_bytes());
+        expected_narrow.extend(
+            encode_packed(&[
+                Token::FixedBytes(vec![0xa9, 0x05, 0x9c, 0xbb]),
+                Token::Bool(true),
+            ])
+            .unwrap(),
+        );
+        assert_eq!(narrow_bytes, expected_narrow);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_abi_decode_static() {
+        let schema = [
+            AbiType::Address,
+            AbiType::Uint(256),
+            AbiType::FixedBytes(32),
+            AbiType::Uint(8),
+            AbiType::Uint(64),
+            AbiType::FixedBytes(4),
+            AbiType::Bool,
+        ];
+        let param_types = [
+            ParamType::Address,
+            ParamType::Uint(256),
+            ParamType::FixedBytes(32),
+            ParamType::Uint(8),
+            ParamType::Uint(64),
+            ParamType::FixedBytes(4),
+            ParamType::Bool,
+        ];
+        let tokens = vec![
+            Token::Address(address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2")),
+            Token::Uint(U256::MAX - 1),
+            Token::FixedBytes(vec![0x42; 32]),
+            Token::Uint(U256::from(18)),
+            Token::Uint(U256::from(u64::MAX)),
+            Token::FixedBytes(vec![0xa9, 0x05, 0x9c, 0xbb]),
+            Token::Bool(true),
+        ];
+        let encoded = encode(&tokens);
+
+        let mut builder = DefaultBuilder::new();
+        let bytes = builder.read::<[ByteVariable; 224]>();
+        let values = builder.abi_decode_static(&bytes, &schema);
+        for value in values.iter() {
+            match value {
+                AbiValueVariable::Address(v) => builder.write(*v),
+                AbiValueVariable::Uint8(v) => builder.write(*v),
+                AbiValueVariable::Uint32(v) => builder.write(*v),
+                AbiValueVariable::Uint64(v) => builder.write(*v),
+                AbiValueVariable::Uint128(v) => builder.write(*v),
+                AbiValueVariable::Uint256(v) => builder.write(*v),
+                AbiValueVariable::FixedBytes(v) => v.iter().for_each(|b| builder.write(*b)),
+                AbiValueVariable::Bool(v) => builder.write(*v),
+            }
+        }
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.write::<[ByteVariable; 224]>(encoded.clone().try_into().unwrap());
+        let mut output = circuit.mock_prove(&input).output;
+        let decoded = schema
+            .iter()
+            .map(|abi_type| match abi_type {
+                AbiType::Address => Token::Address(output.read::<AddressVariable>()),
+                AbiType::Uint(8) => Token::Uint(U256::from(output.read::<ByteVariable>())),
+                AbiType::Uint(64) => Token::Uint(U256::from(output.read::<U64Variable>())),
+                AbiType::Uint(256) => Token::Uint(output.read::<U256Variable>()),
+                AbiType::FixedBytes(len) => {
+                    Token::FixedBytes((0..*len).map(|_| output.read::<ByteVariable>()).collect())
+                }
+                AbiType::Bool => Token::Bool(output.read::<BoolVariable>()),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(decoded, decode(&param_types, &encoded).unwrap());
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_abi_decode_static_dirty_address() {
+        let mut builder = DefaultBuilder::new();
+        let bytes = builder.read::<[ByteVariable; 32]>();
+        builder.abi_decode_static(&bytes, &[AbiType::Address]);
+        let circuit = builder.mock_build();
+
+        // An address with a nonzero byte in its padding.
+        let mut encoded = encode(&[Token::Address(H160::repeat_byte(0x11))]);
+        encoded[0] = 1;
+        let mut input = circuit.input();
+        input.write::<[ByteVariable; 32]>(encoded.try_into().unwrap());
+        circuit.mock_prove(&input);
+    }
+
+    #[test]
+    #[should_panic(expected = "uint264 is not a valid ABI type")]
+    fn test_abi_decode_static_invalid_uint() {
+        let mut builder = DefaultBuilder::new();
+        let bytes = builder.read::<[ByteVariable; 32]>();
+        builder.abi_decode_static(&bytes, &[AbiType::Uint(264)]);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_fuzz_abi_decode_static() {
+        let schema = [
+            AbiType::Address,
+            AbiType::Uint(64),
+            AbiType::Bool,
+            AbiType::FixedBytes(4),
+        ];
+        let mut builder = DefaultBuilder::new();
+        let bytes = (0..schema.len() * ABI_SLOT_BYTES)
+            .map(|_| builder.evm_read::<ByteVariable>())
+            .collect::<Vec<_>>();
+        let values = builder.abi_decode_static(&bytes, &schema);
+        let packed = builder.abi_encode_packed(&values);
+        for byte in packed {
+            builder.evm_write(byte);
+        }
+        let circuit = builder.build();
+
+        // Like the decoder of Solidity, reject nonzero padding and booleans other than 0 and 1.
+        let reference = |input: &[u8]| {
+            let slots = input.chunks(ABI_SLOT_BYTES).collect::<Vec<_>>();
+            let is_zero = |bytes: &[u8]| bytes.iter().all(|b| *b == 0);
+            let valid = is_zero(&slots[0][..12])
+                && is_zero(&slots[1][..24])
+                && is_zero(&slots[2][..31])
+                && slots[2][31] <= 1
+                && is_zero(&slots[3][4..]);
+            valid.then(|| {
+                [
+                    &slots[0][12..],
+                    &slots[1][24..],
+                    &slots[2][31..],
+                    &slots[3][..4],
+                ]
+                .concat()
+            })
+        };
+        let report = CircuitFuzzer::new(&circuit, reference, 0x5eed, 64)
+            .run()
+            .unwrap_or_else(|divergence| panic!("{}", divergence));
+        assert!(report.accepted > 0 && report.rejected > 0, "{:?}", report);
+    }
+}
@@ -3,9 +3,10 @@ use ethers::types::{H256, U256};
 
 use super::generators::{
     BeaconAllWithdrawalsHint, BeaconBalanceBatchWitnessHint, BeaconBalanceGenerator,
-    BeaconBalanceWitnessHint, BeaconBalancesGenerator, BeaconBlockRootsHint, BeaconGraffitiHint,
-    BeaconHeaderHint, BeaconHeadersFromOffsetRangeHint, BeaconHistoricalBlockHint,
-    BeaconPartialBalancesHint, BeaconPartialValidatorsHint, BeaconValidatorBatchHint,
+    BeaconBalanceWitnessHint, BeaconBalancesGenerator, BeaconBlockRootsHint,
+    BeaconExecutionPayloadProofHint, BeaconGraffitiHint, BeaconHeaderHint,
+    BeaconHeadersFromOffsetRangeHint, BeaconHistoricalBlockHint, BeaconPartialBalancesHint,
+    BeaconPartialValidatorsHint, BeaconValidatorBatchHint, BeaconValidatorBatchProofHint,
     BeaconValidatorGenerator, BeaconValidatorSubtreeHint, BeaconValidatorSubtreePoseidonHint,
     BeaconValidatorSubtreesHint, BeaconValidatorsHint, BeaconWithdrawalGenerator,
     BeaconWithdrawalsGenerator, CompressedBeaconValidatorBatchHint, CLOSE_SLOT_BLOCK_ROOT_DEPTH,
@@ -13,17 +14,24 @@ use super::generators::{
 };
 use super::vars::{
     BeaconBalancesVariable, BeaconHeaderVariable, BeaconValidatorVariable,
-    BeaconValidatorsVariable, BeaconWithdrawalVariable, BeaconWithdrawalsVariable,
-    CompressedBeaconValidatorVariable,
+    BeaconValidatorsVariable, BeaconWithdrawalVariable, BeaconWithdrawalsProofVariable,
+    BeaconWithdrawalsVariable, CompressedBeaconValidatorVariable, ExecutionPayloadFieldsVariable,
+    ExecutionPayloadProofVariable, HistoricalBlockRootProofVariable, HISTORICAL_SUMMARY_DEPTH,
 };
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
-use crate::frontend::eth::vars::BLSPubkeyVariable;
+use crate::frontend::eth::vars::{AddressVariable, BLSPubkeyVariable};
 use crate::frontend::uint::uint64::U64Variable;
 use crate::frontend::vars::{
     Bytes32Variable, CircuitVariable, EvmVariable, SSZVariable, VariableStream,
 };
-use crate::prelude::{ArrayVariable, BoolVariable, ByteVariable, BytesVariable, U256Variable};
+use crate::prelude::{
+    ArrayVariable, BoolVariable, ByteVariable, BytesVariable, U256Variable, U32Variable,
+};
+use crate::utils::eth::beacon::block::{
+    BeaconFork, EXECUTION_PAYLOAD_BLOCK_HASH_INDEX, EXECUTION_PAYLOAD_BLOCK_NUMBER_INDEX,
+    EXECUTION_PAYLOAD_STATE_ROOT_INDEX, EXECUTION_PAYLOAD_WITHDRAWALS_INDEX,
+};
 use crate::utils::eth::concat_g_indices;
 
 /// The gindex for blockRoot -> validatorsRoot.
@@ -38,6 +46,9 @@ const BALANCES_ROOT_GINDEX: u64 = 364;
 /// The gindex for blockRoot -> withdrawalsRoot.
 const WITHDRAWALS_ROOT_GINDEX: u64 = 3230;
 
+/// The gindex for blockRoot -> executionPayloadRoot.
+const EXECUTION_PAYLOAD_ROOT_GINDEX: u64 = 201;
+
 /// The gindex for validatorsRoot -> validators[i].
 const VALIDATOR_BASE_GINDEX: u64 = 1099511627776 * 2;
 
@@ -50,6 +61,9 @@ const WITHDRAWAL_BASE_GINDEX: u64 = 32;
 /// The gindex for blockRoot -> state -> state.historicalSummaries[0].
 const HISTORICAL_SUMMARIES_BASE_GINDEX: u64 = 12717129728;
 
+/// The gindex for stateRoot -> historicalSummaries.
+const HISTORICAL_SUMMARIES_GINDEX: u64 = 59;
+
 /// The gindex for state.historicalSummaries[i] -> block_summary/block_roots -> block_roots[0].
 const HISTORICAL_SUMMARY_BLOCK_ROOT_GINDEX: u64 = 16384;
 
@@ -174,6 +188,66 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         output_stream.read::<ArrayVariable<BeaconValidatorVariable, B>>(self)
     }
 
+    /// Verifies that `validators` are the `B` consecutive validators from `start_index` in the
+    /// validators list with root `validators_root`, with a single proof of the root of the
+    /// subtree holding them, which is merkleized from the validators.
+    ///
+    /// `B` must be a power of two and `start_index` a multiple of `B`. The proof goes from
+    /// `validatorsRoot` to the subtree, and has `41 - log2(B)` branches.
+    pub fn verify_validator_batch<const B: usize>(
+        &mut self,
+        validators_root: Bytes32Variable,
+        start_index: U64Variable,
+        validators: &ArrayVariable<BeaconValidatorVariable, B>,
+        proof: &[Bytes32Variable],
+    ) {
+        assert!(B.is_power_of_two(), "the batch size must be a power of two");
+        let b_log2 = B.trailing_zeros() as usize;
+        assert_eq!(proof.len(), VALIDATOR_REGISTRY_LIMIT_LOG2 + 1 - b_log2);
+
+        let batch_size = self.constant::<U64Variable>(B as u64);
+        let offset = self.rem(start_index, batch_size);
+        let zero = self.zero::<U64Variable>();
+        self.assert_is_equal(offset, zero);
+        let subtree_index = self.div(start_index, batch_size);
+        let nb_subtrees =
+            self.constant::<U64Variable>(1 << (VALIDATOR_REGISTRY_LIMIT_LOG2 - b_log2));
+        let is_valid_index = self.lt(subtree_index, nb_subtrees);
+        let t = self._true();
+        self.assert_is_equal(is_valid_index, t);
+
+        let leafs = validators
+            .as_slice()
+            .iter()
+            .map(|validator| self.ssz_hash_tree_root(*validator))
+            .collect::<Vec<_>>();
+        let subtree_root = self.ssz_hash_leafs(&leafs);
+        let mut gindex = self.constant::<U64Variable>(VALIDATOR_BASE_GINDEX >> b_log2);
+        gindex = self.add(gindex, subtree_index);
+        self.ssz_verify_proof(validators_root, subtree_root, proof, gindex);
+    }
+
+    /// Get the `B` validators from a given start index, which must be a multiple of `B`, verified
+    /// with `verify_validator_batch`.
+    pub fn beacon_get_validator_batch<const B: usize>(
+        &mut self,
+        validators: BeaconValidatorsVariable,
+        start_index: U64Variable,
+    ) -> ArrayVariable<BeaconValidatorVariable, B> {
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&validators.block_root);
+        input_stream.write(&start_index);
+        let hint = BeaconValidatorBatchProofHint::<B> {};
+        let output_stream = self.hint(input_stream, hint);
+        let batch = output_stream.read::<ArrayVariable<BeaconValidatorVariable, B>>(self);
+        let nb_branches = VALIDATOR_REGISTRY_LIMIT_LOG2 + 1 - B.trailing_zeros() as usize;
+        let proof = (0..nb_branches)
+            .map(|_| output_stream.read::<Bytes32Variable>(self))
+            .collect::<Vec<_>>();
+        self.verify_validator_batch(validators.validators_root, start_index, &batch, &proof);
+        batch
+    }
+
     /// Witness the first B validators from a given start index.
     pub fn beacon_witness_compressed_validator_batch<const B: usize>(
         &mut self,
@@ -477,6 +551,64 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         withdrawals
     }
 
+    /// Verifies that the first `proof.len` elements of `withdrawals` are the withdrawals of the
+    /// execution payload with root `execution_payload_root`, from a block of `fork`.
+    ///
+    /// The elements past the length must have a zero amount, so that sums over `withdrawals` only
+    /// count the withdrawals of the payload.
+    pub fn verify_withdrawals<const W: usize>(
+        &mut self,
+        execution_payload_root: Bytes32Variable,
+        withdrawals: &ArrayVariable<BeaconWithdrawalVariable, W>,
+        proof: &BeaconWithdrawalsProofVariable,
+        fork: BeaconFork,
+    ) {
+        let withdrawals_root =
+            self.ssz_hash_list(withdrawals, proof.len, MAX_WITHDRAWALS_PER_PAYLOAD);
+        let depth = fork.execution_payload_depth();
+        let gindex = (1 << depth) + EXECUTION_PAYLOAD_WITHDRAWALS_INDEX as u64;
+        self.ssz_verify_proof_const(
+            execution_payload_root,
+            withdrawals_root,
+            &proof.branch[0..depth],
+            gindex,
+        );
+
+        let t = self._true();
+        let zero = self.zero::<U256Variable>();
+        for i in 0..W {
+            let index = self.constant::<U64Variable>(i as u64);
+            let is_withdrawal = self.lt(index, proof.len);
+            let is_zero = self.is_equal(withdrawals[i].amount, zero);
+            let is_valid = self.or(is_withdrawal, is_zero);
+            self.assert_is_equal(is_valid, t);
+        }
+    }
+
+    /// Returns the total amount in gwei of the `withdrawals` to `recipient`.
+    pub fn sum_withdrawals_to<const W: usize>(
+        &mut self,
+        withdrawals: &ArrayVariable<BeaconWithdrawalVariable, W>,
+        recipient: AddressVariable,
+    ) -> U64Variable {
+        let zero_u32 = self.zero::<U32Variable>();
+        let zero = self.zero::<U64Variable>();
+        let mut sum = zero;
+        for withdrawal in withdrawals.as_slice() {
+            // Amounts are in gwei, so they fit in the lower two limbs.
+            for limb in withdrawal.amount.limbs[2..].iter() {
+                self.assert_is_equal(*limb, zero_u32);
+            }
+            let amount = U64Variable {
+                limbs: [withdrawal.amount.limbs[0], withdrawal.amount.limbs[1]],
+            };
+            let is_recipient = self.is_equal(withdrawal.address, recipient);
+            let amount = self.select(is_recipient, amount, zero);
+            sum = self.add(sum, amount);
+        }
+        sum
+    }
+
     /// Get a validator withdrawal from a given index.
     pub fn beacon_get_withdrawal(
         &mut self,
@@ -510,6 +642,59 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         header
     }
 
+    /// Verifies the branches from `beacon_block_root` to the block hash, the state root and the
+    /// block number of its execution payload, whose layout depends on `fork`.
+    pub fn verify_execution_payload_fields(
+        &mut self,
+        beacon_block_root: Bytes32Variable,
+        proof: &ExecutionPayloadProofVariable,
+        fork: BeaconFork,
+    ) -> ExecutionPayloadFieldsVariable {
+        self.ssz_verify_proof_const(
+            beacon_block_root,
+            proof.execution_payload_root,
+            proof.execution_payload_branch.as_slice(),
+            EXECUTION_PAYLOAD_ROOT_GINDEX,
+        );
+
+        let depth = fork.execution_payload_depth();
+        let gindex = |index: usize| ((1 << depth) + index) as u64;
+        let fields = proof.fields;
+        self.ssz_verify_proof_const(
+            proof.execution_payload_root,
+            fields.block_hash,
+            &proof.block_hash_branch[0..depth],
+            gindex(EXECUTION_PAYLOAD_BLOCK_HASH_INDEX),
+        );
+        self.ssz_verify_proof_const(
+            proof.execution_payload_root,
+            fields.state_root,
+            &proof.state_root_branch[0..depth],
+            gindex(EXECUTION_PAYLOAD_STATE_ROOT_INDEX),
+        );
+        let block_number_leaf = fields.block_number.hash_tree_root(self);
+        self.ssz_verify_proof_const(
+            proof.execution_payload_root,
+            block_number_leaf,
+            &proof.block_number_branch[0..depth],
+            gindex(EXECUTION_PAYLOAD_BLOCK_NUMBER_INDEX),
+        );
+        fields
+    }
+
+    /// Get the execution block hash, state root and block number of a block from `fork`.
+    pub fn beacon_get_execution_payload_fields(
+        &mut self,
+        block_root: Bytes32Variable,
+        fork: BeaconFork,
+    ) -> ExecutionPayloadFieldsVariable {
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&block_root);
+        let output_stream = self.async_hint(input_stream, BeaconExecutionPayloadProofHint { fork });
+        let proof = output_stream.read::<ExecutionPayloadProofVariable>(self);
+        self.verify_execution_payload_fields(block_root, &proof, fork)
+    }
+
     /// Get a historical block root using state.block_roots for close slots and historical_summaries for slots > 8192 slots away.
     pub fn beacon_get_historical_block(
         &mut self,
@@ -589,6 +774,62 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         target_block_root
     }
 
+    /// Verifies a block root older than the `block_roots` of the state with root `state_root`,
+    /// through the `historical_summaries` of the state: the block root at `target_slot` is in the
+    /// block roots of the summary at `summary_index`, which must be in the list.
+    ///
+    /// The index of the summary and the index of the block root in it are constrained from
+    /// `target_slot`, which must be after Capella.
+    pub fn verify_historical_block_root(
+        &mut self,
+        state_root: Bytes32Variable,
+        target_slot: U64Variable,
+        summary_index: U64Variable,
+        proof: &HistoricalBlockRootProofVariable,
+    ) -> Bytes32Variable {
+        let t = self._true();
+        let capella_slot = self.constant::<U64Variable>(CAPELLA_FORK_EPOCH * SLOTS_PER_EPOCH);
+        let is_after_capella = self.lte(capella_slot, target_slot);
+        self.assert_is_equal(is_after_capella, t);
+
+        // The summaries are appended every SLOTS_PER_HISTORICAL_ROOT slots since Capella.
+        let slots_per_historical = self.constant::<U64Variable>(SLOTS_PER_HISTORICAL_ROOT as u64);
+        let slots_since_capella = self.sub(target_slot, capella_slot);
+        let expected_summary_index = self.div(slots_since_capella, slots_per_historical);
+        self.assert_is_equal(summary_index, expected_summary_index);
+        let is_in_list = self.lt(summary_index, proof.historical_summaries_len);
+        self.assert_is_equal(is_in_list, t);
+        let block_roots_index = self.rem(target_slot, slots_per_historical);
+
+        // block_roots[i] -> block_summary_root -> historical_summaries[j].
+        let block_root_gindex = self.add(slots_per_historical, block_roots_index);
+        let block_summary_root = self.ssz_restore_merkle_root(
+            proof.block_root,
+            proof.block_root_branch.as_slice(),
+            block_root_gindex,
+        );
+        let summary_root = self.curta_sha256_pair(block_summary_root, proof.state_summary_root);
+
+        // historical_summaries[j] -> historical_summaries, whose root mixes in the length.
+        let mut summary_gindex = self.constant::<U64Variable>(1 << HISTORICAL_SUMMARY_DEPTH);
+        summary_gindex = self.add(summary_gindex, summary_index);
+        let summaries_data_root = self.ssz_restore_merkle_root(
+            summary_root,
+            proof.historical_summary_branch.as_slice(),
+            summary_gindex,
+        );
+        let summaries_root =
+            self.ssz_mix_in_length(summaries_data_root, proof.historical_summaries_len);
+
+        self.ssz_verify_proof_const(
+            state_root,
+            summaries_root,
+            proof.historical_summaries_branch.as_slice(),
+            HISTORICAL_SUMMARIES_GINDEX,
+        );
+        proof.block_root
+    }
+
     pub fn beacon_get_block_roots(
         &mut self,
         block_root: Bytes32Variable,
@@ -736,14 +977,23 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
 pub(crate) mod tests {
     use std::env;
 
+    use ethers::types::H256;
     use log::debug;
 
     use crate::backend::circuit::DefaultParameters;
     use crate::frontend::builder::CircuitBuilder;
-    use crate::frontend::eth::vars::BLSPubkeyVariable;
+    use crate::frontend::eth::beacon::vars::{
+        BeaconValidatorVariable, BeaconWithdrawalValue, BeaconWithdrawalVariable,
+        BeaconWithdrawalsProof, BeaconWithdrawalsProofVariable, ExecutionPayloadFieldsVariable,
+        ExecutionPayloadProof, ExecutionPayloadProofVariable, HistoricalBlockRootProof,
+        HistoricalBlockRootProofVariable,
+    };
+    use crate::frontend::eth::vars::{AddressVariable, BLSPubkeyVariable};
     use crate::frontend::uint::uint64::U64Variable;
     use crate::frontend::vars::Bytes32Variable;
-    use crate::utils::eth::beacon::BeaconClient;
+    use crate::prelude::{ArrayVariable, U256Variable};
+    use crate::utils::eth::beacon::block::{execution_payload_branches, BeaconFork};
+    use crate::utils::eth::beacon::{BeaconClient, BeaconValidator};
     use crate::utils::{bytes, bytes32};
 
     type L = DefaultParameters;
@@ -909,6 +1159,32 @@ pub(crate) mod tests {
         circuit.test_default_serializers();
     }
 
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_beacon_get_validator_batch() {
+        env_logger::try_init().unwrap_or_default();
+        dotenv::dotenv().ok();
+
+        // The batch and its subtree proof are fetched from the finalized state of the network of
+        // the RPC, e.g. Holesky, and checked against the validators root of the state.
+        let consensus_rpc = env::var("CONSENSUS_RPC_URL").unwrap();
+        let client = BeaconClient::new(consensus_rpc);
+        let latest_block_root = client.get_finalized_block_root().unwrap();
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        builder.set_beacon_client(client);
+
+        let block_root = builder.constant::<Bytes32Variable>(bytes32!(latest_block_root));
+        let validators = builder.beacon_get_validators(block_root);
+        let start_index = builder.constant::<U64Variable>(1024);
+        let batch = builder.beacon_get_validator_batch::<64>(validators, start_index);
+        builder.watch(&batch, "batch");
+
+        let circuit = builder.mock_build();
+        let input = circuit.input();
+        circuit.mock_prove(&input);
+    }
+
     #[test]
     #[cfg_attr(feature = "ci", ignore)]
     fn test_beacon_get_balances_root() {
@@ -1275,4 +1551,263 @@ pub(crate) mod tests {
         circuit.verify(&proof, &input, &output);
         circuit.test_default_serializers();
     }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ValidatorBatchFixture {
+        validators_root: H256,
+        start_index: u64,
+        validators: Vec<BeaconValidator>,
+        proof: Vec<H256>,
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_validator_batch() {
+        env_logger::try_init().unwrap_or_default();
+        const B: usize = 4;
+
+        // The validators 4 to 7 of a synthetic registry of 8 validators, with the proof of their
+        // subtree from the validators root.
+        let fixture =
+            std::fs::read_to_string("./src/frontend/eth/beacon/fixtures/validator_batch.json")
+                .expect("failed to read fixture");
+        let fixture: ValidatorBatchFixture = serde_json::from_str(&fixture).unwrap();
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let validators_root = builder.read::<Bytes32Variable>();
+        let start_index = builder.read::<U64Variable>();
+        let validators = builder.read::<ArrayVariable<BeaconValidatorVariable, B>>();
+        let proof = builder.read::<ArrayVariable<Bytes32Variable, 39>>();
+        builder.verify_validator_batch(validators_root, start_index, &validators, proof.as_slice());
+        builder.write(validators[2].effective_balance);
+        builder.write(validators[2].exit_epoch);
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.write::<Bytes32Variable>(fixture.validators_root);
+        input.write::<U64Variable>(fixture.start_index);
+        input.write::<ArrayVariable<BeaconValidatorVariable, B>>(fixture.validators.clone());
+        input.write::<ArrayVariable<Bytes32Variable, 39>>(fixture.proof);
+        let mut output = circuit.mock_prove(&input).output;
+        let effective_balance = output.read::<U256Variable>();
+        let exit_epoch = output.read::<U256Variable>();
+        assert_eq!(
+            effective_balance,
+            fixture.validators[2].effective_balance.into()
+        );
+        assert_eq!(
+            exit_epoch,
+            fixture.validators[2]
+                .exit_epoch
+                .parse::<u64>()
+                .unwrap()
+                .into()
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_execution_payload_fields() {
+        env_logger::try_init().unwrap_or_default();
+
+        // Synthetic Capella and Deneb blocks in the format of the beacon API.
+        let fixtures =
+            std::fs::read_to_string("./src/frontend/eth/beacon/fixtures/execution_payload.json")
+                .expect("failed to read fixtures");
+        let fixtures: Vec<serde_json::Value> = serde_json::from_str(&fixtures).unwrap();
+
+        for fixture in fixtures {
+            let fork = BeaconFork::from_version(fixture["fork"].as_str().unwrap()).unwrap();
+            let message = &fixture["block"]["data"]["message"];
+            let branches = execution_payload_branches(message, fork);
+            let block_root = branches.block_root;
+            let payload = &message["body"]["execution_payload"];
+
+            let mut builder = CircuitBuilder::<L, D>::new();
+            let block_root_variable = builder.read::<Bytes32Variable>();
+            let proof = builder.read::<ExecutionPayloadProofVariable>();
+            let fields = builder.verify_execution_payload_fields(block_root_variable, &proof, fork);
+            builder.write(fields);
+            let circuit = builder.mock_build();
+
+            let mut input = circuit.input();
+            input.write::<Bytes32Variable>(block_root);
+            input.write::<ExecutionPayloadProofVariable>(ExecutionPayloadProof::from(branches));
+            let mut output = circuit.mock_prove(&input).output;
+            let fields = output.read::<ExecutionPayloadFieldsVariable>();
+
+            assert_eq!(
+                fields.block_hash,
+                bytes32!(payload["block_hash"].as_str().unwrap())
+            );
+            assert_eq!(
+                fields.state_root,
+                bytes32!(payload["state_root"].as_str().unwrap())
+            );
+            assert_eq!(
+                fields.block_number,
+                payload["block_number"]
+                    .as_str()
+                    .unwrap()
+                    .parse::<u64>()
+                    .unwrap()
+            );
+        }
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct HistoricalBlockRootFixture {
+        state_root: H256,
+        target_slot: u64,
+        summary_index: u64,
+        block_root: H256,
+        block_root_branch: Vec<H256>,
+        state_summary_root: H256,
+        historical_summary_branch: Vec<H256>,
+        historical_summaries_len: u64,
+        historical_summaries_branch: Vec<H256>,
+    }
+
+    /// Proves the block root at `target_slot` through the historical summaries of a state.
+    fn prove_historical_block_root(fixture: HistoricalBlockRootFixture) -> H256 {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let state_root = builder.read::<Bytes32Variable>();
+        let target_slot = builder.read::<U64Variable>();
+        let summary_index = builder.read::<U64Variable>();
+        let proof = builder.read::<HistoricalBlockRootProofVariable>();
+        let block_root =
+            builder.verify_historical_block_root(state_root, target_slot, summary_index, &proof);
+        builder.write(block_root);
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.write::<Bytes32Variable>(fixture.state_root);
+        input.write::<U64Variable>(fixture.target_slot);
+        input.write::<U64Variable>(fixture.summary_index);
+        input.write::<HistoricalBlockRootProofVariable>(HistoricalBlockRootProof {
+            block_root: fixture.block_root,
+            block_root_branch: fixture.block_root_branch,
+            state_summary_root: fixture.state_summary_root,
+            historical_summary_branch: fixture.historical_summary_branch,
+            historical_summaries_len: fixture.historical_summaries_len,
+            historical_summaries_branch: fixture.historical_summaries_branch,
+        });
+        let mut output = circuit.mock_prove(&input).output;
+        output.read::<Bytes32Variable>()
+    }
+
+    /// A synthetic state with 300 historical summaries, about 11 months after Capella, and the
+    /// proof of a block root in the summary 150, about 5 months older than the state. The branches
+    /// have the depths of a mainnet state, so a fixture recorded from a mainnet state, e.g. from
+    /// `/eth/v2/debug/beacon/states`, has the same format.
+    fn read_historical_block_root_fixture() -> HistoricalBlockRootFixture {
+        let fixture = std::fs::read_to_string(
+            "./src/frontend/eth/beacon/fixtures/historical_block_root.json",
+        )
+        .expect("failed to read fixture");
+        serde_json::from_str(&fixture).unwrap()
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_historical_block_root() {
+        env_logger::try_init().unwrap_or_default();
+        let fixture = read_historical_block_root_fixture();
+        let expected = fixture.block_root;
+        assert_eq!(prove_historical_block_root(fixture), expected);
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_historical_block_root_wrong_slot() {
+        env_logger::try_init().unwrap_or_default();
+        // The same position in the next period: the branches are still valid, but the slot no
+        // longer matches the summary index.
+        let mut fixture = read_historical_block_root_fixture();
+        fixture.target_slot += 8192;
+        prove_historical_block_root(fixture);
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct WithdrawalFixture {
+        index: u64,
+        validator_index: u64,
+        address: ethers::types::Address,
+        amount: u64,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct WithdrawalsFixture {
+        execution_payload_root: H256,
+        withdrawals: Vec<WithdrawalFixture>,
+        withdrawals_branch: Vec<H256>,
+        recipient: ethers::types::Address,
+        expected_sum: u64,
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_withdrawals_sum() {
+        env_logger::try_init().unwrap_or_default();
+        const W: usize = 8;
+
+        // The 6 withdrawals of a synthetic Deneb execution payload, 2 of which are to the
+        // recipient.
+        let fixture =
+            std::fs::read_to_string("./src/frontend/eth/beacon/fixtures/withdrawals.json")
+                .expect("failed to read fixture");
+        let fixture: WithdrawalsFixture = serde_json::from_str(&fixture).unwrap();
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let execution_payload_root = builder.read::<Bytes32Variable>();
+        let withdrawals = builder.read::<ArrayVariable<BeaconWithdrawalVariable, W>>();
+        let proof = builder.read::<BeaconWithdrawalsProofVariable>();
+        let recipient = builder.read::<AddressVariable>();
+        builder.verify_withdrawals(
+            execution_payload_root,
+            &withdrawals,
+            &proof,
+            BeaconFork::Deneb,
+        );
+        let sum = builder.sum_withdrawals_to(&withdrawals, recipient);
+        builder.write(sum);
+        let circuit = builder.mock_build();
+
+        // The trailing withdrawals have a zero amount, and may be to the recipient.
+        let len = fixture.withdrawals.len();
+        let mut withdrawals = fixture
+            .withdrawals
+            .iter()
+            .map(|withdrawal| BeaconWithdrawalValue {
+                index: withdrawal.index,
+                validator_index: withdrawal.validator_index,
+                address: withdrawal.address,
+                amount: withdrawal.amount.into(),
+            })
+            .collect::<Vec<_>>();
+        withdrawals.resize(
+            W,
+            BeaconWithdrawalValue {
+                index: 0,
+                validator_index: 0,
+                address: fixture.recipient,
+                amount: 0.into(),
+            },
+        );
+        let mut input = circuit.input();
+        input.write::<Bytes32Variable>(fixture.execution_payload_root);
+        input.write::<ArrayVariable<BeaconWithdrawalVariable, W>>(withdrawals);
+        input.write::<BeaconWithdrawalsProofVariable>(BeaconWithdrawalsProof {
+            len: len as u64,
+            branch: fixture.withdrawals_branch,
+        });
+        input.write::<AddressVariable>(fixture.recipient);
+        let mut output = circuit.mock_prove(&input).output;
+        assert_eq!(output.read::<U64Variable>(), fixture.expected_sum);
+    }
 }
@@ -0,0 +1,47 @@
+use std::env;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::frontend::eth::beacon::vars::{ExecutionPayloadProof, ExecutionPayloadProofVariable};
+use crate::frontend::hint::asynchronous::hint::AsyncHint;
+use crate::frontend::vars::ValueStream;
+use crate::prelude::{Bytes32Variable, PlonkParameters};
+use crate::utils::eth::beacon::block::{execution_payload_branches, BeaconFork};
+use crate::utils::eth::beacon::BeaconClient;
+use crate::utils::hex;
+
+/// Input: (block_root: bytes32)
+/// Output: (proof: ExecutionPayloadProof)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeaconExecutionPayloadProofHint {
+    pub fork: BeaconFork,
+}
+
+#[async_trait]
+impl<L: PlonkParameters<D>, const D: usize> AsyncHint<L, D> for BeaconExecutionPayloadProofHint {
+    async fn hint(
+        &self,
+        input_stream: &mut ValueStream<L, D>,
+        output_stream: &mut ValueStream<L, D>,
+    ) {
+        let client = BeaconClient::new(env::var("CONSENSUS_RPC_URL").unwrap());
+        let block_root = input_stream.read_value::<Bytes32Variable>();
+
+        let response = client.get_block(hex!(block_root.as_bytes())).await.unwrap();
+        let version = response["version"].as_str().unwrap();
+        assert_eq!(
+            BeaconFork::from_version(version),
+            Some(self.fork),
+            "the block is from the {} fork",
+            version
+        );
+
+        let branches = execution_payload_branches(&response["data"]["message"], self.fork);
+        assert_eq!(branches.block_root, block_root);
+
+        output_stream.write_value::<ExecutionPayloadProofVariable>(
+            ExecutionPayloadProof::<L::Field>::from(branches),
+        );
+    }
+}
@@ -3,6 +3,7 @@ mod balance;
 mod balance_witness;
 mod balances;
 mod block_roots;
+mod execution_payload;
 mod graffiti;
 mod header;
 mod headers;
@@ -20,6 +21,7 @@ pub use balance::BeaconBalanceGenerator;
 pub use balance_witness::{BeaconBalanceBatchWitnessHint, BeaconBalanceWitnessHint};
 pub use balances::BeaconBalancesGenerator;
 pub use block_roots::BeaconBlockRootsHint;
+pub use execution_payload::BeaconExecutionPayloadProofHint;
 pub use graffiti::BeaconGraffitiHint;
 pub use header::BeaconHeaderHint;
 pub use headers::BeaconHeadersFromOffsetRangeHint;
@@ -34,7 +36,8 @@ pub use validator_subtree::{
     BeaconValidatorSubtreeHint, BeaconValidatorSubtreePoseidonHint, BeaconValidatorSubtreesHint,
 };
 pub use validator_witness::{
-    BeaconValidatorBatchHint, BeaconValidatorHint, CompressedBeaconValidatorBatchHint,
+    BeaconValidatorBatchHint, BeaconValidatorBatchProofHint, BeaconValidatorHint,
+    CompressedBeaconValidatorBatchHint,
 };
 pub use validators::{BeaconValidatorsGenerator, BeaconValidatorsHint};
 pub use withdrawal::BeaconWithdrawalGenerator;
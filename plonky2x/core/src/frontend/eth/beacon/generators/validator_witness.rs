@@ -48,6 +48,38 @@ impl<L: PlonkParameters<D>, const D: usize, const B: usize> Hint<L, D>
     }
 }
 
+/// The log2 of the validator registry limit.
+const VALIDATOR_REGISTRY_LIMIT_LOG2: usize = 40;
+
+/// A hint that returns the `B` validators from a start index, which must be a multiple of `B`,
+/// along with the proof from `validatorsRoot` to the root of the subtree holding them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeaconValidatorBatchProofHint<const B: usize> {}
+
+impl<L: PlonkParameters<D>, const D: usize, const B: usize> Hint<L, D>
+    for BeaconValidatorBatchProofHint<B>
+{
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let client = BeaconClient::new(env::var("CONSENSUS_RPC_URL").unwrap());
+        let header_root = input_stream.read_value::<Bytes32Variable>();
+        let start_idx = input_stream.read_value::<U64Variable>();
+        let validators = client
+            .get_validator_batch_witness(hex!(header_root), start_idx, start_idx + B as u64)
+            .unwrap();
+
+        // The proof of the subtree is the proof of its first validator without the branches
+        // inside the subtree.
+        let response = client.get_validator(hex!(header_root), start_idx).unwrap();
+        assert_eq!(response.proof.len(), VALIDATOR_REGISTRY_LIMIT_LOG2 + 1);
+        let b_log2 = B.trailing_zeros() as usize;
+
+        output_stream.write_value::<ArrayVariable<BeaconValidatorVariable, B>>(validators);
+        for branch in response.proof[b_log2..].iter() {
+            output_stream.write_value::<Bytes32Variable>(bytes32!(branch));
+        }
+    }
+}
+
 const ZERO_VALIDATOR_PUBKEY: &str = "0x111111000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
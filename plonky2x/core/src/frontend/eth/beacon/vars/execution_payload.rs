@@ -0,0 +1,64 @@
+use std::fmt::Debug;
+
+use ethers::types::H256;
+use plonky2::hash::hash_types::RichField;
+use plonky2x_derive::CircuitVariable;
+
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::uint::uint64::U64Variable;
+use crate::frontend::vars::{ArrayVariable, Bytes32Variable, CircuitVariable};
+use crate::prelude::Variable;
+use crate::utils::eth::beacon::block::ExecutionPayloadBranches;
+
+/// The depth of the execution payload below the beacon block root: 3 levels for the header and 4
+/// for the block body.
+pub const EXECUTION_PAYLOAD_ROOT_DEPTH: usize = 7;
+
+/// The maximum depth of the fields below the execution payload root, over all forks.
+pub const MAX_EXECUTION_PAYLOAD_DEPTH: usize = 5;
+
+/// The fields of an execution payload that bridge a beacon block to its execution block.
+#[derive(Debug, Clone, Copy, CircuitVariable)]
+#[value_name(ExecutionPayloadFields)]
+pub struct ExecutionPayloadFieldsVariable {
+    pub block_hash: Bytes32Variable,
+    pub state_root: Bytes32Variable,
+    pub block_number: U64Variable,
+}
+
+/// The branches from a beacon block root to the fields of its execution payload.
+///
+/// The branches of the fields are as deep as the execution payload of the fork, and are padded
+/// with zeros to `MAX_EXECUTION_PAYLOAD_DEPTH`.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(ExecutionPayloadProof)]
+pub struct ExecutionPayloadProofVariable {
+    pub execution_payload_root: Bytes32Variable,
+    pub execution_payload_branch: ArrayVariable<Bytes32Variable, EXECUTION_PAYLOAD_ROOT_DEPTH>,
+    pub fields: ExecutionPayloadFieldsVariable,
+    pub block_hash_branch: ArrayVariable<Bytes32Variable, MAX_EXECUTION_PAYLOAD_DEPTH>,
+    pub state_root_branch: ArrayVariable<Bytes32Variable, MAX_EXECUTION_PAYLOAD_DEPTH>,
+    pub block_number_branch: ArrayVariable<Bytes32Variable, MAX_EXECUTION_PAYLOAD_DEPTH>,
+}
+
+impl<F: RichField> From<ExecutionPayloadBranches> for ExecutionPayloadProof<F> {
+    fn from(branches: ExecutionPayloadBranches) -> Self {
+        let pad = |mut branch: Vec<H256>| {
+            branch.resize(MAX_EXECUTION_PAYLOAD_DEPTH, H256::zero());
+            branch
+        };
+        Self {
+            execution_payload_root: branches.execution_payload_root,
+            execution_payload_branch: branches.execution_payload_branch,
+            fields: ExecutionPayloadFields {
+                block_hash: branches.block_hash,
+                state_root: branches.state_root,
+                block_number: branches.block_number,
+            },
+            block_hash_branch: pad(branches.block_hash_branch),
+            state_root_branch: pad(branches.state_root_branch),
+            block_number_branch: pad(branches.block_number_branch),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::fmt::Debug;
+
+use plonky2::hash::hash_types::RichField;
+use plonky2x_derive::CircuitVariable;
+
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::uint::uint64::U64Variable;
+use crate::frontend::vars::{ArrayVariable, Bytes32Variable, CircuitVariable};
+use crate::prelude::Variable;
+
+/// The depth of the historical summaries below the state root.
+pub const HISTORICAL_SUMMARIES_DEPTH: usize = 5;
+
+/// The depth of a summary below the data root of the historical summaries, the log2 of
+/// HISTORICAL_ROOTS_LIMIT.
+pub const HISTORICAL_SUMMARY_DEPTH: usize = 24;
+
+/// The depth of a block root below the block summary root, the log2 of
+/// SLOTS_PER_HISTORICAL_ROOT.
+pub const HISTORICAL_BLOCK_ROOT_DEPTH: usize = 13;
+
+/// The proof of a block root older than the `block_roots` of a state, through the
+/// `historical_summaries` of the state.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(HistoricalBlockRootProof)]
+pub struct HistoricalBlockRootProofVariable {
+    pub block_root: Bytes32Variable,
+    /// The branch from the block root to the block summary root of its summary.
+    pub block_root_branch: ArrayVariable<Bytes32Variable, HISTORICAL_BLOCK_ROOT_DEPTH>,
+    /// The sibling of the block summary root in the summary.
+    pub state_summary_root: Bytes32Variable,
+    /// The branch from the summary to the data root of the historical summaries.
+    pub historical_summary_branch: ArrayVariable<Bytes32Variable, HISTORICAL_SUMMARY_DEPTH>,
+    /// The length of the historical summaries, mixed in with their data root.
+    pub historical_summaries_len: U64Variable,
+    /// The branch from the historical summaries to the state root.
+    pub historical_summaries_branch: ArrayVariable<Bytes32Variable, HISTORICAL_SUMMARIES_DEPTH>,
+}
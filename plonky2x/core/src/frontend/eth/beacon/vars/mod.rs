@@ -1,6 +1,8 @@
 mod balances;
 mod compressed_validator;
+mod execution_payload;
 mod header;
+mod historical;
 mod validator;
 mod validators;
 mod withdrawal;
@@ -8,7 +10,9 @@ mod withdrawals;
 
 pub use balances::*;
 pub use compressed_validator::*;
+pub use execution_payload::*;
 pub use header::*;
+pub use historical::*;
 pub use validator::*;
 pub use validators::*;
 pub use withdrawal::*;
@@ -6,9 +6,9 @@ use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
 use crate::frontend::eth::vars::BLSPubkeyVariable;
 use crate::frontend::vars::{
-    BoolVariable, Bytes32Variable, CircuitVariable, EvmVariable, SSZVariable, U256Variable,
+    BoolVariable, Bytes32Variable, CircuitVariable, SSZVariable, U256Variable,
 };
-use crate::prelude::{ByteVariable, Variable};
+use crate::prelude::Variable;
 use crate::utils::eth::beacon::BeaconValidator;
 use crate::utils::{bytes, bytes32, hex};
 
@@ -170,44 +170,17 @@ impl SSZVariable for BeaconValidatorVariable {
         &self,
         builder: &mut CircuitBuilder<L, D>,
     ) -> Bytes32Variable {
-        let zero = builder.constant::<ByteVariable>(0);
-        let one = builder.constant::<ByteVariable>(1);
-
-        let mut pubkey_serialized = self.pubkey.0 .0.to_vec();
-        pubkey_serialized.extend([zero; 16]);
-
-        let tmp = builder.curta_sha256(&pubkey_serialized);
-        let mut a1 = tmp.0 .0.to_vec();
-        a1.extend(self.withdrawal_credentials.0 .0.to_vec());
-
-        let mut a2 = self.effective_balance.encode(builder);
-        a2.reverse();
-        let mut slashed = vec![builder.select(self.slashed, one, zero)];
-        slashed.extend([zero; 31]);
-        a2.extend(slashed);
-
-        let mut a3 = self.activation_eligibility_epoch.encode(builder);
-        a3.reverse();
-        let mut tmp = self.activation_epoch.encode(builder);
-        tmp.reverse();
-        a3.extend(&tmp);
-
-        let mut a4 = self.exit_epoch.encode(builder);
-        a4.reverse();
-        let mut tmp = self.withdrawable_epoch.encode(builder);
-        tmp.reverse();
-        a4.extend(&tmp);
-
-        let mut b1 = builder.curta_sha256(&a1).0 .0.to_vec();
-        b1.extend(builder.curta_sha256(&a2).0 .0.to_vec());
-
-        let mut b2 = builder.curta_sha256(&a3).0 .0.to_vec();
-        b2.extend(builder.curta_sha256(&a4).0 .0.to_vec());
-
-        let mut c1 = builder.curta_sha256(&b1).0 .0.to_vec();
-        c1.extend(builder.curta_sha256(&b2).0 .0.to_vec());
-
-        let leaf = builder.curta_sha256(&c1);
+        let field_roots = [
+            self.pubkey.hash_tree_root(builder),
+            self.withdrawal_credentials.hash_tree_root(builder),
+            self.effective_balance.hash_tree_root(builder),
+            self.slashed.hash_tree_root(builder),
+            self.activation_eligibility_epoch.hash_tree_root(builder),
+            self.activation_epoch.hash_tree_root(builder),
+            self.exit_epoch.hash_tree_root(builder),
+            self.withdrawable_epoch.hash_tree_root(builder),
+        ];
+        let leaf = builder.ssz_hash_container(&field_roots);
         let zero_leaf = builder.constant::<Bytes32Variable>(bytes32!(ZERO_BYTE32));
         let zero_validator_pubkey =
             builder.constant::<BLSPubkeyVariable>(bytes!(ZERO_VALIDATOR_PUBKEY));
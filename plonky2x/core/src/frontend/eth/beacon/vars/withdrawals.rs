@@ -3,9 +3,11 @@ use std::fmt::Debug;
 use plonky2::hash::hash_types::RichField;
 use plonky2x_derive::CircuitVariable;
 
+use super::MAX_EXECUTION_PAYLOAD_DEPTH;
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
-use crate::frontend::vars::{Bytes32Variable, CircuitVariable};
+use crate::frontend::uint::uint64::U64Variable;
+use crate::frontend::vars::{ArrayVariable, Bytes32Variable, CircuitVariable};
 use crate::prelude::Variable;
 
 #[derive(Debug, Clone, Copy, CircuitVariable)]
@@ -14,3 +16,12 @@ pub struct BeaconWithdrawalsVariable {
     pub block_root: Bytes32Variable,
     pub withdrawals_root: Bytes32Variable,
 }
+
+/// The length of the withdrawals of an execution payload, and the branch from their root to the
+/// execution payload root, padded with zeros to `MAX_EXECUTION_PAYLOAD_DEPTH`.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(BeaconWithdrawalsProof)]
+pub struct BeaconWithdrawalsProofVariable {
+    pub len: U64Variable,
+    pub branch: ArrayVariable<Bytes32Variable, MAX_EXECUTION_PAYLOAD_DEPTH>,
+}
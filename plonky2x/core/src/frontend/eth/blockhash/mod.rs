@@ -0,0 +1,295 @@
+//! An oracle for the hash of a block given its number.
+//!
+//! The hash is fetched by a hint and is not trusted on its own: the oracle also fetches the RLP
+//! encoded header, and constrains that the header hashes to the returned hash and encodes the
+//! requested block number. It does not prove that the block is part of the canonical chain, so
+//! callers must still anchor the hash (e.g. by linking parent hashes up to a trusted block hash).
+
+use ethers::types::H256;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2x_derive::CircuitVariable;
+use serde::{Deserialize, Serialize};
+use tokio::runtime::Runtime;
+
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::uint::uint64::U64Variable;
+use crate::prelude::{
+    ArrayVariable, ByteVariable, Bytes32Variable, CircuitBuilder, CircuitVariable, PlonkParameters,
+    U32Variable, ValueStream, Variable, VariableStream,
+};
+use crate::utils::eth::get_provider;
+
+/// The maximum length of an RLP encoded block header supported by the oracle.
+pub const MAX_HEADER_RLP_LEN: usize = 1024;
+
+// The offset of the difficulty in the RLP encoding of a header. The fields before it (the parent
+// hash, uncle hash, coinbase, state root, transactions root, receipts root and logs bloom) all
+// have a fixed length, and headers are always longer than 255 bytes.
+const DIFFICULTY_OFFSET: usize = 448;
+
+// The expected RLP prefixes of the fixed length fields, by offset.
+const FIXED_PREFIXES: [(usize, u8); 10] = [
+    (0, 0xf9),
+    (3, 0xa0),
+    (36, 0xa0),
+    (69, 0x94),
+    (90, 0xa0),
+    (123, 0xa0),
+    (156, 0xa0),
+    (189, 0xb9),
+    (190, 0x01),
+    (191, 0x00),
+];
+
+// The supported difficulties and block numbers are at most this many bytes long.
+const MAX_DIFFICULTY_LEN: usize = 8;
+const MAX_NUMBER_LEN: usize = 7;
+
+/// An RLP encoded block header, zero padded to `MAX_HEADER_RLP_LEN` bytes.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(EthHeaderRlp)]
+pub struct EthHeaderRlpVariable {
+    pub bytes: ArrayVariable<ByteVariable, MAX_HEADER_RLP_LEN>,
+    pub len: Variable,
+}
+
+/// Where `BlockHashHint` gets block headers from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum HeaderSource {
+    Rpc {
+        chain_id: u64,
+    },
+    #[cfg(test)]
+    Fixtures(Vec<HeaderFixture>),
+}
+
+#[cfg(test)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct HeaderFixture {
+    number: u64,
+    hash: H256,
+    header: ethers::types::Bytes,
+}
+
+/// Fetches the hash and the RLP encoded header of a block. Its outputs are unconstrained, so it
+/// is only used through `blockhash_oracle`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct BlockHashHint {
+    source: HeaderSource,
+}
+
+impl BlockHashHint {
+    fn fetch(&self, number: u64) -> (H256, Vec<u8>) {
+        match &self.source {
+            HeaderSource::Rpc { chain_id } => {
+                let provider = get_provider(*chain_id);
+                let rt = Runtime::new().expect("failed to create tokio runtime");
+                rt.block_on(async {
+                    let block = provider
+                        .get_block(number.into())
+                        .await
+                        .expect("Failed to get block from RPC")
+                        .expect("No matching block found");
+                    let header = provider
+                        .get_raw_header(number)
+                        .await
+                        .expect("Failed to get raw header from RPC");
+                    (block.hash.expect("No block hash"), header.to_vec())
+                })
+            }
+            #[cfg(test)]
+            HeaderSource::Fixtures(fixtures) => {
+                let fixture = fixtures
+                    .iter()
+                    .find(|fixture| fixture.number == number)
+                    .expect("No matching fixture found");
+                (fixture.hash, fixture.header.to_vec())
+            }
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for BlockHashHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let number = input_stream.read_value::<U64Variable>();
+        let (hash, header) = self.fetch(number);
+        assert!(
+            header.len() <= MAX_HEADER_RLP_LEN,
+            "header of block {} is {} bytes long",
+            number,
+            header.len()
+        );
+
+        let mut bytes = header.clone();
+        bytes.resize(MAX_HEADER_RLP_LEN, 0);
+        output_stream.write_value::<Bytes32Variable>(hash);
+        output_stream.write_value::<EthHeaderRlpVariable>(EthHeaderRlp {
+            bytes,
+            len: L::Field::from_canonical_usize(header.len()),
+        });
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns the hash of the block with the given number along with its RLP encoded header.
+    ///
+    /// The header is constrained to hash to the returned hash and to encode `block_number`, but
+    /// the hash itself comes from the execution client and must be anchored to a trusted block
+    /// hash by the caller.
+    pub fn blockhash_oracle(
+        &mut self,
+        block_number: U64Variable,
+    ) -> (Bytes32Variable, EthHeaderRlpVariable) {
+        let chain_id = self.get_chain_id();
+        self.blockhash_oracle_from_source(block_number, HeaderSource::Rpc { chain_id })
+    }
+
+    fn blockhash_oracle_from_source(
+        &mut self,
+        block_number: U64Variable,
+        source: HeaderSource,
+    ) -> (Bytes32Variable, EthHeaderRlpVariable) {
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&block_number);
+        let output_stream = self.hint(input_stream, BlockHashHint { source });
+        let hash = output_stream.read::<Bytes32Variable>(self);
+        let header = output_stream.read::<EthHeaderRlpVariable>(self);
+
+        let header_len = U32Variable::from_variables(self, &[header.len]);
+        let header_hash = self.keccak256_variable::<MAX_HEADER_RLP_LEN>(
+            header.bytes.data.as_slice().try_into().unwrap(),
+            header_len,
+        );
+        self.assert_is_equal(header_hash, hash);
+        self.assert_header_number(&header, block_number);
+
+        (hash, header)
+    }
+
+    /// Asserts that an RLP encoded header encodes the given block number.
+    ///
+    /// Supports headers whose difficulty fits in 8 bytes and whose number fits in 7 bytes.
+    pub fn assert_header_number(&mut self, header: &EthHeaderRlpVariable, number: U64Variable) {
+        let bytes = &header.bytes.data;
+        for (offset, prefix) in FIXED_PREFIXES {
+            let expected = self.constant::<ByteVariable>(prefix);
+            self.assert_is_equal(bytes[offset], expected);
+        }
+
+        // The difficulty is either a single byte below 0x80, or a 0x80 + len prefix followed by
+        // len bytes. The number starts right after it.
+        let difficulty_prefix = self.byte_to_u32(bytes[DIFFICULTY_OFFSET]);
+        let max_difficulty_prefix = self.constant::<U32Variable>(0x80 + MAX_DIFFICULTY_LEN as u32);
+        let is_valid_difficulty = self.lte(difficulty_prefix, max_difficulty_prefix);
+        let _true = self._true();
+        self.assert_is_equal(is_valid_difficulty, _true);
+
+        let string_prefix = self.constant::<U32Variable>(0x80);
+        let is_single_byte = self.lt(difficulty_prefix, string_prefix);
+        let zero = self.zero::<Variable>();
+        let difficulty_len = self.sub(difficulty_prefix.variable, string_prefix.variable);
+        let difficulty_len = self.select(is_single_byte, zero, difficulty_len);
+
+        let windows = (0..=MAX_DIFFICULTY_LEN)
+            .map(|i| {
+                let start = DIFFICULTY_OFFSET + 1 + i;
+                ArrayVariable::<ByteVariable, { MAX_NUMBER_LEN + 1 }>::new(
+                    bytes[start..start + MAX_NUMBER_LEN + 1].to_vec(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let window = self.select_array(&windows, difficulty_len);
+
+        // The number is encoded the same way as the difficulty.
+        let number_prefix = self.byte_to_u32(window[0]);
+        let max_number_prefix = self.constant::<U32Variable>(0x80 + MAX_NUMBER_LEN as u32);
+        let is_valid_number = self.lte(number_prefix, max_number_prefix);
+        self.assert_is_equal(is_valid_number, _true);
+
+        let is_single_byte = self.lt(number_prefix, string_prefix);
+        let number_len = self.sub(number_prefix, string_prefix);
+        let base = self.constant::<Variable>(L::Field::from_canonical_u64(256));
+        let mut value = zero;
+        for i in 0..MAX_NUMBER_LEN {
+            let index = self.constant::<U32Variable>(i as u32);
+            let is_in_range = self.lt(index, number_len);
+            let byte = window[i + 1].to_variable(self);
+            let shifted = self.mul(value, base);
+            let shifted = self.add(shifted, byte);
+            value = self.select(is_in_range, shifted, value);
+        }
+        let value = self.select(is_single_byte, number_prefix.variable, value);
+
+        let expected = number.to_variable(self);
+        self.assert_is_equal(value, expected);
+    }
+
+    fn byte_to_u32(&mut self, byte: ByteVariable) -> U32Variable {
+        let variable = byte.to_variable(self);
+        U32Variable::from_variables_unsafe(&[variable])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::prelude::DefaultBuilder;
+    use crate::utils::bytes32;
+
+    // The mainnet headers of blocks 0 and 1, whose numbers are encoded as the empty string and as
+    // a single byte.
+    fn read_fixtures() -> Vec<HeaderFixture> {
+        let fixtures = fs::read_to_string("./src/frontend/eth/blockhash/fixtures/headers.json")
+            .expect("failed to read fixtures");
+        serde_json::from_str(&fixtures).unwrap()
+    }
+
+    #[test]
+    fn test_blockhash_oracle() {
+        let mut builder = DefaultBuilder::new();
+        let block_number = builder.read::<U64Variable>();
+        let (hash, _) = builder
+            .blockhash_oracle_from_source(block_number, HeaderSource::Fixtures(read_fixtures()));
+        builder.write(hash);
+        let circuit = builder.build();
+
+        let expected = [
+            (
+                0,
+                bytes32!("0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"),
+            ),
+            (
+                1,
+                bytes32!("0x88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6"),
+            ),
+        ];
+        for (number, expected_hash) in expected {
+            let mut input = circuit.input();
+            input.write::<U64Variable>(number);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<Bytes32Variable>(), expected_hash);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_blockhash_oracle_wrong_hash() {
+        let mut fixtures = read_fixtures();
+        fixtures[0].hash = fixtures[1].hash;
+
+        let mut builder = DefaultBuilder::new();
+        let block_number = builder.read::<U64Variable>();
+        let (hash, _) =
+            builder.blockhash_oracle_from_source(block_number, HeaderSource::Fixtures(fixtures));
+        builder.write(hash);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<U64Variable>(0);
+        circuit.prove(&input);
+    }
+}
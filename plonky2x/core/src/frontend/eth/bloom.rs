@@ -0,0 +1,150 @@
+//! Membership checks in the logs bloom of a block header or a receipt.
+//!
+//! An item (the address or a topic of a log) sets three bits of the 2048 bit bloom: each one is
+//! given by 11 bits of the first six bytes of the keccak256 hash of the item, and the bit `idx` is
+//! the bit `idx % 8` of the byte `255 - idx / 8`.
+
+use crate::prelude::{
+    ArrayVariable, BoolVariable, ByteVariable, CircuitBuilder, CircuitVariable, PlonkParameters,
+};
+
+/// The size of a logs bloom in bytes.
+pub const BLOOM_BYTES: usize = 256;
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Selects `array[index]` with a select tree over the little-endian bits of the index. The
+    /// length of `array` must be `2^bits.len()`.
+    fn select_by_le_bits<V: CircuitVariable + Copy>(
+        &mut self,
+        array: &[V],
+        bits: &[BoolVariable],
+    ) -> V {
+        assert_eq!(array.len(), 1 << bits.len());
+        let mut layer = array.to_vec();
+        for bit in bits {
+            layer = layer
+                .chunks_exact(2)
+                .map(|pair| self.select(*bit, pair[1], pair[0]))
+                .collect();
+        }
+        layer[0]
+    }
+
+    /// Returns whether `bloom` may contain `item`, that is whether the three bits of `item` are
+    /// set. False positives are possible, but an item whose bits are not all set is never in the
+    /// logs the bloom was built from.
+    pub fn bloom_contains(
+        &mut self,
+        bloom: &ArrayVariable<ByteVariable, BLOOM_BYTES>,
+        item: &[ByteVariable],
+    ) -> BoolVariable {
+        let hash = self.keccak256(item);
+        let hash = hash.as_bytes();
+
+        // Reverse the bytes, so that the byte `255 - idx / 8` is at the position `idx / 8`.
+        let reversed = bloom.as_slice().iter().rev().copied().collect::<Vec<_>>();
+
+        let mut contains = self._true();
+        for i in 0..3 {
+            // idx = ((hash[2i] & 0x07) << 8) | hash[2i + 1], as 11 little-endian bits.
+            let mut idx_bits = hash[2 * i + 1].as_le_bits().to_vec();
+            idx_bits.extend_from_slice(&hash[2 * i].as_le_bits()[..3]);
+
+            let byte = self.select_by_le_bits(&reversed, &idx_bits[3..]);
+            let bit = self.select_by_le_bits(&byte.as_le_bits(), &idx_bits[..3]);
+            contains = self.and(contains, bit);
+        }
+        contains
+    }
+
+    /// Asserts that `bloom` may contain `item`.
+    pub fn assert_bloom_contains(
+        &mut self,
+        bloom: &ArrayVariable<ByteVariable, BLOOM_BYTES>,
+        item: &[ByteVariable],
+    ) {
+        let contains = self.bloom_contains(bloom, item);
+        let t = self._true();
+        self.assert_is_equal(contains, t);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{Bloom, BloomInput, H256};
+
+    use super::*;
+    use crate::frontend::eth::vars::AddressVariable;
+    use crate::prelude::{Bytes32Variable, DefaultBuilder};
+    use crate::utils::{address, bytes32};
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_bloom_contains() {
+        // The bloom of a block with a WETH and a USDC transfer.
+        let weth = address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let usdc = address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+        let transfer =
+            bytes32!("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+        let holder = H256::from(address!("0x55032650b14df07b85bF18A3a3eC8E0Af2e028d5"));
+        let mut bloom = Bloom::zero();
+        for item in [
+            weth.as_bytes(),
+            usdc.as_bytes(),
+            transfer.as_bytes(),
+            holder.as_bytes(),
+        ] {
+            bloom.accrue(BloomInput::Raw(item));
+        }
+
+        // An address whose bits are not all set.
+        let absent = address!("0xdAC17F958D2ee523a2206206994597C13D831ec7");
+        assert!(!bloom.contains_input(BloomInput::Raw(absent.as_bytes())));
+
+        let mut builder = DefaultBuilder::new();
+        let bloom_variable = builder.read::<ArrayVariable<ByteVariable, BLOOM_BYTES>>();
+        let address = builder.read::<AddressVariable>();
+        let topic = builder.read::<Bytes32Variable>();
+        let contains_address = builder.bloom_contains(&bloom_variable, &address.0 .0);
+        let contains_topic = builder.bloom_contains(&bloom_variable, &topic.as_bytes());
+        builder.write(contains_address);
+        builder.write(contains_topic);
+        let circuit = builder.mock_build();
+
+        for (address, topic, expected_address, expected_topic) in [
+            (weth, transfer, true, true),
+            (usdc, holder, true, true),
+            (absent, H256::repeat_byte(0x42), false, false),
+        ] {
+            let mut input = circuit.input();
+            input.write::<ArrayVariable<ByteVariable, BLOOM_BYTES>>(bloom.as_bytes().to_vec());
+            input.write::<AddressVariable>(address);
+            input.write::<Bytes32Variable>(topic);
+            let mut output = circuit.mock_prove(&input).output;
+            assert_eq!(output.read::<BoolVariable>(), expected_address);
+            assert_eq!(output.read::<BoolVariable>(), expected_topic);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_assert_bloom_contains_absent() {
+        let mut bloom = Bloom::zero();
+        bloom.accrue(BloomInput::Raw(
+            address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2").as_bytes(),
+        ));
+        let absent = address!("0xdAC17F958D2ee523a2206206994597C13D831ec7");
+
+        let mut builder = DefaultBuilder::new();
+        let bloom_variable = builder.read::<ArrayVariable<ByteVariable, BLOOM_BYTES>>();
+        let address = builder.read::<AddressVariable>();
+        builder.assert_bloom_contains(&bloom_variable, &address.0 .0);
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<ByteVariable, BLOOM_BYTES>>(bloom.as_bytes().to_vec());
+        input.write::<AddressVariable>(absent);
+        circuit.mock_prove(&input);
+    }
+}
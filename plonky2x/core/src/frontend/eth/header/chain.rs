@@ -0,0 +1,193 @@
+use super::BlockHeaderVariable;
+use crate::frontend::eth::blockhash::MAX_HEADER_RLP_LEN;
+use crate::frontend::vars::VariableBytesVariable;
+use crate::prelude::{
+    ArrayVariable, BoolVariable, ByteVariable, Bytes32Variable, CircuitBuilder, PlonkParameters,
+    U32Variable, Variable,
+};
+
+/// An RLP encoded block header of at most `MAX_HEADER_RLP_LEN` bytes.
+pub type BlockHeaderBytesVariable = VariableBytesVariable<MAX_HEADER_RLP_LEN>;
+
+/// The offset of the parent hash in a header, after the 3 byte list prefix and the 0xa0 prefix of
+/// the hash.
+const PARENT_HASH_OFFSET: usize = 4;
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns the parent hash of a header, and whether the header starts with the prefixes of a
+    /// long list and a hash.
+    fn header_parent_hash(
+        &mut self,
+        header: &BlockHeaderBytesVariable,
+    ) -> (Bytes32Variable, BoolVariable) {
+        let list_prefix = self.constant::<ByteVariable>(0xf9);
+        let hash_prefix = self.constant::<ByteVariable>(0xa0);
+        let has_list_prefix = self.is_equal(header.data[0], list_prefix);
+        let has_hash_prefix = self.is_equal(header.data[PARENT_HASH_OFFSET - 1], hash_prefix);
+        let is_valid = self.and(has_list_prefix, has_hash_prefix);
+        let parent_hash =
+            Bytes32Variable::from(&header.data[PARENT_HASH_OFFSET..PARENT_HASH_OFFSET + 32]);
+        (parent_hash, is_valid)
+    }
+
+    /// Verifies that the first `chain_len` headers form a chain starting from the block with hash
+    /// `start_hash`: the parent hash of each header is the hash of the previous one. Returns the
+    /// hash of the last header, which is `start_hash` for an empty chain.
+    ///
+    /// The headers past `chain_len`, which must be at most `N`, are ignored.
+    pub fn verify_header_chain<const N: usize>(
+        &mut self,
+        start_hash: Bytes32Variable,
+        headers: &ArrayVariable<BlockHeaderBytesVariable, N>,
+        chain_len: U32Variable,
+    ) -> Bytes32Variable {
+        let max_len = self.constant::<U32Variable>(N as u32);
+        let is_valid_len = self.lte(chain_len, max_len);
+        let t = self._true();
+        self.assert_is_equal(is_valid_len, t);
+
+        let mut hash = start_hash;
+        for i in 0..N {
+            let index = self.constant::<U32Variable>(i as u32);
+            let is_in_chain = self.lt(index, chain_len);
+            let is_not_in_chain = self.not(is_in_chain);
+
+            let (parent_hash, is_valid) = self.header_parent_hash(&headers[i]);
+            let is_child = self.is_equal(parent_hash, hash);
+            let is_valid = self.and(is_valid, is_child);
+            let is_valid = self.or(is_valid, is_not_in_chain);
+            self.assert_is_equal(is_valid, t);
+
+            let header_hash = self.keccak256_variable(&headers[i].data.0, headers[i].len);
+            hash = self.select(is_in_chain, header_hash, hash);
+        }
+        hash
+    }
+
+    /// Verifies a chain of headers like `verify_header_chain`, and decodes the last header of the
+    /// chain, which gives its hash, its state root and its number. The chain must not be empty, so
+    /// the hash returned by `verify_header_chain` is the hash of the last header.
+    pub fn verify_header_chain_last_header<const N: usize>(
+        &mut self,
+        start_hash: Bytes32Variable,
+        headers: &ArrayVariable<BlockHeaderBytesVariable, N>,
+        chain_len: U32Variable,
+    ) -> BlockHeaderVariable {
+        let hash = self.verify_header_chain(start_hash, headers, chain_len);
+
+        let zero = self.constant::<U32Variable>(0);
+        let is_empty = self.is_equal(chain_len, zero);
+        let f = self._false();
+        self.assert_is_equal(is_empty, f);
+        let one = self.one::<Variable>();
+        let last_index = self.sub(chain_len.variable, one);
+        let last = self.select_array(headers.as_slice(), last_index);
+
+        self.decode_block_header_with_hash(&last.data.0, last.len, hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use ethers::types::{Bytes, H256};
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::backend::circuit::{DefaultParameters, MockCircuitBuild};
+    use crate::frontend::eth::header::BlockHeader;
+    use crate::prelude::{DefaultBuilder, GoldilocksField};
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    const N: usize = 5;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ChainHeaderFixture {
+        number: u64,
+        hash: H256,
+        state_root: H256,
+        header: Bytes,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ChainFixture {
+        start_hash: H256,
+        headers: Vec<ChainHeaderFixture>,
+    }
+
+    /// Four consecutive headers with the mainnet layout of Cancun blocks and synthetic values,
+    /// each one the child of the previous one.
+    fn read_chain_fixture() -> ChainFixture {
+        let fixture = fs::read_to_string("./src/frontend/eth/header/fixtures/chain.json")
+            .expect("failed to read fixtures");
+        serde_json::from_str(&fixture).unwrap()
+    }
+
+    fn build_circuit() -> MockCircuitBuild<L, D> {
+        let mut builder = DefaultBuilder::new();
+        let start_hash = builder.read::<Bytes32Variable>();
+        let headers = builder.read::<ArrayVariable<BlockHeaderBytesVariable, N>>();
+        let chain_len = builder.read::<U32Variable>();
+        let header = builder.verify_header_chain_last_header(start_hash, &headers, chain_len);
+        builder.write(header);
+        builder.mock_build()
+    }
+
+    fn prove_chain(
+        circuit: &MockCircuitBuild<L, D>,
+        start_hash: H256,
+        headers: &[Vec<u8>],
+    ) -> BlockHeader<GoldilocksField> {
+        // The trailing slots are filled with garbage, which must be ignored.
+        let mut padded = headers.to_vec();
+        padded.resize(N, vec![0xff; 100]);
+        let mut input = circuit.input();
+        input.write::<Bytes32Variable>(start_hash);
+        input.write::<ArrayVariable<BlockHeaderBytesVariable, N>>(padded);
+        input.write::<U32Variable>(headers.len() as u32);
+        let mut output = circuit.mock_prove(&input).output;
+        output.read::<BlockHeaderVariable>()
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_header_chain() {
+        let fixture = read_chain_fixture();
+        let headers = fixture
+            .headers
+            .iter()
+            .map(|header| header.header.to_vec())
+            .collect::<Vec<_>>();
+        let circuit = build_circuit();
+
+        for chain_len in [2, 4] {
+            let header = prove_chain(&circuit, fixture.start_hash, &headers[..chain_len]);
+            let expected = &fixture.headers[chain_len - 1];
+            assert_eq!(header.hash, expected.hash);
+            assert_eq!(header.state_root, expected.state_root);
+            assert_eq!(header.number, expected.number);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_header_chain_tampered_header() {
+        let fixture = read_chain_fixture();
+        let mut headers = fixture
+            .headers
+            .iter()
+            .map(|header| header.header.to_vec())
+            .collect::<Vec<_>>();
+        // Change the last byte of the parent beacon block root of the second header, so that its
+        // hash no longer matches the parent hash of the third one.
+        *headers[1].last_mut().unwrap() ^= 1;
+        let circuit = build_circuit();
+        prove_chain(&circuit, fixture.start_hash, &headers);
+    }
+}
@@ -0,0 +1,170 @@
+//! Decoding of RLP encoded execution block headers.
+//!
+//! The header is decoded with `decode_rlp_list`, so it may have any number of fields up to
+//! `MAX_HEADER_ITEMS`. This covers the fields added by London (baseFeePerGas), Shanghai
+//! (withdrawalsRoot), Cancun (blobGasUsed, excessBlobGas and parentBeaconBlockRoot) and Prague
+//! (requestsHash).
+
+pub mod chain;
+
+use plonky2::hash::hash_types::RichField;
+
+use crate::frontend::uint::uint64::U64Variable;
+use crate::frontend::vars::VariableBytesVariable;
+use crate::prelude::{
+    ByteVariable, Bytes32Variable, CircuitBuilder, CircuitVariable, PlonkParameters, U32Variable,
+};
+
+/// The maximum number of fields of a header.
+const MAX_HEADER_ITEMS: usize = 21;
+
+/// The indices of the decoded fields in the header.
+const PARENT_HASH_INDEX: usize = 0;
+const STATE_ROOT_INDEX: usize = 3;
+const TRANSACTIONS_ROOT_INDEX: usize = 4;
+const RECEIPTS_ROOT_INDEX: usize = 5;
+const NUMBER_INDEX: usize = 8;
+const TIMESTAMP_INDEX: usize = 11;
+
+/// The offsets of the payloads of the hashes in the header. The fields before the logs bloom all
+/// have a fixed length, and a header is always longer than 255 bytes, so its list prefix is 3
+/// bytes long.
+const PARENT_HASH_OFFSET: usize = 4;
+const STATE_ROOT_OFFSET: usize = 91;
+const TRANSACTIONS_ROOT_OFFSET: usize = 124;
+const RECEIPTS_ROOT_OFFSET: usize = 157;
+
+/// The hash and some of the fields of an execution block header.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(BlockHeader)]
+#[value_derive(PartialEq, Eq)]
+pub struct BlockHeaderVariable {
+    pub hash: Bytes32Variable,
+    pub parent_hash: Bytes32Variable,
+    pub state_root: Bytes32Variable,
+    pub transactions_root: Bytes32Variable,
+    pub receipts_root: Bytes32Variable,
+    pub number: U64Variable,
+    pub timestamp: U64Variable,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Decodes the RLP encoded block header in the first `len` bytes of `header_bytes`, and
+    /// computes its hash.
+    ///
+    /// The number and the timestamp must fit in 8 bytes, which all mainnet headers satisfy.
+    pub fn decode_block_header<const MAX: usize>(
+        &mut self,
+        header_bytes: &[ByteVariable; MAX],
+        len: U32Variable,
+    ) -> BlockHeaderVariable {
+        let hash = self.keccak256_variable(header_bytes, len);
+        self.decode_block_header_with_hash(header_bytes, len, hash)
+    }
+
+    /// Decodes a block header like `decode_block_header`, but takes its `hash` instead of hashing
+    /// it, so `hash` must already be constrained to be the hash of the first `len` bytes of
+    /// `header_bytes`.
+    pub(crate) fn decode_block_header_with_hash<const MAX: usize>(
+        &mut self,
+        header_bytes: &[ByteVariable; MAX],
+        len: U32Variable,
+        hash: Bytes32Variable,
+    ) -> BlockHeaderVariable {
+        let list = self.decode_rlp_list::<MAX, MAX_HEADER_ITEMS>(header_bytes, len);
+
+        let const_32 = self.constant::<U32Variable>(32);
+        let mut read_hash = |index: usize, offset: usize| {
+            let expected_offset = self.constant::<U32Variable>(offset as u32);
+            self.assert_is_equal(list.offsets[index], expected_offset);
+            self.assert_is_equal(list.lens[index], const_32);
+            Bytes32Variable::from(&header_bytes[offset..offset + 32])
+        };
+        let parent_hash = read_hash(PARENT_HASH_INDEX, PARENT_HASH_OFFSET);
+        let state_root = read_hash(STATE_ROOT_INDEX, STATE_ROOT_OFFSET);
+        let transactions_root = read_hash(TRANSACTIONS_ROOT_INDEX, TRANSACTIONS_ROOT_OFFSET);
+        let receipts_root = read_hash(RECEIPTS_ROOT_INDEX, RECEIPTS_ROOT_OFFSET);
+
+        let number_index = self.constant::<U32Variable>(NUMBER_INDEX as u32);
+        let number: VariableBytesVariable<8> =
+            list.get_item_bytes(self, header_bytes, number_index);
+        let number = self.rlp_string_to_uint::<U64Variable>(&number.data.0, number.len.variable);
+        let timestamp_index = self.constant::<U32Variable>(TIMESTAMP_INDEX as u32);
+        let timestamp: VariableBytesVariable<8> =
+            list.get_item_bytes(self, header_bytes, timestamp_index);
+        let timestamp =
+            self.rlp_string_to_uint::<U64Variable>(&timestamp.data.0, timestamp.len.variable);
+
+        BlockHeaderVariable {
+            hash,
+            parent_hash,
+            state_root,
+            transactions_root,
+            receipts_root,
+            number,
+            timestamp,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use ethers::types::{Bytes, H256};
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::prelude::DefaultBuilder;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct HeaderFixture {
+        fork: String,
+        number: u64,
+        timestamp: u64,
+        hash: H256,
+        header: Bytes,
+        parent_hash: H256,
+        state_root: H256,
+        transactions_root: H256,
+        receipts_root: H256,
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_decode_block_header() {
+        const MAX: usize = 640;
+
+        // Headers with the mainnet layout of a London block with a 7 byte difficulty, a Shanghai
+        // block and a Cancun block, with synthetic values in the other fields.
+        let fixtures = fs::read_to_string("./src/frontend/eth/header/fixtures/headers.json")
+            .expect("failed to read fixtures");
+        let fixtures: Vec<HeaderFixture> = serde_json::from_str(&fixtures).unwrap();
+
+        let mut builder = DefaultBuilder::new();
+        let header_bytes = builder.read::<[ByteVariable; MAX]>();
+        let len = builder.read::<U32Variable>();
+        let header = builder.decode_block_header(&header_bytes, len);
+        builder.write(header);
+        let circuit = builder.mock_build();
+
+        for fixture in fixtures {
+            let mut padded = fixture.header.to_vec();
+            padded.resize(MAX, 0);
+            let mut input = circuit.input();
+            input.write::<[ByteVariable; MAX]>(padded.try_into().unwrap());
+            input.write::<U32Variable>(fixture.header.len() as u32);
+            let mut output = circuit.mock_prove(&input).output;
+            let header = output.read::<BlockHeaderVariable>();
+
+            assert_eq!(header.hash, fixture.hash, "{} header", fixture.fork);
+            assert_eq!(header.parent_hash, fixture.parent_hash);
+            assert_eq!(header.state_root, fixture.state_root);
+            assert_eq!(header.transactions_root, fixture.transactions_root);
+            assert_eq!(header.receipts_root, fixture.receipts_root);
+            assert_eq!(header.number, fixture.number);
+            assert_eq!(header.timestamp, fixture.timestamp);
+        }
+    }
+}
@@ -1,4 +1,8 @@
+pub mod abi;
 pub mod beacon;
+pub mod blockhash;
+pub mod bloom;
+pub mod header;
 pub mod mpt;
 pub mod rlp;
 pub mod storage;
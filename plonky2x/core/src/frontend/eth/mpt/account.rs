@@ -0,0 +1,206 @@
+use ethers::types::U256;
+use itertools::Itertools;
+use starkyx::math::field::Field;
+
+use crate::frontend::eth::storage::vars::EthAccountVariable;
+use crate::frontend::eth::vars::AddressVariable;
+use crate::frontend::vars::{EvmVariable, Nibbles};
+use crate::prelude::{
+    ArrayVariable, BoolVariable, ByteVariable, Bytes32Variable, CircuitBuilder, PlonkParameters,
+    U256Variable, U32Variable, Variable,
+};
+use crate::utils::bytes32;
+
+/// The maximum size of the RLP encoding of an account: a 2-byte list prefix, a nonce of up to 8
+/// bytes, a balance of up to 32 bytes and the two hashes, each item with a 1-byte prefix.
+const MAX_ACCOUNT_ENCODING_LEN: usize = 110;
+
+/// The number of items in the RLP encoding of an account.
+const ACCOUNT_LIST_LEN: usize = 4;
+
+/// The root of an empty trie, which is the storage root of an account without storage.
+const EMPTY_TRIE_ROOT: &str = "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421";
+
+/// The hash of empty code, which is the code hash of an account without code.
+const EMPTY_CODE_HASH: &str = "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Converts a big-endian byte string of `len` bytes, padded on the right with zeros, to an
+    /// unsigned integer variable whose encoding has as many bytes as `bytes`.
+    pub(crate) fn rlp_string_to_uint<V: EvmVariable>(
+        &mut self,
+        bytes: &[ByteVariable],
+        len: Variable,
+    ) -> V {
+        // Shift the bytes right by `bytes.len() - len`, so that the padding is on the left instead.
+        let zero = self.zero::<ByteVariable>();
+        let mut shifted = vec![zero; bytes.len()];
+        shifted.extend_from_slice(bytes);
+        let aligned = (0..bytes.len())
+            .map(|i| {
+                let i = self.constant::<Variable>(L::Field::from_canonical_usize(i));
+                let index = self.add(i, len);
+                self.select_array(&shifted, index)
+            })
+            .collect_vec();
+        V::decode(self, &aligned)
+    }
+
+    /// Verifies an EIP-1186 account proof of `address` against `state_root`, walking the state
+    /// trie with `keccak256(address)` as the path.
+    ///
+    /// Returns the account and whether it exists. For a proof of exclusion, the account has the
+    /// fields of an empty account: a zero nonce and balance, the empty trie root and the hash of
+    /// empty code.
+    pub fn verify_account_proof<const ENCODING_LEN: usize, const PROOF_LEN: usize>(
+        &mut self,
+        state_root: Bytes32Variable,
+        address: AddressVariable,
+        proof: ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>,
+        len_nodes: ArrayVariable<U32Variable, PROOF_LEN>,
+    ) -> (EthAccountVariable, BoolVariable) {
+        let key = self.keccak256(&address.0 .0);
+        let key_path = key.as_bytes().to_vec().to_nibbles(self).into();
+        let key_len = self.constant::<Variable>(L::Field::from_canonical_u8(64));
+        let (encoding, encoding_len, exists) = self
+            .walk_mpt_proof::<ENCODING_LEN, PROOF_LEN, MAX_ACCOUNT_ENCODING_LEN>(
+                key_path, key_len, proof, len_nodes, state_root,
+            );
+        let not_exists = self.not(exists);
+        let (fields, field_lens, nb_fields) = self
+            .decode_element_as_list::<MAX_ACCOUNT_ENCODING_LEN, ACCOUNT_LIST_LEN, 32>(
+                encoding.clone(),
+                encoding_len,
+                not_exists,
+            );
+
+        // The fields are decoded by a hint, so they are checked against a constrained decoding of
+        // the encoding, which the walk tied to the leaf. The walk of a proof of exclusion ends at
+        // an arbitrary item, which is not checked.
+        // Can be unsafe because the length of the walked value is the length of a decoded item.
+        let encoding_len = U32Variable::from_variables_unsafe(&[encoding_len]);
+        self.assert_decoded_mpt_node::<MAX_ACCOUNT_ENCODING_LEN, ACCOUNT_LIST_LEN, 32>(
+            &encoding,
+            encoding_len,
+            not_exists,
+            &fields,
+            &field_lens,
+            nb_fields,
+        );
+
+        // The hashes are read as all 32 bytes of their field, so they must fill it.
+        let account_list_len =
+            self.constant::<Variable>(L::Field::from_canonical_usize(ACCOUNT_LIST_LEN));
+        let hash_len = self.constant::<Variable>(L::Field::from_canonical_u8(32));
+        let is_account = self.is_equal(nb_fields, account_list_len);
+        let storage_hash_is_full = self.is_equal(field_lens[2], hash_len);
+        let code_hash_is_full = self.is_equal(field_lens[3], hash_len);
+        let is_account = self.and(is_account, storage_hash_is_full);
+        let is_account = self.and(is_account, code_hash_is_full);
+        let is_valid = self.or(is_account, not_exists);
+        let t = self._true();
+        self.assert_is_equal(is_valid, t);
+
+        let account = EthAccountVariable {
+            nonce: self.rlp_string_to_uint(fields[0].as_slice(), field_lens[0]),
+            balance: self.rlp_string_to_uint(fields[1].as_slice(), field_lens[1]),
+            storage_hash: fields[2].as_slice().into(),
+            code_hash: fields[3].as_slice().into(),
+        };
+        let empty_account = EthAccountVariable {
+            nonce: self.constant::<U256Variable>(U256::zero()),
+            balance: self.constant::<U256Variable>(U256::zero()),
+            storage_hash: self.constant::<Bytes32Variable>(bytes32!(EMPTY_TRIE_ROOT)),
+            code_hash: self.constant::<Bytes32Variable>(bytes32!(EMPTY_CODE_HASH)),
+        };
+        let account = self.select(exists, account, empty_account);
+
+        (account, exists)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H160;
+
+    use super::super::builder::transform_proof_to_padded;
+    use super::super::utils::{read_fixture, EIP1186ProofResponse};
+    use super::*;
+    use crate::prelude::DefaultBuilder;
+    use crate::utils::address;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_account_proof() {
+        let storage_result: EIP1186ProofResponse =
+            read_fixture("./src/frontend/eth/mpt/fixtures/example.json");
+        let state_root =
+            bytes32!("0xff90251f501c864f21d696c811af4c3aa987006916bd0e31a6c06cc612e7632e");
+        let account_proof = storage_result
+            .account_proof
+            .iter()
+            .map(|b| b.to_vec())
+            .collect::<Vec<Vec<u8>>>();
+
+        // The keccak of this address shares its first 6 nibbles with the one of the fixture, and
+        // its 7th nibble points to an empty child of the 7th node, so the first 7 nodes of the
+        // fixture are a proof that the account does not exist.
+        let missing_address = address!("0x000000000000000000000000000000000a5537ef");
+        let exclusion_proof = account_proof[..7].to_vec();
+
+        const ENCODING_LEN: usize = 600;
+        const PROOF_LEN: usize = 16;
+
+        let mut builder = DefaultBuilder::new();
+        let state_root_variable = builder.read::<Bytes32Variable>();
+        let address_variable = builder.read::<AddressVariable>();
+        let proof_variable =
+            builder.read::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>();
+        let len_nodes = builder.read::<ArrayVariable<U32Variable, PROOF_LEN>>();
+        let (account, exists) = builder.verify_account_proof::<ENCODING_LEN, PROOF_LEN>(
+            state_root_variable,
+            address_variable,
+            proof_variable,
+            len_nodes,
+        );
+        builder.write(account);
+        builder.write(exists);
+        let circuit = builder.mock_build();
+
+        let cases: [(H160, Vec<Vec<u8>>); 2] = [
+            (storage_result.address, account_proof),
+            (missing_address, exclusion_proof),
+        ];
+        for (address, proof) in cases {
+            let (proof_as_fixed, lengths_as_fixed) =
+                transform_proof_to_padded::<ENCODING_LEN, PROOF_LEN>(proof);
+
+            let mut input = circuit.input();
+            input.write::<Bytes32Variable>(state_root);
+            input.write::<AddressVariable>(address);
+            input.write::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>(
+                proof_as_fixed,
+            );
+            input.write::<ArrayVariable<U32Variable, PROOF_LEN>>(
+                lengths_as_fixed.iter().map(|x| *x as u32).collect(),
+            );
+
+            let mut output = circuit.mock_prove(&input).output;
+            let account = output.read::<EthAccountVariable>();
+            let exists = output.read::<BoolVariable>();
+            if address == storage_result.address {
+                assert!(exists);
+                assert_eq!(account.nonce, U256::from(storage_result.nonce.as_u64()));
+                assert_eq!(account.balance, storage_result.balance);
+                assert_eq!(account.storage_hash, storage_result.storage_hash);
+                assert_eq!(account.code_hash, storage_result.code_hash);
+            } else {
+                assert!(!exists);
+                assert_eq!(account.nonce, U256::zero());
+                assert_eq!(account.balance, U256::zero());
+                assert_eq!(account.storage_hash, bytes32!(EMPTY_TRIE_ROOT));
+                assert_eq!(account.code_hash, bytes32!(EMPTY_CODE_HASH));
+            }
+        }
+    }
+}
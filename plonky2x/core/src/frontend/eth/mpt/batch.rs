@@ -0,0 +1,574 @@
+//! Verification of several storage proofs of the same contract at once.
+//!
+//! Proofs against the same storage trie share their top nodes, so they are given as one set of
+//! distinct nodes along with, for each key, the indices of the nodes on its path. Each distinct
+//! node is hashed and decoded exactly once, and its items are packed into `U256Variable`s so that
+//! a step of the walk of a key only needs random access lookups into the node set.
+
+use async_trait::async_trait;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use starkyx::math::field::Field;
+
+use super::builder::transform_proof_to_padded;
+use crate::frontend::eth::vars::AddressVariable;
+use crate::frontend::hint::asynchronous::hint::AsyncHint;
+use crate::frontend::uint::uint256::U256Variable;
+use crate::frontend::vars::{EvmVariable, Nibbles};
+use crate::prelude::{
+    ArrayVariable, ByteVariable, Bytes32Variable, CircuitBuilder, PlonkParameters, U32Variable,
+    ValueStream, Variable, VariableStream,
+};
+use crate::utils::eth::cache::RpcCacheMode;
+use crate::utils::eth::get_provider_with_cache;
+
+/// The maximum number of distinct nodes, which is the largest table a random access gate can
+/// look up into.
+const MAX_NB_NODES: usize = 64;
+
+/// Deduplicates the nodes of proofs against the same trie.
+///
+/// Returns the distinct nodes, padded to `ENCODING_LEN` bytes, and their lengths, both padded to
+/// `NB_NODES` entries. For each proof, also returns the indices of its nodes in the distinct
+/// nodes, padded to `PROOF_LEN` entries, and its number of nodes.
+pub fn dedup_storage_proofs<
+    const ENCODING_LEN: usize,
+    const NB_NODES: usize,
+    const PROOF_LEN: usize,
+>(
+    proofs: &[Vec<Vec<u8>>],
+) -> (Vec<Vec<u8>>, Vec<usize>, Vec<Vec<usize>>, Vec<usize>) {
+    let mut nodes: Vec<Vec<u8>> = Vec::new();
+    let mut paths = Vec::with_capacity(proofs.len());
+    let mut path_lens = Vec::with_capacity(proofs.len());
+    for proof in proofs {
+        if proof.len() > PROOF_LEN {
+            panic!(
+                "Proof is too long, has {} elements, but PROOF_LEN is {}",
+                proof.len(),
+                PROOF_LEN
+            );
+        }
+        let mut path = vec![0usize; PROOF_LEN];
+        for (i, node) in proof.iter().enumerate() {
+            path[i] = match nodes.iter().position(|other| other == node) {
+                Some(index) => index,
+                None => {
+                    nodes.push(node.clone());
+                    nodes.len() - 1
+                }
+            };
+        }
+        paths.push(path);
+        path_lens.push(proof.len());
+    }
+
+    if nodes.len() > NB_NODES {
+        panic!(
+            "Proofs have {} distinct nodes, but NB_NODES is {}",
+            nodes.len(),
+            NB_NODES
+        );
+    }
+    let (nodes, node_lens) = transform_proof_to_padded::<ENCODING_LEN, NB_NODES>(nodes);
+    (nodes, node_lens, paths, path_lens)
+}
+
+/// A hint that fetches the storage proofs of `K` slots of a contract with a single
+/// `eth_getProof` call, and deduplicates their nodes with `dedup_storage_proofs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EthStorageProofsBatchedHint<
+    const ENCODING_LEN: usize,
+    const NB_NODES: usize,
+    const PROOF_LEN: usize,
+    const K: usize,
+> {
+    chain_id: u64,
+    rpc_cache_mode: Option<RpcCacheMode>,
+}
+
+impl<const ENCODING_LEN: usize, const NB_NODES: usize, const PROOF_LEN: usize, const K: usize>
+    EthStorageProofsBatchedHint<ENCODING_LEN, NB_NODES, PROOF_LEN, K>
+{
+    pub fn new<L: PlonkParameters<D>, const D: usize>(builder: &CircuitBuilder<L, D>) -> Self {
+        Self {
+            chain_id: builder.get_chain_id(),
+            rpc_cache_mode: builder.rpc_cache_mode.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl<
+        L: PlonkParameters<D>,
+        const D: usize,
+        const ENCODING_LEN: usize,
+        const NB_NODES: usize,
+        const PROOF_LEN: usize,
+        const K: usize,
+    > AsyncHint<L, D> for EthStorageProofsBatchedHint<ENCODING_LEN, NB_NODES, PROOF_LEN, K>
+{
+    async fn hint(
+        &self,
+        input_stream: &mut ValueStream<L, D>,
+        output_stream: &mut ValueStream<L, D>,
+    ) {
+        let block_hash = input_stream.read_value::<Bytes32Variable>();
+        let address = input_stream.read_value::<AddressVariable>();
+        let keys = input_stream.read_value::<ArrayVariable<Bytes32Variable, K>>();
+
+        let cache_mode = self
+            .rpc_cache_mode
+            .clone()
+            .unwrap_or_else(RpcCacheMode::from_env);
+        let provider = get_provider_with_cache(self.chain_id, cache_mode);
+        let result = provider
+            .get_proof(address, keys, Some(block_hash.into()))
+            .await
+            .expect("Failed to get proof");
+        let proofs = result
+            .storage_proof
+            .iter()
+            .map(|proof| proof.proof.iter().map(|node| node.to_vec()).collect_vec())
+            .collect_vec();
+        let (nodes, node_lens, paths, path_lens) =
+            dedup_storage_proofs::<ENCODING_LEN, NB_NODES, PROOF_LEN>(&proofs);
+
+        output_stream.write_value::<Bytes32Variable>(result.storage_hash);
+        output_stream
+            .write_value::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, NB_NODES>>(
+                nodes,
+            );
+        output_stream.write_value::<ArrayVariable<U32Variable, NB_NODES>>(
+            node_lens.into_iter().map(|len| len as u32).collect(),
+        );
+        output_stream.write_value::<ArrayVariable<ArrayVariable<Variable, PROOF_LEN>, K>>(
+            paths
+                .into_iter()
+                .map(|path| {
+                    path.into_iter()
+                        .map(L::Field::from_canonical_usize)
+                        .collect()
+                })
+                .collect(),
+        );
+        output_stream.write_value::<ArrayVariable<U32Variable, K>>(
+            path_lens.into_iter().map(|len| len as u32).collect(),
+        );
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Verifies the storage proofs of `K` keys against `storage_root` and returns their values.
+    ///
+    /// The nodes of the proofs are given once in `shared_nodes`, with their lengths in
+    /// `len_nodes`, and the path of the `k`-th key is given by the indices of its nodes in the
+    /// first `per_key_path_lens[k]` entries of `per_key_paths[k]`. There may be at most 64 distinct
+    /// nodes, and unused slots must have a length of zero.
+    ///
+    /// Like `verify_mpt_proof`, every key must be in the trie, the values must fit in 31 bytes and
+    /// nodes embedded in their parent are not supported.
+    pub fn verify_storage_proofs_batched<
+        const ENCODING_LEN: usize,
+        const NB_NODES: usize,
+        const PROOF_LEN: usize,
+        const K: usize,
+    >(
+        &mut self,
+        storage_root: Bytes32Variable,
+        keys: ArrayVariable<Bytes32Variable, K>,
+        shared_nodes: ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, NB_NODES>,
+        len_nodes: ArrayVariable<U32Variable, NB_NODES>,
+        per_key_paths: ArrayVariable<ArrayVariable<Variable, PROOF_LEN>, K>,
+        per_key_path_lens: ArrayVariable<U32Variable, K>,
+    ) -> ArrayVariable<Bytes32Variable, K> {
+        const LIST_LEN: usize = 17;
+        const ELEMENT_LEN: usize = 32;
+        assert!(
+            NB_NODES <= MAX_NB_NODES,
+            "NB_NODES is {}, but at most {} nodes are supported",
+            NB_NODES,
+            MAX_NB_NODES
+        );
+
+        // Hash and decode every node once, and lay out each of its fields in a table indexed by
+        // the nodes.
+        let mut node_hashes = Vec::with_capacity(NB_NODES);
+        let mut node_list_lens = Vec::with_capacity(NB_NODES);
+        let mut node_path_lens = Vec::with_capacity(NB_NODES);
+        let mut node_items = vec![Vec::with_capacity(NB_NODES); LIST_LEN];
+        let mut node_item_lens = vec![Vec::with_capacity(NB_NODES); LIST_LEN];
+        for j in 0..NB_NODES {
+            let is_padding = self.is_zero(len_nodes[j].variable);
+            let hash = self.keccak256_variable::<ENCODING_LEN>(
+                shared_nodes[j].as_slice().try_into().unwrap(),
+                len_nodes[j],
+            );
+            node_hashes.push(U256Variable::decode(self, &hash.as_bytes()));
+
+            let (decoded_list, decoded_element_lens, len_decoded_list) = self
+                .decode_element_as_list::<ENCODING_LEN, LIST_LEN, ELEMENT_LEN>(
+                    shared_nodes[j].clone(),
+                    len_nodes[j].variable,
+                    is_padding,
+                );
+            // The items are decoded by a hint, so they are checked against a constrained decoding
+            // of the node.
+            self.assert_decoded_mpt_node::<ENCODING_LEN, LIST_LEN, ELEMENT_LEN>(
+                &shared_nodes[j],
+                len_nodes[j],
+                is_padding,
+                &decoded_list,
+                &decoded_element_lens,
+                len_decoded_list,
+            );
+            for (table, item) in node_items.iter_mut().zip(decoded_list.as_slice()) {
+                table.push(U256Variable::decode(self, item.as_slice()));
+            }
+            for (table, item_len) in node_item_lens
+                .iter_mut()
+                .zip(decoded_element_lens.as_slice())
+            {
+                table.push(*item_len);
+            }
+            node_path_lens.push(decoded_element_lens[0]);
+            node_list_lens.push(len_decoded_list);
+        }
+
+        let branch_node_length = self.constant::<Variable>(L::Field::from_canonical_u8(17));
+        let leaf_or_extension_node_length =
+            self.constant::<Variable>(L::Field::from_canonical_u8(2));
+        let prefix_leaf_even = self.constant::<ByteVariable>(Self::PREFIX_LEAF_EVEN);
+        let prefix_leaf_odd = self.constant::<ByteVariable>(Self::PREFIX_LEAF_ODD);
+        let prefix_extension_even = self.constant::<ByteVariable>(Self::PREFIX_EXTENSION_EVEN);
+        let one = self.one::<Variable>();
+        let two = self.constant::<Variable>(L::Field::from_canonical_u8(2));
+        let const_32 = self.constant::<Variable>(L::Field::from_canonical_u8(32));
+        let const_64 = self.constant::<Variable>(L::Field::from_canonical_u8(64));
+        let zero_u32 = self.constant::<U32Variable>(0);
+        let max_path_len = self.constant::<U32Variable>(PROOF_LEN as u32);
+        let root = U256Variable::decode(self, &storage_root.as_bytes());
+        let t = self._true();
+        let f = self._false();
+
+        let mut values = Vec::with_capacity(K);
+        for k in 0..K {
+            let path_len = per_key_path_lens[k];
+            let is_empty = self.is_equal(path_len, zero_u32);
+            self.assert_is_equal(is_empty, f);
+            let fits = self.lte(path_len, max_path_len);
+            self.assert_is_equal(fits, t);
+
+            let hash_key = self.keccak256(&keys[k].as_bytes());
+            let key_path = hash_key.as_bytes().to_vec().to_nibbles(self);
+
+            let mut current_key_idx = self.zero::<Variable>();
+            let mut expected_hash = root;
+            let mut value_item = self.zero::<U256Variable>();
+            for i in 0..PROOF_LEN {
+                let index = self.constant::<U32Variable>(i as u32);
+                let next_index = self.constant::<U32Variable>(i as u32 + 1);
+                let is_active = self.lt(index, path_len);
+                let is_inactive = self.not(is_active);
+                let is_last = self.is_equal(next_index, path_len);
+
+                let node_index = per_key_paths[k][i];
+                let node_hash = self.select_array_random_gate(&node_hashes, node_index);
+                let list_len = self.select_array_random_gate(&node_list_lens, node_index);
+                let path_len_bytes = self.select_array_random_gate(&node_path_lens, node_index);
+                let items = node_items
+                    .iter()
+                    .map(|table| self.select_array_random_gate(table, node_index))
+                    .collect_vec();
+                let item_lens = node_item_lens
+                    .iter()
+                    .map(|table| self.select_array_random_gate(table, node_index))
+                    .collect_vec();
+
+                let is_linked = self.is_equal(node_hash, expected_hash);
+                let is_branch = self.is_equal(list_len, branch_node_length);
+                let is_short = self.is_equal(list_len, leaf_or_extension_node_length);
+
+                // The path of a leaf or extension node must match the next nibbles of the key.
+                let path = items[0].encode(self).to_nibbles(self);
+                let prefix = path[0];
+                let is_prefix_leaf_even = self.is_equal(prefix, prefix_leaf_even);
+                let is_prefix_leaf_odd = self.is_equal(prefix, prefix_leaf_odd);
+                let is_prefix_extension_even = self.is_equal(prefix, prefix_extension_even);
+                let is_prefix_even = self.or(is_prefix_leaf_even, is_prefix_extension_even);
+                let offset = self.add(one, is_prefix_even.variable);
+                let path_nibbles = self.mul(path_len_bytes, two);
+                let path_nibbles = self.sub(path_nibbles, offset);
+                let check_length = self.mul(path_nibbles, is_short.variable);
+                let check_length = self.mul(check_length, is_active.variable);
+                self.assert_subarray_equal(&path, offset, &key_path, current_key_idx, check_length);
+
+                // A branch node is followed at the next nibble of the key, and a leaf or extension
+                // node at its second item.
+                let nibble = self.select_array(&key_path, current_key_idx);
+                let nibble = nibble.to_variable(self);
+                let item_index = self.select(is_branch, nibble, one);
+                let child = self.select_array_random_gate(&items, item_index);
+                let child_len = self.select_array_random_gate(&item_lens, item_index);
+                let key_idx_step = self.select(is_branch, one, check_length);
+                let next_key_idx = self.add(current_key_idx, key_idx_step);
+
+                // The last node must be the leaf holding the rest of the key, and the other nodes
+                // must be branch or extension nodes.
+                let is_prefix_leaf = self.or(is_prefix_leaf_even, is_prefix_leaf_odd);
+                let is_leaf = self.and(is_short, is_prefix_leaf);
+                let is_node = self.or(is_branch, is_short);
+                let is_leaf_last = self.is_equal(is_leaf, is_last);
+                let key_ends = self.is_equal(next_key_idx, const_64);
+                let is_not_last = self.not(is_last);
+                let key_ends = self.or(key_ends, is_not_last);
+                // Only the first bytes of an item are checked against the node, up to its length,
+                // so a child that is followed by its hash must fill all 32 bytes.
+                let child_is_hash = self.is_equal(child_len, const_32);
+                let child_is_hash = self.or(child_is_hash, is_last);
+                let is_valid = self.and(is_linked, is_node);
+                let is_valid = self.and(is_valid, is_leaf_last);
+                let is_valid = self.and(is_valid, key_ends);
+                let is_valid = self.and(is_valid, child_is_hash);
+                let is_valid = self.or(is_valid, is_inactive);
+                self.assert_is_equal(is_valid, t);
+
+                current_key_idx = self.select(is_active, next_key_idx, current_key_idx);
+                expected_hash = self.select(is_active, child, expected_hash);
+                value_item = self.select(is_last, child, value_item);
+            }
+
+            let (value, is_valid_value) = self.rlp_value_to_bytes32(&value_item.encode(self));
+            self.assert_is_equal(is_valid_value, t);
+            values.push(value);
+        }
+        ArrayVariable::new(values)
+    }
+
+    /// Fetches the storage proofs of `keys` in the contract at `address` at the block with hash
+    /// `block_hash`, and verifies them with `verify_storage_proofs_batched`.
+    ///
+    /// Returns the storage root of the contract and the values. The storage root is not checked
+    /// against the block, so the caller must verify it, e.g. with `verify_account_proof`.
+    pub fn eth_get_storage_proofs_batched<
+        const ENCODING_LEN: usize,
+        const NB_NODES: usize,
+        const PROOF_LEN: usize,
+        const K: usize,
+    >(
+        &mut self,
+        block_hash: Bytes32Variable,
+        address: AddressVariable,
+        keys: ArrayVariable<Bytes32Variable, K>,
+    ) -> (Bytes32Variable, ArrayVariable<Bytes32Variable, K>) {
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&block_hash);
+        input_stream.write(&address);
+        input_stream.write(&keys);
+
+        let hint = EthStorageProofsBatchedHint::<ENCODING_LEN, NB_NODES, PROOF_LEN, K>::new(self);
+        let output_stream = self.async_hint(input_stream, hint);
+        let storage_root = output_stream.read::<Bytes32Variable>(self);
+        let shared_nodes = output_stream
+            .read::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, NB_NODES>>(self);
+        let len_nodes = output_stream.read::<ArrayVariable<U32Variable, NB_NODES>>(self);
+        let per_key_paths =
+            output_stream.read::<ArrayVariable<ArrayVariable<Variable, PROOF_LEN>, K>>(self);
+        let per_key_path_lens = output_stream.read::<ArrayVariable<U32Variable, K>>(self);
+
+        let values = self.verify_storage_proofs_batched::<ENCODING_LEN, NB_NODES, PROOF_LEN, K>(
+            storage_root,
+            keys,
+            shared_nodes,
+            len_nodes,
+            per_key_paths,
+            per_key_path_lens,
+        );
+        (storage_root, values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use log::debug;
+
+    use super::super::utils::{read_fixture, EIP1186ProofResponse};
+    use super::*;
+    use crate::frontend::eth::rlp::utils::decode_padded_list;
+    use crate::frontend::eth::utils::u256_to_h256_be;
+    use crate::prelude::{DefaultBuilder, GoldilocksField};
+    use crate::utils;
+
+    const ENCODING_LEN: usize = 600;
+    const NB_NODES: usize = 16;
+    const PROOF_LEN: usize = 4;
+    const K: usize = 8;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_storage_proofs_batched() {
+        utils::setup_logger();
+        // Proofs of 8 slots of a synthetic storage trie of 64 slots, whose values range from a
+        // single byte to 31 bytes.
+        let storage_result: EIP1186ProofResponse =
+            read_fixture("./src/frontend/eth/mpt/fixtures/storage_batch.json");
+        let proofs = storage_result
+            .storage_proof
+            .iter()
+            .map(|proof| proof.proof.iter().map(|node| node.to_vec()).collect_vec())
+            .collect_vec();
+        let keys = storage_result
+            .storage_proof
+            .iter()
+            .map(|proof| u256_to_h256_be(proof.key))
+            .collect_vec();
+        let values = storage_result
+            .storage_proof
+            .iter()
+            .map(|proof| u256_to_h256_be(proof.value))
+            .collect_vec();
+        let (nodes, node_lens, paths, path_lens) =
+            dedup_storage_proofs::<ENCODING_LEN, NB_NODES, PROOF_LEN>(&proofs);
+
+        let mut builder = DefaultBuilder::new();
+        let root = builder.read::<Bytes32Variable>();
+        let key_variables = builder.read::<ArrayVariable<Bytes32Variable, K>>();
+        let shared_nodes =
+            builder.read::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, NB_NODES>>();
+        let len_nodes = builder.read::<ArrayVariable<U32Variable, NB_NODES>>();
+        let per_key_paths = builder.read::<ArrayVariable<ArrayVariable<Variable, PROOF_LEN>, K>>();
+        let per_key_path_lens = builder.read::<ArrayVariable<U32Variable, K>>();
+        let value_variables = builder
+            .verify_storage_proofs_batched::<ENCODING_LEN, NB_NODES, PROOF_LEN, K>(
+                root,
+                key_variables,
+                shared_nodes,
+                len_nodes,
+                per_key_paths,
+                per_key_path_lens,
+            );
+        builder.write(value_variables);
+        let batched_gates = builder.api.num_gates();
+
+        // The same proofs, each verified on its own.
+        let mut builder_independent = DefaultBuilder::new();
+        for _ in 0..K {
+            let key = builder_independent.read::<Bytes32Variable>();
+            let proof = builder_independent
+                .read::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>();
+            let len_nodes = builder_independent.read::<ArrayVariable<U32Variable, PROOF_LEN>>();
+            let root = builder_independent.read::<Bytes32Variable>();
+            let value = builder_independent.read::<Bytes32Variable>();
+            builder_independent
+                .verify_mpt_proof::<ENCODING_LEN, PROOF_LEN>(key, proof, len_nodes, root, value);
+        }
+        let independent_gates = builder_independent.api.num_gates();
+        debug!(
+            "batched: {} gates, independent: {} gates",
+            batched_gates, independent_gates
+        );
+        assert!(batched_gates < independent_gates);
+
+        let circuit = builder.mock_build();
+        let mut input = circuit.input();
+        input.write::<Bytes32Variable>(storage_result.storage_hash);
+        input.write::<ArrayVariable<Bytes32Variable, K>>(keys);
+        input.write::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, NB_NODES>>(nodes);
+        input.write::<ArrayVariable<U32Variable, NB_NODES>>(
+            node_lens.into_iter().map(|len| len as u32).collect(),
+        );
+        input.write::<ArrayVariable<ArrayVariable<Variable, PROOF_LEN>, K>>(
+            paths
+                .into_iter()
+                .map(|path| {
+                    path.into_iter()
+                        .map(GoldilocksField::from_canonical_usize)
+                        .collect()
+                })
+                .collect(),
+        );
+        input.write::<ArrayVariable<U32Variable, K>>(
+            path_lens.into_iter().map(|len| len as u32).collect(),
+        );
+        let mut output = circuit.mock_prove(&input).output;
+        let output_values = output.read::<ArrayVariable<Bytes32Variable, K>>();
+        assert_eq!(output_values, values);
+    }
+
+    /// Checks the items of the root node of the batch fixture given as inputs, as a malicious hint
+    /// would provide them, after flipping a bit of the byte at `tampered`, given as the index of an
+    /// item and of a byte in the item.
+    fn mock_prove_decoded_node(tampered: Option<(usize, usize)>) {
+        const LIST_LEN: usize = 17;
+        const ELEMENT_LEN: usize = 32;
+
+        let storage_result: EIP1186ProofResponse =
+            read_fixture("./src/frontend/eth/mpt/fixtures/storage_batch.json");
+        let node = storage_result.storage_proof[0].proof[0].to_vec();
+        let (mut items, item_lens, nb_items) =
+            decode_padded_list(&node, node.len(), false, LIST_LEN, ELEMENT_LEN);
+        if let Some((index, byte)) = tampered {
+            items[index][byte] ^= 1;
+        }
+
+        let mut builder = DefaultBuilder::new();
+        let node_variable = builder.read::<ArrayVariable<ByteVariable, ENCODING_LEN>>();
+        let len = builder.read::<U32Variable>();
+        let decoded_list =
+            builder.read::<ArrayVariable<ArrayVariable<ByteVariable, ELEMENT_LEN>, LIST_LEN>>();
+        let decoded_element_lens = builder.read::<ArrayVariable<Variable, LIST_LEN>>();
+        let len_decoded_list = builder.read::<Variable>();
+        let skip = builder._false();
+        builder.assert_decoded_mpt_node::<ENCODING_LEN, LIST_LEN, ELEMENT_LEN>(
+            &node_variable,
+            len,
+            skip,
+            &decoded_list,
+            &decoded_element_lens,
+            len_decoded_list,
+        );
+        let circuit = builder.mock_build();
+
+        let mut padded = node.clone();
+        padded.resize(ENCODING_LEN, 0);
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<ByteVariable, ENCODING_LEN>>(padded);
+        input.write::<U32Variable>(node.len() as u32);
+        input.write::<ArrayVariable<ArrayVariable<ByteVariable, ELEMENT_LEN>, LIST_LEN>>(items);
+        input.write::<ArrayVariable<Variable, LIST_LEN>>(
+            item_lens
+                .into_iter()
+                .map(GoldilocksField::from_canonical_usize)
+                .collect(),
+        );
+        input.write::<Variable>(GoldilocksField::from_canonical_usize(nb_items));
+        circuit.mock_prove(&input);
+    }
+
+    #[test]
+    fn test_decoded_node() {
+        mock_prove_decoded_node(None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decoded_node_tampered() {
+        mock_prove_decoded_node(Some((3, 7)));
+    }
+
+    #[test]
+    fn test_dedup_storage_proofs() {
+        let root = vec![1u8, 2, 3];
+        let proofs = vec![
+            vec![root.clone(), vec![4, 5], vec![6]],
+            vec![root.clone(), vec![4, 5], vec![7, 8]],
+            vec![root, vec![9]],
+        ];
+        let (nodes, node_lens, paths, path_lens) = dedup_storage_proofs::<4, 8, 3>(&proofs);
+        assert_eq!(nodes.len(), 8);
+        assert_eq!(nodes[3], vec![7, 8, 0, 0]);
+        assert_eq!(node_lens, vec![3, 2, 1, 2, 1, 0, 0, 0]);
+        assert_eq!(paths, vec![vec![0, 1, 2], vec![0, 1, 3], vec![0, 4, 0]]);
+        assert_eq!(path_lens, vec![3, 3, 2]);
+    }
+}
@@ -0,0 +1,233 @@
+//! Verification of the blob versioned hashes of EIP-4844 transactions.
+//!
+//! A blob transaction commits to each of its blobs with the versioned hash of the KZG commitment
+//! of the blob, `0x01 || sha256(commitment)[1..]`. The header only commits to the blobs through
+//! its transactions root, so the versioned hashes are read from the transaction itself.
+
+use array_macro::array;
+
+use crate::frontend::eth::header::BlockHeaderVariable;
+use crate::frontend::vars::VariableBytesVariable;
+use crate::prelude::{
+    ArrayVariable, ByteVariable, Bytes32Variable, CircuitBuilder, PlonkParameters, U32Variable,
+};
+
+/// The type of an EIP-4844 blob transaction.
+const BLOB_TX_TYPE: u8 = 0x03;
+
+/// The number of fields of a blob transaction: the chain id, the nonce, the two fees, the gas
+/// limit, `to`, the value, the data, the access list, the blob fee, the blob versioned hashes and
+/// the three values of the signature.
+const BLOB_TX_ITEMS: usize = 14;
+
+/// The index of the blob versioned hashes in the fields of a blob transaction.
+const BLOB_VERSIONED_HASHES_INDEX: usize = 10;
+
+/// The version byte of the versioned hash of a KZG commitment.
+const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// The RLP prefix of a 32 byte string.
+const BYTES32_RLP_PREFIX: u8 = 0xa0;
+
+/// The length of the RLP encoding of a 32 byte string.
+const BYTES32_RLP_LEN: u32 = 33;
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns the versioned hash of a KZG commitment, `0x01 || sha256(commitment)[1..]`.
+    pub fn kzg_commitment_to_versioned_hash(
+        &mut self,
+        commitment: ArrayVariable<ByteVariable, 48>,
+    ) -> Bytes32Variable {
+        let hash = self.curta_sha256(commitment.as_slice());
+        let mut bytes = hash.as_bytes();
+        bytes[0] = self.constant::<ByteVariable>(VERSIONED_HASH_VERSION_KZG);
+        Bytes32Variable::from(&bytes[..])
+    }
+
+    /// Returns the blob versioned hash at `index` of a blob transaction, as returned by
+    /// `verify_transaction_inclusion`.
+    ///
+    /// The fields of the transaction are decoded in the circuit. The versioned hashes are not, as
+    /// they are all 32 byte strings in a valid block, so the hash at `index` is encoded in the 33
+    /// bytes at `33 * index` in their list.
+    pub fn get_blob_versioned_hash<const MAX_TX_LEN: usize>(
+        &mut self,
+        tx: &VariableBytesVariable<MAX_TX_LEN>,
+        index: U32Variable,
+    ) -> Bytes32Variable {
+        let t = self._true();
+        let tx_type = self.constant::<ByteVariable>(BLOB_TX_TYPE);
+        self.assert_is_equal(tx.data[0], tx_type);
+
+        // The fields are the RLP list after the type.
+        let zero = self.zero::<ByteVariable>();
+        let fields =
+            array![i => if i + 1 < MAX_TX_LEN { tx.data[i + 1] } else { zero }; MAX_TX_LEN];
+        let one = self.one::<U32Variable>();
+        let fields_len = self.sub(tx.len, one);
+        let list = self.decode_rlp_list::<MAX_TX_LEN, BLOB_TX_ITEMS>(&fields, fields_len);
+        let nb_items = self.constant::<U32Variable>(BLOB_TX_ITEMS as u32);
+        self.assert_is_equal(list.nb_items, nb_items);
+        self.assert_is_equal(list.is_list[BLOB_VERSIONED_HASHES_INDEX], t);
+        let hashes_offset = list.offsets[BLOB_VERSIONED_HASHES_INDEX];
+        let hashes_len = list.lens[BLOB_VERSIONED_HASHES_INDEX];
+
+        // Bound the index first, so that the offsets below can't wrap around.
+        let max_index = self.constant::<U32Variable>(MAX_TX_LEN as u32);
+        let index_fits = self.lt(index, max_index);
+        self.assert_is_equal(index_fits, t);
+        let item_len = self.constant::<U32Variable>(BYTES32_RLP_LEN);
+        let start = self.mul(index, item_len);
+        let end = self.add(start, item_len);
+        let in_range = self.lte(end, hashes_len);
+        self.assert_is_equal(in_range, t);
+
+        let start = self.add(hashes_offset, start);
+        let item = (0..BYTES32_RLP_LEN)
+            .map(|i| {
+                let i = self.constant::<U32Variable>(i);
+                let offset = self.add(start, i);
+                self.select_array(&fields, offset.variable)
+            })
+            .collect::<Vec<_>>();
+        let prefix = self.constant::<ByteVariable>(BYTES32_RLP_PREFIX);
+        self.assert_is_equal(item[0], prefix);
+        Bytes32Variable::from(&item[1..])
+    }
+
+    /// Verifies that the blob transaction at `tx_index` of the block of `block_header` has the
+    /// versioned hash of `commitment` at `index` in its blob versioned hashes.
+    ///
+    /// The transaction, padded to `MAX_TX_LEN` bytes, must fit in a node of `ENCODING_LEN` bytes.
+    pub fn verify_blob_hashes<
+        const ENCODING_LEN: usize,
+        const PROOF_LEN: usize,
+        const MAX_TX_LEN: usize,
+    >(
+        &mut self,
+        block_header: &BlockHeaderVariable,
+        tx_index: U32Variable,
+        proof: ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>,
+        len_nodes: ArrayVariable<U32Variable, PROOF_LEN>,
+        index: U32Variable,
+        commitment: ArrayVariable<ByteVariable, 48>,
+    ) {
+        let tx = self.verify_transaction_inclusion::<ENCODING_LEN, PROOF_LEN, MAX_TX_LEN>(
+            block_header.transactions_root,
+            tx_index,
+            proof,
+            len_nodes,
+        );
+        let versioned_hash = self.get_blob_versioned_hash(&tx, index);
+        let expected = self.kzg_commitment_to_versioned_hash(commitment);
+        self.assert_is_equal(versioned_hash, expected);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use ethers::types::{Bytes, H256};
+    use serde::Deserialize;
+
+    use super::super::builder::transform_proof_to_padded;
+    use super::*;
+    use crate::frontend::eth::header::BlockHeader;
+    use crate::prelude::DefaultBuilder;
+    use crate::utils::bytes32;
+
+    const ENCODING_LEN: usize = 512;
+    const PROOF_LEN: usize = 4;
+    const MAX_TX_LEN: usize = 256;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct BlobTransactionFixture {
+        transactions_root: H256,
+        tx_index: u32,
+        proof: Vec<Bytes>,
+        commitments: Vec<Bytes>,
+    }
+
+    /// Verifies each commitment of the fixture at its own index, or at `index` if given, and
+    /// returns their versioned hashes.
+    fn verify_blob_hashes_fixture(index: Option<u32>) -> Vec<H256> {
+        // A synthetic transactions trie of 4 transactions, where transaction 1 is a Cancun blob
+        // transaction with 3 blobs. The first commitment is the one of the zero blob, and the
+        // other two are synthetic.
+        let file = File::open("./src/frontend/eth/mpt/fixtures/blob_transactions.json").unwrap();
+        let fixture: BlobTransactionFixture = serde_json::from_reader(file).unwrap();
+
+        let mut builder = DefaultBuilder::new();
+        let block_header = builder.read::<BlockHeaderVariable>();
+        let tx_index = builder.read::<U32Variable>();
+        let proof =
+            builder.read::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>();
+        let len_nodes = builder.read::<ArrayVariable<U32Variable, PROOF_LEN>>();
+        let index_variable = builder.read::<U32Variable>();
+        let commitment = builder.read::<ArrayVariable<ByteVariable, 48>>();
+        let versioned_hash = builder.kzg_commitment_to_versioned_hash(commitment.clone());
+        builder.write(versioned_hash);
+        builder.verify_blob_hashes::<ENCODING_LEN, PROOF_LEN, MAX_TX_LEN>(
+            &block_header,
+            tx_index,
+            proof,
+            len_nodes,
+            index_variable,
+            commitment,
+        );
+        let circuit = builder.mock_build();
+
+        let proof = fixture.proof.iter().map(|b| b.to_vec()).collect();
+        let (proof_as_fixed, lengths_as_fixed) =
+            transform_proof_to_padded::<ENCODING_LEN, PROOF_LEN>(proof);
+        let mut versioned_hashes = Vec::new();
+        for (i, commitment) in fixture.commitments.iter().enumerate() {
+            let mut input = circuit.input();
+            input.write::<BlockHeaderVariable>(BlockHeader {
+                hash: H256::zero(),
+                parent_hash: H256::zero(),
+                state_root: H256::zero(),
+                transactions_root: fixture.transactions_root,
+                receipts_root: H256::zero(),
+                number: 0,
+                timestamp: 0,
+            });
+            input.write::<U32Variable>(fixture.tx_index);
+            input.write::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>(
+                proof_as_fixed.clone(),
+            );
+            input.write::<ArrayVariable<U32Variable, PROOF_LEN>>(
+                lengths_as_fixed.iter().map(|x| *x as u32).collect(),
+            );
+            input.write::<U32Variable>(index.unwrap_or(i as u32));
+            input.write::<ArrayVariable<ByteVariable, 48>>(commitment.to_vec());
+            let mut output = circuit.mock_prove(&input).output;
+            versioned_hashes.push(output.read::<Bytes32Variable>());
+        }
+        versioned_hashes
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_blob_hashes() {
+        let versioned_hashes = verify_blob_hashes_fixture(None);
+        assert_eq!(
+            versioned_hashes,
+            vec![
+                bytes32!("0x010657f37554c781402a22917dee2f75def7ab966d7b770905398eba3c444014"),
+                bytes32!("0x0138dbd7b89f598642dc9d2bb3a66180817d9d74a2d285518af1ba9997a1a69f"),
+                bytes32!("0x01dfcf52ceea2082c8c060329122a6a4983eef32ee284490c2296d0459345e1a"),
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_blob_hashes_wrong_index() {
+        // Every commitment is checked against the versioned hash at index 1.
+        verify_blob_hashes_fixture(Some(1));
+    }
+}
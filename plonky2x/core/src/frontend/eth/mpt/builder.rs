@@ -1,9 +1,11 @@
+use ethers::types::H256;
 use starkyx::math::field::Field;
 
-use crate::frontend::vars::Nibbles;
+use crate::frontend::eth::rlp::list::RlpListVariable;
+use crate::frontend::vars::{EvmVariable, Nibbles};
 use crate::prelude::{
-    ArrayVariable, ByteVariable, Bytes32Variable, CircuitBuilder, CircuitVariable, PlonkParameters,
-    U32Variable, Variable,
+    ArrayVariable, BoolVariable, ByteVariable, Bytes32Variable, CircuitBuilder, CircuitVariable,
+    PlonkParameters, U32Variable, Variable,
 };
 
 pub fn transform_proof_to_padded<const ENCODING_LEN: usize, const PROOF_LEN: usize>(
@@ -40,21 +42,74 @@ pub fn transform_proof_to_padded<const ENCODING_LEN: usize, const PROOF_LEN: usi
 }
 
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
-    const PREFIX_EXTENSION_EVEN: u8 = 0;
-    const PREFIX_EXTENSION_ODD: u8 = 1;
-    const PREFIX_LEAF_EVEN: u8 = 2;
-    const PREFIX_LEAF_ODD: u8 = 3;
-    /// Get the validators for a given block root.
-    /// P is the number of proof elements to be considered
-    pub fn verify_mpt_proof<const ENCODING_LEN: usize, const PROOF_LEN: usize>(
+    pub(crate) const PREFIX_EXTENSION_EVEN: u8 = 0;
+    pub(crate) const PREFIX_EXTENSION_ODD: u8 = 1;
+    pub(crate) const PREFIX_LEAF_EVEN: u8 = 2;
+    pub(crate) const PREFIX_LEAF_ODD: u8 = 3;
+    /// Returns the path of `index` in a transactions or receipts trie, which is `rlp(index)`, as
+    /// nibbles padded to 64 along with its number of nibbles. Only indices below 2^16 are
+    /// supported.
+    pub(crate) fn rlp_index_trie_key(
         &mut self,
-        key: Bytes32Variable,
+        index: U32Variable,
+    ) -> (ArrayVariable<ByteVariable, 64>, Variable) {
+        let bytes = index.encode(self);
+        let zero_byte = self.zero::<ByteVariable>();
+        self.assert_is_equal(bytes[0], zero_byte);
+        self.assert_is_equal(bytes[1], zero_byte);
+
+        let zero = self.constant::<U32Variable>(0);
+        let const_128 = self.constant::<U32Variable>(128);
+        let const_256 = self.constant::<U32Variable>(256);
+        let is_zero = self.is_equal(index, zero);
+        let is_single_byte = self.lt(index, const_128);
+        let is_short = self.lt(index, const_256);
+
+        // Zero is the empty string, indices below 128 are their own encoding and larger indices
+        // are prefixed by 0x80 plus their number of bytes.
+        let empty_string = self.constant::<ByteVariable>(0x80);
+        let short_prefix = self.constant::<ByteVariable>(0x81);
+        let long_prefix = self.constant::<ByteVariable>(0x82);
+        let prefix = self.select(is_short, short_prefix, long_prefix);
+        let first = self.select(is_single_byte, bytes[3], prefix);
+        let first = self.select(is_zero, empty_string, first);
+        let second = self.select(is_short, bytes[3], bytes[2]);
+
+        let mut key_path = vec![first, second, bytes[3]].to_nibbles(self);
+        key_path.resize(64, zero_byte);
+
+        let two = self.constant::<Variable>(L::Field::from_canonical_u8(2));
+        let four = self.constant::<Variable>(L::Field::from_canonical_u8(4));
+        let six = self.constant::<Variable>(L::Field::from_canonical_u8(6));
+        let key_len = self.select(is_short, four, six);
+        let key_len = self.select(is_single_byte, two, key_len);
+
+        (ArrayVariable::new(key_path), key_len)
+    }
+
+    /// Walks a Merkle Patricia Trie proof from `root` along the first `key_len` nibbles of
+    /// `key_path`. P is the number of proof elements to be considered and `ELEMENT_LEN` the
+    /// maximum size of the items of a node, which must fit the value at the end of the walk.
+    ///
+    /// Returns the RLP-encoded value, padded to `ELEMENT_LEN` bytes, its length and whether the key
+    /// is in the trie. A proof of exclusion ends at a branch node with no child at the next nibble of the
+    /// key, or at a leaf or extension node whose path diverges from the key.
+    pub(crate) fn walk_mpt_proof<
+        const ENCODING_LEN: usize,
+        const PROOF_LEN: usize,
+        const ELEMENT_LEN: usize,
+    >(
+        &mut self,
+        key_path: ArrayVariable<ByteVariable, 64>,
+        key_len: Variable,
         proof: ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>,
         len_nodes: ArrayVariable<U32Variable, PROOF_LEN>,
         root: Bytes32Variable,
-        value: Bytes32Variable,
+    ) -> (
+        ArrayVariable<ByteVariable, ELEMENT_LEN>,
+        Variable,
+        BoolVariable,
     ) {
-        const ELEMENT_LEN: usize = 32; // Maximum size of list element
         const LIST_LEN: usize = 17; // Maximum length of the list for each proof element
 
         let tree_radix = self.constant::<Variable>(L::Field::from_canonical_u8(16u8));
@@ -67,26 +122,25 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         let prefix_extension_odd = self.constant::<ByteVariable>(Self::PREFIX_EXTENSION_ODD);
         let one: Variable = self.one::<Variable>();
         let two = self.constant::<Variable>(L::Field::from_canonical_u8(2));
-        let const_64 = self.constant::<Variable>(L::Field::from_canonical_u8(64));
         let const_32 = self.constant::<U32Variable>(32u32);
-        let const_128 = self.constant::<U32Variable>(128u32);
 
         let mut current_key_idx = self.zero::<Variable>();
         let mut finished = self._false();
+        let mut excluded = self._false();
 
         let mut padded_root = root.as_bytes().to_vec();
         while padded_root.len() < ELEMENT_LEN {
             padded_root.push(self.constant::<ByteVariable>(0));
         }
         let mut current_node_id = ArrayVariable::<ByteVariable, ELEMENT_LEN>::new(padded_root);
-        let hash_key = self.keccak256_witness(&key.as_bytes());
-        let key_path: ArrayVariable<ByteVariable, 64> =
-            hash_key.as_bytes().to_vec().to_nibbles(self).into();
+        let mut current_node_len = self.constant::<Variable>(L::Field::from_canonical_u8(32));
 
         for i in 0..PROOF_LEN {
             let current_node = proof[i].clone();
-            let current_node_hash =
-                self.keccak256_variable_witness(current_node.as_slice(), len_nodes[i].variable);
+            let current_node_hash = self.keccak256_variable::<ENCODING_LEN>(
+                current_node.as_slice().try_into().unwrap(),
+                len_nodes[i],
+            );
 
             if i == 0 {
                 self.assert_is_equal(current_node_hash, root);
@@ -111,23 +165,55 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
 
             let (decoded_list, decoded_element_lens, len_decoded_list) = self
                 .decode_element_as_list::<ENCODING_LEN, LIST_LEN, ELEMENT_LEN>(
-                    current_node,
+                    current_node.clone(),
                     len_nodes[i].variable,
                     finished,
                 );
 
+            // The decoded items decide which child is followed and whether the key is excluded,
+            // so their lengths and the path are checked against a constrained decoding of the
+            // node.
+            let node_list = self.decode_mpt_node::<ENCODING_LEN, LIST_LEN>(
+                &current_node,
+                len_nodes[i],
+                finished,
+            );
+            let t = self._true();
+            let zero = self.zero::<Variable>();
+            let nb_items_match = self.is_equal(len_decoded_list, node_list.nb_items.variable);
+            let nb_items_match = self.or(nb_items_match, finished);
+            self.assert_is_equal(nb_items_match, t);
+            for j in 0..LIST_LEN {
+                let len_matches =
+                    self.is_equal(decoded_element_lens[j], node_list.lens[j].variable);
+                let is_string = self.not(node_list.is_list[j]);
+                let item_matches = self.and(len_matches, is_string);
+                let item_matches = self.or(item_matches, finished);
+                self.assert_is_equal(item_matches, t);
+            }
+            let path_len = self.select(finished, zero, decoded_element_lens[0]);
+            self.assert_subarray_equal(
+                &decoded_list[0].as_slice()[..ELEMENT_LEN.min(33)],
+                zero,
+                current_node.as_slice(),
+                node_list.offsets[0].variable,
+                path_len,
+            );
+
             let is_branch = self.is_equal(len_decoded_list, branch_node_length);
             let is_leaf = self.is_equal(len_decoded_list, leaf_or_extension_node_length);
-            let key_terminated = self.is_equal(current_key_idx, const_64);
+            let key_terminated = self.is_equal(current_key_idx, key_len);
             let path = decoded_list[0].as_slice().to_vec().to_nibbles(self);
             let prefix = path[0];
             let prefix_leaf_even = self.is_equal(prefix, prefix_leaf_even);
             let prefix_leaf_odd = self.is_equal(prefix, prefix_leaf_odd);
             let prefix_extension_even = self.is_equal(prefix, prefix_extension_even);
             let prefix_extension_odd = self.is_equal(prefix, prefix_extension_odd);
+            let prefix_even = self.or(prefix_leaf_even, prefix_extension_even);
+            let prefix_odd = self.or(prefix_leaf_odd, prefix_extension_odd);
 
-            let offset_even = self.mul(prefix_extension_even.variable, two);
-            let offset_odd = self.mul(prefix_extension_odd.variable, one);
+            let offset_even = self.mul(prefix_even.variable, two);
+            let offset_odd = self.mul(prefix_odd.variable, one);
             let offset = self.add(offset_even, offset_odd);
             let branch_key = self.select_array(key_path.clone().as_slice(), current_key_idx);
             let branch_key_variable = branch_key.to_variable(self);
@@ -148,15 +234,28 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
 
             let updated_current_node_id =
                 self.select_array(decoded_list.as_slice(), updated_current_node_id_idx);
+            let updated_current_node_offset =
+                self.select_array(node_list.offsets.as_slice(), updated_current_node_id_idx);
             // If finished == 1, then we should not update the current_node_id
             current_node_id = self.select_array(
                 &[updated_current_node_id, current_node_id],
                 finished.variable,
             );
+            let updated_current_node_len =
+                self.select_array(decoded_element_lens.as_slice(), updated_current_node_id_idx);
+            let checked_len = self.select(finished, zero, updated_current_node_len);
+            self.assert_subarray_equal(
+                current_node_id.as_slice(),
+                zero,
+                current_node.as_slice(),
+                updated_current_node_offset.variable,
+                checked_len,
+            );
+            current_node_len = self.select(finished, current_node_len, updated_current_node_len);
 
-            let mut do_path_remainder_check = self.not(finished);
-            do_path_remainder_check = self.and(do_path_remainder_check, is_leaf);
-            let d = self.or(prefix_extension_even, prefix_extension_odd);
+            let not_finished = self.not(finished);
+            let mut do_path_remainder_check = self.and(not_finished, is_leaf);
+            let d = self.or(prefix_even, prefix_odd);
             do_path_remainder_check = self.and(do_path_remainder_check, d);
 
             let e = self.mul(decoded_element_lens[0], two);
@@ -164,13 +263,34 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             let mut check_length = self.sub(e, f);
             check_length = self.mul(check_length, do_path_remainder_check.variable);
 
-            self.assert_subarray_equal(
-                &path,
+            // The key is not in the trie if the path of a leaf or extension node diverges from it.
+            // The key has 64 nibbles, so only the first 66 nibbles of the path, including its
+            // prefix, can match it.
+            let path_matches = self.subarray_equal(
+                &path[..path.len().min(66)],
                 offset,
                 key_path.as_slice(),
                 current_key_idx,
                 check_length,
             );
+            // A leaf must also hold the whole rest of the key.
+            let is_leaf_prefix = self.or(prefix_leaf_even, prefix_leaf_odd);
+            let key_idx_after_path = self.add(current_key_idx, check_length);
+            let path_ends_key = self.is_equal(key_idx_after_path, key_len);
+            let path_ends_early = self.not(path_ends_key);
+            let leaf_ends_early = self.and(is_leaf_prefix, path_ends_early);
+            let path_diverges = self.not(path_matches);
+            let path_diverges = self.or(path_diverges, leaf_ends_early);
+            let path_diverges = self.and(do_path_remainder_check, path_diverges);
+
+            // The key is not in the trie if a branch node has no child at its next nibble.
+            let child_len = self.select_array(decoded_element_lens.as_slice(), branch_key_variable);
+            let child_is_empty = self.is_zero(child_len);
+            let branch_is_empty = self.and(is_branch_and_key_not_terminated, child_is_empty);
+            let branch_is_empty = self.and(not_finished, branch_is_empty);
+
+            let is_excluded = self.or(path_diverges, branch_is_empty);
+            excluded = self.or(excluded, is_excluded);
 
             current_key_idx = self.add(current_key_idx, is_branch_and_key_not_terminated.variable);
             let j = self.mul(is_leaf.variable, check_length);
@@ -180,9 +300,177 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             let prefix_leaf_odd_and_leaf = self.and(prefix_leaf_odd, is_leaf);
             let l = self.or(is_branch_and_key_terminated, prefix_leaf_even_and_leaf);
             let m = self.or(l, prefix_leaf_odd_and_leaf);
+            let m = self.or(m, is_excluded);
             finished = self.or(finished, m);
         }
 
+        // The walk must reach the value or prove that the key is not in the trie.
+        let t = self._true();
+        self.assert_is_equal(finished, t);
+
+        let exists = self.not(excluded);
+        (current_node_id, current_node_len, exists)
+    }
+
+    /// Decodes a node of a proof, or another RLP list such as an account, with the constrained RLP
+    /// list decoder. Skipped encodings, such as the padding nodes after the end of a walk, are
+    /// decoded as an empty list.
+    pub(crate) fn decode_mpt_node<const ENCODING_LEN: usize, const LIST_LEN: usize>(
+        &mut self,
+        node: &ArrayVariable<ByteVariable, ENCODING_LEN>,
+        len: U32Variable,
+        finished: BoolVariable,
+    ) -> RlpListVariable<LIST_LEN> {
+        let empty_list = self.constant::<ByteVariable>(0xc0);
+        let one = self.one::<U32Variable>();
+        let mut bytes = node.as_vec();
+        bytes[0] = self.select(finished, empty_list, bytes[0]);
+        let len = self.select(finished, one, len);
+        self.decode_rlp_list::<ENCODING_LEN, LIST_LEN>(bytes.as_slice().try_into().unwrap(), len)
+    }
+
+    /// Asserts that the items decoded by `decode_element_as_list` from a node, or another RLP list,
+    /// are its items: their number, their lengths and their bytes are checked against a
+    /// constrained decoding of the node. Nothing is checked for skipped nodes.
+    ///
+    /// Returns the constrained decoding of the node, which gives the offsets of its items.
+    pub(crate) fn assert_decoded_mpt_node<
+        const ENCODING_LEN: usize,
+        const LIST_LEN: usize,
+        const ELEMENT_LEN: usize,
+    >(
+        &mut self,
+        node: &ArrayVariable<ByteVariable, ENCODING_LEN>,
+        len: U32Variable,
+        skip: BoolVariable,
+        decoded_list: &ArrayVariable<ArrayVariable<ByteVariable, ELEMENT_LEN>, LIST_LEN>,
+        decoded_element_lens: &ArrayVariable<Variable, LIST_LEN>,
+        len_decoded_list: Variable,
+    ) -> RlpListVariable<LIST_LEN> {
+        let node_list = self.decode_mpt_node::<ENCODING_LEN, LIST_LEN>(node, len, skip);
+        let t = self._true();
+        let zero = self.zero::<Variable>();
+        let nb_items_match = self.is_equal(len_decoded_list, node_list.nb_items.variable);
+        let nb_items_match = self.or(nb_items_match, skip);
+        self.assert_is_equal(nb_items_match, t);
+        for i in 0..LIST_LEN {
+            let len_matches = self.is_equal(decoded_element_lens[i], node_list.lens[i].variable);
+            let is_string = self.not(node_list.is_list[i]);
+            let item_matches = self.and(len_matches, is_string);
+            let item_matches = self.or(item_matches, skip);
+            self.assert_is_equal(item_matches, t);
+
+            let checked_len = self.select(skip, zero, decoded_element_lens[i]);
+            self.assert_subarray_equal(
+                decoded_list[i].as_slice(),
+                zero,
+                node.as_slice(),
+                node_list.offsets[i].variable,
+                checked_len,
+            );
+        }
+        node_list
+    }
+
+    /// Decodes the value of a storage slot from its RLP encoding, padded on the right with zeros to
+    /// 32 bytes. The encoding is a single byte below 0x80, or a string prefixed by 0x80 plus its
+    /// length.
+    ///
+    /// Returns the value and whether the encoding is a string of at most 31 bytes.
+    pub(crate) fn rlp_value_to_bytes32(
+        &mut self,
+        encoding: &[ByteVariable],
+    ) -> (Bytes32Variable, BoolVariable) {
+        assert_eq!(encoding.len(), 32);
+        let one = self.one::<Variable>();
+        let const_128 = self.constant::<Variable>(L::Field::from_canonical_u8(128));
+        let const_31 = self.constant::<U32Variable>(31);
+        let const_128_u32 = self.constant::<U32Variable>(128);
+        let zero_byte = self.zero::<ByteVariable>();
+
+        let prefix = encoding[0].to_variable(self);
+        // Can be unsafe because `prefix` comes from a ByteVariable.
+        let prefix_u32 = U32Variable::from_variables_unsafe(&[prefix]);
+        let is_single_byte = self.lt(prefix_u32, const_128_u32);
+        let string_len = self.sub(prefix, const_128);
+        let string_len = self.select(is_single_byte, one, string_len);
+        // Can be unsafe because `string_len` is either 1 or `prefix - 128` with `prefix >= 128`.
+        let string_len_u32 = U32Variable::from_variables_unsafe(&[string_len]);
+        let is_valid = self.lte(string_len_u32, const_31);
+
+        let payload = (0..32)
+            .map(|i| {
+                let string_byte = encoding.get(i + 1).copied().unwrap_or(zero_byte);
+                self.select(is_single_byte, encoding[i], string_byte)
+            })
+            .collect::<Vec<_>>();
+        let value = self.rlp_string_to_uint::<U256Variable>(&payload, string_len);
+        let value = value.encode(self);
+        (Bytes32Variable::from(value.as_slice()), is_valid)
+    }
+
+    /// Returns the value stored at `key` in the Merkle Patricia Trie with the given `root`, and
+    /// whether the key is in the trie.
+    ///
+    /// The proof may be a proof of exclusion, which ends at a branch node with no child at the next
+    /// nibble of the key, at a leaf whose path differs from the rest of the key, or at an extension
+    /// node whose path diverges from the key. The value of a key that is not in the trie is zero,
+    /// which is how a storage slot holding zero is proven.
+    pub fn verify_mpt_value<const ENCODING_LEN: usize, const PROOF_LEN: usize>(
+        &mut self,
+        key: Bytes32Variable,
+        proof: ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>,
+        len_nodes: ArrayVariable<U32Variable, PROOF_LEN>,
+        root: Bytes32Variable,
+    ) -> (Bytes32Variable, BoolVariable) {
+        const ELEMENT_LEN: usize = 32; // Maximum size of list element
+
+        let hash_key = self.keccak256(&key.as_bytes());
+        let key_path = hash_key.as_bytes().to_vec().to_nibbles(self).into();
+        let key_len = self.constant::<Variable>(L::Field::from_canonical_u8(64));
+        let (value_encoding, _, exists) = self
+            .walk_mpt_proof::<ENCODING_LEN, PROOF_LEN, ELEMENT_LEN>(
+                key_path, key_len, proof, len_nodes, root,
+            );
+
+        // The walk of a proof of exclusion ends at an arbitrary item, which is not a value.
+        let (value, is_valid) = self.rlp_value_to_bytes32(value_encoding.as_slice());
+        let not_exists = self.not(exists);
+        let is_valid = self.or(is_valid, not_exists);
+        let t = self._true();
+        self.assert_is_equal(is_valid, t);
+
+        let zero = self.constant::<Bytes32Variable>(H256::zero());
+        let value = self.select(exists, value, zero);
+        (value, exists)
+    }
+
+    /// Verifies that `value` is stored at `key` in the Merkle Patricia Trie with the given `root`.
+    /// P is the number of proof elements to be considered
+    pub fn verify_mpt_proof<const ENCODING_LEN: usize, const PROOF_LEN: usize>(
+        &mut self,
+        key: Bytes32Variable,
+        proof: ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>,
+        len_nodes: ArrayVariable<U32Variable, PROOF_LEN>,
+        root: Bytes32Variable,
+        value: Bytes32Variable,
+    ) {
+        const ELEMENT_LEN: usize = 32; // Maximum size of list element
+
+        let one: Variable = self.one::<Variable>();
+        let const_32 = self.constant::<U32Variable>(32u32);
+        let const_128 = self.constant::<U32Variable>(128u32);
+
+        let hash_key = self.keccak256(&key.as_bytes());
+        let key_path = hash_key.as_bytes().to_vec().to_nibbles(self).into();
+        let key_len = self.constant::<Variable>(L::Field::from_canonical_u8(64));
+        let (current_node_id, _, exists) = self
+            .walk_mpt_proof::<ENCODING_LEN, PROOF_LEN, ELEMENT_LEN>(
+                key_path, key_len, proof, len_nodes, root,
+            );
+        let t = self._true();
+        self.assert_is_equal(exists, t);
+
         // Can be unsafe because `current_node_id` comes from a ByteVariable.
         let current_node_id_u32 =
             U32Variable::from_variables_unsafe(&[current_node_id[0].to_variable(self)]);
@@ -270,6 +558,68 @@ mod tests {
         input.write::<Bytes32Variable>(root);
         input.write::<Bytes32Variable>(value_as_h256);
 
-        let (_witness, mut _output) = circuit.mock_prove(&input);
+        let mut _output = circuit.mock_prove(&input).output;
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_mpt_value_exclusion() {
+        utils::setup_logger();
+        // A synthetic storage trie of 8 slots, whose root branch node has empty children and
+        // where two slots share an extension node. The first proof is a proof of inclusion,
+        // and the others are proofs of exclusion ending at an empty child of the root, at a leaf
+        // with a different key remainder and at the extension node.
+        let storage_result: EIP1186ProofResponse =
+            read_fixture("./src/frontend/eth/mpt/fixtures/storage_exclusion.json");
+        let root = storage_result.storage_hash;
+
+        const ENCODING_LEN: usize = 600;
+        const PROOF_LEN: usize = 4;
+
+        let mut builder = DefaultBuilder::new();
+        let key_variable = builder.read::<Bytes32Variable>();
+        let proof_variable =
+            builder.read::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>();
+        let len_nodes = builder.read::<ArrayVariable<U32Variable, PROOF_LEN>>();
+        let root_variable = builder.read::<Bytes32Variable>();
+        let (value, exists) = builder.verify_mpt_value::<ENCODING_LEN, PROOF_LEN>(
+            key_variable,
+            proof_variable,
+            len_nodes,
+            root_variable,
+        );
+        builder.write(value);
+        builder.write(exists);
+        let circuit = builder.mock_build();
+
+        for (i, storage_proof) in storage_result.storage_proof.iter().enumerate() {
+            let proof = storage_proof
+                .proof
+                .iter()
+                .map(|b| b.to_vec())
+                .collect::<Vec<Vec<u8>>>();
+            let (proof_as_fixed, lengths_as_fixed) =
+                transform_proof_to_padded::<ENCODING_LEN, PROOF_LEN>(proof);
+
+            let mut input = circuit.input();
+            input.write::<Bytes32Variable>(u256_to_h256_be(storage_proof.key));
+            input.write::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>(
+                proof_as_fixed,
+            );
+            input.write::<ArrayVariable<U32Variable, PROOF_LEN>>(
+                lengths_as_fixed.iter().map(|x| *x as u32).collect(),
+            );
+            input.write::<Bytes32Variable>(root);
+            let mut output = circuit.mock_prove(&input).output;
+            let value = output.read::<Bytes32Variable>();
+            let exists = output.read::<BoolVariable>();
+
+            debug!(
+                "key {:?} value {:?} exists {}",
+                storage_proof.key, value, exists
+            );
+            assert_eq!(value, u256_to_h256_be(storage_proof.value));
+            assert_eq!(exists, i == 0);
+        }
     }
 }
@@ -1,5 +1,9 @@
+pub mod account;
+pub mod batch;
+pub mod blob;
 pub mod builder;
-pub mod generators;
+pub mod receipt;
 pub mod reference;
 pub mod rlc;
+pub mod transaction;
 pub mod utils;
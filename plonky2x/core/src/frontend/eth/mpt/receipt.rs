@@ -0,0 +1,336 @@
+use ethers::types::H256;
+use plonky2::hash::hash_types::RichField;
+
+use crate::frontend::eth::vars::AddressVariable;
+use crate::prelude::{
+    ArrayVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitBuilder, CircuitVariable,
+    PlonkParameters, U32Variable, Variable,
+};
+
+/// The maximum number of topics of a log.
+const MAX_LOG_TOPICS: usize = 4;
+
+/// An event log, with up to `MAX_LOG_DATA` bytes of data.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(EventLog)]
+pub struct EventLogVariable<const MAX_LOG_DATA: usize> {
+    pub address: AddressVariable,
+    pub topics: ArrayVariable<Bytes32Variable, MAX_LOG_TOPICS>,
+    pub nb_topics: Variable,
+    pub data: ArrayVariable<ByteVariable, MAX_LOG_DATA>,
+    pub data_len: Variable,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Verifies that the receipt of `tx_index` is in the receipts trie with root `receipts_root`,
+    /// and returns its log at `log_index`. Both legacy and EIP-2718 typed receipts are supported.
+    ///
+    /// The receipt, padded to `MAX_RECEIPT_LEN` bytes, must fit in a node of `ENCODING_LEN` bytes,
+    /// it must have at most `MAX_LOGS` logs and the data of the log must have at most
+    /// `MAX_LOG_DATA` bytes. The receipt and the log are decoded in the circuit. If they are
+    /// provided, the address and the first topic of the log are asserted to be `expected_address`
+    /// and `expected_topic0`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_receipt_log<
+        const ENCODING_LEN: usize,
+        const PROOF_LEN: usize,
+        const MAX_RECEIPT_LEN: usize,
+        const MAX_LOGS: usize,
+        const MAX_LOG_DATA: usize,
+    >(
+        &mut self,
+        receipts_root: Bytes32Variable,
+        tx_index: U32Variable,
+        proof: ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>,
+        len_nodes: ArrayVariable<U32Variable, PROOF_LEN>,
+        log_index: U32Variable,
+        expected_address: Option<AddressVariable>,
+        expected_topic0: Option<Bytes32Variable>,
+    ) -> EventLogVariable<MAX_LOG_DATA> {
+        let (key_path, key_len) = self.rlp_index_trie_key(tx_index);
+        let (receipt, receipt_len, exists) = self
+            .walk_mpt_proof::<ENCODING_LEN, PROOF_LEN, MAX_RECEIPT_LEN>(
+                key_path,
+                key_len,
+                proof,
+                len_nodes,
+                receipts_root,
+            );
+        let t = self._true();
+        let f = self._false();
+        self.assert_is_equal(exists, t);
+
+        // A typed receipt is its type, which is below 0x80, followed by the RLP encoding of the
+        // receipt.
+        let zero_byte = self.zero::<ByteVariable>();
+        let is_typed = self.not(receipt[0].0[0]);
+        let receipt_bytes = (0..MAX_RECEIPT_LEN)
+            .map(|i| {
+                let next = receipt.as_slice().get(i + 1).copied().unwrap_or(zero_byte);
+                self.select(is_typed, next, receipt[i])
+            })
+            .collect::<Vec<_>>();
+        let receipt_bytes: &[ByteVariable; MAX_RECEIPT_LEN] =
+            receipt_bytes.as_slice().try_into().unwrap();
+        // Can be unsafe because the length of the value is checked against the constrained
+        // decoding of its node, and `is_typed` is a bit.
+        let receipt_len = U32Variable::from_variables_unsafe(&[receipt_len]);
+        let type_len = U32Variable::from_variables_unsafe(&[is_typed.variable]);
+        let receipt_len = self.sub(receipt_len, type_len);
+
+        // The receipt is [status, cumulativeGasUsed, logsBloom, logs].
+        let fields = self.decode_rlp_list::<MAX_RECEIPT_LEN, 4>(receipt_bytes, receipt_len);
+        let nb_fields = self.constant::<U32Variable>(4);
+        self.assert_is_equal(fields.nb_items, nb_fields);
+        self.assert_is_equal(fields.is_list[3], t);
+        let logs = self.decode_rlp_list_payload::<MAX_RECEIPT_LEN, MAX_LOGS>(
+            receipt_bytes,
+            fields.offsets[3],
+            fields.lens[3],
+        );
+
+        // A log is [address, topics, data].
+        let is_in_range = self.lt(log_index, logs.nb_items);
+        self.assert_is_equal(is_in_range, t);
+        let log_offset = self.select_array(logs.offsets.as_slice(), log_index.variable);
+        let log_len = self.select_array(logs.lens.as_slice(), log_index.variable);
+        let log_is_list = self.select_array(logs.is_list.as_slice(), log_index.variable);
+        self.assert_is_equal(log_is_list, t);
+        let log_fields =
+            self.decode_rlp_list_payload::<MAX_RECEIPT_LEN, 3>(receipt_bytes, log_offset, log_len);
+        let nb_log_fields = self.constant::<U32Variable>(3);
+        self.assert_is_equal(log_fields.nb_items, nb_log_fields);
+        self.assert_is_equal(log_fields.is_list[0], f);
+        self.assert_is_equal(log_fields.is_list[1], t);
+        self.assert_is_equal(log_fields.is_list[2], f);
+
+        let const_20 = self.constant::<U32Variable>(20);
+        self.assert_is_equal(log_fields.lens[0], const_20);
+        let address =
+            self.select_window(receipt_bytes, log_fields.offsets[0].variable, 20, zero_byte);
+        let address = AddressVariable(BytesVariable(address.try_into().unwrap()));
+
+        let topics_list = self.decode_rlp_list_payload::<MAX_RECEIPT_LEN, MAX_LOG_TOPICS>(
+            receipt_bytes,
+            log_fields.offsets[1],
+            log_fields.lens[1],
+        );
+        let const_32 = self.constant::<U32Variable>(32);
+        let zero_topic = self.constant::<Bytes32Variable>(H256::zero());
+        let mut topics = Vec::with_capacity(MAX_LOG_TOPICS);
+        for i in 0..MAX_LOG_TOPICS {
+            let index = self.constant::<U32Variable>(i as u32);
+            let is_topic = self.lt(index, topics_list.nb_items);
+            let is_not_topic = self.not(is_topic);
+            let is_hash = self.is_equal(topics_list.lens[i], const_32);
+            let is_string = self.not(topics_list.is_list[i]);
+            let is_valid = self.and(is_hash, is_string);
+            let is_valid = self.or(is_valid, is_not_topic);
+            self.assert_is_equal(is_valid, t);
+
+            let topic = self.select_window(
+                receipt_bytes,
+                topics_list.offsets[i].variable,
+                32,
+                zero_byte,
+            );
+            let topic = Bytes32Variable::from(topic.as_slice());
+            topics.push(self.select(is_topic, topic, zero_topic));
+        }
+
+        let data_len = log_fields.lens[2];
+        let max_data_len = self.constant::<U32Variable>(MAX_LOG_DATA as u32);
+        let data_fits = self.lte(data_len, max_data_len);
+        self.assert_is_equal(data_fits, t);
+        let data = self.select_window(
+            receipt_bytes,
+            log_fields.offsets[2].variable,
+            MAX_LOG_DATA,
+            zero_byte,
+        );
+        let data = data
+            .into_iter()
+            .enumerate()
+            .map(|(i, byte)| {
+                let index = self.constant::<U32Variable>(i as u32);
+                let is_data = self.lt(index, data_len);
+                self.select(is_data, byte, zero_byte)
+            })
+            .collect::<Vec<_>>();
+
+        let log = EventLogVariable::<MAX_LOG_DATA> {
+            address,
+            topics: ArrayVariable::new(topics),
+            nb_topics: topics_list.nb_items.variable,
+            data: ArrayVariable::new(data),
+            data_len: data_len.variable,
+        };
+
+        if let Some(expected_address) = expected_address {
+            self.assert_is_equal(log.address, expected_address);
+        }
+        if let Some(expected_topic0) = expected_topic0 {
+            let zero = self.zero::<Variable>();
+            let has_no_topics = self.is_equal(log.nb_topics, zero);
+            self.assert_is_equal(has_no_topics, f);
+            self.assert_is_equal(log.topics[0], expected_topic0);
+        }
+
+        log
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use ethers::types::{Bytes, H160};
+    use plonky2::field::types::Field;
+    use serde::Deserialize;
+
+    use super::super::builder::transform_proof_to_padded;
+    use super::*;
+    use crate::backend::circuit::{DefaultParameters, MockCircuitBuild};
+    use crate::prelude::{DefaultBuilder, GoldilocksField};
+    use crate::utils::{address, bytes32};
+
+    type L = DefaultParameters;
+    type F = GoldilocksField;
+    const D: usize = 2;
+
+    const ENCODING_LEN: usize = 640;
+    const PROOF_LEN: usize = 6;
+    const MAX_RECEIPT_LEN: usize = 640;
+    const MAX_LOGS: usize = 4;
+    const MAX_LOG_DATA: usize = 64;
+
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    const TRANSFER: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ReceiptLogFixture {
+        tx_index: u32,
+        log_index: u32,
+        proof: Vec<Bytes>,
+        address: H160,
+        topics: Vec<H256>,
+        data: Bytes,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ReceiptsFixture {
+        receipts_root: H256,
+        receipts: Vec<ReceiptLogFixture>,
+    }
+
+    /// A receipts trie of 131 receipts. The legacy receipt 0 holds a USDC Transfer log, and the
+    /// typed receipt 130, whose key is two bytes long, holds a Sync, a WETH Deposit and a USDC
+    /// Transfer log.
+    fn read_receipts_fixture() -> ReceiptsFixture {
+        let file = File::open("./src/frontend/eth/mpt/fixtures/receipts.json").unwrap();
+        serde_json::from_reader(file).unwrap()
+    }
+
+    fn build_circuit(expect_transfer: bool) -> MockCircuitBuild<L, D> {
+        let mut builder = DefaultBuilder::new();
+        let receipts_root = builder.read::<Bytes32Variable>();
+        let tx_index = builder.read::<U32Variable>();
+        let proof =
+            builder.read::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>();
+        let len_nodes = builder.read::<ArrayVariable<U32Variable, PROOF_LEN>>();
+        let log_index = builder.read::<U32Variable>();
+        let (expected_address, expected_topic0) = if expect_transfer {
+            (
+                Some(builder.constant::<AddressVariable>(address!(USDC))),
+                Some(builder.constant::<Bytes32Variable>(bytes32!(TRANSFER))),
+            )
+        } else {
+            (None, None)
+        };
+        let log = builder
+            .verify_receipt_log::<ENCODING_LEN, PROOF_LEN, MAX_RECEIPT_LEN, MAX_LOGS, MAX_LOG_DATA>(
+                receipts_root,
+                tx_index,
+                proof,
+                len_nodes,
+                log_index,
+                expected_address,
+                expected_topic0,
+            );
+        builder.write(log);
+        builder.mock_build()
+    }
+
+    fn prove_log(
+        circuit: &MockCircuitBuild<L, D>,
+        receipts_root: H256,
+        case: &ReceiptLogFixture,
+    ) -> EventLog<MAX_LOG_DATA, F> {
+        let proof = case.proof.iter().map(|b| b.to_vec()).collect();
+        let (proof_as_fixed, lengths_as_fixed) =
+            transform_proof_to_padded::<ENCODING_LEN, PROOF_LEN>(proof);
+
+        let mut input = circuit.input();
+        input.write::<Bytes32Variable>(receipts_root);
+        input.write::<U32Variable>(case.tx_index);
+        input.write::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>(
+            proof_as_fixed,
+        );
+        input.write::<ArrayVariable<U32Variable, PROOF_LEN>>(
+            lengths_as_fixed.iter().map(|x| *x as u32).collect(),
+        );
+        input.write::<U32Variable>(case.log_index);
+
+        let mut output = circuit.mock_prove(&input).output;
+        output.read::<EventLogVariable<MAX_LOG_DATA>>()
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_receipt_log() {
+        let fixture = read_receipts_fixture();
+        let circuit = build_circuit(false);
+
+        // Every log of the fixture, including one with a single topic and no data.
+        for case in fixture.receipts.iter() {
+            let log = prove_log(&circuit, fixture.receipts_root, case);
+            assert_eq!(log.address, case.address);
+            assert_eq!(log.topics[..case.topics.len()], case.topics[..]);
+            assert_eq!(log.nb_topics, F::from_canonical_usize(case.topics.len()));
+            assert_eq!(log.data[..case.data.len()], case.data[..]);
+            assert_eq!(log.data_len, F::from_canonical_usize(case.data.len()));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_receipt_log_expected_event() {
+        let fixture = read_receipts_fixture();
+        let circuit = build_circuit(true);
+
+        for case in fixture.receipts.iter() {
+            if case.topics[0] != bytes32!(TRANSFER) {
+                continue;
+            }
+            let log = prove_log(&circuit, fixture.receipts_root, case);
+            assert_eq!(log.address, address!(USDC));
+            assert_eq!(log.topics[0], bytes32!(TRANSFER));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_receipt_log_index_out_of_range() {
+        let mut fixture = read_receipts_fixture();
+        let circuit = build_circuit(false);
+
+        // Receipt 0 has a single log.
+        let mut case = fixture.receipts.remove(0);
+        assert_eq!(case.tx_index, 0);
+        case.log_index = 1;
+        prove_log(&circuit, fixture.receipts_root, &case);
+    }
+}
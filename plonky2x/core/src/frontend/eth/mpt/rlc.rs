@@ -1,7 +1,12 @@
-use std::marker::PhantomData;
+use plonky2::field::types::Field;
 
-use super::generators::SubarrayEqualGenerator;
-use crate::prelude::{BoolVariable, ByteVariable, CircuitBuilder, PlonkParameters, Variable};
+use crate::prelude::{
+    BoolVariable, ByteVariable, CircuitBuilder, CircuitVariable, PlonkParameters, U32Variable,
+    Variable,
+};
+
+/// The number of bits that the offsets and the length of a subarray comparison must fit in.
+const SUBARRAY_INDEX_BITS: usize = 16;
 
 // Checks that a[a_offset:a_offset+len] = b[b_offset:b_offset+len]
 pub fn subarray_equal(a: &[u8], a_offset: usize, b: &[u8], b_offset: usize, len: usize) -> u8 {
@@ -14,7 +19,11 @@ pub fn subarray_equal(a: &[u8], a_offset: usize, b: &[u8], b_offset: usize, len:
 }
 
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
-    #[allow(unused_variables, dead_code)]
+    /// Returns whether `a[a_offset..a_offset + len] = b[b_offset..b_offset + len]`. Out of range
+    /// subarrays are never equal.
+    ///
+    /// The offsets and the length are asserted to be less than 2^16. Both subarrays are selected
+    /// as windows of `min(a.len(), b.len())` bytes, and compared on their first `len` bytes.
     pub fn subarray_equal(
         &mut self,
         a: &[ByteVariable],
@@ -23,10 +32,53 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         b_offset: Variable,
         len: Variable,
     ) -> BoolVariable {
-        todo!();
+        assert!(
+            a.len() < 1 << SUBARRAY_INDEX_BITS && b.len() < 1 << SUBARRAY_INDEX_BITS,
+            "subarray_equal only supports arrays shorter than 2^16 bytes"
+        );
+        for index in [a_offset, b_offset, len] {
+            self.api.range_check(index.0, SUBARRAY_INDEX_BITS);
+        }
+
+        // The offsets and the length fit in 16 bits, so the ends of the subarrays fit in a u32.
+        let a_in_range = self.subarray_in_range(a_offset, len, a.len());
+        let b_in_range = self.subarray_in_range(b_offset, len, b.len());
+        let in_range = self.and(a_in_range, b_in_range);
+
+        // Out of range offsets are replaced by zero, so that the windows can always be selected.
+        let zero = self.zero::<Variable>();
+        let a_offset = self.select(in_range, a_offset, zero);
+        let b_offset = self.select(in_range, b_offset, zero);
+        let a = a
+            .iter()
+            .map(|byte| byte.to_variable(self))
+            .collect::<Vec<_>>();
+        let b = b
+            .iter()
+            .map(|byte| byte.to_variable(self))
+            .collect::<Vec<_>>();
+        let width = a.len().min(b.len());
+        let a_window = self.select_window(&a, a_offset, width, zero);
+        let b_window = self.select_window(&b, b_offset, width, zero);
+
+        let mut equal = in_range;
+        let mut in_len = self._true();
+        for i in 0..width {
+            let index = self.constant::<Variable>(L::Field::from_canonical_usize(i));
+            let is_end = self.is_equal(len, index);
+            let is_not_end = self.not(is_end);
+            in_len = self.and(in_len, is_not_end);
+
+            let bytes_equal = self.is_equal(a_window[i], b_window[i]);
+            let past_len = self.not(in_len);
+            let byte_matches = self.or(past_len, bytes_equal);
+            equal = self.and(equal, byte_matches);
+        }
+        equal
     }
 
-    #[allow(unused_variables, dead_code)]
+    /// Asserts that `a[a_offset..a_offset + len] = b[b_offset..b_offset + len]`, with the same
+    /// bounds as `subarray_equal`.
     pub fn assert_subarray_equal(
         &mut self,
         a: &[ByteVariable],
@@ -35,19 +87,71 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         b_offset: Variable,
         len: Variable,
     ) {
-        // TODO: Need to constrain generator result.
-        let generator: SubarrayEqualGenerator<L, D> = SubarrayEqualGenerator {
-            a: a.to_vec(),
-            a_offset,
-            b: b.to_vec(),
-            b_offset,
-            len,
-            _phantom: PhantomData::<L>,
-        };
-        self.add_simple_generator(generator);
+        let equal = self.subarray_equal(a, a_offset, b, b_offset, len);
+        let t = self._true();
+        self.assert_is_equal(equal, t);
+    }
+
+    /// Returns whether `offset + len <= array_len`, where `offset` and `len` fit in 16 bits.
+    fn subarray_in_range(
+        &mut self,
+        offset: Variable,
+        len: Variable,
+        array_len: usize,
+    ) -> BoolVariable {
+        let end = self.add(offset, len);
+        // Can be unsafe because both terms of the sum fit in 16 bits.
+        let end = U32Variable::from_variables_unsafe(&[end]);
+        let array_len = self.constant::<U32Variable>(array_len as u32);
+        self.lte(end, array_len)
     }
 }
 
+#[cfg(test)]
 pub(crate) mod tests {
-    // TODO add a test for subarray_equal
+    use super::*;
+    use crate::prelude::{ArrayVariable, DefaultBuilder, GoldilocksField};
+
+    #[test]
+    fn test_subarray_equal() {
+        const A_LEN: usize = 6;
+        const B_LEN: usize = 4;
+
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<ArrayVariable<ByteVariable, A_LEN>>();
+        let a_offset = builder.read::<Variable>();
+        let b = builder.read::<ArrayVariable<ByteVariable, B_LEN>>();
+        let b_offset = builder.read::<Variable>();
+        let len = builder.read::<Variable>();
+        let equal = builder.subarray_equal(a.as_slice(), a_offset, b.as_slice(), b_offset, len);
+        builder.write(equal);
+        let circuit = builder.mock_build();
+
+        let a = vec![1u8, 2, 3, 4, 5, 6];
+        let b = vec![3u8, 4, 5, 9];
+        let cases = [
+            (2, 0, 3, true),
+            (2, 0, 4, false),
+            (0, 3, 0, true),
+            (3, 0, 2, false),
+            (5, 3, 1, false),
+            (5, 3, 2, false),
+            (4, 2, 1, true),
+            (6, 4, 0, true),
+            (7, 0, 0, false),
+        ];
+        for (a_offset, b_offset, len, expected) in cases {
+            let mut input = circuit.input();
+            input.write::<ArrayVariable<ByteVariable, A_LEN>>(a.clone());
+            input.write::<Variable>(GoldilocksField::from_canonical_usize(a_offset));
+            input.write::<ArrayVariable<ByteVariable, B_LEN>>(b.clone());
+            input.write::<Variable>(GoldilocksField::from_canonical_usize(b_offset));
+            input.write::<Variable>(GoldilocksField::from_canonical_usize(len));
+            let mut output = circuit.mock_prove(&input).output;
+            let in_range = a_offset + len <= A_LEN && b_offset + len <= B_LEN;
+            let reference = in_range && subarray_equal(&a, a_offset, &b, b_offset, len) == 1;
+            assert_eq!(reference, expected);
+            assert_eq!(output.read::<BoolVariable>(), expected);
+        }
+    }
 }
@@ -0,0 +1,232 @@
+use ethers::types::H160;
+use plonky2::hash::hash_types::RichField;
+
+use crate::frontend::eth::vars::AddressVariable;
+use crate::frontend::vars::VariableBytesVariable;
+use crate::prelude::{
+    ArrayVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitBuilder, CircuitVariable,
+    PlonkParameters, U256Variable, U32Variable, Variable,
+};
+
+/// The type of an EIP-1559 transaction.
+const EIP1559_TX_TYPE: u8 = 0x02;
+
+/// The number of items before `to` in an EIP-1559 transaction: the chain id, the nonce, the two
+/// fees and the gas limit. It is followed by `value` and `data`.
+const EIP1559_TO_INDEX: usize = 5;
+
+/// The number of items of a signed EIP-1559 transaction, which ends with its access list and the
+/// three values of its signature.
+const EIP1559_NB_FIELDS: usize = 12;
+
+/// The recipient, value and calldata of an EIP-1559 transaction. The calldata is given by its
+/// offset and length in the encoding of the transaction.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(Eip1559TxFields)]
+pub struct Eip1559TxFieldsVariable {
+    pub to: AddressVariable,
+    pub value: U256Variable,
+    pub data_offset: U32Variable,
+    pub data_len: U32Variable,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Verifies that the transaction at `tx_index` is in the transactions trie with root
+    /// `transactions_root`, and returns its encoding. Legacy transactions are returned as their
+    /// RLP encoding and EIP-2718 typed transactions as their type followed by their payload.
+    ///
+    /// The transaction, padded to `MAX_TX_LEN` bytes, must fit in a node of `ENCODING_LEN` bytes.
+    pub fn verify_transaction_inclusion<
+        const ENCODING_LEN: usize,
+        const PROOF_LEN: usize,
+        const MAX_TX_LEN: usize,
+    >(
+        &mut self,
+        transactions_root: Bytes32Variable,
+        tx_index: U32Variable,
+        proof: ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>,
+        len_nodes: ArrayVariable<U32Variable, PROOF_LEN>,
+    ) -> VariableBytesVariable<MAX_TX_LEN> {
+        let (key_path, key_len) = self.rlp_index_trie_key(tx_index);
+        let (tx, tx_len, exists) = self.walk_mpt_proof::<ENCODING_LEN, PROOF_LEN, MAX_TX_LEN>(
+            key_path,
+            key_len,
+            proof,
+            len_nodes,
+            transactions_root,
+        );
+        let t = self._true();
+        self.assert_is_equal(exists, t);
+
+        // Can be unsafe because `VariableBytesVariable::new` range checks the length.
+        let tx_len = U32Variable::from_variables_unsafe(&[tx_len]);
+        VariableBytesVariable::new(self, tx.as_slice().try_into().unwrap(), tx_len)
+    }
+
+    /// Decodes the `to`, `value` and `data` fields of an EIP-1559 transaction, as returned by
+    /// `verify_transaction_inclusion`. The fields are decoded in the circuit with
+    /// `decode_rlp_list`, and the recipient of a contract creation is the zero address.
+    pub fn decode_eip1559_tx_fields<const MAX_TX_LEN: usize>(
+        &mut self,
+        tx: &VariableBytesVariable<MAX_TX_LEN>,
+    ) -> Eip1559TxFieldsVariable {
+        let t = self._true();
+        let f = self._false();
+        let tx_type = self.constant::<ByteVariable>(EIP1559_TX_TYPE);
+        self.assert_is_equal(tx.data[0], tx_type);
+
+        // The transaction is its type followed by the RLP encoding of its fields.
+        let zero_byte = self.zero::<ByteVariable>();
+        let mut fields_bytes = tx.data.0[1..].to_vec();
+        fields_bytes.push(zero_byte);
+        let fields_bytes: &[ByteVariable; MAX_TX_LEN] = fields_bytes.as_slice().try_into().unwrap();
+        let one = self.one::<U32Variable>();
+        let fields_len = self.sub(tx.len, one);
+        let fields =
+            self.decode_rlp_list::<MAX_TX_LEN, EIP1559_NB_FIELDS>(fields_bytes, fields_len);
+        let nb_fields = self.constant::<U32Variable>(EIP1559_NB_FIELDS as u32);
+        self.assert_is_equal(fields.nb_items, nb_fields);
+        for i in [EIP1559_TO_INDEX, EIP1559_TO_INDEX + 1, EIP1559_TO_INDEX + 2] {
+            self.assert_is_equal(fields.is_list[i], f);
+        }
+
+        // A contract creation has an empty `to`.
+        let to_len = fields.lens[EIP1559_TO_INDEX];
+        let zero = self.zero::<U32Variable>();
+        let const_20 = self.constant::<U32Variable>(20);
+        let to_is_empty = self.is_equal(to_len, zero);
+        let to_is_address = self.is_equal(to_len, const_20);
+        let to_is_valid = self.or(to_is_empty, to_is_address);
+        self.assert_is_equal(to_is_valid, t);
+        let to = self.select_window(
+            fields_bytes,
+            fields.offsets[EIP1559_TO_INDEX].variable,
+            20,
+            zero_byte,
+        );
+        let to = AddressVariable(BytesVariable(to.try_into().unwrap()));
+        let zero_address = self.constant::<AddressVariable>(H160::zero());
+        let to = self.select(to_is_empty, zero_address, to);
+
+        let value_len = fields.lens[EIP1559_TO_INDEX + 1];
+        let const_32 = self.constant::<U32Variable>(32);
+        let value_fits = self.lte(value_len, const_32);
+        self.assert_is_equal(value_fits, t);
+        let value_bytes = self.select_window(
+            fields_bytes,
+            fields.offsets[EIP1559_TO_INDEX + 1].variable,
+            32,
+            zero_byte,
+        );
+        let value = self.rlp_string_to_uint::<U256Variable>(&value_bytes, value_len.variable);
+
+        // The offset of the data is relative to the transaction, which starts with its type.
+        let data_offset = self.add(fields.offsets[EIP1559_TO_INDEX + 2], one);
+        let data_len = fields.lens[EIP1559_TO_INDEX + 2];
+
+        Eip1559TxFieldsVariable {
+            to,
+            value,
+            data_offset,
+            data_len,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use ethers::types::{Bytes, H256, U256};
+    use serde::Deserialize;
+
+    use super::super::builder::transform_proof_to_padded;
+    use super::*;
+    use crate::prelude::DefaultBuilder;
+    use crate::utils::{address, bytes};
+
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct TransactionFixture {
+        tx_index: u32,
+        proof: Vec<Bytes>,
+        transaction: Bytes,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct TransactionsFixture {
+        transactions_root: H256,
+        transactions: Vec<TransactionFixture>,
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_verify_transaction_inclusion() {
+        // A transactions trie of 130 transactions. Transaction 3 is a legacy ETH transfer and
+        // transaction 129, whose key is two bytes long, is an EIP-1559 USDC transfer.
+        let file = File::open("./src/frontend/eth/mpt/fixtures/transactions.json").unwrap();
+        let fixture: TransactionsFixture = serde_json::from_reader(file).unwrap();
+
+        const ENCODING_LEN: usize = 512;
+        const PROOF_LEN: usize = 6;
+        const MAX_TX_LEN: usize = 192;
+
+        let mut builder = DefaultBuilder::new();
+        let transactions_root = builder.read::<Bytes32Variable>();
+        let tx_index = builder.read::<U32Variable>();
+        let proof =
+            builder.read::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>();
+        let len_nodes = builder.read::<ArrayVariable<U32Variable, PROOF_LEN>>();
+        let tx = builder.verify_transaction_inclusion::<ENCODING_LEN, PROOF_LEN, MAX_TX_LEN>(
+            transactions_root,
+            tx_index,
+            proof,
+            len_nodes,
+        );
+        builder.write(tx);
+        let circuit = builder.mock_build();
+
+        let mut builder = DefaultBuilder::new();
+        let tx = builder.read::<VariableBytesVariable<MAX_TX_LEN>>();
+        let fields = builder.decode_eip1559_tx_fields(&tx);
+        builder.write(fields);
+        let decode_circuit = builder.mock_build();
+
+        for case in fixture.transactions.iter() {
+            let proof = case.proof.iter().map(|b| b.to_vec()).collect();
+            let (proof_as_fixed, lengths_as_fixed) =
+                transform_proof_to_padded::<ENCODING_LEN, PROOF_LEN>(proof);
+
+            let mut input = circuit.input();
+            input.write::<Bytes32Variable>(fixture.transactions_root);
+            input.write::<U32Variable>(case.tx_index);
+            input.write::<ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>>(
+                proof_as_fixed,
+            );
+            input.write::<ArrayVariable<U32Variable, PROOF_LEN>>(
+                lengths_as_fixed.iter().map(|x| *x as u32).collect(),
+            );
+            let mut output = circuit.mock_prove(&input).output;
+            let tx = output.read::<VariableBytesVariable<MAX_TX_LEN>>();
+            assert_eq!(tx, case.transaction.to_vec());
+
+            if tx[0] != EIP1559_TX_TYPE {
+                continue;
+            }
+            let mut input = decode_circuit.input();
+            input.write::<VariableBytesVariable<MAX_TX_LEN>>(tx.clone());
+            let mut output = decode_circuit.mock_prove(&input).output;
+            let fields = output.read::<Eip1559TxFieldsVariable>();
+            assert_eq!(fields.to, address!(USDC));
+            assert_eq!(fields.value, U256::zero());
+            let data_offset = fields.data_offset as usize;
+            let data_len = fields.data_len as usize;
+            let transfer_data: Vec<u8> = bytes!("0xa9059cbb000000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa9604500000000000000000000000000000000000000000000000000000000499602d2");
+            assert_eq!(data_len, 68);
+            assert_eq!(tx[data_offset..data_offset + data_len], transfer_data[..]);
+        }
+    }
+}
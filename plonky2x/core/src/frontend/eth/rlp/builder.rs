@@ -2,8 +2,7 @@ use serde::{Deserialize, Serialize};
 use starkyx::math::field::Field;
 use starkyx::math::prelude::PrimeField64;
 
-use super::utils::decode_padded_mpt_node;
-use crate::frontend::eth::rlp::utils::MAX_RLP_ITEM_SIZE;
+use super::utils::decode_padded_list;
 use crate::frontend::hint::simple::hint::Hint;
 use crate::prelude::{
     ArrayVariable, BoolVariable, ByteVariable, CircuitBuilder, PlonkParameters, ValueStream,
@@ -16,38 +15,41 @@ use crate::prelude::{
 /// `ENCODING_LEN` and is specified as a type parameter. The "true" length of the encoding is given
 /// in the stream. `LIST_LEN` specifies the node size. (e.g., 2 for extension/leaf nodes, 17 for
 /// branch nodes.) The decoded string is returned as a padded 2-dimensional byte array
-/// (`MAX_RLP_ITEM_SIZE` x `LIST_LEN`).
+/// (`ELEMENT_LEN` x `LIST_LEN`).
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct DecodeHint<const ENCODING_LEN: usize, const LIST_LEN: usize> {}
-impl<L: PlonkParameters<D>, const D: usize, const ENCODING_LEN: usize, const LIST_LEN: usize>
-    Hint<L, D> for DecodeHint<ENCODING_LEN, LIST_LEN>
+struct DecodeHint<const ENCODING_LEN: usize, const LIST_LEN: usize, const ELEMENT_LEN: usize> {}
+impl<
+        L: PlonkParameters<D>,
+        const D: usize,
+        const ENCODING_LEN: usize,
+        const LIST_LEN: usize,
+        const ELEMENT_LEN: usize,
+    > Hint<L, D> for DecodeHint<ENCODING_LEN, LIST_LEN, ELEMENT_LEN>
 {
     fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
         let encoded = input_stream.read_value::<ArrayVariable<ByteVariable, ENCODING_LEN>>();
         let len = input_stream.read_value::<Variable>();
         let skip_computation = input_stream.read_value::<BoolVariable>();
 
-        let decoded =
-            decode_padded_mpt_node(&encoded, len.as_canonical_u64() as usize, skip_computation);
+        let (decoded_list, decoded_element_lens, len_decoded_list) = decode_padded_list(
+            &encoded,
+            len.as_canonical_u64() as usize,
+            skip_computation,
+            LIST_LEN,
+            ELEMENT_LEN,
+        );
 
         output_stream
-            .write_value::<ArrayVariable<ArrayVariable<ByteVariable, MAX_RLP_ITEM_SIZE>, LIST_LEN>>(
-                decoded
-                    .data
-                    .iter()
-                    .map(|x| x.data.to_vec())
-                    .take(LIST_LEN)
-                    .collect::<Vec<_>>(),
+            .write_value::<ArrayVariable<ArrayVariable<ByteVariable, ELEMENT_LEN>, LIST_LEN>>(
+                decoded_list,
             );
         output_stream.write_value::<ArrayVariable<Variable, LIST_LEN>>(
-            decoded
-                .data
-                .iter()
-                .map(|x| L::Field::from_canonical_usize(x.len))
-                .take(LIST_LEN)
+            decoded_element_lens
+                .into_iter()
+                .map(L::Field::from_canonical_usize)
                 .collect::<Vec<_>>(),
         );
-        output_stream.write_value::<Variable>(L::Field::from_canonical_usize(decoded.len));
+        output_stream.write_value::<Variable>(L::Field::from_canonical_usize(len_decoded_list));
     }
 }
 
@@ -71,7 +73,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         input_stream.write(&len);
         input_stream.write(&skip_computation);
 
-        let hint = DecodeHint::<ENCODING_LEN, LIST_LEN> {};
+        let hint = DecodeHint::<ENCODING_LEN, LIST_LEN, ELEMENT_LEN> {};
 
         let output_stream = self.hint(input_stream, hint);
         let decoded_list = output_stream
@@ -89,6 +91,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
 mod tests {
     use super::*;
     use crate::backend::circuit::DefaultParameters;
+    use crate::frontend::eth::rlp::utils::{decode_padded_mpt_node, MAX_RLP_ITEM_SIZE};
     use crate::prelude::{DefaultBuilder, GoldilocksField};
     use crate::utils::bytes;
 
@@ -101,7 +104,8 @@ mod tests {
         const ENCODING_LEN: usize = 600;
         const LIST_LEN: usize = 17;
 
-        let hint: DecodeHint<ENCODING_LEN, LIST_LEN> = DecodeHint::<ENCODING_LEN, LIST_LEN> {};
+        let hint: DecodeHint<ENCODING_LEN, LIST_LEN, MAX_RLP_ITEM_SIZE> =
+            DecodeHint::<ENCODING_LEN, LIST_LEN, MAX_RLP_ITEM_SIZE> {};
         let encoded = builder.read::<ArrayVariable<ByteVariable, ENCODING_LEN>>();
         let len = builder.read::<Variable>();
         let skip_computation = builder.read::<BoolVariable>();
@@ -167,7 +171,8 @@ mod tests {
         const ENCODING_LEN: usize = 600;
         const LIST_LEN: usize = 2;
 
-        let hint: DecodeHint<ENCODING_LEN, LIST_LEN> = DecodeHint::<ENCODING_LEN, LIST_LEN> {};
+        let hint: DecodeHint<ENCODING_LEN, LIST_LEN, MAX_RLP_ITEM_SIZE> =
+            DecodeHint::<ENCODING_LEN, LIST_LEN, MAX_RLP_ITEM_SIZE> {};
         let encoded = builder.read::<ArrayVariable<ByteVariable, ENCODING_LEN>>();
         let len = builder.read::<Variable>();
         let skip_computation = builder.read::<BoolVariable>();
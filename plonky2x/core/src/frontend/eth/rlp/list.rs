@@ -0,0 +1,482 @@
+//! A constrained RLP list decoder, where the boundaries of the items are only known at proving
+//! time.
+//!
+//! Unlike `decode_element_as_list`, the decoding is not a hint: every prefix is decoded in the
+//! circuit and the items are constrained to tile the payload of the list exactly.
+
+use array_macro::array;
+use itertools::Itertools;
+use plonky2::hash::hash_types::RichField;
+use starkyx::math::field::Field;
+
+use crate::frontend::vars::VariableBytesVariable;
+use crate::prelude::{
+    ArrayVariable, BoolVariable, ByteVariable, CircuitBuilder, CircuitVariable, PlonkParameters,
+    U32Variable, Variable,
+};
+
+/// The maximum number of bytes of the length of a long string or list, so payloads must be
+/// shorter than 2^24 bytes.
+const MAX_RLP_LEN_BYTES: usize = 3;
+
+/// The items of a decoded RLP list, given by the offsets and the lengths of their payloads in the
+/// encoding of the list. The payload of a nested list is the encoding of its items. The entries
+/// past `nb_items` are zero.
+#[derive(Debug, Clone, CircuitVariable)]
+#[value_name(RlpList)]
+pub struct RlpListVariable<const MAX_ITEMS: usize> {
+    pub offsets: ArrayVariable<U32Variable, MAX_ITEMS>,
+    pub lens: ArrayVariable<U32Variable, MAX_ITEMS>,
+    pub is_list: ArrayVariable<BoolVariable, MAX_ITEMS>,
+    pub nb_items: U32Variable,
+}
+
+impl<const MAX_ITEMS: usize> RlpListVariable<MAX_ITEMS> {
+    /// Returns the payload of the item at index `i` of the list decoded from `input`, asserting
+    /// that `i` is smaller than the number of items and that the payload has at most `MAX_ITEM`
+    /// bytes.
+    pub fn get_item_bytes<L: PlonkParameters<D>, const D: usize, const MAX_ITEM: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        input: &[ByteVariable],
+        i: U32Variable,
+    ) -> VariableBytesVariable<MAX_ITEM> {
+        let is_in_range = builder.lt(i, self.nb_items);
+        let t = builder._true();
+        builder.assert_is_equal(is_in_range, t);
+        let offset = builder.select_array(self.offsets.as_slice(), i.variable);
+        let len = builder.select_array(self.lens.as_slice(), i.variable);
+
+        // Pad the input so that the bytes past the end of the item stay in range.
+        let zero = builder.zero::<ByteVariable>();
+        let mut padded = input.to_vec();
+        padded.resize(input.len() + MAX_ITEM, zero);
+        let data = array![j => {
+            let j = builder.constant::<Variable>(L::Field::from_canonical_usize(j));
+            let index = builder.add(offset.variable, j);
+            builder.select_array(&padded, index)
+        }; MAX_ITEM];
+        VariableBytesVariable::new(builder, data, len)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Decodes the header of an RLP item from its first `1 + MAX_RLP_LEN_BYTES` bytes.
+    ///
+    /// Returns the length of the header, the length of the payload, whether the item is a list
+    /// and whether the header is a canonical encoding.
+    fn decode_rlp_header(
+        &mut self,
+        bytes: &[Variable],
+    ) -> (Variable, Variable, BoolVariable, BoolVariable) {
+        // Can be unsafe because the variables come from bytes.
+        let prefix = U32Variable::from_variables_unsafe(&[bytes[0]]);
+        let first_byte = U32Variable::from_variables_unsafe(&[bytes[1]]);
+
+        let const_0x80 = self.constant::<U32Variable>(0x80);
+        let const_0xb8 = self.constant::<U32Variable>(0xb8);
+        let const_0xc0 = self.constant::<U32Variable>(0xc0);
+        let const_0xf8 = self.constant::<U32Variable>(0xf8);
+        let is_at_least_0x80 = self.lte(const_0x80, prefix);
+        let is_at_least_0xb8 = self.lte(const_0xb8, prefix);
+        let is_at_least_0xc0 = self.lte(const_0xc0, prefix);
+        let is_at_least_0xf8 = self.lte(const_0xf8, prefix);
+
+        let is_single_byte = self.not(is_at_least_0x80);
+        let is_list = is_at_least_0xc0;
+        let is_string = self.not(is_at_least_0xc0);
+        let is_short = self.not(is_at_least_0xb8);
+        let is_short_string = self.and(is_at_least_0x80, is_short);
+        let is_long_string = self.and(is_at_least_0xb8, is_string);
+        let is_long = self.or(is_long_string, is_at_least_0xf8);
+
+        // The prefix minus its base is the length of a short payload, or the number of bytes of
+        // the length of a long one.
+        let base_short_string = self.constant::<Variable>(L::Field::from_canonical_u8(0x80));
+        let base_long_string = self.constant::<Variable>(L::Field::from_canonical_u8(0xb7));
+        let base_short_list = self.constant::<Variable>(L::Field::from_canonical_u8(0xc0));
+        let base_long_list = self.constant::<Variable>(L::Field::from_canonical_u8(0xf7));
+        let base = self.select(is_at_least_0xb8, base_long_string, base_short_string);
+        let base = self.select(is_at_least_0xc0, base_short_list, base);
+        let base = self.select(is_at_least_0xf8, base_long_list, base);
+        let diff = self.sub(bytes[0], base);
+
+        let zero = self.zero::<Variable>();
+        let one = self.one::<Variable>();
+        let const_256 = self.constant::<Variable>(L::Field::from_canonical_usize(256));
+        let mut long_len = zero;
+        let mut acc = zero;
+        let mut has_valid_nb_len_bytes = self._false();
+        for (i, byte) in bytes.iter().enumerate().take(1 + MAX_RLP_LEN_BYTES).skip(1) {
+            let shifted = self.mul(acc, const_256);
+            acc = self.add(shifted, *byte);
+            let nb_len_bytes = self.constant::<Variable>(L::Field::from_canonical_usize(i));
+            let is_nb_len_bytes = self.is_equal(diff, nb_len_bytes);
+            long_len = self.select(is_nb_len_bytes, acc, long_len);
+            has_valid_nb_len_bytes = self.or(has_valid_nb_len_bytes, is_nb_len_bytes);
+        }
+
+        // A long length has no leading zeros and is above 55, and a single byte below 0x80 is its
+        // own encoding.
+        let first_byte_is_zero = self.is_zero(bytes[1]);
+        let first_byte_is_nonzero = self.not(first_byte_is_zero);
+        let const_55 = self.constant::<U32Variable>(55);
+        let long_len_u32 = U32Variable::from_variables_unsafe(&[long_len]);
+        let long_len_is_long = self.lt(const_55, long_len_u32);
+        let long_is_valid = self.and(has_valid_nb_len_bytes, first_byte_is_nonzero);
+        let long_is_valid = self.and(long_is_valid, long_len_is_long);
+        let is_not_long = self.not(is_long);
+        let long_is_valid = self.or(long_is_valid, is_not_long);
+
+        let is_len_one = self.is_equal(diff, one);
+        let is_one_byte_string = self.and(is_short_string, is_len_one);
+        let is_not_one_byte_string = self.not(is_one_byte_string);
+        let first_byte_is_high = self.lte(const_0x80, first_byte);
+        let one_byte_is_valid = self.or(is_not_one_byte_string, first_byte_is_high);
+        let is_valid = self.and(long_is_valid, one_byte_is_valid);
+
+        let long_header_len = self.add(one, diff);
+        let header_len = self.select(is_long, long_header_len, one);
+        let header_len = self.select(is_single_byte, zero, header_len);
+        let payload_len = self.select(is_long, long_len, diff);
+        let payload_len = self.select(is_single_byte, one, payload_len);
+
+        (header_len, payload_len, is_list, is_valid)
+    }
+
+    /// Decodes the RLP list in the first `len` bytes of `input`, returning the offset and the
+    /// length of the payload of each of its items.
+    ///
+    /// Every prefix is asserted to be canonical, and the items to tile the payload of the list
+    /// exactly, so the list must have at most `MAX_ITEMS` items. The items of nested lists are not
+    /// decoded, but the payload of a nested list can itself be decoded with `decode_rlp_list`.
+    pub fn decode_rlp_list<const MAX_LEN: usize, const MAX_ITEMS: usize>(
+        &mut self,
+        input: &[ByteVariable; MAX_LEN],
+        len: U32Variable,
+    ) -> RlpListVariable<MAX_ITEMS> {
+        let t = self._true();
+        let bytes = self.pad_rlp_input(input, len);
+        let (header_len, payload_len, is_list, is_valid) =
+            self.decode_rlp_header(&bytes[..1 + MAX_RLP_LEN_BYTES]);
+        self.assert_is_equal(is_valid, t);
+        self.assert_is_equal(is_list, t);
+        let list_len = self.add(header_len, payload_len);
+        self.assert_is_equal(list_len, len.variable);
+
+        self.decode_rlp_items(&bytes, header_len, len)
+    }
+
+    /// Decodes the payload of an RLP list in `input[offset..offset + len]`, which is the
+    /// concatenation of the encodings of its items, e.g. the payload of a nested list returned by
+    /// `decode_rlp_list`. The offsets of the items are relative to `input`.
+    ///
+    /// The items are checked as in `decode_rlp_list`.
+    pub fn decode_rlp_list_payload<const MAX_LEN: usize, const MAX_ITEMS: usize>(
+        &mut self,
+        input: &[ByteVariable; MAX_LEN],
+        offset: U32Variable,
+        len: U32Variable,
+    ) -> RlpListVariable<MAX_ITEMS> {
+        let t = self._true();
+        let max_len = self.constant::<U32Variable>(MAX_LEN as u32);
+        let offset_fits = self.lte(offset, max_len);
+        self.assert_is_equal(offset_fits, t);
+        let end = self.add(offset, len);
+        let bytes = self.pad_rlp_input(input, end);
+        self.decode_rlp_items(&bytes, offset.variable, end)
+    }
+
+    /// Asserts that `len <= MAX_LEN` and returns the bytes of `input` as variables, padded so that
+    /// reading a header at the end of the input stays in range.
+    fn pad_rlp_input<const MAX_LEN: usize>(
+        &mut self,
+        input: &[ByteVariable; MAX_LEN],
+        len: U32Variable,
+    ) -> Vec<Variable> {
+        let t = self._true();
+        let max_len = self.constant::<U32Variable>(MAX_LEN as u32);
+        let len_fits = self.lte(len, max_len);
+        self.assert_is_equal(len_fits, t);
+
+        let zero = self.zero::<Variable>();
+        let mut bytes = input.iter().map(|b| b.to_variable(self)).collect_vec();
+        bytes.resize(MAX_LEN + 1 + MAX_RLP_LEN_BYTES, zero);
+        bytes
+    }
+
+    /// Decodes the items encoded in `bytes[start..end]`, asserting that they tile it exactly.
+    fn decode_rlp_items<const MAX_ITEMS: usize>(
+        &mut self,
+        bytes: &[Variable],
+        start: Variable,
+        end: U32Variable,
+    ) -> RlpListVariable<MAX_ITEMS> {
+        let t = self._true();
+        let f = self._false();
+        let zero = self.zero::<Variable>();
+        let mut cursor = start;
+        let mut nb_items = zero;
+        let mut offsets = Vec::new();
+        let mut lens = Vec::new();
+        let mut is_lists = Vec::new();
+        for _ in 0..MAX_ITEMS {
+            // Can be unsafe because the cursor is a sum of at most `MAX_ITEMS + 1` lengths below
+            // 2^24, and the list is rejected at the end if it went past `end`.
+            let cursor_u32 = U32Variable::from_variables_unsafe(&[cursor]);
+            let is_item = self.lt(cursor_u32, end);
+            let header = (0..=MAX_RLP_LEN_BYTES)
+                .map(|i| {
+                    let i = self.constant::<Variable>(L::Field::from_canonical_usize(i));
+                    let index = self.add(cursor, i);
+                    self.select_array(bytes, index)
+                })
+                .collect_vec();
+            let (item_header_len, item_payload_len, item_is_list, item_is_valid) =
+                self.decode_rlp_header(&header);
+            let is_not_item = self.not(is_item);
+            let is_valid = self.or(item_is_valid, is_not_item);
+            self.assert_is_equal(is_valid, t);
+
+            let offset = self.add(cursor, item_header_len);
+            let next_cursor = self.add(offset, item_payload_len);
+            let offset = self.select(is_item, offset, zero);
+            let item_len = self.select(is_item, item_payload_len, zero);
+            offsets.push(U32Variable::from_variables_unsafe(&[offset]));
+            lens.push(U32Variable::from_variables_unsafe(&[item_len]));
+            is_lists.push(self.select(is_item, item_is_list, f));
+            cursor = self.select(is_item, next_cursor, cursor);
+            nb_items = self.add(nb_items, is_item.variable);
+        }
+
+        // The items must end exactly at the end of the list, which also rejects lists with more
+        // than `MAX_ITEMS` items.
+        self.assert_is_equal(cursor, end.variable);
+
+        RlpListVariable {
+            offsets: ArrayVariable::new(offsets),
+            lens: ArrayVariable::new(lens),
+            is_list: ArrayVariable::new(is_lists),
+            nb_items: U32Variable::from_variables_unsafe(&[nb_items]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::utils::rlp::{Rlp, RlpStream};
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::prelude::DefaultBuilder;
+    use crate::utils::bytes;
+
+    fn random_bytes(rng: &mut impl Rng, min_len: usize, max_len: usize) -> Vec<u8> {
+        let len = rng.gen_range(min_len..=max_len);
+        (0..len).map(|_| rng.gen()).collect()
+    }
+
+    /// Returns a random list of up to `max_items` single bytes, short and long strings and
+    /// nested lists, encoded in at most `max_len` bytes.
+    fn random_rlp_list(rng: &mut impl Rng, max_len: usize, max_items: usize) -> Vec<u8> {
+        loop {
+            let nb_items = rng.gen_range(0..=max_items);
+            let mut stream = RlpStream::new_list(nb_items);
+            for _ in 0..nb_items {
+                match rng.gen_range(0..4) {
+                    0 => stream.append(&vec![rng.gen_range(0..0x80u8)]),
+                    1 => stream.append(&random_bytes(rng, 0, 55)),
+                    2 => stream.append(&random_bytes(rng, 56, 100)),
+                    _ => stream
+                        .begin_list(2)
+                        .append(&random_bytes(rng, 0, 8))
+                        .append(&random_bytes(rng, 0, 8)),
+                };
+            }
+            let encoding = stream.out().to_vec();
+            if encoding.len() <= max_len {
+                return encoding;
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_decode_rlp_list_fuzz() {
+        const MAX_LEN: usize = 320;
+        const MAX_ITEMS: usize = 8;
+
+        let mut builder = DefaultBuilder::new();
+        let input = builder.read::<[ByteVariable; MAX_LEN]>();
+        let len = builder.read::<U32Variable>();
+        let list = builder.decode_rlp_list::<MAX_LEN, MAX_ITEMS>(&input, len);
+        builder.write(list);
+        let circuit = builder.mock_build();
+
+        let mut rng = thread_rng();
+        let mut encodings = vec![bytes!("0xc0")];
+        encodings.extend((0..16).map(|_| random_rlp_list(&mut rng, MAX_LEN, MAX_ITEMS)));
+        // A long list with a two-byte length.
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&vec![0xaau8; 200]).append(&vec![0xbbu8; 60]);
+        encodings.push(stream.out().to_vec());
+
+        for encoding in encodings {
+            let mut padded = encoding.clone();
+            padded.resize(MAX_LEN, 0);
+            let mut input = circuit.input();
+            input.write::<[ByteVariable; MAX_LEN]>(padded.try_into().unwrap());
+            input.write::<U32Variable>(encoding.len() as u32);
+            let mut output = circuit.mock_prove(&input).output;
+            let list = output.read::<RlpListVariable<MAX_ITEMS>>();
+
+            let rlp = Rlp::new(&encoding);
+            let mut offset = rlp.payload_info().unwrap().header_len;
+            assert_eq!(list.nb_items as usize, rlp.item_count().unwrap());
+            for (i, item) in rlp.iter().enumerate() {
+                let info = item.payload_info().unwrap();
+                assert_eq!(list.offsets[i] as usize, offset + info.header_len);
+                assert_eq!(list.lens[i] as usize, info.value_len);
+                assert_eq!(list.is_list[i], item.is_list());
+                offset += info.header_len + info.value_len;
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_rlp_list_get_item_bytes() {
+        const MAX_LEN: usize = 128;
+        const MAX_ITEMS: usize = 4;
+        const MAX_ITEM: usize = 32;
+
+        // An account: a single byte nonce, a 9 byte balance and two hashes, in a long list.
+        let items: Vec<Vec<u8>> = vec![
+            vec![0x01],
+            bytes!("0x0de0b6b3a763fffff1"),
+            bytes!("0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"),
+            bytes!("0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"),
+        ];
+        let mut stream = RlpStream::new_list(items.len());
+        for item in items.iter() {
+            stream.append(item);
+        }
+        let encoding = stream.out().to_vec();
+
+        let mut builder = DefaultBuilder::new();
+        let input = builder.read::<[ByteVariable; MAX_LEN]>();
+        let len = builder.read::<U32Variable>();
+        let index = builder.read::<U32Variable>();
+        let list = builder.decode_rlp_list::<MAX_LEN, MAX_ITEMS>(&input, len);
+        let item: VariableBytesVariable<MAX_ITEM> =
+            list.get_item_bytes(&mut builder, &input, index);
+        builder.write(item);
+        let circuit = builder.mock_build();
+
+        let mut padded = encoding.clone();
+        padded.resize(MAX_LEN, 0);
+        for (i, item) in items.iter().enumerate() {
+            let mut input = circuit.input();
+            input.write::<[ByteVariable; MAX_LEN]>(padded.clone().try_into().unwrap());
+            input.write::<U32Variable>(encoding.len() as u32);
+            input.write::<U32Variable>(i as u32);
+            let mut output = circuit.mock_prove(&input).output;
+            assert_eq!(output.read::<VariableBytesVariable<MAX_ITEM>>(), *item);
+        }
+    }
+
+    #[test]
+    fn test_decode_rlp_list_payload() {
+        const MAX_LEN: usize = 16;
+        const MAX_ITEMS: usize = 4;
+
+        let mut builder = DefaultBuilder::new();
+        let input = builder.read::<[ByteVariable; MAX_LEN]>();
+        let offset = builder.read::<U32Variable>();
+        let len = builder.read::<U32Variable>();
+        let list = builder.decode_rlp_list_payload::<MAX_LEN, MAX_ITEMS>(&input, offset, len);
+        builder.write(list);
+        let circuit = builder.mock_build();
+
+        // The payload of a list whose last item is a nested list.
+        let encoding: Vec<u8> = bytes!("0xc6010281ffc180");
+        let mut padded = encoding.clone();
+        padded.resize(MAX_LEN, 0);
+        let mut input = circuit.input();
+        input.write::<[ByteVariable; MAX_LEN]>(padded.try_into().unwrap());
+        input.write::<U32Variable>(1);
+        input.write::<U32Variable>(encoding.len() as u32 - 1);
+        let mut output = circuit.mock_prove(&input).output;
+        let list = output.read::<RlpListVariable<MAX_ITEMS>>();
+        assert_eq!(list.nb_items, 4);
+        assert_eq!(list.offsets, vec![1, 2, 4, 6]);
+        assert_eq!(list.lens, vec![1, 1, 1, 1]);
+        assert_eq!(list.is_list, vec![false, false, false, true]);
+    }
+
+    fn prove_decode_rlp_list(encoding: &str) {
+        const MAX_LEN: usize = 16;
+        const MAX_ITEMS: usize = 4;
+
+        let mut builder = DefaultBuilder::new();
+        let input = builder.read::<[ByteVariable; MAX_LEN]>();
+        let len = builder.read::<U32Variable>();
+        builder.decode_rlp_list::<MAX_LEN, MAX_ITEMS>(&input, len);
+        let circuit = builder.build();
+
+        let encoding: Vec<u8> = bytes!(encoding);
+        let mut padded = encoding.clone();
+        padded.resize(MAX_LEN, 0);
+        let mut input = circuit.input();
+        input.write::<[ByteVariable; MAX_LEN]>(padded.try_into().unwrap());
+        input.write::<U32Variable>(encoding.len() as u32);
+        circuit.prove(&input);
+    }
+
+    #[test]
+    fn test_decode_rlp_list_valid() {
+        prove_decode_rlp_list("0xc6010281ffc180");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decode_rlp_list_not_a_list() {
+        prove_decode_rlp_list("0x820102");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decode_rlp_list_wrong_length() {
+        prove_decode_rlp_list("0xc30102");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decode_rlp_list_item_overflows_list() {
+        prove_decode_rlp_list("0xc484010203");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decode_rlp_list_non_canonical_single_byte() {
+        prove_decode_rlp_list("0xc28105");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decode_rlp_list_non_canonical_long_string() {
+        prove_decode_rlp_list("0xc3b80105");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decode_rlp_list_leading_zero_length() {
+        prove_decode_rlp_list("0xf800");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decode_rlp_list_too_many_items() {
+        prove_decode_rlp_list("0xc50102030405");
+    }
+}
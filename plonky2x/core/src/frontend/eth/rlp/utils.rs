@@ -98,6 +98,54 @@ pub fn decode_padded_mpt_node(
     MPTNodeFixedSize::from(decode(&encoded[..len]))
 }
 
+/// This decodes a padded encoding of a list of strings and returns its items, each padded with
+/// zeros to `element_len` bytes, along with their lengths and the number of items.
+///
+/// Unlike `decode_padded_mpt_node`, the items may be longer than `MAX_RLP_ITEM_SIZE` and the list
+/// may have any number of items up to `list_len` (e.g., the 4 fields of an account).
+pub fn decode_padded_list(
+    encoded: &[u8],
+    len: usize,
+    skip_computation: bool,
+    list_len: usize,
+    element_len: usize,
+) -> (Vec<Vec<u8>>, Vec<usize>, usize) {
+    let mut data = vec![vec![0u8; element_len]; list_len];
+    let mut lens = vec![0usize; list_len];
+    if skip_computation {
+        return (data, lens, 0);
+    }
+
+    let items = match decode(&encoded[..len]) {
+        RLPItem::List(items) => items,
+        RLPItem::String(_) => panic!("expected a list"),
+    };
+    assert!(
+        items.len() <= list_len,
+        "list has {} items, but list_len is {}",
+        items.len(),
+        list_len
+    );
+    for (i, item) in items.iter().enumerate() {
+        match item {
+            RLPItem::String(item) => {
+                assert!(
+                    item.len() <= element_len,
+                    "item {} has {} bytes, but element_len is {}",
+                    i,
+                    item.len(),
+                    element_len
+                );
+                data[i][..item.len()].copy_from_slice(item);
+                lens[i] = item.len();
+            }
+            // This is when a node references another node directly.
+            RLPItem::List(_) => panic!("not implemented yet"),
+        }
+    }
+    (data, lens, items.len())
+}
+
 /// This calculates the prefix and the length of the encoding that we would get if we were to encode
 /// the given string.
 ///
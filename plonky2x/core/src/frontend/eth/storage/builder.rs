@@ -73,6 +73,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
 #[cfg(test)]
 mod tests {
     use std::env;
+    use std::path::PathBuf;
 
     use ethers::providers::{Http, Provider};
     use ethers::types::{U256, U64};
@@ -83,6 +84,7 @@ mod tests {
     use crate::frontend::eth::storage::utils::get_map_storage_location;
     use crate::frontend::eth::storage::vars::{EthHeader, EthLog};
     use crate::prelude::DefaultBuilder;
+    use crate::utils::eth::cache::RpcCacheMode;
     use crate::utils::{self, address, bytes32};
 
     type L = DefaultParameters;
@@ -93,13 +95,14 @@ mod tests {
     #[allow(non_snake_case)]
     fn test_eth_get_storage_at_witness() {
         utils::setup_logger();
-        dotenv::dotenv().ok();
-        let rpc_url = env::var("RPC_1").unwrap();
-        let provider = Provider::<Http>::try_from(rpc_url).unwrap();
 
-        // This is the circuit definition
+        // The storage proof is replayed from a recorded response, so the test runs offline. The
+        // response is trimmed to the value read by the hint.
         let mut builder = DefaultBuilder::new();
-        builder.set_execution_client(provider);
+        builder.set_execution_cache(
+            1,
+            RpcCacheMode::Replay(PathBuf::from("./src/frontend/eth/storage/fixtures/rpc")),
+        );
         let block_hash = builder.evm_read::<Bytes32Variable>();
         let address = builder.evm_read::<AddressVariable>();
         let location = builder.evm_read::<Bytes32Variable>();
@@ -1,7 +1,6 @@
 use core::fmt::Debug;
 use core::marker::PhantomData;
 
-use ethers::providers::Middleware;
 use ethers::types::{Block, H256};
 use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
 use plonky2::iop::target::Target;
@@ -68,7 +67,7 @@ impl<L: PlonkParameters<D>, const D: usize> SimpleGenerator<L::Field, D>
         let result: Block<H256> = rt
             .block_on(async {
                 provider
-                    .get_block(block_hash)
+                    .get_block(block_hash.into())
                     .await
                     .expect("Failed to get block from RPC")
             })
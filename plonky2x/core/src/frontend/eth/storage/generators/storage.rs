@@ -2,7 +2,6 @@ use core::fmt::Debug;
 use core::marker::PhantomData;
 
 use async_trait::async_trait;
-use ethers::providers::Middleware;
 use ethers::types::{EIP1186ProofResponse, TransactionReceipt};
 use futures::executor;
 use log::debug;
@@ -24,11 +23,13 @@ use crate::frontend::eth::vars::AddressVariable;
 use crate::frontend::hint::asynchronous::hint::AsyncHint;
 use crate::frontend::uint::uint256::U256Variable;
 use crate::frontend::vars::{Bytes32Variable, CircuitVariable, ValueStream};
-use crate::utils::eth::get_provider;
+use crate::utils::eth::cache::RpcCacheMode;
+use crate::utils::eth::{get_provider, get_provider_with_cache};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EthStorageProofHint<L: PlonkParameters<D>, const D: usize> {
     chain_id: u64,
+    rpc_cache_mode: Option<RpcCacheMode>,
     _phantom: PhantomData<L>,
 }
 
@@ -37,6 +38,7 @@ impl<L: PlonkParameters<D>, const D: usize> EthStorageProofHint<L, D> {
         let chain_id = builder.get_chain_id();
         EthStorageProofHint {
             chain_id,
+            rpc_cache_mode: builder.rpc_cache_mode.clone(),
             _phantom: PhantomData::<L>,
         }
     }
@@ -53,7 +55,11 @@ impl<L: PlonkParameters<D>, const D: usize> AsyncHint<L, D> for EthStorageProofH
         let address = input_stream.read_value::<AddressVariable>();
         let location = input_stream.read_value::<Bytes32Variable>();
 
-        let provider = get_provider(self.chain_id);
+        let cache_mode = self
+            .rpc_cache_mode
+            .clone()
+            .unwrap_or_else(RpcCacheMode::from_env);
+        let provider = get_provider_with_cache(self.chain_id, cache_mode);
         let result = provider
             .get_proof(address, vec![location], Some(block_hash.into()))
             .await
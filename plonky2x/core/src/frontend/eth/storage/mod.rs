@@ -1,4 +1,5 @@
 pub mod builder;
 pub mod generators;
+pub mod slot;
 pub mod utils;
 pub mod vars;
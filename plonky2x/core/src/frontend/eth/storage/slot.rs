@@ -0,0 +1,139 @@
+//! Derivation of the storage slots of Solidity state variables, following the layout of the
+//! Solidity compiler:
+//!
+//! - the value of `mapping[key]` for a mapping at slot `p` is at `keccak256(abi.encode(key, p))`,
+//! - the elements of a dynamic array at slot `p` start at `keccak256(p)`, and each one takes a
+//!   whole number of words.
+
+use ethers::types::U256;
+
+use crate::frontend::uint::uint256::U256Variable;
+use crate::frontend::vars::EvmVariable;
+use crate::prelude::{Bytes32Variable, CircuitBuilder, PlonkParameters};
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns the slot of `mapping[key]` for a mapping at `base_slot`.
+    ///
+    /// Keys of value types (addresses, integers, `bytes32`, ...) must be left padded to 32 bytes,
+    /// as `abi.encode` does.
+    pub fn mapping_slot(
+        &mut self,
+        key: Bytes32Variable,
+        base_slot: Bytes32Variable,
+    ) -> Bytes32Variable {
+        let mut input = key.as_bytes().to_vec();
+        input.extend_from_slice(&base_slot.as_bytes());
+        self.keccak256(&input)
+    }
+
+    /// Returns the slot of `mapping[keys[0]][keys[1]]...` for nested mappings at `base_slot`, such
+    /// as the allowances of an ERC-20 token.
+    pub fn nested_mapping_slot(
+        &mut self,
+        keys: &[Bytes32Variable],
+        base_slot: Bytes32Variable,
+    ) -> Bytes32Variable {
+        keys.iter()
+            .fold(base_slot, |slot, key| self.mapping_slot(*key, slot))
+    }
+
+    /// Returns the first slot of `array[index]` for a dynamic array at `base_slot`, whose elements
+    /// take `ELEMENT_SIZE_WORDS` words each. Arrays of elements packed several per word are not
+    /// supported.
+    ///
+    /// The slot wraps around modulo 2^256, like in the EVM.
+    pub fn array_element_slot<const ELEMENT_SIZE_WORDS: usize>(
+        &mut self,
+        base_slot: Bytes32Variable,
+        index: U256Variable,
+    ) -> Bytes32Variable {
+        assert!(
+            ELEMENT_SIZE_WORDS > 0,
+            "elements must take at least one word"
+        );
+        let start = self.keccak256(&base_slot.as_bytes());
+        let start = U256Variable::decode(self, &start.as_bytes());
+        let element_size = self.constant::<U256Variable>(U256::from(ELEMENT_SIZE_WORDS));
+        let offset = self.mul(index, element_size);
+        let slot = self.add(start, offset);
+        Bytes32Variable::from(slot.encode(self).as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::{encode, Token};
+    use ethers::types::H256;
+    use ethers::utils::keccak256;
+
+    use super::*;
+    use crate::prelude::DefaultBuilder;
+    use crate::utils::address;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_mapping_slot() {
+        let owner = address!("0x55032650b14df07b85bF18A3a3eC8E0Af2e028d5");
+        let spender = address!("0x9c8ff314c9bc7f6e59a9d9225fb22946427edc03");
+
+        // An ERC-20 token with `balanceOf` at slot 0 and `allowance` at slot 1.
+        let mut builder = DefaultBuilder::new();
+        let owner_variable = builder.read::<Bytes32Variable>();
+        let spender_variable = builder.read::<Bytes32Variable>();
+        let balances_slot = builder.constant::<Bytes32Variable>(H256::from_low_u64_be(0));
+        let allowances_slot = builder.constant::<Bytes32Variable>(H256::from_low_u64_be(1));
+        let balance_slot = builder.mapping_slot(owner_variable, balances_slot);
+        let allowance_slot =
+            builder.nested_mapping_slot(&[owner_variable, spender_variable], allowances_slot);
+        builder.write(balance_slot);
+        builder.write(allowance_slot);
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.write::<Bytes32Variable>(H256::from(owner));
+        input.write::<Bytes32Variable>(H256::from(spender));
+        let mut output = circuit.mock_prove(&input).output;
+        let balance_slot = output.read::<Bytes32Variable>();
+        let allowance_slot = output.read::<Bytes32Variable>();
+
+        let expected_balance_slot =
+            keccak256(encode(&[Token::Address(owner), Token::Uint(U256::zero())]));
+        let owner_allowances_slot =
+            keccak256(encode(&[Token::Address(owner), Token::Uint(U256::one())]));
+        let expected_allowance_slot = keccak256(encode(&[
+            Token::Address(spender),
+            Token::FixedBytes(owner_allowances_slot.to_vec()),
+        ]));
+        assert_eq!(balance_slot, H256::from(expected_balance_slot));
+        assert_eq!(allowance_slot, H256::from(expected_allowance_slot));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_array_element_slot() {
+        // A dynamic array at slot 2 of structs of 3 words.
+        let base_slot = H256::from_low_u64_be(2);
+
+        let mut builder = DefaultBuilder::new();
+        let index = builder.read::<U256Variable>();
+        let base_slot_variable = builder.constant::<Bytes32Variable>(base_slot);
+        let slot = builder.array_element_slot::<3>(base_slot_variable, index);
+        builder.write(slot);
+        let circuit = builder.mock_build();
+
+        for index in [U256::zero(), U256::from(5), U256::MAX] {
+            let mut input = circuit.input();
+            input.write::<U256Variable>(index);
+            let mut output = circuit.mock_prove(&input).output;
+            let slot = output.read::<Bytes32Variable>();
+
+            let start = U256::from_big_endian(&keccak256(base_slot));
+            let expected = start
+                .overflowing_add(index.overflowing_mul(U256::from(3)).0)
+                .0;
+            let mut expected_bytes = [0u8; 32];
+            expected.to_big_endian(&mut expected_bytes);
+            assert_eq!(slot, H256::from(expected_bytes), "index {}", index);
+        }
+    }
+}
@@ -114,6 +114,15 @@ impl EvmVariable for AddressVariable {
     }
 }
 
+impl SSZVariable for BLSPubkeyVariable {
+    fn hash_tree_root<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Bytes32Variable {
+        self.0.hash_tree_root(builder)
+    }
+}
+
 impl SSZVariable for AddressVariable {
     fn hash_tree_root<L: PlonkParameters<D>, const D: usize>(
         &self,
@@ -7,7 +7,6 @@ use plonky2::iop::witness::PartitionWitness;
 use plonky2::plonk::circuit_data::CommonCircuitData;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 use plonky2::plonk::proof::{ProofWithPublicInputs, ProofWithPublicInputsTarget};
-use plonky2::recursion::dummy_circuit::cyclic_base_proof;
 use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
 use serde::{Deserialize, Serialize};
 
@@ -110,18 +109,9 @@ where
     input.write::<Accumulator>(acc);
     input.write::<Accumulator>(initial);
     input.write::<U32Variable>(index);
-    input.data_write(circuit.data.verifier_data());
     // If there's a previous result, use the proof from it, otherwise use a dummy proof.
-    let proof = if let Some((proof, _)) = prev_result.take() {
-        proof
-    } else {
-        cyclic_base_proof(
-            &circuit.data.common,
-            &circuit.data.verifier_only,
-            vec![].into_iter().enumerate().collect(),
-        )
-    };
-    input.proof_write(proof);
+    let proof = prev_result.take().map(|(proof, _)| proof);
+    input.self_proof_write(circuit, proof);
     circuit.prove(&input)
 }
 
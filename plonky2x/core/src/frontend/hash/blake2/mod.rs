@@ -1 +1,260 @@
+/// Implementation of BLAKE2b
+/// reference: https://datatracker.ietf.org/doc/html/rfc7693
+use itertools::Itertools;
+
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::vars::EvmVariable;
+use crate::prelude::{BoolVariable, ByteVariable, U32Variable, U64Variable};
+
 pub mod curta;
+
+const BLAKE2B_BLOCK_SIZE: usize = 128;
+
+const BLAKE2B_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const BLAKE2B_SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Implements BLAKE2b for CircuitBuilder with U64Variable words.
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// The mixing function G, applied to the words `a`, `b`, `c` and `d` of the working vector.
+    #[allow(clippy::too_many_arguments)]
+    fn blake2b_mix(
+        &mut self,
+        v: &mut [U64Variable; 16],
+        a: usize,
+        b: usize,
+        c: usize,
+        d: usize,
+        x: U64Variable,
+        y: U64Variable,
+    ) {
+        v[a] = self.add_many(&[v[a], v[b], x]);
+        let d_xor_a = self.xor(v[d], v[a]);
+        v[d] = self.rotate_right(d_xor_a, 32);
+        v[c] = self.add(v[c], v[d]);
+        let b_xor_c = self.xor(v[b], v[c]);
+        v[b] = self.rotate_right(b_xor_c, 24);
+
+        v[a] = self.add_many(&[v[a], v[b], y]);
+        let d_xor_a = self.xor(v[d], v[a]);
+        v[d] = self.rotate_right(d_xor_a, 16);
+        v[c] = self.add(v[c], v[d]);
+        let b_xor_c = self.xor(v[b], v[c]);
+        v[b] = self.rotate_right(b_xor_c, 63);
+    }
+
+    /// The compression function F, where `t` is the number of bytes hashed so far and `is_final`
+    /// is set for the last block.
+    fn blake2b_compress(
+        &mut self,
+        h: [U64Variable; 8],
+        block: &[ByteVariable],
+        t: U64Variable,
+        is_final: BoolVariable,
+    ) -> [U64Variable; 8] {
+        let m = block
+            .chunks_exact(8)
+            .map(|bytes| U64Variable::from_le_bytes(self, bytes))
+            .collect_vec();
+
+        let iv = BLAKE2B_IV.map(|x| self.constant::<U64Variable>(x));
+        let mut v: [U64Variable; 16] =
+            core::array::from_fn(|i| if i < 8 { h[i] } else { iv[i - 8] });
+        // The counter is 128 bits, but messages here are shorter than 2^32 bytes so its high word
+        // is always zero.
+        v[12] = self.xor(v[12], t);
+        let not_v14 = self.not(v[14]);
+        v[14] = self.select(is_final, not_v14, v[14]);
+
+        for round in 0..12 {
+            let s = BLAKE2B_SIGMA[round % 10];
+            self.blake2b_mix(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+            self.blake2b_mix(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+            self.blake2b_mix(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+            self.blake2b_mix(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+            self.blake2b_mix(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+            self.blake2b_mix(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+            self.blake2b_mix(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+            self.blake2b_mix(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+
+        core::array::from_fn(|i| {
+            let h_xor_v = self.xor(h[i], v[i]);
+            self.xor(h_xor_v, v[i + 8])
+        })
+    }
+
+    /// Computes the unkeyed BLAKE2b hash of the first `length` bytes of `input`, with a digest of
+    /// `digest_len` bytes. `length` is only known at proving time and must be at most `MAX_BYTES`.
+    ///
+    /// Every block that fits in `MAX_BYTES` bytes is compressed, and the state stops changing
+    /// after the block containing the last byte of the message.
+    pub fn blake2b<const MAX_BYTES: usize>(
+        &mut self,
+        input: &[ByteVariable; MAX_BYTES],
+        length: U32Variable,
+        digest_len: usize,
+    ) -> Vec<ByteVariable> {
+        assert!(
+            (1..=64).contains(&digest_len),
+            "digest length must be between 1 and 64 bytes"
+        );
+        let max = self.constant::<U32Variable>(MAX_BYTES as u32);
+        let is_valid_length = self.lte(length, max);
+        let _true = self._true();
+        self.assert_is_equal(is_valid_length, _true);
+
+        // The parameter block of an unkeyed hash only sets the digest length, the fanout and the
+        // depth.
+        let mut h = BLAKE2B_IV;
+        h[0] ^= 0x01010000 ^ digest_len as u64;
+        let mut h = h.map(|x| self.constant::<U64Variable>(x));
+
+        // The bytes past the message are zero, which is the padding of the last block.
+        let zero = self.zero::<ByteVariable>();
+        let mut is_in_message = self._true();
+        let num_blocks = MAX_BYTES.div_ceil(BLAKE2B_BLOCK_SIZE).max(1);
+        let padded = (0..num_blocks * BLAKE2B_BLOCK_SIZE)
+            .map(|i| {
+                let index = self.constant::<U32Variable>(i as u32);
+                let is_end = self.is_equal(index, length);
+                let is_not_end = self.not(is_end);
+                is_in_message = self.and(is_in_message, is_not_end);
+                match input.get(i) {
+                    Some(byte) => self.select(is_in_message, *byte, zero),
+                    None => zero,
+                }
+            })
+            .collect_vec();
+
+        let last_block = self.compute_blake2b_last_chunk_index(length);
+        let length_u64 = length.to_u64(self);
+        for (i, block) in padded.chunks_exact(BLAKE2B_BLOCK_SIZE).enumerate() {
+            let block_index = self.constant::<U32Variable>(i as u32);
+            let is_last_block = self.is_equal(block_index, last_block);
+            let is_active = self.lte(block_index, last_block);
+
+            let bytes_hashed = self.constant::<U64Variable>(((i + 1) * BLAKE2B_BLOCK_SIZE) as u64);
+            let t = self.select(is_last_block, length_u64, bytes_hashed);
+            let compressed = self.blake2b_compress(h, block, t, is_last_block);
+            h = core::array::from_fn(|j| self.select(is_active, compressed[j], h[j]));
+        }
+
+        h.iter()
+            .flat_map(|x| x.to_le_bytes(self))
+            .take(digest_len)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto::blake2b::Blake2b;
+    use crypto::digest::Digest;
+    use rand::{thread_rng, Rng};
+
+    use crate::backend::circuit::DefaultParameters;
+    use crate::prelude::{ByteVariable, CircuitBuilder, U32Variable};
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    fn blake2b_digest(msg: &[u8], digest_len: usize) -> Vec<u8> {
+        let mut hasher = Blake2b::new(digest_len);
+        hasher.input(msg);
+        let mut digest = vec![0u8; digest_len];
+        hasher.result(&mut digest);
+        digest
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_blake2b_rfc_vectors() {
+        const MAX: usize = 3;
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let message = builder.read::<[ByteVariable; MAX]>();
+        let length = builder.read::<U32Variable>();
+        let digest = builder.blake2b(&message, length, 64);
+        for byte in digest {
+            builder.write(byte);
+        }
+        let circuit = builder.build();
+
+        let cases = [
+            (
+                0,
+                "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce",
+            ),
+            (
+                3,
+                "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+            ),
+        ];
+        for (length, expected) in cases {
+            let mut input = circuit.input();
+            input.write::<[ByteVariable; MAX]>(*b"abc");
+            input.write::<U32Variable>(length);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            let digest = (0..64)
+                .map(|_| output.read::<ByteVariable>())
+                .collect::<Vec<_>>();
+            assert_eq!(hex::encode(digest), expected);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_blake2b_multi_block() {
+        const MAX: usize = 300;
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let message = builder.read::<[ByteVariable; MAX]>();
+        let length = builder.read::<U32Variable>();
+        let digest_32 = builder.blake2b(&message, length, 32);
+        let digest_64 = builder.blake2b(&message, length, 64);
+        for byte in digest_32.into_iter().chain(digest_64) {
+            builder.write(byte);
+        }
+        let circuit = builder.build();
+
+        let mut rng = thread_rng();
+        let message: [u8; MAX] = core::array::from_fn(|_| rng.gen());
+        // Lengths around the block boundaries, where the last block is full or holds one byte.
+        for length in [0, 1, 127, 128, 129, 256, MAX] {
+            let mut input = circuit.input();
+            input.write::<[ByteVariable; MAX]>(message);
+            input.write::<U32Variable>(length as u32);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            for digest_len in [32, 64] {
+                let digest = (0..digest_len)
+                    .map(|_| output.read::<ByteVariable>())
+                    .collect::<Vec<_>>();
+                assert_eq!(digest, blake2b_digest(&message[..length], digest_len));
+            }
+        }
+    }
+}
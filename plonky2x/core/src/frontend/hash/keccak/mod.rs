@@ -1,13 +1,18 @@
-//! An implementation of the keccak256 hash functions in a plonky2 circuit
+//! An implementation of the keccak hash functions in a plonky2 circuit
 
 use core::marker::PhantomData;
 
 use self::keccak256::Keccak256Generator;
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::vars::Bytes32Variable;
-use crate::prelude::{ByteVariable, CircuitBuilder, Variable};
+use crate::prelude::{ByteVariable, CircuitBuilder, U32Variable, Variable};
 
 pub mod keccak256;
+pub mod permutation;
+pub mod sponge;
+
+/// The rate of Keccak-256 in bytes.
+const KECCAK256_RATE: usize = 136;
 
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     /// WARNING: DO NOT USE IN PRODUCTION, this is unconstrained!
@@ -40,10 +45,86 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         self.add_simple_generator(generator.clone());
         generator.output
     }
+
+    /// Pads the first `length` bytes of `input` with the pad10*1 rule of Keccak, into enough
+    /// blocks for any `length <= input.len()`. The bytes past the padding are zero.
+    fn pad_keccak256_variable_length(
+        &mut self,
+        input: &[ByteVariable],
+        length: U32Variable,
+        last_block: U32Variable,
+    ) -> Vec<ByteVariable> {
+        let num_blocks = input.len() / KECCAK256_RATE + 1;
+        let _false = self._false();
+
+        let mut is_in_message = self._true();
+        let mut padded = Vec::with_capacity(num_blocks * KECCAK256_RATE);
+        for i in 0..num_blocks * KECCAK256_RATE {
+            let index = self.constant::<U32Variable>(i as u32);
+            let is_end = self.is_equal(index, length);
+            let is_not_end = self.not(is_end);
+            is_in_message = self.and(is_in_message, is_not_end);
+
+            // Since `length <= input.len()`, the bytes past the input are never in the message.
+            let mut bits = match input.get(i) {
+                Some(byte) => byte.as_be_bits().map(|b| self.and(is_in_message, b)),
+                None => [_false; 8],
+            };
+
+            // The first padding bit goes right after the message and the last one at the end of
+            // the last block, which may be the same byte.
+            bits[7] = self.or(bits[7], is_end);
+            if i % KECCAK256_RATE == KECCAK256_RATE - 1 {
+                let block = self.constant::<U32Variable>((i / KECCAK256_RATE) as u32);
+                let is_last_block = self.is_equal(block, last_block);
+                bits[0] = self.or(bits[0], is_last_block);
+            }
+            padded.push(ByteVariable(bits));
+        }
+        padded
+    }
+
+    /// Computes the Keccak-256 hash of the first `length` bytes of `input`, where `length` is only
+    /// known at proving time and must be at most `MAX`.
+    ///
+    /// The padding is placed at the position given by `length`, every block that fits in `MAX`
+    /// bytes is absorbed, and the digest is selected from the state after the last block of the
+    /// padded message. The bytes past `length` do not affect the digest.
+    pub fn keccak256_variable<const MAX: usize>(
+        &mut self,
+        input: &[ByteVariable; MAX],
+        length: U32Variable,
+    ) -> Bytes32Variable {
+        let max = self.constant::<U32Variable>(MAX as u32);
+        let is_valid_length = self.lte(length, max);
+        let _true = self._true();
+        self.assert_is_equal(is_valid_length, _true);
+
+        let rate = self.constant::<U32Variable>(KECCAK256_RATE as u32);
+        let last_block = self.div(length, rate);
+        let padded = self.pad_keccak256_variable_length(input, length, last_block);
+
+        let mut state = self.keccak_init();
+        let mut digest = None;
+        for (i, block) in padded.chunks_exact(KECCAK256_RATE).enumerate() {
+            state = self.keccak_absorb(state, block);
+            let output = Bytes32Variable(self.keccak_squeeze(&state));
+            let block_index = self.constant::<U32Variable>(i as u32);
+            let is_last_block = self.is_equal(block_index, last_block);
+            digest = Some(match digest {
+                None => output,
+                Some(digest) => self.select(is_last_block, output, digest),
+            });
+        }
+        digest.unwrap()
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use ethers::types::H256;
+    use ethers::utils::keccak256;
+    use rand::{thread_rng, Rng};
 
     use super::*;
     use crate::backend::circuit::DefaultParameters;
@@ -67,4 +148,51 @@ mod tests {
         let input = circuit.input();
         let (_, _) = circuit.prove(&input);
     }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_keccak256_variable() {
+        const MAX: usize = 300;
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let message = builder.read::<[ByteVariable; MAX]>();
+        let length = builder.read::<U32Variable>();
+        let digest = builder.keccak256_variable(&message, length);
+        builder.write(digest);
+        let circuit = builder.build();
+
+        let mut rng = thread_rng();
+        let message: [u8; MAX] = core::array::from_fn(|_| rng.gen());
+        // Lengths around the rate boundaries, where the padding spills into an extra block, and a
+        // random one.
+        let random_length = rng.gen_range(0..=MAX);
+        for length in [0, 135, 136, 137, 271, 272, MAX, random_length] {
+            let mut input = circuit.input();
+            input.write::<[ByteVariable; MAX]>(message);
+            input.write::<U32Variable>(length as u32);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(
+                output.read::<Bytes32Variable>(),
+                H256::from(keccak256(&message[..length]))
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_keccak256_variable_too_long() {
+        const MAX: usize = 8;
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let message = builder.read::<[ByteVariable; MAX]>();
+        let length = builder.read::<U32Variable>();
+        builder.keccak256_variable(&message, length);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<[ByteVariable; MAX]>([0; MAX]);
+        input.write::<U32Variable>(MAX as u32 + 1);
+        circuit.prove(&input);
+    }
 }
@@ -0,0 +1,101 @@
+//! The Keccak-f[1600] permutation over bits.
+//!
+//! reference: https://keccak.team/keccak_specs_summary.html
+
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::hash::common::{and_arr, not_arr, xor2_arr, xor3_arr};
+use crate::prelude::BoolVariable;
+
+/// A lane of the state, as 64 bits in little-endian order.
+pub(crate) type KeccakLane = [BoolVariable; 64];
+
+/// The 5x5 state of Keccak-f[1600], with the lane `(x, y)` at index `x + 5 * y`.
+pub(crate) type KeccakState = [KeccakLane; 25];
+
+const NUM_ROUNDS: usize = 24;
+
+const ROUND_CONSTANTS: [u64; NUM_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808A,
+    0x8000000080008000,
+    0x000000000000808B,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008A,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000A,
+    0x000000008000808B,
+    0x800000000000008B,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800A,
+    0x800000008000000A,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// The rotation offsets of the rho step, indexed by `[x][y]`.
+const ROTATION_OFFSETS: [[usize; 5]; 5] = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+];
+
+fn rotate_lane_left(lane: KeccakLane, n: usize) -> KeccakLane {
+    core::array::from_fn(|i| lane[(i + 64 - n % 64) % 64])
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Applies the Keccak-f[1600] permutation to the state.
+    pub(crate) fn keccak_f(&mut self, mut state: KeccakState) -> KeccakState {
+        for round_constant in ROUND_CONSTANTS {
+            // Theta.
+            let c: [KeccakLane; 5] = core::array::from_fn(|x| {
+                let c = xor3_arr(state[x], state[x + 5], state[x + 10], self);
+                xor3_arr(c, state[x + 15], state[x + 20], self)
+            });
+            for x in 0..5 {
+                let d = xor2_arr(c[(x + 4) % 5], rotate_lane_left(c[(x + 1) % 5], 1), self);
+                for y in 0..5 {
+                    state[x + 5 * y] = xor2_arr(state[x + 5 * y], d, self);
+                }
+            }
+
+            // Rho and pi.
+            let mut b = state;
+            for x in 0..5 {
+                for y in 0..5 {
+                    b[y + 5 * ((2 * x + 3 * y) % 5)] =
+                        rotate_lane_left(state[x + 5 * y], ROTATION_OFFSETS[x][y]);
+                }
+            }
+
+            // Chi.
+            for x in 0..5 {
+                for y in 0..5 {
+                    let not_b1 = not_arr(b[(x + 1) % 5 + 5 * y], self);
+                    let t = and_arr(not_b1, b[(x + 2) % 5 + 5 * y], self);
+                    state[x + 5 * y] = xor2_arr(b[x + 5 * y], t, self);
+                }
+            }
+
+            // Iota. Xoring with a constant bit is either the identity or a negation.
+            for i in 0..64 {
+                if (round_constant >> i) & 1 == 1 {
+                    state[0][i] = self.not(state[0][i]);
+                }
+            }
+        }
+        state
+    }
+}
@@ -0,0 +1,183 @@
+//! The Keccak sponge, parameterized by its rate, output length and padding.
+
+use super::permutation::KeccakState;
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::prelude::{ByteVariable, Bytes32Variable, BytesVariable};
+
+/// The padding rule of a Keccak sponge, which only differs by its domain separation bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeccakPadding {
+    /// The original Keccak padding, used by Ethereum's keccak256.
+    Keccak,
+    /// The padding of the SHA-3 standard (FIPS 202).
+    Sha3,
+}
+
+impl KeccakPadding {
+    /// The first byte of the padding, which is or-ed with `0x80` if it is also the last one.
+    pub fn domain_byte(&self) -> u8 {
+        match self {
+            KeccakPadding::Keccak => 0x01,
+            KeccakPadding::Sha3 => 0x06,
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns the all-zero initial state of the sponge.
+    pub(crate) fn keccak_init(&mut self) -> KeccakState {
+        let _false = self._false();
+        [[_false; 64]; 25]
+    }
+
+    /// Xors a block of `rate` bytes into the state as little-endian lanes and permutes it.
+    pub(crate) fn keccak_absorb(
+        &mut self,
+        mut state: KeccakState,
+        block: &[ByteVariable],
+    ) -> KeccakState {
+        assert_eq!(
+            block.len() % 8,
+            0,
+            "the rate must be a whole number of lanes"
+        );
+        for (lane, bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+            let bits = bytes
+                .iter()
+                .flat_map(|b| b.as_le_bits())
+                .collect::<Vec<_>>();
+            for (state_bit, bit) in lane.iter_mut().zip(bits) {
+                *state_bit = self.xor(*state_bit, bit);
+            }
+        }
+        self.keccak_f(state)
+    }
+
+    /// Returns the first `N` bytes of the state, with the lanes in little-endian. Only the output
+    /// lengths that fit in the rate are supported, so a single squeeze is enough.
+    pub(crate) fn keccak_squeeze<const N: usize>(
+        &mut self,
+        state: &KeccakState,
+    ) -> BytesVariable<N> {
+        assert!(N <= 200, "the output must fit in the state");
+        let bytes = state
+            .iter()
+            .flat_map(|lane| lane.chunks_exact(8))
+            .take(N)
+            .map(|bits| ByteVariable(core::array::from_fn(|i| bits[7 - i])))
+            .collect::<Vec<_>>();
+        BytesVariable(bytes.try_into().unwrap())
+    }
+
+    /// Computes a Keccak hash of a fixed size input, with a rate of `RATE` bytes and an output of
+    /// `OUTPUT` bytes.
+    pub fn keccak<const RATE: usize, const OUTPUT: usize>(
+        &mut self,
+        input: &[ByteVariable],
+        padding: KeccakPadding,
+    ) -> BytesVariable<OUTPUT> {
+        assert!(
+            RATE > 0 && RATE < 200 && RATE % 8 == 0,
+            "the rate must be a whole number of lanes smaller than the state"
+        );
+        assert!(OUTPUT <= RATE, "the output must fit in the rate");
+
+        // The padding is known at build time, so the padding bytes are constants.
+        let num_blocks = input.len() / RATE + 1;
+        let mut padding_bytes = vec![0u8; num_blocks * RATE - input.len()];
+        padding_bytes[0] |= padding.domain_byte();
+        *padding_bytes.last_mut().unwrap() |= 0x80;
+        let mut padded = input.to_vec();
+        padded.extend(
+            padding_bytes
+                .into_iter()
+                .map(|b| self.constant::<ByteVariable>(b)),
+        );
+
+        self.profile("keccak", |builder| {
+            let mut state = builder.keccak_init();
+            for block in padded.chunks_exact(RATE) {
+                state = builder.keccak_absorb(state, block);
+            }
+            builder.keccak_squeeze(&state)
+        })
+    }
+
+    /// Computes the Keccak-256 hash of a fixed size input, as used by Ethereum.
+    pub fn keccak256(&mut self, input: &[ByteVariable]) -> Bytes32Variable {
+        Bytes32Variable(self.keccak::<136, 32>(input, KeccakPadding::Keccak))
+    }
+
+    /// Computes the Keccak-512 hash of a fixed size input.
+    pub fn keccak512(&mut self, input: &[ByteVariable]) -> BytesVariable<64> {
+        self.keccak::<72, 64>(input, KeccakPadding::Keccak)
+    }
+
+    /// Computes the SHA3-256 hash of a fixed size input.
+    pub fn sha3_256(&mut self, input: &[ByteVariable]) -> Bytes32Variable {
+        Bytes32Variable(self.keccak::<136, 32>(input, KeccakPadding::Sha3))
+    }
+
+    /// Computes the SHA3-512 hash of a fixed size input.
+    pub fn sha3_512(&mut self, input: &[ByteVariable]) -> BytesVariable<64> {
+        self.keccak::<72, 64>(input, KeccakPadding::Sha3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sha3::{Digest, Keccak256, Keccak512, Sha3_256, Sha3_512};
+
+    use crate::prelude::*;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_keccak_variants() {
+        // Empty, single block and multi-block inputs for the 72 and 136 byte rates.
+        let lengths = [0, 3, 71, 72, 135, 136, 300];
+        let messages = lengths
+            .iter()
+            .map(|length| (0..*length).map(|i| (i * 31 + 7) as u8).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let mut builder = DefaultBuilder::new();
+        for message in messages.iter() {
+            let input = message
+                .iter()
+                .map(|b| builder.constant::<ByteVariable>(*b))
+                .collect::<Vec<_>>();
+            let keccak256 = builder.keccak256(&input);
+            let keccak512 = builder.keccak512(&input);
+            let sha3_256 = builder.sha3_256(&input);
+            let sha3_512 = builder.sha3_512(&input);
+            builder.write(keccak256);
+            builder.write(keccak512);
+            builder.write(sha3_256);
+            builder.write(sha3_512);
+        }
+        let circuit = builder.build();
+
+        let input = circuit.input();
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        for message in messages.iter() {
+            assert_eq!(
+                output.read::<Bytes32Variable>().as_bytes(),
+                Keccak256::digest(message).as_slice()
+            );
+            assert_eq!(
+                output.read::<BytesVariable<64>>().as_slice(),
+                Keccak512::digest(message).as_slice()
+            );
+            assert_eq!(
+                output.read::<Bytes32Variable>().as_bytes(),
+                Sha3_256::digest(message).as_slice()
+            );
+            assert_eq!(
+                output.read::<BytesVariable<64>>().as_slice(),
+                Sha3_512::digest(message).as_slice()
+            );
+        }
+    }
+}
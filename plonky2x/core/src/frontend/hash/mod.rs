@@ -3,4 +3,5 @@ pub mod common;
 pub mod curta;
 pub mod keccak;
 pub mod poseidon;
+pub mod ripemd160;
 pub mod sha;
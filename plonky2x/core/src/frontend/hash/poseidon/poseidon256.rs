@@ -1,5 +1,6 @@
 use array_macro::array;
 use plonky2::hash::hash_types::{HashOut, HashOutTarget, RichField, NUM_HASH_OUT_ELTS};
+use plonky2::hash::poseidon::PoseidonHash;
 use plonky2::iop::target::BoolTarget;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 
@@ -64,9 +65,12 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
             AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
     {
         let targets = variables.iter().map(|v| v.0).collect::<Vec<_>>();
-        PoseidonHashOutVariable::from_targets(
+        self.push_scope("poseidon");
+        let hash = PoseidonHashOutVariable::from_targets(
             &self.api.hash_n_to_hash_no_pad::<<<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher>(targets).elements,
-        )
+        );
+        self.pop_scope();
+        hash
     }
 
     /// Computes the Poseidon hash of the given variables with no padding.
@@ -85,6 +89,30 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         self.poseidon_hash(&input)
     }
 
+    /// Computes the Poseidon hash of a byte string, as `utils::poseidon::poseidon_hash_bytes` does
+    /// outside of the circuit.
+    ///
+    /// The input is packed into field elements as the length in bytes, followed by the bytes in
+    /// chunks of 7, each read as a little-endian integer with the last chunk zero-extended. Chunks
+    /// of 7 bytes always fit in the Goldilocks field, and the length prefix separates inputs that
+    /// only differ by trailing zero bytes. The packed elements are hashed with Poseidon with no
+    /// padding, regardless of the hasher of the circuit configuration.
+    pub fn poseidon_hash_bytes(&mut self, input: &[ByteVariable]) -> PoseidonHashOutVariable {
+        let mut elements = vec![
+            self.constant::<Variable>(L::Field::from_canonical_usize(input.len()))
+                .0,
+        ];
+        for chunk in input.chunks(7) {
+            let bits = chunk
+                .iter()
+                .flat_map(|byte| byte.as_le_bits())
+                .map(|bit| BoolTarget::new_unsafe(bit.variable.0))
+                .collect::<Vec<_>>();
+            elements.push(self.api.le_sum(bits.into_iter()));
+        }
+        PoseidonHashOutVariable::from(self.api.hash_n_to_hash_no_pad::<PoseidonHash>(elements))
+    }
+
     /// Note: This Poseidon implementation operates on bytes, not field elements. The input bytes to
     /// the Poseidon hash are converted into field elements internally. Specifically, we convert the
     /// [ByteVariable; N] into a [u32; N/4] and then represent the u32 as a [F; N/4]. We use u32's
@@ -155,9 +183,12 @@ mod tests {
     use anyhow::Result;
     use plonky2::plonk::config::GenericConfig;
 
+    use super::PoseidonHashOutVariable;
     use crate::backend::circuit::{DefaultParameters, PlonkParameters};
+    use crate::frontend::builder::size::assert_circuit_size;
     use crate::frontend::vars::Bytes32Variable;
-    use crate::prelude::CircuitBuilder;
+    use crate::prelude::{ByteVariable, CircuitBuilder, GoldilocksField};
+    use crate::utils::poseidon::{poseidon_hash_bytes, poseidon_hash_pair};
     use crate::utils::{self, bytes32};
 
     #[test]
@@ -200,4 +231,62 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_poseidon_hash_bytes() {
+        type L = DefaultParameters;
+        const D: usize = 2;
+        const LENGTHS: [usize; 7] = [0, 1, 6, 7, 8, 14, 50];
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let message = builder.read::<[ByteVariable; 50]>();
+        let hashes = LENGTHS
+            .iter()
+            .map(|length| builder.poseidon_hash_bytes(&message[..*length]))
+            .collect::<Vec<_>>();
+        let pair = builder.poseidon_hash_pair(hashes[1].clone(), hashes[2].clone());
+        for hash in hashes {
+            builder.write(hash);
+        }
+        builder.write(pair);
+        let circuit = builder.build();
+
+        let message: [u8; 50] = core::array::from_fn(|i| (i * 37 + 11) as u8);
+        let mut input = circuit.input();
+        input.write::<[ByteVariable; 50]>(message);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        let expected = LENGTHS
+            .iter()
+            .map(|length| poseidon_hash_bytes(&message[..*length]))
+            .collect::<Vec<_>>();
+        for hash in expected.iter() {
+            assert_eq!(output.read::<PoseidonHashOutVariable>(), *hash);
+        }
+        assert_eq!(
+            output.read::<PoseidonHashOutVariable>(),
+            poseidon_hash_pair(expected[1], expected[2])
+        );
+
+        // Trailing zero bytes change the hash through the length prefix.
+        assert_ne!(
+            poseidon_hash_bytes::<GoldilocksField>(&[]),
+            poseidon_hash_bytes::<GoldilocksField>(&[0])
+        );
+    }
+
+    #[test]
+    fn test_poseidon_hash_pair_circuit_size() {
+        // A pair of hashes fits in the rate of the sponge, so it is hashed with a single gate.
+        assert_circuit_size(
+            |builder: &mut CircuitBuilder<DefaultParameters, 2>| {
+                let left = builder.read::<PoseidonHashOutVariable>();
+                let right = builder.read::<PoseidonHashOutVariable>();
+                let hash = builder.poseidon_hash_pair(left, right);
+                builder.write(hash);
+            },
+            1,
+            12,
+        );
+    }
 }
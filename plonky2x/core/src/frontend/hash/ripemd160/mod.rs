@@ -0,0 +1,259 @@
+/// Implementation of RIPEMD-160
+/// reference: https://homes.esat.kuleuven.be/~bosselae/ripemd160/pdf/AB-9601/AB-9601.pdf
+use itertools::Itertools;
+
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::vars::EvmVariable;
+use crate::prelude::{ArrayVariable, ByteVariable, U32Variable};
+
+const RIPEMD160_CHUNK_SIZE_BYTES: usize = 64;
+
+const RIPEMD160_INITIAL_HASH: [u32; 5] =
+    [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+const RIPEMD160_K_LEFT: [u32; 5] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+const RIPEMD160_K_RIGHT: [u32; 5] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+/// The message word selected at each step of the left line.
+const RIPEMD160_R_LEFT: [usize; 80] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, //
+    7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8, //
+    3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, //
+    1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2, //
+    4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+
+/// The message word selected at each step of the right line.
+const RIPEMD160_R_RIGHT: [usize; 80] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, //
+    6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2, //
+    15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, //
+    8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14, //
+    12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+/// The rotation applied at each step of the left line.
+const RIPEMD160_S_LEFT: [usize; 80] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, //
+    7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12, //
+    11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, //
+    11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, //
+    9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+
+/// The rotation applied at each step of the right line.
+const RIPEMD160_S_RIGHT: [usize; 80] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, //
+    9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11, //
+    9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, //
+    15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8, //
+    8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+
+/// Implements RIPEMD-160 for CircuitBuilder with U32Variable words.
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Pads the input with a one bit, zeros and the little-endian bit length of the input, to a
+    /// multiple of 64 bytes.
+    fn pad_message_ripemd160(&mut self, input: &[ByteVariable]) -> Vec<ByteVariable> {
+        let mut padded = input.to_vec();
+        padded.push(self.constant::<ByteVariable>(0x80));
+        while padded.len() % RIPEMD160_CHUNK_SIZE_BYTES != RIPEMD160_CHUNK_SIZE_BYTES - 8 {
+            padded.push(self.zero());
+        }
+        for byte in ((input.len() as u64) * 8).to_le_bytes() {
+            padded.push(self.constant::<ByteVariable>(byte));
+        }
+        padded
+    }
+
+    /// The boolean function of the given round, applied to `x`, `y` and `z`.
+    fn ripemd160_f(
+        &mut self,
+        round: usize,
+        x: U32Variable,
+        y: U32Variable,
+        z: U32Variable,
+    ) -> U32Variable {
+        match round {
+            0 => {
+                let x_xor_y = self.xor(x, y);
+                self.xor(x_xor_y, z)
+            }
+            1 => {
+                let x_and_y = self.and(x, y);
+                let not_x = self.not(x);
+                let not_x_and_z = self.and(not_x, z);
+                self.or(x_and_y, not_x_and_z)
+            }
+            2 => {
+                let not_y = self.not(y);
+                let x_or_not_y = self.or(x, not_y);
+                self.xor(x_or_not_y, z)
+            }
+            3 => {
+                let x_and_z = self.and(x, z);
+                let not_z = self.not(z);
+                let y_and_not_z = self.and(y, not_z);
+                self.or(x_and_z, y_and_not_z)
+            }
+            4 => {
+                let not_z = self.not(z);
+                let y_or_not_z = self.or(y, not_z);
+                self.xor(x, y_or_not_z)
+            }
+            _ => unreachable!("RIPEMD-160 has 5 rounds"),
+        }
+    }
+
+    fn ripemd160_compress(
+        &mut self,
+        h: [U32Variable; 5],
+        chunk: &[ByteVariable],
+    ) -> [U32Variable; 5] {
+        let x = chunk
+            .chunks_exact(4)
+            .map(|bytes| U32Variable::from_le_bytes(self, bytes))
+            .collect_vec();
+        let k_left = RIPEMD160_K_LEFT.map(|k| self.constant::<U32Variable>(k));
+        let k_right = RIPEMD160_K_RIGHT.map(|k| self.constant::<U32Variable>(k));
+
+        let [mut al, mut bl, mut cl, mut dl, mut el] = h;
+        let [mut ar, mut br, mut cr, mut dr, mut er] = h;
+        for j in 0..80 {
+            let round = j / 16;
+
+            let f = self.ripemd160_f(round, bl, cl, dl);
+            let sum = self.add_many(&[al, f, x[RIPEMD160_R_LEFT[j]], k_left[round]]);
+            let rotated = self.rotate_left(sum, RIPEMD160_S_LEFT[j]);
+            let t = self.add(rotated, el);
+            al = el;
+            el = dl;
+            dl = self.rotate_left(cl, 10);
+            cl = bl;
+            bl = t;
+
+            // The right line uses the boolean functions in reverse order.
+            let f = self.ripemd160_f(4 - round, br, cr, dr);
+            let sum = self.add_many(&[ar, f, x[RIPEMD160_R_RIGHT[j]], k_right[round]]);
+            let rotated = self.rotate_left(sum, RIPEMD160_S_RIGHT[j]);
+            let t = self.add(rotated, er);
+            ar = er;
+            er = dr;
+            dr = self.rotate_left(cr, 10);
+            cr = br;
+            br = t;
+        }
+
+        [
+            self.add_many(&[h[1], cl, dr]),
+            self.add_many(&[h[2], dl, er]),
+            self.add_many(&[h[3], el, ar]),
+            self.add_many(&[h[4], al, br]),
+            self.add_many(&[h[0], bl, cr]),
+        ]
+    }
+
+    /// Computes the RIPEMD-160 hash of a fixed-length input.
+    pub fn ripemd160(&mut self, input: &[ByteVariable]) -> ArrayVariable<ByteVariable, 20> {
+        let padded = self.pad_message_ripemd160(input);
+        let mut h = RIPEMD160_INITIAL_HASH.map(|x| self.constant::<U32Variable>(x));
+        for chunk in padded.chunks_exact(RIPEMD160_CHUNK_SIZE_BYTES) {
+            h = self.ripemd160_compress(h, chunk);
+        }
+        let digest = h.iter().flat_map(|x| x.to_le_bytes(self)).collect_vec();
+        ArrayVariable::new(digest)
+    }
+
+    /// Computes `ripemd160(sha256(input))`, the hash used to derive Bitcoin addresses from public
+    /// keys.
+    pub fn hash160(&mut self, input: &[ByteVariable]) -> ArrayVariable<ByteVariable, 20> {
+        let sha256 = self.sha256(input);
+        self.ripemd160(&sha256.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto::digest::Digest;
+    use crypto::ripemd160::Ripemd160;
+    use rand::{thread_rng, Rng};
+
+    use crate::backend::circuit::DefaultParameters;
+    use crate::prelude::{ArrayVariable, ByteVariable, CircuitBuilder};
+
+    type L = DefaultParameters;
+    const D: usize = 2;
+
+    fn ripemd160_digest(msg: &[u8]) -> Vec<u8> {
+        let mut hasher = Ripemd160::new();
+        hasher.input(msg);
+        let mut digest = vec![0u8; 20];
+        hasher.result(&mut digest);
+        digest
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ripemd160() {
+        let mut rng = thread_rng();
+        let random = (0..100).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+        let cases = [
+            (
+                b"".to_vec(),
+                "9c1185a5c5e9fc54612808977ee8f548b2258d31".to_string(),
+            ),
+            (
+                b"abc".to_vec(),
+                "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc".to_string(),
+            ),
+            // A message that spans two chunks.
+            (random.clone(), hex::encode(ripemd160_digest(&random))),
+        ];
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        for (msg, _) in cases.iter() {
+            let msg = msg
+                .iter()
+                .map(|b| builder.constant::<ByteVariable>(*b))
+                .collect::<Vec<_>>();
+            let digest = builder.ripemd160(&msg);
+            builder.write(digest);
+        }
+        let circuit = builder.build();
+
+        let input = circuit.input();
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        for (_, expected) in cases {
+            let digest = output.read::<ArrayVariable<ByteVariable, 20>>();
+            assert_eq!(hex::encode(digest), expected);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_hash160() {
+        // The compressed public key of the secp256k1 private key 1, whose P2WPKH address is
+        // bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4.
+        let pubkey_bytes =
+            hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let pubkey = builder.read::<[ByteVariable; 33]>();
+        let digest = builder.hash160(&pubkey);
+        builder.write(digest);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<[ByteVariable; 33]>(pubkey_bytes.try_into().unwrap());
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        let digest = output.read::<ArrayVariable<ByteVariable, 20>>();
+        assert_eq!(
+            hex::encode(digest),
+            "751e76e8199196d454941c45d1b3a323f1433bd6"
+        );
+    }
+}
@@ -20,6 +20,10 @@ use crate::prelude::*;
 
 pub type SHA256Accelerator = HashAccelerator<U32Variable, 8>;
 
+/// The number of `sha256_accelerated` requests from which they are proven with the curta
+/// accelerator. Below it, the fixed cost of verifying the STARK outweighs the plain gadgets.
+pub const SHA256_ACCELERATION_THRESHOLD: usize = 8;
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct SHA256AirParameters<L, const D: usize>(PhantomData<L>);
 
@@ -181,6 +185,44 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         digest
     }
 
+    /// Computes the SHA256 hash of a fixed size input, deferring the choice of gadget to build
+    /// time.
+    ///
+    /// The digest is returned immediately and can be used like any other variable. When the
+    /// circuit is built, all the requests are proven together with the curta accelerator if there
+    /// are at least `SHA256_ACCELERATION_THRESHOLD` of them, and with the plain `sha256` gadget
+    /// otherwise.
+    pub fn sha256_accelerated(&mut self, input: &[ByteVariable]) -> Bytes32Variable {
+        let digest = self.init::<Bytes32Variable>();
+        self.sha256_accelerated_requests
+            .push((input.to_vec(), digest));
+        digest
+    }
+
+    /// Constrains the digests of the `sha256_accelerated` requests.
+    pub(crate) fn constrain_sha256_accelerated_requests(&mut self) {
+        let requests = core::mem::take(&mut self.sha256_accelerated_requests);
+        if requests.len() < SHA256_ACCELERATION_THRESHOLD {
+            for (input, digest) in requests {
+                let expected = self.sha256(&input);
+                self.assert_is_equal(digest, expected);
+            }
+            return;
+        }
+
+        for (input, digest) in requests {
+            let digest_array = SHA256::digest_to_array(self, digest);
+            let accelerator = self
+                .sha256_accelerator
+                .get_or_insert_with(|| SHA256Accelerator {
+                    hash_requests: Vec::new(),
+                    hash_responses: Vec::new(),
+                });
+            accelerator.hash_requests.push(HashRequest::Fixed(input));
+            accelerator.hash_responses.push(digest_array);
+        }
+    }
+
     pub fn curta_sha256_pair(
         &mut self,
         left: Bytes32Variable,
@@ -200,6 +242,7 @@ mod tests {
     use ethers::types::H256;
     use rand::{thread_rng, Rng};
 
+    use super::SHA256_ACCELERATION_THRESHOLD;
     use crate::backend::circuit::CircuitBuild;
     use crate::prelude::*;
     use crate::utils::hash::sha256;
@@ -467,4 +510,59 @@ mod tests {
         let (proof, output) = circuit.prove(&input);
         circuit.verify(&proof, &input, &output);
     }
+
+    fn build_sha256_accelerated(messages: &[Vec<u8>], accelerated: bool) -> CircuitBuild<L, D> {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        for message in messages {
+            let input = message
+                .iter()
+                .map(|b| builder.constant::<ByteVariable>(*b))
+                .collect::<Vec<_>>();
+            let digest = if accelerated {
+                builder.sha256_accelerated(&input)
+            } else {
+                builder.sha256(&input)
+            };
+            builder.write(digest);
+        }
+        builder.build()
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_sha256_accelerated() {
+        env::set_var("RUST_LOG", "debug");
+        env_logger::try_init().unwrap_or_default();
+
+        let mut rng = thread_rng();
+        let lengths = [0, 1, 32, 55, 56, 64, 100, 128];
+        // Below the threshold the plain gadget is used, at and above it the accelerator.
+        for num_requests in [3, 4 * lengths.len()] {
+            let messages = (0..num_requests)
+                .map(|i| {
+                    (0..lengths[i % lengths.len()])
+                        .map(|_| rng.gen::<u8>())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+
+            let accelerated = build_sha256_accelerated(&messages, true);
+            let input = accelerated.input();
+            let (proof, mut output) = accelerated.prove(&input);
+            accelerated.verify(&proof, &input, &output);
+            for message in messages.iter() {
+                assert_eq!(
+                    output.read::<Bytes32Variable>(),
+                    H256::from(sha256(message))
+                );
+            }
+
+            let plain = build_sha256_accelerated(&messages, false);
+            if num_requests < SHA256_ACCELERATION_THRESHOLD {
+                assert_eq!(accelerated.data.common.degree(), plain.data.common.degree());
+            } else {
+                assert!(accelerated.data.common.degree() < plain.data.common.degree());
+            }
+        }
+    }
 }
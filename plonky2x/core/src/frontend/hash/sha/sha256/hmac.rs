@@ -0,0 +1,112 @@
+use super::pad::SHA256_CHUNK_SIZE_BYTES;
+use crate::prelude::*;
+
+const HMAC_IPAD: u8 = 0x36;
+const HMAC_OPAD: u8 = 0x5c;
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Xors every byte of `bytes` with a constant byte, by negating the bits set in `pad`.
+    fn xor_const_byte(&mut self, bytes: &[ByteVariable], pad: u8) -> Vec<ByteVariable> {
+        bytes
+            .iter()
+            .map(|byte| {
+                let mut bits = byte.as_be_bits();
+                for (i, bit) in bits.iter_mut().enumerate() {
+                    if (pad >> (7 - i)) & 1 == 1 {
+                        *bit = self.not(*bit);
+                    }
+                }
+                ByteVariable(bits)
+            })
+            .collect()
+    }
+
+    /// Computes the HMAC-SHA256 of `message` under `key`, as specified in RFC 2104.
+    ///
+    /// Keys longer than a SHA-256 block are hashed first. Both hashes go through the incremental
+    /// SHA-256 API, so the key blocks are compressed once each.
+    pub fn hmac_sha256(
+        &mut self,
+        key: &[ByteVariable],
+        message: &[ByteVariable],
+    ) -> Bytes32Variable {
+        let mut key_block = if key.len() > SHA256_CHUNK_SIZE_BYTES {
+            self.sha256(key).as_bytes().to_vec()
+        } else {
+            key.to_vec()
+        };
+        key_block.resize(SHA256_CHUNK_SIZE_BYTES, self.zero());
+
+        let inner_key = self.xor_const_byte(&key_block, HMAC_IPAD);
+        let inner = self.sha256_init();
+        let inner = self.sha256_update(inner, &inner_key);
+        let inner = self.sha256_update(inner, message);
+        let inner_digest = self.sha256_finalize(inner, SHA256_CHUNK_SIZE_BYTES + message.len());
+
+        let outer_key = self.xor_const_byte(&key_block, HMAC_OPAD);
+        let outer = self.sha256_init();
+        let outer = self.sha256_update(outer, &outer_key);
+        let outer = self.sha256_update(outer, &inner_digest.as_bytes());
+        self.sha256_finalize(outer, SHA256_CHUNK_SIZE_BYTES + 32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_hmac_sha256_rfc4231() {
+        // Test cases 1 to 4 and 6 of RFC 4231. Test case 6 uses a key longer than a block.
+        let cases = [
+            (
+                vec![0x0b; 20],
+                b"Hi There".to_vec(),
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                b"Jefe".to_vec(),
+                b"what do ya want for nothing?".to_vec(),
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                vec![0xaa; 20],
+                vec![0xdd; 50],
+                "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+            ),
+            (
+                (0x01..=0x19).collect(),
+                vec![0xcd; 50],
+                "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+            ),
+            (
+                vec![0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec(),
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+        ];
+
+        let mut builder = DefaultBuilder::new();
+        for (key, message, _) in cases.iter() {
+            let key = key
+                .iter()
+                .map(|b| builder.constant::<ByteVariable>(*b))
+                .collect::<Vec<_>>();
+            let message = message
+                .iter()
+                .map(|b| builder.constant::<ByteVariable>(*b))
+                .collect::<Vec<_>>();
+            let mac = builder.hmac_sha256(&key, &message);
+            builder.write(mac);
+        }
+        let circuit = builder.build();
+
+        let input = circuit.input();
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        for (_, _, expected) in cases {
+            assert_eq!(output.read::<Bytes32Variable>(), bytes32!(expected));
+        }
+    }
+}
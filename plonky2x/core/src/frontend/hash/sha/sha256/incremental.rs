@@ -0,0 +1,193 @@
+use ::starkyx::machine::hash::sha::algorithm::SHAPure;
+use ::starkyx::machine::hash::sha::sha256::SHA256;
+use itertools::Itertools;
+
+use super::pad::{SHA256_CHUNK_SIZE_BYTES, SHA256_INPUT_LENGTH_BYTE_SIZE};
+use crate::frontend::vars::EvmVariable;
+use crate::prelude::*;
+
+/// The state of an incremental SHA-256 hash.
+///
+/// `state` is the chaining value after every complete chunk absorbed so far, `buffer` holds the
+/// bytes of the incomplete chunk and `length` counts all the bytes absorbed. Cloning the state
+/// after a shared prefix lets several hashes reuse the compressions of the prefix.
+#[derive(Debug, Clone)]
+pub struct SHA256StateVariable {
+    pub state: [U32Variable; 8],
+    pub buffer: Vec<ByteVariable>,
+    pub length: usize,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns the state of a SHA-256 hash of the empty message.
+    pub fn sha256_init(&mut self) -> SHA256StateVariable {
+        SHA256StateVariable {
+            state: SHA256::INITIAL_HASH.map(|x| self.constant::<U32Variable>(x)),
+            buffer: Vec::new(),
+            length: 0,
+        }
+    }
+
+    /// Absorbs `bytes` into the state, compressing every chunk that becomes complete.
+    pub fn sha256_update(
+        &mut self,
+        state: SHA256StateVariable,
+        bytes: &[ByteVariable],
+    ) -> SHA256StateVariable {
+        let SHA256StateVariable {
+            state,
+            mut buffer,
+            length,
+        } = state;
+        buffer.extend_from_slice(bytes);
+
+        let num_chunks = buffer.len() / SHA256_CHUNK_SIZE_BYTES;
+        let rest = buffer.split_off(num_chunks * SHA256_CHUNK_SIZE_BYTES);
+        let state = self.compress_sha256_chunks(state, &buffer);
+        SHA256StateVariable {
+            state,
+            buffer: rest,
+            length: length + bytes.len(),
+        }
+    }
+
+    /// Pads the absorbed message and returns its digest. `total_len` is the number of bytes
+    /// absorbed since `sha256_init`, and must match the length tracked by the state.
+    pub fn sha256_finalize(
+        &mut self,
+        state: SHA256StateVariable,
+        total_len: usize,
+    ) -> Bytes32Variable {
+        assert_eq!(
+            total_len, state.length,
+            "total length does not match the number of bytes absorbed"
+        );
+        let SHA256StateVariable {
+            state, mut buffer, ..
+        } = state;
+
+        buffer.push(self.constant::<ByteVariable>(0x80));
+        while buffer.len() % SHA256_CHUNK_SIZE_BYTES
+            != SHA256_CHUNK_SIZE_BYTES - SHA256_INPUT_LENGTH_BYTE_SIZE
+        {
+            buffer.push(self.zero());
+        }
+        for byte in ((total_len as u64) * 8).to_be_bytes() {
+            buffer.push(self.constant::<ByteVariable>(byte));
+        }
+        let state = self.compress_sha256_chunks(state, &buffer);
+
+        let bytes = state.iter().flat_map(|x| x.encode(self)).collect_vec();
+        Bytes32Variable::from(bytes.as_slice())
+    }
+
+    /// Compresses whole chunks of `bytes` into the chaining value.
+    fn compress_sha256_chunks(
+        &mut self,
+        state: [U32Variable; 8],
+        bytes: &[ByteVariable],
+    ) -> [U32Variable; 8] {
+        if bytes.is_empty() {
+            return state;
+        }
+
+        let round_constants = self.get_round_constants();
+        let mut state_bits = state.map(|x| {
+            let mut bits = x.split_le_bits(self);
+            bits.reverse();
+            <[BoolVariable; 32]>::try_from(bits).unwrap()
+        });
+        let bits = bytes
+            .iter()
+            .flat_map(|b| b.as_be_bits().to_vec())
+            .collect_vec();
+        for chunk in bits.chunks_exact(SHA256_CHUNK_SIZE_BYTES * 8) {
+            state_bits = self.compress_sha256(state_bits, chunk, &round_constants);
+        }
+
+        state_bits.map(|bits| {
+            let le_bits = bits.iter().rev().copied().collect_vec();
+            U32Variable::from_le_bits_unsafe(self, &le_bits)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+    use rand::{thread_rng, Rng};
+
+    use crate::prelude::*;
+    use crate::utils::hash::sha256;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_sha256_incremental() {
+        const LEN: usize = 150;
+
+        let mut builder = DefaultBuilder::new();
+        let message = builder.read::<[ByteVariable; LEN]>();
+        let one_shot = builder.sha256(&message);
+        builder.write(one_shot);
+
+        // Split points on and off the chunk boundaries.
+        let splits = [
+            (0, 0),
+            (1, 2),
+            (30, 64),
+            (55, 56),
+            (64, 128),
+            (65, 149),
+            (150, 150),
+        ];
+        for (i, j) in splits {
+            let state = builder.sha256_init();
+            let state = builder.sha256_update(state, &message[..i]);
+            let state = builder.sha256_update(state, &message[i..j]);
+            let state = builder.sha256_update(state, &message[j..]);
+            let digest = builder.sha256_finalize(state, LEN);
+            builder.write(digest);
+        }
+
+        // A shared prefix reused for two suffixes.
+        let prefix = builder.sha256_init();
+        let prefix = builder.sha256_update(prefix, &message[..100]);
+        let first = builder.sha256_update(prefix.clone(), &message[100..]);
+        let first = builder.sha256_finalize(first, LEN);
+        let second = builder.sha256_update(prefix, &message[120..]);
+        let second = builder.sha256_finalize(second, 130);
+        builder.write(first);
+        builder.write(second);
+        let circuit = builder.build();
+
+        let mut rng = thread_rng();
+        let message: [u8; LEN] = core::array::from_fn(|_| rng.gen());
+        let mut input = circuit.input();
+        input.write::<[ByteVariable; LEN]>(message);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let expected = H256::from(sha256(&message));
+        assert_eq!(output.read::<Bytes32Variable>(), expected);
+        for _ in splits {
+            assert_eq!(output.read::<Bytes32Variable>(), expected);
+        }
+        assert_eq!(output.read::<Bytes32Variable>(), expected);
+        let mut concatenated = message[..100].to_vec();
+        concatenated.extend_from_slice(&message[120..]);
+        assert_eq!(
+            output.read::<Bytes32Variable>(),
+            H256::from(sha256(&concatenated))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "total length does not match")]
+    fn test_sha256_finalize_wrong_length() {
+        let mut builder = DefaultBuilder::new();
+        let message = builder.read::<[ByteVariable; 10]>();
+        let state = builder.sha256_init();
+        let state = builder.sha256_update(state, &message);
+        builder.sha256_finalize(state, 11);
+    }
+}
@@ -7,9 +7,13 @@ use itertools::Itertools;
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
 use crate::frontend::hash::common::{and_arr, not_arr, xor2_arr, xor3_arr};
-use crate::frontend::vars::{BoolVariable, ByteVariable, Bytes32Variable, CircuitVariable};
+use crate::frontend::vars::{
+    BoolVariable, ByteVariable, Bytes32Variable, CircuitVariable, U32Variable,
+};
 
 pub mod curta;
+pub mod hmac;
+pub mod incremental;
 pub mod pad;
 
 /// Implements SHA256 implementation for CircuitBuilder
@@ -41,104 +45,116 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
 
         // Process the input with 512 bit chunks aka 64 byte chunks
         for chunk in msg_input_bits.chunks_exact(512) {
-            let mut u: Vec<BoolVariable> = Vec::new();
+            sha256_hash = self.compress_sha256(sha256_hash, chunk, &round_constants);
+        }
 
-            for bit in chunk.iter() {
-                // 0 .. 16 chunk size * 32 bits7
-                u.push(*bit);
-            }
-            for _ in 512..64 * 32 {
-                // 16 * 8 ... 64 * 8 because of L
-                u.push(self._false());
-            }
+        sha256_hash.iter().flat_map(|x| x.to_vec()).collect()
+    }
 
-            let mut w = self.reshape(u);
-
-            for i in 16..64 {
-                let s0 = xor3_arr(
-                    self._right_rotate(w[i - 15], 7),
-                    self._right_rotate(w[i - 15], 18),
-                    self._shr(w[i - 15], 3),
-                    self,
-                );
-
-                let s1 = xor3_arr(
-                    self._right_rotate(w[i - 2], 17),
-                    self._right_rotate(w[i - 2], 19),
-                    self._shr(w[i - 2], 10),
-                    self,
-                );
-
-                let inter1 = self.add_arr(w[i - 16], s0);
-                let inter2 = self.add_arr(inter1, w[i - 7]);
-                w[i] = self.add_arr(s1, inter2);
-            }
-            let mut a = sha256_hash[0];
-            let mut b = sha256_hash[1];
-            let mut c = sha256_hash[2];
-            let mut d = sha256_hash[3];
-            let mut e = sha256_hash[4];
-            let mut f = sha256_hash[5];
-            let mut g = sha256_hash[6];
-            let mut h = sha256_hash[7];
-
-            for i in 0..64 {
-                let sum1 = xor3_arr(
-                    self._right_rotate(e, 6),
-                    self._right_rotate(e, 11),
-                    self._right_rotate(e, 25),
-                    self,
-                );
-                let ch = xor2_arr(
-                    and_arr(e, f, self),
-                    and_arr(not_arr(e, self), g, self),
-                    self,
-                );
-                let temp1 = self.add_arr(h, sum1);
-                let temp2 = self.add_arr(temp1, ch);
-                let temp3 = self.add_arr(temp2, round_constants[i]);
-                let temp4 = self.add_arr(temp3, w[i]);
-                let final_temp1 = temp4;
-
-                let sum0 = xor3_arr(
-                    self._right_rotate(a, 2),
-                    self._right_rotate(a, 13),
-                    self._right_rotate(a, 22),
-                    self,
-                );
-
-                let maj = xor3_arr(
-                    and_arr(a, b, self),
-                    and_arr(a, c, self),
-                    and_arr(b, c, self),
-                    self,
-                );
-                let final_temp2 = self.add_arr(sum0, maj);
-
-                h = g;
-                g = f;
-                f = e;
-                e = self.add_arr(d, final_temp1);
-                d = c;
-                c = b;
-                b = a;
-                a = self.add_arr(final_temp1, final_temp2);
-            }
+    /// Applies the SHA256 compression function to the hash state and a 512 bit chunk.
+    fn compress_sha256(
+        &mut self,
+        sha256_hash: [[BoolVariable; 32]; 8],
+        chunk: &[BoolVariable],
+        round_constants: &[[BoolVariable; 32]; 64],
+    ) -> [[BoolVariable; 32]; 8] {
+        let mut u: Vec<BoolVariable> = Vec::new();
+
+        for bit in chunk.iter() {
+            // 0 .. 16 chunk size * 32 bits7
+            u.push(*bit);
+        }
+        for _ in 512..64 * 32 {
+            // 16 * 8 ... 64 * 8 because of L
+            u.push(self._false());
+        }
 
-            sha256_hash = self.zip_add(sha256_hash, [a, b, c, d, e, f, g, h]);
+        let mut w = self.reshape(u);
+
+        for i in 16..64 {
+            let s0 = xor3_arr(
+                self._right_rotate(w[i - 15], 7),
+                self._right_rotate(w[i - 15], 18),
+                self._shr(w[i - 15], 3),
+                self,
+            );
+
+            let s1 = xor3_arr(
+                self._right_rotate(w[i - 2], 17),
+                self._right_rotate(w[i - 2], 19),
+                self._shr(w[i - 2], 10),
+                self,
+            );
+
+            let inter1 = self.add_arr(w[i - 16], s0);
+            let inter2 = self.add_arr(inter1, w[i - 7]);
+            w[i] = self.add_arr(s1, inter2);
+        }
+        let mut a = sha256_hash[0];
+        let mut b = sha256_hash[1];
+        let mut c = sha256_hash[2];
+        let mut d = sha256_hash[3];
+        let mut e = sha256_hash[4];
+        let mut f = sha256_hash[5];
+        let mut g = sha256_hash[6];
+        let mut h = sha256_hash[7];
+
+        for i in 0..64 {
+            let sum1 = xor3_arr(
+                self._right_rotate(e, 6),
+                self._right_rotate(e, 11),
+                self._right_rotate(e, 25),
+                self,
+            );
+            let ch = xor2_arr(
+                and_arr(e, f, self),
+                and_arr(not_arr(e, self), g, self),
+                self,
+            );
+            let temp1 = self.add_arr(h, sum1);
+            let temp2 = self.add_arr(temp1, ch);
+            let temp3 = self.add_arr(temp2, round_constants[i]);
+            let temp4 = self.add_arr(temp3, w[i]);
+            let final_temp1 = temp4;
+
+            let sum0 = xor3_arr(
+                self._right_rotate(a, 2),
+                self._right_rotate(a, 13),
+                self._right_rotate(a, 22),
+                self,
+            );
+
+            let maj = xor3_arr(
+                and_arr(a, b, self),
+                and_arr(a, c, self),
+                and_arr(b, c, self),
+                self,
+            );
+            let final_temp2 = self.add_arr(sum0, maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = self.add_arr(d, final_temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = self.add_arr(final_temp1, final_temp2);
         }
 
-        sha256_hash.iter().flat_map(|x| x.to_vec()).collect()
+        self.zip_add(sha256_hash, [a, b, c, d, e, f, g, h])
     }
 
     pub fn sha256(&mut self, input: &[ByteVariable]) -> Bytes32Variable {
-        let padded = self.pad_message_sha256(input);
-        let bool_digest: Vec<BoolVariable> = self.process_padded_message(&padded);
-        assert_eq!(bool_digest.len(), 256);
-        // Ok to use `from_variables_unsafe` as we know `process_padded_message` returns 256 bits
-        Bytes32Variable::from_variables_unsafe(
-            &bool_digest.iter().map(|b| b.variable).collect_vec(),
-        )
+        self.profile("sha256", |builder| {
+            let padded = builder.pad_message_sha256(input);
+            let bool_digest: Vec<BoolVariable> = builder.process_padded_message(&padded);
+            assert_eq!(bool_digest.len(), 256);
+            // Ok to use `from_variables_unsafe` as we know `process_padded_message` returns 256 bits
+            Bytes32Variable::from_variables_unsafe(
+                &bool_digest.iter().map(|b| b.variable).collect_vec(),
+            )
+        })
     }
 
     pub fn sha256_pair(
@@ -150,16 +166,57 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         left_bytes.extend(&right.as_bytes());
         self.sha256(&left_bytes)
     }
+
+    /// Computes the SHA256 hash of the first `length` bytes of `input`, where `length` is only
+    /// known at proving time and must be at most `input.len()`.
+    ///
+    /// The padding and the length field are placed at the position given by `length`, every chunk
+    /// that fits in `input` is compressed, and the digest is selected from the state after the
+    /// last chunk of the padded message. The bytes past `length` do not affect the digest.
+    pub fn sha256_variable(
+        &mut self,
+        input: &[ByteVariable],
+        length: U32Variable,
+    ) -> Bytes32Variable {
+        let padded = self.pad_sha256_variable_length(input, length);
+        let last_chunk = self.compute_sha256_last_chunk(length);
+        let padded_bits = padded
+            .iter()
+            .flat_map(|b| b.as_be_bits().to_vec())
+            .collect_vec();
+
+        let mut sha256_hash = self.get_inital_hash();
+        let round_constants = self.get_round_constants();
+        let mut digest = None;
+        for (i, chunk) in padded_bits.chunks_exact(512).enumerate() {
+            sha256_hash = self.compress_sha256(sha256_hash, chunk, &round_constants);
+
+            let state = sha256_hash
+                .iter()
+                .flat_map(|x| x.iter().map(|b| b.variable))
+                .collect_vec();
+            // Ok to use `from_variables_unsafe` as the state is 256 bits.
+            let state = Bytes32Variable::from_variables_unsafe(&state);
+            let chunk_index = self.constant::<U32Variable>(i as u32);
+            let is_last_chunk = self.is_equal(chunk_index, last_chunk);
+            digest = Some(match digest {
+                None => state,
+                Some(digest) => self.select(is_last_chunk, state, digest),
+            });
+        }
+        digest.unwrap()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::env;
 
+    use ethers::types::H256;
     use rand::{thread_rng, Rng};
 
     use super::*;
-    use crate::prelude::{DefaultParameters, U32Variable};
+    use crate::prelude::DefaultParameters;
     use crate::utils::hash::sha256;
 
     type L = DefaultParameters;
@@ -282,4 +339,32 @@ mod tests {
             circuit.test_default_serializers();
         }
     }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_sha256_variable() {
+        const MAX: usize = 128;
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let message = builder.read::<[ByteVariable; MAX]>();
+        let length = builder.read::<U32Variable>();
+        let digest = builder.sha256_variable(&message, length);
+        builder.write(digest);
+        let circuit = builder.build();
+
+        let mut rng = thread_rng();
+        let message: [u8; MAX] = core::array::from_fn(|_| rng.gen());
+        // Lengths around the block boundaries, where the padding spills into an extra block.
+        for length in [0, 55, 56, 64, 119, 120, MAX] {
+            let mut input = circuit.input();
+            input.write::<[ByteVariable; MAX]>(message);
+            input.write::<U32Variable>(length as u32);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(
+                output.read::<Bytes32Variable>(),
+                H256::from(sha256(&message[..length]))
+            );
+        }
+    }
 }
@@ -1,2 +1,160 @@
+use ::starkyx::machine::hash::sha::algorithm::SHAPure;
+use ::starkyx::machine::hash::sha::sha512::SHA512;
+/// Implementation of sha512
+/// reference: https://github.com/thomdixon/pysha2/blob/master/sha2/sha512.py
+use itertools::Itertools;
+
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::vars::EvmVariable;
+use crate::prelude::{ByteVariable, BytesVariable, U64Variable};
+
 pub mod curta;
 pub mod pad;
+
+/// Implements SHA512 for CircuitBuilder with U64Variable words.
+///
+/// The rotations, shifts and bitwise operations of `U64Variable` share bit decompositions, so each
+/// word of the schedule and state is decomposed about once per use in a round.
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    fn xor3_u64(&mut self, a: U64Variable, b: U64Variable, c: U64Variable) -> U64Variable {
+        let a_xor_b = self.xor(a, b);
+        self.xor(a_xor_b, c)
+    }
+
+    fn sigma_u64(&mut self, x: U64Variable, r1: usize, r2: usize, shift: usize) -> U64Variable {
+        let x_r1 = self.rotate_right(x, r1);
+        let x_r2 = self.rotate_right(x, r2);
+        let x_shift = self.shr(x, shift);
+        self.xor3_u64(x_r1, x_r2, x_shift)
+    }
+
+    fn big_sigma_u64(&mut self, x: U64Variable, r1: usize, r2: usize, r3: usize) -> U64Variable {
+        let x_r1 = self.rotate_right(x, r1);
+        let x_r2 = self.rotate_right(x, r2);
+        let x_r3 = self.rotate_right(x, r3);
+        self.xor3_u64(x_r1, x_r2, x_r3)
+    }
+
+    fn process_padded_message_sha512(&mut self, padded: &[ByteVariable]) -> [U64Variable; 8] {
+        let round_constants = SHA512::ROUND_CONSTANTS.map(|x| self.constant::<U64Variable>(x));
+        let mut sha512_hash = SHA512::INITIAL_HASH.map(|x| self.constant::<U64Variable>(x));
+
+        // Process the input with 1024 bit chunks aka 128 byte chunks
+        for chunk in padded.chunks_exact(128) {
+            let mut w = chunk
+                .chunks_exact(8)
+                .map(|bytes| U64Variable::decode(self, bytes))
+                .collect_vec();
+
+            for i in 16..80 {
+                let s0 = self.sigma_u64(w[i - 15], 1, 8, 7);
+                let s1 = self.sigma_u64(w[i - 2], 19, 61, 6);
+                let inter1 = self.add(w[i - 16], s0);
+                let inter2 = self.add(inter1, w[i - 7]);
+                w.push(self.add(s1, inter2));
+            }
+
+            let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = sha512_hash;
+
+            for i in 0..80 {
+                let sum1 = self.big_sigma_u64(e, 14, 18, 41);
+                let e_and_f = self.and(e, f);
+                let not_e = self.not(e);
+                let not_e_and_g = self.and(not_e, g);
+                let ch = self.xor(e_and_f, not_e_and_g);
+                let temp1 = self.add_many(&[h, sum1, ch, round_constants[i], w[i]]);
+
+                let sum0 = self.big_sigma_u64(a, 28, 34, 39);
+                let a_and_b = self.and(a, b);
+                let a_and_c = self.and(a, c);
+                let b_and_c = self.and(b, c);
+                let maj = self.xor3_u64(a_and_b, a_and_c, b_and_c);
+                let temp2 = self.add(sum0, maj);
+
+                h = g;
+                g = f;
+                f = e;
+                e = self.add(d, temp1);
+                d = c;
+                c = b;
+                b = a;
+                a = self.add(temp1, temp2);
+            }
+
+            let state = [a, b, c, d, e, f, g, h];
+            for (hash, word) in sha512_hash.iter_mut().zip(state) {
+                *hash = self.add(*hash, word);
+            }
+        }
+
+        sha512_hash
+    }
+
+    /// Computes the SHA512 hash of a fixed length input in the circuit, without the curta
+    /// accelerator.
+    pub fn sha512(&mut self, input: &[ByteVariable]) -> BytesVariable<64> {
+        self.profile("sha512", |builder| {
+            let padded = builder.pad_message_sha512(input);
+            let digest = builder.process_padded_message_sha512(&padded);
+            let bytes = digest
+                .iter()
+                .flat_map(|word| word.encode(builder))
+                .collect_vec();
+            BytesVariable(bytes.try_into().unwrap())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::utils::hash::sha512;
+
+    fn test_sha512_vector(message: &[u8], expected_digest: [u8; 64]) {
+        assert_eq!(sha512(message), expected_digest);
+
+        let mut builder = DefaultBuilder::new();
+        let message = message
+            .iter()
+            .map(|b| builder.constant::<ByteVariable>(*b))
+            .collect::<Vec<_>>();
+        let digest = builder.sha512(&message);
+        let expected_digest = builder.constant::<BytesVariable<64>>(expected_digest);
+        builder.assert_is_equal(digest, expected_digest);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_sha512_empty() {
+        test_sha512_vector(
+            b"",
+            bytes!("cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"),
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_sha512_abc() {
+        test_sha512_vector(
+            b"abc",
+            bytes!("ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"),
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_sha512_two_blocks() {
+        // 112 bytes, which pads to two 128 byte blocks.
+        test_sha512_vector(
+            b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+            bytes!("8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"),
+        );
+    }
+}
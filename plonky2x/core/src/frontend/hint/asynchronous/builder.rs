@@ -1,3 +1,4 @@
+use super::config::AsyncHintConfig;
 use super::generator::{AsyncHintData, AsyncHintDataRef};
 use super::hint::AsyncHint;
 use crate::frontend::vars::{OutputVariableStream, VariableStream};
@@ -9,9 +10,20 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         &mut self,
         input_stream: VariableStream,
         hint: H,
+    ) -> OutputVariableStream<L, D> {
+        self.async_hint_with_config(input_stream, hint, AsyncHintConfig::default())
+    }
+
+    /// Get the outputs of an asynchronous hint to the circuit, whose calls are run with the
+    /// timeout, retries and concurrency limit of `config`.
+    pub fn async_hint_with_config<H: AsyncHint<L, D>>(
+        &mut self,
+        input_stream: VariableStream,
+        hint: H,
+        config: AsyncHintConfig,
     ) -> OutputVariableStream<L, D> {
         let output_stream = VariableStream::new();
-        let hint_data = AsyncHintData::new(hint, input_stream, output_stream.clone());
+        let hint_data = AsyncHintData::new(hint, config, input_stream, output_stream.clone());
         let hint_id = self.hints.len();
         self.hints.push(Box::new(hint_data.clone()));
 
@@ -24,12 +36,15 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
     use async_trait::async_trait;
     use serde::{Deserialize, Serialize};
     use tokio::time::{sleep, Duration};
 
     use super::*;
     use crate::backend::circuit::CircuitBuild;
+    use crate::frontend::hint::asynchronous::config::Backoff;
     use crate::frontend::vars::ValueStream;
     use crate::prelude::{
         ByteVariable, DefaultBuilder, DefaultParameters, GateRegistry, HintRegistry,
@@ -148,4 +163,196 @@ mod tests {
         let byte_plus_one = output.read::<ByteVariable>();
         assert_eq!(byte_plus_one, 5u8);
     }
+
+    /// Builds a circuit that passes a byte through `TestAsyncGenerator`.
+    fn echo_circuit() -> CircuitBuild<DefaultParameters, 2> {
+        let mut builder = DefaultBuilder::new();
+        let time = builder.read::<ByteVariable>();
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&time);
+        let output_stream = builder.async_hint(input_stream, TestAsyncGenerator {});
+        let back_time = output_stream.read::<ByteVariable>(&mut builder);
+        builder.write(back_time);
+        builder.build()
+    }
+
+    #[tokio::test]
+    async fn test_async_hint_prove_in_runtime() {
+        // The async hints of `prove` run on the runtime of the crate, not the test's runtime.
+        let circuit = echo_circuit();
+        let mut input = circuit.input();
+        input.write::<ByteVariable>(1u8);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<ByteVariable>(), 1u8);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_async_hint_prove_with_current_runtime() {
+        let circuit = echo_circuit();
+        let mut input = circuit.input();
+        input.write::<ByteVariable>(1u8);
+        let (proof, mut output) = tokio::task::block_in_place(|| {
+            circuit.prove_with_executor(&input, tokio::runtime::Handle::current())
+        });
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<ByteVariable>(), 1u8);
+    }
+
+    #[test]
+    fn test_async_hint_prove_with_executor() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let circuit = echo_circuit();
+        let mut input = circuit.input();
+        input.write::<ByteVariable>(1u8);
+        let (proof, mut output) = circuit.prove_with_executor(&input, runtime.handle().clone());
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<ByteVariable>(), 1u8);
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct SlowHint;
+
+    #[async_trait]
+    impl<L: PlonkParameters<D>, const D: usize> AsyncHint<L, D> for SlowHint {
+        async fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            let byte = input_stream.read_value::<ByteVariable>();
+            sleep(Duration::from_secs(10)).await;
+            output_stream.write_value::<ByteVariable>(byte);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "SlowHint\" timed out after 100ms after 2 attempts")]
+    fn test_async_hint_timeout() {
+        setup_logger();
+        let mut builder = DefaultBuilder::new();
+        let byte = builder.read::<ByteVariable>();
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&byte);
+        let output_stream = builder.async_hint(input_stream, SlowHint);
+        let byte = output_stream.read::<ByteVariable>(&mut builder);
+        builder.write(byte);
+        let circuit = builder.build();
+
+        // The config of the registry replaces the default one the hint was built with.
+        let gate_serializer = GateRegistry::<DefaultParameters, 2>::new();
+        let mut hint_serializer = HintRegistry::new();
+        hint_serializer.register_async_hint_with_config::<SlowHint>(
+            AsyncHintConfig::default()
+                .with_timeout(Duration::from_millis(100))
+                .with_max_retries(1),
+        );
+        let circuit_bytes = circuit
+            .serialize(&gate_serializer, &hint_serializer)
+            .unwrap();
+        let circuit = CircuitBuild::<DefaultParameters, 2>::deserialize(
+            &circuit_bytes,
+            &gate_serializer,
+            &hint_serializer,
+        )
+        .unwrap();
+
+        let mut input = circuit.input();
+        input.write::<ByteVariable>(7u8);
+        circuit.prove(&input);
+    }
+
+    static FLAKY_HINT_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct FlakyHint;
+
+    #[async_trait]
+    impl<L: PlonkParameters<D>, const D: usize> AsyncHint<L, D> for FlakyHint {
+        async fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            let byte = input_stream.read_value::<ByteVariable>();
+            if FLAKY_HINT_CALLS.fetch_add(1, Ordering::SeqCst) < 2 {
+                panic!("Test panic, flaky failure");
+            }
+            output_stream.write_value::<ByteVariable>(byte);
+        }
+    }
+
+    #[test]
+    fn test_async_hint_retries() {
+        setup_logger();
+        let mut builder = DefaultBuilder::new();
+        let byte = builder.read::<ByteVariable>();
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&byte);
+        let config =
+            AsyncHintConfig::default()
+                .with_max_retries(2)
+                .with_backoff(Backoff::Exponential {
+                    initial: Duration::from_millis(10),
+                    factor: 2,
+                    max: Duration::from_millis(100),
+                });
+        let output_stream = builder.async_hint_with_config(input_stream, FlakyHint, config);
+        let byte = output_stream.read::<ByteVariable>(&mut builder);
+        builder.write(byte);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<ByteVariable>(7u8);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<ByteVariable>(), 7u8);
+        assert_eq!(FLAKY_HINT_CALLS.load(Ordering::SeqCst), 3);
+    }
+
+    static RUNNING_HINTS: AtomicUsize = AtomicUsize::new(0);
+    static MAX_RUNNING_HINTS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct CountingHint;
+
+    #[async_trait]
+    impl<L: PlonkParameters<D>, const D: usize> AsyncHint<L, D> for CountingHint {
+        async fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            let byte = input_stream.read_value::<ByteVariable>();
+            let running = RUNNING_HINTS.fetch_add(1, Ordering::SeqCst) + 1;
+            MAX_RUNNING_HINTS.fetch_max(running, Ordering::SeqCst);
+            sleep(Duration::from_millis(50)).await;
+            RUNNING_HINTS.fetch_sub(1, Ordering::SeqCst);
+            output_stream.write_value::<ByteVariable>(byte);
+        }
+    }
+
+    #[test]
+    fn test_async_hint_max_concurrency() {
+        setup_logger();
+        let mut builder = DefaultBuilder::new();
+        let config = AsyncHintConfig::default().with_max_concurrency(2);
+        for i in 0..8u8 {
+            let byte = builder.constant::<ByteVariable>(i);
+            let mut input_stream = VariableStream::new();
+            input_stream.write(&byte);
+            let output_stream = builder.async_hint_with_config(input_stream, CountingHint, config);
+            let byte = output_stream.read::<ByteVariable>(&mut builder);
+            builder.write(byte);
+        }
+        let circuit = builder.build();
+
+        let input = circuit.input();
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        for i in 0..8u8 {
+            assert_eq!(output.read::<ByteVariable>(), i);
+        }
+        assert_eq!(MAX_RUNNING_HINTS.load(Ordering::SeqCst), 2);
+    }
 }
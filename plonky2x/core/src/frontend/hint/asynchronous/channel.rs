@@ -1,5 +1,6 @@
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
+use super::config::AsyncHintConfig;
 use super::hint::AnyAsyncHint;
 use crate::frontend::vars::ValueStream;
 use crate::prelude::PlonkParameters;
@@ -14,7 +15,9 @@ pub struct HintChannel<L: PlonkParameters<D>, const D: usize> {
 /// A message sent to the hint handler.
 #[derive(Debug)]
 pub struct HintInMessage<L: PlonkParameters<D>, const D: usize> {
+    pub(crate) id: String,
     pub(crate) hint: Box<dyn AnyAsyncHint<L, D>>,
+    pub(crate) config: AsyncHintConfig,
     pub(crate) tx: UnboundedSender<ValueStream<L, D>>,
     pub(crate) inputs: ValueStream<L, D>,
 }
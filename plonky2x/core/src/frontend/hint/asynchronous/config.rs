@@ -0,0 +1,161 @@
+use core::time::Duration;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Controls how the hint handler runs the calls of an asynchronous hint.
+///
+/// Each call of the hint is run with a `timeout`. If it times out or panics, the call is dropped
+/// and retried up to `max_retries` times, waiting for `backoff` between attempts, after which
+/// witness generation fails. At most `max_concurrency` calls of the same hint type run at once.
+///
+/// The default configuration runs every call once, without a timeout or a concurrency limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AsyncHintConfig {
+    /// The maximum duration of a single attempt of a call.
+    pub timeout: Duration,
+    /// The number of times a call is retried after its first attempt fails.
+    pub max_retries: u32,
+    /// The delay between the attempts of a call.
+    pub backoff: Backoff,
+    /// The maximum number of calls of the hint type that run at the same time, which is positive.
+    ///
+    /// The limit is shared by all the calls of a hint type, and is the one of the first call the
+    /// handler receives.
+    #[serde(deserialize_with = "deserialize_max_concurrency")]
+    max_concurrency: usize,
+}
+
+/// The delay between the attempts of a call of an asynchronous hint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Backoff {
+    /// Retry immediately.
+    None,
+    /// Wait the same duration before every retry.
+    Constant(Duration),
+    /// Wait `initial` before the first retry, and `factor` times longer before each of the next
+    /// ones, up to `max`.
+    Exponential {
+        initial: Duration,
+        factor: u32,
+        max: Duration,
+    },
+}
+
+impl Default for AsyncHintConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::MAX,
+            max_retries: 0,
+            backoff: Backoff::None,
+            max_concurrency: usize::MAX,
+        }
+    }
+}
+
+impl AsyncHintConfig {
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// The maximum number of calls of the hint type that run at the same time.
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency
+    }
+
+    /// Sets the maximum number of calls of the hint type that run at the same time. Panics if
+    /// `max_concurrency` is zero, since no call could then ever run.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        assert!(max_concurrency > 0, "max_concurrency must be positive");
+        self.max_concurrency = max_concurrency;
+        self
+    }
+}
+
+/// Deserializes a `max_concurrency`, rejecting zero as `with_max_concurrency` does.
+fn deserialize_max_concurrency<'de, De: Deserializer<'de>>(
+    deserializer: De,
+) -> Result<usize, De::Error> {
+    let max_concurrency = usize::deserialize(deserializer)?;
+    if max_concurrency == 0 {
+        return Err(De::Error::custom("max_concurrency must be positive"));
+    }
+    Ok(max_concurrency)
+}
+
+impl Backoff {
+    /// The delay before the retry number `retry`, starting from zero.
+    pub fn delay(&self, retry: u32) -> Duration {
+        match *self {
+            Backoff::None => Duration::ZERO,
+            Backoff::Constant(delay) => delay,
+            Backoff::Exponential {
+                initial,
+                factor,
+                max,
+            } => factor
+                .checked_pow(retry)
+                .and_then(|multiplier| initial.checked_mul(multiplier))
+                .map_or(max, |delay| delay.min(max)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(Backoff::None.delay(3), Duration::ZERO);
+
+        let constant = Backoff::Constant(Duration::from_millis(10));
+        assert_eq!(constant.delay(0), Duration::from_millis(10));
+        assert_eq!(constant.delay(5), Duration::from_millis(10));
+
+        let exponential = Backoff::Exponential {
+            initial: Duration::from_millis(10),
+            factor: 2,
+            max: Duration::from_millis(50),
+        };
+        assert_eq!(exponential.delay(0), Duration::from_millis(10));
+        assert_eq!(exponential.delay(1), Duration::from_millis(20));
+        assert_eq!(exponential.delay(2), Duration::from_millis(40));
+        assert_eq!(exponential.delay(3), Duration::from_millis(50));
+        assert_eq!(exponential.delay(100), Duration::from_millis(50));
+    }
+
+    #[test]
+    #[should_panic(expected = "max_concurrency must be positive")]
+    fn test_zero_max_concurrency() {
+        AsyncHintConfig::default().with_max_concurrency(0);
+    }
+
+    #[test]
+    fn test_deserialize_zero_max_concurrency() {
+        let config = AsyncHintConfig::default().with_max_concurrency(3);
+        let bytes = bincode::serialize(&config).unwrap();
+        assert_eq!(
+            bincode::deserialize::<AsyncHintConfig>(&bytes).unwrap(),
+            config
+        );
+
+        let zero = AsyncHintConfig {
+            max_concurrency: 0,
+            ..config
+        };
+        let bytes = bincode::serialize(&zero).unwrap();
+        assert!(bincode::deserialize::<AsyncHintConfig>(&bytes).is_err());
+    }
+}
@@ -9,8 +9,10 @@ use plonky2::util::serialization::IoError;
 use tokio::sync::mpsc::UnboundedSender;
 
 use super::channel::{HintChannel, HintInMessage};
+use super::config::AsyncHintConfig;
 use super::hint::{AnyAsyncHint, AnyHint, AsyncHint};
 use crate::backend::circuit::PlonkParameters;
+use crate::frontend::hint::validation::check_outputs_written;
 use crate::frontend::hint::HintGenerator;
 use crate::frontend::vars::{ValueStream, VariableStream};
 use crate::prelude::{CircuitVariable, Variable};
@@ -71,10 +73,12 @@ impl<L: PlonkParameters<D>, const D: usize> AsyncHintDataRef<L, D> {
 #[derive(Debug)]
 pub(crate) struct AsyncHintGenerator<L: PlonkParameters<D>, H, const D: usize> {
     pub(crate) hint: H,
+    pub(crate) config: AsyncHintConfig,
     pub(crate) tx: UnboundedSender<HintInMessage<L, D>>,
     pub(crate) channel: HintChannel<L, D>,
     pub(crate) input_stream: VariableStream,
     pub(crate) output_stream: VariableStream,
+    pub(crate) input_values: Vec<L::Field>,
     pub(crate) state: HintPoll,
 }
 
@@ -85,6 +89,7 @@ pub(crate) struct AsyncHintGenerator<L: PlonkParameters<D>, H, const D: usize> {
 #[derive(Debug, Clone)]
 pub(crate) struct AsyncHintData<L, H, const D: usize> {
     pub(crate) hint: H,
+    pub(crate) config: AsyncHintConfig,
     pub(crate) input_stream: VariableStream,
     pub(crate) output_stream: VariableStream,
     _marker: std::marker::PhantomData<L>,
@@ -107,9 +112,15 @@ impl<L: PlonkParameters<D>, H: AsyncHint<L, D>, const D: usize> AsyncGeneratorDa
 }
 
 impl<L: PlonkParameters<D>, H: AsyncHint<L, D>, const D: usize> AsyncHintData<L, H, D> {
-    pub fn new(hint: H, input_stream: VariableStream, output_stream: VariableStream) -> Self {
+    pub fn new(
+        hint: H,
+        config: AsyncHintConfig,
+        input_stream: VariableStream,
+        output_stream: VariableStream,
+    ) -> Self {
         Self {
             hint,
+            config,
             input_stream,
             output_stream,
             _marker: std::marker::PhantomData,
@@ -121,6 +132,7 @@ impl<L: PlonkParameters<D>, H: AsyncHint<L, D>, const D: usize> AsyncHintData<L,
             self.input_stream.clone(),
             self.output_stream.clone(),
             self.hint.clone(),
+            self.config,
             tx,
             HintChannel::new(),
         )
@@ -132,6 +144,7 @@ impl<L: PlonkParameters<D>, H: AsyncHint<L, D>, const D: usize> AsyncHintGenerat
         input_stream: VariableStream,
         output_stream: VariableStream,
         hint: H,
+        config: AsyncHintConfig,
         tx: UnboundedSender<HintInMessage<L, D>>,
         channel: HintChannel<L, D>,
     ) -> Self {
@@ -139,8 +152,10 @@ impl<L: PlonkParameters<D>, H: AsyncHint<L, D>, const D: usize> AsyncHintGenerat
             input_stream,
             output_stream,
             hint,
+            config,
             tx,
             channel,
+            input_values: Vec::new(),
             state: HintPoll::InputPending,
         }
     }
@@ -150,7 +165,9 @@ impl<L: PlonkParameters<D>, H: AsyncHint<L, D>, const D: usize> AsyncHintGenerat
         let hint: Box<dyn AnyAsyncHint<L, D>> = Box::new(AnyHint(self.hint.clone()));
 
         let message = HintInMessage {
+            id: H::id(),
             hint,
+            config: self.config,
             tx: self.channel.tx_out.clone(),
             inputs: input_stream,
         };
@@ -188,7 +205,8 @@ impl<L: PlonkParameters<D>, H: AsyncHint<L, D>, const D: usize> AsyncHintRunner<
                     .map(|v| v.get(witness))
                     .collect::<Vec<_>>();
 
-                let input_stream = ValueStream::<L, D>::from_values(input_values);
+                let input_stream = ValueStream::<L, D>::from_values(input_values.clone());
+                self.input_values = input_values;
 
                 self.send(input_stream).unwrap();
 
@@ -202,7 +220,12 @@ impl<L: PlonkParameters<D>, H: AsyncHint<L, D>, const D: usize> AsyncHintRunner<
                     trace!("Async Hint {:?} : recieved output from hint", H::id());
                     let output_values = output_stream.read_all();
                     let output_vars = self.output_stream.real_all();
-                    assert_eq!(output_values.len(), output_vars.len());
+                    check_outputs_written::<L, D>(
+                        &H::id(),
+                        &self.input_values,
+                        output_values,
+                        output_vars.len(),
+                    );
 
                     for (var, val) in output_vars.iter().zip(output_values) {
                         var.set(out_buffer, *val)
@@ -236,7 +259,10 @@ impl<L: PlonkParameters<D>, H: AsyncHint<L, D>, const D: usize> WitnessGenerator
         self.output_stream.serialize_to_writer(dst)?;
 
         let bytes = bincode::serialize(&self.hint).map_err(|_| IoError)?;
-        dst.write_bytes(&bytes)
+        dst.write_bytes(&bytes)?;
+
+        let config_bytes = bincode::serialize(&self.config).map_err(|_| IoError)?;
+        dst.write_bytes(&config_bytes)
     }
 
     fn deserialize(
@@ -1,8 +1,18 @@
-use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use log::debug;
 use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
+use tokio::time::{sleep, timeout};
 
 use super::channel::HintInMessage;
+use super::config::AsyncHintConfig;
+use super::hint::AnyAsyncHint;
+use crate::frontend::hint::validation::display_values;
+use crate::frontend::vars::ValueStream;
 use crate::prelude::PlonkParameters;
 
 /// A handler for asynchronous hints.
@@ -23,17 +33,29 @@ impl<L: PlonkParameters<D>, const D: usize> HintHandler<L, D> {
     ///
     /// The handler will wait for requests and spawns a new task for each request. Awaiting this
     /// this method will return `Ok(())` when all tasks have finished, or `Err` if any task fails.
+    ///
+    /// Each request is run according to the `AsyncHintConfig` of its hint. The requests of a hint
+    /// type share a concurrency limit.
     pub async fn run(&mut self) -> Result<()> {
         let mut set = JoinSet::new();
+        let mut semaphores = HashMap::<String, Arc<Semaphore>>::new();
 
         loop {
             tokio::select! {
                 Some(message) = self.rx.recv() => {
-                    let HintInMessage { hint, tx, inputs } = message;
+                    let HintInMessage { id, hint, config, tx, inputs } = message;
+                    let semaphore = semaphores
+                        .entry(id.clone())
+                        .or_insert_with(|| {
+                            let permits = config.max_concurrency().min(Semaphore::MAX_PERMITS);
+                            Arc::new(Semaphore::new(permits))
+                        })
+                        .clone();
 
                     set.spawn(async move {
-                        let outputs = hint.hint_fn(inputs).await;
-                        tx.send(outputs)
+                        let outputs = run_hint(&id, hint.into(), config, &semaphore, inputs).await?;
+                        tx.send(outputs)?;
+                        Ok::<_, anyhow::Error>(())
                     });
                 }
                 Some(result) = set.join_next() => {
@@ -46,3 +68,45 @@ impl<L: PlonkParameters<D>, const D: usize> HintHandler<L, D> {
         }
     }
 }
+
+/// Runs a call of a hint, retrying it on timeouts and panics as allowed by `config`.
+async fn run_hint<L: PlonkParameters<D>, const D: usize>(
+    id: &str,
+    hint: Arc<dyn AnyAsyncHint<L, D>>,
+    config: AsyncHintConfig,
+    semaphore: &Semaphore,
+    mut inputs: ValueStream<L, D>,
+) -> Result<ValueStream<L, D>> {
+    let mut failure = String::new();
+    for attempt in 0..=config.max_retries {
+        if attempt > 0 {
+            sleep(config.backoff.delay(attempt - 1)).await;
+        }
+
+        let permit = semaphore.acquire().await?;
+        let attempt_hint = hint.clone();
+        let attempt_inputs = inputs.clone();
+        let mut handle = tokio::spawn(async move { attempt_hint.hint_fn(attempt_inputs).await });
+        let result = timeout(config.timeout, &mut handle).await;
+        drop(permit);
+
+        failure = match result {
+            Ok(Ok(outputs)) => return Ok(outputs),
+            Ok(Err(e)) => format!("failed: {}", e),
+            Err(_) => {
+                // Dropping the join handle does not cancel the task.
+                handle.abort();
+                format!("timed out after {:?}", config.timeout)
+            }
+        };
+        debug!("Async hint {:?} : attempt {} {}", id, attempt + 1, failure);
+    }
+
+    Err(anyhow!(
+        "Async hint {:?} {} after {} attempts, inputs: {}",
+        id,
+        failure,
+        config.max_retries + 1,
+        display_values(inputs.read_all())
+    ))
+}
@@ -1,5 +1,6 @@
 pub mod builder;
 pub mod channel;
+pub mod config;
 pub mod generator;
 pub mod handler;
 pub mod hint;
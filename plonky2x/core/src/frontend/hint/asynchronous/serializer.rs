@@ -4,6 +4,7 @@ use plonky2::iop::generator::WitnessGeneratorRef;
 use plonky2::plonk::circuit_data::CommonCircuitData;
 use plonky2::util::serialization::{Buffer, IoError, IoResult};
 
+use super::config::AsyncHintConfig;
 use super::generator::{AsyncHintData, AsyncHintDataRef};
 use super::hint::AsyncHint;
 use crate::backend::circuit::Serializer;
@@ -11,15 +12,45 @@ use crate::frontend::vars::VariableStream;
 use crate::prelude::PlonkParameters;
 use crate::utils::serde::BufferRead;
 
+/// A serializer for asynchronous hints.
+///
+/// If the serializer has a config, it replaces the config the hints were serialized with.
 #[derive(Debug, Clone)]
-pub struct AsyncHintSerializer<L, H>(PhantomData<L>, PhantomData<H>);
+pub struct AsyncHintSerializer<L, H>(PhantomData<L>, PhantomData<H>, Option<AsyncHintConfig>);
 
 impl<L, H> AsyncHintSerializer<L, H> {
     pub fn new() -> Self {
-        Self(PhantomData, PhantomData)
+        Self(PhantomData, PhantomData, None)
+    }
+
+    pub fn with_config(config: AsyncHintConfig) -> Self {
+        Self(PhantomData, PhantomData, Some(config))
     }
 }
 
+/// Reads the data of an asynchronous hint, replacing its config by `config` if given.
+fn read_hint_data<L: PlonkParameters<D>, H: AsyncHint<L, D>, const D: usize>(
+    buf: &mut Buffer,
+    config: Option<AsyncHintConfig>,
+) -> IoResult<AsyncHintData<L, H, D>> {
+    let input_stream = VariableStream::deserialize_from_reader(buf)?;
+    let output_stream = VariableStream::deserialize_from_reader(buf)?;
+
+    let bytes = buf.read_bytes()?;
+    let hint: H = bincode::deserialize(&bytes).map_err(|_| IoError)?;
+
+    let config_bytes = buf.read_bytes()?;
+    let serialized_config: AsyncHintConfig =
+        bincode::deserialize(&config_bytes).map_err(|_| IoError)?;
+
+    Ok(AsyncHintData::new(
+        hint,
+        config.unwrap_or(serialized_config),
+        input_stream,
+        output_stream,
+    ))
+}
+
 impl<L, H> Default for AsyncHintSerializer<L, H> {
     fn default() -> Self {
         Self::new()
@@ -34,13 +65,7 @@ impl<L: PlonkParameters<D>, H: AsyncHint<L, D>, const D: usize>
         buf: &mut Buffer,
         _common_data: &CommonCircuitData<L::Field, D>,
     ) -> IoResult<WitnessGeneratorRef<L::Field, D>> {
-        let input_stream = VariableStream::deserialize_from_reader(buf)?;
-        let output_stream = VariableStream::deserialize_from_reader(buf)?;
-
-        let bytes = buf.read_bytes()?;
-        let hint: H = bincode::deserialize(&bytes).map_err(|_| IoError)?;
-        let hint_data = AsyncHintData::<L, H, D>::new(hint, input_stream, output_stream);
-
+        let hint_data = read_hint_data::<L, H, D>(buf, self.2)?;
         Ok(WitnessGeneratorRef::new(hint_data))
     }
 
@@ -62,13 +87,7 @@ impl<L: PlonkParameters<D>, H: AsyncHint<L, D>, const D: usize>
         buf: &mut Buffer,
         _common_data: &CommonCircuitData<L::Field, D>,
     ) -> IoResult<AsyncHintDataRef<L, D>> {
-        let input_stream = VariableStream::deserialize_from_reader(buf)?;
-        let output_stream = VariableStream::deserialize_from_reader(buf)?;
-
-        let bytes = buf.read_bytes()?;
-        let hint: H = bincode::deserialize(&bytes).map_err(|_| IoError)?;
-        let hint_data = AsyncHintData::<L, H, D>::new(hint, input_stream, output_stream);
-
+        let hint_data = read_hint_data::<L, H, D>(buf, self.2)?;
         Ok(AsyncHintDataRef::new(hint_data))
     }
 
@@ -4,8 +4,10 @@ use super::vars::VariableStream;
 use crate::prelude::PlonkParameters;
 
 pub mod asynchronous;
+pub mod secret;
 pub mod simple;
 pub mod synchronous;
+pub(crate) mod validation;
 
 pub trait HintGenerator<L: PlonkParameters<D>, const D: usize>:
     WitnessGenerator<L::Field, D>
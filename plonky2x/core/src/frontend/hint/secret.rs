@@ -0,0 +1,162 @@
+use core::cell::RefCell;
+use core::fmt::Debug;
+
+use log::info;
+use plonky2::hash::hash_types::RichField;
+
+use crate::frontend::vars::CircuitVariable;
+
+thread_local! {
+    static DECLASSIFICATIONS: RefCell<Option<Vec<String>>> = RefCell::new(None);
+}
+
+/// A private value read inside a hint.
+///
+/// Hints run natively, so branching on a private value (e.g. returning early on a zero balance)
+/// leaks it through the prover's timing. `SecretValue` does not implement `PartialEq`, `Ord` or
+/// any other way of inspecting the value: it can only be transformed with `map`, combined with
+/// the branchless `select`, written back to a stream, or explicitly released with `declassify`.
+///
+/// ```compile_fail
+/// # use plonky2x::frontend::hint::secret::SecretValue;
+/// let balance = SecretValue::new(0u64);
+/// if balance == SecretValue::new(0u64) {
+///     return;
+/// }
+/// ```
+pub struct SecretValue<T>(T);
+
+impl<T> SecretValue<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Applies a function to the value. The function must not branch on its input.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> SecretValue<U> {
+        SecretValue(f(self.0))
+    }
+
+    /// Combines two secret values. The function must not branch on its inputs.
+    pub fn zip_with<U, V>(
+        self,
+        other: SecretValue<U>,
+        f: impl FnOnce(T, U) -> V,
+    ) -> SecretValue<V> {
+        SecretValue(f(self.0, other.0))
+    }
+
+    /// Releases the value. The justification is written to the prove log, along with the type of
+    /// the value.
+    pub fn declassify(self, justification: &str) -> T {
+        let event = format!("{}: {}", core::any::type_name::<T>(), justification);
+        info!(target: "plonky2x::secret", "declassified {}", event);
+        DECLASSIFICATIONS.with(|events| {
+            if let Some(events) = events.borrow_mut().as_mut() {
+                events.push(event);
+            }
+        });
+        self.0
+    }
+
+    /// Returns the value without recording a declassification. Only used to write the value back
+    /// into a stream.
+    pub(crate) fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl SecretValue<bool> {
+    /// Returns `a` if the condition is true and `b` otherwise, computed as `b + c * (a - b)` over
+    /// the field elements of the values so that both cases take the same time.
+    pub fn select<V: CircuitVariable, F: RichField>(
+        &self,
+        a: SecretValue<V::ValueType<F>>,
+        b: SecretValue<V::ValueType<F>>,
+    ) -> SecretValue<V::ValueType<F>> {
+        let c = F::from_bool(self.0);
+        let elements = V::elements::<F>(a.0)
+            .into_iter()
+            .zip(V::elements::<F>(b.0))
+            .map(|(a, b)| b + c * (a - b))
+            .collect::<Vec<_>>();
+        SecretValue(V::from_elements::<F>(&elements))
+    }
+}
+
+impl<T: Clone> Clone for SecretValue<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Debug for SecretValue<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("SecretValue(..)")
+    }
+}
+
+/// Runs `f` and returns the justifications of the values declassified on the current thread while
+/// it ran.
+pub fn record_declassifications<R>(f: impl FnOnce() -> R) -> (R, Vec<String>) {
+    let previous = DECLASSIFICATIONS.with(|events| events.replace(Some(Vec::new())));
+    let result = f();
+    let events = DECLASSIFICATIONS.with(|events| events.replace(previous));
+    (result, events.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::frontend::hint::simple::hint::Hint;
+    use crate::prelude::*;
+
+    /// Returns the input if it is nonzero and a default value otherwise, and reveals whether the
+    /// default was used.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct DefaultIfZeroHint;
+
+    impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for DefaultIfZeroHint {
+        fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            let value = input_stream.read_secret::<U64Variable>();
+            let is_zero = value.clone().map(|v| v == 0);
+            let default = SecretValue::new(42u64);
+            let result = is_zero.select::<U64Variable, L::Field>(default, value);
+            output_stream.write_secret::<U64Variable>(result);
+            output_stream
+                .write_value::<BoolVariable>(is_zero.declassify("whether the default was used"));
+        }
+    }
+
+    #[test]
+    fn test_secret_value_hint() {
+        let mut builder = DefaultBuilder::new();
+        let value = builder.read::<U64Variable>();
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&value);
+        let output_stream = builder.hint(input_stream, DefaultIfZeroHint);
+        let result = output_stream.read::<U64Variable>(&mut builder);
+        let is_default = output_stream.read::<BoolVariable>(&mut builder);
+        builder.write(result);
+        builder.write(is_default);
+        let circuit = builder.build();
+
+        for (value, expected, expected_is_default) in [(0, 42, true), (7, 7, false)] {
+            let mut input = circuit.input();
+            input.write::<U64Variable>(value);
+            let ((proof, mut output), events) = record_declassifications(|| circuit.prove(&input));
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<U64Variable>(), expected);
+            assert_eq!(output.read::<BoolVariable>(), expected_is_default);
+            assert_eq!(
+                events,
+                vec!["bool: whether the default was used".to_string()]
+            );
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! Memoization of the outputs of deterministic hints across proofs.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use plonky2::field::types::{Field, PrimeField64};
+use serde::{Deserialize, Serialize};
+
+use super::hint::Hint;
+use crate::frontend::vars::ValueStream;
+use crate::prelude::PlonkParameters;
+
+lazy_static! {
+    /// The caches of the hints, keyed by the hint id and the serialized hint, so that they are
+    /// shared by all the proofs of the process.
+    static ref HINT_CACHES: Mutex<HashMap<(String, Vec<u8>), Arc<Mutex<LruCache>>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// A hint that memoizes the outputs of `H` for each of its inputs.
+///
+/// The outputs are kept in a least recently used cache of `capacity` entries, shared by all the
+/// `CachedHint`s with the same hint in the process, so that proofs of the same circuit with
+/// recurring inputs skip the hint. The first hint to use a cache sets its capacity. If `H` is not
+/// deterministic, the hint is always run.
+///
+/// The hint must be registered as `CachedHint<H>` in the hint registry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedHint<H> {
+    pub hint: H,
+    pub capacity: usize,
+}
+
+impl<H> CachedHint<H> {
+    pub fn new(hint: H, capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Self { hint, capacity }
+    }
+}
+
+impl<H: Serialize> CachedHint<H> {
+    fn cache(&self, id: String) -> Arc<Mutex<LruCache>> {
+        let state = bincode::serialize(&self.hint).unwrap();
+        HINT_CACHES
+            .lock()
+            .unwrap()
+            .entry((id, state))
+            .or_insert_with(|| Arc::new(Mutex::new(LruCache::new(self.capacity))))
+            .clone()
+    }
+}
+
+impl<L: PlonkParameters<D>, H: Hint<L, D>, const D: usize> Hint<L, D> for CachedHint<H> {
+    const DETERMINISTIC: bool = H::DETERMINISTIC;
+
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        if !H::DETERMINISTIC {
+            return self.hint.hint(input_stream, output_stream);
+        }
+
+        let inputs = input_stream
+            .read_all()
+            .iter()
+            .map(|x| x.to_canonical_u64())
+            .collect_vec();
+        let cache = self.cache(H::id());
+
+        let cached_outputs = cache.lock().unwrap().get(&inputs);
+        if let Some(outputs) = cached_outputs {
+            input_stream.read_exact(inputs.len());
+            let outputs = outputs
+                .into_iter()
+                .map(L::Field::from_canonical_u64)
+                .collect_vec();
+            output_stream.write_slice(&outputs);
+            return;
+        }
+
+        self.hint.hint(input_stream, output_stream);
+        let outputs = output_stream
+            .read_all()
+            .iter()
+            .map(|x| x.to_canonical_u64())
+            .collect_vec();
+        cache.lock().unwrap().insert(inputs, outputs);
+    }
+}
+
+/// A least recently used cache from the input values of a hint to its output values.
+#[derive(Debug)]
+struct LruCache {
+    capacity: usize,
+    /// The number of accesses, used to order the entries by recency.
+    clock: u64,
+    entries: HashMap<Vec<u64>, (Vec<u64>, u64)>,
+    /// The keys of the entries, by the time of their last access.
+    recency: BTreeMap<u64, Vec<u64>>,
+}
+
+impl LruCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            clock: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+        }
+    }
+
+    /// Updates the time of the last access of `key`, which must be in the cache.
+    fn touch(&mut self, key: &[u64]) {
+        self.clock += 1;
+        let (_, last_access) = self.entries.get_mut(key).unwrap();
+        let key = self.recency.remove(&*last_access).unwrap();
+        *last_access = self.clock;
+        self.recency.insert(self.clock, key);
+    }
+
+    fn get(&mut self, key: &[u64]) -> Option<Vec<u64>> {
+        if !self.entries.contains_key(key) {
+            return None;
+        }
+        self.touch(key);
+        Some(self.entries[key].0.clone())
+    }
+
+    fn insert(&mut self, key: Vec<u64>, value: Vec<u64>) {
+        if let Some((entry, _)) = self.entries.get_mut(&key) {
+            *entry = value;
+            self.touch(&key);
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            let (_, evicted) = self.recency.pop_first().unwrap();
+            self.entries.remove(&evicted);
+        }
+        self.clock += 1;
+        self.recency.insert(self.clock, key.clone());
+        self.entries.insert(key, (value, self.clock));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::frontend::vars::VariableStream;
+    use crate::prelude::{ByteVariable, DefaultBuilder, DefaultParameters};
+
+    static DOUBLE_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static RANDOM_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Double;
+
+    impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for Double {
+        fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            DOUBLE_CALLS.fetch_add(1, Ordering::SeqCst);
+            let a = input_stream.read_value::<ByteVariable>();
+            output_stream.write_value::<ByteVariable>(a.wrapping_mul(2));
+        }
+    }
+
+    /// A hint standing for one that is not a function of its inputs, e.g. a sampled nonce.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Random;
+
+    impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for Random {
+        const DETERMINISTIC: bool = false;
+
+        fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            RANDOM_CALLS.fetch_add(1, Ordering::SeqCst);
+            let a = input_stream.read_value::<ByteVariable>();
+            output_stream.write_value::<ByteVariable>(a);
+        }
+    }
+
+    /// Proves a circuit calling `hint` on two copies of the same input, and returns the outputs.
+    fn prove_twice_hinted<H: Hint<DefaultParameters, 2>>(hint: H, value: u8) -> (u8, u8) {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<ByteVariable>();
+        for _ in 0..2 {
+            let mut input_stream = VariableStream::new();
+            input_stream.write(&a);
+            let output_stream = builder.hint(input_stream, hint.clone());
+            let b = output_stream.read::<ByteVariable>(&mut builder);
+            builder.write(b);
+        }
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<ByteVariable>(value);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        (output.read::<ByteVariable>(), output.read::<ByteVariable>())
+    }
+
+    #[test]
+    fn test_cached_hint() {
+        let hint = CachedHint::new(Double, 16);
+        assert_eq!(prove_twice_hinted(hint.clone(), 3), (6, 6));
+        assert_eq!(DOUBLE_CALLS.load(Ordering::SeqCst), 1);
+
+        // The second proof with the same inputs reads both outputs from the cache.
+        assert_eq!(prove_twice_hinted(hint.clone(), 3), (6, 6));
+        assert_eq!(DOUBLE_CALLS.load(Ordering::SeqCst), 1);
+
+        assert_eq!(prove_twice_hinted(hint, 5), (10, 10));
+        assert_eq!(DOUBLE_CALLS.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_cached_hint_non_deterministic() {
+        let hint = CachedHint::new(Random, 16);
+        assert_eq!(prove_twice_hinted(hint.clone(), 3), (3, 3));
+        assert_eq!(prove_twice_hinted(hint, 3), (3, 3));
+        assert_eq!(RANDOM_CALLS.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_lru_cache() {
+        let mut cache = LruCache::new(2);
+        cache.insert(vec![1], vec![10]);
+        cache.insert(vec![2], vec![20]);
+        assert_eq!(cache.get(&[1]), Some(vec![10]));
+
+        // The least recently used entry is evicted.
+        cache.insert(vec![3], vec![30]);
+        assert_eq!(cache.get(&[2]), None);
+        assert_eq!(cache.get(&[1]), Some(vec![10]));
+        assert_eq!(cache.get(&[3]), Some(vec![30]));
+
+        cache.insert(vec![1], vec![11]);
+        cache.insert(vec![4], vec![40]);
+        assert_eq!(cache.get(&[3]), None);
+        assert_eq!(cache.get(&[1]), Some(vec![11]));
+    }
+}
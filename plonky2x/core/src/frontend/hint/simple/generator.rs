@@ -1,4 +1,5 @@
 use core::marker::PhantomData;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 
 use plonky2::iop::generator::{GeneratedValues, WitnessGenerator};
 use plonky2::iop::target::Target;
@@ -7,6 +8,10 @@ use plonky2::plonk::circuit_data::CommonCircuitData;
 use plonky2::util::serialization::{Buffer, IoError, IoResult};
 
 use super::hint::Hint;
+use crate::backend::circuit::dump::{record_hint_inputs, record_hint_outputs};
+use crate::frontend::hint::validation::{
+    check_inputs_read, check_outputs_written, display_values, panic_message,
+};
 use crate::frontend::hint::HintGenerator;
 use crate::frontend::vars::{ValueStream, VariableStream};
 use crate::prelude::{CircuitVariable, PlonkParameters};
@@ -64,18 +69,29 @@ impl<L: PlonkParameters<D>, const D: usize, H: Hint<L, D>> WitnessGenerator<L::F
             .iter()
             .map(|v| v.get(witness))
             .collect::<Vec<_>>();
-        let mut input_stream = ValueStream::from_values(input_values);
+        record_hint_inputs(&H::id(), &input_values);
+        let mut input_stream = ValueStream::from_values(input_values.clone());
         let mut output_stream = ValueStream::new();
 
-        self.hint.hint(&mut input_stream, &mut output_stream);
+        // Add the hint and its inputs to its panics, e.g. when it reads more values than the
+        // circuit wrote to its input stream.
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            self.hint.hint(&mut input_stream, &mut output_stream)
+        }));
+        if let Err(payload) = result {
+            panic!(
+                "Hint {} panicked: {}, inputs: {}",
+                H::id(),
+                panic_message(&*payload),
+                display_values(&input_values)
+            );
+        }
+        check_inputs_read(&H::id(), &input_values, &mut input_stream);
 
         let output_values = output_stream.read_all();
         let output_vars = self.output_stream.real_all();
-        assert_eq!(
-            output_values.len(),
-            output_vars.len(),
-            "Hint output stream length does not match output variables length"
-        );
+        check_outputs_written::<L, D>(&H::id(), &input_values, output_values, output_vars.len());
+        record_hint_outputs(output_values);
 
         for (var, val) in output_vars.iter().zip(output_values) {
             var.set(out_buffer, *val);
@@ -39,6 +39,10 @@ use crate::prelude::CircuitBuilder;
 pub trait Hint<L: PlonkParameters<D>, const D: usize>:
     'static + Debug + Clone + Send + Sync + serde::Serialize + DeserializeOwned
 {
+    /// Whether the outputs of the hint only depend on its inputs. Hints that are not deterministic
+    /// are never memoized by `CachedHint`.
+    const DETERMINISTIC: bool = true;
+
     /// the hint function.
     fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>);
 
@@ -133,4 +137,132 @@ mod tests {
         assert_eq!(byte_plus_two, 7u8);
         assert_eq!(c_plus_3, 4u8);
     }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Square;
+
+    impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for Square {
+        fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            let x = input_stream.read_value::<Variable>();
+            output_stream.write_value::<Variable>(x * x);
+        }
+    }
+
+    #[test]
+    fn test_dependent_hints() {
+        type F = GoldilocksField;
+
+        let mut builder = DefaultBuilder::new();
+        let x = builder.read::<Variable>();
+
+        // The second hint reads a value computed from the output of the first one, and is only
+        // run once that value is set.
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&x);
+        let a = builder
+            .hint(input_stream, Square)
+            .read::<Variable>(&mut builder);
+        let one = builder.one::<Variable>();
+        let b = builder.add(a, one);
+        let three = builder.constant::<Variable>(F::from_canonical_u64(3));
+        let c = builder.mul(b, three);
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&c);
+        let d = builder
+            .hint(input_stream, Square)
+            .read::<Variable>(&mut builder);
+        builder.write(d);
+
+        let circuit = builder.build();
+        for i in 0..10 {
+            let mut input = circuit.input();
+            input.write::<Variable>(F::from_canonical_u64(i));
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            let expected = (3 * (i * i + 1)).pow(2);
+            assert_eq!(output.read::<Variable>(), F::from_canonical_u64(expected));
+        }
+    }
+
+    /// A hint that reads `nb_reads` bytes and writes `nb_writes` bytes.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct MisbehavingHint {
+        nb_reads: usize,
+        nb_writes: usize,
+    }
+
+    impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for MisbehavingHint {
+        fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            let bytes = input_stream.read_vec::<ByteVariable>(self.nb_reads);
+            for i in 0..self.nb_writes {
+                output_stream.write_value::<ByteVariable>(bytes.get(i).copied().unwrap_or(0));
+            }
+        }
+    }
+
+    /// Proves a circuit that writes one byte to `hint` and reads one byte from it.
+    fn prove_one_byte_hint(hint: MisbehavingHint) {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<ByteVariable>();
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&a);
+        let output_stream = builder.hint(input_stream, hint);
+        let b = output_stream.read::<ByteVariable>(&mut builder);
+        builder.write(b);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<ByteVariable>(1u8);
+        circuit.prove(&input);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "MisbehavingHint wrote 16 output values, but the circuit reads 8, \
+                               inputs: [0, 0, 0, 0, 0, 0, 0, 1]"
+    )]
+    fn test_hint_writes_too_many_outputs() {
+        prove_one_byte_hint(MisbehavingHint {
+            nb_reads: 1,
+            nb_writes: 2,
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "MisbehavingHint wrote 0 output values, but the circuit reads 8")]
+    fn test_hint_writes_too_few_outputs() {
+        prove_one_byte_hint(MisbehavingHint {
+            nb_reads: 1,
+            nb_writes: 0,
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "MisbehavingHint panicked: Not enough elements in Stream: \
+                               reading 8 elements at position 8 of 8")]
+    fn test_hint_reads_too_many_inputs() {
+        prove_one_byte_hint(MisbehavingHint {
+            nb_reads: 2,
+            nb_writes: 1,
+        });
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "MisbehavingHint read 0 of the 8 input values written by the circuit"
+    )]
+    fn test_hint_reads_too_few_inputs() {
+        prove_one_byte_hint(MisbehavingHint {
+            nb_reads: 0,
+            nb_writes: 1,
+        });
+    }
 }
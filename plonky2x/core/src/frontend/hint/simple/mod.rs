@@ -1,3 +1,4 @@
+pub mod cached;
 pub(crate) mod generator;
 pub mod hint;
 pub(crate) mod serializer;
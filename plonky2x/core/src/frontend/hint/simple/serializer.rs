@@ -125,4 +125,65 @@ mod tests {
         let byte_plus_one = output.read::<ByteVariable>();
         assert_eq!(byte_plus_one, 7u8);
     }
+
+    /// A hint whose state has no meaningful default.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct PrefixedChunks {
+        prefix: String,
+        address: [u8; 20],
+        chunk_size: usize,
+    }
+
+    impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for PrefixedChunks {
+        fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            let a = input_stream.read_value::<ByteVariable>();
+            let chunk = self.address[..self.chunk_size]
+                .iter()
+                .fold(a, |acc, byte| acc ^ byte);
+            output_stream.write_value::<ByteVariable>(chunk ^ self.prefix.len() as u8)
+        }
+    }
+
+    #[test]
+    fn test_stateful_hint_serialization() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<ByteVariable>();
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&a);
+        let mut address = [0u8; 20];
+        address[0] = 0x0f;
+        address[1] = 0xf0;
+        address[2] = 0xff;
+        let hint = PrefixedChunks {
+            prefix: "https://rpc".to_string(),
+            address,
+            chunk_size: 2,
+        };
+        let output_stream = builder.hint(input_stream, hint);
+        let b = output_stream.read::<ByteVariable>(&mut builder);
+        builder.write(b);
+        let circuit = builder.build();
+
+        let gate_serializer = GateRegistry::new();
+        let mut hint_serializer = HintRegistry::new();
+        hint_serializer.register_hint::<PrefixedChunks>();
+        let bytes = circuit
+            .serialize(&gate_serializer, &hint_serializer)
+            .unwrap();
+        drop(circuit);
+        let circuit =
+            CircuitBuild::deserialize(&bytes, &gate_serializer, &hint_serializer).unwrap();
+
+        let mut input = circuit.input();
+        input.write::<ByteVariable>(0x01u8);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        // 0x01 ^ 0x0f ^ 0xf0 ^ 11, with the address chunk and prefix of the restored hint.
+        assert_eq!(output.read::<ByteVariable>(), 0x01 ^ 0xff ^ 11);
+    }
 }
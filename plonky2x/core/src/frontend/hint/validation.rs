@@ -0,0 +1,71 @@
+//! Checks that hints read and write as many values as the circuit expects.
+
+use core::any::Any;
+use core::fmt::Display;
+
+use itertools::Itertools;
+
+use crate::frontend::vars::ValueStream;
+use crate::prelude::PlonkParameters;
+
+/// The number of values shown when a hint's inputs are dumped in an error.
+const MAX_DISPLAYED_VALUES: usize = 16;
+
+/// Formats `values`, truncated to their first `MAX_DISPLAYED_VALUES`.
+pub(crate) fn display_values<F: Display>(values: &[F]) -> String {
+    let displayed = values.iter().take(MAX_DISPLAYED_VALUES).join(", ");
+    if values.len() > MAX_DISPLAYED_VALUES {
+        format!("[{}, ...] ({} values)", displayed, values.len())
+    } else {
+        format!("[{}]", displayed)
+    }
+}
+
+/// Returns the message of a panic payload.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+/// Panics if the hint with id `hint_id` did not read all of the `inputs` that the circuit wrote
+/// to its `input_stream`.
+pub(crate) fn check_inputs_read<L: PlonkParameters<D>, const D: usize>(
+    hint_id: &str,
+    inputs: &[L::Field],
+    input_stream: &mut ValueStream<L, D>,
+) {
+    let nb_unread = input_stream.read_all().len();
+    if nb_unread > 0 {
+        panic!(
+            "Hint {} read {} of the {} input values written by the circuit, inputs: {}",
+            hint_id,
+            inputs.len() - nb_unread,
+            inputs.len(),
+            display_values(inputs)
+        );
+    }
+}
+
+/// Panics if the hint with id `hint_id` wrote `outputs` of another length than the
+/// `nb_expected` values that the circuit reads from its output stream.
+pub(crate) fn check_outputs_written<L: PlonkParameters<D>, const D: usize>(
+    hint_id: &str,
+    inputs: &[L::Field],
+    outputs: &[L::Field],
+    nb_expected: usize,
+) {
+    if outputs.len() != nb_expected {
+        panic!(
+            "Hint {} wrote {} output values, but the circuit reads {}, inputs: {}",
+            hint_id,
+            outputs.len(),
+            nb_expected,
+            display_values(inputs)
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! The map phase of MapReduce on its own, for callers that aggregate the map proofs themselves.
+
+use core::marker::PhantomData;
+
+use itertools::Itertools;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+
+use super::prover::LeafProver;
+use super::{MapReduceInputVariable, MapReduceInputVariableValue, MapReduceOutputVariable};
+use crate::backend::circuit::{CircuitBuild, CircuitSerializer, PublicInput};
+use crate::prelude::{ArrayVariable, CircuitBuilder, CircuitVariable, PlonkParameters};
+
+/// Builds the map circuit of a MapReduce once, and proves it over chunks of `B` inputs.
+///
+/// The map circuit is the one built by `mapreduce`: its public inputs are the context and a chunk
+/// of inputs, followed by the context, the output of the map function and the hash of the chunk.
+#[derive(Debug)]
+pub struct MapDriver<L, Ctx, Input, Output, Serializer, const B: usize, const D: usize>
+where
+    L: PlonkParameters<D>,
+{
+    circuit: CircuitBuild<L, D>,
+    _phantom: PhantomData<(Ctx, Input, Output, Serializer)>,
+}
+
+impl<L, Ctx, Input, Output, Serializer, const B: usize, const D: usize>
+    MapDriver<L, Ctx, Input, Output, Serializer, B, D>
+where
+    L: PlonkParameters<D>,
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+    Ctx: CircuitVariable,
+    Input: CircuitVariable,
+    Output: CircuitVariable,
+    Serializer: CircuitSerializer,
+{
+    /// Builds the map circuit of `map_fn` and saves it to the build folder, where the provers load
+    /// it from.
+    pub fn new<MapFn>(map_fn: MapFn) -> Self
+    where
+        MapFn: Fn(Ctx, ArrayVariable<Input, B>, &mut CircuitBuilder<L, D>) -> Output,
+    {
+        let circuit = CircuitBuilder::<L, D>::new().build_map(&map_fn);
+        let circuit_path = format!("./build/{}.circuit", circuit.id());
+        circuit.save(
+            &circuit_path,
+            &Serializer::gate_registry::<L, D>(),
+            &Serializer::generator_registry::<L, D>(),
+        );
+        Self {
+            circuit,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The map circuit.
+    pub fn circuit(&self) -> &CircuitBuild<L, D> {
+        &self.circuit
+    }
+
+    /// Proves the map circuit over each chunk of `B` inputs with `prover`, and returns the proofs
+    /// with the outputs of the map function, in the order of the chunks. As in `mapreduce`, the
+    /// number of chunks must be a power of two.
+    #[allow(clippy::type_complexity)]
+    pub fn prove<P: LeafProver>(
+        &self,
+        prover: &P,
+        ctx: Ctx::ValueType<L::Field>,
+        inputs: &[Input::ValueType<L::Field>],
+    ) -> Vec<(
+        ProofWithPublicInputs<L::Field, L::Config, D>,
+        Output::ValueType<L::Field>,
+    )> {
+        assert_eq!(inputs.len() % B, 0, "inputs length must be a multiple of B");
+        assert!(
+            (inputs.len() / B).is_power_of_two(),
+            "inputs.len() / B must be a power of two"
+        );
+
+        let map_inputs = inputs
+            .chunks(B)
+            .map(|chunk| {
+                let mut map_input = PublicInput::Elements(Vec::new());
+                map_input.write::<MapReduceInputVariable<Ctx, Input, B>>(
+                    MapReduceInputVariableValue {
+                        ctx: ctx.clone(),
+                        inputs: chunk.to_vec(),
+                    },
+                );
+                map_input
+            })
+            .collect_vec();
+
+        let (proofs, outputs) = prover
+            .batch_prove::<L, Serializer, D>(&self.circuit.id(), &map_inputs)
+            .unwrap()
+            .materialize()
+            .unwrap();
+        proofs
+            .into_iter()
+            .zip_eq(outputs)
+            .map(|(proof, mut output)| {
+                let output = output.read::<MapReduceOutputVariable<Ctx, Output>>();
+                (proof, output.output)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Field;
+
+    use super::*;
+    use crate::backend::circuit::DefaultSerializer;
+    use crate::frontend::aggregation::AggregationTreeBuilder;
+    use crate::frontend::mapreduce::prover::RayonProver;
+    use crate::prelude::{DefaultParameters, Variable};
+
+    type F = GoldilocksField;
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_map_driver() {
+        let driver = MapDriver::<
+            DefaultParameters,
+            Variable,
+            Variable,
+            Variable,
+            DefaultSerializer,
+            2,
+            2,
+        >::new(|ctx, inputs, builder| {
+            let sum = builder.add(inputs[0], inputs[1]);
+            builder.mul(ctx, sum)
+        });
+        let inputs = (0..8).map(F::from_canonical_u64).collect_vec();
+        let results = driver.prove(&RayonProver::new(2), F::TWO, &inputs);
+        assert_eq!(results.len(), 4);
+        for (i, (_, output)) in results.iter().enumerate() {
+            let expected = 2 * (4 * i as u64 + 1);
+            assert_eq!(*output, F::from_canonical_u64(expected));
+        }
+
+        // The map proofs are aggregated by the aggregation tree instead of a reduce.
+        let (proofs, _): (Vec<_>, Vec<_>) = results.into_iter().unzip();
+        let leaf_public_inputs = proofs
+            .iter()
+            .map(|proof| proof.public_inputs.clone())
+            .collect_vec();
+        let mut tree = AggregationTreeBuilder::new(driver.circuit(), 2);
+        let commitment = tree.commitment(&leaf_public_inputs);
+        let root = tree.aggregate(proofs);
+        tree.node_circuit(1).data.verify(root.clone()).unwrap();
+        assert_eq!(root.public_inputs, commitment.elements.to_vec());
+    }
+}
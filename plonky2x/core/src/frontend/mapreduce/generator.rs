@@ -10,14 +10,23 @@ use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 use plonky2::plonk::proof::ProofWithPublicInputsTarget;
 use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
 
+use super::prover::LeafProver;
 use super::{MapReduceInputVariable, MapReduceInputVariableValue};
 use crate::backend::circuit::{CircuitSerializer, PublicInput};
-use crate::backend::prover::{EnvProver, ProverOutputs};
+use crate::backend::prover::ProverOutputs;
 use crate::prelude::{CircuitVariable, PlonkParameters};
 
 #[derive(Debug, Clone)]
-pub struct MapReduceGenerator<L, Ctx, Input, Output, Serializer, const B: usize, const D: usize>
-where
+pub struct MapReduceGenerator<
+    L,
+    Ctx,
+    Input,
+    Output,
+    Serializer,
+    Prover,
+    const B: usize,
+    const D: usize,
+> where
     L: PlonkParameters<D>,
     <L as PlonkParameters<D>>::Config: GenericConfig<D, F = L::Field> + 'static,
     <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
@@ -25,6 +34,7 @@ where
     Input: CircuitVariable,
     Output: CircuitVariable,
     Serializer: CircuitSerializer,
+    Prover: LeafProver,
 {
     /// The identifier for the compiled map circuit.
     pub map_circuit_id: String,
@@ -41,12 +51,15 @@ where
     /// The proof target for the final circuit proof.
     pub proof: ProofWithPublicInputsTarget<D>,
 
+    /// The strategy for proving the map and reduce layers.
+    pub leaf_prover: Prover,
+
     /// Phantom data.
     pub _phantom: PhantomData<(L, Output, Serializer)>,
 }
 
-impl<L, Ctx, Input, Output, Serializer, const B: usize, const D: usize>
-    MapReduceGenerator<L, Ctx, Input, Output, Serializer, B, D>
+impl<L, Ctx, Input, Output, Serializer, Prover, const B: usize, const D: usize>
+    MapReduceGenerator<L, Ctx, Input, Output, Serializer, Prover, B, D>
 where
     L: PlonkParameters<D>,
     <L as PlonkParameters<D>>::Config: GenericConfig<D, F = L::Field> + 'static,
@@ -55,14 +68,16 @@ where
     Input: CircuitVariable,
     Output: CircuitVariable,
     Serializer: CircuitSerializer,
+    Prover: LeafProver,
 {
     pub fn id() -> String {
         "MapReduceGenerator".to_string()
     }
 }
 
-impl<L, Ctx, Input, Output, Serializer, const B: usize, const D: usize> SimpleGenerator<L::Field, D>
-    for MapReduceGenerator<L, Ctx, Input, Output, Serializer, B, D>
+impl<L, Ctx, Input, Output, Serializer, Prover, const B: usize, const D: usize>
+    SimpleGenerator<L::Field, D>
+    for MapReduceGenerator<L, Ctx, Input, Output, Serializer, Prover, B, D>
 where
     L: PlonkParameters<D>,
     <L as PlonkParameters<D>>::Config: GenericConfig<D, F = L::Field> + 'static,
@@ -71,6 +86,7 @@ where
     Input: CircuitVariable,
     Output: CircuitVariable,
     Serializer: CircuitSerializer,
+    Prover: LeafProver,
     <Input as CircuitVariable>::ValueType<<L as PlonkParameters<D>>::Field>: Sync + Send,
 {
     fn id(&self) -> String {
@@ -88,8 +104,7 @@ where
         witness: &PartitionWitness<L::Field>,
         out_buffer: &mut GeneratedValues<L::Field>,
     ) {
-        // Create the prover and the async runtime.
-        let prover = EnvProver::new();
+        let prover = &self.leaf_prover;
 
         // Calculate the inputs to the map.
         let ctx_value = self.ctx.get(witness);
@@ -172,7 +187,12 @@ where
         }
 
         // Write proof target.
-        dst.write_target_proof_with_public_inputs(&self.proof)
+        dst.write_target_proof_with_public_inputs(&self.proof)?;
+
+        // Write leaf prover.
+        let leaf_prover = bincode::serialize(&self.leaf_prover).unwrap();
+        dst.write_usize(leaf_prover.len())?;
+        dst.write_all(&leaf_prover)
     }
 
     fn deserialize(src: &mut Buffer, _: &CommonCircuitData<L::Field, D>) -> IoResult<Self> {
@@ -205,12 +225,19 @@ where
         // Read proof.
         let proof = src.read_target_proof_with_public_inputs()?;
 
+        // Read leaf prover.
+        let leaf_prover_length = src.read_usize()?;
+        let mut leaf_prover = vec![0u8; leaf_prover_length];
+        src.read_exact(&mut leaf_prover)?;
+        let leaf_prover = bincode::deserialize(&leaf_prover).unwrap();
+
         Ok(Self {
             map_circuit_id: String::from_utf8(map_circuit_id).unwrap(),
             reduce_circuit_ids,
             ctx,
             inputs,
             proof,
+            leaf_prover,
             _phantom: PhantomData,
         })
     }
@@ -223,6 +250,7 @@ pub struct MapReduceDynamicGenerator<
     Input,
     Output,
     Serializer,
+    Prover,
     const B: usize,
     const D: usize,
 > where
@@ -233,6 +261,7 @@ pub struct MapReduceDynamicGenerator<
     Input: CircuitVariable,
     Output: CircuitVariable,
     Serializer: CircuitSerializer,
+    Prover: LeafProver,
 {
     /// The identifier for the compiled map circuit.
     pub map_circuit_id: String,
@@ -249,12 +278,15 @@ pub struct MapReduceDynamicGenerator<
     /// The proof target for the final circuit proof.
     pub proof: ProofWithPublicInputsTarget<D>,
 
+    /// The strategy for proving the map and reduce layers.
+    pub leaf_prover: Prover,
+
     /// Phantom data.
     pub _phantom: PhantomData<(L, Output, Serializer)>,
 }
 
-impl<L, Ctx, Input, Output, Serializer, const B: usize, const D: usize>
-    MapReduceDynamicGenerator<L, Ctx, Input, Output, Serializer, B, D>
+impl<L, Ctx, Input, Output, Serializer, Prover, const B: usize, const D: usize>
+    MapReduceDynamicGenerator<L, Ctx, Input, Output, Serializer, Prover, B, D>
 where
     L: PlonkParameters<D>,
     <L as PlonkParameters<D>>::Config: GenericConfig<D, F = L::Field> + 'static,
@@ -263,14 +295,16 @@ where
     Input: CircuitVariable,
     Output: CircuitVariable,
     Serializer: CircuitSerializer,
+    Prover: LeafProver,
 {
     pub fn id() -> String {
         "MapReduceDynamicGenerator".to_string()
     }
 }
 
-impl<L, Ctx, Input, Output, Serializer, const B: usize, const D: usize> SimpleGenerator<L::Field, D>
-    for MapReduceDynamicGenerator<L, Ctx, Input, Output, Serializer, B, D>
+impl<L, Ctx, Input, Output, Serializer, Prover, const B: usize, const D: usize>
+    SimpleGenerator<L::Field, D>
+    for MapReduceDynamicGenerator<L, Ctx, Input, Output, Serializer, Prover, B, D>
 where
     L: PlonkParameters<D>,
     <L as PlonkParameters<D>>::Config: GenericConfig<D, F = L::Field> + 'static,
@@ -279,6 +313,7 @@ where
     Input: CircuitVariable,
     Output: CircuitVariable,
     Serializer: CircuitSerializer,
+    Prover: LeafProver,
     <Input as CircuitVariable>::ValueType<<L as PlonkParameters<D>>::Field>: Sync + Send,
 {
     fn id(&self) -> String {
@@ -299,8 +334,7 @@ where
         witness: &PartitionWitness<L::Field>,
         out_buffer: &mut GeneratedValues<L::Field>,
     ) {
-        // Create the prover and the async runtime.
-        let prover = EnvProver::new();
+        let prover = &self.leaf_prover;
 
         // Calculate the inputs to the map.
         let ctx_value = self.ctx.get(witness);
@@ -383,7 +417,12 @@ where
         }
 
         // Write proof target.
-        dst.write_target_proof_with_public_inputs(&self.proof)
+        dst.write_target_proof_with_public_inputs(&self.proof)?;
+
+        // Write leaf prover.
+        let leaf_prover = bincode::serialize(&self.leaf_prover).unwrap();
+        dst.write_usize(leaf_prover.len())?;
+        dst.write_all(&leaf_prover)
     }
 
     fn deserialize(src: &mut Buffer, _: &CommonCircuitData<L::Field, D>) -> IoResult<Self> {
@@ -415,12 +454,19 @@ where
         // Read proof.
         let proof = src.read_target_proof_with_public_inputs()?;
 
+        // Read leaf prover.
+        let leaf_prover_length = src.read_usize()?;
+        let mut leaf_prover = vec![0u8; leaf_prover_length];
+        src.read_exact(&mut leaf_prover)?;
+        let leaf_prover = bincode::deserialize(&leaf_prover).unwrap();
+
         Ok(Self {
             map_circuit_id: String::from_utf8(map_circuit_id).unwrap(),
             reduce_circuit_ids,
             ctx,
             inputs,
             proof,
+            leaf_prover,
             _phantom: PhantomData,
         })
     }
@@ -15,7 +15,9 @@
 //! Under the hood, we compute each map in a seperate proof and perform the reductions by generating
 //! a proof for each reduction between two proofs until we have a single proof.
 
+pub mod driver;
 pub mod generator;
+pub mod prover;
 
 use core::fmt::Debug;
 use core::marker::PhantomData;
@@ -27,12 +29,13 @@ use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 use plonky2x_derive::CircuitVariable;
 
 use self::generator::MapReduceGenerator;
+use self::prover::{LeafProver, SequentialProver};
 use super::hash::poseidon::poseidon256::PoseidonHashOutVariable;
 use crate::backend::circuit::{CircuitBuild, CircuitSerializer};
 use crate::frontend::builder::CircuitBuilder;
 use crate::frontend::mapreduce::generator::MapReduceDynamicGenerator;
 use crate::frontend::vars::CircuitVariable;
-use crate::prelude::{ArrayVariable, PlonkParameters, Variable};
+use crate::prelude::{ArrayVariable, BoolVariable, PlonkParameters, U64Variable, Variable};
 use crate::utils::poseidon::{mapreduce_merkle_tree_root, MapReducePoseidonBuilderMethods};
 use crate::utils::proof::ProofWithPublicInputsTargetUtils;
 
@@ -81,6 +84,8 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         let mut builder = CircuitBuilder::<L, D>::new();
         builder.beacon_client = self.beacon_client.clone();
         builder.execution_client = self.execution_client.clone();
+        builder.chain_id = self.chain_id;
+        builder.rpc_cache_mode = self.rpc_cache_mode.clone();
 
         // Read the inputs.
         let data = builder.read::<MapReduceInputVariable<Ctx, Input, B>>();
@@ -156,6 +161,12 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         builder.build()
     }
 
+    /// Applies MapReduce to `inputs`, which are build-time constants, in chunks of `B` elements.
+    ///
+    /// The context `ctx` is passed as the first argument to every call of the map and reduce
+    /// functions instead of being duplicated into the chunks. It is a public input of every map and
+    /// reduce proof: each reduce circuit checks that the contexts of its two children are equal,
+    /// and the final proof is checked against `ctx` in this circuit.
     pub fn mapreduce<Ctx, Input, Output, Serializer, const B: usize, MapFn, ReduceFn>(
         &mut self,
         ctx: Ctx,
@@ -173,6 +184,45 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         <Input as CircuitVariable>::ValueType<<L as PlonkParameters<D>>::Field>: Sync + Send,
         MapFn: Fn(Ctx, ArrayVariable<Input, B>, &mut CircuitBuilder<L, D>) -> Output,
         ReduceFn: Fn(Ctx, Output, Output, &mut CircuitBuilder<L, D>) -> Output,
+    {
+        self.mapreduce_with_prover::<Ctx, Input, Output, Serializer, _, B, MapFn, ReduceFn>(
+            ctx,
+            inputs,
+            SequentialProver,
+            map_fn,
+            reduce_fn,
+        )
+    }
+
+    /// Applies MapReduce like `mapreduce`, proving the map and reduce layers with `leaf_prover`.
+    pub fn mapreduce_with_prover<
+        Ctx,
+        Input,
+        Output,
+        Serializer,
+        Prover,
+        const B: usize,
+        MapFn,
+        ReduceFn,
+    >(
+        &mut self,
+        ctx: Ctx,
+        inputs: Vec<Input::ValueType<L::Field>>,
+        leaf_prover: Prover,
+        map_fn: MapFn,
+        reduce_fn: ReduceFn,
+    ) -> Output
+    where
+        Ctx: CircuitVariable,
+        Input: CircuitVariable,
+        Output: CircuitVariable,
+        Serializer: CircuitSerializer,
+        Prover: LeafProver,
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+        <Input as CircuitVariable>::ValueType<<L as PlonkParameters<D>>::Field>: Sync + Send,
+        MapFn: Fn(Ctx, ArrayVariable<Input, B>, &mut CircuitBuilder<L, D>) -> Output,
+        ReduceFn: Fn(Ctx, Output, Output, &mut CircuitBuilder<L, D>) -> Output,
     {
         // Sanity checks.
         assert_eq!(inputs.len() % B, 0, "inputs length must be a multiple of B");
@@ -228,12 +278,13 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         let reduce_circuit_ids = reduce_circuits.iter().map(|c| c.id()).collect_vec();
         let final_circuit = &reduce_circuits[reduce_circuits.len() - 1];
         let final_proof = self.add_virtual_proof_with_pis(&final_circuit.data.common);
-        let generator = MapReduceGenerator::<L, Ctx, Input, Output, Serializer, B, D> {
+        let generator = MapReduceGenerator::<L, Ctx, Input, Output, Serializer, Prover, B, D> {
             map_circuit_id,
             reduce_circuit_ids,
             ctx: ctx.clone(),
             inputs: inputs.clone(),
             proof: final_proof.clone(),
+            leaf_prover,
             _phantom: PhantomData,
         };
         self.add_simple_generator(generator);
@@ -274,6 +325,46 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         <Input as CircuitVariable>::ValueType<<L as PlonkParameters<D>>::Field>: Sync + Send,
         MapFn: Fn(Ctx, ArrayVariable<Input, B>, &mut CircuitBuilder<L, D>) -> Output,
         ReduceFn: Fn(Ctx, Output, Output, &mut CircuitBuilder<L, D>) -> Output,
+    {
+        self.mapreduce_dynamic_with_prover::<Ctx, Input, Output, Serializer, _, B, MapFn, ReduceFn>(
+            ctx,
+            inputs,
+            SequentialProver,
+            map_fn,
+            reduce_fn,
+        )
+    }
+
+    /// Applies MapReduce like `mapreduce_dynamic`, proving the map and reduce layers with
+    /// `leaf_prover`.
+    pub fn mapreduce_dynamic_with_prover<
+        Ctx,
+        Input,
+        Output,
+        Serializer,
+        Prover,
+        const B: usize,
+        MapFn,
+        ReduceFn,
+    >(
+        &mut self,
+        ctx: Ctx,
+        inputs: Vec<Input>,
+        leaf_prover: Prover,
+        map_fn: MapFn,
+        reduce_fn: ReduceFn,
+    ) -> Output
+    where
+        Ctx: CircuitVariable,
+        Input: CircuitVariable,
+        Output: CircuitVariable,
+        Serializer: CircuitSerializer,
+        Prover: LeafProver,
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+        <Input as CircuitVariable>::ValueType<<L as PlonkParameters<D>>::Field>: Sync + Send,
+        MapFn: Fn(Ctx, ArrayVariable<Input, B>, &mut CircuitBuilder<L, D>) -> Output,
+        ReduceFn: Fn(Ctx, Output, Output, &mut CircuitBuilder<L, D>) -> Output,
     {
         // Sanity checks.
         assert_eq!(inputs.len() % B, 0, "inputs length must be a multiple of B");
@@ -325,14 +416,16 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         let reduce_circuit_ids = reduce_circuits.iter().map(|c| c.id()).collect_vec();
         let final_circuit = &reduce_circuits[reduce_circuits.len() - 1];
         let final_proof = self.add_virtual_proof_with_pis(&final_circuit.data.common);
-        let generator = MapReduceDynamicGenerator::<L, Ctx, Input, Output, Serializer, B, D> {
-            map_circuit_id,
-            reduce_circuit_ids,
-            ctx: ctx.clone(),
-            inputs: inputs.clone(),
-            proof: final_proof.clone(),
-            _phantom: PhantomData,
-        };
+        let generator =
+            MapReduceDynamicGenerator::<L, Ctx, Input, Output, Serializer, Prover, B, D> {
+                map_circuit_id,
+                reduce_circuit_ids,
+                ctx: ctx.clone(),
+                inputs: inputs.clone(),
+                proof: final_proof.clone(),
+                leaf_prover,
+                _phantom: PhantomData,
+            };
         self.add_simple_generator(generator);
 
         // Verify the final proof.
@@ -353,15 +446,146 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         // Return the output.
         output.output
     }
+
+    /// Applies MapReduce to the first `count` elements of `inputs`, where `count` is only known at
+    /// proving time and `inputs` is padded to a fixed length.
+    ///
+    /// The map function receives an `is_real` flag for each element, which is false for padding,
+    /// and must map padding to the identity of the reduce function. The map and reduce circuits
+    /// also count the real elements, and the final proof exposes this count, which is checked to be
+    /// equal to `count`.
+    pub fn mapreduce_with_count<Ctx, Input, Output, Serializer, const B: usize, MapFn, ReduceFn>(
+        &mut self,
+        ctx: Ctx,
+        inputs: Vec<Input>,
+        count: U64Variable,
+        map_fn: MapFn,
+        reduce_fn: ReduceFn,
+    ) -> Output
+    where
+        Ctx: CircuitVariable,
+        Input: CircuitVariable,
+        Output: CircuitVariable,
+        Serializer: CircuitSerializer,
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+        <Input as CircuitVariable>::ValueType<<L as PlonkParameters<D>>::Field>: Sync + Send,
+        MapFn: Fn(
+            Ctx,
+            ArrayVariable<Input, B>,
+            ArrayVariable<BoolVariable, B>,
+            &mut CircuitBuilder<L, D>,
+        ) -> Output,
+        ReduceFn: Fn(Ctx, Output, Output, &mut CircuitBuilder<L, D>) -> Output,
+    {
+        // Flag the elements before `count` as real, and pair them with their flags.
+        let inputs = inputs
+            .into_iter()
+            .enumerate()
+            .map(|(i, input)| {
+                let index = self.constant::<U64Variable>(i as u64);
+                let is_real = self.lt(index, count);
+                (input, is_real)
+            })
+            .collect_vec();
+
+        let (output, nb_real) = self
+            .mapreduce_dynamic::<Ctx, (Input, BoolVariable), (Output, U64Variable), Serializer, B, _, _>(
+                ctx,
+                inputs,
+                |ctx, inputs, builder| {
+                    let (inputs, is_real): (Vec<_>, Vec<_>) = inputs.as_vec().into_iter().unzip();
+                    let one = builder.one::<U64Variable>();
+                    let mut nb_real = builder.zero::<U64Variable>();
+                    for flag in is_real.iter() {
+                        let incremented = builder.add(nb_real, one);
+                        nb_real = builder.select(*flag, incremented, nb_real);
+                    }
+                    let output = map_fn(
+                        ctx,
+                        ArrayVariable::new(inputs),
+                        ArrayVariable::new(is_real),
+                        builder,
+                    );
+                    (output, nb_real)
+                },
+                |ctx, (left, left_nb_real), (right, right_nb_real), builder| {
+                    let output = reduce_fn(ctx, left, right, builder);
+                    let nb_real = builder.add(left_nb_real, right_nb_real);
+                    (output, nb_real)
+                },
+            );
+
+        // Verify the count of real elements.
+        self.assert_is_equal(nb_real, count);
+
+        output
+    }
+
+    /// Applies MapReduce like `mapreduce`, where the accumulator of the reduce function may have
+    /// another type than the output of the map function.
+    ///
+    /// The output of each map call is turned into an accumulator by `init_fn` in the map circuit,
+    /// and accumulators are combined by `reduce_fn`. The whole accumulator is carried through the
+    /// public inputs of the reduce proofs.
+    pub fn mapreduce_with_accumulator<
+        Ctx,
+        Input,
+        MapOutput,
+        Acc,
+        Serializer,
+        const B: usize,
+        MapFn,
+        InitFn,
+        ReduceFn,
+    >(
+        &mut self,
+        ctx: Ctx,
+        inputs: Vec<Input::ValueType<L::Field>>,
+        map_fn: MapFn,
+        init_fn: InitFn,
+        reduce_fn: ReduceFn,
+    ) -> Acc
+    where
+        Ctx: CircuitVariable,
+        Input: CircuitVariable,
+        MapOutput: CircuitVariable,
+        Acc: CircuitVariable,
+        Serializer: CircuitSerializer,
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+        <Input as CircuitVariable>::ValueType<<L as PlonkParameters<D>>::Field>: Sync + Send,
+        MapFn: Fn(Ctx, ArrayVariable<Input, B>, &mut CircuitBuilder<L, D>) -> MapOutput,
+        InitFn: Fn(Ctx, MapOutput, &mut CircuitBuilder<L, D>) -> Acc,
+        ReduceFn: Fn(Ctx, Acc, Acc, &mut CircuitBuilder<L, D>) -> Acc,
+    {
+        self.mapreduce::<Ctx, Input, Acc, Serializer, B, _, ReduceFn>(
+            ctx,
+            inputs,
+            |ctx, inputs, builder| {
+                let output = map_fn(ctx.clone(), inputs, builder);
+                init_fn(ctx, output, builder)
+            },
+            reduce_fn,
+        )
+    }
 }
 
 #[cfg(test)]
 pub(crate) mod tests {
+    use itertools::Itertools;
     use plonky2::field::goldilocks_field::GoldilocksField;
     use plonky2::field::types::Field;
+    use plonky2::hash::hash_types::HashOut;
+    use plonky2::hash::poseidon::PoseidonHash;
+    use plonky2::plonk::config::Hasher;
+    use plonky2x_derive::CircuitVariable;
 
+    use super::{MapReduceInputVariable, MapReduceInputVariableValue};
     use crate::backend::circuit::DefaultSerializer;
-    use crate::prelude::{CircuitBuilder, DefaultParameters, Variable};
+    use crate::frontend::hash::poseidon::poseidon256::PoseidonHashOutVariable;
+    use crate::frontend::vars::CircuitVariable;
+    use crate::prelude::{ArrayVariable, CircuitBuilder, DefaultParameters, U64Variable, Variable};
 
     type F = GoldilocksField;
     type L = DefaultParameters;
@@ -405,4 +629,237 @@ pub(crate) mod tests {
         let result = output.read::<Variable>();
         println!("{}", result);
     }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_mapreduce_ctx() {
+        env_logger::try_init().unwrap_or_default();
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let ctx = builder.read::<Variable>();
+        let inputs = (0..8).map(F::from_canonical_u64).collect_vec();
+        let output = builder.mapreduce::<Variable, Variable, Variable, DefaultSerializer, 2, _, _>(
+            ctx,
+            inputs,
+            |ctx, inputs, builder| {
+                let o1 = builder.mul(ctx, inputs[0]);
+                let o2 = builder.mul(ctx, inputs[1]);
+                builder.add(o1, o2)
+            },
+            |_, left, right, builder| builder.add(left, right),
+        );
+        builder.write(output);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        input.write::<Variable>(F::from_canonical_u64(3));
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<Variable>(), F::from_canonical_u64(3 * 28));
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    #[should_panic]
+    fn test_mapreduce_ctx_mismatch() {
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let map_fn = |ctx: Variable,
+                      inputs: ArrayVariable<Variable, 2>,
+                      builder: &mut CircuitBuilder<L, D>| {
+            let o1 = builder.mul(ctx, inputs[0]);
+            let o2 = builder.mul(ctx, inputs[1]);
+            builder.add(o1, o2)
+        };
+        let reduce_fn =
+            |_: Variable, left: Variable, right: Variable, builder: &mut CircuitBuilder<L, D>| {
+                builder.add(left, right)
+            };
+        let map_circuit = builder.build_map::<Variable, Variable, Variable, _, 2>(&map_fn);
+        let reduce_circuit =
+            builder.build_reduce::<Variable, Variable, _>(&map_circuit, &reduce_fn);
+
+        // The two leaves are valid proofs on their own, but with different contexts.
+        let leaf_proofs = [2, 3].map(|ctx| {
+            let mut input = map_circuit.input();
+            input.write::<MapReduceInputVariable<Variable, Variable, 2>>(
+                MapReduceInputVariableValue {
+                    ctx: F::from_canonical_u64(ctx),
+                    inputs: vec![F::ONE, F::TWO],
+                },
+            );
+            let (proof, _) = map_circuit.prove(&input);
+            proof
+        });
+
+        let mut input = reduce_circuit.input();
+        for proof in leaf_proofs {
+            input.proof_write(proof);
+        }
+        reduce_circuit.prove(&input);
+    }
+
+    /// The accumulator of `test_mapreduce_with_accumulator`.
+    #[derive(Debug, Clone, CircuitVariable)]
+    #[value_name(Stats)]
+    struct StatsVariable {
+        sum: U64Variable,
+        min: U64Variable,
+        root: PoseidonHashOutVariable,
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_mapreduce_with_accumulator() {
+        env_logger::try_init().unwrap_or_default();
+
+        let values = [13u64, 4, 25, 9, 7, 30, 2, 18];
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let ctx = builder.constant::<Variable>(F::ZERO);
+        let stats = builder
+            .mapreduce_with_accumulator::<Variable, U64Variable, U64Variable, StatsVariable, DefaultSerializer, 1, _, _, _>(
+                ctx,
+                values.to_vec(),
+                |_, inputs, _| inputs[0],
+                |_, value, builder| StatsVariable {
+                    sum: value,
+                    min: value,
+                    root: builder.poseidon_hash(&value.variables()),
+                },
+                |_, left, right, builder| {
+                    let lte = builder.lte(left.min, right.min);
+                    let elements = [left.root.variables(), right.root.variables()].concat();
+                    StatsVariable {
+                        sum: builder.add(left.sum, right.sum),
+                        min: builder.select(lte, left.min, right.min),
+                        root: builder.poseidon_hash(&elements),
+                    }
+                },
+            );
+        builder.write(stats);
+
+        let circuit = builder.build();
+        let input = circuit.input();
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        let hash = |elements: &[F]| PoseidonHash::hash_no_pad(elements);
+        let mut digests = values
+            .iter()
+            .map(|value| hash(&U64Variable::elements::<F>(*value)))
+            .collect_vec();
+        while digests.len() > 1 {
+            digests = digests
+                .chunks(2)
+                .map(|pair| hash(&[pair[0].elements, pair[1].elements].concat()))
+                .collect();
+        }
+
+        let stats = output.read::<StatsVariable>();
+        assert_eq!(stats.sum, values.iter().sum::<u64>());
+        assert_eq!(stats.min, *values.iter().min().unwrap());
+        assert_eq!(stats.root, digests[0]);
+    }
+
+    /// Combines two digests, where the zero digest is the identity.
+    fn hash_combine(
+        builder: &mut CircuitBuilder<L, D>,
+        left: PoseidonHashOutVariable,
+        right: PoseidonHashOutVariable,
+    ) -> PoseidonHashOutVariable {
+        let zero = builder.constant::<PoseidonHashOutVariable>(HashOut::ZERO);
+        let left_is_zero = builder.is_equal(left.clone(), zero.clone());
+        let right_is_zero = builder.is_equal(right.clone(), zero);
+        let elements = [left.variables(), right.variables()].concat();
+        let hash = builder.poseidon_hash(&elements);
+        let output = builder.select(right_is_zero, left, hash);
+        builder.select(left_is_zero, right, output)
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_mapreduce_with_count() {
+        env_logger::try_init().unwrap_or_default();
+
+        let values = (0..13u64).map(|i| (i * 7) % 23 + 1).collect_vec();
+        // Each of the results would change if the padding were not ignored.
+        let padding = [0u64, 1000, 5];
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let ctx = builder.constant::<Variable>(F::ZERO);
+        let inputs = (0..16).map(|_| builder.read::<U64Variable>()).collect_vec();
+        let count = builder.read::<U64Variable>();
+
+        type Output = (U64Variable, (U64Variable, PoseidonHashOutVariable));
+        let (sum, (min, digest)) = builder
+            .mapreduce_with_count::<Variable, U64Variable, Output, DefaultSerializer, 2, _, _>(
+                ctx,
+                inputs,
+                count,
+                |_, inputs, is_real, builder| {
+                    let zero = builder.zero::<U64Variable>();
+                    let zero_digest = builder.constant::<PoseidonHashOutVariable>(HashOut::ZERO);
+                    let mut sum = zero;
+                    let mut min = builder.constant::<U64Variable>(u64::MAX);
+                    let mut digest = zero_digest.clone();
+                    for (input, is_real) in inputs.as_vec().into_iter().zip(is_real.as_vec()) {
+                        let term = builder.select(is_real, input, zero);
+                        sum = builder.add(sum, term);
+
+                        let lte = builder.lte(input, min);
+                        let is_min = builder.and(is_real, lte);
+                        min = builder.select(is_min, input, min);
+
+                        let leaf = builder.poseidon_hash(&input.variables());
+                        let leaf = builder.select(is_real, leaf, zero_digest.clone());
+                        digest = hash_combine(builder, digest, leaf);
+                    }
+                    (sum, (min, digest))
+                },
+                |_,
+                 (left_sum, (left_min, left_digest)),
+                 (right_sum, (right_min, right_digest)),
+                 builder| {
+                    let sum = builder.add(left_sum, right_sum);
+                    let lte = builder.lte(left_min, right_min);
+                    let min = builder.select(lte, left_min, right_min);
+                    let digest = hash_combine(builder, left_digest, right_digest);
+                    (sum, (min, digest))
+                },
+            );
+        builder.write(sum);
+        builder.write(min);
+        builder.write(digest);
+        builder.write(count);
+
+        let circuit = builder.build();
+        let mut input = circuit.input();
+        for value in values.iter().chain(padding.iter()) {
+            input.write::<U64Variable>(*value);
+        }
+        input.write::<U64Variable>(values.len() as u64);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+
+        // Reduce only the real elements, promoting the last digest of a level of odd length.
+        let hash = |elements: &[F]| PoseidonHash::hash_no_pad(elements);
+        let mut digests = values
+            .iter()
+            .map(|value| hash(&U64Variable::elements::<F>(*value)))
+            .collect_vec();
+        while digests.len() > 1 {
+            digests = digests
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash(&[left.elements, right.elements].concat()),
+                    _ => pair[0],
+                })
+                .collect();
+        }
+
+        assert_eq!(output.read::<U64Variable>(), values.iter().sum::<u64>());
+        assert_eq!(output.read::<U64Variable>(), *values.iter().min().unwrap());
+        assert_eq!(output.read::<PoseidonHashOutVariable>(), digests[0]);
+        assert_eq!(output.read::<U64Variable>(), values.len() as u64);
+    }
 }
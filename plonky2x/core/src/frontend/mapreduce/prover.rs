@@ -0,0 +1,450 @@
+//! Strategies for proving the layers of a mapreduce tree.
+
+use core::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use anyhow::Result;
+use itertools::Itertools;
+use log::debug;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, GenericHashOut};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::backend::circuit::{
+    CircuitBuild, CircuitSerializer, PlonkParameters, PublicInput, PublicOutput,
+};
+use crate::backend::prover::{EnvProver, ProverOutputs};
+use crate::utils::{encode_digest, DigestFormat, Endianness};
+
+/// A strategy for proving a layer of a mapreduce tree, which is a batch of proofs of the same
+/// circuit. The proofs must be returned in the order of the inputs, as the reduce layer above
+/// pairs them up by position.
+pub trait LeafProver: Debug + Clone + Send + Sync + Serialize + DeserializeOwned + 'static {
+    /// Generates a proof of the circuit with the given id for each input.
+    fn batch_prove<L: PlonkParameters<D>, S: CircuitSerializer, const D: usize>(
+        &self,
+        circuit_id: &str,
+        inputs: &[PublicInput<L, D>],
+    ) -> Result<ProverOutputs<L, D>>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>;
+}
+
+/// Proves the inputs one after the other, locally or remotely depending on the env variable
+/// `PROVER`, like `EnvProver`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SequentialProver;
+
+impl LeafProver for SequentialProver {
+    fn batch_prove<L: PlonkParameters<D>, S: CircuitSerializer, const D: usize>(
+        &self,
+        circuit_id: &str,
+        inputs: &[PublicInput<L, D>],
+    ) -> Result<ProverOutputs<L, D>>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        EnvProver::new().batch_prove::<L, S, D>(circuit_id, inputs)
+    }
+}
+
+/// Proves the inputs locally on a thread pool of `num_workers` threads.
+///
+/// Each worker proves one input at a time, so at most `num_workers` proofs are in memory while
+/// they are being generated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RayonProver {
+    pub num_workers: usize,
+}
+
+impl RayonProver {
+    pub fn new(num_workers: usize) -> Self {
+        assert!(num_workers > 0, "num_workers must be positive");
+        Self { num_workers }
+    }
+}
+
+impl LeafProver for RayonProver {
+    fn batch_prove<L: PlonkParameters<D>, S: CircuitSerializer, const D: usize>(
+        &self,
+        circuit_id: &str,
+        inputs: &[PublicInput<L, D>],
+    ) -> Result<ProverOutputs<L, D>>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let gate_serializer = S::gate_registry::<L, D>();
+        let generator_serializer = S::generator_registry::<L, D>();
+        let circuit_path = format!("./build/{}.circuit", circuit_id);
+        let circuit =
+            CircuitBuild::<L, D>::load(&circuit_path, &gate_serializer, &generator_serializer)
+                .unwrap();
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.num_workers)
+            .build()?;
+
+        // The workers take the next input by index rather than splitting the inputs with a
+        // parallel iterator, which could start more proofs than workers through work stealing.
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(inputs.iter().map(|_| None).collect_vec());
+        pool.scope(|scope| {
+            for _ in 0..self.num_workers {
+                scope.spawn(|_| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= inputs.len() {
+                        break;
+                    }
+                    let result = circuit.prove(&inputs[i]);
+                    results.lock().unwrap()[i] = Some(result);
+                });
+            }
+        });
+
+        let (proofs, outputs) = results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| result.expect("missing proof"))
+            .unzip();
+        Ok(ProverOutputs::Local(proofs, outputs))
+    }
+}
+
+/// Wraps a prover with checkpoints, so that a run that was interrupted can be resumed without
+/// proving again the proofs that were already generated.
+///
+/// Each proof is saved to `dir` as soon as it is generated, in the byte encoding of
+/// `ProofWithPublicInputs`, under a key made of the circuit digest, the position of the proof in
+/// its layer and the hash of its input. Saved proofs are verified before they are reused, and
+/// proofs of a circuit with a different digest are ignored. The missing proofs are generated with
+/// a single call to the inner prover, so that it can prove them in parallel, and are saved once it
+/// returns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointProver<P> {
+    pub dir: String,
+    pub inner: P,
+}
+
+impl<P: LeafProver> CheckpointProver<P> {
+    pub fn new(dir: &str, inner: P) -> Self {
+        Self {
+            dir: dir.to_string(),
+            inner,
+        }
+    }
+
+    fn checkpoint_path<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        circuit_digest: &str,
+        position: usize,
+        input: &PublicInput<L, D>,
+    ) -> PathBuf {
+        let input_hash = hex::encode(Sha256::digest(bincode::serialize(input).unwrap()));
+        Path::new(&self.dir).join(format!(
+            "{}_{}_{}.proof",
+            circuit_digest, position, input_hash
+        ))
+    }
+}
+
+impl<P: LeafProver> LeafProver for CheckpointProver<P> {
+    fn batch_prove<L: PlonkParameters<D>, S: CircuitSerializer, const D: usize>(
+        &self,
+        circuit_id: &str,
+        inputs: &[PublicInput<L, D>],
+    ) -> Result<ProverOutputs<L, D>>
+    where
+        <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+            AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+    {
+        let gate_serializer = S::gate_registry::<L, D>();
+        let generator_serializer = S::generator_registry::<L, D>();
+        let circuit_path = format!("./build/{}.circuit", circuit_id);
+        let circuit =
+            CircuitBuild::<L, D>::load(&circuit_path, &gate_serializer, &generator_serializer)
+                .unwrap();
+        let circuit_digest = encode_digest(
+            &circuit.data.verifier_only.circuit_digest.to_vec(),
+            DigestFormat {
+                endianness: Endianness::Big,
+                prefix: false,
+                uppercase: false,
+            },
+        );
+        fs::create_dir_all(&self.dir)?;
+
+        let paths = inputs
+            .iter()
+            .enumerate()
+            .map(|(position, input)| self.checkpoint_path(&circuit_digest, position, input))
+            .collect_vec();
+        let mut proofs = paths
+            .iter()
+            .map(|path| {
+                let bytes = fs::read(path).ok()?;
+                let proof = ProofWithPublicInputs::from_bytes(bytes, &circuit.data.common).ok()?;
+                circuit.data.verify(proof.clone()).ok()?;
+                debug!("loaded checkpoint: {}", path.display());
+                Some(proof)
+            })
+            .collect_vec();
+
+        let missing = (0..inputs.len())
+            .filter(|&i| proofs[i].is_none())
+            .collect_vec();
+        if !missing.is_empty() {
+            let missing_inputs = missing.iter().map(|&i| inputs[i].clone()).collect_vec();
+            let outputs = self
+                .inner
+                .batch_prove::<L, S, D>(circuit_id, &missing_inputs)?;
+            let (missing_proofs, _) = outputs.materialize()?;
+            for (i, proof) in missing.into_iter().zip_eq(missing_proofs) {
+                fs::write(&paths[i], proof.to_bytes())?;
+                proofs[i] = Some(proof);
+            }
+        }
+
+        let proofs = proofs.into_iter().map(Option::unwrap).collect_vec();
+        let outputs = proofs
+            .iter()
+            .map(|proof| PublicOutput::from_proof_with_pis(&circuit.io, proof))
+            .collect();
+        Ok(ProverOutputs::Local(proofs, outputs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::backend::circuit::{Circuit, DefaultSerializer, HintRegistry};
+    use crate::frontend::hint::simple::hint::Hint;
+    use crate::frontend::vars::{ValueStream, VariableStream};
+    use crate::prelude::{
+        CircuitBuilder, DefaultParameters, Field, GoldilocksField, PlonkParameters, Variable,
+    };
+
+    const LEAF_DELAY: Duration = Duration::from_millis(500);
+
+    /// A hint that returns its input after a delay.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct DelayHint;
+
+    impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for DelayHint {
+        fn hint(
+            &self,
+            input_stream: &mut ValueStream<L, D>,
+            output_stream: &mut ValueStream<L, D>,
+        ) {
+            let input = input_stream.read_value::<Variable>();
+            thread::sleep(LEAF_DELAY);
+            output_stream.write_value::<Variable>(input);
+        }
+    }
+
+    /// A leaf circuit whose witness generation takes at least `LEAF_DELAY`.
+    #[derive(Debug, Clone)]
+    struct DelayCircuit;
+
+    impl Circuit for DelayCircuit {
+        fn define<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>)
+        where
+            <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+                AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+        {
+            let input = builder.read::<Variable>();
+            let mut input_stream = VariableStream::new();
+            input_stream.write(&input);
+            let output = builder
+                .hint(input_stream, DelayHint)
+                .read::<Variable>(builder);
+            builder.write(output);
+        }
+
+        fn register_generators<L: PlonkParameters<D>, const D: usize>(
+            registry: &mut HintRegistry<L, D>,
+        ) where
+            <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+                AlgebraicHasher<L::Field>,
+        {
+            registry.register_hint::<DelayHint>();
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_rayon_prover() {
+        type L = DefaultParameters;
+        const D: usize = 2;
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        DelayCircuit::define(&mut builder);
+        let circuit = builder.build();
+        let circuit_id = circuit.id();
+        circuit.save(
+            &format!("./build/{}.circuit", circuit_id),
+            &DelayCircuit::gate_registry::<L, D>(),
+            &DelayCircuit::generator_registry::<L, D>(),
+        );
+
+        let inputs = (0..8)
+            .map(|i| {
+                let mut input = circuit.input();
+                input.write::<Variable>(GoldilocksField::from_canonical_u64(i));
+                input
+            })
+            .collect_vec();
+
+        let start = Instant::now();
+        let sequential = SequentialProver
+            .batch_prove::<L, DelayCircuit, D>(&circuit_id, &inputs)
+            .unwrap();
+        let sequential_time = start.elapsed();
+
+        let start = Instant::now();
+        let parallel = RayonProver::new(4)
+            .batch_prove::<L, DelayCircuit, D>(&circuit_id, &inputs)
+            .unwrap();
+        let parallel_time = start.elapsed();
+
+        assert!(sequential_time >= LEAF_DELAY * 8);
+        assert!(
+            parallel_time < sequential_time / 2,
+            "parallel proving took {:?}, sequential proving took {:?}",
+            parallel_time,
+            sequential_time
+        );
+
+        // The proofs are in the order of the inputs.
+        let (sequential, _) = sequential.materialize().unwrap();
+        let (parallel, _) = parallel.materialize().unwrap();
+        for (i, proof) in parallel.iter().enumerate() {
+            circuit.data.verify(proof.clone()).unwrap();
+            assert_eq!(proof.public_inputs, sequential[i].public_inputs);
+            assert_eq!(
+                proof.public_inputs[1],
+                GoldilocksField::from_canonical_u64(i as u64)
+            );
+        }
+    }
+
+    /// The number of proofs generated by `AbortingProver`.
+    static NB_PROOFS: AtomicUsize = AtomicUsize::new(0);
+
+    /// The number of batches proven by `AbortingProver`.
+    static NB_BATCHES: AtomicUsize = AtomicUsize::new(0);
+
+    /// Proves the inputs one after the other, and panics instead of generating a proof once
+    /// `abort_after` proofs have been generated in total.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct AbortingProver {
+        abort_after: Option<usize>,
+    }
+
+    impl LeafProver for AbortingProver {
+        fn batch_prove<L: PlonkParameters<D>, S: CircuitSerializer, const D: usize>(
+            &self,
+            circuit_id: &str,
+            inputs: &[PublicInput<L, D>],
+        ) -> Result<ProverOutputs<L, D>>
+        where
+            <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher:
+                AlgebraicHasher<<L as PlonkParameters<D>>::Field>,
+        {
+            NB_BATCHES.fetch_add(1, Ordering::SeqCst);
+            let mut proofs = Vec::new();
+            let mut outputs = Vec::new();
+            for input in inputs {
+                if Some(NB_PROOFS.load(Ordering::SeqCst)) == self.abort_after {
+                    panic!("aborted");
+                }
+                let (proof, output) = SequentialProver
+                    .batch_prove::<L, S, D>(circuit_id, core::slice::from_ref(input))?
+                    .materialize()?;
+                NB_PROOFS.fetch_add(1, Ordering::SeqCst);
+                proofs.extend(proof);
+                outputs.extend(output);
+            }
+            Ok(ProverOutputs::Local(proofs, outputs))
+        }
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_checkpoint_prover() {
+        type L = DefaultParameters;
+        const D: usize = 2;
+
+        let mut builder = CircuitBuilder::<L, D>::new();
+        let x = builder.read::<Variable>();
+        let y = builder.mul(x, x);
+        builder.write(y);
+        let circuit = builder.build();
+        let circuit_id = circuit.id();
+        circuit.save(
+            &format!("./build/{}.circuit", circuit_id),
+            &DefaultSerializer::gate_registry::<L, D>(),
+            &DefaultSerializer::generator_registry::<L, D>(),
+        );
+
+        let inputs = (0..8)
+            .map(|i| {
+                let mut input = circuit.input();
+                input.write::<Variable>(GoldilocksField::from_canonical_u64(i));
+                input
+            })
+            .collect_vec();
+        let dir = "./build/checkpoints/test_checkpoint_prover";
+        let _ = fs::remove_dir_all(dir);
+
+        // A run that is interrupted in the middle of the batch of the inner prover saves none of
+        // its proofs.
+        let prover = CheckpointProver::new(
+            dir,
+            AbortingProver {
+                abort_after: Some(3),
+            },
+        );
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            prover.batch_prove::<L, DefaultSerializer, D>(&circuit_id, &inputs)
+        }));
+        assert!(result.is_err());
+        assert_eq!(NB_PROOFS.load(Ordering::SeqCst), 3);
+        assert_eq!(fs::read_dir(dir).unwrap().count(), 0);
+
+        // A run over the first 3 inputs saves their proofs.
+        let prover = CheckpointProver::new(dir, AbortingProver { abort_after: None });
+        prover
+            .batch_prove::<L, DefaultSerializer, D>(&circuit_id, &inputs[..3])
+            .unwrap();
+        assert_eq!(NB_PROOFS.load(Ordering::SeqCst), 6);
+
+        // The last run loads the 3 saved proofs and generates the 5 remaining ones in a single
+        // batch.
+        let nb_batches = NB_BATCHES.load(Ordering::SeqCst);
+        let (proofs, _) = prover
+            .batch_prove::<L, DefaultSerializer, D>(&circuit_id, &inputs)
+            .unwrap()
+            .materialize()
+            .unwrap();
+        assert_eq!(NB_PROOFS.load(Ordering::SeqCst), 11);
+        assert_eq!(NB_BATCHES.load(Ordering::SeqCst), nb_batches + 1);
+        for (i, proof) in proofs.into_iter().enumerate() {
+            let i = GoldilocksField::from_canonical_u64(i as u64);
+            assert_eq!(proof.public_inputs, vec![i, i * i]);
+            circuit.data.verify(proof).unwrap();
+        }
+    }
+}
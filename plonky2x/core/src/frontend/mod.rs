@@ -1,3 +1,4 @@
+pub mod aggregation;
 pub mod builder;
 pub mod curta;
 pub mod ecc;
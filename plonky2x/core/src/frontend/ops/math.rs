@@ -2,7 +2,9 @@
 
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
-use crate::prelude::BoolVariable;
+use crate::prelude::{
+    ArrayVariable, BoolVariable, CircuitVariable, U256Variable, U32Variable, U64Variable,
+};
 
 /// The addition operation.
 ///
@@ -18,6 +20,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     where
         Lhs: Add<L, D, Rhs>,
     {
+        self.count_arithmetic_op();
         lhs.add(rhs, self)
     }
 
@@ -47,6 +50,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     where
         Lhs: Sub<L, D, Rhs>,
     {
+        self.count_arithmetic_op();
         lhs.sub(rhs, self)
     }
 }
@@ -65,6 +69,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     where
         Lhs: Mul<L, D, Rhs>,
     {
+        self.count_arithmetic_op();
         lhs.mul(rhs, self)
     }
 }
@@ -83,6 +88,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     where
         T: Neg<L, D>,
     {
+        self.count_arithmetic_op();
         value.neg(self)
     }
 }
@@ -101,6 +107,7 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     where
         Lhs: Div<L, D, Rhs>,
     {
+        self.count_arithmetic_op();
         lhs.div(rhs, self)
     }
 }
@@ -123,6 +130,32 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     }
 }
 
+/// The division with remainder operation.
+///
+/// Types implementing this trait can be used within the `builder.div_rem(lhs, rhs)` method, which
+/// returns the quotient and the remainder.
+pub trait DivRem<L: PlonkParameters<D>, const D: usize, Rhs = Self> {
+    type Output;
+
+    fn div_rem(self, rhs: Rhs, builder: &mut CircuitBuilder<L, D>) -> (Self::Output, Self::Output);
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    pub fn div_rem<Lhs, Rhs>(
+        &mut self,
+        lhs: Lhs,
+        rhs: Rhs,
+    ) -> (
+        <Lhs as DivRem<L, D, Rhs>>::Output,
+        <Lhs as DivRem<L, D, Rhs>>::Output,
+    )
+    where
+        Lhs: DivRem<L, D, Rhs>,
+    {
+        lhs.div_rem(rhs, self)
+    }
+}
+
 /// A zero element.
 ///
 /// Types implementing this trait can be used via the `builder.zero()` method.
@@ -207,9 +240,139 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     }
 }
 
+/// Addition, subtraction and multiplication that report whether the result wrapped around.
+///
+/// Types implementing this trait can be used within the `builder.checked_add(lhs, rhs)`,
+/// `builder.checked_sub(lhs, rhs)`, `builder.checked_mul(lhs, rhs)`,
+/// `builder.saturating_add(lhs, rhs)` and `builder.saturating_sub(lhs, rhs)` methods.
+pub trait CheckedArithmetic<L: PlonkParameters<D>, const D: usize>:
+    CircuitVariable + Zero<L, D>
+{
+    /// Returns the wrapped sum and whether the addition overflowed.
+    fn checked_add(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> (Self, BoolVariable);
+
+    /// Returns the wrapped difference and whether the subtraction underflowed.
+    fn checked_sub(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> (Self, BoolVariable);
+
+    /// Returns the wrapped product and whether the multiplication overflowed.
+    fn checked_mul(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> (Self, BoolVariable);
+
+    /// Returns the largest value of the type.
+    fn max_value(builder: &mut CircuitBuilder<L, D>) -> Self;
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// The addition operation, returning the wrapped sum and an overflow flag.
+    pub fn checked_add<V: CheckedArithmetic<L, D>>(&mut self, lhs: V, rhs: V) -> (V, BoolVariable) {
+        lhs.checked_add(rhs, self)
+    }
+
+    /// The subtraction operation, returning the wrapped difference and an underflow flag.
+    pub fn checked_sub<V: CheckedArithmetic<L, D>>(&mut self, lhs: V, rhs: V) -> (V, BoolVariable) {
+        lhs.checked_sub(rhs, self)
+    }
+
+    /// The multiplication operation, returning the wrapped product and an overflow flag.
+    pub fn checked_mul<V: CheckedArithmetic<L, D>>(&mut self, lhs: V, rhs: V) -> (V, BoolVariable) {
+        lhs.checked_mul(rhs, self)
+    }
+
+    /// The addition operation, returning the largest value of the type on overflow.
+    pub fn saturating_add<V: CheckedArithmetic<L, D>>(&mut self, lhs: V, rhs: V) -> V {
+        let (sum, overflow) = self.checked_add(lhs, rhs);
+        let max = V::max_value(self);
+        self.select(overflow, max, sum)
+    }
+
+    /// The subtraction operation, returning zero on underflow.
+    pub fn saturating_sub<V: CheckedArithmetic<L, D>>(&mut self, lhs: V, rhs: V) -> V {
+        let (difference, underflow) = self.checked_sub(lhs, rhs);
+        let zero = self.zero::<V>();
+        self.select(underflow, zero, difference)
+    }
+}
+
+/// The minimum and maximum operations.
+///
+/// Types implementing this trait can be used within the `builder.min(lhs, rhs)`,
+/// `builder.max(lhs, rhs)` and `builder.clamp(variable, lo, hi)` methods.
+pub trait MinMax<L: PlonkParameters<D>, const D: usize>: Sized {
+    fn min(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self;
+
+    fn max(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self;
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// The minimum of two values, elementwise for arrays.
+    pub fn min<V: MinMax<L, D>>(&mut self, lhs: V, rhs: V) -> V {
+        lhs.min(rhs, self)
+    }
+
+    /// The maximum of two values, elementwise for arrays.
+    pub fn max<V: MinMax<L, D>>(&mut self, lhs: V, rhs: V) -> V {
+        lhs.max(rhs, self)
+    }
+
+    /// Clamps a value to the range `[lo, hi]`, elementwise for arrays. Returns `hi` if `lo > hi`.
+    pub fn clamp<V: MinMax<L, D>>(&mut self, variable: V, lo: V, hi: V) -> V {
+        let lower_bounded = self.max(variable, lo);
+        self.min(lower_bounded, hi)
+    }
+}
+
+macro_rules! impl_min_max {
+    ($($t:ty),*) => {
+        $(
+            impl<L: PlonkParameters<D>, const D: usize> MinMax<L, D> for $t {
+                fn min(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self {
+                    let lte = builder.lte(self, rhs);
+                    builder.select(lte, self, rhs)
+                }
+
+                fn max(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self {
+                    let lte = builder.lte(self, rhs);
+                    builder.select(lte, rhs, self)
+                }
+            }
+        )*
+    };
+}
+
+impl_min_max!(U32Variable, U64Variable, U256Variable);
+
+impl<L: PlonkParameters<D>, const D: usize, V, const N: usize> MinMax<L, D> for ArrayVariable<V, N>
+where
+    V: CircuitVariable + MinMax<L, D>,
+{
+    fn min(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self {
+        self.as_vec()
+            .into_iter()
+            .zip(rhs.as_vec())
+            .map(|(a, b)| a.min(b, builder))
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    fn max(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self {
+        self.as_vec()
+            .into_iter()
+            .zip(rhs.as_vec())
+            .map(|(a, b)| a.max(b, builder))
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
 mod tests {
     #[allow(unused_imports)]
-    use crate::prelude::{BoolVariable, DefaultBuilder, U32Variable};
+    use ethers::types::U256;
+    #[allow(unused_imports)]
+    use rand::Rng;
+
+    #[allow(unused_imports)]
+    use crate::prelude::{
+        ArrayVariable, BoolVariable, DefaultBuilder, U256Variable, U32Variable, U64Variable,
+    };
 
     #[test]
     fn test_math_lt() {
@@ -326,4 +489,78 @@ mod tests {
             circuit.verify(&proof, &input, &output);
         }
     }
+
+    #[test]
+    fn test_math_min_max() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U32Variable>();
+        let b = builder.read::<U32Variable>();
+        let c = builder.read::<U64Variable>();
+        let d = builder.read::<U64Variable>();
+        let e = builder.read::<U256Variable>();
+        let f = builder.read::<U256Variable>();
+        let min = builder.min(a, b);
+        let max = builder.max(a, b);
+        builder.write(min);
+        builder.write(max);
+        let min = builder.min(c, d);
+        let max = builder.max(c, d);
+        builder.write(min);
+        builder.write(max);
+        let min = builder.min(e, f);
+        let max = builder.max(e, f);
+        builder.write(min);
+        builder.write(max);
+        let circuit = builder.build();
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..4 {
+            let (a, b) = (rng.gen::<u32>(), rng.gen::<u32>());
+            let (c, d) = (rng.gen::<u64>(), rng.gen::<u64>());
+            let (e, f) = (U256(rng.gen()), U256(rng.gen()));
+            let mut input = circuit.input();
+            input.write::<U32Variable>(a);
+            input.write::<U32Variable>(b);
+            input.write::<U64Variable>(c);
+            input.write::<U64Variable>(d);
+            input.write::<U256Variable>(e);
+            input.write::<U256Variable>(f);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<U32Variable>(), a.min(b));
+            assert_eq!(output.read::<U32Variable>(), a.max(b));
+            assert_eq!(output.read::<U64Variable>(), c.min(d));
+            assert_eq!(output.read::<U64Variable>(), c.max(d));
+            assert_eq!(output.read::<U256Variable>(), e.min(f));
+            assert_eq!(output.read::<U256Variable>(), e.max(f));
+        }
+    }
+
+    #[test]
+    fn test_math_clamp() {
+        let mut builder = DefaultBuilder::new();
+        let x = builder.read::<ArrayVariable<U64Variable, 3>>();
+        let lo = builder.read::<ArrayVariable<U64Variable, 3>>();
+        let hi = builder.read::<ArrayVariable<U64Variable, 3>>();
+        let clamped = builder.clamp(x, lo, hi);
+        builder.write(clamped);
+        let circuit = builder.build();
+
+        let cases = [
+            // Below, inside and above the range.
+            ([1, 50, 900], [10, 10, 10], [100, 100, 100]),
+            // The degenerate range lo == hi.
+            ([0, 7, u64::MAX], [7, 7, 7], [7, 7, 7]),
+        ];
+        for (x, lo, hi) in cases {
+            let mut input = circuit.input();
+            input.write::<ArrayVariable<U64Variable, 3>>(x.to_vec());
+            input.write::<ArrayVariable<U64Variable, 3>>(lo.to_vec());
+            input.write::<ArrayVariable<U64Variable, 3>>(hi.to_vec());
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            let expected = (0..3).map(|i| x[i].clamp(lo[i], hi[i])).collect::<Vec<_>>();
+            assert_eq!(output.read::<ArrayVariable<U64Variable, 3>>(), expected);
+        }
+    }
 }
@@ -0,0 +1,208 @@
+use itertools::Itertools;
+use plonky2::fri::proof::FriChallengesTarget;
+use plonky2::fri::FriConfig;
+use plonky2::hash::hash_types::HashOutTarget;
+use plonky2::iop::challenger::RecursiveChallenger;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::config::AlgebraicHasher;
+
+use crate::frontend::hash::poseidon::poseidon256::PoseidonHashOutVariable;
+use crate::frontend::recursion::extension::ExtensionVariable;
+use crate::frontend::recursion::hash::MerkleCapVariable;
+use crate::frontend::recursion::polynomial::PolynomialCoeffsExtVariable;
+use crate::prelude::{CircuitBuilder, PlonkParameters, Variable};
+
+/// A Fiat-Shamir challenger in the circuit, which absorbs observations into a duplex sponge over
+/// the permutation of `H` and squeezes challenges out of it. It generates the same challenges as
+/// the native `Challenger` for the same observations.
+#[derive(Debug)]
+pub struct ChallengerVariable<L: PlonkParameters<D>, H: AlgebraicHasher<L::Field>, const D: usize>(
+    RecursiveChallenger<L::Field, H, D>,
+);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FriChallengesVariable<const D: usize> {
+    pub fri_alpha: ExtensionVariable<D>,
+    pub fri_betas: Vec<ExtensionVariable<D>>,
+    pub fri_pow_response: Variable,
+    pub fri_query_indices: Vec<Variable>,
+}
+
+impl<L: PlonkParameters<D>, H: AlgebraicHasher<L::Field>, const D: usize>
+    ChallengerVariable<L, H, D>
+{
+    pub fn new(builder: &mut CircuitBuilder<L, D>) -> Self {
+        Self(RecursiveChallenger::new(&mut builder.api))
+    }
+
+    pub fn observe_element(&mut self, element: Variable) {
+        self.0.observe_element(element.0);
+    }
+
+    pub fn observe_elements(&mut self, elements: &[Variable]) {
+        self.0
+            .observe_elements(&elements.iter().map(|v| v.0).collect_vec());
+    }
+
+    pub fn observe_extension_elements(&mut self, elements: &[ExtensionVariable<D>]) {
+        self.0.observe_extension_elements(
+            &elements
+                .iter()
+                .cloned()
+                .map(ExtensionTarget::from)
+                .collect_vec(),
+        );
+    }
+
+    pub fn observe_hash(&mut self, hash: &PoseidonHashOutVariable) {
+        self.0.observe_hash(&HashOutTarget::from(hash.clone()));
+    }
+
+    pub fn observe_cap(&mut self, cap: &MerkleCapVariable) {
+        self.0.observe_cap(&cap.clone().into());
+    }
+
+    pub fn get_challenge(&mut self, builder: &mut CircuitBuilder<L, D>) -> Variable {
+        Variable(self.0.get_challenge(&mut builder.api))
+    }
+
+    pub fn get_n_challenges(
+        &mut self,
+        builder: &mut CircuitBuilder<L, D>,
+        n: usize,
+    ) -> Vec<Variable> {
+        self.0
+            .get_n_challenges(&mut builder.api, n)
+            .into_iter()
+            .map(Variable)
+            .collect()
+    }
+
+    pub fn get_extension_challenge(
+        &mut self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> ExtensionVariable<D> {
+        self.0.get_extension_challenge(&mut builder.api).into()
+    }
+
+    /// Generates the challenges of the FRI protocol, after the openings of the proof have been
+    /// observed: the combination challenge `alpha`, one folding challenge per commit phase Merkle
+    /// cap, the proof of work response and the query indices.
+    pub fn get_fri_challenges(
+        &mut self,
+        builder: &mut CircuitBuilder<L, D>,
+        commit_phase_merkle_caps: &[MerkleCapVariable],
+        final_poly: &PolynomialCoeffsExtVariable<D>,
+        pow_witness: Variable,
+        config: &FriConfig,
+    ) -> FriChallengesVariable<D> {
+        let commit_phase_merkle_caps = commit_phase_merkle_caps
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect_vec();
+        self.0
+            .fri_challenges(
+                &mut builder.api,
+                &commit_phase_merkle_caps,
+                &final_poly.clone().into(),
+                pow_witness.0,
+                config,
+            )
+            .into()
+    }
+}
+
+impl<L: PlonkParameters<D>, H: AlgebraicHasher<L::Field>, const D: usize>
+    From<RecursiveChallenger<L::Field, H, D>> for ChallengerVariable<L, H, D>
+{
+    fn from(value: RecursiveChallenger<L::Field, H, D>) -> Self {
+        Self(value)
+    }
+}
+
+impl<L: PlonkParameters<D>, H: AlgebraicHasher<L::Field>, const D: usize>
+    From<ChallengerVariable<L, H, D>> for RecursiveChallenger<L::Field, H, D>
+{
+    fn from(value: ChallengerVariable<L, H, D>) -> Self {
+        value.0
+    }
+}
+
+impl<const D: usize> From<FriChallengesTarget<D>> for FriChallengesVariable<D> {
+    fn from(value: FriChallengesTarget<D>) -> Self {
+        Self {
+            fri_alpha: value.fri_alpha.into(),
+            fri_betas: value.fri_betas.into_iter().map(Into::into).collect(),
+            fri_pow_response: Variable(value.fri_pow_response),
+            fri_query_indices: value.fri_query_indices.into_iter().map(Variable).collect(),
+        }
+    }
+}
+
+impl<const D: usize> From<FriChallengesVariable<D>> for FriChallengesTarget<D> {
+    fn from(value: FriChallengesVariable<D>) -> Self {
+        Self {
+            fri_alpha: value.fri_alpha.into(),
+            fri_betas: value.fri_betas.into_iter().map(Into::into).collect(),
+            fri_pow_response: value.fri_pow_response.0,
+            fri_query_indices: value.fri_query_indices.into_iter().map(|v| v.0).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::hash::merkle_tree::MerkleCap;
+    use plonky2::hash::poseidon::PoseidonHash;
+    use plonky2::iop::challenger::Challenger;
+    use plonky2::plonk::config::Hasher;
+
+    use super::*;
+    use crate::prelude::{DefaultBuilder, DefaultParameters, Field, GoldilocksField};
+
+    type F = GoldilocksField;
+
+    #[test]
+    fn test_challenger() {
+        // More elements than the rate of the sponge, so that absorbing takes several permutations.
+        let elements = (0..10).map(F::from_canonical_u64).collect_vec();
+        let cap = MerkleCap::<F, PoseidonHash>(
+            (0..4)
+                .map(|i| PoseidonHash::hash_no_pad(&[F::from_canonical_u64(i)]))
+                .collect(),
+        );
+
+        let mut builder = DefaultBuilder::new();
+        let element_variables = elements
+            .iter()
+            .map(|element| builder.constant::<Variable>(*element))
+            .collect_vec();
+        let cap_variable = MerkleCapVariable(
+            cap.0
+                .iter()
+                .map(|hash| builder.constant::<PoseidonHashOutVariable>(*hash))
+                .collect(),
+        );
+        let mut challenger =
+            ChallengerVariable::<DefaultParameters, PoseidonHash, 2>::new(&mut builder);
+        challenger.observe_elements(&element_variables);
+        let challenge = challenger.get_challenge(&mut builder);
+        builder.write(challenge);
+        challenger.observe_cap(&cap_variable);
+        // More challenges than the rate of the sponge, so that squeezing takes several permutations.
+        for challenge in challenger.get_n_challenges(&mut builder, 12) {
+            builder.write(challenge);
+        }
+        let circuit = builder.mock_build();
+        let mut output = circuit.mock_prove(&circuit.input()).output;
+
+        let mut native_challenger = Challenger::<F, PoseidonHash>::new();
+        native_challenger.observe_elements(&elements);
+        assert_eq!(output.read::<Variable>(), native_challenger.get_challenge());
+        native_challenger.observe_cap(&cap);
+        for expected in native_challenger.get_n_challenges(12) {
+            assert_eq!(output.read::<Variable>(), expected);
+        }
+    }
+}
@@ -6,7 +6,7 @@ use plonky2::fri::proof::{
 };
 use plonky2::fri::FriParams;
 use plonky2::gadgets::polynomial::PolynomialCoeffsExtTarget;
-use plonky2::hash::hash_types::MerkleCapTarget;
+use plonky2::hash::hash_types::{MerkleCapTarget, RichField};
 use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::plonk::config::AlgebraicHasher;
 
@@ -384,6 +384,18 @@ impl<L: PlonkParameters<D>, const D: usize> ValueStream<L, D> {
         self.write_value::<Variable>(pow_witness);
     }
 
+    /// Writes a FRI proof like `write_fri_proof`, after checking that its shape is the one that
+    /// `read_fri_proof` expects for the same parameters.
+    pub fn write_fri_proof_value<H: AlgebraicHasher<L::Field>>(
+        &mut self,
+        proof: FriProof<L::Field, H, D>,
+        num_leaves_per_oracle: &[usize],
+        params: &FriParams,
+    ) {
+        assert_fri_proof_shape(&proof, num_leaves_per_oracle, params);
+        self.write_fri_proof(proof);
+    }
+
     pub fn write_poly_coeff_ext(
         &mut self,
         coefficients: PolynomialCoeffs<<L::Field as Extendable<D>>::Extension>,
@@ -432,6 +444,73 @@ impl<L: PlonkParameters<D>, const D: usize> ValueStream<L, D> {
     }
 }
 
+/// Panics if `proof` does not have the shape of a FRI proof for `params`, since reading it back
+/// would silently misalign every value that follows it in the stream.
+fn assert_fri_proof_shape<F: RichField + Extendable<D>, H: AlgebraicHasher<F>, const D: usize>(
+    proof: &FriProof<F, H, D>,
+    num_leaves_per_oracle: &[usize],
+    params: &FriParams,
+) {
+    let cap_len = 1 << params.config.cap_height;
+    assert_eq!(
+        proof.commit_phase_merkle_caps.len(),
+        params.reduction_arity_bits.len(),
+        "wrong number of commit phase Merkle caps"
+    );
+    for cap in proof.commit_phase_merkle_caps.iter() {
+        assert_eq!(cap.0.len(), cap_len, "wrong commit phase Merkle cap length");
+    }
+
+    assert_eq!(
+        proof.query_round_proofs.len(),
+        params.config.num_query_rounds,
+        "wrong number of query rounds"
+    );
+    for query_round in proof.query_round_proofs.iter() {
+        let mut merkle_proof_len = params.lde_bits() - params.config.cap_height;
+        let evals_proofs = &query_round.initial_trees_proof.evals_proofs;
+        assert_eq!(
+            evals_proofs.len(),
+            num_leaves_per_oracle.len(),
+            "wrong number of initial trees"
+        );
+        for ((leaves, merkle_proof), &num_leaves) in evals_proofs.iter().zip(num_leaves_per_oracle)
+        {
+            assert_eq!(
+                leaves.len(),
+                num_leaves,
+                "wrong number of initial tree leaves"
+            );
+            assert_eq!(
+                merkle_proof.siblings.len(),
+                merkle_proof_len,
+                "wrong initial tree Merkle proof length"
+            );
+        }
+
+        assert_eq!(
+            query_round.steps.len(),
+            params.reduction_arity_bits.len(),
+            "wrong number of query steps"
+        );
+        for (step, &arity_bits) in query_round.steps.iter().zip(&params.reduction_arity_bits) {
+            merkle_proof_len -= arity_bits;
+            assert_eq!(step.evals.len(), 1 << arity_bits, "wrong query step arity");
+            assert_eq!(
+                step.merkle_proof.siblings.len(),
+                merkle_proof_len,
+                "wrong query step Merkle proof length"
+            );
+        }
+    }
+
+    assert_eq!(
+        proof.final_poly.len(),
+        params.final_poly_len(),
+        "wrong final polynomial length"
+    );
+}
+
 impl From<FriInitialTreeProofVariable> for FriInitialTreeProofTarget {
     fn from(value: FriInitialTreeProofVariable) -> Self {
         Self {
@@ -651,4 +730,57 @@ mod tests {
 
         assert_eq!(fri_proof, proof_back);
     }
+
+    fn prove_value_stream_circuit() -> (
+        FriProof<GoldilocksField, PoseidonHash, 2>,
+        Vec<usize>,
+        FriParams,
+    ) {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let _ = builder.mul(a, b);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::TWO);
+        input.write::<Variable>(GoldilocksField::ONE);
+        let (proof, _) = circuit.prove(&input);
+
+        let common_data = &circuit.data.common;
+        let salt = salt_size(common_data.fri_params.hiding);
+        let num_leaves_per_oracle = vec![
+            common_data.sigmas_range().end,
+            common_data.config.num_wires + salt,
+            common_data.config.num_challenges * (1 + common_data.num_partial_products) + salt,
+            common_data.config.num_challenges * common_data.quotient_degree_factor + salt,
+        ];
+        (
+            proof.proof.opening_proof,
+            num_leaves_per_oracle,
+            common_data.fri_params.clone(),
+        )
+    }
+
+    #[test]
+    fn test_value_stream_checked() {
+        let (fri_proof, num_leaves_per_oracle, fri_params) = prove_value_stream_circuit();
+
+        let mut stream = ValueStream::<DefaultParameters, 2>::new();
+        stream.write_fri_proof_value(fri_proof.clone(), &num_leaves_per_oracle, &fri_params);
+        let proof_back: FriProof<GoldilocksField, PoseidonHash, 2> =
+            stream.read_fri_proof(&num_leaves_per_oracle, &fri_params);
+
+        assert_eq!(fri_proof, proof_back);
+    }
+
+    #[test]
+    #[should_panic(expected = "wrong final polynomial length")]
+    fn test_value_stream_checked_wrong_shape() {
+        let (mut fri_proof, num_leaves_per_oracle, fri_params) = prove_value_stream_circuit();
+        fri_proof.final_poly.coeffs.pop();
+
+        let mut stream = ValueStream::<DefaultParameters, 2>::new();
+        stream.write_fri_proof_value(fri_proof, &num_leaves_per_oracle, &fri_params);
+    }
 }
@@ -1,4 +1,6 @@
+pub mod challenger;
 pub mod extension;
 pub mod fri;
 pub mod hash;
 pub mod polynomial;
+pub mod proof;
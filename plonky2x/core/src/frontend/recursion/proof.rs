@@ -0,0 +1,557 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::{MerkleCapTarget, RichField};
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::iop::witness::WitnessWrite;
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::plonk_common::salt_size;
+use plonky2::plonk::proof::{
+    OpeningSet, OpeningSetTarget, Proof, ProofTarget, ProofWithPublicInputs,
+    ProofWithPublicInputsTarget,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::recursion::extension::ExtensionVariable;
+use crate::frontend::recursion::fri::proof::FriProofVariable;
+use crate::frontend::recursion::hash::MerkleCapVariable;
+use crate::frontend::vars::{ValueStream, VariableStream};
+use crate::prelude::{CircuitBuilder, OutputVariableStream, PlonkParameters, Variable};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofWithPublicInputsVariable<const D: usize> {
+    pub proof: ProofVariable<D>,
+    pub public_inputs: Vec<Variable>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProofVariable<const D: usize> {
+    pub wires_cap: MerkleCapVariable,
+    pub plonk_zs_partial_products_cap: MerkleCapVariable,
+    pub quotient_polys_cap: MerkleCapVariable,
+    pub openings: OpeningSetVariable<D>,
+    pub opening_proof: FriProofVariable<D>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OpeningSetVariable<const D: usize> {
+    pub constants: Vec<ExtensionVariable<D>>,
+    pub plonk_sigmas: Vec<ExtensionVariable<D>>,
+    pub wires: Vec<ExtensionVariable<D>>,
+    pub plonk_zs: Vec<ExtensionVariable<D>>,
+    pub plonk_zs_next: Vec<ExtensionVariable<D>>,
+    pub lookup_zs: Vec<ExtensionVariable<D>>,
+    pub next_lookup_zs: Vec<ExtensionVariable<D>>,
+    pub partial_products: Vec<ExtensionVariable<D>>,
+    pub quotient_polys: Vec<ExtensionVariable<D>>,
+}
+
+/// The number of polynomials committed to by each oracle of a proof, in the order of the initial
+/// trees of its FRI proof.
+fn num_leaves_per_oracle<F: RichField + Extendable<D>, const D: usize>(
+    common_data: &CommonCircuitData<F, D>,
+) -> Vec<usize> {
+    let salt = salt_size(common_data.fri_params.hiding);
+    let mut num_leaves_per_oracle = vec![
+        common_data.num_preprocessed_polys(),
+        common_data.config.num_wires + salt,
+        common_data.num_zs_partial_products_polys() + common_data.num_all_lookup_polys() + salt,
+    ];
+    if common_data.num_quotient_polys() > 0 {
+        num_leaves_per_oracle.push(common_data.num_quotient_polys() + salt);
+    }
+    num_leaves_per_oracle
+}
+
+/// The number of lookup polynomials opened by a proof, which is zero if the circuit has no lookups.
+fn num_lookup_openings<F: RichField + Extendable<D>, const D: usize>(
+    common_data: &CommonCircuitData<F, D>,
+) -> usize {
+    if common_data.num_lookup_polys != 0 {
+        common_data.num_all_lookup_polys()
+    } else {
+        0
+    }
+}
+
+impl VariableStream {
+    pub fn read_proof_with_pis<F: RichField + Extendable<D>, const D: usize>(
+        &mut self,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> ProofWithPublicInputsVariable<D> {
+        let cap_height = common_data.fri_params.config.cap_height;
+
+        let wires_cap = self.read_merkle_cap(cap_height);
+        let plonk_zs_partial_products_cap = self.read_merkle_cap(cap_height);
+        let quotient_polys_cap = self.read_merkle_cap(cap_height);
+        let openings = self.read_opening_set(common_data);
+        let opening_proof =
+            self.read_fri_proof(&num_leaves_per_oracle(common_data), &common_data.fri_params);
+        let public_inputs = self.read_exact(common_data.num_public_inputs).to_vec();
+
+        ProofWithPublicInputsVariable {
+            proof: ProofVariable {
+                wires_cap,
+                plonk_zs_partial_products_cap,
+                quotient_polys_cap,
+                openings,
+                opening_proof,
+            },
+            public_inputs,
+        }
+    }
+
+    pub fn read_opening_set<F: RichField + Extendable<D>, const D: usize>(
+        &mut self,
+        common_data: &CommonCircuitData<F, D>,
+    ) -> OpeningSetVariable<D> {
+        let config = &common_data.config;
+        let num_challenges = config.num_challenges;
+        let num_lookups = num_lookup_openings(common_data);
+        OpeningSetVariable {
+            constants: self.read_vec::<ExtensionVariable<D>>(common_data.num_constants),
+            plonk_sigmas: self.read_vec::<ExtensionVariable<D>>(config.num_routed_wires),
+            wires: self.read_vec::<ExtensionVariable<D>>(config.num_wires),
+            plonk_zs: self.read_vec::<ExtensionVariable<D>>(num_challenges),
+            plonk_zs_next: self.read_vec::<ExtensionVariable<D>>(num_challenges),
+            lookup_zs: self.read_vec::<ExtensionVariable<D>>(num_lookups),
+            next_lookup_zs: self.read_vec::<ExtensionVariable<D>>(num_lookups),
+            partial_products: self.read_vec::<ExtensionVariable<D>>(
+                num_challenges * common_data.num_partial_products,
+            ),
+            quotient_polys: self.read_vec::<ExtensionVariable<D>>(common_data.num_quotient_polys()),
+        }
+    }
+
+    pub fn write_proof_with_pis<const D: usize>(
+        &mut self,
+        proof_with_pis: &ProofWithPublicInputsVariable<D>,
+    ) {
+        let ProofWithPublicInputsVariable {
+            proof,
+            public_inputs,
+        } = proof_with_pis;
+
+        self.write_merkle_cap(&proof.wires_cap);
+        self.write_merkle_cap(&proof.plonk_zs_partial_products_cap);
+        self.write_merkle_cap(&proof.quotient_polys_cap);
+        self.write_opening_set(&proof.openings);
+        self.write_fri_proof(&proof.opening_proof);
+        self.write_slice(public_inputs);
+    }
+
+    pub fn write_opening_set<const D: usize>(&mut self, openings: &OpeningSetVariable<D>) {
+        self.write_slice(&openings.constants);
+        self.write_slice(&openings.plonk_sigmas);
+        self.write_slice(&openings.wires);
+        self.write_slice(&openings.plonk_zs);
+        self.write_slice(&openings.plonk_zs_next);
+        self.write_slice(&openings.lookup_zs);
+        self.write_slice(&openings.next_lookup_zs);
+        self.write_slice(&openings.partial_products);
+        self.write_slice(&openings.quotient_polys);
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> OutputVariableStream<L, D> {
+    pub fn read_proof_with_pis(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        common_data: &CommonCircuitData<L::Field, D>,
+    ) -> ProofWithPublicInputsVariable<D> {
+        let cap_height = common_data.fri_params.config.cap_height;
+
+        let wires_cap = self.read_merkle_cap(builder, cap_height);
+        let plonk_zs_partial_products_cap = self.read_merkle_cap(builder, cap_height);
+        let quotient_polys_cap = self.read_merkle_cap(builder, cap_height);
+        let openings = self.read_opening_set(builder, common_data);
+        let opening_proof = self.read_fri_proof(
+            builder,
+            &num_leaves_per_oracle(common_data),
+            &common_data.fri_params,
+        );
+        let public_inputs = self.read_exact(builder, common_data.num_public_inputs);
+
+        ProofWithPublicInputsVariable {
+            proof: ProofVariable {
+                wires_cap,
+                plonk_zs_partial_products_cap,
+                quotient_polys_cap,
+                openings,
+                opening_proof,
+            },
+            public_inputs,
+        }
+    }
+
+    pub fn read_opening_set(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        common_data: &CommonCircuitData<L::Field, D>,
+    ) -> OpeningSetVariable<D> {
+        let config = &common_data.config;
+        let num_challenges = config.num_challenges;
+        let num_lookups = num_lookup_openings(common_data);
+        OpeningSetVariable {
+            constants: self.read_vec::<ExtensionVariable<D>>(builder, common_data.num_constants),
+            plonk_sigmas: self.read_vec::<ExtensionVariable<D>>(builder, config.num_routed_wires),
+            wires: self.read_vec::<ExtensionVariable<D>>(builder, config.num_wires),
+            plonk_zs: self.read_vec::<ExtensionVariable<D>>(builder, num_challenges),
+            plonk_zs_next: self.read_vec::<ExtensionVariable<D>>(builder, num_challenges),
+            lookup_zs: self.read_vec::<ExtensionVariable<D>>(builder, num_lookups),
+            next_lookup_zs: self.read_vec::<ExtensionVariable<D>>(builder, num_lookups),
+            partial_products: self.read_vec::<ExtensionVariable<D>>(
+                builder,
+                num_challenges * common_data.num_partial_products,
+            ),
+            quotient_polys: self
+                .read_vec::<ExtensionVariable<D>>(builder, common_data.num_quotient_polys()),
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> ValueStream<L, D> {
+    pub fn read_proof_with_pis<C>(
+        &mut self,
+        common_data: &CommonCircuitData<L::Field, D>,
+    ) -> ProofWithPublicInputs<L::Field, C, D>
+    where
+        C: GenericConfig<D, F = L::Field>,
+        C::Hasher: AlgebraicHasher<L::Field>,
+    {
+        let cap_height = common_data.fri_params.config.cap_height;
+
+        let wires_cap = self.read_merkle_cap(cap_height);
+        let plonk_zs_partial_products_cap = self.read_merkle_cap(cap_height);
+        let quotient_polys_cap = self.read_merkle_cap(cap_height);
+        let openings = self.read_opening_set(common_data);
+        let opening_proof =
+            self.read_fri_proof(&num_leaves_per_oracle(common_data), &common_data.fri_params);
+        let public_inputs = self.read_exact(common_data.num_public_inputs).to_vec();
+
+        ProofWithPublicInputs {
+            proof: Proof {
+                wires_cap,
+                plonk_zs_partial_products_cap,
+                quotient_polys_cap,
+                openings,
+                opening_proof,
+            },
+            public_inputs,
+        }
+    }
+
+    pub fn read_opening_set(
+        &mut self,
+        common_data: &CommonCircuitData<L::Field, D>,
+    ) -> OpeningSet<L::Field, D> {
+        let config = &common_data.config;
+        let num_challenges = config.num_challenges;
+        let num_lookups = num_lookup_openings(common_data);
+        OpeningSet {
+            constants: self.read_extension_vec(common_data.num_constants),
+            plonk_sigmas: self.read_extension_vec(config.num_routed_wires),
+            wires: self.read_extension_vec(config.num_wires),
+            plonk_zs: self.read_extension_vec(num_challenges),
+            plonk_zs_next: self.read_extension_vec(num_challenges),
+            lookup_zs: self.read_extension_vec(num_lookups),
+            lookup_zs_next: self.read_extension_vec(num_lookups),
+            partial_products: self
+                .read_extension_vec(num_challenges * common_data.num_partial_products),
+            quotient_polys: self.read_extension_vec(common_data.num_quotient_polys()),
+        }
+    }
+
+    pub fn write_proof_with_pis<C>(&mut self, proof_with_pis: ProofWithPublicInputs<L::Field, C, D>)
+    where
+        C: GenericConfig<D, F = L::Field>,
+        C::Hasher: AlgebraicHasher<L::Field>,
+    {
+        let ProofWithPublicInputs {
+            proof,
+            public_inputs,
+        } = proof_with_pis;
+
+        self.write_merkle_cap(proof.wires_cap);
+        self.write_merkle_cap(proof.plonk_zs_partial_products_cap);
+        self.write_merkle_cap(proof.quotient_polys_cap);
+        self.write_opening_set(proof.openings);
+        self.write_fri_proof(proof.opening_proof);
+        self.write_slice(&public_inputs);
+    }
+
+    pub fn write_opening_set(&mut self, openings: OpeningSet<L::Field, D>) {
+        let OpeningSet {
+            constants,
+            plonk_sigmas,
+            wires,
+            plonk_zs,
+            plonk_zs_next,
+            lookup_zs,
+            lookup_zs_next,
+            partial_products,
+            quotient_polys,
+        } = openings;
+
+        self.write_extension_vec(constants);
+        self.write_extension_vec(plonk_sigmas);
+        self.write_extension_vec(wires);
+        self.write_extension_vec(plonk_zs);
+        self.write_extension_vec(plonk_zs_next);
+        self.write_extension_vec(lookup_zs);
+        self.write_extension_vec(lookup_zs_next);
+        self.write_extension_vec(partial_products);
+        self.write_extension_vec(quotient_polys);
+    }
+}
+
+/// Outputs a fixed proof, such as the dummy proof that `conditionally_verify_proof` verifies when
+/// its condition is false.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct DummyProofHint<L: PlonkParameters<D>, const D: usize> {
+    pub proof: ProofWithPublicInputs<L::Field, L::Config, D>,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for DummyProofHint<L, D>
+where
+    <<L as PlonkParameters<D>>::Config as GenericConfig<D>>::Hasher: AlgebraicHasher<L::Field>,
+{
+    fn hint(&self, _input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        output_stream.write_proof_with_pis(self.proof.clone());
+    }
+}
+
+/// Sets the value of a `ProofWithPublicInputsVariable` in a witness, like
+/// `WitnessWrite::set_proof_with_pis_target` does for its target.
+pub trait WitnessProofWithPublicInputs<F: RichField + Extendable<D>, const D: usize>:
+    WitnessWrite<F>
+{
+    fn set_proof_with_pis_variable<C: GenericConfig<D, F = F>>(
+        &mut self,
+        variable: &ProofWithPublicInputsVariable<D>,
+        proof_with_pis: &ProofWithPublicInputs<F, C, D>,
+    ) where
+        C::Hasher: AlgebraicHasher<F>;
+}
+
+impl<T: WitnessWrite<F>, F: RichField + Extendable<D>, const D: usize>
+    WitnessProofWithPublicInputs<F, D> for T
+{
+    fn set_proof_with_pis_variable<C: GenericConfig<D, F = F>>(
+        &mut self,
+        variable: &ProofWithPublicInputsVariable<D>,
+        proof_with_pis: &ProofWithPublicInputs<F, C, D>,
+    ) where
+        C::Hasher: AlgebraicHasher<F>,
+    {
+        let target = ProofWithPublicInputsTarget::from(variable.clone());
+        self.set_proof_with_pis_target(&target, proof_with_pis);
+    }
+}
+
+impl<const D: usize> From<OpeningSetTarget<D>> for OpeningSetVariable<D> {
+    fn from(value: OpeningSetTarget<D>) -> Self {
+        let convert = |targets: Vec<ExtensionTarget<D>>| {
+            targets
+                .into_iter()
+                .map(ExtensionVariable::from)
+                .collect::<Vec<_>>()
+        };
+        Self {
+            constants: convert(value.constants),
+            plonk_sigmas: convert(value.plonk_sigmas),
+            wires: convert(value.wires),
+            plonk_zs: convert(value.plonk_zs),
+            plonk_zs_next: convert(value.plonk_zs_next),
+            lookup_zs: convert(value.lookup_zs),
+            next_lookup_zs: convert(value.next_lookup_zs),
+            partial_products: convert(value.partial_products),
+            quotient_polys: convert(value.quotient_polys),
+        }
+    }
+}
+
+impl<const D: usize> From<OpeningSetVariable<D>> for OpeningSetTarget<D> {
+    fn from(value: OpeningSetVariable<D>) -> Self {
+        let convert = |variables: Vec<ExtensionVariable<D>>| {
+            variables
+                .into_iter()
+                .map(ExtensionTarget::from)
+                .collect::<Vec<_>>()
+        };
+        Self {
+            constants: convert(value.constants),
+            plonk_sigmas: convert(value.plonk_sigmas),
+            wires: convert(value.wires),
+            plonk_zs: convert(value.plonk_zs),
+            plonk_zs_next: convert(value.plonk_zs_next),
+            lookup_zs: convert(value.lookup_zs),
+            next_lookup_zs: convert(value.next_lookup_zs),
+            partial_products: convert(value.partial_products),
+            quotient_polys: convert(value.quotient_polys),
+        }
+    }
+}
+
+impl<const D: usize> From<ProofTarget<D>> for ProofVariable<D> {
+    fn from(value: ProofTarget<D>) -> Self {
+        Self {
+            wires_cap: value.wires_cap.into(),
+            plonk_zs_partial_products_cap: value.plonk_zs_partial_products_cap.into(),
+            quotient_polys_cap: value.quotient_polys_cap.into(),
+            openings: value.openings.into(),
+            opening_proof: value.opening_proof.into(),
+        }
+    }
+}
+
+impl<const D: usize> From<ProofVariable<D>> for ProofTarget<D> {
+    fn from(value: ProofVariable<D>) -> Self {
+        Self {
+            wires_cap: MerkleCapTarget::from(value.wires_cap),
+            plonk_zs_partial_products_cap: MerkleCapTarget::from(
+                value.plonk_zs_partial_products_cap,
+            ),
+            quotient_polys_cap: MerkleCapTarget::from(value.quotient_polys_cap),
+            openings: value.openings.into(),
+            opening_proof: value.opening_proof.into(),
+        }
+    }
+}
+
+impl<const D: usize> From<ProofWithPublicInputsTarget<D>> for ProofWithPublicInputsVariable<D> {
+    fn from(value: ProofWithPublicInputsTarget<D>) -> Self {
+        Self {
+            proof: value.proof.into(),
+            public_inputs: value.public_inputs.into_iter().map(Variable).collect(),
+        }
+    }
+}
+
+impl<const D: usize> From<ProofWithPublicInputsVariable<D>> for ProofWithPublicInputsTarget<D> {
+    fn from(value: ProofWithPublicInputsVariable<D>) -> Self {
+        Self {
+            proof: value.proof.into(),
+            public_inputs: value.public_inputs.into_iter().map(|v| v.0).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::circuit::CircuitBuild;
+    use crate::prelude::*;
+
+    type C = <DefaultParameters as PlonkParameters<2>>::Config;
+
+    fn inner_circuit() -> CircuitBuild<DefaultParameters, 2> {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let c = builder.add(a, b);
+        builder.write(c);
+        builder.build()
+    }
+
+    #[test]
+    fn test_conversion() {
+        let circuit = inner_circuit();
+        let mut builder = DefaultBuilder::new();
+        let proof = builder.add_virtual_proof_with_pis(&circuit.data.common);
+
+        let proof_variable = ProofWithPublicInputsVariable::from(proof.clone());
+        let proof_back = ProofWithPublicInputsTarget::from(proof_variable);
+
+        assert_eq!(proof, proof_back);
+    }
+
+    #[test]
+    fn test_opening_set_conversion() {
+        let circuit = inner_circuit();
+        let mut builder = DefaultBuilder::new();
+        let openings = builder
+            .add_virtual_proof_with_pis(&circuit.data.common)
+            .proof
+            .openings;
+
+        let openings_variable = OpeningSetVariable::from(openings.clone());
+        let openings_back = OpeningSetTarget::from(openings_variable.clone());
+        assert_eq!(openings, openings_back);
+
+        let mut stream = VariableStream::new();
+        stream.write_opening_set(&openings_variable);
+        let openings_read = stream.read_opening_set(&circuit.data.common);
+        assert_eq!(openings_variable, openings_read);
+    }
+
+    #[test]
+    fn test_variable_stream() {
+        let circuit = inner_circuit();
+        let mut builder = DefaultBuilder::new();
+        let proof = builder.add_virtual_proof_with_pis(&circuit.data.common);
+        let proof_variable = ProofWithPublicInputsVariable::from(proof);
+
+        let mut stream = VariableStream::new();
+        stream.write_proof_with_pis(&proof_variable);
+        let proof_back = stream.read_proof_with_pis(&circuit.data.common);
+
+        assert_eq!(proof_variable, proof_back);
+    }
+
+    #[test]
+    fn test_value_stream() {
+        let circuit = inner_circuit();
+        let mut input = circuit.input();
+        input.write::<Variable>(GoldilocksField::ONE);
+        input.write::<Variable>(GoldilocksField::TWO);
+        let (proof, _) = circuit.prove(&input);
+
+        let mut stream = ValueStream::<DefaultParameters, 2>::new();
+        stream.write_proof_with_pis(proof.clone());
+        let proof_back: ProofWithPublicInputs<GoldilocksField, C, 2> =
+            stream.read_proof_with_pis(&circuit.data.common);
+
+        assert_eq!(proof, proof_back);
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct ProofReadHint {
+        proof: ProofWithPublicInputs<GoldilocksField, C, 2>,
+    }
+
+    impl Hint<DefaultParameters, 2> for ProofReadHint {
+        fn hint(
+            &self,
+            _input_stream: &mut ValueStream<DefaultParameters, 2>,
+            output_stream: &mut ValueStream<DefaultParameters, 2>,
+        ) {
+            output_stream.write_proof_with_pis(self.proof.clone());
+        }
+    }
+
+    #[test]
+    fn test_output_variable_stream() {
+        let inner = inner_circuit();
+        let mut input = inner.input();
+        input.write::<Variable>(GoldilocksField::ONE);
+        input.write::<Variable>(GoldilocksField::TWO);
+        let (proof, _) = inner.prove(&input);
+
+        let mut builder = DefaultBuilder::new();
+        let output_stream = builder.hint(VariableStream::new(), ProofReadHint { proof });
+        let proof_variable = output_stream.read_proof_with_pis(&mut builder, &inner.data.common);
+        let verifier_data = builder.constant_verifier_data::<DefaultParameters>(&inner.data);
+        builder.verify_proof::<DefaultParameters>(
+            &proof_variable.into(),
+            &verifier_data,
+            &inner.data.common,
+        );
+        let circuit = builder.build();
+
+        let input = circuit.input();
+        let (proof, output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+    }
+}
@@ -0,0 +1,394 @@
+use array_macro::array;
+use num::BigUint;
+use plonky2::iop::target::BoolTarget;
+
+use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
+use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::{CircuitBuilderU32, U32Target};
+use crate::frontend::vars::EvmVariable;
+use crate::prelude::*;
+
+/// An unsigned integer of `LIMBS` u32 limbs, in little-endian order, for values wider than 256
+/// bits such as RSA moduli.
+#[derive(Debug, Clone, Copy)]
+pub struct BigUintVariable<const LIMBS: usize> {
+    pub limbs: [U32Variable; LIMBS],
+}
+
+impl<const LIMBS: usize> CircuitVariable for BigUintVariable<LIMBS> {
+    type ValueType<F: RichField> = BigUint;
+
+    fn init_unsafe<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        Self {
+            limbs: array![_ => U32Variable::init_unsafe(builder); LIMBS],
+        }
+    }
+
+    fn variables(&self) -> Vec<Variable> {
+        self.limbs.iter().map(|x| x.variable).collect()
+    }
+
+    fn from_variables_unsafe(variables: &[Variable]) -> Self {
+        assert_eq!(variables.len(), LIMBS);
+        Self {
+            limbs: array![i => U32Variable::from_variables_unsafe(&[variables[i]]); LIMBS],
+        }
+    }
+
+    fn assert_is_valid<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        for limb in self.limbs.iter() {
+            limb.assert_is_valid(builder);
+        }
+    }
+
+    fn nb_elements() -> usize {
+        LIMBS
+    }
+
+    fn elements<F: RichField>(value: BigUint) -> Vec<F> {
+        let mut limbs = value.to_u32_digits();
+        assert!(
+            limbs.len() <= LIMBS,
+            "the value doesn't fit in {} limbs",
+            LIMBS
+        );
+        limbs.resize(LIMBS, 0);
+        limbs
+            .into_iter()
+            .flat_map(U32Variable::elements::<F>)
+            .collect()
+    }
+
+    fn from_elements<F: RichField>(elements: &[F]) -> Self::ValueType<F> {
+        assert_eq!(elements.len(), LIMBS);
+        let limbs = elements
+            .iter()
+            .map(|x| U32Variable::from_elements::<F>(&[*x]))
+            .collect::<Vec<_>>();
+        BigUint::from_slice(&limbs)
+    }
+}
+
+impl<const LIMBS: usize> EvmVariable for BigUintVariable<LIMBS> {
+    fn encode<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        self.limbs
+            .iter()
+            .rev()
+            .flat_map(|x| x.encode(builder))
+            .collect()
+    }
+
+    fn decode<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        bytes: &[ByteVariable],
+    ) -> Self {
+        assert_eq!(bytes.len(), LIMBS * 4);
+        let mut limbs =
+            array![i => U32Variable::decode(builder, &bytes[i * 4..(i + 1) * 4]); LIMBS];
+        limbs.reverse();
+        Self { limbs }
+    }
+
+    fn encode_value<F: RichField>(value: Self::ValueType<F>) -> Vec<u8> {
+        let bytes = value.to_bytes_be();
+        assert!(
+            bytes.len() <= LIMBS * 4,
+            "the value doesn't fit in {} limbs",
+            LIMBS
+        );
+        let mut padded = vec![0u8; LIMBS * 4 - bytes.len()];
+        padded.extend(bytes);
+        padded
+    }
+
+    fn decode_value<F: RichField>(bytes: &[u8]) -> Self::ValueType<F> {
+        assert_eq!(bytes.len(), LIMBS * 4);
+        BigUint::from_bytes_be(bytes)
+    }
+}
+
+impl<const LIMBS: usize> BigUintVariable<LIMBS> {
+    pub(crate) fn to_biguint_target(self) -> BigUintTarget {
+        BigUintTarget {
+            limbs: self.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+        }
+    }
+
+    /// Takes the first `N` limbs of `target`, which must have at least `N` limbs.
+    fn from_biguint_target<const N: usize>(target: &BigUintTarget) -> BigUintVariable<N> {
+        BigUintVariable {
+            limbs: array![i => U32Variable::from(target.limbs[i]); N],
+        }
+    }
+
+    /// Returns `self + rhs` modulo `2^(32 * LIMBS)` and whether the addition overflowed.
+    pub fn overflowing_add<L: PlonkParameters<D>, const D: usize>(
+        self,
+        rhs: Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> (Self, BoolVariable) {
+        let sum = builder
+            .api
+            .add_biguint(&self.to_biguint_target(), &rhs.to_biguint_target());
+        // The carry out of the last limb is at most 1.
+        let carry = BoolTarget::new_unsafe(sum.limbs[LIMBS].target);
+        (Self::from_biguint_target(&sum), carry.into())
+    }
+
+    /// Returns `self - rhs` modulo `2^(32 * LIMBS)` and whether the subtraction borrowed, that is
+    /// whether `self < rhs`.
+    pub fn overflowing_sub<L: PlonkParameters<D>, const D: usize>(
+        self,
+        rhs: Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> (Self, BoolVariable) {
+        let mut borrow = builder.api.zero_u32();
+        let mut limbs = Vec::with_capacity(LIMBS);
+        for i in 0..LIMBS {
+            let (limb, new_borrow) =
+                builder
+                    .api
+                    .sub_u32(self.limbs[i].into(), rhs.limbs[i].into(), borrow);
+            limbs.push(limb);
+            borrow = new_borrow;
+        }
+        // The subtraction gate constrains the borrow to be a bit.
+        let borrow = BoolTarget::new_unsafe(borrow.target);
+        (
+            Self::from_biguint_target(&BigUintTarget { limbs }),
+            borrow.into(),
+        )
+    }
+
+    /// Returns the full product `self * rhs`, which has `OUT == 2 * LIMBS` limbs.
+    pub fn widening_mul<L: PlonkParameters<D>, const D: usize, const OUT: usize>(
+        self,
+        rhs: Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> BigUintVariable<OUT> {
+        assert_eq!(OUT, 2 * LIMBS, "the product has twice as many limbs");
+        // The last carry of the limb multiplication is always zero, as the product fits.
+        let product = builder
+            .api
+            .mul_biguint(&self.to_biguint_target(), &rhs.to_biguint_target());
+        Self::from_biguint_target(&product)
+    }
+
+    /// Converts a `U256Variable`, which must fit in `LIMBS` limbs.
+    pub fn from_u256<L: PlonkParameters<D>, const D: usize>(
+        value: U256Variable,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        assert!(LIMBS >= 8, "a U256Variable doesn't fit in {} limbs", LIMBS);
+        let zero = builder.zero::<U32Variable>();
+        Self {
+            limbs: array![i => if i < 8 { value.limbs[i] } else { zero }; LIMBS],
+        }
+    }
+
+    /// Converts to a `U256Variable`, asserting that the limbs above the 8th are zero.
+    pub fn to_u256<L: PlonkParameters<D>, const D: usize>(
+        self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> U256Variable {
+        let zero = builder.zero::<U32Variable>();
+        for limb in self.limbs.iter().skip(8) {
+            builder.assert_is_equal(*limb, zero);
+        }
+        U256Variable {
+            limbs: array![i => if i < LIMBS { self.limbs[i] } else { zero }; 8],
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize, const LIMBS: usize> Add<L, D>
+    for BigUintVariable<LIMBS>
+{
+    type Output = Self;
+
+    /// The sum modulo `2^(32 * LIMBS)`. Use `overflowing_add` to get the carry.
+    fn add(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self {
+        self.overflowing_add(rhs, builder).0
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize, const LIMBS: usize> Sub<L, D>
+    for BigUintVariable<LIMBS>
+{
+    type Output = Self;
+
+    /// The difference modulo `2^(32 * LIMBS)`. Use `overflowing_sub` to get the borrow.
+    fn sub(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self {
+        self.overflowing_sub(rhs, builder).0
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize, const LIMBS: usize> LessThanOrEqual<L, D>
+    for BigUintVariable<LIMBS>
+{
+    fn lte(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> BoolVariable {
+        builder
+            .api
+            .cmp_biguint(&self.to_biguint_target(), &rhs.to_biguint_target())
+            .into()
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize, const LIMBS: usize, const MODULUS_LIMBS: usize>
+    Rem<L, D, BigUintVariable<MODULUS_LIMBS>> for BigUintVariable<LIMBS>
+{
+    type Output = BigUintVariable<MODULUS_LIMBS>;
+
+    /// The quotient and the remainder are hinted, and constrained by `self == q * modulus + r` on
+    /// the full product with its carries, and by `r < modulus`. There is no valid assignment when
+    /// the modulus is zero.
+    fn rem(
+        self,
+        modulus: BigUintVariable<MODULUS_LIMBS>,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self::Output {
+        let (_, rem) = builder
+            .api
+            .div_rem_biguint(&self.to_biguint_target(), &modulus.to_biguint_target());
+        Self::from_biguint_target(&rem)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::{One, Zero};
+    use num_bigint::RandBigInt;
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::prelude::DefaultBuilder;
+
+    /// Checks the operations on `LIMBS` limbs against num-bigint, with `OUT == 2 * LIMBS`. The
+    /// product `a * b` is reduced modulo `n`.
+    fn check_biguint_ops<const LIMBS: usize, const OUT: usize>(
+        cases: &[(BigUint, BigUint, BigUint)],
+    ) {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<BigUintVariable<LIMBS>>();
+        let b = builder.read::<BigUintVariable<LIMBS>>();
+        let n = builder.read::<BigUintVariable<LIMBS>>();
+        let (sum, carry) = a.overflowing_add(b, &mut builder);
+        let (difference, borrow) = a.overflowing_sub(b, &mut builder);
+        let product: BigUintVariable<OUT> = a.widening_mul(b, &mut builder);
+        let lte = builder.lte(a, b);
+        let rem = builder.rem(product, n);
+        builder.write(sum);
+        builder.write(carry);
+        builder.write(difference);
+        builder.write(borrow);
+        builder.write(product);
+        builder.write(lte);
+        builder.write(rem);
+        let circuit = builder.mock_build();
+
+        let modulus = BigUint::one() << (32 * LIMBS);
+        for (a, b, n) in cases {
+            let mut input = circuit.input();
+            input.write::<BigUintVariable<LIMBS>>(a.clone());
+            input.write::<BigUintVariable<LIMBS>>(b.clone());
+            input.write::<BigUintVariable<LIMBS>>(n.clone());
+            let mut output = circuit.mock_prove(&input).output;
+
+            let sum = a + b;
+            assert_eq!(output.read::<BigUintVariable<LIMBS>>(), &sum % &modulus);
+            assert_eq!(output.read::<BoolVariable>(), sum >= modulus);
+            assert_eq!(
+                output.read::<BigUintVariable<LIMBS>>(),
+                (a + &modulus - b) % &modulus
+            );
+            assert_eq!(output.read::<BoolVariable>(), a < b);
+            assert_eq!(output.read::<BigUintVariable<OUT>>(), a * b);
+            assert_eq!(output.read::<BoolVariable>(), a <= b);
+            assert_eq!(output.read::<BigUintVariable<LIMBS>>(), (a * b) % n);
+        }
+    }
+
+    /// Random values of `LIMBS` limbs and the values around the carries between limbs, with
+    /// random nonzero moduli.
+    fn cases<const LIMBS: usize>() -> Vec<(BigUint, BigUint, BigUint)> {
+        let bits = 32 * LIMBS as u64;
+        let max = (BigUint::one() << bits) - 1u32;
+        let limb_max = BigUint::from(u32::MAX);
+        let mut rng = OsRng;
+        let mut cases = vec![
+            // The carry and the borrow out of the last limb.
+            (max.clone(), BigUint::one()),
+            (BigUint::one(), max.clone()),
+            (max.clone(), max.clone()),
+            (BigUint::zero(), BigUint::zero()),
+            // A carry and a borrow across every limb.
+            (&max >> 32u32, BigUint::one()),
+            (BigUint::one() << (bits - 32), BigUint::one()),
+            // The carry between the first two limbs only.
+            (limb_max.clone(), BigUint::one()),
+            (BigUint::one() << 32u32, limb_max),
+        ];
+        for _ in 0..4 {
+            cases.push((rng.gen_biguint(bits), rng.gen_biguint(bits)));
+        }
+        let mut cases = cases
+            .into_iter()
+            .map(|(a, b)| (a, b, rng.gen_biguint(bits) | BigUint::one()))
+            .collect::<Vec<_>>();
+        // The largest modulus, and a modulus much smaller than the product.
+        cases.push((max.clone(), max.clone(), max.clone()));
+        cases.push((
+            rng.gen_biguint(bits),
+            rng.gen_biguint(bits),
+            rng.gen_biguint(40) | BigUint::one(),
+        ));
+        cases
+    }
+
+    #[test]
+    fn test_biguint_512() {
+        check_biguint_ops::<16, 32>(&cases::<16>());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_biguint_2048() {
+        check_biguint_ops::<64, 128>(&cases::<64>());
+    }
+
+    #[test]
+    fn test_biguint_conversions() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U256Variable>();
+        let wide = BigUintVariable::<16>::from_u256(a, &mut builder);
+        let bytes = wide.encode(&mut builder);
+        let decoded = BigUintVariable::<16>::decode(&mut builder, &bytes);
+        let narrow = decoded.to_u256(&mut builder);
+        builder.write(wide);
+        builder.write(narrow);
+        let circuit = builder.mock_build();
+
+        let value = U256::MAX - 12345;
+        let mut input = circuit.input();
+        input.write::<U256Variable>(value);
+        let mut output = circuit.mock_prove(&input).output;
+        let mut bytes = [0u8; 32];
+        value.to_big_endian(&mut bytes);
+        assert_eq!(
+            output.read::<BigUintVariable<16>>(),
+            BigUint::from_bytes_be(&bytes)
+        );
+        assert_eq!(output.read::<U256Variable>(), value);
+        assert_eq!(
+            BigUintVariable::<16>::encode_value::<GoldilocksField>(BigUint::from_bytes_be(&bytes)),
+            [vec![0u8; 32], bytes.to_vec()].concat()
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+
+use super::uint64::U64Variable;
+use crate::frontend::vars::EvmVariable;
+use crate::prelude::{
+    Add, BoolVariable, ByteVariable, CircuitBuilder, CircuitVariable, LessThanOrEqual, Neg, One,
+    PlonkParameters, Sub, U32Variable, Variable, Zero,
+};
+
+/// A signed 64-bit integer, stored as the two's complement of its value in a `U64Variable`.
+///
+/// Arithmetic follows `i64::wrapping_*` semantics. Use `checked_add` to detect overflows.
+#[derive(Debug, Clone, Copy)]
+pub struct I64Variable {
+    pub value: U64Variable,
+}
+
+impl CircuitVariable for I64Variable {
+    type ValueType<F: RichField> = i64;
+
+    fn init_unsafe<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        Self {
+            value: U64Variable::init_unsafe(builder),
+        }
+    }
+
+    fn variables(&self) -> Vec<Variable> {
+        self.value.variables()
+    }
+
+    fn from_variables_unsafe(variables: &[Variable]) -> Self {
+        Self {
+            value: U64Variable::from_variables_unsafe(variables),
+        }
+    }
+
+    fn assert_is_valid<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        self.value.assert_is_valid(builder)
+    }
+
+    fn nb_elements() -> usize {
+        U64Variable::nb_elements()
+    }
+
+    fn elements<F: RichField>(value: Self::ValueType<F>) -> Vec<F> {
+        U64Variable::elements::<F>(value as u64)
+    }
+
+    fn from_elements<F: RichField>(elements: &[F]) -> Self::ValueType<F> {
+        U64Variable::from_elements::<F>(elements) as i64
+    }
+}
+
+impl EvmVariable for I64Variable {
+    fn encode<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        self.value.encode(builder)
+    }
+
+    fn decode<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        bytes: &[ByteVariable],
+    ) -> Self {
+        Self {
+            value: U64Variable::decode(builder, bytes),
+        }
+    }
+
+    fn encode_value<F: RichField>(value: Self::ValueType<F>) -> Vec<u8> {
+        value.to_be_bytes().to_vec()
+    }
+
+    fn decode_value<F: RichField>(bytes: &[u8]) -> Self::ValueType<F> {
+        i64::from_be_bytes(bytes.try_into().expect("expected 8 bytes"))
+    }
+}
+
+impl I64Variable {
+    /// Returns whether the value is negative, i.e. whether its most significant bit is set.
+    pub fn is_negative<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> BoolVariable {
+        let bits = builder.api.split_le(self.value.limbs[1].variable.0, 32);
+        bits[31].into()
+    }
+
+    /// Returns the absolute value. Like `i64::wrapping_abs`, the absolute value of `i64::MIN` is
+    /// `i64::MIN`.
+    pub fn abs<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        let is_negative = self.is_negative(builder);
+        let negated = builder.neg(*self);
+        builder.select(is_negative, negated, *self)
+    }
+
+    /// Returns the wrapping sum along with whether the addition overflowed.
+    pub fn checked_add<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        rhs: Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> (Self, BoolVariable) {
+        let sum = builder.add(*self, rhs);
+
+        // An overflow happens exactly when both operands have the same sign and the sum does not.
+        let lhs_is_negative = self.is_negative(builder);
+        let rhs_is_negative = rhs.is_negative(builder);
+        let sum_is_negative = sum.is_negative(builder);
+        let same_sign = builder.is_equal(lhs_is_negative, rhs_is_negative);
+        let sign_changed = builder.is_equal(lhs_is_negative, sum_is_negative);
+        let sign_changed = builder.not(sign_changed);
+        let overflow = builder.and(same_sign, sign_changed);
+
+        (sum, overflow)
+    }
+
+    /// Maps the value to an unsigned integer with the same ordering by flipping the sign bit.
+    fn to_biased<L: PlonkParameters<D>, const D: usize>(
+        self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> U64Variable {
+        let is_negative = self.is_negative(builder);
+        let high = self.value.limbs[1].variable;
+        let sign_bit = builder.constant::<Variable>(L::Field::from_canonical_u64(1 << 31));
+        let high_with_sign_bit = builder.add(high, sign_bit);
+        let high_without_sign_bit = builder.sub(high, sign_bit);
+        let high = builder.select(is_negative, high_without_sign_bit, high_with_sign_bit);
+        U64Variable {
+            limbs: [
+                self.value.limbs[0],
+                U32Variable::from_variables_unsafe(&[high]),
+            ],
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Add<L, D> for I64Variable {
+    type Output = Self;
+
+    fn add(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        Self {
+            value: builder.add(self.value, rhs.value),
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Sub<L, D> for I64Variable {
+    type Output = Self;
+
+    fn sub(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        Self {
+            value: builder.sub(self.value, rhs.value),
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Neg<L, D> for I64Variable {
+    type Output = Self;
+
+    fn neg(self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        let zero = builder.zero::<Self>();
+        builder.sub(zero, self)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Zero<L, D> for I64Variable {
+    fn zero(builder: &mut CircuitBuilder<L, D>) -> Self {
+        Self {
+            value: builder.zero(),
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> One<L, D> for I64Variable {
+    fn one(builder: &mut CircuitBuilder<L, D>) -> Self {
+        Self {
+            value: builder.one(),
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> LessThanOrEqual<L, D> for I64Variable {
+    fn lte(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> BoolVariable {
+        let lhs = self.to_biased(builder);
+        let rhs = rhs.to_biased(builder);
+        builder.lte(lhs, rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{DefaultBuilder, GoldilocksField};
+
+    #[test]
+    fn test_i64_arithmetic() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<I64Variable>();
+        let b = builder.read::<I64Variable>();
+        let sum = builder.add(a, b);
+        let diff = builder.sub(a, b);
+        let neg = builder.neg(a);
+        let abs = a.abs(&mut builder);
+        let is_negative = a.is_negative(&mut builder);
+        let (checked_sum, overflow) = a.checked_add(b, &mut builder);
+        builder.write(sum);
+        builder.write(diff);
+        builder.write(neg);
+        builder.write(abs);
+        builder.write(is_negative);
+        builder.write(checked_sum);
+        builder.write(overflow);
+        let circuit = builder.build();
+
+        let cases = [
+            (-5, 3),
+            (7, -12),
+            (i64::MIN, -1),
+            (i64::MIN, 1),
+            (i64::MAX, 1),
+            (-1, -1),
+            (0, i64::MIN),
+        ];
+        for (a, b) in cases {
+            let mut input = circuit.input();
+            input.write::<I64Variable>(a);
+            input.write::<I64Variable>(b);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<I64Variable>(), a.wrapping_add(b));
+            assert_eq!(output.read::<I64Variable>(), a.wrapping_sub(b));
+            assert_eq!(output.read::<I64Variable>(), a.wrapping_neg());
+            assert_eq!(output.read::<I64Variable>(), a.wrapping_abs());
+            assert_eq!(output.read::<BoolVariable>(), a < 0);
+            let (expected_sum, expected_overflow) = a.overflowing_add(b);
+            assert_eq!(output.read::<I64Variable>(), expected_sum);
+            assert_eq!(output.read::<BoolVariable>(), expected_overflow);
+        }
+    }
+
+    #[test]
+    fn test_i64_comparison() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<I64Variable>();
+        let b = builder.read::<I64Variable>();
+        let lt = builder.lt(a, b);
+        let lte = builder.lte(a, b);
+        let gt = builder.gt(a, b);
+        let gte = builder.gte(a, b);
+        builder.write(lt);
+        builder.write(lte);
+        builder.write(gt);
+        builder.write(gte);
+        let circuit = builder.build();
+
+        let cases = [
+            (-1, 1),
+            (1, -1),
+            (i64::MIN, i64::MAX),
+            (i64::MAX, i64::MIN),
+            (-3, -3),
+            (-4, -3),
+            (0, -1),
+        ];
+        for (a, b) in cases {
+            let mut input = circuit.input();
+            input.write::<I64Variable>(a);
+            input.write::<I64Variable>(b);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<BoolVariable>(), a < b);
+            assert_eq!(output.read::<BoolVariable>(), a <= b);
+            assert_eq!(output.read::<BoolVariable>(), a > b);
+            assert_eq!(output.read::<BoolVariable>(), a >= b);
+        }
+    }
+
+    #[test]
+    fn test_i64_evm_io() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.evm_read::<I64Variable>();
+        let neg = builder.neg(a);
+        builder.evm_write(neg);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.evm_write::<I64Variable>(-2);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.evm_read::<I64Variable>(), 2);
+
+        let bytes = I64Variable::encode_value::<GoldilocksField>(-2);
+        assert_eq!(bytes, vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe]);
+    }
+}
@@ -1,5 +1,7 @@
 use core::fmt::Debug;
 
+pub mod biguint;
+pub mod int64;
 pub mod uint128;
 pub mod uint256;
 pub mod uint32;
@@ -103,6 +103,16 @@ pub trait CircuitBuilderBiguint<F: RichField + Extendable<D>, const D: usize> {
         div_num_limbs: usize,
     ) -> (BigUintTarget, BigUintTarget);
 
+    /// Asserts that `div` and `rem` are the quotient and the remainder of `a` divided by `b`,
+    /// that is `a == div * b + rem` and `rem < b`, with the product computed on all its limbs.
+    fn assert_div_rem_biguint(
+        &mut self,
+        a: &BigUintTarget,
+        b: &BigUintTarget,
+        div: &BigUintTarget,
+        rem: &BigUintTarget,
+    );
+
     fn div_rem_biguint(
         &mut self,
         a: &BigUintTarget,
@@ -303,18 +313,28 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderBiguint<F, D>
             _phantom: PhantomData,
         });
 
+        self.assert_div_rem_biguint(a, b, &div, &rem);
+
+        (div, rem)
+    }
+
+    fn assert_div_rem_biguint(
+        &mut self,
+        a: &BigUintTarget,
+        b: &BigUintTarget,
+        div: &BigUintTarget,
+        rem: &BigUintTarget,
+    ) {
         range_check_u32_circuit(self, div.limbs.clone());
         range_check_u32_circuit(self, rem.limbs.clone());
 
-        let div_b = self.mul_biguint(&div, b);
-        let div_b_plus_rem = self.add_biguint(&div_b, &rem);
+        let div_b = self.mul_biguint(div, b);
+        let div_b_plus_rem = self.add_biguint(&div_b, rem);
         self.connect_biguint(a, &div_b_plus_rem);
 
         // Assert that `r < b`. We do that by asserting that the result of `b \leq r` is `false`.
-        let cmp_b_rem = self.cmp_biguint(b, &rem);
+        let cmp_b_rem = self.cmp_biguint(b, rem);
         self.assert_zero(cmp_b_rem.target);
-
-        (div, rem)
     }
 
     fn div_rem_biguint(
@@ -481,7 +501,13 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
     fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
         let a = witness.get_biguint_target(self.a.clone());
         let b = witness.get_biguint_target(self.b.clone());
-        let (div, rem) = a.div_rem(&b);
+        // There is no quotient for a zero divisor, and no remainder can satisfy `rem < b`, so any
+        // values make the constraints unsatisfiable.
+        let (div, rem) = if b.is_zero() {
+            (BigUint::zero(), BigUint::zero())
+        } else {
+            a.div_rem(&b)
+        };
 
         out_buffer.set_biguint_target(&self.div, &div);
         out_buffer.set_biguint_target(&self.rem, &rem);
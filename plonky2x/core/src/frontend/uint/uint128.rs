@@ -1,32 +1,33 @@
 use array_macro::array;
-use ethers::types::U128;
 use plonky2::hash::hash_types::RichField;
 
+use super::uint64::U64Variable;
 use super::Uint;
 use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
 use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::U32Target;
 use crate::frontend::vars::{EvmVariable, SSZVariable, U256Variable, U32Variable};
 use crate::prelude::{
-    Add, BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitBuilder,
-    CircuitVariable, Div, LessThanOrEqual, Mul, One, PlonkParameters, Rem, Sub, Variable, Zero,
+    Add, BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CheckedArithmetic,
+    CircuitBuilder, CircuitVariable, Div, DivRem, LessThanOrEqual, Mul, One, PlonkParameters, Rem,
+    Sub, Variable, Zero,
 };
 use crate::{make_uint32_n, make_uint32_n_tests};
 
-impl Uint<4> for U128 {
+impl Uint<4> for u128 {
     fn to_little_endian(&self, bytes: &mut [u8]) {
-        self.to_little_endian(bytes);
+        bytes.copy_from_slice(&self.to_le_bytes());
     }
 
     fn from_little_endian(slice: &[u8]) -> Self {
-        Self::from_little_endian(slice)
+        u128::from_le_bytes(slice.try_into().unwrap())
     }
 
     fn to_big_endian(&self, bytes: &mut [u8]) {
-        self.to_big_endian(bytes);
+        bytes.copy_from_slice(&self.to_be_bytes());
     }
 
     fn from_big_endian(slice: &[u8]) -> Self {
-        Self::from_big_endian(slice)
+        u128::from_be_bytes(slice.try_into().unwrap())
     }
 
     fn overflowing_add(self, rhs: Self) -> (Self, bool) {
@@ -42,10 +43,72 @@ impl Uint<4> for U128 {
     }
 }
 
-make_uint32_n!(U128Variable, U128, 4);
-make_uint32_n_tests!(U128Variable, U128, 4);
+make_uint32_n!(U128Variable, u128, 4);
+make_uint32_n_tests!(U128Variable, u128, 4);
 
+/// Arithmetic on `U128Variable` follows `u128::wrapping_*` semantics. Use `mul_wide` to get the
+/// full 256-bit product.
 impl U128Variable {
+    /// Widens a U64Variable.
+    pub fn from_u64<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        value: U64Variable,
+    ) -> Self {
+        let zero = builder.zero::<U32Variable>();
+        Self {
+            limbs: [value.limbs[0], value.limbs[1], zero, zero],
+        }
+    }
+
+    /// Converts to a U64Variable, asserting that the value fits in 64 bits.
+    pub fn to_u64<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> U64Variable {
+        let zero = builder.zero::<U32Variable>();
+        builder.assert_is_equal(self.limbs[2], zero);
+        builder.assert_is_equal(self.limbs[3], zero);
+        U64Variable {
+            limbs: [self.limbs[0], self.limbs[1]],
+        }
+    }
+
+    /// Converts a U256Variable, asserting that the value fits in 128 bits.
+    pub fn from_u256<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        value: U256Variable,
+    ) -> Self {
+        let zero = builder.zero::<U32Variable>();
+        for limb in value.limbs[4..].iter() {
+            builder.assert_is_equal(*limb, zero);
+        }
+        Self {
+            limbs: array![i => value.limbs[i]; 4],
+        }
+    }
+
+    /// Returns the full product as its low and high halves.
+    pub fn mul_wide<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        rhs: Self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> (Self, Self) {
+        let lhs = BigUintTarget {
+            limbs: self.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+        };
+        let rhs = BigUintTarget {
+            limbs: rhs.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+        };
+        let product = builder.api.mul_biguint(&lhs, &rhs);
+        let low = Self {
+            limbs: array![i => product.limbs[i].into(); 4],
+        };
+        let high = Self {
+            limbs: array![i => product.limbs[i + 4].into(); 4],
+        };
+        (low, high)
+    }
+
     pub fn to_u256<L: PlonkParameters<D>, const D: usize>(
         &self,
         builder: &mut CircuitBuilder<L, D>,
@@ -62,3 +125,96 @@ impl U128Variable {
         result
     }
 }
+
+#[cfg(test)]
+mod u128_tests {
+    use ethers::types::U256;
+
+    use super::*;
+    use crate::prelude::DefaultBuilder;
+
+    #[test]
+    fn test_u128_arithmetic() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U128Variable>();
+        let b = builder.read::<U128Variable>();
+        let sum = builder.add(a, b);
+        let diff = builder.sub(a, b);
+        let product = builder.mul(a, b);
+        let (low, high) = a.mul_wide(b, &mut builder);
+        let lt = builder.lt(a, b);
+        let gte = builder.gte(a, b);
+        builder.write(sum);
+        builder.write(diff);
+        builder.write(product);
+        builder.write(low);
+        builder.write(high);
+        builder.write(lt);
+        builder.write(gte);
+        let circuit = builder.build();
+
+        let cases = [
+            (u128::MAX, 1),
+            (u128::MAX, u128::MAX),
+            (0, 1),
+            (u32::MAX as u128, 1),
+            (u64::MAX as u128, u64::MAX as u128),
+            (1 << 96, 1 << 32),
+        ];
+        for (a, b) in cases {
+            let mut input = circuit.input();
+            input.write::<U128Variable>(a);
+            input.write::<U128Variable>(b);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<U128Variable>(), a.wrapping_add(b));
+            assert_eq!(output.read::<U128Variable>(), a.wrapping_sub(b));
+            assert_eq!(output.read::<U128Variable>(), a.wrapping_mul(b));
+            let expected = U256::from(a) * U256::from(b);
+            assert_eq!(output.read::<U128Variable>(), expected.low_u128());
+            assert_eq!(output.read::<U128Variable>(), (expected >> 128).low_u128());
+            assert_eq!(output.read::<BoolVariable>(), a < b);
+            assert_eq!(output.read::<BoolVariable>(), a >= b);
+        }
+    }
+
+    #[test]
+    fn test_u128_conversions() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U128Variable>();
+        let small = a.to_u64(&mut builder);
+        let widened = U128Variable::from_u64(&mut builder, small);
+        builder.assert_is_equal(widened, a);
+        let wide = a.to_u256(&mut builder);
+        let narrowed = U128Variable::from_u256(&mut builder, wide);
+        builder.assert_is_equal(narrowed, a);
+        builder.write(small);
+        builder.write(wide);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<U128Variable>(u64::MAX as u128);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<U64Variable>(), u64::MAX);
+        assert_eq!(output.read::<U256Variable>(), U256::from(u64::MAX));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_u128_to_u64_overflow() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.constant::<U128Variable>(1 << 64);
+        a.to_u64(&mut builder);
+        let circuit = builder.build();
+        let input = circuit.input();
+        circuit.prove(&input);
+    }
+
+    #[test]
+    fn test_u128_evm_value() {
+        let value = 0x0102030405060708090a0b0c0d0e0f10u128;
+        let bytes = U128Variable::encode_value::<crate::prelude::GoldilocksField>(value);
+        assert_eq!(bytes, (1..=16).collect::<Vec<u8>>());
+    }
+}
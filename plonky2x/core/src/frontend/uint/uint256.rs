@@ -1,5 +1,6 @@
 use array_macro::array;
 use ethers::types::U256;
+use num::BigUint;
 use plonky2::hash::hash_types::RichField;
 
 use super::Uint;
@@ -7,8 +8,9 @@ use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
 use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::U32Target;
 use crate::frontend::vars::{EvmVariable, SSZVariable, U32Variable};
 use crate::prelude::{
-    Add, BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitBuilder,
-    CircuitVariable, Div, LessThanOrEqual, Mul, One, PlonkParameters, Rem, Sub, Variable, Zero,
+    Add, ArrayVariable, BoolVariable, ByteVariable, Bytes32Variable, BytesVariable,
+    CheckedArithmetic, CircuitBuilder, CircuitVariable, Div, DivRem, LessThanOrEqual, Mul, One,
+    PlonkParameters, Rem, Sub, Variable, Zero,
 };
 use crate::{make_uint32_n, make_uint32_n_tests};
 
@@ -44,3 +46,544 @@ impl Uint<8> for U256 {
 
 make_uint32_n!(U256Variable, U256, 8);
 make_uint32_n_tests!(U256Variable, U256, 8);
+
+impl U256Variable {
+    /// Returns whether the value is zero.
+    pub fn is_zero<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> BoolVariable {
+        self.all_limbs_equal(builder, 0)
+    }
+
+    /// Returns whether the value is 2^256 - 1.
+    pub fn is_max<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> BoolVariable {
+        self.all_limbs_equal(builder, u32::MAX)
+    }
+
+    fn all_limbs_equal<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        value: u32,
+    ) -> BoolVariable {
+        let constant = builder.constant::<U32Variable>(value);
+        let mut result = builder._true();
+        for limb in self.limbs {
+            let is_equal = builder.is_equal(limb, constant);
+            result = builder.and(result, is_equal);
+        }
+        result
+    }
+
+    /// Compares against a constant limb-wise from the most significant limb down, like the
+    /// comparison of two variables. Limbs of the constant that are 0 or `u32::MAX` only need an
+    /// equality check, which is the common case for small thresholds.
+    fn lte_constant<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        constant: U256,
+        variable_on_left: bool,
+    ) -> BoolVariable {
+        let _true = builder._true();
+        let mut lte_acc = builder._false();
+        let mut equal_so_far = _true;
+        for i in (0..self.limbs.len()).rev() {
+            let limb = self.limbs[i];
+            let constant_limb = (constant >> (32 * i)).low_u32();
+            let constant_variable = builder.constant::<U32Variable>(constant_limb);
+            let equal = builder.is_equal(limb, constant_variable);
+            let lte = match (variable_on_left, constant_limb) {
+                (true, u32::MAX) | (false, 0) => _true,
+                (true, 0) | (false, u32::MAX) => equal,
+                (true, _) => builder.lte(limb, constant_variable),
+                (false, _) => builder.lte(constant_variable, limb),
+            };
+            lte_acc = builder.select(equal_so_far, lte, lte_acc);
+            equal_so_far = builder.and(equal_so_far, equal);
+        }
+        builder.or(lte_acc, equal_so_far)
+    }
+
+    /// Divides by a nonzero constant. The divisor only has its significant limbs, so the product
+    /// of the quotient and the divisor is cheaper than with a variable divisor.
+    fn div_rem_constant<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        divisor: U256,
+    ) -> (Self, Self) {
+        assert!(!divisor.is_zero(), "division by the constant zero");
+        let mut bytes = [0u8; 32];
+        divisor.to_little_endian(&mut bytes);
+        let divisor_biguint = builder
+            .api
+            .constant_biguint(&BigUint::from_bytes_le(&bytes));
+        let self_biguint = self.to_biguint_target();
+
+        // The top limb of the divisor is nonzero, so the quotient fits in the limbs of the
+        // dividend above the divisor, and the unsafe variant is sound here.
+        let (quotient_biguint, rem_biguint) = builder
+            .api
+            .div_rem_biguint_unsafe(&self_biguint, &divisor_biguint);
+        let zero = builder.zero::<U32Variable>();
+        let limb = |limbs: &[U32Target], i: usize| limbs.get(i).map_or(zero, |&x| x.into());
+        let quotient = array![i => limb(&quotient_biguint.limbs, i); 8];
+        let rem = array![i => limb(&rem_biguint.limbs, i); 8];
+        (Self { limbs: quotient }, Self { limbs: rem })
+    }
+
+    fn to_biguint_target(self) -> BigUintTarget {
+        BigUintTarget {
+            limbs: self.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+        }
+    }
+}
+
+/// Returns the 32 bytes of `value` from the least significant, as in SSZ.
+pub fn u256_to_le_bytes(value: U256) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    value.to_little_endian(&mut bytes);
+    bytes
+}
+
+/// Returns the value of 32 bytes ordered from the least significant, as in SSZ.
+pub fn u256_from_le_bytes(bytes: [u8; 32]) -> U256 {
+    U256::from_little_endian(&bytes)
+}
+
+/// Returns the 32 bytes of `value` from the most significant, as in the EVM.
+pub fn u256_to_be_bytes(value: U256) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    bytes
+}
+
+/// Returns the value of 32 bytes ordered from the most significant, as in the EVM.
+pub fn u256_from_be_bytes(bytes: [u8; 32]) -> U256 {
+    U256::from_big_endian(&bytes)
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns the bytes of `value` from the least significant, as in SSZ.
+    pub fn u256_to_le_bytes(&mut self, value: U256Variable) -> BytesVariable<32> {
+        let mut bytes = value.encode(self);
+        bytes.reverse();
+        BytesVariable(bytes.try_into().unwrap())
+    }
+
+    /// Returns the value of bytes ordered from the least significant, as in SSZ.
+    pub fn u256_from_le_bytes(&mut self, bytes: BytesVariable<32>) -> U256Variable {
+        let mut bytes = bytes.0;
+        bytes.reverse();
+        U256Variable::decode(self, &bytes)
+    }
+
+    /// Returns the bytes of `value` from the most significant, as in the EVM.
+    pub fn u256_to_be_bytes(&mut self, value: U256Variable) -> BytesVariable<32> {
+        BytesVariable(value.encode(self).try_into().unwrap())
+    }
+
+    /// Returns the value of bytes ordered from the most significant, as in the EVM. A keccak256
+    /// hash is read as an integer this way.
+    pub fn u256_from_be_bytes(&mut self, bytes: BytesVariable<32>) -> U256Variable {
+        U256Variable::decode(self, &bytes.0)
+    }
+
+    /// Returns the u32 limbs of `value`, from the least significant: the limb `i` holds the bits
+    /// `32 * i` to `32 * i + 31`.
+    pub fn u256_limbs(&mut self, value: U256Variable) -> ArrayVariable<U32Variable, 8> {
+        ArrayVariable::new(value.limbs.to_vec())
+    }
+
+    /// Returns `(a * b) % n` without overflow, like the `MULMOD` opcode. As in the EVM, the result
+    /// is 0 when `n` is 0.
+    pub fn mulmod(&mut self, a: U256Variable, b: U256Variable, n: U256Variable) -> U256Variable {
+        // The 512-bit product is computed with limb multiplications, whose carries are range
+        // checked.
+        let product = self
+            .api
+            .mul_biguint(&a.to_biguint_target(), &b.to_biguint_target());
+        self.reduce_biguint(&product, n)
+    }
+
+    /// Returns `(a + b) % n` without overflow, like the `ADDMOD` opcode. As in the EVM, the result
+    /// is 0 when `n` is 0.
+    pub fn addmod(&mut self, a: U256Variable, b: U256Variable, n: U256Variable) -> U256Variable {
+        let sum = self
+            .api
+            .add_biguint(&a.to_biguint_target(), &b.to_biguint_target());
+        self.reduce_biguint(&sum, n)
+    }
+
+    /// Returns `value % n`, or 0 if `n` is 0. The quotient is hinted and constrained by
+    /// `value == q * n + r` and `r < n`.
+    fn reduce_biguint(&mut self, value: &BigUintTarget, n: U256Variable) -> U256Variable {
+        // Reducing modulo 1 instead of 0 gives the zero result of the EVM.
+        let is_zero = n.is_zero(self);
+        let one = self.one::<U256Variable>();
+        let modulus = self.select(is_zero, one, n);
+
+        let (_, rem) = self
+            .api
+            .div_rem_biguint(value, &modulus.to_biguint_target());
+        U256Variable {
+            limbs: array![i => U32Variable::from(rem.limbs[i]); 8],
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> LessThanOrEqual<L, D, U256> for U256Variable {
+    fn lte(self, rhs: U256, builder: &mut CircuitBuilder<L, D>) -> BoolVariable {
+        self.lte_constant(builder, rhs, true)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> LessThanOrEqual<L, D, U256Variable> for U256 {
+    fn lte(self, rhs: U256Variable, builder: &mut CircuitBuilder<L, D>) -> BoolVariable {
+        rhs.lte_constant(builder, self, false)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> DivRem<L, D, U256> for U256Variable {
+    type Output = Self;
+
+    fn div_rem(self, rhs: U256, builder: &mut CircuitBuilder<L, D>) -> (Self, Self) {
+        self.div_rem_constant(builder, rhs)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Div<L, D, U256> for U256Variable {
+    type Output = Self;
+
+    fn div(self, rhs: U256, builder: &mut CircuitBuilder<L, D>) -> Self {
+        self.div_rem_constant(builder, rhs).0
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Rem<L, D, U256> for U256Variable {
+    type Output = Self;
+
+    fn rem(self, rhs: U256, builder: &mut CircuitBuilder<L, D>) -> Self {
+        self.div_rem_constant(builder, rhs).1
+    }
+}
+
+#[cfg(test)]
+mod u256_tests {
+    use ethers::types::U512;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::prelude::DefaultBuilder;
+
+    #[test]
+    fn test_u256_comparisons() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U256Variable>();
+        let b = builder.read::<U256Variable>();
+        let constant = U256::from(10).pow(U256::from(18)) + 1;
+        let comparisons = [
+            builder.lt(a, b),
+            builder.lte(a, b),
+            builder.gt(a, b),
+            builder.gte(a, b),
+            builder.lt(a, constant),
+            builder.lte(a, constant),
+            builder.gt(a, constant),
+            builder.gte(a, constant),
+            a.is_zero(&mut builder),
+            a.is_max(&mut builder),
+        ];
+        for comparison in comparisons {
+            builder.write(comparison);
+        }
+        let circuit = builder.build();
+
+        let x = U256::from_big_endian(&[0x5a; 32]);
+        let cases = [
+            // Differing only in the least significant limb.
+            (x, x + 1),
+            (x + 1, x),
+            // Differing only in the most significant limb.
+            (x, x + (U256::one() << 224)),
+            (x + (U256::one() << 224), x),
+            // Equal values.
+            (x, x),
+            (U256::zero(), U256::zero()),
+            (U256::MAX, U256::MAX),
+            // Around the constant, which has zero limbs above its second.
+            (constant - 1, constant),
+            (constant, constant),
+            (constant + (U256::one() << 32), constant),
+        ];
+        for (a, b) in cases {
+            let mut input = circuit.input();
+            input.write::<U256Variable>(a);
+            input.write::<U256Variable>(b);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            let expected = [
+                a < b,
+                a <= b,
+                a > b,
+                a >= b,
+                a < constant,
+                a <= constant,
+                a > constant,
+                a >= constant,
+                a.is_zero(),
+                a == U256::MAX,
+            ];
+            for expected in expected {
+                assert_eq!(output.read::<BoolVariable>(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_u256_div_rem() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U256Variable>();
+        let b = builder.read::<U256Variable>();
+        let constant = U256::from(10).pow(U256::from(18));
+        let (quotient, rem) = builder.div_rem(a, b);
+        let div = builder.div(a, b);
+        let rem_only = builder.rem(a, b);
+        let (constant_quotient, constant_rem) = builder.div_rem(a, constant);
+        let constant_div = builder.div(a, U256::one());
+        for result in [
+            quotient,
+            rem,
+            div,
+            rem_only,
+            constant_quotient,
+            constant_rem,
+            constant_div,
+        ] {
+            builder.write(result);
+        }
+        let circuit = builder.mock_build();
+
+        let x = U256::from_big_endian(&[0x5a; 32]);
+        let cases = [
+            // A dividend smaller than the divisor.
+            (U256::from(7), U256::from(9)),
+            (constant - 1, x),
+            // Equal values.
+            (x, x),
+            (U256::MAX, U256::MAX),
+            // Division by one.
+            (x, U256::one()),
+            (U256::MAX, U256::one()),
+            // Values with the high limbs set.
+            (U256::MAX, (U256::one() << 200) + 12345),
+            (U256::MAX - 1, U256::MAX >> 1),
+            (x, U256::from(u32::MAX) << 224),
+            (U256::zero(), x),
+        ];
+        for (a, b) in cases {
+            let mut input = circuit.input();
+            input.write::<U256Variable>(a);
+            input.write::<U256Variable>(b);
+            let mut output = circuit.mock_prove(&input).output;
+            let (expected_quotient, expected_rem) = a.div_mod(b);
+            let (expected_constant_quotient, expected_constant_rem) = a.div_mod(constant);
+            for expected in [
+                expected_quotient,
+                expected_rem,
+                expected_quotient,
+                expected_rem,
+                expected_constant_quotient,
+                expected_constant_rem,
+                a,
+            ] {
+                assert_eq!(output.read::<U256Variable>(), expected);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_u256_div_by_zero() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U256Variable>();
+        let b = builder.read::<U256Variable>();
+        let quotient = builder.div(a, b);
+        builder.write(quotient);
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.write::<U256Variable>(U256::from(42));
+        input.write::<U256Variable>(U256::zero());
+        circuit.mock_prove(&input);
+    }
+
+    #[test]
+    fn test_u256_byte_conversions() {
+        let mut builder = DefaultBuilder::new();
+        let value = builder.read::<U256Variable>();
+        let le_bytes = builder.u256_to_le_bytes(value);
+        let be_bytes = builder.u256_to_be_bytes(value);
+        let from_le = builder.u256_from_le_bytes(le_bytes);
+        let from_be = builder.u256_from_be_bytes(be_bytes);
+        let limbs = builder.u256_limbs(value);
+        builder.assert_is_equal(from_le, value);
+        builder.assert_is_equal(from_be, value);
+        builder.write(le_bytes);
+        builder.write(be_bytes);
+        builder.write(limbs);
+        let circuit = builder.mock_build();
+
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = i as u8 + 1;
+        }
+        let value = u256_from_be_bytes(bytes);
+        assert_eq!(u256_to_be_bytes(value), bytes);
+        assert_eq!(u256_from_le_bytes(u256_to_le_bytes(value)), value);
+
+        let mut input = circuit.input();
+        input.write::<U256Variable>(value);
+        let mut output = circuit.mock_prove(&input).output;
+        assert_eq!(output.read::<BytesVariable<32>>(), u256_to_le_bytes(value));
+        assert_eq!(output.read::<BytesVariable<32>>(), bytes);
+        let limbs = output.read::<ArrayVariable<U32Variable, 8>>();
+        assert_eq!(limbs[0], 0x1d1e1f20);
+        assert_eq!(limbs[7], 0x01020304);
+    }
+
+    #[test]
+    fn test_u256_from_keccak_output() {
+        let mut builder = DefaultBuilder::new();
+        let input = builder.read::<BytesVariable<20>>();
+        let hash = builder.keccak256(&input.0);
+        let value = builder.u256_from_be_bytes(hash.0);
+        builder.write(value);
+        let circuit = builder.mock_build();
+
+        let preimage = [0xab; 20];
+        let mut input = circuit.input();
+        input.write::<BytesVariable<20>>(preimage);
+        let mut output = circuit.mock_prove(&input).output;
+        assert_eq!(
+            output.read::<U256Variable>(),
+            U256::from_big_endian(&ethers::utils::keccak256(preimage))
+        );
+    }
+
+    fn mul_mod(a: U256, b: U256, n: U256) -> U256 {
+        if n.is_zero() {
+            return U256::zero();
+        }
+        U256::try_from(a.full_mul(b) % U512::from(n)).unwrap()
+    }
+
+    fn add_mod(a: U256, b: U256, n: U256) -> U256 {
+        if n.is_zero() {
+            return U256::zero();
+        }
+        let (sum, overflow) = a.overflowing_add(b);
+        let sum = U512::from(sum)
+            + if overflow {
+                U512::one() << 256
+            } else {
+                U512::zero()
+            };
+        U256::try_from(sum % U512::from(n)).unwrap()
+    }
+
+    #[test]
+    fn test_u256_mulmod_addmod() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U256Variable>();
+        let b = builder.read::<U256Variable>();
+        let n = builder.read::<U256Variable>();
+        let mulmod = builder.mulmod(a, b, n);
+        let addmod = builder.addmod(a, b, n);
+        builder.write(mulmod);
+        builder.write(addmod);
+        let circuit = builder.mock_build();
+
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        let mut random = || U256::from_big_endian(&rng.gen::<[u8; 32]>());
+        let mut cases = vec![
+            // A zero modulus gives zero, as in the EVM.
+            (U256::from(3), U256::from(5), U256::zero()),
+            (U256::MAX, U256::MAX, U256::zero()),
+            // Everything is zero modulo one.
+            (U256::MAX, U256::MAX, U256::one()),
+            // Operands near 2^256.
+            (U256::MAX, U256::MAX, U256::MAX),
+            (U256::MAX, U256::MAX - 1, U256::MAX - 2),
+            (U256::MAX, U256::MAX, U256::from(7)),
+            (U256::MAX - 1, U256::from(2), U256::one() << 255),
+        ];
+        for _ in 0..8 {
+            cases.push((random(), random(), random()));
+        }
+        // A small random modulus, so that the quotient has many limbs.
+        cases.push((random(), random(), random() >> 200));
+
+        for (a, b, n) in cases {
+            let mut input = circuit.input();
+            input.write::<U256Variable>(a);
+            input.write::<U256Variable>(b);
+            input.write::<U256Variable>(n);
+            let mut output = circuit.mock_prove(&input).output;
+            assert_eq!(output.read::<U256Variable>(), mul_mod(a, b, n));
+            assert_eq!(output.read::<U256Variable>(), add_mod(a, b, n));
+        }
+    }
+
+    /// Checks the constraints of the division on a quotient and a remainder given as inputs, as
+    /// a malicious hint would provide them.
+    fn mock_prove_div_rem(a: U256, b: U256, quotient: U256, rem: U256) {
+        let mut builder = DefaultBuilder::new();
+        let [a_biguint, b_biguint, quotient_biguint, rem_biguint] = array![_ => {
+            let value = builder.read::<U256Variable>();
+            BigUintTarget {
+                limbs: value.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+            }
+        }; 4];
+        builder
+            .api
+            .assert_div_rem_biguint(&a_biguint, &b_biguint, &quotient_biguint, &rem_biguint);
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        for value in [a, b, quotient, rem] {
+            input.write::<U256Variable>(value);
+        }
+        circuit.mock_prove(&input);
+    }
+
+    #[test]
+    fn test_u256_div_rem_hint() {
+        let a = U256::MAX - 12345;
+        let b = (U256::one() << 130) + 7;
+        let (quotient, rem) = a.div_mod(b);
+        mock_prove_div_rem(a, b, quotient, rem);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_u256_div_rem_wrong_quotient() {
+        let a = U256::MAX - 12345;
+        let b = (U256::one() << 130) + 7;
+        let (quotient, rem) = a.div_mod(b);
+        mock_prove_div_rem(a, b, quotient + 1, rem);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_u256_div_rem_unreduced_remainder() {
+        // The quotient is off by one, and the remainder makes up for it, so only `r < b` fails.
+        let a = U256::MAX - 12345;
+        let b = (U256::one() << 130) + 7;
+        let (quotient, rem) = a.div_mod(b);
+        mock_prove_div_rem(a, b, quotient - 1, rem + b);
+    }
+}
@@ -221,6 +221,52 @@ impl<L: PlonkParameters<D>, const D: usize> Sub<L, D> for U32Variable {
     }
 }
 
+impl<L: PlonkParameters<D>, const D: usize> CheckedArithmetic<L, D> for U32Variable {
+    fn checked_add(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> (Self, BoolVariable) {
+        let self_biguint = BigUintTarget {
+            limbs: vec![self.into()],
+        };
+        let rhs_biguint = BigUintTarget {
+            limbs: vec![rhs.into()],
+        };
+        let sum_biguint = builder.api.add_biguint(&self_biguint, &rhs_biguint);
+
+        // The second limb holds the carry.
+        let carry: U32Variable = sum_biguint.limbs[1].into();
+        let zero = builder.zero::<U32Variable>();
+        let no_overflow = builder.is_equal(carry, zero);
+        let overflow = builder.not(no_overflow);
+        (sum_biguint.limbs[0].into(), overflow)
+    }
+
+    fn checked_sub(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> (Self, BoolVariable) {
+        let underflow = builder.lt(self, rhs);
+        let difference = builder.sub(self, rhs);
+        (difference, underflow)
+    }
+
+    fn checked_mul(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> (Self, BoolVariable) {
+        let self_biguint = BigUintTarget {
+            limbs: vec![self.into()],
+        };
+        let rhs_biguint = BigUintTarget {
+            limbs: vec![rhs.into()],
+        };
+        let product_biguint = builder.api.mul_biguint(&self_biguint, &rhs_biguint);
+
+        // The product of two u32s fits in the first two limbs.
+        let high: U32Variable = product_biguint.limbs[1].into();
+        let zero = builder.zero::<U32Variable>();
+        let no_overflow = builder.is_equal(high, zero);
+        let overflow = builder.not(no_overflow);
+        (product_biguint.limbs[0].into(), overflow)
+    }
+
+    fn max_value(builder: &mut CircuitBuilder<L, D>) -> Self {
+        builder.constant::<U32Variable>(u32::MAX)
+    }
+}
+
 impl U32Variable {
     pub fn to_u64<L: PlonkParameters<D>, const D: usize>(
         &self,
@@ -270,6 +316,178 @@ impl U32Variable {
     }
 }
 
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Computes `x << s`, asserting that `s <= 32`. Shifting by 32 yields zero.
+    pub fn shl_variable(&mut self, x: U32Variable, s: U32Variable) -> U32Variable {
+        let bits = x.split_le_bits(self);
+        let shifted = self.barrel_shift_le(&bits, s, true);
+        U32Variable::from_le_bits_unsafe(self, &shifted)
+    }
+
+    /// Computes `x >> s`, asserting that `s <= 32`. Shifting by 32 yields zero.
+    pub fn shr_variable(&mut self, x: U32Variable, s: U32Variable) -> U32Variable {
+        let bits = x.split_le_bits(self);
+        let shifted = self.barrel_shift_le(&bits, s, false);
+        U32Variable::from_le_bits_unsafe(self, &shifted)
+    }
+
+    /// Shifts little-endian `bits` by `s` positions towards the most significant bit if `left` is
+    /// set and towards the least significant bit otherwise, filling with zeros. Asserts that
+    /// `s <= bits.len()`.
+    ///
+    /// The shift is a barrel shifter: stage `k` shifts by `2^k` if bit `k` of `s` is set.
+    pub(crate) fn barrel_shift_le(
+        &mut self,
+        bits: &[BoolVariable],
+        s: U32Variable,
+        left: bool,
+    ) -> Vec<BoolVariable> {
+        let width = bits.len();
+        let max_shift = self.constant::<U32Variable>(width as u32);
+        let is_in_range = self.lte(s, max_shift);
+        let _true = self._true();
+        self.assert_is_equal(is_in_range, _true);
+
+        // `s <= width` fits in this many bits, so the decomposition below is complete.
+        let nb_shift_bits = (usize::BITS - width.leading_zeros()) as usize;
+        let shift_bits = self.api.split_le(s.variable.0, nb_shift_bits);
+
+        let _false = self._false();
+        let mut result = bits.to_vec();
+        for (k, shift_bit) in shift_bits.into_iter().enumerate() {
+            let amount = 1usize << k;
+            let shifted = (0..width)
+                .map(|i| {
+                    let source = if left {
+                        i.checked_sub(amount)
+                    } else {
+                        Some(i + amount).filter(|j| *j < width)
+                    };
+                    source.map_or(_false, |j| result[j])
+                })
+                .collect_vec();
+            result = (0..width)
+                .map(|i| self.select(shift_bit.into(), shifted[i], result[i]))
+                .collect();
+        }
+        result
+    }
+}
+
+impl U32Variable {
+    /// Sums at most 32 little-endian bits into a U32Variable, without range checks since the sum
+    /// is always less than 2^32.
+    pub(crate) fn from_le_bits_unsafe<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        bits: &[BoolVariable],
+    ) -> Self {
+        assert!(bits.len() <= 32);
+        let bits = bits
+            .iter()
+            .map(|b| BoolTarget::new_unsafe(b.variable.0))
+            .collect_vec();
+        let target = builder.le_sum_cached(&bits);
+        Self::from_targets(&[target])
+    }
+
+    /// Returns the 32 little-endian bits of the value.
+    pub(crate) fn split_le_bits<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<BoolVariable> {
+        builder
+            .split_le_cached(self.variable.0, 32)
+            .into_iter()
+            .map(BoolVariable::from)
+            .collect()
+    }
+}
+
+/// Rotates little-endian bits left by `n` positions, i.e. towards the most significant bit.
+pub(crate) fn rotate_le_bits_left(bits: &[BoolVariable], n: usize) -> Vec<BoolVariable> {
+    let width = bits.len();
+    let n = n % width;
+    (0..width).map(|i| bits[(i + width - n) % width]).collect()
+}
+
+impl<L: PlonkParameters<D>, const D: usize> RotateLeft<L, D, usize> for U32Variable {
+    type Output = Self;
+
+    fn rotate_left(self, rhs: usize, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        let bits = self.split_le_bits(builder);
+        let rotated = rotate_le_bits_left(&bits, rhs);
+        U32Variable::from_le_bits_unsafe(builder, &rotated)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> RotateRight<L, D, usize> for U32Variable {
+    type Output = Self;
+
+    fn rotate_right(self, rhs: usize, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        let bits = self.split_le_bits(builder);
+        let rotated = rotate_le_bits_left(&bits, 32 - rhs % 32);
+        U32Variable::from_le_bits_unsafe(builder, &rotated)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> BitAnd<L, D> for U32Variable {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        let self_bits = self.split_le_bits(builder);
+        let rhs_bits = rhs.split_le_bits(builder);
+        let bits = self_bits
+            .into_iter()
+            .zip(rhs_bits)
+            .map(|(a, b)| builder.and(a, b))
+            .collect_vec();
+        U32Variable::from_le_bits_unsafe(builder, &bits)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> BitOr<L, D> for U32Variable {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        let self_bits = self.split_le_bits(builder);
+        let rhs_bits = rhs.split_le_bits(builder);
+        let bits = self_bits
+            .into_iter()
+            .zip(rhs_bits)
+            .map(|(a, b)| builder.or(a, b))
+            .collect_vec();
+        U32Variable::from_le_bits_unsafe(builder, &bits)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> BitXor<L, D> for U32Variable {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        let self_bits = self.split_le_bits(builder);
+        let rhs_bits = rhs.split_le_bits(builder);
+        let bits = self_bits
+            .into_iter()
+            .zip(rhs_bits)
+            .map(|(a, b)| builder.xor(a, b))
+            .collect_vec();
+        U32Variable::from_le_bits_unsafe(builder, &bits)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Not<L, D> for U32Variable {
+    type Output = Self;
+
+    fn not(self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        let bits = self
+            .split_le_bits(builder)
+            .into_iter()
+            .map(|b| builder.not(b))
+            .collect_vec();
+        U32Variable::from_le_bits_unsafe(builder, &bits)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rand::Rng;
@@ -401,4 +619,141 @@ mod tests {
         let proof = circuit.data.prove(pw).unwrap();
         circuit.data.verify(proof).unwrap();
     }
+
+    #[test]
+    fn test_u32_variable_shifts() {
+        let mut builder = DefaultBuilder::new();
+        let x = builder.read::<U32Variable>();
+        let s = builder.read::<U32Variable>();
+        let shl = builder.shl_variable(x, s);
+        let shr = builder.shr_variable(x, s);
+        builder.write(shl);
+        builder.write(shr);
+        let circuit = builder.build();
+
+        for x in [0x8765_4321_u32, u32::MAX] {
+            for s in 0..=32 {
+                let mut input = circuit.input();
+                input.write::<U32Variable>(x);
+                input.write::<U32Variable>(s);
+                let (proof, mut output) = circuit.prove(&input);
+                circuit.verify(&proof, &input, &output);
+                assert_eq!(output.read::<U32Variable>(), x.checked_shl(s).unwrap_or(0));
+                assert_eq!(output.read::<U32Variable>(), x.checked_shr(s).unwrap_or(0));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_u32_variable_shift_out_of_range() {
+        let mut builder = DefaultBuilder::new();
+        let x = builder.constant::<U32Variable>(1);
+        let s = builder.constant::<U32Variable>(33);
+        builder.shl_variable(x, s);
+        let circuit = builder.build();
+        let input = circuit.input();
+        circuit.prove(&input);
+    }
+
+    #[test]
+    fn test_u32_rotate() {
+        let mut builder = DefaultBuilder::new();
+        let x = builder.read::<U32Variable>();
+        let amounts = [0, 1, 7, 8, 16, 25, 31, 32, 33];
+        for n in amounts {
+            let left = builder.rotate_left(x, n);
+            let right = builder.rotate_right(x, n);
+            // Rotating back must give the original word, reusing the cached decompositions.
+            let back = builder.rotate_right(left, n);
+            builder.assert_is_equal(back, x);
+            builder.write(left);
+            builder.write(right);
+        }
+        let circuit = builder.build();
+
+        let x = 0x8765_4321_u32;
+        let mut input = circuit.input();
+        input.write::<U32Variable>(x);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        for n in amounts {
+            assert_eq!(output.read::<U32Variable>(), x.rotate_left(n as u32));
+            assert_eq!(output.read::<U32Variable>(), x.rotate_right(n as u32));
+        }
+    }
+
+    #[test]
+    fn test_u32_checked_arithmetic() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U32Variable>();
+        let b = builder.read::<U32Variable>();
+        let (sum, add_overflow) = builder.checked_add(a, b);
+        let (difference, sub_overflow) = builder.checked_sub(a, b);
+        let (product, mul_overflow) = builder.checked_mul(a, b);
+        let saturated_sum = builder.saturating_add(a, b);
+        let saturated_difference = builder.saturating_sub(a, b);
+        builder.write(sum);
+        builder.write(add_overflow);
+        builder.write(difference);
+        builder.write(sub_overflow);
+        builder.write(product);
+        builder.write(mul_overflow);
+        builder.write(saturated_sum);
+        builder.write(saturated_difference);
+        let circuit = builder.build();
+
+        let cases = [
+            (u32::MAX, 1),
+            (u32::MAX, 0),
+            (0, 1),
+            (1, 1),
+            (u32::MAX, 2),
+            (1 << 31, 2),
+            (0xffff, 0x10001),
+        ];
+        for (a, b) in cases {
+            let mut input = circuit.input();
+            input.write::<U32Variable>(a);
+            input.write::<U32Variable>(b);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<U32Variable>(), a.wrapping_add(b));
+            assert_eq!(output.read::<BoolVariable>(), a.checked_add(b).is_none());
+            assert_eq!(output.read::<U32Variable>(), a.wrapping_sub(b));
+            assert_eq!(output.read::<BoolVariable>(), a.checked_sub(b).is_none());
+            assert_eq!(output.read::<U32Variable>(), a.wrapping_mul(b));
+            assert_eq!(output.read::<BoolVariable>(), a.checked_mul(b).is_none());
+            assert_eq!(output.read::<U32Variable>(), a.saturating_add(b));
+            assert_eq!(output.read::<U32Variable>(), a.saturating_sub(b));
+        }
+    }
+
+    #[test]
+    fn test_u32_bitwise() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U32Variable>();
+        let b = builder.read::<U32Variable>();
+        let and = builder.and(a, b);
+        let or = builder.or(a, b);
+        let xor = builder.xor(a, b);
+        let not = builder.not(a);
+        builder.write(and);
+        builder.write(or);
+        builder.write(xor);
+        builder.write(not);
+        let circuit = builder.build();
+
+        let mut rng = rand::thread_rng();
+        let (a, b) = (rng.gen::<u32>(), rng.gen::<u32>());
+        let mut input = circuit.input();
+        input.write::<U32Variable>(a);
+        input.write::<U32Variable>(b);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<U32Variable>(), a & b);
+        assert_eq!(output.read::<U32Variable>(), a | b);
+        assert_eq!(output.read::<U32Variable>(), a ^ b);
+        assert_eq!(output.read::<U32Variable>(), !a);
+    }
 }
@@ -109,6 +109,15 @@ macro_rules! make_uint32_n {
                 }
                 Bytes32Variable(BytesVariable::<32>(bytes.try_into().unwrap()))
             }
+
+            fn ssz_basic_serialize<L: PlonkParameters<D>, const D: usize>(
+                &self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> Option<Vec<ByteVariable>> {
+                let mut bytes = self.encode(builder);
+                bytes.reverse();
+                Some(bytes)
+            }
         }
 
         impl<L: PlonkParameters<D>, const D: usize> Zero<L, D> for $a {
@@ -234,37 +243,32 @@ macro_rules! make_uint32_n {
             }
         }
 
-        impl<L: PlonkParameters<D>, const D: usize> Div<L, D> for $a {
+        impl<L: PlonkParameters<D>, const D: usize> DivRem<L, D> for $a {
             type Output = Self;
 
-            fn div(self, rhs: $a, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
-                let self_targets = self
-                    .limbs
-                    .iter()
-                    .map(|x| U32Target::from(*x))
-                    .collect::<Vec<_>>();
-                let rhs_targets = rhs
-                    .limbs
-                    .iter()
-                    .map(|x| U32Target::from(*x))
-                    .collect::<Vec<_>>();
-                assert_eq!(self_targets.len(), rhs_targets.len());
-                assert_eq!(self_targets.len(), $c);
-
+            /// The quotient and the remainder are hinted, and constrained by `a == q * b + r` on
+            /// the full product of `q` and `b`, and by `r < b`. There is no valid assignment when
+            /// `b` is zero.
+            fn div_rem(self, rhs: $a, builder: &mut CircuitBuilder<L, D>) -> (Self, Self) {
                 let self_biguint = BigUintTarget {
-                    limbs: self_targets,
+                    limbs: self.limbs.iter().map(|x| U32Target::from(*x)).collect(),
                 };
-                let rhs_biguint = BigUintTarget { limbs: rhs_targets };
-                let quotient_biguint = builder.api.div_biguint(&self_biguint, &rhs_biguint);
+                let rhs_biguint = BigUintTarget {
+                    limbs: rhs.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+                };
+                let (quotient_biguint, rem_biguint) =
+                    builder.api.div_rem_biguint(&self_biguint, &rhs_biguint);
+                let quotient = array![i => U32Variable::from(quotient_biguint.limbs[i]); $c];
+                let rem = array![i => U32Variable::from(rem_biguint.limbs[i]); $c];
+                (Self { limbs: quotient }, Self { limbs: rem })
+            }
+        }
 
-                let mut limbs: [U32Variable; $c] = Self::zero(builder).limbs;
-                for i in 0..$c {
-                    limbs[i] = quotient_biguint.limbs[i].into();
-                }
+        impl<L: PlonkParameters<D>, const D: usize> Div<L, D> for $a {
+            type Output = Self;
 
-                Self {
-                    limbs
-                }
+            fn div(self, rhs: $a, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+                self.div_rem(rhs, builder).0
             }
         }
 
@@ -272,33 +276,7 @@ macro_rules! make_uint32_n {
             type Output = Self;
 
             fn rem(self, rhs: $a, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
-                let self_targets = self
-                    .limbs
-                    .iter()
-                    .map(|x| U32Target::from(*x))
-                    .collect::<Vec<_>>();
-                let rhs_targets = rhs
-                    .limbs
-                    .iter()
-                    .map(|x| U32Target::from(*x))
-                    .collect::<Vec<_>>();
-                assert_eq!(self_targets.len(), rhs_targets.len());
-                assert_eq!(self_targets.len(), $c);
-
-                let self_biguint = BigUintTarget {
-                    limbs: self_targets,
-                };
-                let rhs_biguint = BigUintTarget { limbs: rhs_targets };
-                let rem_biguint = builder.api.rem_biguint(&self_biguint, &rhs_biguint);
-
-                let mut limbs: [U32Variable; $c] = Self::zero(builder).limbs;
-                for i in 0..$c {
-                    limbs[i] = rem_biguint.limbs[i].into();
-                }
-
-                Self {
-                    limbs
-                }
+                self.div_rem(rhs, builder).1
             }
         }
 
@@ -318,6 +296,72 @@ macro_rules! make_uint32_n {
                 builder.or(lte_acc, equal_so_far)
             }
         }
+
+        impl<L: PlonkParameters<D>, const D: usize> CheckedArithmetic<L, D> for $a {
+            fn checked_add(
+                self,
+                rhs: Self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> (Self, BoolVariable) {
+                let self_biguint = BigUintTarget {
+                    limbs: self.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+                };
+                let rhs_biguint = BigUintTarget {
+                    limbs: rhs.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+                };
+                let sum_biguint = builder.api.add_biguint(&self_biguint, &rhs_biguint);
+
+                // The extra limb holds the carry out of the most significant limb.
+                let carry: U32Variable = sum_biguint.limbs[$c].into();
+                let zero = builder.zero::<U32Variable>();
+                let no_overflow = builder.is_equal(carry, zero);
+                let overflow = builder.not(no_overflow);
+                let limbs = array![i => U32Variable::from(sum_biguint.limbs[i]); $c];
+                (Self { limbs }, overflow)
+            }
+
+            fn checked_sub(
+                self,
+                rhs: Self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> (Self, BoolVariable) {
+                let underflow = builder.lt(self, rhs);
+                let difference = builder.sub(self, rhs);
+                (difference, underflow)
+            }
+
+            fn checked_mul(
+                self,
+                rhs: Self,
+                builder: &mut CircuitBuilder<L, D>,
+            ) -> (Self, BoolVariable) {
+                let self_biguint = BigUintTarget {
+                    limbs: self.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+                };
+                let rhs_biguint = BigUintTarget {
+                    limbs: rhs.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+                };
+                let product_biguint = builder.api.mul_biguint(&self_biguint, &rhs_biguint);
+
+                // The product overflows if any limb above the low `$c` ones is nonzero.
+                let zero = builder.zero::<U32Variable>();
+                let mut overflow = builder._false();
+                for limb in product_biguint.limbs[$c..].iter() {
+                    let is_zero = builder.is_equal(U32Variable::from(*limb), zero);
+                    let is_nonzero = builder.not(is_zero);
+                    overflow = builder.or(overflow, is_nonzero);
+                }
+                let limbs = array![i => U32Variable::from(product_biguint.limbs[i]); $c];
+                (Self { limbs }, overflow)
+            }
+
+            fn max_value(builder: &mut CircuitBuilder<L, D>) -> Self {
+                let max_limb = builder.constant::<U32Variable>(u32::MAX);
+                Self {
+                    limbs: [max_limb; $c],
+                }
+            }
+        }
     };
 }
 
@@ -454,6 +498,79 @@ macro_rules! make_uint32_n_tests {
                 let proof = circuit.data.prove(pw).unwrap();
                 circuit.data.verify(proof).unwrap();
             }
+
+            #[test]
+            fn test_u32n_checked_arithmetic() {
+                let mut builder = CircuitBuilder::<L, D>::new();
+                let a = builder.read::<$a>();
+                let b = builder.read::<$a>();
+                let (sum, add_overflow) = builder.checked_add(a, b);
+                let (difference, sub_overflow) = builder.checked_sub(a, b);
+                let (product, mul_overflow) = builder.checked_mul(a, b);
+                let saturated_sum = builder.saturating_add(a, b);
+                let saturated_difference = builder.saturating_sub(a, b);
+                builder.write(sum);
+                builder.write(add_overflow);
+                builder.write(difference);
+                builder.write(sub_overflow);
+                builder.write(product);
+                builder.write(mul_overflow);
+                builder.write(saturated_sum);
+                builder.write(saturated_difference);
+                let circuit = builder.build();
+
+                let value = |limbs: [u32; $c]| <$b as Uint<$c>>::from_u32_limbs(limbs);
+                let zero = value([0; $c]);
+                let max = value([u32::MAX; $c]);
+                let mut one_limbs = [0; $c];
+                one_limbs[0] = 1;
+                let one = value(one_limbs);
+                let two = one.overflowing_add(one).0;
+                let mut high_limbs = [0; $c];
+                high_limbs[$c - 1] = 1;
+                let high = value(high_limbs);
+
+                // MAX + 1, 0 - 1, MAX * 2 and their non-overflowing neighbours.
+                let cases = [
+                    (max, one),
+                    (max, zero),
+                    (zero, one),
+                    (one, one),
+                    (max, two),
+                    (high, two),
+                    (high, high),
+                ];
+                for (a, b) in cases {
+                    let mut input = circuit.input();
+                    input.write::<$a>(a);
+                    input.write::<$a>(b);
+                    let (proof, mut output) = circuit.prove(&input);
+                    circuit.verify(&proof, &input, &output);
+
+                    let (sum, add_overflow) = a.overflowing_add(b);
+                    let (difference, sub_overflow) = a.overflowing_sub(b);
+                    let (product, mul_overflow) = a.overflowing_mul(b);
+                    let saturated_sum = if add_overflow { max } else { sum };
+                    let saturated_difference = if sub_overflow { zero } else { difference };
+                    assert_eq!(output.read::<$a>().to_u32_limbs(), sum.to_u32_limbs());
+                    assert_eq!(output.read::<BoolVariable>(), add_overflow);
+                    assert_eq!(
+                        output.read::<$a>().to_u32_limbs(),
+                        difference.to_u32_limbs()
+                    );
+                    assert_eq!(output.read::<BoolVariable>(), sub_overflow);
+                    assert_eq!(output.read::<$a>().to_u32_limbs(), product.to_u32_limbs());
+                    assert_eq!(output.read::<BoolVariable>(), mul_overflow);
+                    assert_eq!(
+                        output.read::<$a>().to_u32_limbs(),
+                        saturated_sum.to_u32_limbs()
+                    );
+                    assert_eq!(
+                        output.read::<$a>().to_u32_limbs(),
+                        saturated_difference.to_u32_limbs()
+                    );
+                }
+            }
         }
     };
 }
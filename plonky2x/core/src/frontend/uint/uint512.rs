@@ -7,8 +7,9 @@ use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
 use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::U32Target;
 use crate::frontend::vars::{EvmVariable, SSZVariable, U32Variable};
 use crate::prelude::{
-    Add, BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitBuilder,
-    CircuitVariable, Div, LessThanOrEqual, Mul, One, PlonkParameters, Rem, Sub, Variable, Zero,
+    Add, BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CheckedArithmetic,
+    CircuitBuilder, CircuitVariable, Div, DivRem, LessThanOrEqual, Mul, One, PlonkParameters, Rem,
+    Sub, Variable, Zero,
 };
 use crate::{make_uint32_n, make_uint32_n_tests};
 
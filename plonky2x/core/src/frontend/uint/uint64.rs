@@ -4,13 +4,15 @@ use plonky2::field::types::Field;
 use plonky2::hash::hash_types::RichField;
 
 use super::uint256::U256Variable;
+use super::uint32::rotate_le_bits_left;
 use super::Uint;
 use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
 use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::U32Target;
 use crate::frontend::vars::{EvmVariable, SSZVariable, U32Variable};
 use crate::prelude::{
-    Add, BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitBuilder,
-    CircuitVariable, Div, LessThanOrEqual, Mul, One, PlonkParameters, Rem, Sub, Variable, Zero,
+    Add, BitAnd, BitXor, BoolVariable, ByteVariable, Bytes32Variable, BytesVariable,
+    CheckedArithmetic, CircuitBuilder, CircuitVariable, Div, DivRem, LessThanOrEqual, Mul, Not,
+    One, PlonkParameters, Rem, RotateLeft, RotateRight, Shr, Sub, Variable, Zero,
 };
 use crate::{make_uint32_n, make_uint32_n_tests};
 
@@ -50,7 +52,58 @@ impl Uint<2> for u64 {
 make_uint32_n!(U64Variable, u64, 2);
 make_uint32_n_tests!(U64Variable, u64, 2);
 
+/// Arithmetic on `U64Variable` follows `u64::wrapping_*` semantics: `add`, `sub` and `mul` are
+/// computed modulo 2^64, and any carry, borrow or high half of the product is discarded.
 impl U64Variable {
+    /// Returns the low and high 32-bit limbs, in that order.
+    pub fn to_limbs(&self) -> [U32Variable; 2] {
+        self.limbs
+    }
+
+    /// Converts a Variable to a U64Variable, decomposing the canonical representative of the field
+    /// element into two range-checked limbs.
+    pub fn from_variable<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        variable: Variable,
+    ) -> Self {
+        let (low, high) = builder.api.split_low_high(variable.0, 32, 64);
+        let result = Self {
+            limbs: [
+                U32Variable::from_variables_unsafe(&[Variable(low)]),
+                U32Variable::from_variables_unsafe(&[Variable(high)]),
+            ],
+        };
+        // Values below 2^32 - 1 have a second decomposition, offset by the field modulus.
+        result.assert_is_canonical(builder);
+        result
+    }
+
+    /// Converts a U64Variable to Variable, asserting that the value is less than the field modulus
+    /// 2^64 - 2^32 + 1 so that the conversion does not overflow.
+    pub fn to_variable<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Variable {
+        self.assert_is_canonical(builder);
+        self.to_variable_with_overflow(builder)
+    }
+
+    /// Asserts that the value is less than the field modulus, i.e. that the low limb is zero
+    /// whenever the high limb is 2^32 - 1.
+    fn assert_is_canonical<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        let max_limb = builder.constant::<U32Variable>(u32::MAX);
+        let zero_limb = builder.zero::<U32Variable>();
+        let high_is_max = builder.is_equal(self.limbs[1], max_limb);
+        let low_is_zero = builder.is_equal(self.limbs[0], zero_limb);
+        let high_is_not_max = builder.not(high_is_max);
+        let is_canonical = builder.or(high_is_not_max, low_is_zero);
+        let _true = builder._true();
+        builder.assert_is_equal(is_canonical, _true);
+    }
+
     /// Converts a U64Variable to Variable with overflow.
     ///
     /// Note: This function assumes that the U64 is in the range [0, 2^64-2^32+1). Otherwise, it
@@ -81,3 +134,325 @@ impl U64Variable {
         result
     }
 }
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Computes `x << s`, asserting that `s <= 64`. Shifting by 64 yields zero.
+    pub fn shl_variable_u64(&mut self, x: U64Variable, s: U32Variable) -> U64Variable {
+        let bits = x.split_le_bits(self);
+        let shifted = self.barrel_shift_le(&bits, s, true);
+        U64Variable::from_le_bits_unsafe(self, &shifted)
+    }
+
+    /// Computes `x >> s`, asserting that `s <= 64`. Shifting by 64 yields zero.
+    pub fn shr_variable_u64(&mut self, x: U64Variable, s: U32Variable) -> U64Variable {
+        let bits = x.split_le_bits(self);
+        let shifted = self.barrel_shift_le(&bits, s, false);
+        U64Variable::from_le_bits_unsafe(self, &shifted)
+    }
+}
+
+impl U64Variable {
+    fn split_le_bits<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<BoolVariable> {
+        self.limbs
+            .iter()
+            .flat_map(|limb| limb.split_le_bits(builder))
+            .collect()
+    }
+
+    fn from_le_bits_unsafe<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        bits: &[BoolVariable],
+    ) -> Self {
+        assert_eq!(bits.len(), 64);
+        Self {
+            limbs: [
+                U32Variable::from_le_bits_unsafe(builder, &bits[..32]),
+                U32Variable::from_le_bits_unsafe(builder, &bits[32..]),
+            ],
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> RotateLeft<L, D, usize> for U64Variable {
+    type Output = Self;
+
+    fn rotate_left(self, rhs: usize, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        let bits = self.split_le_bits(builder);
+        let rotated = rotate_le_bits_left(&bits, rhs);
+        U64Variable::from_le_bits_unsafe(builder, &rotated)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> RotateRight<L, D, usize> for U64Variable {
+    type Output = Self;
+
+    fn rotate_right(self, rhs: usize, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        let bits = self.split_le_bits(builder);
+        let rotated = rotate_le_bits_left(&bits, 64 - rhs % 64);
+        U64Variable::from_le_bits_unsafe(builder, &rotated)
+    }
+}
+
+// The bitwise operations below reuse the cached bit decompositions, so chaining them with
+// rotations, as in SHA-512, only decomposes each word once.
+
+impl<L: PlonkParameters<D>, const D: usize> Shr<L, D, usize> for U64Variable {
+    type Output = Self;
+
+    fn shr(self, rhs: usize, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        assert!(rhs < 64, "shift amount is too large, must be less than 64");
+        let bits = self.split_le_bits(builder);
+        let _false = builder._false();
+        let shifted = (0..64)
+            .map(|i| if i + rhs < 64 { bits[i + rhs] } else { _false })
+            .collect::<Vec<_>>();
+        U64Variable::from_le_bits_unsafe(builder, &shifted)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> BitXor<L, D> for U64Variable {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        let self_bits = self.split_le_bits(builder);
+        let rhs_bits = rhs.split_le_bits(builder);
+        let bits = self_bits
+            .into_iter()
+            .zip(rhs_bits)
+            .map(|(a, b)| builder.xor(a, b))
+            .collect::<Vec<_>>();
+        U64Variable::from_le_bits_unsafe(builder, &bits)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> BitAnd<L, D> for U64Variable {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        let self_bits = self.split_le_bits(builder);
+        let rhs_bits = rhs.split_le_bits(builder);
+        let bits = self_bits
+            .into_iter()
+            .zip(rhs_bits)
+            .map(|(a, b)| builder.and(a, b))
+            .collect::<Vec<_>>();
+        U64Variable::from_le_bits_unsafe(builder, &bits)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Not<L, D> for U64Variable {
+    type Output = Self;
+
+    fn not(self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        let bits = self
+            .split_le_bits(builder)
+            .into_iter()
+            .map(|b| builder.not(b))
+            .collect::<Vec<_>>();
+        U64Variable::from_le_bits_unsafe(builder, &bits)
+    }
+}
+
+#[cfg(test)]
+mod u64_tests {
+    use plonky2::field::types::Field64;
+
+    use super::*;
+    use crate::prelude::{DefaultBuilder, GoldilocksField};
+
+    #[test]
+    fn test_u64_wrapping_arithmetic() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U64Variable>();
+        let b = builder.read::<U64Variable>();
+        let sum = builder.add(a, b);
+        let diff = builder.sub(a, b);
+        let product = builder.mul(a, b);
+        let lt = builder.lt(a, b);
+        let lte = builder.lte(a, b);
+        let gt = builder.gt(a, b);
+        let gte = builder.gte(a, b);
+        builder.write(sum);
+        builder.write(diff);
+        builder.write(product);
+        builder.write(lt);
+        builder.write(lte);
+        builder.write(gt);
+        builder.write(gte);
+        let circuit = builder.build();
+
+        let cases = [
+            (u64::MAX, 1),
+            (0, 1),
+            (u64::MAX, u64::MAX),
+            (1 << 32, 1 << 32),
+            (0xffff_ffff, 0x1_0000_0001),
+        ];
+        for (a, b) in cases {
+            let mut input = circuit.input();
+            input.write::<U64Variable>(a);
+            input.write::<U64Variable>(b);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<U64Variable>(), a.wrapping_add(b));
+            assert_eq!(output.read::<U64Variable>(), a.wrapping_sub(b));
+            assert_eq!(output.read::<U64Variable>(), a.wrapping_mul(b));
+            assert_eq!(output.read::<BoolVariable>(), a < b);
+            assert_eq!(output.read::<BoolVariable>(), a <= b);
+            assert_eq!(output.read::<BoolVariable>(), a > b);
+            assert_eq!(output.read::<BoolVariable>(), a >= b);
+        }
+    }
+
+    #[test]
+    fn test_u64_variable_conversion() {
+        let mut builder = DefaultBuilder::new();
+        let variable = builder.read::<Variable>();
+        let value = U64Variable::from_variable(&mut builder, variable);
+        let [low, high] = value.to_limbs();
+        let roundtrip = value.to_variable(&mut builder);
+        builder.assert_is_equal(roundtrip, variable);
+        builder.write(value);
+        builder.write(low);
+        builder.write(high);
+        let circuit = builder.build();
+
+        let modulus = GoldilocksField::ORDER;
+        for x in [0, 1, 0xffff_ffff, 1 << 32, modulus - 1] {
+            let mut input = circuit.input();
+            input.write::<Variable>(GoldilocksField::from_canonical_u64(x));
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<U64Variable>(), x);
+            assert_eq!(output.read::<U32Variable>(), x as u32);
+            assert_eq!(output.read::<U32Variable>(), (x >> 32) as u32);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_u64_to_variable_overflow() {
+        let mut builder = DefaultBuilder::new();
+        let value = builder.constant::<U64Variable>(u64::MAX);
+        value.to_variable(&mut builder);
+        let circuit = builder.build();
+        let input = circuit.input();
+        circuit.prove(&input);
+    }
+
+    #[test]
+    fn test_u64_evm_io() {
+        let mut builder = DefaultBuilder::new();
+        let value = builder.evm_read::<U64Variable>();
+        let one = builder.one::<U64Variable>();
+        let next = builder.add(value, one);
+        builder.evm_write(next);
+        let circuit = builder.build();
+
+        for x in [0, 0x0102_0304_0506_0708, u64::MAX] {
+            let mut input = circuit.input();
+            input.evm_write::<U64Variable>(x);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.evm_read::<U64Variable>(), x.wrapping_add(1));
+        }
+
+        let bytes = U64Variable::encode_value::<GoldilocksField>(0x0102_0304_0506_0708);
+        assert_eq!(bytes, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(
+            U64Variable::decode_value::<GoldilocksField>(&bytes),
+            0x0102_0304_0506_0708
+        );
+    }
+
+    #[test]
+    fn test_u64_variable_shifts() {
+        let mut builder = DefaultBuilder::new();
+        let x = builder.read::<U64Variable>();
+        let s = builder.read::<U32Variable>();
+        let shl = builder.shl_variable_u64(x, s);
+        let shr = builder.shr_variable_u64(x, s);
+        builder.write(shl);
+        builder.write(shr);
+        let circuit = builder.build();
+
+        let x = 0xfedc_ba98_7654_3210_u64;
+        for s in 0..=64 {
+            let mut input = circuit.input();
+            input.write::<U64Variable>(x);
+            input.write::<U32Variable>(s);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<U64Variable>(), x.checked_shl(s).unwrap_or(0));
+            assert_eq!(output.read::<U64Variable>(), x.checked_shr(s).unwrap_or(0));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_u64_variable_shift_out_of_range() {
+        let mut builder = DefaultBuilder::new();
+        let x = builder.constant::<U64Variable>(1);
+        let s = builder.constant::<U32Variable>(65);
+        builder.shr_variable_u64(x, s);
+        let circuit = builder.build();
+        let input = circuit.input();
+        circuit.prove(&input);
+    }
+
+    #[test]
+    fn test_u64_rotate() {
+        let mut builder = DefaultBuilder::new();
+        let x = builder.read::<U64Variable>();
+        let amounts = [0, 1, 14, 18, 32, 41, 63, 64];
+        for n in amounts {
+            let left = builder.rotate_left(x, n);
+            let right = builder.rotate_right(x, n);
+            let back = builder.rotate_left(right, n);
+            builder.assert_is_equal(back, x);
+            builder.write(left);
+            builder.write(right);
+        }
+        let circuit = builder.build();
+
+        let x = 0xfedc_ba98_7654_3210_u64;
+        let mut input = circuit.input();
+        input.write::<U64Variable>(x);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        for n in amounts {
+            assert_eq!(output.read::<U64Variable>(), x.rotate_left(n as u32));
+            assert_eq!(output.read::<U64Variable>(), x.rotate_right(n as u32));
+        }
+    }
+
+    #[test]
+    fn test_u64_bitwise() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<U64Variable>();
+        let b = builder.read::<U64Variable>();
+        let xor = builder.xor(a, b);
+        let and = builder.and(a, b);
+        let not = builder.not(a);
+        let shr = builder.shr(a, 7);
+        builder.write(xor);
+        builder.write(and);
+        builder.write(not);
+        builder.write(shr);
+        let circuit = builder.build();
+
+        let (a, b) = (0xfedc_ba98_7654_3210_u64, 0x0f0f_0f0f_ffff_0000_u64);
+        let mut input = circuit.input();
+        input.write::<U64Variable>(a);
+        input.write::<U64Variable>(b);
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<U64Variable>(), a ^ b);
+        assert_eq!(output.read::<U64Variable>(), a & b);
+        assert_eq!(output.read::<U64Variable>(), !a);
+        assert_eq!(output.read::<U64Variable>(), a >> 7);
+    }
+}
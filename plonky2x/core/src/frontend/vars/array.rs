@@ -9,10 +9,14 @@ use plonky2::iop::challenger::RecursiveChallenger;
 use plonky2::iop::target::Target;
 use serde::{Deserialize, Serialize};
 
-use super::{ByteVariable, CircuitVariable, ValueStream, Variable, VariableStream};
+use super::{
+    BoolVariable, ByteVariable, Bytes32Variable, CircuitVariable, SSZVariable, ValueStream,
+    Variable, VariableStream,
+};
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
 use crate::frontend::hint::simple::hint::Hint;
+use crate::frontend::ops::{BitAnd, BitOr, BitXor, Not};
 
 /// A variable in the circuit representing a fixed length array of variables.
 /// We use this to avoid stack overflow arrays associated with fixed-length arrays.
@@ -66,6 +70,10 @@ impl<V: CircuitVariable, const N: usize> From<Vec<V>> for ArrayVariable<V, N> {
 impl<V: CircuitVariable, const N: usize> CircuitVariable for ArrayVariable<V, N> {
     type ValueType<F: RichField> = Vec<V::ValueType<F>>;
 
+    fn format_value<F: RichField>(value: &Vec<V::ValueType<F>>) -> String {
+        format!("[{}]", value.iter().map(V::format_value::<F>).join(", "))
+    }
+
     fn init_unsafe<L: PlonkParameters<D>, const D: usize>(
         builder: &mut CircuitBuilder<L, D>,
     ) -> Self {
@@ -125,6 +133,16 @@ impl<V: CircuitVariable, const N: usize> CircuitVariable for ArrayVariable<V, N>
     }
 }
 
+/// A fixed vector of `N` elements.
+impl<V: SSZVariable, const N: usize> SSZVariable for ArrayVariable<V, N> {
+    fn hash_tree_root<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Bytes32Variable {
+        builder.ssz_hash_vector(self.as_slice())
+    }
+}
+
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     /// Given `array` of variables and dynamic `selector`, returns `array[selector]` as a variable.
     pub fn select_array<V: CircuitVariable>(&mut self, array: &[V], selector: Variable) -> V {
@@ -177,6 +195,122 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
         V::from_variables_unsafe(&selected_vars)
     }
 
+    /// Returns `array[index]`, asserting that `index < N`.
+    ///
+    /// Unlike `select_array`, which returns `array[0]` for an out of range selector, the index is
+    /// decomposed into bits and the element is selected with a log-depth select tree.
+    pub fn get_array_element<V: CircuitVariable, const N: usize>(
+        &mut self,
+        array: &ArrayVariable<V, N>,
+        index: Variable,
+    ) -> V {
+        let bits = self.assert_index_in_range(index, N);
+
+        // Pad the array to a power of two. The padding is never selected since `index < N`.
+        let mut layer = array.data.clone();
+        layer.resize(1 << bits.len(), array.data[N - 1].clone());
+        for bit in bits {
+            layer = layer
+                .chunks_exact(2)
+                .map(|pair| self.select(bit, pair[1].clone(), pair[0].clone()))
+                .collect();
+        }
+        layer.remove(0)
+    }
+
+    /// Returns a copy of `array` with `array[index]` replaced by `value`, asserting that
+    /// `index < N`.
+    pub fn set_array_element<V: CircuitVariable, const N: usize>(
+        &mut self,
+        array: &ArrayVariable<V, N>,
+        index: Variable,
+        value: V,
+    ) -> ArrayVariable<V, N> {
+        self.assert_index_in_range(index, N);
+        let data = array
+            .data
+            .iter()
+            .enumerate()
+            .map(|(i, element)| {
+                let i = self.constant::<Variable>(L::Field::from_canonical_usize(i));
+                let is_index = self.is_equal(i, index);
+                self.select(is_index, value.clone(), element.clone())
+            })
+            .collect();
+        ArrayVariable::new(data)
+    }
+
+    /// Returns `array[offset..offset + M]`, asserting that `offset + M <= N`.
+    ///
+    /// The subarray is extracted with a barrel shifter: for each bit of the offset, the array is
+    /// shifted left by the corresponding power of two if the bit is set. Unlike
+    /// `get_fixed_subarray`, this works for any `CircuitVariable` and does not need a seed.
+    pub fn select_subarray<V: CircuitVariable, const N: usize, const M: usize>(
+        &mut self,
+        array: &ArrayVariable<V, N>,
+        offset: Variable,
+    ) -> ArrayVariable<V, M> {
+        assert!(M <= N, "subarray of length {} is longer than the array", M);
+        let bits = self.assert_index_in_range(offset, N - M + 1);
+        ArrayVariable::new(self.shift_left(array.data.clone(), bits, M))
+    }
+
+    /// Returns the `width` elements of `array` starting at `offset`, where the positions past the
+    /// end of `array` are filled with `padding`. Asserts that `offset <= array.len()`.
+    pub(crate) fn select_window<V: CircuitVariable>(
+        &mut self,
+        array: &[V],
+        offset: Variable,
+        width: usize,
+        padding: V,
+    ) -> Vec<V> {
+        let bits = self.assert_index_in_range(offset, array.len() + 1);
+        let mut padded = array.to_vec();
+        padded.resize(array.len() + width, padding);
+        self.shift_left(padded, bits, width)
+    }
+
+    /// Shifts `array` left by the little endian `bits` with a barrel shifter, and returns its first
+    /// `width` elements. The shift must leave at least `width` elements in the array.
+    fn shift_left<V: CircuitVariable>(
+        &mut self,
+        array: Vec<V>,
+        bits: Vec<BoolVariable>,
+        width: usize,
+    ) -> Vec<V> {
+        // Shift by the most significant bits first, so that each layer only needs to keep the
+        // elements that can still be shifted into the first `width` positions.
+        let mut layer = array;
+        for (k, bit) in bits.into_iter().enumerate().rev() {
+            let shift = 1 << k;
+            let len = layer.len().min(width + shift - 1);
+            layer = (0..len)
+                .map(|i| {
+                    // Positions past the end of the layer are never part of the result.
+                    let shifted = layer.get(i + shift).unwrap_or(&layer[i]).clone();
+                    self.select(bit, shifted, layer[i].clone())
+                })
+                .collect();
+        }
+        layer.truncate(width);
+        layer
+    }
+
+    /// Asserts that `index < bound` and returns its little endian bits.
+    fn assert_index_in_range(&mut self, index: Variable, bound: usize) -> Vec<BoolVariable> {
+        assert!(bound > 0, "cannot index into an empty array");
+        let nb_bits = (usize::BITS - (bound - 1).leading_zeros()).max(1) as usize;
+
+        // Both `index` and `bound - 1 - index` fit in `nb_bits` bits if and only if
+        // `index < bound`.
+        let bits = self.api.split_le(index.0, nb_bits);
+        let last = self.constant::<Variable>(L::Field::from_canonical_usize(bound - 1));
+        let remaining = self.sub(last, index);
+        self.api.split_le(remaining.0, nb_bits);
+
+        bits.into_iter().map(BoolVariable::from).collect()
+    }
+
     /// Given an `array` of ByteVariable's, a dynamic `index` start_idx, and a commitment to the
     /// `array`, 'seed', return `array[start_idx..start_idx+sub_array_size]` as an `array`.
     /// `seed` is used to generate randomness for the proof, and must contain a valid commitment to
@@ -343,10 +477,11 @@ pub struct SubArrayExtractorHint {
 
 impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for SubArrayExtractorHint {
     fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        // The bytes of the array may be private, so they are only copied. The index is public.
         let mut array_elements = Vec::new();
 
         for _i in 0..self.array_size {
-            let element = input_stream.read_value::<ByteVariable>();
+            let element = input_stream.read_secret::<ByteVariable>();
             array_elements.push(element);
         }
 
@@ -356,12 +491,75 @@ impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for SubArrayExtractorHint
         assert!(end_idx <= self.array_size as u64);
 
         for i in 0..self.sub_array_size {
-            let element = array_elements[start_idx as usize + i];
-            output_stream.write_value::<ByteVariable>(element);
+            let element = array_elements[start_idx as usize + i].clone();
+            output_stream.write_secret::<ByteVariable>(element);
         }
     }
 }
 
+impl<L: PlonkParameters<D>, const D: usize, V, const N: usize> Not<L, D> for ArrayVariable<V, N>
+where
+    V: CircuitVariable + Not<L, D, Output = V>,
+{
+    type Output = Self;
+
+    fn not(self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        self.data
+            .into_iter()
+            .map(|x| builder.not(x))
+            .collect_vec()
+            .into()
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize, V, const N: usize> BitAnd<L, D> for ArrayVariable<V, N>
+where
+    V: CircuitVariable + BitAnd<L, D, Output = V>,
+{
+    type Output = Self;
+
+    fn bitand(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        self.data
+            .into_iter()
+            .zip(rhs.data)
+            .map(|(a, b)| builder.and(a, b))
+            .collect_vec()
+            .into()
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize, V, const N: usize> BitOr<L, D> for ArrayVariable<V, N>
+where
+    V: CircuitVariable + BitOr<L, D, Output = V>,
+{
+    type Output = Self;
+
+    fn bitor(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        self.data
+            .into_iter()
+            .zip(rhs.data)
+            .map(|(a, b)| builder.or(a, b))
+            .collect_vec()
+            .into()
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize, V, const N: usize> BitXor<L, D> for ArrayVariable<V, N>
+where
+    V: CircuitVariable + BitXor<L, D, Output = V>,
+{
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        self.data
+            .into_iter()
+            .zip(rhs.data)
+            .map(|(a, b)| builder.xor(a, b))
+            .collect_vec()
+            .into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Instant;
@@ -442,6 +640,100 @@ mod tests {
         assert_eq!(output.read::<U256Variable>(), input_u256[1]);
     }
 
+    #[test]
+    fn test_get_and_set_array_element() {
+        const N: usize = 5;
+
+        let mut builder = DefaultBuilder::new();
+        let array = builder.read::<ArrayVariable<U32Variable, N>>();
+        let index = builder.read::<Variable>();
+        let value = builder.read::<U32Variable>();
+        let element = builder.get_array_element(&array, index);
+        let updated = builder.set_array_element(&array, index, value);
+        builder.write(element);
+        builder.write(updated);
+        let circuit = builder.build();
+
+        let array = vec![10, 11, 12, 13, 14];
+        for i in 0..N {
+            let mut input = circuit.input();
+            input.write::<ArrayVariable<U32Variable, N>>(array.clone());
+            input.write::<Variable>(GoldilocksField::from_canonical_usize(i));
+            input.write::<U32Variable>(99);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+
+            let mut expected = array.clone();
+            expected[i] = 99;
+            assert_eq!(output.read::<U32Variable>(), array[i]);
+            assert_eq!(output.read::<ArrayVariable<U32Variable, N>>(), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_get_array_element_out_of_range() {
+        const N: usize = 5;
+
+        let mut builder = DefaultBuilder::new();
+        let array = builder.read::<ArrayVariable<U32Variable, N>>();
+        let index = builder.read::<Variable>();
+        let element = builder.get_array_element(&array, index);
+        builder.write(element);
+        let circuit = builder.build();
+
+        // 5 fits in the 3 bits used to select the element, but is past the end of the array.
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<U32Variable, N>>(vec![10, 11, 12, 13, 14]);
+        input.write::<Variable>(GoldilocksField::from_canonical_usize(N));
+        circuit.prove(&input);
+    }
+
+    #[test]
+    fn test_select_subarray() {
+        const N: usize = 7;
+        const M: usize = 3;
+
+        let mut builder = DefaultBuilder::new();
+        let array = builder.read::<ArrayVariable<ByteVariable, N>>();
+        let offset = builder.read::<Variable>();
+        let subarray = builder.select_subarray::<_, N, M>(&array, offset);
+        builder.write(subarray);
+        let circuit = builder.build();
+
+        let array = vec![10, 11, 12, 13, 14, 15, 16];
+        for offset in 0..=N - M {
+            let mut input = circuit.input();
+            input.write::<ArrayVariable<ByteVariable, N>>(array.clone());
+            input.write::<Variable>(GoldilocksField::from_canonical_usize(offset));
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(
+                output.read::<ArrayVariable<ByteVariable, M>>(),
+                array[offset..offset + M]
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_select_subarray_overflow() {
+        const N: usize = 7;
+        const M: usize = 3;
+
+        let mut builder = DefaultBuilder::new();
+        let array = builder.read::<ArrayVariable<ByteVariable, N>>();
+        let offset = builder.read::<Variable>();
+        let subarray = builder.select_subarray::<_, N, M>(&array, offset);
+        builder.write(subarray);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<ByteVariable, N>>(vec![10, 11, 12, 13, 14, 15, 16]);
+        input.write::<Variable>(GoldilocksField::from_canonical_usize(N - M + 1));
+        circuit.prove(&input);
+    }
+
     #[test]
     fn test_select_index_random_gate() {
         utils::setup_logger();
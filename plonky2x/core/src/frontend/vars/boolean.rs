@@ -4,7 +4,7 @@ use plonky2::hash::hash_types::RichField;
 use plonky2::iop::target::BoolTarget;
 use serde::{Deserialize, Serialize};
 
-use super::{CircuitVariable, Variable};
+use super::{ByteVariable, Bytes32Variable, BytesVariable, CircuitVariable, SSZVariable, Variable};
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
 use crate::frontend::ops::{BitAnd, BitOr, BitXor, Not};
@@ -65,6 +65,27 @@ impl CircuitVariable for BoolVariable {
     }
 }
 
+impl SSZVariable for BoolVariable {
+    fn hash_tree_root<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Bytes32Variable {
+        let mut bytes = self.ssz_basic_serialize(builder).unwrap();
+        let zero = builder.constant::<ByteVariable>(0);
+        bytes.resize(32, zero);
+        Bytes32Variable(BytesVariable::<32>(bytes.try_into().unwrap()))
+    }
+
+    fn ssz_basic_serialize<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Option<Vec<ByteVariable>> {
+        let zero = builder.constant::<ByteVariable>(0);
+        let one = builder.constant::<ByteVariable>(1);
+        Some(vec![builder.select(*self, one, zero)])
+    }
+}
+
 impl From<BoolTarget> for BoolVariable {
     fn from(v: BoolTarget) -> Self {
         // BoolTarget's range is the same as BoolVariable's.
@@ -18,6 +18,10 @@ pub struct ByteVariable(pub [BoolVariable; 8]);
 impl CircuitVariable for ByteVariable {
     type ValueType<F: RichField> = u8;
 
+    fn format_value<F: RichField>(value: &u8) -> String {
+        format!("0x{:02x}", value)
+    }
+
     fn init_unsafe<L: PlonkParameters<D>, const D: usize>(
         builder: &mut CircuitBuilder<L, D>,
     ) -> Self {
@@ -4,7 +4,7 @@ use std::ops::{Index, Range};
 use array_macro::array;
 use plonky2::hash::hash_types::RichField;
 
-use super::{CircuitVariable, EvmVariable, Variable};
+use super::{Bytes32Variable, CircuitVariable, EvmVariable, SSZVariable, Variable};
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
 use crate::frontend::ops::{BitAnd, BitOr, BitXor, Not, RotateLeft, RotateRight, Shl, Shr, Zero};
@@ -17,6 +17,10 @@ pub struct BytesVariable<const N: usize>(pub [ByteVariable; N]);
 impl<const N: usize> CircuitVariable for BytesVariable<N> {
     type ValueType<F: RichField> = [u8; N];
 
+    fn format_value<F: RichField>(value: &[u8; N]) -> String {
+        format!("0x{}", hex::encode(value))
+    }
+
     fn init_unsafe<L: PlonkParameters<D>, const D: usize>(
         builder: &mut CircuitBuilder<L, D>,
     ) -> Self {
@@ -128,6 +132,23 @@ impl<const N: usize> EvmVariable for BytesVariable<N> {
     }
 }
 
+/// A fixed vector of `N` bytes, such as a BLS public key.
+impl<const N: usize> SSZVariable for BytesVariable<N> {
+    fn hash_tree_root<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Bytes32Variable {
+        let zero = builder.constant::<ByteVariable>(0);
+        let mut bytes = self.0.to_vec();
+        bytes.resize(N.div_ceil(32) * 32, zero);
+        let chunks = bytes
+            .chunks(32)
+            .map(|chunk| Bytes32Variable(BytesVariable(chunk.try_into().unwrap())))
+            .collect::<Vec<_>>();
+        builder.ssz_merkleize(&chunks, chunks.len())
+    }
+}
+
 impl<L: PlonkParameters<D>, const D: usize, const N: usize> Not<L, D> for BytesVariable<N> {
     type Output = Self;
 
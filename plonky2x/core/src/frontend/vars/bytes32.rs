@@ -8,6 +8,7 @@ use super::{
 };
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::ops::{BitAnd, BitOr, BitXor, Not};
 
 /// A variable in the circuit representing a byte32 value.
 #[derive(Debug, Clone, Copy)]
@@ -110,6 +111,40 @@ impl SSZVariable for Bytes32Variable {
     }
 }
 
+// The bitwise operations work on the bits of each byte, so they add no decomposition.
+
+impl<L: PlonkParameters<D>, const D: usize> Not<L, D> for Bytes32Variable {
+    type Output = Self;
+
+    fn not(self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        Bytes32Variable(builder.not(self.0))
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> BitAnd<L, D> for Bytes32Variable {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        Bytes32Variable(builder.and(self.0, rhs.0))
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> BitOr<L, D> for Bytes32Variable {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        Bytes32Variable(builder.or(self.0, rhs.0))
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> BitXor<L, D> for Bytes32Variable {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self, builder: &mut CircuitBuilder<L, D>) -> Self::Output {
+        Bytes32Variable(builder.xor(self.0, rhs.0))
+    }
+}
+
 impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
     // Returns a Bytes32Variable with the first `num_bits` set to 0.
     pub fn mask_be_bits(&mut self, original: Bytes32Variable, num_bits: usize) -> Bytes32Variable {
@@ -122,16 +157,26 @@ impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
 
         Bytes32Variable::from_variables_unsafe(&new_variables)
     }
+
+    /// Returns `value & mask`, keeping the bits of `value` that are set in `mask`.
+    pub fn mask_bytes32(
+        &mut self,
+        value: Bytes32Variable,
+        mask: Bytes32Variable,
+    ) -> Bytes32Variable {
+        self.and(value, mask)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use ethers::types::U256;
+    use ethers::types::{H256, U256};
     use plonky2::iop::witness::PartialWitness;
+    use rand::Rng;
 
     use super::Bytes32Variable;
     use crate::frontend::uint::uint256::U256Variable;
-    use crate::prelude::{CircuitVariable, DefaultBuilder};
+    use crate::prelude::{ArrayVariable, ByteVariable, CircuitVariable, DefaultBuilder};
     use crate::utils::bytes32;
 
     #[test]
@@ -158,4 +203,73 @@ mod test {
         let proof = circuit.data.prove(pw).unwrap();
         circuit.data.verify(proof).unwrap();
     }
+
+    #[test]
+    fn test_bytes32_bitwise() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Bytes32Variable>();
+        let b = builder.read::<Bytes32Variable>();
+        let xor = builder.xor(a, b);
+        let and = builder.and(a, b);
+        let or = builder.or(a, b);
+        let not = builder.not(a);
+        let masked = builder.mask_bytes32(a, b);
+        builder.write(xor);
+        builder.write(and);
+        builder.write(or);
+        builder.write(not);
+        builder.write(masked);
+
+        let c = builder.read::<ArrayVariable<ByteVariable, 5>>();
+        let d = builder.read::<ArrayVariable<ByteVariable, 5>>();
+        let xor = builder.xor(c.clone(), d.clone());
+        let and = builder.and(c.clone(), d.clone());
+        let or = builder.or(c.clone(), d);
+        let not = builder.not(c);
+        builder.write(xor);
+        builder.write(and);
+        builder.write(or);
+        builder.write(not);
+        let circuit = builder.build();
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..3 {
+            let a: [u8; 32] = rng.gen();
+            let b: [u8; 32] = rng.gen();
+            let c: [u8; 5] = rng.gen();
+            let d: [u8; 5] = rng.gen();
+            let mut input = circuit.input();
+            input.write::<Bytes32Variable>(H256(a));
+            input.write::<Bytes32Variable>(H256(b));
+            input.write::<ArrayVariable<ByteVariable, 5>>(c.to_vec());
+            input.write::<ArrayVariable<ByteVariable, 5>>(d.to_vec());
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+
+            let zip = |f: fn(u8, u8) -> u8| -> [u8; 32] { core::array::from_fn(|i| f(a[i], b[i])) };
+            assert_eq!(output.read::<Bytes32Variable>(), H256(zip(|x, y| x ^ y)));
+            assert_eq!(output.read::<Bytes32Variable>(), H256(zip(|x, y| x & y)));
+            assert_eq!(output.read::<Bytes32Variable>(), H256(zip(|x, y| x | y)));
+            assert_eq!(output.read::<Bytes32Variable>(), H256(a.map(|x| !x)));
+            assert_eq!(output.read::<Bytes32Variable>(), H256(zip(|x, y| x & y)));
+
+            let zip = |f: fn(u8, u8) -> u8| (0..5).map(|i| f(c[i], d[i])).collect::<Vec<_>>();
+            assert_eq!(
+                output.read::<ArrayVariable<ByteVariable, 5>>(),
+                zip(|x, y| x ^ y)
+            );
+            assert_eq!(
+                output.read::<ArrayVariable<ByteVariable, 5>>(),
+                zip(|x, y| x & y)
+            );
+            assert_eq!(
+                output.read::<ArrayVariable<ByteVariable, 5>>(),
+                zip(|x, y| x | y)
+            );
+            assert_eq!(
+                output.read::<ArrayVariable<ByteVariable, 5>>(),
+                c.map(|x| !x).to_vec()
+            );
+        }
+    }
 }
@@ -4,9 +4,13 @@ mod byte;
 mod bytes;
 mod bytes32;
 mod collections;
+mod option;
+mod ssz;
 
 mod stream;
+mod string;
 mod variable;
+mod variable_bytes;
 use std::fmt::Debug;
 
 pub use array::*;
@@ -15,12 +19,16 @@ pub use byte::*;
 pub use bytes::*;
 pub use bytes32::*;
 use itertools::Itertools;
+pub use option::*;
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::target::Target;
 use plonky2::iop::witness::{Witness, WitnessWrite};
 pub use stream::*;
+pub use string::*;
 pub use variable::*;
+pub use variable_bytes::*;
 
+pub use super::uint::biguint::*;
 pub use super::uint::uint256::*;
 pub use super::uint::uint32::*;
 use crate::backend::circuit::PlonkParameters;
@@ -30,6 +38,11 @@ pub trait CircuitVariable: Debug + Clone + Sized + Sync + Send + 'static {
     /// The underlying type of the variable if it were not in a circuit.
     type ValueType<F: RichField>: Debug + Clone;
 
+    /// Formats a value of the variable for logs, e.g. by `watch`. Byte types are formatted as hex.
+    fn format_value<F: RichField>(value: &Self::ValueType<F>) -> String {
+        format!("{:?}", value)
+    }
+
     /// Initializes the variable with no value in the circuit and checks that the variable is valid
     /// (i.e., range checks).
     fn init<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>) -> Self {
@@ -177,6 +190,84 @@ pub trait EvmVariable: CircuitVariable {
         bits.reverse();
         bits
     }
+
+    /// Deserializes the variable from big endian bits, asserting that there are `nb_bits()` of
+    /// them. The bits are boolean, as for any `BoolVariable`.
+    fn from_be_bits<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        bits: &[BoolVariable],
+    ) -> Self {
+        assert_eq!(
+            bits.len(),
+            Self::nb_bits::<L, D>(),
+            "wrong number of bits to deserialize"
+        );
+        let bytes = bits
+            .chunks(8)
+            .map(|chunk| ByteVariable::from_be_bits(chunk.try_into().unwrap()))
+            .collect_vec();
+        Self::decode(builder, &bytes)
+    }
+
+    /// Deserializes the variable from little endian bits, asserting that there are `nb_bits()` of
+    /// them.
+    fn from_le_bits<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        bits: &[BoolVariable],
+    ) -> Self {
+        let mut bits = bits.to_vec();
+        bits.reverse();
+        Self::from_be_bits(builder, &bits)
+    }
+
+    /// Serializes the variable to big endian bytes, which is the same as `encode`.
+    fn to_be_bytes<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        self.encode(builder)
+    }
+
+    /// Serializes the variable to little endian bytes.
+    fn to_le_bytes<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Vec<ByteVariable> {
+        let mut bytes = self.encode(builder);
+        bytes.reverse();
+        bytes
+    }
+
+    /// Deserializes the variable from big endian bytes, asserting that there are `nb_bytes()` of
+    /// them.
+    fn from_be_bytes<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        bytes: &[ByteVariable],
+    ) -> Self {
+        assert_eq!(
+            bytes.len(),
+            Self::nb_bytes::<L, D>(),
+            "wrong number of bytes to deserialize"
+        );
+        Self::decode(builder, bytes)
+    }
+
+    /// Deserializes the variable from little endian bytes, asserting that there are `nb_bytes()`
+    /// of them.
+    fn from_le_bytes<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        bytes: &[ByteVariable],
+    ) -> Self {
+        let mut bytes = bytes.to_vec();
+        bytes.reverse();
+        Self::from_be_bytes(builder, &bytes)
+    }
+}
+
+/// A variable generated by deriving `CircuitVariable` on an enum.
+pub trait EnumVariable: CircuitVariable {
+    /// The index of the active variant, in declaration order.
+    fn discriminant(&self) -> Variable;
 }
 
 pub trait SSZVariable: CircuitVariable {
@@ -184,10 +275,24 @@ pub trait SSZVariable: CircuitVariable {
         &self,
         builder: &mut CircuitBuilder<L, D>,
     ) -> Bytes32Variable;
+
+    /// The serialization of a basic SSZ type (`uintN` or `boolean`), which is packed together with
+    /// its neighbours when it is an element of a vector or a list. Composite types return `None`,
+    /// and are represented by their hash tree root instead.
+    fn ssz_basic_serialize<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        _builder: &mut CircuitBuilder<L, D>,
+    ) -> Option<Vec<ByteVariable>> {
+        None
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use ethers::types::U256;
+    use rand::Rng;
+
+    use super::EvmVariable;
     use crate::prelude::*;
 
     #[test]
@@ -229,6 +334,84 @@ mod tests {
         });
     }
 
+    #[derive(Debug, Clone, CircuitVariable)]
+    #[value_name(Shape)]
+    #[value_derive(PartialEq, Eq)]
+    #[variable_name(ShapeVariable)]
+    enum ShapeVariant {
+        Empty,
+        Circle(U32Variable),
+        Rect {
+            width: U32Variable,
+            height: ByteVariable,
+        },
+    }
+
+    #[test]
+    fn test_derive_enum() {
+        let mut builder = CircuitBuilder::<DefaultParameters, 2>::new();
+        let shape = builder.read::<ShapeVariable>();
+        let discriminant = builder.match_variant(&shape);
+        builder.write(shape);
+        builder.write(discriminant);
+
+        let radius = builder.constant::<U32Variable>(3);
+        let circle = ShapeVariable::new(&mut builder, ShapeVariant::Circle(radius));
+        builder.write(circle);
+        let circuit = builder.build();
+
+        // The discriminant, the radius, then the width and the eight bits of the height.
+        assert_eq!(ShapeVariable::nb_elements(), 11);
+        let mut expected = vec![GoldilocksField::ONE, GoldilocksField::from_canonical_u64(5)];
+        expected.resize(11, GoldilocksField::ZERO);
+        assert_eq!(
+            ShapeVariable::elements::<GoldilocksField>(Shape::Circle(5)),
+            expected
+        );
+
+        let values = [
+            (Shape::Empty, 0),
+            (Shape::Circle(7), 1),
+            (
+                Shape::Rect {
+                    width: 4,
+                    height: 2,
+                },
+                2,
+            ),
+        ];
+        for (value, discriminant) in values {
+            let mut input = circuit.input();
+            input.write::<ShapeVariable>(value.clone());
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<ShapeVariable>(), value);
+            assert_eq!(
+                output.read::<Variable>(),
+                GoldilocksField::from_canonical_u64(discriminant)
+            );
+            assert_eq!(output.read::<ShapeVariable>(), Shape::Circle(3));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_derive_enum_inactive_fields() {
+        let mut builder = CircuitBuilder::<DefaultParameters, 2>::new();
+        let elements = builder.read::<ArrayVariable<Variable, 11>>();
+        let shape = ShapeVariable::from_variables_unsafe(elements.as_slice());
+        shape.assert_is_valid(&mut builder);
+        let circuit = builder.mock_build();
+
+        // A circle of radius 5 whose rectangle fields are not zero.
+        let mut elements = vec![GoldilocksField::ONE, GoldilocksField::from_canonical_u64(5)];
+        elements.push(GoldilocksField::from_canonical_u64(4));
+        elements.resize(11, GoldilocksField::ZERO);
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<Variable, 11>>(elements);
+        circuit.mock_prove(&input);
+    }
+
     #[test]
     fn test_value_derive_struct() {
         #[derive(Debug, Clone, CircuitVariable)]
@@ -265,4 +448,83 @@ mod tests {
             y: GoldilocksField::ZERO,
         });
     }
+
+    /// Round trips a variable through its bit and byte conversions, and returns the little endian
+    /// bits and the big and little endian bytes.
+    fn check_endianness<V: EvmVariable, const NB_BYTES: usize, const NB_BITS: usize>(
+        values: &[V::ValueType<GoldilocksField>],
+        native_be_bytes: impl Fn(&V::ValueType<GoldilocksField>) -> Vec<u8>,
+    ) {
+        let mut builder = DefaultBuilder::new();
+        let x = builder.read::<V>();
+        let be_bits = builder.to_be_bits(x.clone());
+        let le_bits = builder.to_le_bits(x.clone());
+        let be_bytes = builder.to_be_bytes(x.clone());
+        let le_bytes = builder.to_le_bytes(x.clone());
+        let from_be_bits = builder.from_be_bits::<V>(&be_bits);
+        let from_le_bits = builder.from_le_bits::<V>(&le_bits);
+        let from_be_bytes = builder.from_be_bytes::<V>(&be_bytes);
+        let from_le_bytes = builder.from_le_bytes::<V>(&le_bytes);
+        for y in [from_be_bits, from_le_bits, from_be_bytes, from_le_bytes] {
+            builder.assert_is_equal(y, x.clone());
+        }
+        builder.write(ArrayVariable::<BoolVariable, NB_BITS>::new(le_bits));
+        builder.write(ArrayVariable::<ByteVariable, NB_BYTES>::new(be_bytes));
+        builder.write(ArrayVariable::<ByteVariable, NB_BYTES>::new(le_bytes));
+        let circuit = builder.build();
+
+        for value in values {
+            let mut input = circuit.input();
+            input.write::<V>(value.clone());
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+
+            let be = native_be_bytes(value);
+            let le = be.iter().rev().copied().collect::<Vec<_>>();
+            let le_bits = le
+                .iter()
+                .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                output.read::<ArrayVariable<BoolVariable, NB_BITS>>(),
+                le_bits
+            );
+            assert_eq!(output.read::<ArrayVariable<ByteVariable, NB_BYTES>>(), be);
+            assert_eq!(output.read::<ArrayVariable<ByteVariable, NB_BYTES>>(), le);
+        }
+    }
+
+    #[test]
+    fn test_endianness_conversions() {
+        let mut rng = rand::thread_rng();
+
+        let bytes = [0, 1, 0x80, rng.gen()];
+        check_endianness::<ByteVariable, 1, 8>(&bytes, |x| vec![*x]);
+
+        let words = [0, 1, 0x1234_5678, rng.gen()];
+        check_endianness::<U32Variable, 4, 32>(&words, |x| x.to_be_bytes().to_vec());
+
+        let longs = [0, 1, 0x0102_0304_0506_0708, rng.gen()];
+        check_endianness::<U64Variable, 8, 64>(&longs, |x| x.to_be_bytes().to_vec());
+
+        let wides = [
+            U256::zero(),
+            U256::one(),
+            U256::MAX - 1,
+            U256(rng.gen::<[u64; 4]>()),
+        ];
+        check_endianness::<U256Variable, 32, 256>(&wides, |x| {
+            let mut bytes = vec![0; 32];
+            x.to_big_endian(&mut bytes);
+            bytes
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "wrong number of bits")]
+    fn test_from_be_bits_wrong_length() {
+        let mut builder = DefaultBuilder::new();
+        let bits = vec![builder._true(); 31];
+        builder.from_be_bits::<U32Variable>(&bits);
+    }
 }
@@ -0,0 +1,186 @@
+use plonky2::hash::hash_types::RichField;
+
+use super::{BoolVariable, CircuitVariable, Variable};
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+
+/// A variable in the circuit representing an optional value.
+///
+/// The inner value of a valid none is zero, which `assert_is_valid` enforces with
+/// `assert_none_is_zero`, so that all nones have the same variables. Values written from the
+/// witness are always in this form.
+#[derive(Debug, Clone)]
+pub struct OptionVariable<V: CircuitVariable> {
+    pub is_some: BoolVariable,
+    pub value: V,
+}
+
+impl<V: CircuitVariable> CircuitVariable for OptionVariable<V> {
+    type ValueType<F: RichField> = Option<V::ValueType<F>>;
+
+    fn init_unsafe<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        Self {
+            is_some: BoolVariable::init_unsafe(builder),
+            value: V::init_unsafe(builder),
+        }
+    }
+
+    fn variables(&self) -> Vec<Variable> {
+        let mut variables = self.is_some.variables();
+        variables.extend(self.value.variables());
+        variables
+    }
+
+    fn from_variables_unsafe(variables: &[Variable]) -> Self {
+        assert_eq!(variables.len(), Self::nb_elements());
+        Self {
+            is_some: BoolVariable::from_variables_unsafe(&variables[..1]),
+            value: V::from_variables_unsafe(&variables[1..]),
+        }
+    }
+
+    fn assert_is_valid<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        self.is_some.assert_is_valid(builder);
+        self.value.assert_is_valid(builder);
+        self.assert_none_is_zero(builder);
+    }
+
+    fn nb_elements() -> usize {
+        1 + V::nb_elements()
+    }
+
+    fn elements<F: RichField>(value: Self::ValueType<F>) -> Vec<F> {
+        match value {
+            Some(value) => {
+                let mut elements = vec![F::ONE];
+                elements.extend(V::elements::<F>(value));
+                elements
+            }
+            None => vec![F::ZERO; Self::nb_elements()],
+        }
+    }
+
+    fn from_elements<F: RichField>(elements: &[F]) -> Self::ValueType<F> {
+        assert_eq!(elements.len(), Self::nb_elements());
+        if BoolVariable::from_elements::<F>(&elements[..1]) {
+            Some(V::from_elements::<F>(&elements[1..]))
+        } else {
+            None
+        }
+    }
+}
+
+impl<V: CircuitVariable> OptionVariable<V> {
+    /// Returns the inner value if it is some and `default` otherwise.
+    pub fn unwrap_or<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        default: V,
+    ) -> V {
+        builder.select(self.is_some, self.value.clone(), default)
+    }
+
+    /// Asserts that the inner value is zero if the option is none, so that all nones have the
+    /// same variables.
+    pub fn assert_none_is_zero<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        let is_none = builder.not(self.is_some);
+        let zero = builder.zero::<Variable>();
+        for variable in self.value.variables() {
+            let masked = builder.mul(is_none.variable, variable);
+            builder.assert_is_equal(masked, zero);
+        }
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Wraps a value in an option that is some.
+    pub fn some<V: CircuitVariable>(&mut self, value: V) -> OptionVariable<V> {
+        OptionVariable {
+            is_some: self._true(),
+            value,
+        }
+    }
+
+    /// Returns an option that is none, with a zero inner value.
+    pub fn none<V: CircuitVariable>(&mut self) -> OptionVariable<V> {
+        let zero = self.zero::<Variable>();
+        OptionVariable {
+            is_some: self._false(),
+            value: V::from_variables_unsafe(&vec![zero; V::nb_elements()]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_option_variable() {
+        let mut builder = DefaultBuilder::new();
+        let option = builder.read::<OptionVariable<U64Variable>>();
+        option.assert_none_is_zero(&mut builder);
+        let default = builder.constant::<U64Variable>(7);
+        let unwrapped = option.unwrap_or(&mut builder, default);
+        let value = builder.constant::<U64Variable>(3);
+        let some = builder.some(value);
+        let none = builder.none::<U64Variable>();
+        builder.write(option);
+        builder.write(unwrapped);
+        builder.write(some);
+        builder.write(none);
+        let circuit = builder.build();
+
+        for (value, expected) in [(Some(5), 5), (None, 7)] {
+            let mut input = circuit.input();
+            input.write::<OptionVariable<U64Variable>>(value);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<OptionVariable<U64Variable>>(), value);
+            assert_eq!(output.read::<U64Variable>(), expected);
+            assert_eq!(output.read::<OptionVariable<U64Variable>>(), Some(3));
+            assert_eq!(output.read::<OptionVariable<U64Variable>>(), None);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_option_variable_non_canonical_none() {
+        let mut builder = DefaultBuilder::new();
+        let is_some = builder.read::<BoolVariable>();
+        let value = builder.read::<U64Variable>();
+        let option = OptionVariable { is_some, value };
+        option.assert_none_is_zero(&mut builder);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<BoolVariable>(false);
+        input.write::<U64Variable>(5);
+        circuit.prove(&input);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_option_variable_invalid_none() {
+        let mut builder = DefaultBuilder::new();
+        let is_some = builder.read::<BoolVariable>();
+        let value = builder.read::<U64Variable>();
+        let option = OptionVariable { is_some, value };
+        option.assert_is_valid(&mut builder);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<BoolVariable>(false);
+        input.write::<U64Variable>(5);
+        circuit.prove(&input);
+    }
+}
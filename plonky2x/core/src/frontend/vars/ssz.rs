@@ -0,0 +1,273 @@
+//! Merkleization of SSZ values, following the consensus specs:
+//!
+//! - basic values (`uintN` and `boolean`) are serialized in little-endian, and packed into
+//!   32 byte chunks when they are elements of a vector or a list,
+//! - composite values (containers, vectors and lists of composite values) are represented by the
+//!   hash tree roots of their elements,
+//! - the chunks are merkleized with sha256, padded with zero chunks up to the limit of the type,
+//! - the root of a list is mixed in with its length.
+
+use ethers::types::H256;
+
+use super::{BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, SSZVariable};
+use crate::frontend::uint::uint64::U64Variable;
+use crate::prelude::{ArrayVariable, CircuitBuilder, PlonkParameters};
+use crate::utils::hash::sha256;
+
+/// Returns the roots of the trees of zero chunks of depth 0 to `depth`.
+fn ssz_zero_hashes(depth: usize) -> Vec<H256> {
+    let mut hashes = vec![H256::zero()];
+    for _ in 0..depth {
+        let last = hashes.last().unwrap().as_bytes();
+        hashes.push(H256::from(sha256(&[last, last].concat())));
+    }
+    hashes
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Merkleizes `chunks`, padded with zero chunks up to `limit` chunks rounded up to a power of
+    /// two. The zero subtrees are constants, so they don't cost any hash.
+    pub fn ssz_merkleize(&mut self, chunks: &[Bytes32Variable], limit: usize) -> Bytes32Variable {
+        assert!(
+            chunks.len() <= limit.max(1),
+            "too many chunks for the limit"
+        );
+        let depth = limit.max(1).next_power_of_two().trailing_zeros() as usize;
+        let zero_hashes = ssz_zero_hashes(depth);
+        if chunks.is_empty() {
+            return self.constant::<Bytes32Variable>(zero_hashes[depth]);
+        }
+
+        let mut layer = chunks.to_vec();
+        for zero_hash in zero_hashes.iter().take(depth) {
+            if layer.len() % 2 == 1 {
+                layer.push(self.constant::<Bytes32Variable>(*zero_hash));
+            }
+            let mut next = Vec::with_capacity(layer.len() / 2);
+            for pair in layer.chunks(2) {
+                next.push(self.curta_sha256_pair(pair[0], pair[1]));
+            }
+            layer = next;
+        }
+        layer[0]
+    }
+
+    /// Mixes the length of a list into the root of its elements.
+    pub fn ssz_mix_in_length(
+        &mut self,
+        root: Bytes32Variable,
+        length: U64Variable,
+    ) -> Bytes32Variable {
+        let length = length.hash_tree_root(self);
+        self.curta_sha256_pair(root, length)
+    }
+
+    /// Returns the hash tree root of a container, given the hash tree roots of its fields in
+    /// declaration order.
+    pub fn ssz_hash_container(&mut self, field_roots: &[Bytes32Variable]) -> Bytes32Variable {
+        self.ssz_merkleize(field_roots, field_roots.len())
+    }
+
+    /// Returns the hash tree root of a vector, whose length is the number of `elements`.
+    pub fn ssz_hash_vector<V: SSZVariable>(&mut self, elements: &[V]) -> Bytes32Variable {
+        let (chunks, basic_size) = self.ssz_chunks(elements, None);
+        let limit = match basic_size {
+            Some(size) => (elements.len() * size).div_ceil(32),
+            None => elements.len(),
+        };
+        self.ssz_merkleize(&chunks, limit)
+    }
+
+    /// Returns the hash tree root of a list of at most `limit` elements, made of the first `len`
+    /// elements of `elements`. The elements past `len`, which must be at most `N`, are ignored.
+    pub fn ssz_hash_list<V: SSZVariable, const N: usize>(
+        &mut self,
+        elements: &ArrayVariable<V, N>,
+        len: U64Variable,
+        limit: usize,
+    ) -> Bytes32Variable {
+        assert!(N <= limit, "the list has more elements than its limit");
+        let max_len = self.constant::<U64Variable>(N as u64);
+        let is_valid_len = self.lte(len, max_len);
+        let t = self._true();
+        self.assert_is_equal(is_valid_len, t);
+
+        let is_element = (0..N)
+            .map(|i| {
+                let index = self.constant::<U64Variable>(i as u64);
+                self.lt(index, len)
+            })
+            .collect::<Vec<_>>();
+        let (chunks, basic_size) = self.ssz_chunks(elements.as_slice(), Some(&is_element));
+        let limit = match basic_size {
+            Some(size) => (limit * size).div_ceil(32),
+            None => limit,
+        };
+        let root = self.ssz_merkleize(&chunks, limit);
+        self.ssz_mix_in_length(root, len)
+    }
+
+    /// Returns the chunks of a sequence of values, along with the size of a value if they are
+    /// basic. The values for which `is_element` is false are replaced by zeros.
+    fn ssz_chunks<V: SSZVariable>(
+        &mut self,
+        elements: &[V],
+        is_element: Option<&[BoolVariable]>,
+    ) -> (Vec<Bytes32Variable>, Option<usize>) {
+        let zero = self.constant::<ByteVariable>(0);
+        let zero_chunk = self.constant::<Bytes32Variable>(H256::zero());
+        let basic_size = elements
+            .first()
+            .and_then(|element| element.ssz_basic_serialize(self))
+            .map(|bytes| bytes.len());
+
+        match basic_size {
+            Some(_) => {
+                let mut bytes = Vec::new();
+                for (i, element) in elements.iter().enumerate() {
+                    let serialized = element.ssz_basic_serialize(self).unwrap();
+                    match is_element {
+                        Some(is_element) => bytes.extend(
+                            serialized
+                                .into_iter()
+                                .map(|byte| self.select(is_element[i], byte, zero)),
+                        ),
+                        None => bytes.extend(serialized),
+                    }
+                }
+                bytes.resize(bytes.len().div_ceil(32) * 32, zero);
+                let chunks = bytes
+                    .chunks(32)
+                    .map(|chunk| Bytes32Variable(BytesVariable(chunk.try_into().unwrap())))
+                    .collect();
+                (chunks, basic_size)
+            }
+            None => {
+                let mut chunks = Vec::with_capacity(elements.len());
+                for (i, element) in elements.iter().enumerate() {
+                    let root = element.hash_tree_root(self);
+                    match is_element {
+                        Some(is_element) => {
+                            chunks.push(self.select(is_element[i], root, zero_chunk))
+                        }
+                        None => chunks.push(root),
+                    }
+                }
+                (chunks, None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::eth::beacon::vars::{BeaconHeaderValue, BeaconHeaderVariable};
+    use crate::prelude::DefaultBuilder;
+    use crate::utils::bytes32;
+
+    // The expected roots are computed off-circuit with the merkleization of the consensus specs.
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ssz_hash_container() {
+        let mut builder = DefaultBuilder::new();
+        let header = builder.read::<BeaconHeaderVariable>();
+        let root = builder.ssz_hash_tree_root(header);
+        let field_roots = [
+            header.slot.hash_tree_root(&mut builder),
+            header.proposer_index.hash_tree_root(&mut builder),
+            header.parent_root,
+            header.state_root,
+            header.body_root,
+        ];
+        let container_root = builder.ssz_hash_container(&field_roots);
+        builder.write(root);
+        builder.write(container_root);
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.write::<BeaconHeaderVariable>(BeaconHeaderValue {
+            slot: 7404237,
+            proposer_index: 350145,
+            parent_root: bytes32!(
+                "0x0c8f2fa2a3a1b3a4fe4a9d7f6c6e6f9f0f0e0d0c0b0a09080706050403020100"
+            ),
+            state_root: bytes32!(
+                "0x8d4c6a1ef93b7d5a2c1e0f9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f1a0b9c"
+            ),
+            body_root: bytes32!(
+                "0x1f2e3d4c5b6a79881f2e3d4c5b6a79881f2e3d4c5b6a79881f2e3d4c5b6a7988"
+            ),
+        });
+        let mut output = circuit.mock_prove(&input).output;
+        let expected =
+            bytes32!("0x5ceb44349fa1156b4c7db45b6721fdc7b37ac9da299342ef6531f25a2962a460");
+        assert_eq!(output.read::<Bytes32Variable>(), expected);
+        assert_eq!(output.read::<Bytes32Variable>(), expected);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ssz_hash_vector() {
+        let mut builder = DefaultBuilder::new();
+        let roots = builder.read::<ArrayVariable<Bytes32Variable, 3>>();
+        let values = builder.read::<ArrayVariable<U64Variable, 3>>();
+        let roots_root = roots.hash_tree_root(&mut builder);
+        let values_root = values.hash_tree_root(&mut builder);
+        builder.write(roots_root);
+        builder.write(values_root);
+        let circuit = builder.mock_build();
+
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<Bytes32Variable, 3>>(
+            (1..=3).map(|i| H256::repeat_byte(i)).collect(),
+        );
+        input.write::<ArrayVariable<U64Variable, 3>>(vec![5, 6, 7]);
+        let mut output = circuit.mock_prove(&input).output;
+
+        // Vector[Bytes32, 3] is padded to 4 chunks.
+        assert_eq!(
+            output.read::<Bytes32Variable>(),
+            bytes32!("0xd6cfa0d1046a0f4c1f9a6dc57afb0f4577680c106a48cf04125e7ba8606da219")
+        );
+        // Vector[uint64, 3] fits in a single chunk, which is its root.
+        assert_eq!(
+            output.read::<Bytes32Variable>(),
+            bytes32!("0x0500000000000000060000000000000007000000000000000000000000000000")
+        );
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_ssz_hash_list() {
+        const N: usize = 8;
+
+        // A List[uint64, 1024], like a short list of balances.
+        let mut builder = DefaultBuilder::new();
+        let values = builder.read::<ArrayVariable<U64Variable, N>>();
+        let len = builder.read::<U64Variable>();
+        let root = builder.ssz_hash_list(&values, len, 1024);
+        builder.write(root);
+        let circuit = builder.mock_build();
+
+        // The trailing values are garbage, which must be ignored.
+        let mut input = circuit.input();
+        input.write::<ArrayVariable<U64Variable, N>>(vec![
+            32000000000,
+            31999999999,
+            1,
+            0,
+            u64::MAX,
+            42,
+            42,
+            42,
+        ]);
+        input.write::<U64Variable>(5);
+        let mut output = circuit.mock_prove(&input).output;
+        assert_eq!(
+            output.read::<Bytes32Variable>(),
+            bytes32!("0xb76154b835deaefd5ca32e3e752d6273d227ce7ffc853981209548e9ca3df60c")
+        );
+    }
+}
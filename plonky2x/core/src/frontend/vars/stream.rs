@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use super::{CircuitVariable, Variable};
 use crate::backend::circuit::PlonkParameters;
+use crate::frontend::hint::secret::SecretValue;
 use crate::prelude::CircuitBuilder;
 use crate::utils::stream::Stream;
 
@@ -118,6 +119,11 @@ impl VariableStream {
         (0..len).map(|_| self.read::<V>()).collect()
     }
 
+    /// Writes a variable to the stream.
+    ///
+    /// The variables of the input stream of a hint are the watch list of its generator, so the hint
+    /// only runs once all of them are set in the witness. A hint can thus depend on any variable,
+    /// including one computed from the outputs of another hint, by writing it to its input stream.
     pub fn write<V: CircuitVariable>(&mut self, value: &V) {
         self.0.write_slice(&value.variables());
     }
@@ -163,6 +169,11 @@ impl<L: PlonkParameters<D>, const D: usize> ValueStream<L, D> {
         V::from_elements::<L::Field>(elements)
     }
 
+    /// Reads a value that must not be branched on. See `SecretValue`.
+    pub fn read_secret<V: CircuitVariable>(&mut self) -> SecretValue<V::ValueType<L::Field>> {
+        SecretValue::new(self.read_value::<V>())
+    }
+
     pub fn read_exact(&mut self, len: usize) -> &[L::Field] {
         self.0.read_exact(len)
     }
@@ -182,6 +193,11 @@ impl<L: PlonkParameters<D>, const D: usize> ValueStream<L, D> {
     pub fn write_value<V: CircuitVariable>(&mut self, value: V::ValueType<L::Field>) {
         self.0.write_slice(&V::elements::<L::Field>(value));
     }
+
+    /// Writes a secret value without declassifying it.
+    pub fn write_secret<V: CircuitVariable>(&mut self, value: SecretValue<V::ValueType<L::Field>>) {
+        self.write_value::<V>(value.into_inner());
+    }
 }
 
 impl<L: PlonkParameters<D>, const D: usize> Default for ValueStream<L, D> {
@@ -0,0 +1,256 @@
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::BoolTarget;
+
+use super::{BoolVariable, ByteVariable, BytesVariable, CircuitVariable, U32Variable, Variable};
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::uint::uint128::U128Variable;
+use crate::frontend::uint::uint64::U64Variable;
+
+/// The number of digits of `u64::MAX`.
+const MAX_U64_DIGITS: usize = 20;
+
+/// A variable in the circuit representing an ASCII string of at most `N` bytes, padded with zero
+/// bytes.
+///
+/// Since the padding is part of the value, two strings are equal if and only if their bytes are.
+#[derive(Debug, Clone, Copy)]
+pub struct StringVariable<const N: usize>(pub BytesVariable<N>);
+
+impl<const N: usize> CircuitVariable for StringVariable<N> {
+    type ValueType<F: RichField> = String;
+
+    fn init_unsafe<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        Self(BytesVariable::init_unsafe(builder))
+    }
+
+    fn variables(&self) -> Vec<Variable> {
+        self.0.variables()
+    }
+
+    fn from_variables_unsafe(variables: &[Variable]) -> Self {
+        Self(BytesVariable::from_variables_unsafe(variables))
+    }
+
+    fn assert_is_valid<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        self.0.assert_is_valid(builder);
+        // ASCII bytes have their most significant bit unset.
+        let _false = builder._false();
+        for byte in self.0 .0.iter() {
+            builder.assert_is_equal(byte.0[0], _false);
+        }
+    }
+
+    fn nb_elements() -> usize {
+        BytesVariable::<N>::nb_elements()
+    }
+
+    fn elements<F: RichField>(value: Self::ValueType<F>) -> Vec<F> {
+        assert!(value.is_ascii(), "string {:?} is not ASCII", value);
+        assert!(
+            value.len() <= N,
+            "string {:?} is too long: expected at most {} bytes got {}",
+            value,
+            N,
+            value.len()
+        );
+        let mut bytes = [0u8; N];
+        bytes[..value.len()].copy_from_slice(value.as_bytes());
+        BytesVariable::<N>::elements::<F>(bytes)
+    }
+
+    fn from_elements<F: RichField>(elements: &[F]) -> Self::ValueType<F> {
+        let bytes = BytesVariable::<N>::from_elements::<F>(elements);
+        let len = bytes.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+        assert!(bytes.is_ascii(), "string bytes {:?} are not ASCII", bytes);
+        String::from_utf8(bytes[..len].to_vec()).unwrap()
+    }
+}
+
+impl<const N: usize> StringVariable<N> {
+    /// Returns whether the string starts with `prefix`.
+    pub fn starts_with<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        prefix: &str,
+    ) -> BoolVariable {
+        assert!(
+            prefix.len() <= N,
+            "prefix {:?} is longer than the string",
+            prefix
+        );
+        let mut result = builder._true();
+        for (byte, expected) in self.0 .0.iter().zip(prefix.bytes()) {
+            let expected = builder.constant::<ByteVariable>(expected);
+            let is_equal = builder.is_equal(*byte, expected);
+            result = builder.and(result, is_equal);
+        }
+        result
+    }
+
+    /// Parses the string as a decimal number.
+    ///
+    /// Asserts that the string is non-empty, has at most 20 digits, contains only `'0'..='9'`
+    /// before its padding, and that its value fits in a `u64`.
+    pub fn to_u64<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> U64Variable {
+        assert!(N > 0, "cannot parse an empty string");
+        let _true = builder._true();
+        let _false = builder._false();
+        let ten = builder.constant::<U128Variable>(10);
+        let zero = builder.zero::<U32Variable>();
+
+        // Accumulate in 128 bits so that a 20 digit number cannot wrap around.
+        let mut value = builder.zero::<U128Variable>();
+        let mut in_padding = _false;
+        for (i, byte) in self.0 .0.iter().enumerate() {
+            let byte_variable = byte.to_variable(builder);
+            let is_padding = builder.is_zero(byte_variable);
+
+            // Once the padding has started, every byte after it must be padding.
+            let padding_continues = builder.select(in_padding, is_padding, _true);
+            builder.assert_is_equal(padding_continues, _true);
+            in_padding = is_padding;
+
+            if i == 0 {
+                builder.assert_is_equal(is_padding, _false);
+            }
+            if i >= MAX_U64_DIGITS {
+                builder.assert_is_equal(is_padding, _true);
+                continue;
+            }
+
+            // A digit is a byte 0x30 to 0x39: its high nibble is 0b0011 and its low nibble is at
+            // most 0b1001.
+            let bits = byte.as_be_bits();
+            let high_nibble_is_three = {
+                let not_b0 = builder.not(bits[0]);
+                let not_b1 = builder.not(bits[1]);
+                let high = builder.and(not_b0, not_b1);
+                let high = builder.and(high, bits[2]);
+                builder.and(high, bits[3])
+            };
+            let low_nibble_is_small = {
+                let b5_or_b6 = builder.or(bits[5], bits[6]);
+                let is_large = builder.and(bits[4], b5_or_b6);
+                builder.not(is_large)
+            };
+            let is_digit = builder.and(high_nibble_is_three, low_nibble_is_small);
+            let is_valid = builder.or(is_digit, is_padding);
+            builder.assert_is_equal(is_valid, _true);
+
+            let digit = builder.api.le_sum(
+                byte.as_le_bits()[0..4]
+                    .iter()
+                    .map(|b| BoolTarget::new_unsafe(b.variable.0)),
+            );
+            let digit = U128Variable {
+                limbs: [
+                    U32Variable::from_variables_unsafe(&[Variable(digit)]),
+                    zero,
+                    zero,
+                    zero,
+                ],
+            };
+            let shifted = builder.mul(value, ten);
+            let next = builder.add(shifted, digit);
+            value = builder.select(is_padding, value, next);
+        }
+
+        value.to_u64(builder)
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Fails if the two strings are not equal.
+    pub fn assert_eq_string<const N: usize>(&mut self, a: StringVariable<N>, b: StringVariable<N>) {
+        self.assert_is_equal(a, b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_string_to_u64() {
+        let mut builder = DefaultBuilder::new();
+        let string = builder.read::<StringVariable<20>>();
+        let value = string.to_u64(&mut builder);
+        builder.write(value);
+        let circuit = builder.build();
+
+        for (string, expected) in [("0", 0), ("42", 42), ("18446744073709551615", u64::MAX)] {
+            let mut input = circuit.input();
+            input.write::<StringVariable<20>>(string.to_string());
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<U64Variable>(), expected);
+        }
+    }
+
+    fn prove_to_u64(string: &str) {
+        let mut builder = DefaultBuilder::new();
+        let string_variable = builder.read::<StringVariable<20>>();
+        string_variable.to_u64(&mut builder);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<StringVariable<20>>(string.to_string());
+        circuit.prove(&input);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_string_to_u64_non_digit() {
+        prove_to_u64("12a4");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_string_to_u64_overflow() {
+        prove_to_u64("18446744073709551616");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_string_to_u64_empty() {
+        prove_to_u64("");
+    }
+
+    #[test]
+    fn test_string_comparison() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<StringVariable<8>>();
+        let b = builder.constant::<StringVariable<8>>("USDC".to_string());
+        builder.assert_eq_string(a, b);
+        let starts_with_us = a.starts_with(&mut builder, "US");
+        let starts_with_eth = a.starts_with(&mut builder, "ETH");
+        builder.write(starts_with_us);
+        builder.write(starts_with_eth);
+        builder.write(a);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<StringVariable<8>>("USDC".to_string());
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert!(output.read::<BoolVariable>());
+        assert!(!output.read::<BoolVariable>());
+        assert_eq!(output.read::<StringVariable<8>>(), "USDC");
+    }
+
+    #[test]
+    #[should_panic(expected = "is not ASCII")]
+    fn test_string_non_ascii() {
+        StringVariable::<8>::elements::<GoldilocksField>("héllo".to_string());
+    }
+}
@@ -1,13 +1,18 @@
 use std::fmt::Debug;
 
+use plonky2::field::types::{Field, Field64, PrimeField64};
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::target::Target;
 use serde::{Deserialize, Serialize};
 
-use super::CircuitVariable;
+use super::{CircuitVariable, U32Variable, ValueStream, VariableStream};
 use crate::backend::circuit::PlonkParameters;
 use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::hint::simple::hint::Hint;
 use crate::frontend::ops::{Add, Div, Mul, Neg, One, Sub, Zero};
+use crate::frontend::uint::num::biguint::{BigUintTarget, CircuitBuilderBiguint};
+use crate::frontend::uint::num::u32::gadgets::arithmetic_u32::U32Target;
+use crate::frontend::uint::uint64::U64Variable;
 
 /// A variable in the circuit. It represents a value between `[0, 2**64 - 2**32 + 1)`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -116,3 +121,321 @@ impl<L: PlonkParameters<D>, const D: usize> One<L, D> for Variable {
         Variable(builder.api.one())
     }
 }
+
+/// Computes the quotient and remainder of the canonical representative of a field element by a
+/// constant. Its outputs are unconstrained, so it is only used through `div_rem_const`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct DivRemConstHint {
+    divisor: u64,
+}
+
+impl<L: PlonkParameters<D>, const D: usize> Hint<L, D> for DivRemConstHint {
+    fn hint(&self, input_stream: &mut ValueStream<L, D>, output_stream: &mut ValueStream<L, D>) {
+        let a = input_stream.read_value::<Variable>().to_canonical_u64();
+        output_stream.write_value::<Variable>(L::Field::from_canonical_u64(a / self.divisor));
+        output_stream.write_value::<Variable>(L::Field::from_canonical_u64(a % self.divisor));
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Returns the quotient and remainder of the integer division of the canonical representatives
+    /// of `a` and `b`.
+    ///
+    /// Both operands are decomposed into range-checked 32-bit limbs, and the quotient and
+    /// remainder are constrained with `a == q * b + r` and `r < b` over the integers. Dividing by
+    /// zero is unsatisfiable.
+    pub fn div_rem(&mut self, a: Variable, b: Variable) -> (Variable, Variable) {
+        let a = U64Variable::from_variable(self, a);
+        let b = U64Variable::from_variable(self, b);
+        let a_biguint = BigUintTarget {
+            limbs: a.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+        };
+        let b_biguint = BigUintTarget {
+            limbs: b.limbs.iter().map(|x| U32Target::from(*x)).collect(),
+        };
+        let (quotient, remainder) = self.api.div_rem_biguint(&a_biguint, &b_biguint);
+
+        // The quotient is at most `a` and the remainder is less than `b`, so both are less than
+        // the field modulus.
+        let quotient = U64Variable {
+            limbs: [quotient.limbs[0].into(), quotient.limbs[1].into()],
+        };
+        let remainder = U64Variable {
+            limbs: [remainder.limbs[0].into(), remainder.limbs[1].into()],
+        };
+        (
+            quotient.to_variable_with_overflow(self),
+            remainder.to_variable_with_overflow(self),
+        )
+    }
+
+    /// Returns the quotient and remainder of the integer division of the canonical representative
+    /// of `a` by a constant.
+    ///
+    /// For divisors below 2^32, the identity `a == q * b + r` is checked directly in the field,
+    /// with `q` and `r` bounded so that `q * b + r` cannot exceed the field modulus. Larger
+    /// divisors fall back to `div_rem`.
+    pub fn div_rem_const(&mut self, a: Variable, divisor: u64) -> (Variable, Variable) {
+        assert!(divisor != 0, "division by zero");
+        let order = L::Field::ORDER;
+        if divisor == 1 {
+            let zero = self.zero();
+            return (a, zero);
+        }
+        if divisor >= order {
+            let zero = self.zero();
+            return (zero, a);
+        }
+        if divisor >= 1 << 32 {
+            let b = self.constant::<Variable>(L::Field::from_canonical_u64(divisor));
+            return self.div_rem(a, b);
+        }
+
+        let mut input_stream = VariableStream::new();
+        input_stream.write(&a);
+        let output_stream = self.hint(input_stream, DivRemConstHint { divisor });
+        let quotient = output_stream.read::<Variable>(self);
+        let remainder = output_stream.read::<Variable>(self);
+
+        // The largest quotient of a field element, and the largest remainder allowed with it.
+        let max_quotient = (order - 1) / divisor;
+        let max_remainder_at_max_quotient = (order - 1) - max_quotient * divisor;
+
+        let max_quotient_variable =
+            self.constant::<Variable>(L::Field::from_canonical_u64(max_quotient));
+        self.assert_at_most(quotient, max_quotient_variable, bit_length(max_quotient));
+
+        let is_max_quotient = self.is_equal(quotient, max_quotient_variable);
+        let max_remainder = self.constant::<Variable>(L::Field::from_canonical_u64(divisor - 1));
+        let max_remainder_at_max_quotient =
+            self.constant::<Variable>(L::Field::from_canonical_u64(max_remainder_at_max_quotient));
+        let remainder_bound = self.select(
+            is_max_quotient,
+            max_remainder_at_max_quotient,
+            max_remainder,
+        );
+        self.assert_at_most(remainder, remainder_bound, bit_length(divisor - 1));
+
+        // With the bounds above, `q * b + r` is less than the field modulus, so the identity holds
+        // over the integers.
+        let b = self.constant::<Variable>(L::Field::from_canonical_u64(divisor));
+        let product = self.mul(quotient, b);
+        let sum = self.add(product, remainder);
+        self.assert_is_equal(sum, a);
+
+        (quotient, remainder)
+    }
+
+    /// Returns `base^e` for a constant exponent, by square-and-multiply over the bits of `e`.
+    pub fn exp_const(&mut self, base: Variable, e: u64) -> Variable {
+        Variable(self.api.exp_u64(base.0, e))
+    }
+
+    /// Returns `base^e`, where `e` is decomposed into `max_bits` bits and each conditional
+    /// multiplication is muxed on a bit. The circuit size grows with `max_bits`, and an exponent of
+    /// `2^max_bits` or more is unsatisfiable.
+    pub fn exp(&mut self, base: Variable, e: U32Variable, max_bits: usize) -> Variable {
+        assert!(max_bits <= 32, "the exponent has at most 32 bits");
+        Variable(self.api.exp(base.0, e.variable.0, max_bits))
+    }
+
+    /// Returns `2^e` in the field. Since the base is constant, each bit of `e` costs a single
+    /// arithmetic gate.
+    pub fn exp2(&mut self, e: U32Variable) -> Variable {
+        let bits = self.api.split_le(e.variable.0, 32);
+        let mut result = self.api.one();
+        for (i, bit) in bits.into_iter().enumerate() {
+            // result * (1 + bit * (2^(2^i) - 1)) == result + (2^(2^i) - 1) * bit * result
+            let factor = L::Field::TWO.exp_power_of_2(i) - L::Field::ONE;
+            result = self
+                .api
+                .arithmetic(factor, L::Field::ONE, bit.target, result, result);
+        }
+        Variable(result)
+    }
+
+    /// Asserts that `x <= bound`, where `bound < 2^nb_bits < 2^63`.
+    fn assert_at_most(&mut self, x: Variable, bound: Variable, nb_bits: usize) {
+        self.api.range_check(x.0, nb_bits);
+        let difference = self.sub(bound, x);
+        self.api.range_check(difference.0, nb_bits);
+    }
+}
+
+/// The number of bits needed to represent `x`, and at least one.
+fn bit_length(x: u64) -> usize {
+    ((u64::BITS - x.leading_zeros()) as usize).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Sample;
+    use rand::Rng;
+
+    use super::*;
+    use crate::prelude::{DefaultBuilder, GoldilocksField};
+
+    type F = GoldilocksField;
+
+    #[test]
+    fn test_div_rem() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        let (quotient, remainder) = builder.div_rem(a, b);
+        builder.write(quotient);
+        builder.write(remainder);
+        let circuit = builder.build();
+
+        let max = F::ORDER - 1;
+        let cases = [
+            (max, 1),
+            (max, 2),
+            (max, max),
+            (max - 1, max),
+            (max, 1 << 32),
+            (7, 3),
+            (0, 5),
+            (3, max),
+            (u32::MAX as u64, u32::MAX as u64 + 2),
+        ];
+        for (a, b) in cases {
+            let mut input = circuit.input();
+            input.write::<Variable>(F::from_canonical_u64(a));
+            input.write::<Variable>(F::from_canonical_u64(b));
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<Variable>(), F::from_canonical_u64(a / b));
+            assert_eq!(output.read::<Variable>(), F::from_canonical_u64(a % b));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_div_rem_by_zero() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        let b = builder.read::<Variable>();
+        builder.div_rem(a, b);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Variable>(F::from_canonical_u64(7));
+        input.write::<Variable>(F::ZERO);
+        circuit.prove(&input);
+    }
+
+    #[test]
+    fn test_div_rem_const() {
+        let divisors = [
+            1,
+            2,
+            3,
+            10,
+            u32::MAX as u64,
+            1 << 32,
+            F::ORDER - 1,
+            u64::MAX,
+        ];
+
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<Variable>();
+        for divisor in divisors {
+            let (quotient, remainder) = builder.div_rem_const(a, divisor);
+            builder.write(quotient);
+            builder.write(remainder);
+        }
+        let circuit = builder.build();
+
+        let max = F::ORDER - 1;
+        for a in [0, 1, 9, 12345678901234567, max - 1, max] {
+            let mut input = circuit.input();
+            input.write::<Variable>(F::from_canonical_u64(a));
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            for divisor in divisors {
+                assert_eq!(
+                    output.read::<Variable>(),
+                    F::from_canonical_u64(a / divisor)
+                );
+                assert_eq!(
+                    output.read::<Variable>(),
+                    F::from_canonical_u64(a % divisor)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_exp() {
+        let exponents = [0, 1, 2, 3, 7, 64, 0xdead_beef_1234_5678];
+        let max_bits = 12;
+
+        let mut builder = DefaultBuilder::new();
+        let base = builder.read::<Variable>();
+        let e = builder.read::<U32Variable>();
+        for exponent in exponents {
+            let power = builder.exp_const(base, exponent);
+            builder.write(power);
+        }
+        let power = builder.exp(base, e, max_bits);
+        let power_of_two = builder.exp2(e);
+        builder.write(power);
+        builder.write(power_of_two);
+        let circuit = builder.build();
+
+        let mut rng = rand::thread_rng();
+        let cases = [
+            (F::ZERO, 0),
+            (F::ZERO, 5),
+            (F::TWO, 0),
+            (F::NEG_ONE, 3),
+            (F::rand(), (1 << max_bits) - 1),
+            (F::rand(), rng.gen_range(0..1 << max_bits)),
+        ];
+        for (base, e) in cases {
+            let mut input = circuit.input();
+            input.write::<Variable>(base);
+            input.write::<U32Variable>(e);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            for exponent in exponents {
+                assert_eq!(output.read::<Variable>(), base.exp_u64(exponent));
+            }
+            assert_eq!(output.read::<Variable>(), base.exp_u64(e as u64));
+            assert_eq!(output.read::<Variable>(), F::TWO.exp_u64(e as u64));
+        }
+    }
+
+    #[test]
+    fn test_exp2() {
+        let mut builder = DefaultBuilder::new();
+        let e = builder.read::<U32Variable>();
+        let power = builder.exp2(e);
+        builder.write(power);
+        let circuit = builder.build();
+
+        for e in [0, 1, 31, 63, 64, 191, 192, u32::MAX] {
+            let mut input = circuit.input();
+            input.write::<U32Variable>(e);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<Variable>(), F::TWO.exp_u64(e as u64));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_exp_exponent_too_large() {
+        let mut builder = DefaultBuilder::new();
+        let base = builder.read::<Variable>();
+        let e = builder.read::<U32Variable>();
+        builder.exp(base, e, 4);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<Variable>(F::TWO);
+        input.write::<U32Variable>(16);
+        circuit.prove(&input);
+    }
+}
@@ -0,0 +1,297 @@
+use array_macro::array;
+use plonky2::hash::hash_types::RichField;
+
+use super::{
+    BoolVariable, ByteVariable, Bytes32Variable, BytesVariable, CircuitVariable, Variable,
+};
+use crate::backend::circuit::PlonkParameters;
+use crate::frontend::builder::CircuitBuilder;
+use crate::frontend::vars::U32Variable;
+
+/// A byte string whose length is only known at proving time, up to `MAX` bytes.
+///
+/// The bytes past `len` are padding. They are unconstrained and ignored by `get`, `is_equal` and
+/// the hash functions.
+#[derive(Debug, Clone, Copy)]
+pub struct VariableBytesVariable<const MAX: usize> {
+    pub data: BytesVariable<MAX>,
+    pub len: U32Variable,
+}
+
+impl<const MAX: usize> CircuitVariable for VariableBytesVariable<MAX> {
+    type ValueType<F: RichField> = Vec<u8>;
+
+    fn format_value<F: RichField>(value: &Vec<u8>) -> String {
+        format!("0x{}", hex::encode(value))
+    }
+
+    fn init_unsafe<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> Self {
+        Self {
+            data: BytesVariable::init_unsafe(builder),
+            len: U32Variable::init_unsafe(builder),
+        }
+    }
+
+    fn variables(&self) -> Vec<Variable> {
+        let mut variables = self.data.variables();
+        variables.extend(self.len.variables());
+        variables
+    }
+
+    fn from_variables_unsafe(variables: &[Variable]) -> Self {
+        assert_eq!(variables.len(), Self::nb_elements());
+        let (data, len) = variables.split_at(BytesVariable::<MAX>::nb_elements());
+        Self {
+            data: BytesVariable::from_variables_unsafe(data),
+            len: U32Variable::from_variables_unsafe(len),
+        }
+    }
+
+    fn assert_is_valid<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) {
+        self.data.assert_is_valid(builder);
+        self.len.assert_is_valid(builder);
+        Self::assert_len_is_valid(builder, self.len);
+    }
+
+    fn nb_elements() -> usize {
+        BytesVariable::<MAX>::nb_elements() + U32Variable::nb_elements()
+    }
+
+    fn elements<F: RichField>(value: Self::ValueType<F>) -> Vec<F> {
+        assert!(
+            value.len() <= MAX,
+            "byte string is too long: expected at most {} got {}",
+            MAX,
+            value.len()
+        );
+        let mut data = [0u8; MAX];
+        data[..value.len()].copy_from_slice(&value);
+        let mut elements = BytesVariable::<MAX>::elements::<F>(data);
+        elements.extend(U32Variable::elements::<F>(value.len() as u32));
+        elements
+    }
+
+    fn from_elements<F: RichField>(elements: &[F]) -> Self::ValueType<F> {
+        assert_eq!(elements.len(), Self::nb_elements());
+        let (data, len) = elements.split_at(BytesVariable::<MAX>::nb_elements());
+        let data = BytesVariable::<MAX>::from_elements::<F>(data);
+        let len = U32Variable::from_elements::<F>(len) as usize;
+        assert!(len <= MAX, "byte string length {} exceeds {}", len, MAX);
+        data[..len].to_vec()
+    }
+}
+
+impl<const MAX: usize> VariableBytesVariable<MAX> {
+    /// Creates a byte string from the first `len` bytes of `data`, asserting that `len <= MAX`.
+    pub fn new<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        data: [ByteVariable; MAX],
+        len: U32Variable,
+    ) -> Self {
+        Self::assert_len_is_valid(builder, len);
+        Self {
+            data: BytesVariable(data),
+            len,
+        }
+    }
+
+    /// Returns the byte at index `i`, asserting that `i < len`.
+    pub fn get<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        i: U32Variable,
+    ) -> ByteVariable {
+        let is_in_bounds = builder.lt(i, self.len);
+        let _true = builder._true();
+        builder.assert_is_equal(is_in_bounds, _true);
+        builder.select_array(&self.data.0, i.variable)
+    }
+
+    /// Returns whether two byte strings have the same length and the same bytes up to it.
+    pub fn is_equal<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+        other: &Self,
+    ) -> BoolVariable {
+        let mut result = builder.is_equal(self.len, other.len);
+        let mask = self.mask(builder);
+        for i in 0..MAX {
+            let byte_eq = builder.is_equal(self.data[i], other.data[i]);
+            let is_padding = builder.not(mask[i]);
+            let byte_eq = builder.or(byte_eq, is_padding);
+            result = builder.and(result, byte_eq);
+        }
+        result
+    }
+
+    /// Returns `[i < len for i in 0..MAX]`. Only sound if `len <= MAX`.
+    fn mask<L: PlonkParameters<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<L, D>,
+    ) -> [BoolVariable; MAX] {
+        let mut is_in_range = builder._true();
+        array![i => {
+            let index = builder.constant::<U32Variable>(i as u32);
+            let is_end = builder.is_equal(index, self.len);
+            let is_not_end = builder.not(is_end);
+            is_in_range = builder.and(is_in_range, is_not_end);
+            is_in_range
+        }; MAX]
+    }
+
+    fn assert_len_is_valid<L: PlonkParameters<D>, const D: usize>(
+        builder: &mut CircuitBuilder<L, D>,
+        len: U32Variable,
+    ) {
+        let max = builder.constant::<U32Variable>(MAX as u32);
+        let is_valid = builder.lte(len, max);
+        let _true = builder._true();
+        builder.assert_is_equal(is_valid, _true);
+    }
+}
+
+impl<L: PlonkParameters<D>, const D: usize> CircuitBuilder<L, D> {
+    /// Computes the SHA256 hash of a variable length byte string using the curta accelerator.
+    pub fn curta_sha256_variable_bytes<const MAX: usize>(
+        &mut self,
+        input: &VariableBytesVariable<MAX>,
+    ) -> Bytes32Variable {
+        self.curta_sha256_variable(&input.data.0, input.len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use ethers::types::H256;
+
+    use super::*;
+    use crate::prelude::*;
+    use crate::utils::hash::sha256;
+
+    const MAX: usize = 8;
+
+    #[test]
+    fn test_variable_bytes_get() {
+        let mut builder = DefaultBuilder::new();
+        let bytes = builder.read::<VariableBytesVariable<MAX>>();
+        let first = builder.constant::<U32Variable>(0);
+        let last = builder.constant::<U32Variable>(MAX as u32 - 1);
+        let first = bytes.get(&mut builder, first);
+        let last = bytes.get(&mut builder, last);
+        builder.write(first);
+        builder.write(last);
+        builder.write(bytes);
+        let circuit = builder.build();
+
+        let value = (1..=MAX as u8).collect::<Vec<_>>();
+        let mut input = circuit.input();
+        input.write::<VariableBytesVariable<MAX>>(value.clone());
+        let (proof, mut output) = circuit.prove(&input);
+        circuit.verify(&proof, &input, &output);
+        assert_eq!(output.read::<ByteVariable>(), 1);
+        assert_eq!(output.read::<ByteVariable>(), MAX as u8);
+        assert_eq!(output.read::<VariableBytesVariable<MAX>>(), value);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_variable_bytes_get_out_of_bounds() {
+        let mut builder = DefaultBuilder::new();
+        let bytes = builder.read::<VariableBytesVariable<MAX>>();
+        let index = builder.constant::<U32Variable>(0);
+        bytes.get(&mut builder, index);
+        let circuit = builder.build();
+
+        let mut input = circuit.input();
+        input.write::<VariableBytesVariable<MAX>>(vec![]);
+        circuit.prove(&input);
+    }
+
+    #[test]
+    fn test_variable_bytes_is_equal() {
+        let mut builder = DefaultBuilder::new();
+        let a = builder.read::<[ByteVariable; MAX]>();
+        let a_len = builder.read::<U32Variable>();
+        let b = builder.read::<[ByteVariable; MAX]>();
+        let b_len = builder.read::<U32Variable>();
+        let a = VariableBytesVariable::new(&mut builder, a, a_len);
+        let b = VariableBytesVariable::new(&mut builder, b, b_len);
+        let is_equal = a.is_equal(&mut builder, &b);
+        builder.write(is_equal);
+        let circuit = builder.build();
+
+        let cases = [
+            // Same content, different padding.
+            (
+                [1, 2, 3, 0, 0, 0, 0, 0],
+                3,
+                [1, 2, 3, 9, 9, 9, 9, 9],
+                3,
+                true,
+            ),
+            // Empty strings.
+            ([5; MAX], 0, [6; MAX], 0, true),
+            // Full strings.
+            ([7; MAX], MAX as u32, [7; MAX], MAX as u32, true),
+            (
+                [7; MAX],
+                MAX as u32,
+                [7, 7, 7, 7, 7, 7, 7, 8],
+                MAX as u32,
+                false,
+            ),
+            // A prefix of another string.
+            (
+                [1, 2, 3, 4, 0, 0, 0, 0],
+                3,
+                [1, 2, 3, 4, 0, 0, 0, 0],
+                4,
+                false,
+            ),
+        ];
+        for (a, a_len, b, b_len, expected) in cases {
+            let mut input = circuit.input();
+            input.write::<[ByteVariable; MAX]>(a);
+            input.write::<U32Variable>(a_len);
+            input.write::<[ByteVariable; MAX]>(b);
+            input.write::<U32Variable>(b_len);
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<BoolVariable>(), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_variable_bytes_too_long() {
+        VariableBytesVariable::<MAX>::elements::<GoldilocksField>(vec![0; MAX + 1]);
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_curta_sha256_variable_bytes() {
+        env::set_var("RUST_LOG", "debug");
+        env_logger::try_init().unwrap_or_default();
+
+        let mut builder = DefaultBuilder::new();
+        let bytes = builder.read::<VariableBytesVariable<MAX>>();
+        let digest = builder.curta_sha256_variable_bytes(&bytes);
+        builder.write(digest);
+        let circuit = builder.build();
+
+        for value in [vec![], vec![1, 2, 3], vec![0xff; MAX]] {
+            let mut input = circuit.input();
+            input.write::<VariableBytesVariable<MAX>>(value.clone());
+            let (proof, mut output) = circuit.prove(&input);
+            circuit.verify(&proof, &input, &output);
+            assert_eq!(output.read::<Bytes32Variable>(), H256::from(sha256(&value)));
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use plonky2::field::types::PrimeField64;
+
+/// The byte order in which each field element of a digest is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Big,
+    Little,
+}
+
+/// How a digest made of field elements is rendered as a hex string.
+///
+/// Every element is written as the 8 bytes of its canonical u64 representation, in the given byte
+/// order, and the elements are concatenated in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigestFormat {
+    pub endianness: Endianness,
+    /// Whether the string starts with `0x`.
+    pub prefix: bool,
+    /// Whether the hex digits are uppercase. The `0x` prefix is always lowercase.
+    pub uppercase: bool,
+}
+
+impl DigestFormat {
+    /// Big-endian, `0x`-prefixed and lowercase, which is what `hex!` produces.
+    pub const BE_PREFIXED: Self = Self {
+        endianness: Endianness::Big,
+        prefix: true,
+        uppercase: false,
+    };
+
+    /// Little-endian, `0x`-prefixed and lowercase.
+    pub const LE_PREFIXED: Self = Self {
+        endianness: Endianness::Little,
+        prefix: true,
+        uppercase: false,
+    };
+}
+
+/// Encodes a digest made of field elements as a hex string in the given format.
+pub fn encode_digest<F: PrimeField64>(elements: &[F], format: DigestFormat) -> String {
+    let bytes = elements
+        .iter()
+        .flat_map(|e| match format.endianness {
+            Endianness::Big => e.to_canonical_u64().to_be_bytes(),
+            Endianness::Little => e.to_canonical_u64().to_le_bytes(),
+        })
+        .collect::<Vec<u8>>();
+    let digits = match format.uppercase {
+        true => hex::encode_upper(bytes),
+        false => hex::encode(bytes),
+    };
+    match format.prefix {
+        true => format!("0x{}", digits),
+        false => digits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Field;
+
+    use super::*;
+
+    fn digest() -> Vec<GoldilocksField> {
+        vec![
+            GoldilocksField::from_canonical_u64(0x0123456789abcdef),
+            GoldilocksField::ONE,
+        ]
+    }
+
+    #[test]
+    fn test_encode_digest_formats() {
+        let digest = digest();
+        assert_eq!(
+            encode_digest(&digest, DigestFormat::BE_PREFIXED),
+            "0x0123456789abcdef0000000000000001"
+        );
+        assert_eq!(
+            encode_digest(&digest, DigestFormat::LE_PREFIXED),
+            "0xefcdab89674523010100000000000000"
+        );
+        assert_eq!(
+            encode_digest(
+                &digest,
+                DigestFormat {
+                    endianness: Endianness::Big,
+                    prefix: false,
+                    uppercase: true,
+                }
+            ),
+            "0123456789ABCDEF0000000000000001"
+        );
+        assert_eq!(
+            encode_digest(
+                &digest,
+                DigestFormat {
+                    endianness: Endianness::Little,
+                    prefix: true,
+                    uppercase: true,
+                }
+            ),
+            "0xEFCDAB89674523010100000000000000"
+        );
+    }
+}
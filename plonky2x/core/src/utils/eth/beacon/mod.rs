@@ -1,15 +1,22 @@
-use core::time::Duration;
+use std::collections::BTreeMap;
+use std::io::{self, BufReader, Cursor, Read};
+use std::ops::Range;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use ethers::types::{H256, U256};
+use futures::{StreamExt, TryStreamExt};
 use itertools::Itertools;
 use log::{debug, info};
 use num::BigInt;
-use reqwest::blocking::Client;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde_json::Value;
 use serde_with::serde_as;
+use tokio::sync::mpsc;
 
+pub mod block;
+
+use super::cache::RpcCacheMode;
 use crate::utils::hash::sha256;
 use crate::utils::reqwest::ReqwestClient;
 use crate::utils::serde::deserialize_bigint;
@@ -19,6 +26,40 @@ use crate::utils::serde::deserialize_bigint;
 pub struct BeaconClient {
     rpc_url: String,
     client: ReqwestClient,
+    cache: RpcCacheMode,
+}
+
+/// The maximum length of the comma-separated validator ids of a request, which keeps the url well
+/// below the usual 8 KB limit of the servers.
+const MAX_VALIDATOR_IDS_LEN: usize = 4000;
+
+/// The maximum number of concurrent requests of `get_validators_range`.
+const MAX_CONCURRENT_VALIDATOR_REQUESTS: usize = 8;
+
+/// The maximum number of chunks of a response body that are downloaded ahead of its parsing.
+const MAX_BUFFERED_CHUNKS: usize = 16;
+
+/// A reader of the chunks of a response body, which are sent by the task downloading it, so that
+/// the body is deserialized by a blocking reader while it is downloaded.
+struct ChunkReader {
+    chunks: mpsc::Receiver<reqwest::Result<Vec<u8>>>,
+    chunk: Cursor<Vec<u8>>,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let read = self.chunk.read(buf)?;
+            if read > 0 || buf.is_empty() {
+                return Ok(read);
+            }
+            match self.chunks.blocking_recv() {
+                Some(Ok(chunk)) => self.chunk = Cursor::new(chunk),
+                Some(Err(e)) => return Err(io::Error::new(io::ErrorKind::Other, e)),
+                None => return Ok(0),
+            }
+        }
+    }
 }
 
 /// The data format returned by official Eth Beacon Node APIs.
@@ -155,6 +196,83 @@ impl BeaconValidator {
     }
 }
 
+/// A validator returned by the official Beacon Node API.
+/// https://ethereum.github.io/beacon-APIs/#/Beacon/getStateValidators
+#[derive(Debug, Deserialize)]
+struct BeaconApiValidator {
+    pub index: String,
+    pub validator: BeaconApiValidatorFields,
+}
+
+#[derive(Debug, Deserialize)]
+struct BeaconApiValidatorFields {
+    pub pubkey: String,
+    pub withdrawal_credentials: String,
+    pub effective_balance: String,
+    pub slashed: bool,
+    pub activation_eligibility_epoch: String,
+    pub activation_epoch: String,
+    pub exit_epoch: String,
+    pub withdrawable_epoch: String,
+}
+
+impl BeaconApiValidator {
+    /// Returns the index of the validator and the validator.
+    fn into_indexed_validator(self) -> Result<(u64, BeaconValidator)> {
+        let fields = self.validator;
+        let validator = BeaconValidator {
+            pubkey: fields.pubkey,
+            withdrawal_credentials: fields.withdrawal_credentials,
+            effective_balance: fields.effective_balance.parse()?,
+            slashed: fields.slashed,
+            activation_eligibility_epoch: fields.activation_eligibility_epoch,
+            activation_epoch: fields.activation_epoch,
+            exit_epoch: fields.exit_epoch,
+            withdrawable_epoch: fields.withdrawable_epoch,
+        };
+        Ok((self.index.parse()?, validator))
+    }
+}
+
+/// Splits the validator indices `start..start + count` into ranges whose comma-separated ids fit
+/// in `MAX_VALIDATOR_IDS_LEN`.
+fn validator_id_chunks(start: u64, count: u64) -> Vec<Range<u64>> {
+    let end = start + count;
+    let mut chunks = Vec::new();
+    let mut chunk_start = start;
+    let mut len = 0;
+    for idx in start..end {
+        // The length of the id and of the comma before it.
+        let id_len = idx.to_string().len() + 1;
+        if len + id_len - 1 > MAX_VALIDATOR_IDS_LEN && idx > chunk_start {
+            chunks.push(chunk_start..idx);
+            chunk_start = idx;
+            len = 0;
+        }
+        len += id_len;
+    }
+    if chunk_start < end {
+        chunks.push(chunk_start..end);
+    }
+    chunks
+}
+
+/// Returns the validators at the indices `range` in index order, or an error naming the first
+/// index missing from `validators`.
+fn validators_in_order(
+    range: Range<u64>,
+    validators: Vec<(u64, BeaconValidator)>,
+) -> Result<Vec<BeaconValidator>> {
+    let mut by_index = validators.into_iter().collect::<BTreeMap<_, _>>();
+    range
+        .map(|idx| {
+            by_index
+                .remove(&idx)
+                .ok_or_else(|| anyhow!("validator {} is missing from the response", idx))
+        })
+        .collect()
+}
+
 /// The beacon validator balance returned by the official Beacon Node API.
 /// https://ethereum.github.io/beacon-APIs/#/Beacon/getStateValidatorBalances
 #[derive(Debug, Deserialize)]
@@ -342,14 +460,85 @@ pub struct GetBeaconHeadersFromOffsetRange {
 }
 
 impl BeaconClient {
-    /// Creates a new BeaconClient based on a rpc url.
+    /// Creates a new BeaconClient based on a rpc url, with the cache mode of the `RPC_CACHE_MODE`
+    /// environment variable.
     pub fn new(rpc_url: String) -> Self {
+        Self::with_cache(rpc_url, RpcCacheMode::from_env())
+    }
+
+    /// Creates a new BeaconClient based on a rpc url, which records or replays its responses
+    /// according to `cache`. The url isn't used to replay responses.
+    pub fn with_cache(rpc_url: String, cache: RpcCacheMode) -> Self {
         Self {
             rpc_url,
             client: ReqwestClient::new(),
+            cache,
         }
     }
 
+    /// Returns the path of `endpoint` without the rpc url, which identifies its responses in the
+    /// cache.
+    fn cache_params(&self, endpoint: &str) -> Value {
+        Value::String(endpoint.trim_start_matches(&self.rpc_url).to_string())
+    }
+
+    /// Fetches the JSON response of `endpoint`, or replays it from the cache.
+    fn fetch_json<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T> {
+        if self.cache == RpcCacheMode::Live {
+            // Deserialize the body as it is read, without holding the whole JSON in memory.
+            let response = BufReader::new(self.client.fetch(endpoint)?);
+            return Ok(serde_json::from_reader(response)?);
+        }
+        let params = self.cache_params(endpoint);
+        let response = match self.cache.replay("GET", &params)? {
+            Some(response) => response,
+            None => {
+                let response: Value = self.client.fetch(endpoint)?.json()?;
+                self.cache.record("GET", &params, &response)?;
+                response
+            }
+        };
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Fetches the JSON response of `endpoint` asynchronously, or replays it from the cache.
+    async fn fetch_json_async<T: DeserializeOwned + Send + 'static>(
+        &self,
+        endpoint: &str,
+    ) -> Result<T> {
+        if self.cache == RpcCacheMode::Live {
+            // Deserialize the body as its chunks arrive, without holding the whole JSON in memory.
+            let mut response = self.client.fetch_async(endpoint).await?;
+            let (sender, chunks) = mpsc::channel(MAX_BUFFERED_CHUNKS);
+            let parser = tokio::task::spawn_blocking(move || {
+                let reader = ChunkReader {
+                    chunks,
+                    chunk: Cursor::new(Vec::new()),
+                };
+                serde_json::from_reader::<_, T>(BufReader::new(reader))
+            });
+            while let Some(chunk) = response.chunk().await.transpose() {
+                let failed = chunk.is_err();
+                // The parser drops the receiver if it fails before the end of the body.
+                if sender.send(chunk.map(|c| c.to_vec())).await.is_err() || failed {
+                    break;
+                }
+            }
+            drop(sender);
+            return Ok(parser.await??);
+        }
+        let params = self.cache_params(endpoint);
+        let response = match self.cache.replay("GET", &params)? {
+            Some(response) => response,
+            None => {
+                let response: Value = self.client.fetch_async(endpoint).await?.json().await?;
+                self.cache.record("GET", &params, &response)?;
+                response
+            }
+        };
+        Ok(serde_json::from_value(response)?)
+    }
+
     /// Gets the block root at `head`.
     pub fn get_finalized_block_root_sync(&self) -> Result<String> {
         self.get_finalized_block_root()
@@ -358,8 +547,7 @@ impl BeaconClient {
     /// Gets the latest finalized block root asynchronously.
     pub fn get_finalized_block_root(&self) -> Result<String> {
         let endpoint = format!("{}/eth/v1/beacon/headers/finalized", self.rpc_url);
-        let response = self.client.fetch(&endpoint)?;
-        let parsed: Value = response.json()?;
+        let parsed: Value = self.fetch_json(&endpoint)?;
 
         if let Value::Object(data) = &parsed["data"] {
             return Ok(data["root"].as_str().unwrap().to_string());
@@ -371,8 +559,7 @@ impl BeaconClient {
     /// Gets the latest finalized slot asynchronously.
     pub fn get_finalized_slot(&self) -> Result<String> {
         let endpoint = format!("{}/eth/v1/beacon/headers/finalized", self.rpc_url);
-        let response = self.client.fetch(&endpoint)?;
-        let parsed: Value = response.json()?;
+        let parsed: Value = self.fetch_json(&endpoint)?;
 
         if let Value::Object(data) = &parsed["data"] {
             return Ok(data["header"]["message"]["slot"]
@@ -395,8 +582,8 @@ impl BeaconClient {
             self.rpc_url, beacon_id, nb_balances
         );
         info!("{}", endpoint);
-        let response = self.client.fetch_async(&endpoint).await?;
-        let response: CustomResponse<GetBeaconPartialValidatorsRoot> = response.json().await?;
+        let response: CustomResponse<GetBeaconPartialValidatorsRoot> =
+            self.fetch_json_async(&endpoint).await?;
         assert!(response.success);
         Ok(response.result)
     }
@@ -414,8 +601,7 @@ impl BeaconClient {
             self.rpc_url, batch_size, limit, beacon_id
         );
         info!("{}", endpoint);
-        let response = self.client.fetch_async(&endpoint).await?;
-        let response: CustomResponse<Vec<String>> = response.json().await?;
+        let response: CustomResponse<Vec<String>> = self.fetch_json_async(&endpoint).await?;
         assert!(response.success);
         Ok(response.result)
     }
@@ -433,8 +619,8 @@ impl BeaconClient {
             self.rpc_url, batch_size, limit, subtree_hash
         );
         info!("{}", endpoint);
-        let response = self.client.fetch_async(&endpoint).await?;
-        let response: CustomResponse<Vec<BeaconValidator>> = response.json().await?;
+        let response: CustomResponse<Vec<BeaconValidator>> =
+            self.fetch_json_async(&endpoint).await?;
         assert!(response.success);
         Ok(response.result)
     }
@@ -444,12 +630,7 @@ impl BeaconClient {
     pub fn get_validators_root(&self, beacon_id: String) -> Result<GetBeaconValidatorsRoot> {
         let endpoint = format!("{}/api/beacon/proof/validator/{}", self.rpc_url, beacon_id);
         debug!("{}", endpoint);
-        let client = Client::new();
-        let response = client
-            .get(&endpoint)
-            .timeout(Duration::new(120, 0))
-            .send()?;
-        let response: CustomResponse<GetBeaconValidatorsRoot> = response.json()?;
+        let response: CustomResponse<GetBeaconValidatorsRoot> = self.fetch_json(&endpoint)?;
         assert!(response.success);
         debug!("done {}", endpoint);
         Ok(response.result)
@@ -467,8 +648,7 @@ impl BeaconClient {
             self.rpc_url, beacon_id, validator_idx
         );
         debug!("{}", endpoint);
-        let response = self.client.fetch(&endpoint)?;
-        let response: CustomResponse<GetBeaconValidatorWitness> = response.json()?;
+        let response: CustomResponse<GetBeaconValidatorWitness> = self.fetch_json(&endpoint)?;
         assert!(response.success);
         Ok(response.result)
     }
@@ -484,11 +664,51 @@ impl BeaconClient {
             self.rpc_url, beacon_id, start_idx, end_idx
         );
         debug!("{}", endpoint);
-        let response = self.client.fetch(&endpoint)?;
-        let response: GetBeaconValidatorBatchWitness = response.json()?;
+        let response: GetBeaconValidatorBatchWitness = self.fetch_json(&endpoint)?;
         Ok(response.validators)
     }
 
+    /// Gets the `count` validators from `start_index` at `state_id` in index order, with the
+    /// official Beacon Node API. The indices are queried in chunks which keep the urls short, with
+    /// up to `MAX_CONCURRENT_VALIDATOR_REQUESTS` chunks in flight. A validator missing from the
+    /// responses is an error.
+    pub async fn get_validators_range(
+        &self,
+        state_id: String,
+        start_index: u64,
+        count: u64,
+    ) -> Result<Vec<BeaconValidator>> {
+        let chunks = futures::stream::iter(validator_id_chunks(start_index, count))
+            .map(|range| self.get_validators_chunk(&state_id, range))
+            .buffered(MAX_CONCURRENT_VALIDATOR_REQUESTS)
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(chunks.into_iter().flatten().collect())
+    }
+
+    /// Gets the validators at the indices `range` at `state_id` in index order.
+    async fn get_validators_chunk(
+        &self,
+        state_id: &str,
+        range: Range<u64>,
+    ) -> Result<Vec<BeaconValidator>> {
+        let endpoint = format!(
+            "{}/eth/v1/beacon/states/{}/validators?id={}",
+            self.rpc_url,
+            state_id,
+            range.clone().join(",")
+        );
+        debug!("fetching validators {:?} at {}", range, state_id);
+        let response: BeaconData<Vec<BeaconApiValidator>> =
+            self.fetch_json_async(&endpoint).await?;
+        let validators = response
+            .data
+            .into_iter()
+            .map(BeaconApiValidator::into_indexed_validator)
+            .collect::<Result<Vec<_>>>()?;
+        validators_in_order(range, validators)
+    }
+
     /// Gets the state of a validator based on a beacon_id and index, including the SSZ proof from
     /// `validatorsRoot -> validator[validator_idx]`.
     pub fn get_validator(
@@ -501,8 +721,7 @@ impl BeaconClient {
             self.rpc_url, beacon_id, validator_idx
         );
         debug!("{}", endpoint);
-        let response = self.client.fetch(&endpoint)?;
-        let response: CustomResponse<GetBeaconValidator> = response.json()?;
+        let response: CustomResponse<GetBeaconValidator> = self.fetch_json(&endpoint)?;
         assert!(response.success);
         debug!("done {}", endpoint);
         Ok(response.result)
@@ -520,9 +739,7 @@ impl BeaconClient {
             self.rpc_url, beacon_id, pubkey
         );
         info!("{}", endpoint);
-        let client = Client::new();
-        let response = client.get(endpoint).timeout(Duration::new(120, 0)).send()?;
-        let response: CustomResponse<GetBeaconValidator> = response.json()?;
+        let response: CustomResponse<GetBeaconValidator> = self.fetch_json(&endpoint)?;
         assert!(response.success);
         Ok(response.result)
     }
@@ -531,8 +748,7 @@ impl BeaconClient {
     pub fn get_balances_root(&self, beacon_id: String) -> Result<GetBeaconBalancesRoot> {
         let endpoint = format!("{}/api/beacon/proof/balance/{}", self.rpc_url, beacon_id);
         info!("{}", endpoint);
-        let response = self.client.fetch(&endpoint)?;
-        let response: CustomResponse<GetBeaconBalancesRoot> = response.json()?;
+        let response: CustomResponse<GetBeaconBalancesRoot> = self.fetch_json(&endpoint)?;
         assert!(response.success);
         Ok(response.result)
     }
@@ -548,20 +764,15 @@ impl BeaconClient {
             self.rpc_url, beacon_id, nb_balances
         );
         info!("{}", endpoint);
-        let response = self.client.fetch_async(&endpoint).await?;
-        let response: CustomResponse<GetBeaconPartialBalancesRoot> = response.json().await?;
+        let response: CustomResponse<GetBeaconPartialBalancesRoot> =
+            self.fetch_json_async(&endpoint).await?;
         assert!(response.success);
         Ok(response.result)
     }
 
     pub fn get_balance_witness(&self, beacon_id: String, idx: u64) -> Result<u64> {
         let endpoint = format!("{}/api/beacon/balance/{}/{}", self.rpc_url, beacon_id, idx);
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .get(endpoint)
-            .timeout(Duration::from_secs(300))
-            .send()?;
-        let response: GetBeaconBalanceWitness = response.json()?;
+        let response: GetBeaconBalanceWitness = self.fetch_json(&endpoint)?;
         Ok(response.balance)
     }
 
@@ -576,12 +787,7 @@ impl BeaconClient {
             self.rpc_url, beacon_id, start_idx, end_idx
         );
         debug!("{}", endpoint);
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .get(endpoint)
-            .timeout(Duration::from_secs(300))
-            .send()?;
-        let response: GetBeaconBalanceBatchWitness = response.json()?;
+        let response: GetBeaconBalanceBatchWitness = self.fetch_json(&endpoint)?;
         Ok(response.balances)
     }
 
@@ -595,8 +801,7 @@ impl BeaconClient {
             "{}/api/beacon/proof/balance/{}/{}",
             self.rpc_url, beacon_id, validator_idx
         );
-        let response = self.client.fetch(&endpoint)?;
-        let response: CustomResponse<GetBeaconBalance> = response.json()?;
+        let response: CustomResponse<GetBeaconBalance> = self.fetch_json(&endpoint)?;
         assert!(response.success);
         Ok(response.result)
     }
@@ -611,8 +816,7 @@ impl BeaconClient {
             "{}/api/beacon/proof/balance/{}/{}",
             self.rpc_url, beacon_id, pubkey
         );
-        let response = self.client.fetch(&endpoint)?;
-        let response: CustomResponse<GetBeaconBalance> = response.json()?;
+        let response: CustomResponse<GetBeaconBalance> = self.fetch_json(&endpoint)?;
         assert!(response.success);
         Ok(response.result)
     }
@@ -628,8 +832,7 @@ impl BeaconClient {
             "{}/eth/v1/beacon/states/{}/validator_balances?id={}",
             self.rpc_url, beacon_id, validator_idx
         );
-        let response = self.client.fetch(&endpoint)?;
-        let response: BeaconData<Vec<BeaconValidatorBalance>> = response.json()?;
+        let response: BeaconData<Vec<BeaconValidatorBalance>> = self.fetch_json(&endpoint)?;
         let balance = response.data[0].balance.parse::<u64>()?;
         Ok(U256::from(balance))
     }
@@ -645,8 +848,7 @@ impl BeaconClient {
             "{}/eth/v1/beacon/states/{}/validator_balances?id={}",
             self.rpc_url, beacon_id, pubkey
         );
-        let response = self.client.fetch(&endpoint)?;
-        let response: BeaconData<Vec<BeaconValidatorBalance>> = response.json()?;
+        let response: BeaconData<Vec<BeaconValidatorBalance>> = self.fetch_json(&endpoint)?;
         let balance = response.data[0].balance.parse::<u64>()?;
         Ok(U256::from(balance))
     }
@@ -654,8 +856,7 @@ impl BeaconClient {
     pub fn get_withdrawals(&self, beacon_id: String) -> Result<GetBeaconWithdrawals> {
         let endpoint = format!("{}/api/beacon/proof/withdrawal/{}", self.rpc_url, beacon_id);
         info!("{}", endpoint);
-        let response = self.client.fetch(&endpoint)?;
-        let response: CustomResponse<GetBeaconWithdrawals> = response.json()?;
+        let response: CustomResponse<GetBeaconWithdrawals> = self.fetch_json(&endpoint)?;
         assert!(response.success);
         debug!("done {}", endpoint);
         Ok(response.result)
@@ -664,8 +865,8 @@ impl BeaconClient {
     pub async fn get_withdrawals_async(&self, beacon_id: String) -> Result<GetBeaconWithdrawals> {
         let endpoint = format!("{}/api/beacon/proof/withdrawal/{}", self.rpc_url, beacon_id);
         info!("{}", endpoint);
-        let response = self.client.fetch_async(&endpoint).await?;
-        let response: CustomResponse<GetBeaconWithdrawals> = response.json().await?;
+        let response: CustomResponse<GetBeaconWithdrawals> =
+            self.fetch_json_async(&endpoint).await?;
         assert!(response.success);
         Ok(response.result)
     }
@@ -676,8 +877,7 @@ impl BeaconClient {
             self.rpc_url, beacon_id, idx
         );
         info!("{}", endpoint);
-        let response = self.client.fetch(&endpoint)?;
-        let response: CustomResponse<GetBeaconWithdrawal> = response.json()?;
+        let response: CustomResponse<GetBeaconWithdrawal> = self.fetch_json(&endpoint)?;
         assert!(response.success);
         debug!("done {}", endpoint);
         Ok(response.result)
@@ -693,8 +893,8 @@ impl BeaconClient {
             self.rpc_url, beacon_id, offset
         );
         info!("{}", endpoint);
-        let response = self.client.fetch_async(&endpoint).await?;
-        let response: CustomResponse<GetBeaconHistoricalBlock> = response.json().await?;
+        let response: CustomResponse<GetBeaconHistoricalBlock> =
+            self.fetch_json_async(&endpoint).await?;
         assert!(response.success);
         Ok(response.result)
     }
@@ -706,9 +906,7 @@ impl BeaconClient {
             self.rpc_url, beacon_id
         );
         info!("{}", endpoint);
-        let client = Client::new();
-        let response = client.get(endpoint).timeout(Duration::new(60, 0)).send()?;
-        let response: CustomResponse<GetBeaconExecutionPayload> = response.json()?;
+        let response: CustomResponse<GetBeaconExecutionPayload> = self.fetch_json(&endpoint)?;
         assert!(response.success);
         Ok(response.result)
     }
@@ -717,19 +915,23 @@ impl BeaconClient {
     pub fn get_slot_number(&self, beacon_id: String) -> Result<GetBeaconSlotNumber> {
         let endpoint = format!("{}/api/beacon/proof/slot/{}", self.rpc_url, beacon_id);
         info!("{}", endpoint);
-        let client = Client::new();
-        let response = client.get(endpoint).timeout(Duration::new(60, 0)).send()?;
-        let response: CustomResponse<GetBeaconSlotNumber> = response.json()?;
+        let response: CustomResponse<GetBeaconSlotNumber> = self.fetch_json(&endpoint)?;
         assert!(response.success);
         Ok(response.result)
     }
 
+    /// Gets the block at the given `beacon_id`, as returned by the beacon API with its `version`.
+    pub async fn get_block(&self, beacon_id: String) -> Result<Value> {
+        let endpoint = format!("{}/eth/v2/beacon/blocks/{}", self.rpc_url, beacon_id);
+        info!("{}", endpoint);
+        self.fetch_json_async(&endpoint).await
+    }
+
     /// Gets the block header at the given `beacon_id`.
     pub async fn get_header(&self, beacon_id: String) -> Result<BeaconHeader> {
         let endpoint = format!("{}/eth/v1/beacon/headers/{}", self.rpc_url, beacon_id);
         info!("{}", endpoint);
-        let response = self.client.fetch_async(&endpoint).await?;
-        let parsed: BeaconData<BeaconHeaderContainer> = response.json().await?;
+        let parsed: BeaconData<BeaconHeaderContainer> = self.fetch_json_async(&endpoint).await?;
 
         Ok(parsed.data.header.message)
     }
@@ -737,8 +939,7 @@ impl BeaconClient {
     pub fn get_block_roots(&self, beacon_id: String) -> Result<GetBeaconBlockRoots> {
         let endpoint = format!("{}/api/beacon/proof/blockRoots/{}", self.rpc_url, beacon_id);
         info!("{}", endpoint);
-        let response = self.client.fetch(&endpoint)?;
-        let response: CustomResponse<GetBeaconBlockRoots> = response.json()?;
+        let response: CustomResponse<GetBeaconBlockRoots> = self.fetch_json(&endpoint)?;
         assert!(response.success);
         Ok(response.result)
     }
@@ -746,8 +947,7 @@ impl BeaconClient {
     pub fn get_graffiti(&self, beacon_id: String) -> Result<GetBeaconGraffiti> {
         let endpoint = format!("{}/api/beacon/proof/graffiti/{}", self.rpc_url, beacon_id);
         info!("{}", endpoint);
-        let response = self.client.fetch(&endpoint)?;
-        let response: CustomResponse<GetBeaconGraffiti> = response.json()?;
+        let response: CustomResponse<GetBeaconGraffiti> = self.fetch_json(&endpoint)?;
         assert!(response.success);
         Ok(response.result)
     }
@@ -763,8 +963,8 @@ impl BeaconClient {
             self.rpc_url, beacon_id, start_offset, end_offset
         );
         info!("{}", endpoint);
-        let response = self.client.fetch(&endpoint)?;
-        let response: CustomResponse<GetBeaconHeadersFromOffsetRange> = response.json()?;
+        let response: CustomResponse<GetBeaconHeadersFromOffsetRange> =
+            self.fetch_json(&endpoint)?;
         assert!(response.success);
         Ok(response.result)
     }
@@ -797,6 +997,93 @@ mod tests {
         println!("{}", root);
     }
 
+    #[test]
+    fn test_validator_id_chunks() {
+        let chunks = validator_id_chunks(0, 2000);
+        assert_eq!(chunks, vec![0..1022, 1022..1822, 1822..2000]);
+        for chunk in chunks {
+            assert!(chunk.clone().join(",").len() <= MAX_VALIDATOR_IDS_LEN);
+        }
+        assert_eq!(validator_id_chunks(5, 3), vec![5..8]);
+        assert!(validator_id_chunks(5, 0).is_empty());
+    }
+
+    #[test]
+    fn test_validators_in_order_missing() {
+        let validator = |pubkey: &str| BeaconValidator {
+            pubkey: pubkey.to_string(),
+            withdrawal_credentials: String::new(),
+            effective_balance: 0,
+            slashed: false,
+            activation_eligibility_epoch: "0".to_string(),
+            activation_epoch: "0".to_string(),
+            exit_epoch: "0".to_string(),
+            withdrawable_epoch: "0".to_string(),
+        };
+        let ordered =
+            validators_in_order(3..5, vec![(4, validator("b")), (3, validator("a"))]).unwrap();
+        assert_eq!(ordered[0].pubkey, "a");
+        assert_eq!(ordered[1].pubkey, "b");
+
+        let error = validators_in_order(3..6, vec![(3, validator("a")), (5, validator("c"))])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("validator 4"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_get_validators_range_replay() -> Result<()> {
+        // Records synthetic responses for the 3 chunks of the 2000 first validators, with the
+        // validators of each chunk in reverse order, and replays them.
+        let dir = env::temp_dir().join(format!("validators-{}", uuid::Uuid::new_v4()));
+        let record = RpcCacheMode::Record(dir.clone());
+        let pubkey = |idx: u64| format!("0x{:096x}", idx);
+        for range in validator_id_chunks(0, 2000) {
+            let params = Value::String(format!(
+                "/eth/v1/beacon/states/7052735/validators?id={}",
+                range.clone().join(",")
+            ));
+            let data = range
+                .rev()
+                .map(|idx| {
+                    serde_json::json!({
+                        "index": idx.to_string(),
+                        "balance": "32000000000",
+                        "status": "active_ongoing",
+                        "validator": {
+                            "pubkey": pubkey(idx),
+                            "withdrawal_credentials": format!("0x{:064x}", idx),
+                            "effective_balance": "32000000000",
+                            "slashed": false,
+                            "activation_eligibility_epoch": "0",
+                            "activation_epoch": "0",
+                            "exit_epoch": "18446744073709551615",
+                            "withdrawable_epoch": "18446744073709551615",
+                        },
+                    })
+                })
+                .collect::<Vec<_>>();
+            record.record("GET", &params, &serde_json::json!({ "data": data }))?;
+        }
+
+        let client = BeaconClient::with_cache(
+            "http://localhost:5052".to_string(),
+            RpcCacheMode::Replay(dir.clone()),
+        );
+        let validators = client
+            .get_validators_range("7052735".to_string(), 0, 2000)
+            .await?;
+        std::fs::remove_dir_all(&dir)?;
+        assert_eq!(validators.len(), 2000);
+        assert_eq!(validators[1234].pubkey, pubkey(1234));
+        assert!(validators
+            .iter()
+            .enumerate()
+            .all(|(idx, validator)| validator.pubkey == pubkey(idx as u64)
+                && validator.effective_balance == 32000000000));
+        Ok(())
+    }
+
     #[cfg_attr(feature = "ci", ignore)]
     #[test]
     fn test_get_validators_root_by_slot() -> Result<()> {
@@ -0,0 +1,288 @@
+//! Record and replay of the RPC responses used by the hints, so that the witness generation of
+//! circuits reading the chain is deterministic and can run offline.
+//!
+//! In record mode, each response is written to a fixture directory, in a file keyed by the hash
+//! of its request. In replay mode, the responses are only read from the fixtures, and a missing
+//! fixture is an error naming the request.
+
+use std::fmt::{self, Debug, Display};
+use std::path::PathBuf;
+use std::{env, fs};
+
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, JsonRpcError, ProviderError, RpcError};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+/// Where the responses of the RPC requests come from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RpcCacheMode {
+    /// The requests are sent to the RPC.
+    #[default]
+    Live,
+    /// The requests are sent to the RPC, and the responses are written to the directory.
+    Record(PathBuf),
+    /// The responses are read from the directory, without any request to the RPC.
+    Replay(PathBuf),
+}
+
+/// An error of the RPC cache.
+#[derive(Debug)]
+pub enum RpcCacheError {
+    /// There is no fixture for the request in replay mode.
+    Miss {
+        method: String,
+        params: String,
+        path: PathBuf,
+    },
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl Display for RpcCacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcCacheError::Miss {
+                method,
+                params,
+                path,
+            } => write!(
+                f,
+                "no recorded response for {}({}), expected at {}",
+                method,
+                params,
+                path.display()
+            ),
+            RpcCacheError::Io(error) => write!(f, "failed to access the RPC cache: {}", error),
+            RpcCacheError::Json(error) => write!(f, "invalid RPC cache entry: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for RpcCacheError {}
+
+impl From<std::io::Error> for RpcCacheError {
+    fn from(error: std::io::Error) -> Self {
+        RpcCacheError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for RpcCacheError {
+    fn from(error: serde_json::Error) -> Self {
+        RpcCacheError::Json(error)
+    }
+}
+
+/// A recorded request and its response.
+#[derive(Debug, Serialize, Deserialize)]
+struct RpcCacheEntry {
+    method: String,
+    params: Value,
+    response: Value,
+}
+
+impl RpcCacheMode {
+    /// Reads the mode from the `RPC_CACHE_MODE` environment variable, which is `record` or
+    /// `replay` with the fixture directory in `RPC_CACHE_DIR`. Defaults to `Live`.
+    pub fn from_env() -> Self {
+        dotenv::dotenv().ok();
+        let dir = || PathBuf::from(env::var("RPC_CACHE_DIR").expect("RPC_CACHE_DIR must be set"));
+        match env::var("RPC_CACHE_MODE").as_deref() {
+            Ok("record") => RpcCacheMode::Record(dir()),
+            Ok("replay") => RpcCacheMode::Replay(dir()),
+            Ok("live") | Err(_) => RpcCacheMode::Live,
+            Ok(mode) => panic!("unknown RPC_CACHE_MODE {}", mode),
+        }
+    }
+
+    /// Returns the path of the fixture of a request in `dir`, which is named after the method and
+    /// the sha256 hash of the request.
+    fn path(dir: &std::path::Path, method: &str, params: &Value) -> PathBuf {
+        let request = json!({ "method": method, "params": params }).to_string();
+        let hash = Sha256::digest(request.as_bytes());
+        let name = method.replace('/', "_");
+        dir.join(format!("{}-{}.json", name, hex::encode(hash)))
+    }
+
+    /// Returns the recorded response of a request in replay mode, and `None` in the other modes.
+    pub fn replay(&self, method: &str, params: &Value) -> Result<Option<Value>, RpcCacheError> {
+        let RpcCacheMode::Replay(dir) = self else {
+            return Ok(None);
+        };
+        let path = Self::path(dir, method, params);
+        let contents = fs::read_to_string(&path).map_err(|_| RpcCacheError::Miss {
+            method: method.to_string(),
+            params: params.to_string(),
+            path: path.clone(),
+        })?;
+        let entry: RpcCacheEntry = serde_json::from_str(&contents)?;
+        Ok(Some(entry.response))
+    }
+
+    /// Writes the response of a request in record mode, and does nothing in the other modes.
+    pub fn record(
+        &self,
+        method: &str,
+        params: &Value,
+        response: &Value,
+    ) -> Result<(), RpcCacheError> {
+        let RpcCacheMode::Record(dir) = self else {
+            return Ok(());
+        };
+        fs::create_dir_all(dir)?;
+        let entry = RpcCacheEntry {
+            method: method.to_string(),
+            params: params.clone(),
+            response: response.clone(),
+        };
+        fs::write(
+            Self::path(dir, method, params),
+            serde_json::to_string_pretty(&entry)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// An error of a `CachedClient` request.
+#[derive(Debug)]
+pub enum CachedClientError<E> {
+    Rpc(E),
+    Cache(RpcCacheError),
+}
+
+impl<E: Display> Display for CachedClientError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CachedClientError::Rpc(error) => Display::fmt(error, f),
+            CachedClientError::Cache(error) => Display::fmt(error, f),
+        }
+    }
+}
+
+impl<E: std::error::Error> std::error::Error for CachedClientError<E> {}
+
+impl<E: RpcError> RpcError for CachedClientError<E> {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            CachedClientError::Rpc(error) => error.as_error_response(),
+            CachedClientError::Cache(_) => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            CachedClientError::Rpc(error) => error.as_serde_error(),
+            CachedClientError::Cache(RpcCacheError::Json(error)) => Some(error),
+            CachedClientError::Cache(_) => None,
+        }
+    }
+}
+
+impl<E: RpcError + 'static> From<CachedClientError<E>> for ProviderError {
+    fn from(error: CachedClientError<E>) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(error))
+    }
+}
+
+/// A JSON-RPC client which records or replays the responses of `inner`, to be used as
+/// `Provider<CachedClient<C>>`. The inner client may be omitted in replay mode.
+#[derive(Debug)]
+pub struct CachedClient<C> {
+    inner: Option<C>,
+    mode: RpcCacheMode,
+}
+
+impl<C> CachedClient<C> {
+    pub fn new(inner: Option<C>, mode: RpcCacheMode) -> Self {
+        assert!(
+            inner.is_some() || matches!(mode, RpcCacheMode::Replay(_)),
+            "a client is required unless the responses are replayed"
+        );
+        Self { inner, mode }
+    }
+}
+
+#[async_trait]
+impl<C> JsonRpcClient for CachedClient<C>
+where
+    C: JsonRpcClient,
+    C::Error: 'static,
+{
+    type Error = CachedClientError<C::Error>;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = serde_json::to_value(&params)
+            .map_err(|e| CachedClientError::Cache(RpcCacheError::Json(e)))?;
+        let response = match self.mode.replay(method, &params) {
+            Ok(Some(response)) => response,
+            Ok(None) => {
+                let inner = self.inner.as_ref().expect("no client to send the request");
+                let response: Value = inner
+                    .request(method, &params)
+                    .await
+                    .map_err(CachedClientError::Rpc)?;
+                self.mode
+                    .record(method, &params, &response)
+                    .map_err(CachedClientError::Cache)?;
+                response
+            }
+            Err(error) => return Err(CachedClientError::Cache(error)),
+        };
+        serde_json::from_value(response)
+            .map_err(|e| CachedClientError::Cache(RpcCacheError::Json(e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use ethers::providers::{Middleware, MockProvider, Provider};
+    use ethers::types::U64;
+
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        env::temp_dir().join(format!("rpc-cache-{}", nanos))
+    }
+
+    #[tokio::test]
+    async fn test_cached_client_record_replay() {
+        let dir = temp_dir();
+
+        let mock = MockProvider::new();
+        mock.push(U64::from(17880427)).unwrap();
+        let recorder = Provider::new(CachedClient::new(
+            Some(mock),
+            RpcCacheMode::Record(dir.clone()),
+        ));
+        assert_eq!(
+            recorder.get_block_number().await.unwrap().as_u64(),
+            17880427
+        );
+
+        // The replay doesn't need a client, and fails on a request that wasn't recorded.
+        let replayer = Provider::new(CachedClient::<MockProvider>::new(
+            None,
+            RpcCacheMode::Replay(dir.clone()),
+        ));
+        assert_eq!(
+            replayer.get_block_number().await.unwrap().as_u64(),
+            17880427
+        );
+        let error = replayer.get_chainid().await.unwrap_err().to_string();
+        assert!(error.contains("eth_chainId"), "{}", error);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
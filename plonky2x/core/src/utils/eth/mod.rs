@@ -2,9 +2,17 @@ extern crate dotenv;
 
 use std::env;
 
-use ethers::providers::{Http, Provider};
+use ethers::providers::Provider;
+
+use self::cache::{CachedClient, RpcCacheMode};
+use self::provider::RetryConfig;
+use self::rpc::{EthRpc, EthTransport};
 
 pub mod beacon;
+pub mod cache;
+pub mod provider;
+pub mod rpc;
+pub mod values;
 
 #[derive(Debug, Clone)]
 pub struct Address(pub [u8; 20]);
@@ -12,12 +20,31 @@ pub struct Address(pub [u8; 20]);
 #[derive(Debug, Clone)]
 pub struct BLSPubkey(pub [u8; 48]);
 
-pub fn get_provider(chain_id: u64) -> Provider<Http> {
+/// Returns the provider of the chain `chain_id`, with the cache mode of the `RPC_CACHE_MODE`
+/// environment variable.
+pub fn get_provider(chain_id: u64) -> Box<dyn EthRpc> {
+    get_provider_with_cache(chain_id, RpcCacheMode::from_env())
+}
+
+/// Returns the provider of the chain `chain_id` with the cache mode `mode`. Its RPC url is in the
+/// `RPC_{chain_id}` environment variable, which is not needed to replay responses. The transport
+/// is given by the scheme of the url, see `EthTransport`. Over HTTP, the variable may hold several
+/// comma-separated urls, which are tried in order when the previous ones keep failing.
+pub fn get_provider_with_cache(chain_id: u64, mode: RpcCacheMode) -> Box<dyn EthRpc> {
+    if let RpcCacheMode::Replay(_) = mode {
+        return Box::new(Provider::new(CachedClient::<EthTransport>::new(None, mode)));
+    }
     dotenv::dotenv().ok();
     let rpc_str = format!("RPC_{}", chain_id);
-    let rpc_url = env::var(rpc_str)
-        .unwrap_or_else(|_| format!("RPC_{} environment variable was not found", chain_id));
-    Provider::<Http>::try_from(rpc_url).unwrap()
+    let rpc_urls = env::var(rpc_str)
+        .unwrap_or_else(|_| panic!("RPC_{} environment variable was not found", chain_id));
+    let urls = rpc_urls
+        .split(',')
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .collect();
+    let client = EthTransport::new(urls, RetryConfig::default());
+    Box::new(Provider::new(CachedClient::new(Some(client), mode)))
 }
 
 pub fn concat_g_indices(gindexes: &[usize]) -> usize {
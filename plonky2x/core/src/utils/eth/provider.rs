@@ -0,0 +1,432 @@
+//! A JSON-RPC client for the execution provider used by the hints, which retries the requests that
+//! fail for transient reasons instead of failing the whole proof.
+//!
+//! Rate limits (HTTP 429 or the JSON-RPC error -32005), server errors and connection errors are
+//! retried with an exponential backoff with jitter. Once the retries of an endpoint are exhausted,
+//! the next fallback endpoint is tried. Other errors are returned immediately, along with the
+//! method and the parameters of the request.
+
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Display};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, JsonRpcError, ProviderError, RpcError};
+use lazy_static::lazy_static;
+use log::debug;
+use rand::Rng;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::Semaphore;
+
+/// The JSON-RPC error code of a rate limited request.
+const LIMIT_EXCEEDED_ERROR_CODE: i64 = -32005;
+
+lazy_static! {
+    /// The permits of the endpoints, shared by all the clients so that the limit of concurrent
+    /// requests holds across hints.
+    static ref ENDPOINT_PERMITS: Mutex<HashMap<String, Arc<Semaphore>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// The retry policy of a `RetryingHttp` client.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// The number of retries of a request on each endpoint.
+    pub max_retries: u32,
+    /// The delay before the first retry, which doubles at each retry.
+    pub initial_backoff: Duration,
+    /// The maximum delay between two retries.
+    pub max_backoff: Duration,
+    /// The maximum number of concurrent requests to an endpoint. The first client to use an
+    /// endpoint sets its limit.
+    pub max_concurrent_requests: usize,
+}
+
+impl RetryConfig {
+    /// Returns the delay before the retry `retry`, which is the exponential backoff with a random
+    /// jitter of up to half of it.
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(1 << retry.min(16))
+            .min(self.max_backoff);
+        let jitter = rand::thread_rng().gen_range(0.0..0.5);
+        backoff.mul_f64(1.0 - jitter)
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_concurrent_requests: 16,
+        }
+    }
+}
+
+/// An error of a `RetryingHttp` request. Each one carries the method and the parameters of the
+/// request.
+#[derive(Debug)]
+pub enum RetryingHttpError {
+    /// The request kept failing with transient errors on every endpoint.
+    RetriesExhausted {
+        method: String,
+        params: String,
+        attempts: u32,
+        last_error: String,
+    },
+    /// An endpoint rejected the request with a client error.
+    Status {
+        method: String,
+        params: String,
+        status: StatusCode,
+        body: String,
+    },
+    /// The node returned a JSON-RPC error.
+    JsonRpc {
+        method: String,
+        params: String,
+        error: JsonRpcError,
+    },
+    /// The response could not be parsed.
+    Parse {
+        method: String,
+        params: String,
+        error: serde_json::Error,
+        text: String,
+    },
+}
+
+impl Display for RetryingHttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RetryingHttpError::RetriesExhausted {
+                method,
+                params,
+                attempts,
+                last_error,
+            } => write!(
+                f,
+                "{}({}) failed after {} attempts: {}",
+                method, params, attempts, last_error
+            ),
+            RetryingHttpError::Status {
+                method,
+                params,
+                status,
+                body,
+            } => write!(f, "{}({}) failed with {}: {}", method, params, status, body),
+            RetryingHttpError::JsonRpc {
+                method,
+                params,
+                error,
+            } => write!(f, "{}({}) failed: {}", method, params, error),
+            RetryingHttpError::Parse {
+                method,
+                params,
+                error,
+                text,
+            } => write!(
+                f,
+                "{}({}) returned an invalid response: {}: {}",
+                method, params, error, text
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RetryingHttpError {}
+
+impl RpcError for RetryingHttpError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            RetryingHttpError::JsonRpc { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            RetryingHttpError::Parse { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<RetryingHttpError> for ProviderError {
+    fn from(error: RetryingHttpError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(error))
+    }
+}
+
+/// The outcome of a failed attempt.
+enum AttemptError {
+    /// A transient error, described by the string, after which the request is retried.
+    Transient(String),
+    Fatal(RetryingHttpError),
+}
+
+/// An HTTP JSON-RPC client with retries and fallback endpoints, to be used as
+/// `Provider<RetryingHttp>`.
+#[derive(Debug)]
+pub struct RetryingHttp {
+    client: reqwest::Client,
+    urls: Vec<String>,
+    config: RetryConfig,
+    id: AtomicU64,
+}
+
+impl RetryingHttp {
+    /// Creates a client for the endpoints `urls`, tried in order.
+    pub fn new(urls: Vec<String>, config: RetryConfig) -> Self {
+        assert!(!urls.is_empty(), "at least one RPC url is required");
+        Self {
+            client: reqwest::Client::new(),
+            urls,
+            config,
+            id: AtomicU64::new(1),
+        }
+    }
+
+    /// Returns the permits of the endpoint `url`.
+    fn permits(&self, url: &str) -> Arc<Semaphore> {
+        let mut permits = ENDPOINT_PERMITS.lock().unwrap();
+        permits
+            .entry(url.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.config.max_concurrent_requests)))
+            .clone()
+    }
+
+    /// Sends the request once to `url`.
+    async fn attempt<R: DeserializeOwned>(
+        &self,
+        url: &str,
+        body: &Value,
+        method: &str,
+        params: &str,
+    ) -> Result<R, AttemptError> {
+        let permits = self.permits(url);
+        let _permit = permits
+            .acquire()
+            .await
+            .expect("the permits are never closed");
+
+        let response = self
+            .client
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| AttemptError::Transient(e.to_string()))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| AttemptError::Transient(e.to_string()))?;
+
+        if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            return Err(AttemptError::Transient(format!("{}: {}", status, text)));
+        }
+        if !status.is_success() {
+            return Err(AttemptError::Fatal(RetryingHttpError::Status {
+                method: method.to_string(),
+                params: params.to_string(),
+                status,
+                body: text,
+            }));
+        }
+
+        let parse_error = |error: serde_json::Error, text: &str| {
+            AttemptError::Fatal(RetryingHttpError::Parse {
+                method: method.to_string(),
+                params: params.to_string(),
+                error,
+                text: text.to_string(),
+            })
+        };
+        let mut response: Value = serde_json::from_str(&text).map_err(|e| parse_error(e, &text))?;
+        if let Some(error) = response.get_mut("error") {
+            let error: JsonRpcError =
+                serde_json::from_value(error.take()).map_err(|e| parse_error(e, &text))?;
+            if error.code == LIMIT_EXCEEDED_ERROR_CODE {
+                return Err(AttemptError::Transient(error.to_string()));
+            }
+            return Err(AttemptError::Fatal(RetryingHttpError::JsonRpc {
+                method: method.to_string(),
+                params: params.to_string(),
+                error,
+            }));
+        }
+        let result = response
+            .get_mut("result")
+            .map(Value::take)
+            .unwrap_or(Value::Null);
+        serde_json::from_value(result).map_err(|e| parse_error(e, &text))
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for RetryingHttp {
+    type Error = RetryingHttpError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = serde_json::to_value(&params).map_err(|error| RetryingHttpError::Parse {
+            method: method.to_string(),
+            params: format!("{:?}", params),
+            error,
+            text: String::new(),
+        })?;
+        let params_str = params.to_string();
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": self.id.fetch_add(1, Ordering::SeqCst),
+            "method": method,
+            "params": params,
+        });
+
+        let mut attempts = 0;
+        let mut last_error = String::new();
+        for url in self.urls.iter() {
+            for retry in 0..=self.config.max_retries {
+                if retry > 0 {
+                    tokio::time::sleep(self.config.backoff(retry - 1)).await;
+                }
+                attempts += 1;
+                match self.attempt(url, &body, method, &params_str).await {
+                    Ok(result) => return Ok(result),
+                    Err(AttemptError::Fatal(error)) => return Err(error),
+                    Err(AttemptError::Transient(error)) => {
+                        debug!(
+                            "{}({}) failed on {} (attempt {}): {}",
+                            method, params_str, url, attempts, error
+                        );
+                        last_error = error;
+                    }
+                }
+            }
+        }
+        Err(RetryingHttpError::RetriesExhausted {
+            method: method.to_string(),
+            params: params_str,
+            attempts,
+            last_error,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use ethers::providers::{Middleware, Provider};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Starts an HTTP server which answers the requests with `responses` in order, repeating the
+    /// last one, and returns its url and its number of requests so far.
+    async fn mock_server(responses: Vec<(u16, &'static str)>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let count = Arc::new(AtomicUsize::new(0));
+        let server_count = count.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let i = server_count.fetch_add(1, Ordering::SeqCst);
+                let (status, body) = responses[i.min(responses.len() - 1)];
+
+                // Read the headers and the body of the request.
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                loop {
+                    let n = socket.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..n]);
+                    let text = String::from_utf8_lossy(&request).to_lowercase();
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let len = text
+                            .lines()
+                            .find_map(|l| l.strip_prefix("content-length:"))
+                            .map_or(0, |l| l.trim().parse::<usize>().unwrap());
+                        if request.len() >= end + 4 + len {
+                            break;
+                        }
+                    }
+                }
+
+                let response = format!(
+                    "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+                socket.shutdown().await.unwrap();
+            }
+        });
+        (url, count)
+    }
+
+    fn test_config() -> RetryConfig {
+        RetryConfig {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+            max_concurrent_requests: 4,
+        }
+    }
+
+    const BLOCK_NUMBER: &str = r#"{"jsonrpc":"2.0","id":1,"result":"0x10d4f"}"#;
+
+    #[tokio::test]
+    async fn test_retrying_http_retries_transient_errors() {
+        let (url, count) = mock_server(vec![
+            (429, "rate limited"),
+            (503, "unavailable"),
+            (200, BLOCK_NUMBER),
+        ])
+        .await;
+        let provider = Provider::new(RetryingHttp::new(vec![url], test_config()));
+        let block_number = provider.get_block_number().await.unwrap();
+        assert_eq!(block_number.as_u64(), 0x10d4f);
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retrying_http_falls_back() {
+        let (url, count) = mock_server(vec![(502, "bad gateway")]).await;
+        let (fallback_url, fallback_count) = mock_server(vec![(200, BLOCK_NUMBER)]).await;
+        let provider = Provider::new(RetryingHttp::new(vec![url, fallback_url], test_config()));
+        let block_number = provider.get_block_number().await.unwrap();
+        assert_eq!(block_number.as_u64(), 0x10d4f);
+        assert_eq!(count.load(Ordering::SeqCst), 4);
+        assert_eq!(fallback_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retrying_http_surfaces_client_errors() {
+        let (url, count) = mock_server(vec![(400, "parse error"), (200, BLOCK_NUMBER)]).await;
+        let client = RetryingHttp::new(vec![url], test_config());
+        let error = client
+            .request::<_, Value>("eth_getBlockByNumber", ("0x10d4f", false))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, RetryingHttpError::Status { .. }));
+        let message = error.to_string();
+        assert!(message.contains("eth_getBlockByNumber"), "{}", message);
+        assert!(message.contains("[\"0x10d4f\",false]"), "{}", message);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,268 @@
+//! The execution RPC used by the hints, over HTTP, WebSocket or IPC.
+//!
+//! The hints only depend on the `EthRpc` trait, which has the calls they make. It is implemented
+//! by any `Provider`, and `EthTransport` selects the transport of a provider by the scheme of its
+//! url: `http(s)://`, `ws(s)://`, or `ipc://` and paths for IPC sockets.
+
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use ethers::providers::{Ipc, JsonRpcClient, Middleware, Provider, ProviderError, Ws};
+use ethers::types::{
+    Address, Block, BlockId, Bytes, EIP1186ProofResponse, TransactionReceipt, H256, U64,
+};
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::sync::OnceCell;
+
+use super::provider::{RetryConfig, RetryingHttp};
+
+/// The calls made by the hints to an execution RPC.
+#[async_trait]
+pub trait EthRpc: Debug + Send + Sync {
+    async fn get_block(&self, block: BlockId) -> Result<Option<Block<H256>>, ProviderError>;
+
+    async fn get_proof(
+        &self,
+        address: Address,
+        locations: Vec<H256>,
+        block: Option<BlockId>,
+    ) -> Result<EIP1186ProofResponse, ProviderError>;
+
+    async fn get_storage_at(
+        &self,
+        address: Address,
+        location: H256,
+        block: Option<BlockId>,
+    ) -> Result<H256, ProviderError>;
+
+    async fn get_transaction_receipt(
+        &self,
+        hash: H256,
+    ) -> Result<Option<TransactionReceipt>, ProviderError>;
+
+    /// Returns the RLP encoded header of the block `number`, with `debug_getRawHeader`.
+    async fn get_raw_header(&self, number: u64) -> Result<Bytes, ProviderError>;
+}
+
+#[async_trait]
+impl<P: JsonRpcClient> EthRpc for Provider<P> {
+    async fn get_block(&self, block: BlockId) -> Result<Option<Block<H256>>, ProviderError> {
+        Middleware::get_block(self, block).await
+    }
+
+    async fn get_proof(
+        &self,
+        address: Address,
+        locations: Vec<H256>,
+        block: Option<BlockId>,
+    ) -> Result<EIP1186ProofResponse, ProviderError> {
+        Middleware::get_proof(self, address, locations, block).await
+    }
+
+    async fn get_storage_at(
+        &self,
+        address: Address,
+        location: H256,
+        block: Option<BlockId>,
+    ) -> Result<H256, ProviderError> {
+        Middleware::get_storage_at(self, address, location, block).await
+    }
+
+    async fn get_transaction_receipt(
+        &self,
+        hash: H256,
+    ) -> Result<Option<TransactionReceipt>, ProviderError> {
+        Middleware::get_transaction_receipt(self, hash).await
+    }
+
+    async fn get_raw_header(&self, number: u64) -> Result<Bytes, ProviderError> {
+        self.request("debug_getRawHeader", [U64::from(number)])
+            .await
+    }
+}
+
+/// A JSON-RPC client over the transport given by the scheme of its url, to be used as
+/// `Provider<EthTransport>`.
+///
+/// The WebSocket and IPC connections are opened on the first request, so that the client can be
+/// created outside of a runtime. A dropped WebSocket connection is reopened, and its pending
+/// requests are sent again, up to `max_retries` times.
+#[derive(Debug)]
+pub enum EthTransport {
+    Http(RetryingHttp),
+    Ws {
+        url: String,
+        config: RetryConfig,
+        client: OnceCell<Ws>,
+    },
+    Ipc {
+        path: PathBuf,
+        client: OnceCell<Ipc>,
+    },
+}
+
+impl EthTransport {
+    /// Creates a client for the endpoints `urls`. Fallback endpoints are only supported over
+    /// HTTP, so the WebSocket and IPC transports take a single url.
+    pub fn new(urls: Vec<String>, config: RetryConfig) -> Self {
+        assert!(!urls.is_empty(), "at least one RPC url is required");
+        let url = &urls[0];
+        if url.starts_with("http://") || url.starts_with("https://") {
+            assert!(
+                urls.iter()
+                    .all(|url| url.starts_with("http://") || url.starts_with("https://")),
+                "the fallback urls of an HTTP endpoint must be HTTP urls"
+            );
+            return EthTransport::Http(RetryingHttp::new(urls, config));
+        }
+
+        assert_eq!(
+            urls.len(),
+            1,
+            "fallback urls are only supported over HTTP, got {:?}",
+            urls
+        );
+        if url.starts_with("ws://") || url.starts_with("wss://") {
+            EthTransport::Ws {
+                url: url.clone(),
+                config,
+                client: OnceCell::new(),
+            }
+        } else if let Some(path) = url.strip_prefix("ipc://") {
+            EthTransport::Ipc {
+                path: PathBuf::from(path),
+                client: OnceCell::new(),
+            }
+        } else if url.starts_with('/') || url.ends_with(".ipc") {
+            EthTransport::Ipc {
+                path: PathBuf::from(url),
+                client: OnceCell::new(),
+            }
+        } else {
+            panic!("unsupported RPC url {}", url)
+        }
+    }
+
+    /// Opens the WebSocket connection to `url`, retrying with the backoff of `config`.
+    async fn connect_ws(url: &str, config: &RetryConfig) -> Result<Ws, ProviderError> {
+        let mut retry = 0;
+        loop {
+            match Ws::connect_with_reconnects(url, config.max_retries as usize).await {
+                Ok(ws) => return Ok(ws),
+                Err(error) if retry < config.max_retries => {
+                    debug!("failed to connect to {} (retry {}): {}", url, retry, error);
+                    tokio::time::sleep(config.backoff(retry)).await;
+                    retry += 1;
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for EthTransport {
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        match self {
+            EthTransport::Http(client) => client.request(method, params).await.map_err(Into::into),
+            EthTransport::Ws {
+                url,
+                config,
+                client,
+            } => {
+                let client = client
+                    .get_or_try_init(|| Self::connect_ws(url, config))
+                    .await?;
+                client.request(method, params).await.map_err(Into::into)
+            }
+            EthTransport::Ipc { path, client } => {
+                let client = client
+                    .get_or_try_init(|| async {
+                        Ipc::connect(path).await.map_err(ProviderError::from)
+                    })
+                    .await?;
+                client.request(method, params).await.map_err(Into::into)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use ethers::types::{BlockNumber, U256};
+
+    use super::*;
+
+    #[test]
+    fn test_eth_transport_scheme() {
+        let transport =
+            |url: &str| EthTransport::new(vec![url.to_string()], RetryConfig::default());
+        assert!(matches!(
+            transport("https://eth.llamarpc.com"),
+            EthTransport::Http(_)
+        ));
+        assert!(matches!(
+            transport("ws://127.0.0.1:8545"),
+            EthTransport::Ws { .. }
+        ));
+        assert!(matches!(
+            transport("ipc:///tmp/geth.ipc"),
+            EthTransport::Ipc { .. }
+        ));
+        assert!(matches!(
+            transport("/tmp/anvil.ipc"),
+            EthTransport::Ipc { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_eth_rpc_ws_anvil() {
+        // Runs against an anvil node, e.g. `anvil --port 8545` with
+        // `ANVIL_WS_URL=ws://127.0.0.1:8545`.
+        dotenv::dotenv().ok();
+        let Ok(url) = env::var("ANVIL_WS_URL") else {
+            debug!("ANVIL_WS_URL is not set, skipping");
+            return;
+        };
+        let provider = Provider::new(EthTransport::new(vec![url], RetryConfig::default()));
+        assert!(matches!(provider.as_ref(), EthTransport::Ws { .. }));
+
+        let block = EthRpc::get_block(&provider, BlockNumber::Latest.into())
+            .await
+            .unwrap()
+            .unwrap();
+        let block_id = Some(BlockId::from(block.hash.unwrap()));
+
+        // The first funded account of anvil, and the slot 0 of its empty storage.
+        let address: Address = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+            .parse()
+            .unwrap();
+        let value = EthRpc::get_storage_at(&provider, address, H256::zero(), block_id)
+            .await
+            .unwrap();
+        let proof = EthRpc::get_proof(&provider, address, vec![H256::zero()], block_id)
+            .await
+            .unwrap();
+        assert_eq!(proof.address, address);
+        assert_eq!(
+            proof.storage_proof[0].value,
+            U256::from_big_endian(value.as_bytes())
+        );
+
+        let receipt = EthRpc::get_transaction_receipt(&provider, H256::zero())
+            .await
+            .unwrap();
+        assert!(receipt.is_none());
+    }
+}
@@ -0,0 +1,277 @@
+//! Conversions of the RPC responses into the values of the circuit variables, so that the hints
+//! can write them directly with `write_value`.
+//!
+//! The block header is re-encoded from its fields, including the optional fields of London
+//! (baseFeePerGas), Shanghai (withdrawalsRoot), Cancun (blobGasUsed, excessBlobGas and
+//! parentBeaconBlockRoot) and Prague (requestsHash), and its hash is checked against the hash
+//! returned by the RPC.
+
+use anyhow::{anyhow, ensure, Result};
+use ethers::types::{Address, Block, BlockId, EIP1186ProofResponse, StorageProof, H256, U256};
+use ethers::utils::keccak256;
+use ethers::utils::rlp::{self, RlpStream};
+use plonky2::hash::hash_types::RichField;
+
+use super::rpc::EthRpc;
+use crate::frontend::eth::header::{BlockHeader, BlockHeaderVariable};
+use crate::frontend::eth::mpt::builder::transform_proof_to_padded;
+use crate::frontend::eth::storage::vars::{EthAccount, EthAccountVariable};
+use crate::frontend::eth::utils::u256_to_h256_be;
+use crate::prelude::CircuitVariable;
+
+/// The number of fields of a header before the London fork.
+const LEGACY_HEADER_ITEMS: usize = 15;
+
+/// The value of a `BlockHeaderVariable`.
+pub type BlockHeaderValue<F> = <BlockHeaderVariable as CircuitVariable>::ValueType<F>;
+
+/// The value of an `EthAccountVariable`.
+pub type EthAccountValue<F> = <EthAccountVariable as CircuitVariable>::ValueType<F>;
+
+/// A storage proof, with its nodes padded for `verify_mpt_value`. The proof and the lengths are
+/// the values of an `ArrayVariable<ArrayVariable<ByteVariable, ENCODING_LEN>, PROOF_LEN>` and an
+/// `ArrayVariable<U32Variable, PROOF_LEN>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageProofValue {
+    pub key: H256,
+    pub value: H256,
+    pub proof: Vec<Vec<u8>>,
+    pub len_nodes: Vec<u32>,
+}
+
+/// Returns the RLP encoding of the header of `block`, with the optional fields it has.
+pub fn block_header_rlp(block: &Block<H256>) -> Result<Vec<u8>> {
+    let missing = |field: &str| anyhow!("the block has no {}", field);
+
+    // Each fork appends its fields after the ones of the previous forks.
+    let requests_hash = block
+        .other
+        .get_deserialized::<H256>("requestsHash")
+        .transpose()?;
+    let optional_fields = [
+        block.base_fee_per_gas.map(|fee| rlp::encode(&fee)),
+        block.withdrawals_root.map(|root| rlp::encode(&root)),
+        block.blob_gas_used.map(|gas| rlp::encode(&gas)),
+        block.excess_blob_gas.map(|gas| rlp::encode(&gas)),
+        block
+            .parent_beacon_block_root
+            .map(|root| rlp::encode(&root)),
+        requests_hash.map(|hash| rlp::encode(&hash)),
+    ];
+    let nb_optional_fields = optional_fields.iter().take_while(|f| f.is_some()).count();
+    ensure!(
+        optional_fields[nb_optional_fields..]
+            .iter()
+            .all(Option::is_none),
+        "the block has the fields of a fork without the fields of the previous forks"
+    );
+
+    let mut stream = RlpStream::new_list(LEGACY_HEADER_ITEMS + nb_optional_fields);
+    stream.append(&block.parent_hash);
+    stream.append(&block.uncles_hash);
+    stream.append(&block.author.ok_or_else(|| missing("miner"))?);
+    stream.append(&block.state_root);
+    stream.append(&block.transactions_root);
+    stream.append(&block.receipts_root);
+    stream.append(&block.logs_bloom.ok_or_else(|| missing("logs bloom"))?);
+    stream.append(&block.difficulty);
+    stream.append(&block.number.ok_or_else(|| missing("number"))?);
+    stream.append(&block.gas_limit);
+    stream.append(&block.gas_used);
+    stream.append(&block.timestamp);
+    stream.append(&block.extra_data.to_vec());
+    stream.append(&block.mix_hash.ok_or_else(|| missing("mix hash"))?);
+    stream.append(&block.nonce.ok_or_else(|| missing("nonce"))?);
+    for field in optional_fields.iter().flatten() {
+        stream.append_raw(field, 1);
+    }
+    Ok(stream.out().to_vec())
+}
+
+/// Returns the value of the `BlockHeaderVariable` of `block`. The block must not be pending, and
+/// its header must hash to its hash.
+pub fn block_header_value<F: RichField>(block: &Block<H256>) -> Result<BlockHeaderValue<F>> {
+    let hash = block.hash.ok_or_else(|| anyhow!("the block is pending"))?;
+    let header = block_header_rlp(block)?;
+    ensure!(
+        H256(keccak256(&header)) == hash,
+        "the header of block {:?} doesn't hash to its hash, it may have unsupported fields",
+        hash
+    );
+    ensure!(
+        block.timestamp <= U256::from(u64::MAX),
+        "the timestamp of block {:?} doesn't fit in 64 bits",
+        hash
+    );
+    Ok(BlockHeader {
+        hash,
+        parent_hash: block.parent_hash,
+        state_root: block.state_root,
+        transactions_root: block.transactions_root,
+        receipts_root: block.receipts_root,
+        number: block.number.expect("checked by block_header_rlp").as_u64(),
+        timestamp: block.timestamp.as_u64(),
+    })
+}
+
+/// Returns the value of the `EthAccountVariable` of the account of `proof`.
+pub fn account_value<F: RichField>(proof: &EIP1186ProofResponse) -> EthAccountValue<F> {
+    EthAccount {
+        balance: proof.balance,
+        code_hash: proof.code_hash,
+        nonce: U256::from(proof.nonce.as_u64()),
+        storage_hash: proof.storage_hash,
+    }
+}
+
+/// Returns the storage proof `proof`, padded to `PROOF_LEN` nodes of `ENCODING_LEN` bytes.
+///
+/// Panics if the proof doesn't fit.
+pub fn storage_proof_value<const ENCODING_LEN: usize, const PROOF_LEN: usize>(
+    proof: &StorageProof,
+) -> StorageProofValue {
+    let nodes = proof.proof.iter().map(|node| node.to_vec()).collect();
+    let (proof_as_fixed, lengths_as_fixed) =
+        transform_proof_to_padded::<ENCODING_LEN, PROOF_LEN>(nodes);
+    StorageProofValue {
+        key: proof.key,
+        value: u256_to_h256_be(proof.value),
+        proof: proof_as_fixed,
+        len_nodes: lengths_as_fixed.into_iter().map(|len| len as u32).collect(),
+    }
+}
+
+/// Fetches the block `block` and returns the value of its `BlockHeaderVariable`.
+pub async fn get_block_header_value<F: RichField>(
+    provider: &dyn EthRpc,
+    block: BlockId,
+) -> Result<BlockHeaderValue<F>> {
+    let result = provider
+        .get_block(block)
+        .await?
+        .ok_or_else(|| anyhow!("block {:?} not found", block))?;
+    block_header_value(&result)
+}
+
+/// Fetches the account `address` at the block `block` and returns the value of its
+/// `EthAccountVariable`.
+pub async fn get_account_value<F: RichField>(
+    provider: &dyn EthRpc,
+    address: Address,
+    block: BlockId,
+) -> Result<EthAccountValue<F>> {
+    let proof = provider.get_proof(address, vec![], Some(block)).await?;
+    Ok(account_value(&proof))
+}
+
+/// Fetches the storage proof of `key` in the account `address` at the block `block`, padded to
+/// `PROOF_LEN` nodes of `ENCODING_LEN` bytes.
+pub async fn get_storage_proof_value<const ENCODING_LEN: usize, const PROOF_LEN: usize>(
+    provider: &dyn EthRpc,
+    address: Address,
+    key: H256,
+    block: BlockId,
+) -> Result<StorageProofValue> {
+    let result = provider.get_proof(address, vec![key], Some(block)).await?;
+    let proof = result
+        .storage_proof
+        .first()
+        .ok_or_else(|| anyhow!("no storage proof for {:?} in {:?}", key, address))?;
+    Ok(storage_proof_value::<ENCODING_LEN, PROOF_LEN>(proof))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use ethers::types::Bytes;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::frontend::eth::header::chain::BlockHeaderBytesVariable;
+    use crate::frontend::eth::mpt::utils::read_fixture;
+    use crate::prelude::{DefaultBuilder, GoldilocksField};
+
+    #[derive(Debug, Deserialize)]
+    struct BlockFixture {
+        fork: String,
+        header: Bytes,
+        block: Block<H256>,
+    }
+
+    /// The London, Shanghai and Cancun headers of the header fixtures, as returned by
+    /// `eth_getBlockByHash` without transactions.
+    fn read_block_fixtures() -> Vec<BlockFixture> {
+        let fixture = fs::read_to_string("./src/utils/eth/fixtures/blocks.json")
+            .expect("failed to read fixtures");
+        serde_json::from_str(&fixture).unwrap()
+    }
+
+    #[test]
+    fn test_block_header_value() {
+        for fixture in read_block_fixtures() {
+            assert_eq!(
+                block_header_rlp(&fixture.block).unwrap(),
+                fixture.header.to_vec(),
+                "{}",
+                fixture.fork
+            );
+            let value = block_header_value::<GoldilocksField>(&fixture.block).unwrap();
+            assert_eq!(value.hash, fixture.block.hash.unwrap());
+            assert_eq!(value.number, fixture.block.number.unwrap().as_u64());
+            assert_eq!(value.timestamp, fixture.block.timestamp.as_u64());
+        }
+
+        // A header missing the Shanghai fields doesn't hash to the hash of the block.
+        let mut block = read_block_fixtures().remove(1).block;
+        block.withdrawals_root = None;
+        assert!(block_header_value::<GoldilocksField>(&block).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "ci", ignore)]
+    fn test_block_header_value_matches_circuit() {
+        // The header decoded in the circuit must be the value computed from the RPC fields.
+        let mut builder = DefaultBuilder::new();
+        let header_bytes = builder.read::<BlockHeaderBytesVariable>();
+        let expected = builder.read::<BlockHeaderVariable>();
+        let header = builder.decode_block_header(&header_bytes.data.0, header_bytes.len);
+        builder.assert_is_equal(header, expected.clone());
+        builder.write(expected);
+        let circuit = builder.mock_build();
+
+        for fixture in read_block_fixtures() {
+            let value = block_header_value(&fixture.block).unwrap();
+            let mut input = circuit.input();
+            input.write::<BlockHeaderBytesVariable>(fixture.header.to_vec());
+            input.write::<BlockHeaderVariable>(value.clone());
+            let mut output = circuit.mock_prove(&input).output;
+            assert_eq!(
+                output.read::<BlockHeaderVariable>(),
+                value,
+                "{}",
+                fixture.fork
+            );
+        }
+    }
+
+    #[test]
+    fn test_account_and_storage_proof_values() {
+        let response = read_fixture("./src/frontend/eth/mpt/fixtures/example.json");
+        let account = account_value::<GoldilocksField>(&response);
+        assert_eq!(account.balance, response.balance);
+        assert_eq!(account.nonce, U256::from(response.nonce.as_u64()));
+        assert_eq!(account.storage_hash, response.storage_hash);
+
+        let storage_proof = &response.storage_proof[0];
+        let value = storage_proof_value::<600, 16>(storage_proof);
+        assert_eq!(value.key, storage_proof.key);
+        assert_eq!(value.value, u256_to_h256_be(storage_proof.value));
+        assert_eq!(value.proof.len(), 16);
+        assert!(value.proof.iter().all(|node| node.len() == 600));
+        for (i, node) in storage_proof.proof.iter().enumerate() {
+            assert_eq!(value.len_nodes[i] as usize, node.len());
+            assert_eq!(&value.proof[i][..node.len()], node.as_ref());
+        }
+    }
+}
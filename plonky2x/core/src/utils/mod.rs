@@ -1,4 +1,6 @@
+use std::cell::RefCell;
 use std::sync::Once;
+pub mod digest;
 pub mod eth;
 pub mod hash;
 pub mod lido;
@@ -9,7 +11,8 @@ pub mod serde;
 pub mod stream;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
-use log::{set_max_level, LevelFilter};
+pub use digest::{encode_digest, DigestFormat, Endianness};
+use log::{set_max_level, LevelFilter, Log, Metadata, Record};
 
 pub macro bytes32($hex_literal:expr) {
     $hex_literal.parse::<ethers::types::H256>().unwrap()
@@ -67,15 +70,61 @@ pub fn to_be_bits(msg: &[u8]) -> Vec<bool> {
 
 static INIT: Once = Once::new();
 
+thread_local! {
+    /// The messages logged on the current thread while it runs `capture_logs`.
+    static CAPTURED_LOGS: RefCell<Option<Vec<String>>> = RefCell::new(None);
+}
+
+/// A logger that forwards records to `env_logger` and keeps the messages captured by
+/// `capture_logs`.
+struct CapturingLogger {
+    inner: env_logger::Logger,
+}
+
+impl Log for CapturingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.inner.matches(record) {
+            CAPTURED_LOGS.with(|logs| {
+                if let Some(logs) = logs.borrow_mut().as_mut() {
+                    logs.push(record.args().to_string());
+                }
+            });
+        }
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
 pub fn setup_logger() {
     INIT.call_once(|| {
-        env_logger::Builder::from_default_env()
+        let inner = env_logger::Builder::from_default_env()
             .format_timestamp(None)
             .filter_level(LevelFilter::Trace)
-            .init();
+            .build();
+        set_max_level(inner.filter());
+        log::set_boxed_logger(Box::new(CapturingLogger { inner })).unwrap();
     });
 }
 
+/// Runs `f` and returns its result with the messages it logged on the current thread.
+///
+/// Sets up the logger if needed. Messages logged by other threads, e.g. by the hints that run in
+/// parallel during witness generation, are not captured.
+pub fn capture_logs<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    setup_logger();
+    CAPTURED_LOGS.with(|logs| *logs.borrow_mut() = Some(Vec::new()));
+    let result = f();
+    let logs = CAPTURED_LOGS.with(|logs| logs.borrow_mut().take().unwrap());
+    (result, logs)
+}
+
 static ORIGINAL_LEVEL: AtomicUsize = AtomicUsize::new(LevelFilter::Info as usize);
 
 pub fn disable_logging() {
@@ -1,11 +1,33 @@
 use itertools::Itertools;
-use plonky2::hash::hash_types::HashOut;
+use plonky2::hash::hash_types::{HashOut, RichField};
 use plonky2::hash::hashing::hash_n_to_hash_no_pad;
+use plonky2::hash::poseidon::PoseidonHash;
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
 
 use crate::frontend::hash::poseidon::poseidon256::PoseidonHashOutVariable;
 use crate::prelude::{CircuitBuilder, CircuitVariable, PlonkParameters};
 
+/// Computes the Poseidon hash of a byte string, matching `CircuitBuilder::poseidon_hash_bytes`.
+///
+/// The bytes are packed as their length followed by little-endian chunks of 7 bytes.
+pub fn poseidon_hash_bytes<F: RichField>(bytes: &[u8]) -> HashOut<F> {
+    let mut elements = vec![F::from_canonical_usize(bytes.len())];
+    elements.extend(bytes.chunks(7).map(|chunk| {
+        let mut arr = [0; 8];
+        arr[..chunk.len()].copy_from_slice(chunk);
+        F::from_canonical_u64(u64::from_le_bytes(arr))
+    }));
+    PoseidonHash::hash_no_pad(&elements)
+}
+
+/// Computes the Poseidon hash of two hash outputs, matching `CircuitBuilder::poseidon_hash_pair`
+/// for circuits hashing with Poseidon.
+pub fn poseidon_hash_pair<F: RichField>(left: HashOut<F>, right: HashOut<F>) -> HashOut<F> {
+    let mut elements = left.elements.to_vec();
+    elements.extend(right.elements);
+    PoseidonHash::hash_no_pad(&elements)
+}
+
 pub fn mapreduce_merkle_tree_root<
     L: PlonkParameters<D>,
     Input: CircuitVariable,
@@ -11,7 +11,12 @@ impl<T> Stream<T> {
 
     pub fn read_exact(&mut self, len: usize) -> &[T] {
         if (self.position + len) > self.data.len() {
-            panic!("Not enough elements in Stream");
+            panic!(
+                "Not enough elements in Stream: reading {} elements at position {} of {}",
+                len,
+                self.position,
+                self.data.len()
+            );
         }
         let out_slice = self.data[self.position..self.position + len].as_ref();
         self.position += len;
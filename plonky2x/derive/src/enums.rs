@@ -0,0 +1,377 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{DataEnum, Fields, Type, Visibility};
+
+use crate::assert_is_valid::assert_is_valid;
+use crate::elements::nb_elements;
+use crate::init::init_unsafe;
+use crate::variables::{from_variables_unsafe, variables};
+use crate::StructData;
+
+struct VariantData {
+    name: Ident,
+    /// The field names of a struct variant, or `None` for unit and tuple variants.
+    field_names: Option<Vec<Ident>>,
+    /// The names of the fields in the generated variable struct.
+    flat_names: Vec<Ident>,
+    types: Vec<Type>,
+}
+
+/// Derives `CircuitVariable` for an enum whose variants hold circuit variables.
+///
+/// Since the active variant is only known at proving time, the enum itself cannot be used as a
+/// variable. Instead, this generates:
+///
+/// - a value enum with the same variants, holding the value types of the fields,
+/// - a variable struct holding a `discriminant: Variable` followed by the fields of every variant,
+///   in declaration order, named `{variant}_{field}` (or `{variant}_{index}` for tuple variants),
+/// - a `new` constructor on the variable struct, which builds it from an instance of the enum.
+///
+/// The encoding is the discriminant, which is the index of the variant in declaration order,
+/// followed by the fields of all the variants in declaration order. The fields of the inactive
+/// variants are zero, which `assert_is_valid` constrains.
+pub(crate) fn derive_enum(
+    name: Ident,
+    vis: Visibility,
+    data: DataEnum,
+    value_ident: Ident,
+    value_derive: Vec<Ident>,
+    variable_ident: Ident,
+) -> TokenStream {
+    let variants = data
+        .variants
+        .into_iter()
+        .map(|variant| {
+            let prefix = to_snake_case(&variant.ident.to_string());
+            let (field_names, flat_names, types) = match variant.fields {
+                Fields::Unit => (None, vec![], vec![]),
+                Fields::Unnamed(fields) => {
+                    let flat_names = (0..fields.unnamed.len())
+                        .map(|i| format_ident!("{}_{}", prefix, i))
+                        .collect();
+                    let types = fields.unnamed.into_iter().map(|f| f.ty).collect();
+                    (None, flat_names, types)
+                }
+                Fields::Named(fields) => {
+                    let field_names = fields
+                        .named
+                        .iter()
+                        .map(|f| f.ident.clone().unwrap())
+                        .collect::<Vec<_>>();
+                    let flat_names = field_names
+                        .iter()
+                        .map(|f| format_ident!("{}_{}", prefix, f))
+                        .collect();
+                    let types = fields.named.into_iter().map(|f| f.ty).collect();
+                    (Some(field_names), flat_names, types)
+                }
+            };
+            VariantData {
+                name: variant.ident,
+                field_names,
+                flat_names,
+                types,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    assert!(
+        !variants.is_empty(),
+        "Cannot derive CircuitVariable for an empty enum"
+    );
+    assert!(
+        variants.iter().any(|v| !v.types.is_empty()),
+        "Cannot derive CircuitVariable for an enum without fields, use a Variable instead"
+    );
+
+    let discriminant = Ident::new("discriminant", Span::call_site());
+    let mut fields = vec![(
+        Some(discriminant.clone()),
+        syn::parse_quote!(Variable),
+        syn::parse_quote!(pub),
+    )];
+    for variant in variants.iter() {
+        for (flat_name, ty) in variant.flat_names.iter().zip(variant.types.iter()) {
+            fields.push((Some(flat_name.clone()), ty.clone(), syn::parse_quote!(pub)));
+        }
+    }
+    let struct_data = StructData { fields };
+
+    let struct_fields = struct_data.fields.iter().map(|(name, ty, vis)| {
+        quote! {
+            #vis #name: #ty,
+        }
+    });
+
+    let value_variants = variants.iter().map(|variant| {
+        let variant_name = &variant.name;
+        let types = &variant.types;
+        match &variant.field_names {
+            Some(field_names) => quote! {
+                #variant_name { #(#field_names: <#types as CircuitVariable>::ValueType<F>,)* },
+            },
+            None if types.is_empty() => quote! {
+                #variant_name,
+            },
+            None => quote! {
+                #variant_name(#(<#types as CircuitVariable>::ValueType<F>,)*),
+            },
+        }
+    });
+
+    let nb_variants = variants.len();
+    let init_unsafe_expanded = init_unsafe(&struct_data);
+    let variables_expanded = variables(&struct_data);
+    let from_variables_unsafe_expanded = from_variables_unsafe(&struct_data);
+    let assert_is_valid_expanded = assert_is_valid(&struct_data);
+    let nb_elements_expanded = nb_elements(&struct_data);
+
+    // Each check multiplies the variables of the fields of a variant by whether the variant is
+    // inactive, so that they must be zero unless it is active.
+    let inactive_checks = variants
+        .iter()
+        .enumerate()
+        .filter(|(_, variant)| !variant.flat_names.is_empty())
+        .map(|(i, variant)| {
+            let flat_names = &variant.flat_names;
+            quote! {
+                let index = builder.constant::<Variable>(L::Field::from_canonical_usize(#i));
+                let is_active = builder.is_equal(self.#discriminant, index);
+                let is_inactive = builder.not(is_active);
+                #(
+                    for variable in self.#flat_names.variables() {
+                        let inactive_value = builder.mul(is_inactive.variable, variable);
+                        builder.assert_is_equal(inactive_value, zero);
+                    }
+                )*
+            }
+        });
+
+    // Each arm writes the discriminant, then the fields of the active variant and zeros for the
+    // fields of all the other variants.
+    let elements_arms = variants.iter().enumerate().map(|(i, variant)| {
+        let pattern = variant_pattern(&value_ident, variant);
+        let pushes = variants.iter().enumerate().map(|(j, other)| {
+            let pushes = other.flat_names.iter().zip(other.types.iter()).map(|(flat_name, ty)| {
+                if i == j {
+                    quote! {
+                        elements_vec.extend(<#ty as CircuitVariable>::elements::<F>(#flat_name));
+                    }
+                } else {
+                    quote! {
+                        elements_vec.extend(vec![F::ZERO; <#ty as CircuitVariable>::nb_elements()]);
+                    }
+                }
+            });
+            quote! { #(#pushes)* }
+        });
+        quote! {
+            #pattern => {
+                let mut elements_vec = vec![F::from_canonical_usize(#i)];
+                #(#pushes)*
+                elements_vec
+            }
+        }
+    });
+
+    let from_elements_arms = variants.iter().enumerate().map(|(i, variant)| {
+        let skipped = variants[..i].iter().flat_map(|v| v.types.iter()).map(|ty| {
+            quote! {
+                cv_derive_impl_index += <#ty as CircuitVariable>::nb_elements();
+            }
+        });
+        let reads = variant
+            .flat_names
+            .iter()
+            .zip(variant.types.iter())
+            .map(|(flat_name, ty)| {
+                quote! {
+                    let cv_derive_imple_size = <#ty as CircuitVariable>::nb_elements();
+                    let #flat_name = <#ty as CircuitVariable>::from_elements::<F>(
+                        &elements[cv_derive_impl_index..cv_derive_impl_index + cv_derive_imple_size],
+                    );
+                    cv_derive_impl_index += cv_derive_imple_size;
+                }
+            });
+        let pattern = variant_pattern(&value_ident, variant);
+        quote! {
+            #i => {
+                let mut cv_derive_impl_index = 1;
+                #(#skipped)*
+                #(#reads)*
+                #pattern
+            }
+        }
+    });
+
+    let new_arms = variants.iter().enumerate().map(|(i, variant)| {
+        let pattern = variant_pattern(&name, variant);
+        let assignments = variants.iter().enumerate().flat_map(|(j, other)| {
+            other
+                .flat_names
+                .iter()
+                .zip(other.types.iter())
+                .map(move |(flat_name, ty)| {
+                    if i == j {
+                        quote! { #flat_name, }
+                    } else {
+                        quote! {
+                            #flat_name: <#ty as CircuitVariable>::from_variables_unsafe(
+                                &vec![zero; <#ty as CircuitVariable>::nb_elements()],
+                            ),
+                        }
+                    }
+                })
+                .collect::<Vec<_>>()
+        });
+        quote! {
+            #pattern => Self {
+                #discriminant: builder.constant::<Variable>(L::Field::from_canonical_usize(#i)),
+                #(#assignments)*
+            },
+        }
+    });
+
+    let value_derive_expanded = quote! {
+        #[derive(#(#value_derive,)*)]
+    };
+
+    let discriminants = variants
+        .iter()
+        .enumerate()
+        .map(|(i, variant)| format!("`{}` is {}", variant.name, i))
+        .collect::<Vec<_>>();
+    let flat_names = variants
+        .iter()
+        .flat_map(|variant| variant.flat_names.iter())
+        .map(|flat_name| format!("`{}`", flat_name))
+        .collect::<Vec<_>>();
+    let variable_doc = format!(
+        "The circuit variable of `{}`.\n\n\
+         Its encoding is the `discriminant`, which is the index of the active variant in \
+         declaration order ({}), followed by the fields of every variant in declaration order: \
+         {}. The fields of the inactive variants are constrained to be zero. See the \
+         [`CircuitVariable`](derive@CircuitVariable) derive macro.",
+        name,
+        discriminants.join(", "),
+        flat_names.join(", "),
+    );
+
+    quote! {
+        #value_derive_expanded
+        #vis enum #value_ident<F: RichField> {
+            #(#value_variants)*
+        }
+
+        #[doc = #variable_doc]
+        #[derive(Debug, Clone)]
+        #vis struct #variable_ident {
+            #(#struct_fields)*
+        }
+
+        impl #variable_ident {
+            /// Encodes an instance of the enum, with zeros for the fields of the inactive variants.
+            #[allow(unused_variables)]
+            pub fn new<L: PlonkParameters<D>, const D: usize>(
+                builder: &mut CircuitBuilder<L, D>,
+                variant: #name,
+            ) -> Self {
+                let zero = builder.zero::<Variable>();
+                match variant {
+                    #(#new_arms)*
+                }
+            }
+        }
+
+        impl EnumVariable for #variable_ident {
+            fn discriminant(&self) -> Variable {
+                self.#discriminant
+            }
+        }
+
+        #[allow(unused_mut, unused_assignments)]
+        impl CircuitVariable for #variable_ident {
+            type ValueType<F: RichField> = #value_ident<F>;
+
+            fn init_unsafe<L: PlonkParameters<D>, const D: usize>(builder: &mut CircuitBuilder<L, D>) -> Self {
+                #init_unsafe_expanded
+            }
+
+            fn variables(&self) -> Vec<Variable> {
+                #variables_expanded
+            }
+
+            fn from_variables_unsafe(variables: &[Variable]) -> Self {
+                #from_variables_unsafe_expanded
+            }
+
+            fn assert_is_valid<L: PlonkParameters<D>, const D: usize>(&self, builder: &mut CircuitBuilder<L, D>) {
+                #assert_is_valid_expanded
+
+                // The discriminant is one of 0..nb_variants.
+                let mut product = builder.one::<Variable>();
+                for i in 0..#nb_variants {
+                    let index = builder.constant::<Variable>(L::Field::from_canonical_usize(i));
+                    let difference = builder.sub(self.#discriminant, index);
+                    product = builder.mul(product, difference);
+                }
+                let zero = builder.zero::<Variable>();
+                builder.assert_is_equal(product, zero);
+
+                // The fields of the inactive variants are zero.
+                #(#inactive_checks)*
+            }
+
+            fn nb_elements() -> usize {
+                #nb_elements_expanded
+            }
+
+            fn elements<F: RichField>(value: Self::ValueType<F>) -> Vec<F> {
+                match value {
+                    #(#elements_arms)*
+                }
+            }
+
+            fn from_elements<F: RichField>(elements: &[F]) -> Self::ValueType<F> {
+                assert_eq!(elements.len(), Self::nb_elements());
+                let discriminant = elements[0].to_canonical_u64() as usize;
+                match discriminant {
+                    #(#from_elements_arms)*
+                    _ => panic!("invalid discriminant {}", discriminant),
+                }
+            }
+        }
+    }
+}
+
+/// Returns a pattern (or expression) for a variant that binds its fields to their flat names.
+fn variant_pattern(enum_name: &Ident, variant: &VariantData) -> TokenStream {
+    let variant_name = &variant.name;
+    let flat_names = &variant.flat_names;
+    match &variant.field_names {
+        Some(field_names) => quote! {
+            #enum_name::#variant_name { #(#field_names: #flat_names,)* }
+        },
+        None if flat_names.is_empty() => quote! {
+            #enum_name::#variant_name
+        },
+        None => quote! {
+            #enum_name::#variant_name(#(#flat_names,)*)
+        },
+    }
+}
+
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
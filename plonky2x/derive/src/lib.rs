@@ -3,6 +3,7 @@ extern crate proc_macro;
 mod assert_is_valid;
 mod constant;
 mod elements;
+mod enums;
 mod init;
 mod value;
 mod variables;
@@ -11,6 +12,7 @@ mod witness;
 use assert_is_valid::assert_is_valid;
 use constant::constant;
 use elements::{elements, from_elements, nb_elements};
+use enums::derive_enum;
 use init::init_unsafe;
 use proc_macro2::Ident;
 use quote::quote;
@@ -26,13 +28,24 @@ struct StructData {
     fields: Vec<(Option<Ident>, Type, Visibility)>,
 }
 
-#[proc_macro_derive(CircuitVariable, attributes(value_name, value_derive))]
+/// Derives `CircuitVariable` for a struct whose fields are circuit variables, or for an enum whose
+/// variants hold circuit variables.
+///
+/// The value type is named with `#[value_name(...)]` and its derives are extended with
+/// `#[value_derive(...)]`.
+///
+/// The active variant of an enum is only known at proving time, so for enums the trait is
+/// implemented by a generated struct named with `#[variable_name(...)]` (by default `{Name}Variable`)
+/// instead. Its encoding, which is stable, is a discriminant `Variable` holding the index of the
+/// variant in declaration order, followed by the fields of every variant in declaration order.
+/// The fields of the inactive variants are zero in values set from the witness.
+#[proc_macro_derive(CircuitVariable, attributes(value_name, value_derive, variable_name))]
 pub fn derive_circuit_variable(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
     let name = input.ident;
-    let data = parse_struct_data(input.data);
 
+    let mut variable_ident = Ident::new(&format!("{}Variable", name), name.span());
     let mut value_ident = Ident::new(&format!("{}Value", name), name.span());
     let mut value_derive = vec![parse_quote!(Debug), parse_quote!(Clone)];
 
@@ -40,6 +53,9 @@ pub fn derive_circuit_variable(input: proc_macro::TokenStream) -> proc_macro::To
         if attr.path().is_ident("value_name") {
             value_ident = attr.parse_args::<Ident>().unwrap();
         }
+        if attr.path().is_ident("variable_name") {
+            variable_ident = attr.parse_args::<Ident>().unwrap();
+        }
         if attr.path().is_ident("value_derive") {
             match attr.meta {
                 Meta::Path(ref path) => value_derive.push(path.get_ident().unwrap().clone()),
@@ -59,6 +75,22 @@ pub fn derive_circuit_variable(input: proc_macro::TokenStream) -> proc_macro::To
         }
     }
 
+    if let Data::Enum(data) = input.data {
+        if !input.generics.params.is_empty() {
+            panic!("Cannot derive CircuitVariable for an enum with generic parameters");
+        }
+        return derive_enum(
+            name,
+            input.vis,
+            data,
+            value_ident,
+            value_derive,
+            variable_ident,
+        )
+        .into();
+    }
+
+    let data = parse_struct_data(input.data);
     let mut generics = input.generics;
     make_where_clause(&data, &mut generics);
 
@@ -138,7 +170,7 @@ fn parse_struct_data(data: Data) -> StructData {
                 })
                 .collect(),
         },
-        Data::Enum(_) => unimplemented!("enums not supported"),
+        Data::Enum(_) => unreachable!("enums are handled by derive_enum"),
         Data::Union(_) => unimplemented!("unions not supported"),
     }
 }